@@ -0,0 +1,301 @@
+//! Python bindings: the price aggregator, trading engine and PnL tracker, driven from a notebook.
+//! Prices, trades and stats cross the boundary as plain dicts in the same shape as the JSON exports.
+
+use market_maker_simulator::{
+    AggregatedPrices, Config, DrawSource, PnLTracker, PriceAggregator, Source, SyntheticConfig, Trade, TradeSide,
+    TradingEngine,
+};
+use market_maker_simulator::aggregator::Quote;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::{Arc, OnceLock};
+use tokio::runtime::Runtime;
+
+/// Runtime the feed handlers and tracker run on; it outlives every call so feeds keep polling
+/// between them
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| Runtime::new().expect("failed to start the tokio runtime"))
+}
+
+fn runtime_err(e: anyhow::Error) -> PyErr {
+    PyRuntimeError::new_err(format!("{:#}", e))
+}
+
+fn value_err(e: impl std::fmt::Display) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+/// Convert through JSON, so Python sees exactly what the exports write
+fn to_py<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let json = serde_json::to_string(value).map_err(value_err)?;
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+fn from_py<T: DeserializeOwned>(value: &Bound<'_, PyAny>) -> PyResult<T> {
+    let json: String = value.py().import("json")?.call_method1("dumps", (value,))?.extract()?;
+    serde_json::from_str(&json).map_err(value_err)
+}
+
+fn resolve_config(path: Option<&str>) -> PyResult<Config> {
+    Config::resolve(path).map_err(runtime_err)
+}
+
+fn parse_side(side: &str) -> PyResult<TradeSide> {
+    match side.to_ascii_lowercase().as_str() {
+        "buy" => Ok(TradeSide::Buy),
+        "sell" => Ok(TradeSide::Sell),
+        other => Err(PyValueError::new_err(format!("side should be 'buy' or 'sell', not '{}'", other))),
+    }
+}
+
+/// Prices from a `PriceAggregator` (its latest snapshot) or a dict like `PriceAggregator.prices()`
+fn prices_arg(py: Python<'_>, prices: &Bound<'_, PyAny>) -> PyResult<AggregatedPrices> {
+    match prices.downcast::<PyPriceAggregator>() {
+        Ok(aggregator) => {
+            let aggregator = Arc::clone(&aggregator.get().inner);
+            Ok(py.allow_threads(|| runtime().block_on(aggregator.get_prices())))
+        }
+        Err(_) => from_py(prices),
+    }
+}
+
+/// Live price feeds, optionally with synthetic prices standing in for some sources
+#[pyclass(name = "PriceAggregator", frozen)]
+struct PyPriceAggregator {
+    inner: Arc<PriceAggregator>,
+}
+
+#[pymethods]
+impl PyPriceAggregator {
+    /// `config` is a TOML file like `sim.example.toml`; `synthetic` names sources (or `["all"]`) to simulate
+    #[new]
+    #[pyo3(signature = (config=None, synthetic=None))]
+    fn new(config: Option<&str>, synthetic: Option<Vec<String>>) -> PyResult<Self> {
+        let config = resolve_config(config)?;
+        let slots = match synthetic.as_deref() {
+            Some([all]) if all == "all" => Source::ALL.to_vec(),
+            Some(names) => names.iter().map(|name| name.parse()).collect::<anyhow::Result<Vec<Source>>>().map_err(value_err)?,
+            None => Vec::new(),
+        };
+        let mut aggregator = PriceAggregator::new()
+            .with_sources(config.sources.clone())
+            .with_quote_notional(config.quote_notional());
+        let synthetic_config = config.synthetic.config();
+        for (i, &source) in slots.iter().enumerate() {
+            let config = SyntheticConfig {
+                seed: synthetic_config.seed.wrapping_add(i as u64),
+                ..synthetic_config
+            };
+            aggregator = aggregator.with_synthetic(source, config);
+        }
+        Ok(Self { inner: Arc::new(aggregator) })
+    }
+
+    /// Start the feed handlers; they keep running in the background until `stop`
+    fn start(&self, py: Python<'_>) -> PyResult<()> {
+        let aggregator = Arc::clone(&self.inner);
+        py.allow_threads(|| runtime().block_on(aggregator.start())).map_err(runtime_err)
+    }
+
+    fn stop(&self) {
+        self.inner.stop();
+    }
+
+    /// Feed a quote in by hand, e.g. from a recording loaded in pandas
+    #[pyo3(signature = (source, bid, ask, timestamp))]
+    fn ingest(&self, py: Python<'_>, source: &str, bid: f64, ask: f64, timestamp: i64) -> PyResult<()> {
+        let source: Source = source.parse().map_err(value_err)?;
+        let aggregator = Arc::clone(&self.inner);
+        py.allow_threads(|| runtime().block_on(aggregator.ingest(source, Quote { bid, ask, timestamp })));
+        Ok(())
+    }
+
+    /// Latest quote from every source, as `{"binance": {"bid", "ask", "timestamp"}, ...}`
+    fn prices(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let aggregator = Arc::clone(&self.inner);
+        let prices = py.allow_threads(|| runtime().block_on(aggregator.get_prices()));
+        to_py(py, &prices)
+    }
+
+    fn median_mid(&self, py: Python<'_>) -> Option<f64> {
+        let aggregator = Arc::clone(&self.inner);
+        py.allow_threads(|| runtime().block_on(aggregator.get_prices())).median_mid()
+    }
+}
+
+/// Quoting and fill simulation as a session configures it
+#[pyclass(name = "TradingEngine", frozen)]
+struct PyTradingEngine {
+    inner: TradingEngine,
+}
+
+#[pymethods]
+impl PyTradingEngine {
+    /// `seed` makes the fill draws reproducible
+    #[new]
+    #[pyo3(signature = (config=None, seed=None))]
+    fn new(config: Option<&str>, seed: Option<u64>) -> PyResult<Self> {
+        let config = resolve_config(config)?;
+        let model = config.execution.execution_model(config.simulation.trade_interval_secs as f64).map_err(runtime_err)?;
+        let mut engine = config.session_engine(model).map_err(runtime_err)?;
+        if let Some(seed) = seed {
+            engine = engine.with_draws(DrawSource::seeded(seed));
+        }
+        Ok(Self { inner: engine })
+    }
+
+    /// Quote `side` ("buy" or "sell") against `prices` and decide the fill. Returns None when the
+    /// engine has nothing to quote, otherwise the attempt with its `trade` if it filled.
+    #[pyo3(signature = (prices, side, draw=None))]
+    fn attempt(&self, py: Python<'_>, prices: &Bound<'_, PyAny>, side: &str, draw: Option<f64>) -> PyResult<Option<PyObject>> {
+        let prices = prices_arg(py, prices)?;
+        let side = parse_side(side)?;
+        let attempt = match draw {
+            Some(draw) => self.inner.attempt_with_draw(&prices, side, draw),
+            None => self.inner.attempt(&prices, side),
+        };
+        let Some(attempt) = attempt else {
+            return Ok(None);
+        };
+        let dict = PyDict::new(py);
+        dict.set_item("side", to_py(py, &attempt.side)?)?;
+        dict.set_item("price", attempt.price)?;
+        dict.set_item("execution_prob", attempt.execution_prob)?;
+        dict.set_item("draw", attempt.draw)?;
+        dict.set_item("expected_edge_bps", attempt.expected_edge_bps)?;
+        dict.set_item("quoted", attempt.quoted)?;
+        dict.set_item("degraded", attempt.degraded)?;
+        dict.set_item("touch_distance_bps", attempt.touch_distance_bps)?;
+        dict.set_item("order_notional", attempt.order_notional)?;
+        dict.set_item("pending", attempt.pending)?;
+        dict.set_item("trade", attempt.trade.as_ref().map(|trade| to_py(py, trade)).transpose()?)?;
+        Ok(Some(dict.into_any().unbind()))
+    }
+
+    /// Fills whose latency has passed by the feed time of `prices`
+    fn confirm_fills(&self, py: Python<'_>, prices: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        let prices = prices_arg(py, prices)?;
+        to_py(py, &self.inner.confirm_fills(&prices))
+    }
+
+    /// Every pending fill, e.g. at the end of a run
+    fn confirm_all(&self, py: Python<'_>, prices: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        let prices = prices_arg(py, prices)?;
+        to_py(py, &self.inner.confirm_all(&prices))
+    }
+
+    /// Inventory hedge trade, if the hedger is configured and inventory is over its threshold
+    fn hedge(&self, py: Python<'_>, prices: &Bound<'_, PyAny>) -> PyResult<Option<PyObject>> {
+        let prices = prices_arg(py, prices)?;
+        self.inner.hedge(&prices).map(|trade| to_py(py, &trade)).transpose()
+    }
+
+    fn position(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &self.inner.position())
+    }
+
+    fn notional_per_trade(&self) -> f64 {
+        self.inner.notional_per_trade()
+    }
+}
+
+/// PnL accounting over the trades it's given
+#[pyclass(name = "PnLTracker", frozen)]
+struct PyPnLTracker {
+    inner: PnLTracker,
+}
+
+#[pymethods]
+impl PyPnLTracker {
+    #[new]
+    #[pyo3(signature = (config=None))]
+    fn new(config: Option<&str>) -> PyResult<Self> {
+        let config = resolve_config(config)?;
+        let mut tracker = PnLTracker::new()
+            .with_cost_basis(config.simulation.cost_basis().map_err(runtime_err)?)
+            .with_mark_interval(config.simulation.mark_interval());
+        if let Some(rates) = config.funding.rates() {
+            tracker = tracker.with_funding(rates);
+        }
+        Ok(Self { inner: tracker })
+    }
+
+    /// Book a trade dict as returned by `TradingEngine.attempt(...)["trade"]`
+    fn record_trade(&self, py: Python<'_>, trade: &Bound<'_, PyAny>) -> PyResult<()> {
+        let trade: Trade = from_py(trade)?;
+        py.allow_threads(|| runtime().block_on(self.inner.record_trade(trade)));
+        Ok(())
+    }
+
+    /// Mark open inventory to `prices`, adding an equity point each mark interval
+    fn mark_to_market(&self, py: Python<'_>, prices: &Bound<'_, PyAny>) -> PyResult<()> {
+        let prices = prices_arg(py, prices)?;
+        py.allow_threads(|| runtime().block_on(self.inner.mark_to_market(&prices)));
+        Ok(())
+    }
+
+    fn stats(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let stats = py.allow_threads(|| runtime().block_on(self.inner.get_stats()));
+        to_py(py, &stats)
+    }
+
+    fn trades(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let trades = py.allow_threads(|| runtime().block_on(self.inner.get_trades()));
+        to_py(py, &trades)
+    }
+
+    /// Trades stamped from `t0` up to but excluding `t1`, Unix milliseconds
+    fn trades_between(&self, py: Python<'_>, t0: i64, t1: i64) -> PyResult<PyObject> {
+        let trades = py.allow_threads(|| runtime().block_on(self.inner.trades_between(t0, t1)));
+        to_py(py, &trades)
+    }
+
+    fn trades_by_side(&self, py: Python<'_>, side: &str) -> PyResult<PyObject> {
+        let side = parse_side(side)?;
+        let trades = py.allow_threads(|| runtime().block_on(self.inner.trades_by_side(side)));
+        to_py(py, &trades)
+    }
+
+    fn trades_by_tag(&self, py: Python<'_>, tag: &str) -> PyResult<PyObject> {
+        let trades = py.allow_threads(|| runtime().block_on(self.inner.trades_by_tag(tag)));
+        to_py(py, &trades)
+    }
+
+    fn best_n(&self, py: Python<'_>, n: usize) -> PyResult<PyObject> {
+        let trades = py.allow_threads(|| runtime().block_on(self.inner.best_n(n)));
+        to_py(py, &trades)
+    }
+
+    fn worst_n(&self, py: Python<'_>, n: usize) -> PyResult<PyObject> {
+        let trades = py.allow_threads(|| runtime().block_on(self.inner.worst_n(n)));
+        to_py(py, &trades)
+    }
+
+    /// Mid 1s, 5s and 30s of feed time after every non-hedge fill
+    fn markouts(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let markouts = py.allow_threads(|| runtime().block_on(self.inner.markouts()));
+        to_py(py, &markouts)
+    }
+
+    fn equity_series(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let series = py.allow_threads(|| runtime().block_on(self.inner.equity_series()));
+        to_py(py, &series)
+    }
+
+    /// The session summary as printed at the end of a run
+    fn summary(&self, py: Python<'_>) -> String {
+        py.allow_threads(|| runtime().block_on(self.inner.get_stats())).to_string()
+    }
+}
+
+#[pymodule]
+fn market_maker(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyPriceAggregator>()?;
+    m.add_class::<PyTradingEngine>()?;
+    m.add_class::<PyPnLTracker>()?;
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use crate::aggregator::{AggregatedPrices, Quote};
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::fmt;
+
+/// Half-life of a quote's weight under `FreshnessWeighted` unless configured
+pub const DEFAULT_FRESHNESS_HALF_LIFE_MS: i64 = 2_000;
+
+/// How the engine combines the venues' quotes into the reference price it judges quotes, edge and
+/// taker signals against. The touch it marks fills to is always the best bid and offer.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+#[serde(tag = "policy", rename_all = "kebab-case")]
+pub enum AggregationPolicy {
+    /// Median bid and ask across venues, and the median of their mids
+    #[default]
+    Median,
+    /// Highest bid and lowest ask across venues
+    BestBidOffer,
+    /// Venue quotes weighted by the inverse of their spreads
+    LiquidityWeighted,
+    /// Venue quotes weighted by how recent they are, halving every `half_life_ms`
+    FreshnessWeighted { half_life_ms: i64 },
+}
+
+impl AggregationPolicy {
+    /// Build a policy by name (`median`, `bbo`, `liquidity-weighted` or `freshness-weighted`)
+    pub fn parse(name: &str, half_life_ms: i64) -> Result<Self> {
+        match name.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "median" => Ok(AggregationPolicy::Median),
+            "bbo" | "best-bid-offer" => Ok(AggregationPolicy::BestBidOffer),
+            "liquidity-weighted" => Ok(AggregationPolicy::LiquidityWeighted),
+            "freshness-weighted" => Ok(AggregationPolicy::FreshnessWeighted { half_life_ms }),
+            other => bail!(
+                "unknown aggregation policy '{}' (expected median, bbo, liquidity-weighted or freshness-weighted)",
+                other
+            ),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AggregationPolicy::Median => "median",
+            AggregationPolicy::BestBidOffer => "bbo",
+            AggregationPolicy::LiquidityWeighted => "liquidity-weighted",
+            AggregationPolicy::FreshnessWeighted { .. } => "freshness-weighted",
+        }
+    }
+}
+
+impl fmt::Display for AggregationPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AggregationPolicy::FreshnessWeighted { half_life_ms } => write!(f, "{} ({}ms half-life)", self.name(), half_life_ms),
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+impl AggregatedPrices {
+    /// Reference bid and ask under `policy`, with quote ages taken at `now_ms`
+    pub fn reference_quote(&self, policy: AggregationPolicy, now_ms: i64) -> Option<Quote> {
+        match policy {
+            AggregationPolicy::Median => self.median_quote(),
+            AggregationPolicy::BestBidOffer => self.best_quote(),
+            AggregationPolicy::LiquidityWeighted => self.liquidity_weighted_quote(),
+            AggregationPolicy::FreshnessWeighted { half_life_ms } => self.freshness_weighted_quote(half_life_ms, now_ms),
+        }
+    }
+
+    /// Reference mid under `policy`; the median policy takes the median of the venues' mids
+    pub fn reference_mid(&self, policy: AggregationPolicy, now_ms: i64) -> Option<f64> {
+        match policy {
+            AggregationPolicy::Median => self.median_mid(),
+            _ => self.reference_quote(policy, now_ms).map(|q| (q.bid + q.ask) / 2.0),
+        }
+    }
+}
@@ -2,41 +2,131 @@
 use anyhow::{Context, Result};
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
-use std::{sync::Arc, time::Duration};
-use tokio::{sync::RwLock, time::interval};
+use std::{future::Future, sync::Arc, time::Duration};
+use tokio::{
+    sync::{broadcast, RwLock},
+    task::JoinHandle,
+    time::{interval, MissedTickBehavior},
+};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, error, info, info_span, warn, Instrument};
 
-#[derive(Debug, Clone, Copy)]
+use crate::clock::{self, Clock};
+use crate::config::SourcesConfig;
+use crate::error::MarketMakerError;
+use crate::flow::{FlowImbalance, FlowTracker, TapeEvent, TapeTrade};
+use crate::health::{ReconnectPolicy, SourceHealth};
+use crate::http::{HttpClient, HttpPolicy, HttpStats};
+use crate::metrics::{MetricsState, SourceMetrics};
+use crate::recorder::TickRecorder;
+use crate::synthetic::{SyntheticConfig, SyntheticSource};
+use crate::tokens::{self, TradingPair, WRAPPED_SOL_MINT};
+use crate::trader::TradeSide;
+use crate::uniswap::{self, UniswapConfig};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Binance,
+    Jupiter,
+    CowSwap,
+    Uniswap,
+}
+
+impl Source {
+    pub const ALL: [Source; 4] = [Source::Binance, Source::Jupiter, Source::CowSwap, Source::Uniswap];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Source::Binance => "binance",
+            Source::Jupiter => "jupiter",
+            Source::CowSwap => "cowswap",
+            Source::Uniswap => "uniswap",
+        }
+    }
+}
+
+impl std::str::FromStr for Source {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "binance" => Ok(Source::Binance),
+            "jupiter" => Ok(Source::Jupiter),
+            "cowswap" => Ok(Source::CowSwap),
+            "uniswap" => Ok(Source::Uniswap),
+            other => anyhow::bail!("unknown price source '{}'", other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Quote {
     pub bid: f64,
     pub ask: f64,
     pub timestamp: i64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AggregatedPrices {
     pub binance: Option<Quote>,
     pub jupiter: Option<Quote>,
     pub cowswap: Option<Quote>,
+    /// Best fee tier of the on-chain Uniswap V3 pools, if an RPC URL is configured
+    pub uniswap: Option<Quote>,
+    /// SOL/USD reference rate, used to express costs in lamports
+    pub sol_usd: Option<f64>,
+    /// Taker flow on the Binance tape over the configured window, once the trade stream has printed
+    pub flow: Option<FlowImbalance>,
 }
 
 impl AggregatedPrices {
+    pub fn get(&self, source: Source) -> Option<Quote> {
+        match source {
+            Source::Binance => self.binance,
+            Source::Jupiter => self.jupiter,
+            Source::CowSwap => self.cowswap,
+            Source::Uniswap => self.uniswap,
+        }
+    }
+
+    pub fn set(&mut self, source: Source, quote: Quote) {
+        match source {
+            Source::Binance => self.binance = Some(quote),
+            Source::Jupiter => self.jupiter = Some(quote),
+            Source::CowSwap => self.cowswap = Some(quote),
+            Source::Uniswap => self.uniswap = Some(quote),
+        }
+    }
+
+    /// Every source slot that currently holds a quote
+    pub fn quotes(&self) -> impl Iterator<Item = Quote> + '_ {
+        Source::ALL.iter().filter_map(|&s| self.get(s))
+    }
+
+    /// Copy keeping only the quotes from `sources`
+    pub fn restricted_to(&self, sources: &[Source]) -> AggregatedPrices {
+        let mut restricted = AggregatedPrices {
+            sol_usd: self.sol_usd,
+            flow: self.flow,
+            ..AggregatedPrices::default()
+        };
+        for &source in sources {
+            if let Some(quote) = self.get(source) {
+                restricted.set(source, quote);
+            }
+        }
+        restricted
+    }
+
     pub fn median_quote(&self) -> Option<Quote> {
         let mut bids = Vec::new();
         let mut asks = Vec::new();
         let mut timestamps = Vec::new();
 
-        if let Some(q) = self.binance {
-            bids.push(q.bid);
-            asks.push(q.ask);
-            timestamps.push(q.timestamp);
-        }
-        if let Some(q) = self.jupiter {
-            bids.push(q.bid);
-            asks.push(q.ask);
-            timestamps.push(q.timestamp);
-        }
-        if let Some(q) = self.cowswap {
+        for q in self.quotes() {
             bids.push(q.bid);
             asks.push(q.ask);
             timestamps.push(q.timestamp);
@@ -65,10 +155,7 @@ impl AggregatedPrices {
         let mut best_ask = None;
         let mut latest_timestamp = 0;
 
-        for quote in [self.binance, self.jupiter, self.cowswap]
-            .iter()
-            .filter_map(|&q| q)
-        {
+        for quote in self.quotes() {
             best_bid = Some(best_bid.map_or(quote.bid, |b: f64| b.max(quote.bid)));
             best_ask = Some(best_ask.map_or(quote.ask, |a: f64| a.min(quote.ask)));
             latest_timestamp = latest_timestamp.max(quote.timestamp);
@@ -76,26 +163,75 @@ impl AggregatedPrices {
 
         match (best_bid, best_ask) {
             (Some(bid), Some(ask)) => Some(Quote {
-                bid: bid,
-                ask: ask,
+                bid,
+                ask,
                 timestamp: latest_timestamp,
             }),
             _ => None,
         }
     }
 
-    pub fn median_mid(&self) -> Option<f64> {
-        let mut mids = Vec::new();
+    /// Source quoting the highest bid
+    pub fn best_bid_source(&self) -> Option<Source> {
+        Source::ALL
+            .iter()
+            .copied()
+            .filter_map(|s| Some((s, self.get(s)?.bid)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(s, _)| s)
+    }
 
-        if let Some(q) = self.binance {
-            mids.push((q.bid + q.ask) / 2.0);
-        }
-        if let Some(q) = self.jupiter {
-            mids.push((q.bid + q.ask) / 2.0);
-        }
-        if let Some(q) = self.cowswap { 
-            mids.push((q.bid + q.ask) / 2.0);
+    /// Source quoting the lowest ask
+    pub fn best_ask_source(&self) -> Option<Source> {
+        Source::ALL
+            .iter()
+            .copied()
+            .filter_map(|s| Some((s, self.get(s)?.ask)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(s, _)| s)
+    }
+
+    /// Bid and ask averaged across venues by `weight` of each quote
+    fn weighted_quote(&self, weight: impl Fn(&Quote) -> f64) -> Option<Quote> {
+        let (mut bid, mut ask, mut total_weight) = (0.0, 0.0, 0.0);
+        let mut timestamp = 0;
+        for q in self.quotes() {
+            let w = weight(&q);
+            bid += q.bid * w;
+            ask += q.ask * w;
+            total_weight += w;
+            timestamp = timestamp.max(q.timestamp);
         }
+        (total_weight > 0.0).then(|| Quote {
+            bid: bid / total_weight,
+            ask: ask / total_weight,
+            timestamp,
+        })
+    }
+
+    /// Venue quotes weighted by the inverse of each venue's spread, so tight books count for more
+    pub fn liquidity_weighted_quote(&self) -> Option<Quote> {
+        self.weighted_quote(|q| {
+            // A locked or crossed book would get infinite weight; floor its spread at 0.01 bps
+            let mid = (q.bid + q.ask) / 2.0;
+            1.0 / (q.ask - q.bid).max(mid * 1e-6)
+        })
+    }
+
+    /// Venue quotes weighted by their age at `now_ms`, halving every `half_life_ms`. Quotes so old
+    /// that every weight underflows give no quote at all.
+    pub fn freshness_weighted_quote(&self, half_life_ms: i64, now_ms: i64) -> Option<Quote> {
+        let half_life = half_life_ms.max(1) as f64;
+        self.weighted_quote(|q| 0.5f64.powf((now_ms - q.timestamp).max(0) as f64 / half_life))
+    }
+
+    /// Mid of `liquidity_weighted_quote`
+    pub fn weighted_mid(&self) -> Option<f64> {
+        self.liquidity_weighted_quote().map(|q| (q.bid + q.ask) / 2.0)
+    }
+
+    pub fn median_mid(&self) -> Option<f64> {
+        let mut mids: Vec<f64> = self.quotes().map(|q| (q.bid + q.ask) / 2.0).collect();
 
         if mids.is_empty() {
             return None;
@@ -106,19 +242,115 @@ impl AggregatedPrices {
     }
 }
 
+/// One source's latest quote and how old it was when the snapshot was taken
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SourceQuote {
+    pub source: Source,
+    pub quote: Quote,
+    pub age_ms: i64,
+}
+
+/// Every source's latest quote as of `taken_at`, so callers can ask how old each one is rather
+/// than only whether the slot is filled
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PriceSnapshot {
+    pub taken_at: i64,
+    pub prices: AggregatedPrices,
+}
+
+impl PriceSnapshot {
+    pub fn new(prices: AggregatedPrices, taken_at: i64) -> Self {
+        Self { taken_at, prices }
+    }
+
+    /// Snapshot aged against its own newest quote, for feeds that run on feed time
+    pub fn at_feed_time(prices: AggregatedPrices) -> Self {
+        let taken_at = prices.quotes().map(|q| q.timestamp).max().unwrap_or(0);
+        Self::new(prices, taken_at)
+    }
+
+    /// Milliseconds since `source` last quoted, `None` if it never has
+    pub fn age_ms(&self, source: Source) -> Option<i64> {
+        self.prices.get(source).map(|q| (self.taken_at - q.timestamp).max(0))
+    }
+
+    /// Every source holding a quote, with its age
+    pub fn sources(&self) -> impl Iterator<Item = SourceQuote> + '_ {
+        Source::ALL.iter().filter_map(|&source| {
+            Some(SourceQuote {
+                source,
+                quote: self.prices.get(source)?,
+                age_ms: self.age_ms(source)?,
+            })
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.sources().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The same snapshot with only the sources that quoted within `max_age`
+    pub fn fresh(&self, max_age: Duration) -> PriceSnapshot {
+        let max_age_ms = max_age.as_millis() as i64;
+        let live: Vec<Source> = self.sources().filter(|s| s.age_ms <= max_age_ms).map(|s| s.source).collect();
+        Self::new(self.prices.restricted_to(&live), self.taken_at)
+    }
+
+    /// Median bid above median ask: the sources disagree by more than the spread, so some quote is bad
+    pub fn is_crossed(&self) -> bool {
+        self.prices.median_quote().is_some_and(|q| q.bid > q.ask)
+    }
+
+    /// At least `min_sources` quotes on an uncrossed book. Counts every source held, so call it
+    /// on `fresh(..)` to leave stale quotes out.
+    pub fn is_tradeable(&self, min_sources: usize) -> bool {
+        self.len() >= min_sources && !self.is_crossed()
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct BinanceBookTicker {
-    #[serde(rename = "b")]
+    // Short names on the WebSocket stream, long ones from the REST endpoint
+    #[serde(rename = "b", alias = "bidPrice")]
     bid_price: String,
-    #[serde(rename = "a")]
+    #[serde(rename = "a", alias = "askPrice")]
     ask_price: String,
 }
 
+/// Binance aggTrade event; `m` is set when the buyer was the maker, i.e. the taker sold
+#[derive(Debug, Deserialize)]
+pub struct BinanceAggTrade {
+    #[serde(rename = "p")]
+    price: String,
+    #[serde(rename = "q")]
+    quantity: String,
+    #[serde(rename = "m")]
+    buyer_is_maker: bool,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct JupiterResponse {
     data: serde_json::Value,
 }
 
+/// Default USD size quotes are priced at when no notional is configured
+pub const DEFAULT_QUOTE_NOTIONAL: f64 = 1000.0;
+
+/// Binance spot REST API, used for one-off book ticker fetches
+pub const BINANCE_REST_URL: &str = "https://api.binance.com";
+
+#[derive(Debug, Deserialize)]
+pub struct JupiterSwapQuote {
+    #[serde(rename = "inAmount")]
+    in_amount: String,
+    #[serde(rename = "outAmount")]
+    out_amount: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CowSwapQuote {
     quote: CowSwapQuoteData,
@@ -130,74 +362,506 @@ pub struct CowSwapQuoteData {
     buy_amount: String,
     #[serde(rename = "sellAmount")]
     sell_amount: String,
+    #[serde(rename = "feeAmount", default = "zero_amount")]
+    fee_amount: String,
+}
+
+fn zero_amount() -> String {
+    "0".to_string()
+}
+
+/// Capacity of the quote update broadcast channel; slow subscribers lag past this
+pub const QUOTE_CHANNEL_CAPACITY: usize = 1024;
+
+/// A single quote change pushed to subscribers
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct QuoteUpdate {
+    pub source: Source,
+    pub quote: Quote,
+}
+
+/// Capacity of the feed event channel; slow subscribers lag past this
+pub const FEED_EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// A change in a source's connection, pushed to `subscribe_events` subscribers
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FeedEvent {
+    /// A streaming source (re)connected
+    Connected { source: Source, timestamp: i64 },
+    Error { timestamp: i64, error: MarketMakerError },
+    /// A source quoted again after `failures` failed attempts in a row
+    Recovered { source: Source, timestamp: i64, failures: u32 },
+}
+
+impl FeedEvent {
+    pub fn source(&self) -> Source {
+        match self {
+            FeedEvent::Connected { source, .. } | FeedEvent::Recovered { source, .. } => *source,
+            FeedEvent::Error { error, .. } => error.feed(),
+        }
+    }
+}
+
+/// Anything the trading loop can pull price snapshots from
+pub trait PriceFeed {
+    fn get_prices(&self) -> impl Future<Output = AggregatedPrices> + Send;
+
+    /// `get_prices` with each quote's age; feeds without a clock of their own age quotes against the newest one
+    fn snapshot(&self) -> impl Future<Output = PriceSnapshot> + Send {
+        let prices = self.get_prices();
+        async move { PriceSnapshot::at_feed_time(prices.await) }
+    }
+
+    /// Push-based stream of every quote update
+    fn subscribe(&self) -> broadcast::Receiver<QuoteUpdate>;
+
+    /// Source connections, errors and recoveries; `None` for feeds that don't connect to anything
+    fn subscribe_events(&self) -> Option<broadcast::Receiver<FeedEvent>> {
+        None
+    }
+}
+
+/// Where an HTTP poller fetches from, the pair it quotes and the size in quote tokens it prices quotes at
+#[derive(Debug, Clone)]
+struct HttpEndpoint {
+    base_url: String,
+    poll_interval: Duration,
+    notional: f64,
+    pair: TradingPair,
+    http: HttpPolicy,
+}
+
+/// Shared write path for source tasks: every quote update goes through here
+#[derive(Clone)]
+struct QuoteSink {
+    prices: Arc<RwLock<AggregatedPrices>>,
+    recorder: Option<Arc<TickRecorder>>,
+    health: Arc<RwLock<HashMap<Source, SourceHealth>>>,
+    metrics: Arc<RwLock<HashMap<Source, MetricsState>>>,
+    policy: ReconnectPolicy,
+    events: broadcast::Sender<QuoteUpdate>,
+    feed_events: broadcast::Sender<FeedEvent>,
+    tape: broadcast::Sender<TapeEvent>,
+    clock: Arc<dyn Clock>,
+    flow: Arc<RwLock<FlowTracker>>,
+}
+
+impl QuoteSink {
+    fn now_ms(&self) -> i64 {
+        self.clock.now_ms()
+    }
+
+    /// Mark a failed fetch/connection, report it, and return how long to back off before retrying
+    async fn failure(&self, error: MarketMakerError) -> Duration {
+        let delay = {
+            let mut health = self.health.write().await;
+            let entry = health.entry(error.feed()).or_default();
+            entry.record_failure(error.to_string());
+            let delay = self.policy.delay(entry.consecutive_failures);
+            debug!(failures = entry.consecutive_failures, retry_in_ms = delay.as_millis() as u64, "backing off");
+            delay
+        };
+        self.report(error);
+        delay
+    }
+
+    /// Report an error to event subscribers without counting it against the source's health
+    fn report(&self, error: MarketMakerError) {
+        // No subscribers is not an error
+        let _ = self.feed_events.send(FeedEvent::Error { timestamp: self.now_ms(), error });
+    }
+
+    async fn record_latency(&self, source: Source, latency: Duration) {
+        self.metrics.write().await.entry(source).or_default().record_latency(latency);
+    }
+
+    async fn record_http(&self, source: Source, stats: HttpStats) {
+        self.metrics.write().await.entry(source).or_default().record_http(stats);
+    }
+
+    async fn record_connect(&self, source: Source) {
+        self.metrics.write().await.entry(source).or_default().record_connect();
+        let _ = self.feed_events.send(FeedEvent::Connected { source, timestamp: self.now_ms() });
+    }
+
+    async fn set_sol_usd(&self, price: f64) {
+        self.prices.write().await.sol_usd = Some(price);
+    }
+
+    async fn record_trade(&self, trade: TapeTrade) {
+        self.flow.write().await.record(trade);
+        let _ = self.tape.send(TapeEvent::Trade(trade));
+    }
+
+    fn tape_dropped(&self) {
+        let _ = self.tape.send(TapeEvent::Dropped { timestamp: self.now_ms() });
+    }
+
+    async fn update(&self, source: Source, quote: Quote) {
+        self.prices.write().await.set(source, quote);
+        let failures = {
+            let mut health = self.health.write().await;
+            let entry = health.entry(source).or_default();
+            let failures = entry.consecutive_failures;
+            entry.record_success(quote.timestamp);
+            failures
+        };
+        if failures > 0 {
+            let _ = self.feed_events.send(FeedEvent::Recovered { source, timestamp: quote.timestamp, failures });
+        }
+        self.metrics
+            .write()
+            .await
+            .entry(source)
+            .or_default()
+            .record_update(quote.timestamp);
+
+        // No subscribers is not an error
+        let _ = self.events.send(QuoteUpdate { source, quote });
+
+        if let Some(recorder) = &self.recorder {
+            if let Err(e) = recorder.record(source, &quote) {
+                warn!(source = source.name(), error = %e, "failed to record tick");
+            }
+        }
+    }
+}
+
+/// Span a source's feed task runs in, so every event it logs carries the source
+fn source_span(source: Source) -> tracing::Span {
+    info_span!("source", source = source.name())
 }
 
 pub struct PriceAggregator {
     prices: Arc<RwLock<AggregatedPrices>>,
+    recorder: Option<Arc<TickRecorder>>,
+    synthetic: HashMap<Source, SyntheticConfig>,
+    health: Arc<RwLock<HashMap<Source, SourceHealth>>>,
+    metrics: Arc<RwLock<HashMap<Source, MetricsState>>>,
+    reconnect_policy: ReconnectPolicy,
+    uniswap: Option<UniswapConfig>,
+    quote_notional: f64,
+    pair: TradingPair,
+    sources: SourcesConfig,
+    events: broadcast::Sender<QuoteUpdate>,
+    feed_events: broadcast::Sender<FeedEvent>,
+    /// Every trade off the Binance aggTrade stream, and its drops
+    tape: broadcast::Sender<TapeEvent>,
+    /// Report a source stale once its last quote is older than this
+    stale_after: Option<Duration>,
+    clock: Arc<dyn Clock>,
+    /// Taker trades from the Binance aggTrade stream
+    flow: Arc<RwLock<FlowTracker>>,
+    /// Source tasks started by `start`, aborted by `stop`
+    tasks: std::sync::Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl Default for PriceAggregator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PriceAggregator {
     pub fn new() -> Self {
         Self {
-            prices: Arc::new(RwLock::new(AggregatedPrices {
-                binance: None,
-                jupiter: None,
-                cowswap: None,
-            })),
+            prices: Arc::new(RwLock::new(AggregatedPrices::default())),
+            recorder: None,
+            synthetic: HashMap::new(),
+            health: Arc::new(RwLock::new(HashMap::new())),
+            metrics: Arc::new(RwLock::new(HashMap::new())),
+            reconnect_policy: ReconnectPolicy::default(),
+            uniswap: None,
+            quote_notional: DEFAULT_QUOTE_NOTIONAL,
+            pair: TradingPair::default(),
+            sources: SourcesConfig::default(),
+            events: broadcast::channel(QUOTE_CHANNEL_CAPACITY).0,
+            feed_events: broadcast::channel(FEED_EVENT_CHANNEL_CAPACITY).0,
+            tape: broadcast::channel(QUOTE_CHANNEL_CAPACITY).0,
+            stale_after: None,
+            clock: clock::system(),
+            flow: Arc::new(RwLock::new(FlowTracker::default())),
+            tasks: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Receive every quote update as it arrives instead of polling `get_prices`
+    pub fn subscribe(&self) -> broadcast::Receiver<QuoteUpdate> {
+        self.events.subscribe()
+    }
+
+    /// Receive source connections, typed errors and recoveries as they happen, e.g. to alert on
+    /// an outage or feed them to the risk manager
+    pub fn subscribe_events(&self) -> broadcast::Receiver<FeedEvent> {
+        self.feed_events.subscribe()
+    }
+
+    /// Receive every taker trade off the Binance aggTrade stream, and a `Dropped` whenever the
+    /// stream goes down after printing; nothing arrives while the stream is off or Binance is synthetic
+    pub fn subscribe_tape(&self) -> broadcast::Receiver<TapeEvent> {
+        self.tape.subscribe()
+    }
+
+    /// Report `StaleData` for a source whose last quote gets older than `max_age`, once per
+    /// stretch without quotes
+    pub fn with_stale_after(mut self, max_age: Duration) -> Self {
+        self.stale_after = Some(max_age).filter(|d| !d.is_zero());
+        self
+    }
+
+    pub fn with_reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = policy;
+        self
+    }
+
+    /// Stamp quotes and measure staleness on `clock` instead of the wall clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Update rate, staleness, poll latency and reconnect count for every source slot
+    pub async fn metrics(&self) -> HashMap<Source, SourceMetrics> {
+        let now = self.clock.now_ms();
+        let mut metrics = self.metrics.write().await;
+        Source::ALL
+            .iter()
+            .map(|&s| (s, metrics.entry(s).or_default().snapshot(now)))
+            .collect()
+    }
+
+    /// HTTP request stats of the sources polled over REST
+    pub async fn http_stats(&self) -> Vec<(Source, HttpStats)> {
+        let metrics = self.metrics.read().await;
+        Source::ALL
+            .iter()
+            .filter_map(|&s| Some((s, metrics.get(&s)?.http()?)))
+            .collect()
+    }
+
+    /// Current health of every source slot
+    pub async fn health(&self) -> HashMap<Source, SourceHealth> {
+        let health = self.health.read().await;
+        Source::ALL
+            .iter()
+            .map(|&s| (s, health.get(&s).cloned().unwrap_or_default()))
+            .collect()
+    }
+
+    /// Replace the live feed in `source`'s slot with a synthetic GBM path
+    pub fn with_synthetic(mut self, source: Source, config: SyntheticConfig) -> Self {
+        self.synthetic.insert(source, config);
+        self
+    }
+
+    /// USD size that Jupiter and CowSwap quotes are priced at, so bid/ask reflect routing and slippage at that size
+    pub fn with_quote_notional(mut self, notional: f64) -> Self {
+        self.quote_notional = notional;
+        self
+    }
+
+    /// Pair the DEX sources quote, ETH/USDC by default; resolved through the token registry
+    pub fn with_pair(mut self, pair: TradingPair) -> Self {
+        self.pair = pair;
+        self
+    }
+
+    /// Endpoints and poll intervals for the live sources
+    pub fn with_sources(mut self, sources: SourcesConfig) -> Self {
+        self.sources = sources;
+        self
+    }
+
+    /// Poll Uniswap V3 pools through an Ethereum RPC node; the slot stays empty otherwise
+    pub fn with_uniswap(mut self, config: UniswapConfig) -> Self {
+        self.uniswap = Some(config);
+        self
+    }
+
+    pub fn is_synthetic(&self, source: Source) -> bool {
+        self.synthetic.contains_key(&source)
+    }
+
+    /// Persist every quote update to the given recorder
+    pub fn with_recorder(mut self, recorder: Arc<TickRecorder>) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Push a quote through the same write path as the source tasks: snapshot, health, metrics,
+    /// subscribers and recorder. For feeding the aggregator from outside, e.g. benchmarks.
+    pub async fn ingest(&self, source: Source, quote: Quote) {
+        self.sink().update(source, quote).await;
+    }
+
+    fn sink(&self) -> QuoteSink {
+        QuoteSink {
+            prices: Arc::clone(&self.prices),
+            recorder: self.recorder.clone(),
+            health: Arc::clone(&self.health),
+            metrics: Arc::clone(&self.metrics),
+            policy: self.reconnect_policy,
+            events: self.events.clone(),
+            feed_events: self.feed_events.clone(),
+            tape: self.tape.clone(),
+            clock: Arc::clone(&self.clock),
+            flow: Arc::clone(&self.flow),
         }
     }
 
     pub async fn start(&self) -> Result<()> {
-        let prices_binance = Arc::clone(&self.prices);
-        let prices_jupiter = Arc::clone(&self.prices);
-        let prices_cowswap = Arc::clone(&self.prices);
+        for (&source, &config) in &self.synthetic {
+            let sink = self.sink();
+            self.spawn(Self::synthetic_feed(sink, source, config).instrument(source_span(source)));
+        }
 
-        tokio::spawn(async move {
-            if let Err(e) = Self::binance_stream(prices_binance).await {
-                eprintln!("[ERROR] Binance stream error: {}", e);
-            }
-        });
+        if let (false, Err(e)) = (self.is_synthetic(Source::Binance), self.pair.binance_symbol()) {
+            warn!(source = "binance", error = %e, "can't quote {} on Binance, leaving the slot empty", self.pair);
+        } else if !self.is_synthetic(Source::Binance) {
+            let prices_binance = self.sink();
+            let url = self.sources.binance_stream(&self.pair)?;
+            self.spawn(async move {
+                if let Err(e) = Self::binance_stream(prices_binance.clone(), url).await {
+                    error!(error = %e, "stream stopped");
+                    prices_binance.report(MarketMakerError::connection(Source::Binance, e));
+                }
+            }.instrument(source_span(Source::Binance)));
 
-        tokio::spawn(async move {
-            if let Err(e) = Self::jupiter_poll(prices_jupiter).await {
-                eprintln!("[ERROR] Jupiter poll error: {}", e);
+            if let Some(url) = self.sources.binance_trades_stream(&self.pair)? {
+                let prices_trades = self.sink();
+                self.spawn(Self::binance_trades(prices_trades, url).instrument(info_span!("tape", source = "binance")));
             }
-        });
+        }
 
-        tokio::spawn(async move {
-            if let Err(e) = Self::cowswap_poll(prices_cowswap).await {
-                eprintln!("[ERROR] Cowswap poll error: {}", e);
-            }
-        });
+        if let (false, Err(e)) = (self.is_synthetic(Source::Jupiter), self.pair.spl()) {
+            warn!(source = "jupiter", error = %e, "can't quote {} on Jupiter, leaving the slot empty", self.pair);
+        } else if !self.is_synthetic(Source::Jupiter) {
+            let prices_jupiter = self.sink();
+            let endpoint = HttpEndpoint {
+                base_url: self.sources.jupiter_api_url.clone(),
+                poll_interval: Duration::from_secs(self.sources.jupiter_poll_secs),
+                notional: self.quote_notional,
+                pair: self.pair.clone(),
+                http: self.sources.http_policy(Source::Jupiter),
+            };
+            self.spawn(async move {
+                if let Err(e) = Self::jupiter_poll(prices_jupiter.clone(), endpoint).await {
+                    error!(error = %e, "poll stopped");
+                    prices_jupiter.report(MarketMakerError::from_fetch(Source::Jupiter, &e));
+                }
+            }.instrument(source_span(Source::Jupiter)));
+        }
+
+        if let (false, Err(e)) = (self.is_synthetic(Source::CowSwap), self.pair.evm()) {
+            warn!(source = "cowswap", error = %e, "can't quote {} on CowSwap, leaving the slot empty", self.pair);
+        } else if !self.is_synthetic(Source::CowSwap) {
+            let prices_cowswap = self.sink();
+            let endpoint = HttpEndpoint {
+                base_url: self.sources.cowswap_api_url.clone(),
+                poll_interval: Duration::from_secs(self.sources.cowswap_poll_secs),
+                notional: self.quote_notional,
+                pair: self.pair.clone(),
+                http: self.sources.http_policy(Source::CowSwap),
+            };
+            self.spawn(async move {
+                if let Err(e) = Self::cowswap_poll(prices_cowswap.clone(), endpoint).await {
+                    error!(error = %e, "poll stopped");
+                    prices_cowswap.report(MarketMakerError::from_fetch(Source::CowSwap, &e));
+                }
+            }.instrument(source_span(Source::CowSwap)));
+        }
+
+        if let (false, Some(config)) = (self.is_synthetic(Source::Uniswap), self.uniswap.clone()) {
+            let prices_uniswap = self.sink();
+            self.spawn(async move {
+                if let Err(e) = Self::uniswap_poll(prices_uniswap.clone(), config).await {
+                    error!(error = %e, "poll stopped");
+                    prices_uniswap.report(MarketMakerError::from_fetch(Source::Uniswap, &e));
+                }
+            }.instrument(source_span(Source::Uniswap)));
+        }
+
+        if let Some(max_age) = self.stale_after {
+            self.spawn(Self::stale_watchdog(self.sink(), max_age));
+        }
 
         Ok(())
     }
 
-    async fn binance_stream(prices: Arc<RwLock<AggregatedPrices>>) -> Result<()> {
-        let url = "wss://stream.binance.com:9443/ws/ethusdc@bookTicker";
+    /// Stop every source task started by `start`; the last prices stay readable
+    pub fn stop(&self) {
+        for task in self.tasks.lock().unwrap().drain(..) {
+            task.abort();
+        }
+    }
+
+    fn spawn(&self, task: impl Future<Output = ()> + Send + 'static) {
+        self.tasks.lock().unwrap().push(tokio::spawn(task));
+    }
+
+    /// Report sources whose last quote is older than `max_age`. Sources that never quoted are
+    /// left to their connection errors.
+    async fn stale_watchdog(prices: QuoteSink, max_age: Duration) {
+        let mut interval = interval(max_age.min(Duration::from_secs(1)));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut reported = std::collections::HashSet::new();
+        loop {
+            interval.tick().await;
+            let now = prices.now_ms();
+            let last_quotes: Vec<(Source, i64)> = prices
+                .health
+                .read()
+                .await
+                .iter()
+                .filter_map(|(&source, health)| Some((source, health.last_success?)))
+                .collect();
+            for (source, last) in last_quotes {
+                let age_ms = now - last;
+                if age_ms <= max_age.as_millis() as i64 {
+                    reported.remove(&source);
+                } else if reported.insert(source) {
+                    warn!(source = source.name(), age_ms, "no quote within the staleness limit");
+                    prices.report(MarketMakerError::stale(source, age_ms, max_age));
+                }
+            }
+        }
+    }
+
+    async fn synthetic_feed(prices: QuoteSink, source: Source, config: SyntheticConfig) {
+        let mut generator = SyntheticSource::new(config);
+        let mut interval = interval(config.tick_interval);
+
+        loop {
+            interval.tick().await;
+            let quote = generator.next_quote(prices.now_ms());
+            prices.update(source, quote).await;
+        }
+    }
 
+    async fn binance_stream(prices: QuoteSink, url: String) -> Result<()> {
         loop {
-            match connect_async(url).await {
+            match connect_async(url.as_str()).await {
                 Ok((ws_stream, _)) => {
-                    println!("Connected to Binance WebSocket");
+                    info!("connected to websocket");
+                    prices.record_connect(Source::Binance).await;
                     let (mut _write, mut read) = ws_stream.split();
 
+                    // Why the connection ended, reported once with the backoff below
+                    let mut cause = "websocket disconnected".to_string();
                     while let Some(msg) = read.next().await {
                         match msg {
                             Ok(Message::Text(text)) => {
-                                if let Ok(ticker) = serde_json::from_str::<BinanceBookTicker>(&text)
-                                {
-                                    if let (Ok(bid), Ok(ask)) = (
-                                        ticker.bid_price.parse::<f64>(),
-                                        ticker.ask_price.parse::<f64>(),
-                                    ) {
+                                match Self::parse_book_ticker(&text) {
+                                    Ok((bid, ask)) => {
                                         let quote = Quote {
                                             bid,
                                             ask,
-                                            timestamp: chrono::Utc::now().timestamp_millis(),
+                                            timestamp: prices.now_ms(),
                                         };
-                                        prices.write().await.binance = Some(quote);
+                                        prices.update(Source::Binance, quote).await;
                                     }
+                                    Err(e) => prices.report(MarketMakerError::parse(Source::Binance, e)),
                                 }
                             }
                             Ok(Message::Binary(_)) => {}
@@ -205,104 +869,397 @@ impl PriceAggregator {
                             Ok(Message::Pong(_)) => {}
                             Ok(Message::Frame(_)) => {}
                             Ok(Message::Close(_)) => {
-                                println!("[INFO] Binance websocket closed, reconnecting...");
+                                info!("websocket closed, reconnecting");
+                                cause = "websocket closed".to_string();
                                 break;
                             }
                             Err(e) => {
-                                eprintln!("[ERROR] Binance WebSocket error: {}", e);
+                                error!(error = %e, "websocket error");
+                                cause = e.to_string();
+                                break;
                             }
                         }
                     }
+
+                    let delay = prices.failure(MarketMakerError::connection(Source::Binance, cause)).await;
+                    tokio::time::sleep(delay).await;
                 }
                 Err(e) => {
-                    eprintln!("[ERROR] Failed to connect to Binance: {}", e);
+                    error!(error = %e, "failed to connect");
+                    let delay = prices.failure(MarketMakerError::connection(Source::Binance, e)).await;
+                    tokio::time::sleep(delay).await;
                 }
             }
+        }
+    }
+
+    /// Bid and ask of a bookTicker stream message
+    fn parse_book_ticker(text: &str) -> Result<(f64, f64)> {
+        let ticker = serde_json::from_str::<BinanceBookTicker>(text)?;
+        Ok((ticker.bid_price.parse()?, ticker.ask_price.parse()?))
+    }
+
+    /// Price, size and aggressor side of an aggTrade stream message
+    fn parse_agg_trade(text: &str) -> Result<(f64, f64, TradeSide)> {
+        let trade = serde_json::from_str::<BinanceAggTrade>(text)?;
+        let aggressor = if trade.buyer_is_maker { TradeSide::Sell } else { TradeSide::Buy };
+        Ok((trade.price.parse()?, trade.quantity.parse()?, aggressor))
+    }
 
-            tokio::time::sleep(Duration::from_secs(5)).await;
+    /// Feed the flow tracker from the aggTrade stream. Outages back off like the quote stream's
+    /// but stay out of the Binance source's health, which tracks quotes.
+    async fn binance_trades(prices: QuoteSink, url: String) {
+        let mut failures = 0;
+        // Whether trades have printed since the last drop, so each outage is marked on the tape once
+        let mut streaming = false;
+        loop {
+            match connect_async(url.as_str()).await {
+                Ok((ws_stream, _)) => {
+                    info!("connected to trade stream");
+                    failures = 0;
+                    let (_write, mut read) = ws_stream.split();
+
+                    while let Some(msg) = read.next().await {
+                        match msg {
+                            Ok(Message::Text(text)) => match Self::parse_agg_trade(&text) {
+                                Ok((price, amount_eth, aggressor)) => {
+                                    streaming = true;
+                                    prices.record_trade(TapeTrade {
+                                        timestamp: prices.now_ms(),
+                                        price,
+                                        amount_eth,
+                                        aggressor,
+                                    }).await;
+                                }
+                                Err(e) => prices.report(MarketMakerError::trade_stream(Source::Binance, format!("unreadable trade: {}", e))),
+                            },
+                            Ok(Message::Close(_)) => {
+                                info!("trade stream closed, reconnecting");
+                                prices.report(MarketMakerError::trade_stream(Source::Binance, "stream closed"));
+                                break;
+                            }
+                            Ok(_) => {}
+                            Err(e) => {
+                                error!(error = %e, "trade stream error");
+                                prices.report(MarketMakerError::trade_stream(Source::Binance, e));
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    error!(error = %e, "failed to connect to trade stream");
+                    prices.report(MarketMakerError::trade_stream(Source::Binance, e));
+                }
+            }
+            if streaming {
+                prices.tape_dropped();
+                streaming = false;
+            }
+            failures += 1;
+            tokio::time::sleep(prices.policy.delay(failures)).await;
         }
     }
 
-    async fn jupiter_poll(prices: Arc<RwLock<AggregatedPrices>>) -> Result<()> {
-        let client = reqwest::Client::new();
-        let mut interval = interval(Duration::from_secs(2));
-        let url =
-            "https://lite-api.jup.ag/price/v3?ids=7vfCXTUXx5WJV5JADk17DUJ4ksgau7utNKj4b963voxs";
-        let eth_token_id = "7vfCXTUXx5WJV5JADk17DUJ4ksgau7utNKj4b963voxs";
+    async fn jupiter_poll(prices: QuoteSink, endpoint: HttpEndpoint) -> Result<()> {
+        let client = HttpClient::new(endpoint.http)?;
+        let mut interval = interval(endpoint.poll_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
         loop {
             interval.tick().await;
 
-            match client.get(url).send().await {
-                Ok(response) => {
-                    if let Ok(data) = response.json::<serde_json::Value>().await {
-                        if let Some(price) = data[eth_token_id]["usdPrice"].as_f64() {
-                            let spread = price * 0.0005;
-                            let quote = Quote {
-                                bid: price - spread,
-                                ask: price + spread,
-                                timestamp: chrono::Utc::now().timestamp_millis(),
-                            };
-                            prices.write().await.jupiter = Some(quote);
-                        }
+            let started = std::time::Instant::now();
+            let result = Self::fetch_jupiter(&client, &endpoint).await;
+            prices.record_latency(Source::Jupiter, started.elapsed()).await;
+            prices.record_http(Source::Jupiter, client.stats()).await;
+
+            match result {
+                Ok((mut quote, sol_price)) => {
+                    quote.timestamp = prices.now_ms();
+                    if let Some(sol_price) = sol_price {
+                        prices.set_sol_usd(sol_price).await;
                     }
+                    prices.update(Source::Jupiter, quote).await;
                 }
                 Err(e) => {
-                    eprintln!("[ERROR] Jupiter fetch error: {}", e);
+                    error!(error = %e, "fetch failed");
+                    let delay = prices.failure(MarketMakerError::from_fetch(Source::Jupiter, &e)).await;
+                    tokio::time::sleep(delay).await;
                 }
             }
         }
     }
 
-    async fn cowswap_poll(prices: Arc<RwLock<AggregatedPrices>>) -> Result<()> {
-        let client = reqwest::Client::new();
-        let mut interval = interval(Duration::from_secs(3));
+    /// Base token quote priced by swapping `notional` both ways, plus the SOL/USD reference rate
+    async fn fetch_jupiter(client: &HttpClient, endpoint: &HttpEndpoint) -> Result<(Quote, Option<f64>)> {
+        let notional = endpoint.notional;
+        let base_url = endpoint.base_url.as_str();
+        let pair = &endpoint.pair;
+        let (base, quote) = pair.spl()?;
+
+        // Buy side: quote token in, base token out
+        let quote_in = tokens::to_units(notional, quote.decimals) as u64;
+        let (_, base_out) = Self::jupiter_swap_quote(client, base_url, &quote.mint, &base.mint, quote_in).await?;
+        let base_amount = tokens::from_units(base_out as u128, base.decimals);
+        if base_amount <= 0.0 {
+            anyhow::bail!("Jupiter quoted zero {} for {} {}", pair.base.symbol, notional, pair.quote.symbol);
+        }
+        let ask = notional / base_amount;
+
+        // Sell side: the same base amount back into the quote token
+        let (base_in, quote_out) = Self::jupiter_swap_quote(client, base_url, &base.mint, &quote.mint, base_out).await?;
+        let bid = tokens::from_units(quote_out as u128, quote.decimals) / tokens::from_units(base_in as u128, base.decimals);
+
+        let quote = Quote {
+            bid,
+            ask,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        };
+
+        // The SOL rate is only used for display, so a failed lookup isn't fatal
+        let sol_price = Self::fetch_sol_usd(client, base_url).await.ok();
+
+        Ok((quote, sol_price))
+    }
+
+    /// `(inAmount, outAmount)` in base units for an exact-in swap
+    async fn jupiter_swap_quote(
+        client: &HttpClient,
+        base_url: &str,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+    ) -> Result<(u64, u64)> {
+        let url = format!(
+            "{}/swap/v1/quote?inputMint={}&outputMint={}&amount={}&swapMode=ExactIn&slippageBps=50",
+            base_url, input_mint, output_mint, amount
+        );
+
+        let data = client
+            .send(|c| c.get(&url))
+            .await?
+            .json::<JupiterSwapQuote>()
+            .await?;
+
+        Ok((data.in_amount.parse()?, data.out_amount.parse()?))
+    }
+
+    async fn fetch_sol_usd(client: &HttpClient, base_url: &str) -> Result<f64> {
+        Self::fetch_usd_price(client, base_url, WRAPPED_SOL_MINT).await
+    }
+
+    /// USD price of `mint` from the Jupiter price API
+    async fn fetch_usd_price(client: &HttpClient, base_url: &str, mint: &str) -> Result<f64> {
+        let url = format!("{}/price/v3?ids={}", base_url, mint);
+
+        let data = client
+            .send(|c| c.get(&url))
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+
+        data[mint]["usdPrice"]
+            .as_f64()
+            .with_context(|| format!("missing usdPrice for {} in Jupiter response", mint))
+    }
+
+    /// One Jupiter swap-quote fetch at `notional`, outside of a running aggregator
+    pub(crate) async fn fetch_jupiter_once(client: &HttpClient, base_url: &str, pair: &TradingPair, notional: f64) -> Result<Quote> {
+        let endpoint = HttpEndpoint {
+            base_url: base_url.to_string(),
+            poll_interval: Duration::ZERO,
+            notional,
+            pair: pair.clone(),
+            http: *client.policy(),
+        };
+        Ok(Self::fetch_jupiter(client, &endpoint).await?.0)
+    }
+
+    /// The base token from the Jupiter price API: a single USD reference price, so bid and ask are equal
+    pub(crate) async fn fetch_jupiter_price_once(client: &HttpClient, base_url: &str, pair: &TradingPair) -> Result<Quote> {
+        let price = Self::fetch_usd_price(client, base_url, &pair.base.spl()?.mint).await?;
+        Ok(Quote {
+            bid: price,
+            ask: price,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        })
+    }
 
-        let eth_address = "0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE";
-        let usdc_address = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+    /// One CowSwap round-trip quote at `notional`, outside of a running aggregator
+    pub(crate) async fn fetch_cowswap_once(client: &HttpClient, base_url: &str, pair: &TradingPair, notional: f64) -> Result<Quote> {
+        let endpoint = HttpEndpoint {
+            base_url: base_url.to_string(),
+            poll_interval: Duration::ZERO,
+            notional,
+            pair: pair.clone(),
+            http: *client.policy(),
+        };
+        Self::fetch_cowswap(client, &endpoint).await
+    }
+
+    /// Binance top of book over REST instead of the WebSocket stream
+    pub(crate) async fn fetch_binance_rest_once(client: &HttpClient, base_url: &str, pair: &TradingPair) -> Result<Quote> {
+        let url = format!("{}/api/v3/ticker/bookTicker?symbol={}", base_url, pair.binance_symbol()?);
+        let data = client
+            .send(|c| c.get(&url))
+            .await?
+            .json::<BinanceBookTicker>()
+            .await?;
+
+        Ok(Quote {
+            bid: data.bid_price.parse()?,
+            ask: data.ask_price.parse()?,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        })
+    }
+
+    async fn cowswap_poll(prices: QuoteSink, endpoint: HttpEndpoint) -> Result<()> {
+        let client = HttpClient::new(endpoint.http)?;
+        let mut interval = interval(endpoint.poll_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
         loop {
             interval.tick().await;
 
-            let buy_url = "https://api.cow.fi/mainnet/api/v1/quote";
-            let buy_params = serde_json::json!({
-                "sellToken": usdc_address,
-                "buyToken": eth_address,
-                "sellAmountBeforeFee": "1000000000", // 1000 USDC (6 decimals)
-                "kind": "sell",
-                "from": "0x0000000000000000000000000000000000000000"
-            });
-
-            match client.post(buy_url).json(&buy_params).send().await {
-                Ok(response) => {
-                    if let Ok(data) = response.json::<serde_json::Value>().await {
-                        if let (Some(sell_amount), Some(buy_amount)) = (
-                            data["quote"]["sellAmount"].as_str(),
-                            data["quote"]["buyAmount"].as_str(),
-                        ) {
-                            if let (Ok(sell), Ok(buy)) =
-                                (sell_amount.parse::<f64>(), buy_amount.parse::<f64>())
-                            {
-                                // Calculate price with proper decimals
-                                let price = (sell / 1e6) / (buy / 1e18);
-                                let spread = price * 0.001; // 0.1% spread estimate
-
-                                let quote = Quote {
-                                    bid: price - spread,
-                                    ask: price + spread,
-                                    timestamp: chrono::Utc::now().timestamp_millis(),
-                                };
-                                prices.write().await.cowswap = Some(quote);
-                            }
-                        }
-                    }
+            let started = std::time::Instant::now();
+            let result = Self::fetch_cowswap(&client, &endpoint).await;
+            prices.record_latency(Source::CowSwap, started.elapsed()).await;
+            prices.record_http(Source::CowSwap, client.stats()).await;
+
+            match result {
+                Ok(mut quote) => {
+                    quote.timestamp = prices.now_ms();
+                    prices.update(Source::CowSwap, quote).await
+                }
+                Err(e) => {
+                    error!(error = %e, "fetch failed");
+                    let delay = prices.failure(MarketMakerError::from_fetch(Source::CowSwap, &e)).await;
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+
+    /// Price both sides by selling `notional` of the quote token for the base token and that back
+    async fn fetch_cowswap(client: &HttpClient, endpoint: &HttpEndpoint) -> Result<Quote> {
+        let notional = endpoint.notional;
+        let pair = &endpoint.pair;
+        let (base, quote) = pair.evm()?;
+
+        // Buy side: quote token in, base token out (the native coin when it has one)
+        let quote_in = tokens::to_units(notional, quote.decimals);
+        let buy_token = base.native_address.as_deref().unwrap_or(&base.address);
+        let (quote_sold, base_bought) =
+            Self::cowswap_sell_quote(client, &endpoint.base_url, &quote.address, buy_token, quote_in).await?;
+        if base_bought == 0 {
+            anyhow::bail!("CowSwap quoted zero {} for {} {}", pair.base.symbol, notional, pair.quote.symbol);
+        }
+        let ask = tokens::from_units(quote_sold, quote.decimals) / tokens::from_units(base_bought, base.decimals);
+
+        // Sell side: the bought amount back into the quote token (a native coin can't be sold, so sell the wrapped token)
+        let (base_sold, quote_bought) =
+            Self::cowswap_sell_quote(client, &endpoint.base_url, &base.address, &quote.address, base_bought).await?;
+        let bid = tokens::from_units(quote_bought, quote.decimals) / tokens::from_units(base_sold, base.decimals);
+
+        Ok(Quote {
+            bid,
+            ask,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        })
+    }
+
+    /// `(amount sold including fee, amount bought)` in base units for a sell order
+    async fn cowswap_sell_quote(
+        client: &HttpClient,
+        base_url: &str,
+        sell_token: &str,
+        buy_token: &str,
+        amount: u128,
+    ) -> Result<(u128, u128)> {
+        let params = serde_json::json!({
+            "sellToken": sell_token,
+            "buyToken": buy_token,
+            "sellAmountBeforeFee": amount.to_string(),
+            "kind": "sell",
+            "from": "0x0000000000000000000000000000000000000000"
+        });
+
+        let url = format!("{}/api/v1/quote", base_url);
+        let data = client
+            .send(|c| c.post(&url).json(&params))
+            .await?
+            .json::<CowSwapQuote>()
+            .await?;
+
+        // Fees are taken from the sell amount, so price against the full amount given up
+        let sell: u128 = data.quote.sell_amount.parse()?;
+        let fee: u128 = data.quote.fee_amount.parse()?;
+        Ok((sell + fee, data.quote.buy_amount.parse()?))
+    }
+
+    async fn uniswap_poll(prices: QuoteSink, config: UniswapConfig) -> Result<()> {
+        let client = reqwest::Client::new();
+        let mut interval = interval(config.poll_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            let started = std::time::Instant::now();
+            let result = uniswap::fetch_quote(&client, &config).await;
+            prices.record_latency(Source::Uniswap, started.elapsed()).await;
+
+            match result {
+                Ok((mut quote, _tiers)) => {
+                    quote.timestamp = prices.now_ms();
+                    prices.update(Source::Uniswap, quote).await
+                }
+                Err(e) => {
+                    error!(error = format!("{:#}", e), "fetch failed");
+                    let delay = prices.failure(MarketMakerError::from_fetch(Source::Uniswap, &e)).await;
+                    tokio::time::sleep(delay).await;
                 }
-                Err(e) => eprintln!("[ERROR] CowSwap fetch error: {}", e),
             }
         }
     }
 
+    /// Snapshot of every source's latest quote, with taker flow over the configured window
     pub async fn get_prices(&self) -> AggregatedPrices {
-        self.prices.read().await.clone()
+        let mut prices = self.prices.read().await.clone();
+        prices.flow = self.flow_imbalance(Duration::from_secs(self.sources.flow_window_secs)).await;
+        prices
+    }
+
+    /// `get_prices` with every quote aged against the aggregator's clock, so a feed that has gone
+    /// quiet shows its real age even when every other feed has too
+    pub async fn snapshot(&self) -> PriceSnapshot {
+        let prices = self.get_prices().await;
+        PriceSnapshot::new(prices, self.clock.now_ms())
+    }
+
+    /// Taker buy/sell imbalance on the Binance tape over the last `window` (at most five minutes);
+    /// `None` until the trade stream has delivered a trade
+    pub async fn flow_imbalance(&self, window: Duration) -> Option<FlowImbalance> {
+        let flow = self.flow.read().await;
+        (!flow.is_empty()).then(|| flow.imbalance(self.clock.now_ms(), window))
+    }
+}
+
+impl PriceFeed for PriceAggregator {
+    async fn get_prices(&self) -> AggregatedPrices {
+        PriceAggregator::get_prices(self).await
+    }
+
+    async fn snapshot(&self) -> PriceSnapshot {
+        PriceAggregator::snapshot(self).await
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<QuoteUpdate> {
+        PriceAggregator::subscribe(self)
+    }
+
+    fn subscribe_events(&self) -> Option<broadcast::Receiver<FeedEvent>> {
+        Some(PriceAggregator::subscribe_events(self))
     }
 }
@@ -0,0 +1,226 @@
+use crate::aggregator::{AggregatedPrices, QuoteUpdate};
+use crate::say;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// A condition over the aggregated feed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// Median mid moves through `level` in either direction
+    MidCross { level: f64 },
+    /// Median bid/ask spread wider than `bps`
+    SpreadAbove { bps: f64 },
+    /// Gap between the highest and lowest source mid wider than `bps` of the median mid
+    DispersionAbove { bps: f64 },
+}
+
+impl fmt::Display for AlertCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlertCondition::MidCross { level } => write!(f, "mid crosses ${:.2}", level),
+            AlertCondition::SpreadAbove { bps } => write!(f, "spread > {:.1} bps", bps),
+            AlertCondition::DispersionAbove { bps } => write!(f, "dispersion > {:.1} bps", bps),
+        }
+    }
+}
+
+/// Parse `kind=value`, e.g. `mid_cross=3500` or `spread_above=8`
+impl FromStr for AlertCondition {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, value) = s
+            .split_once('=')
+            .with_context(|| format!("alert rule '{}' should look like kind=value", s))?;
+        let value: f64 = value
+            .trim()
+            .parse()
+            .with_context(|| format!("invalid value in alert rule '{}'", s))?;
+        match kind.trim().to_ascii_lowercase().as_str() {
+            "mid_cross" => Ok(AlertCondition::MidCross { level: value }),
+            "spread_above" => Ok(AlertCondition::SpreadAbove { bps: value }),
+            "dispersion_above" => Ok(AlertCondition::DispersionAbove { bps: value }),
+            other => bail!(
+                "unknown alert kind '{}' (expected mid_cross, spread_above or dispersion_above)",
+                other
+            ),
+        }
+    }
+}
+
+/// A user-defined alert: a condition plus an optional label
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub condition: AlertCondition,
+}
+
+impl AlertRule {
+    pub fn new(condition: AlertCondition) -> Self {
+        Self { name: None, condition }
+    }
+
+    pub fn label(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.condition.to_string())
+    }
+}
+
+/// A rule that fired
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub rule: String,
+    pub message: String,
+    /// Timestamp of the quote that triggered it
+    pub timestamp: i64,
+}
+
+/// Where fired alerts are delivered
+pub trait AlertSink: Send + Sync {
+    fn dispatch(&self, alert: &Alert);
+}
+
+/// Print alerts to the session log
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsoleAlertSink;
+
+impl AlertSink for ConsoleAlertSink {
+    fn dispatch(&self, alert: &Alert) {
+        say!("[ALERT] {} │ {}", alert.rule, alert.message);
+    }
+}
+
+struct RuleState {
+    rule: AlertRule,
+    /// Condition held on the previous evaluation; rules fire on the transition to true
+    active: bool,
+}
+
+/// Evaluates alert rules on every quote update and dispatches the ones that fire
+pub struct AlertEngine {
+    rules: Vec<RuleState>,
+    sinks: Vec<Box<dyn AlertSink>>,
+    prices: AggregatedPrices,
+    last_mid: Option<f64>,
+    fired: Vec<Alert>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        Self {
+            rules: rules.into_iter().map(|rule| RuleState { rule, active: false }).collect(),
+            sinks: Vec::new(),
+            prices: AggregatedPrices::default(),
+            last_mid: None,
+            fired: Vec::new(),
+        }
+    }
+
+    pub fn with_sink(mut self, sink: Box<dyn AlertSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    pub fn on_update(&mut self, update: &QuoteUpdate) {
+        self.prices.set(update.source, update.quote);
+        let Some(mid) = self.prices.median_mid() else {
+            return;
+        };
+        let last_mid = self.last_mid.replace(mid);
+        let timestamp = update.quote.timestamp;
+
+        for state in &mut self.rules {
+            let observed = match state.rule.condition {
+                AlertCondition::MidCross { level } => match last_mid {
+                    Some(prev) if (prev < level) != (mid < level) => {
+                        let direction = if mid >= level { "above" } else { "below" };
+                        Some(format!("median mid ${:.2} moved {} ${:.2} (was ${:.2})", mid, direction, level, prev))
+                    }
+                    _ => None,
+                },
+                AlertCondition::SpreadAbove { bps } => spread_bps(&self.prices)
+                    .filter(|&spread| spread > bps)
+                    .map(|spread| format!("median spread {:.1} bps above {:.1} bps", spread, bps)),
+                AlertCondition::DispersionAbove { bps } => dispersion_bps(&self.prices)
+                    .filter(|&dispersion| dispersion > bps)
+                    .map(|dispersion| format!("source mids {:.1} bps apart, above {:.1} bps", dispersion, bps)),
+            };
+
+            let was_active = std::mem::replace(&mut state.active, observed.is_some());
+            // Crosses are events, the others fire once per excursion
+            let fires = match state.rule.condition {
+                AlertCondition::MidCross { .. } => observed.is_some(),
+                _ => observed.is_some() && !was_active,
+            };
+            if let (true, Some(message)) = (fires, observed) {
+                let alert = Alert {
+                    rule: state.rule.label(),
+                    message,
+                    timestamp,
+                };
+                for sink in &self.sinks {
+                    sink.dispatch(&alert);
+                }
+                self.fired.push(alert);
+            }
+        }
+    }
+
+    /// Every alert fired so far
+    pub fn fired(&self) -> &[Alert] {
+        &self.fired
+    }
+
+    pub fn report(&self) -> String {
+        let mut out = format!("Alerts: {} fired across {} rules\n", self.fired.len(), self.rules.len());
+        for state in &self.rules {
+            let label = state.rule.label();
+            let count = self.fired.iter().filter(|a| a.rule == label).count();
+            out.push_str(&format!("  {:<32} {:>5}\n", label, count));
+        }
+        out
+    }
+}
+
+fn spread_bps(prices: &AggregatedPrices) -> Option<f64> {
+    let median = prices.median_quote()?;
+    let mid = prices.median_mid()?;
+    Some((median.ask - median.bid) / mid * 10000.0)
+}
+
+fn dispersion_bps(prices: &AggregatedPrices) -> Option<f64> {
+    let mids: Vec<f64> = prices.quotes().map(|q| (q.bid + q.ask) / 2.0).collect();
+    if mids.len() < 2 {
+        return None;
+    }
+    let high = mids.iter().copied().fold(f64::MIN, f64::max);
+    let low = mids.iter().copied().fold(f64::MAX, f64::min);
+    Some((high - low) / prices.median_mid()? * 10000.0)
+}
+
+/// Evaluate `engine` against every update on `updates` in the background
+pub fn spawn_alert_engine(
+    mut updates: broadcast::Receiver<QuoteUpdate>,
+    engine: AlertEngine,
+) -> Arc<Mutex<AlertEngine>> {
+    let engine = Arc::new(Mutex::new(engine));
+    let handle = Arc::clone(&engine);
+
+    tokio::spawn(async move {
+        loop {
+            match updates.recv().await {
+                Ok(update) => handle.lock().unwrap().on_update(&update),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    engine
+}
@@ -0,0 +1,173 @@
+//! Arithmetic for USD amounts in the accounting: trade PnL and fees, cash, and the session totals.
+//! Amounts are read and reported as `f64` in both builds. With the `decimal` feature each sum and
+//! product here is worked out exactly in `rust_decimal::Decimal` from the shortest decimal form of
+//! its inputs and rounded to `f64` once, so $0.10 + $0.20 comes out as 0.3 rather than
+//! 0.30000000000000004. Every running total is kept beside its `f64` as an `Exact`, which holds
+//! the sum in `Decimal` so a total over many trades is the exact sum of their amounts, rounded
+//! once when read. Prices, sizes and probabilities stay `f64` either way.
+//!
+//! Non-finite amounts are never read as zero. An amount `Decimal` can't hold (NaN, infinite, or
+//! beyond its range, or a result that overflows it) makes that operation fall back to `f64`, so it carries through as it would
+//! without the feature. Use `finite` to reject such an amount where it enters the accounting.
+
+use anyhow::{bail, Result};
+#[cfg(feature = "decimal")]
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
+
+/// Whether the accounting runs in decimal (built with the `decimal` feature)
+pub const DECIMAL: bool = cfg!(feature = "decimal");
+
+/// `value`, or an error naming `what` when it's NaN or infinite
+pub fn finite(what: &str, value: f64) -> Result<f64> {
+    if !value.is_finite() {
+        bail!("{} is {}", what, value);
+    }
+    Ok(value)
+}
+
+/// `a + b`
+pub fn add(a: f64, b: f64) -> f64 {
+    sum([a, b])
+}
+
+/// Sum of `amounts`
+#[cfg(feature = "decimal")]
+pub fn sum(amounts: impl IntoIterator<Item = f64>) -> f64 {
+    let amounts: Vec<f64> = amounts.into_iter().collect();
+    match amounts.iter().try_fold(Decimal::ZERO, |total, &a| total.checked_add(decimal(a)?)) {
+        Some(total) => float(total),
+        None => amounts.into_iter().sum(),
+    }
+}
+
+/// Sum of `amounts`
+#[cfg(not(feature = "decimal"))]
+pub fn sum(amounts: impl IntoIterator<Item = f64>) -> f64 {
+    amounts.into_iter().sum()
+}
+
+/// The exact value of an `f64` running total. `add` keeps both in step; when the `f64` was set
+/// some other way (deserialized, or assigned directly), the sum restarts from its decimal form.
+/// Without the `decimal` feature it holds nothing and `add` is a plain `+=`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Exact {
+    #[cfg(feature = "decimal")]
+    value: Option<Decimal>,
+}
+
+impl Exact {
+    /// Add `amount` to `total`, the `f64` this tracks
+    #[cfg(feature = "decimal")]
+    pub fn add(&mut self, total: &mut f64, amount: f64) {
+        self.add_decimal(total, decimal(amount), amount);
+    }
+
+    /// Add `amount` to `total`, the `f64` this tracks
+    #[cfg(not(feature = "decimal"))]
+    pub fn add(&mut self, total: &mut f64, amount: f64) {
+        *total += amount;
+    }
+
+    /// Add another running total, `other` tracking `other_total`, to `total`
+    #[cfg(feature = "decimal")]
+    pub fn merge(&mut self, total: &mut f64, other: Exact, other_total: f64) {
+        self.add_decimal(total, other.synced(other_total), other_total);
+    }
+
+    /// Add another running total, `other` tracking `other_total`, to `total`
+    #[cfg(not(feature = "decimal"))]
+    pub fn merge(&mut self, total: &mut f64, _other: Exact, other_total: f64) {
+        *total += other_total;
+    }
+
+    /// The exact sum behind `total`, or its decimal form when they've drifted apart
+    #[cfg(feature = "decimal")]
+    fn synced(&self, total: f64) -> Option<Decimal> {
+        match self.value {
+            Some(value) if float(value) == total => Some(value),
+            _ => decimal(total),
+        }
+    }
+
+    #[cfg(feature = "decimal")]
+    fn add_decimal(&mut self, total: &mut f64, amount: Option<Decimal>, fallback: f64) {
+        self.value = self.synced(*total).zip(amount).and_then(|(total, amount)| total.checked_add(amount));
+        *total = match self.value {
+            Some(value) => float(value),
+            None => *total + fallback,
+        };
+    }
+}
+
+/// `(a - b) * qty`, the value of a price difference over a size
+#[cfg(feature = "decimal")]
+pub fn value_of(a: f64, b: f64, qty: f64) -> f64 {
+    match (decimal(a), decimal(b), decimal(qty)) {
+        (Some(a), Some(b), Some(qty)) => a.checked_sub(b).and_then(|d| d.checked_mul(qty)).map(float),
+        _ => None,
+    }
+    .unwrap_or((a - b) * qty)
+}
+
+/// `(a - b) * qty`, the value of a price difference over a size
+#[cfg(not(feature = "decimal"))]
+pub fn value_of(a: f64, b: f64, qty: f64) -> f64 {
+    (a - b) * qty
+}
+
+/// Shortest decimal that reads back as `value`, or `None` when `Decimal` can't hold it
+#[cfg(feature = "decimal")]
+fn decimal(value: f64) -> Option<Decimal> {
+    Decimal::from_f64(value)
+}
+
+/// Every `Decimal` is within `f64` range
+#[cfg(feature = "decimal")]
+fn float(value: Decimal) -> f64 {
+    value.to_f64().expect("Decimal is within f64 range")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn running_totals_add_up() {
+        let (mut total, mut exact) = (0.0, Exact::default());
+        for _ in 0..10 {
+            exact.add(&mut total, 0.1);
+        }
+        if DECIMAL {
+            assert_eq!(total, 1.0);
+        } else {
+            assert!((total - 1.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn totals_set_elsewhere_carry_on_from_their_value() {
+        let (mut total, mut exact) = (0.0, Exact::default());
+        exact.add(&mut total, 0.2);
+        total = 5.0;
+        exact.add(&mut total, 0.1);
+        assert!((total - 5.1).abs() < 1e-12);
+
+        let (mut merged, mut merged_exact) = (0.7, Exact::default());
+        merged_exact.merge(&mut merged, exact, total);
+        assert!((merged - 5.8).abs() < 1e-12);
+        if DECIMAL {
+            assert_eq!((total, merged), (5.1, 5.8));
+        }
+    }
+
+    #[test]
+    fn unrepresentable_amounts_carry_through() {
+        let (mut total, mut exact) = (1.0, Exact::default());
+        exact.add(&mut total, f64::NAN);
+        assert!(total.is_nan());
+        assert!(finite("pnl", total).is_err());
+        assert_eq!(value_of(3.0, 1.0, 0.5), 1.0);
+    }
+}
@@ -0,0 +1,282 @@
+use crate::aggregator::{AggregatedPrices, QuoteUpdate, SourceQuote};
+use crate::candles::{Candle, CandleBuilder, CandleInterval, CandleSeries};
+use crate::fanout::{ClientStats, FanOut, FlowControl};
+use crate::introspect::StrategyInfo;
+use crate::pnl_distribution::DEFAULT_PNL_BINS;
+use crate::portfolio::Simulator;
+use crate::position::Position;
+use crate::quorum::TradingMode;
+use anyhow::{Context, Result};
+use hyper::server::conn::AddrStream;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Request, Response, Server, StatusCode};
+use serde::Serialize;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, RwLock};
+use tracing::error;
+
+/// Trades `/trades` returns without a `limit`
+pub const DEFAULT_TRADE_LIMIT: usize = 100;
+
+/// Bars `/candles` returns without a `limit`
+pub const DEFAULT_CANDLE_LIMIT: usize = 60;
+
+/// Most histogram bins `/pnl_distribution` will build
+pub const MAX_PNL_BINS: usize = 1000;
+
+/// Position with its mark at the live median mid
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionView {
+    #[serde(flatten)]
+    pub position: Position,
+    pub mid: Option<f64>,
+    pub unrealized_pnl: Option<f64>,
+}
+
+/// Quorum trading mode at the live prices, with every source's quote age behind it
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusView {
+    #[serde(flatten)]
+    pub mode: TradingMode,
+    pub min_sources: usize,
+    pub max_quote_age_ms: i64,
+    pub quotes: Vec<SourceQuote>,
+}
+
+#[derive(Clone)]
+struct ApiState {
+    /// Latest quote of every source, kept current from the feed's update stream
+    prices: Arc<RwLock<AggregatedPrices>>,
+    /// The same updates, streamed to `/stream` clients
+    quotes: Arc<FanOut<QuoteUpdate>>,
+    /// Bars built from the same updates, for `/candles`
+    candles: Arc<Mutex<CandleBuilder>>,
+    simulator: Simulator,
+}
+
+/// Read-only HTTP API over a running session, for tooling that would otherwise scrape the console:
+/// `GET /prices`, `/stats`, `/trades?limit=N`, `/position`, `/status`, `/strategy` and
+/// `/candles?series=mid|<venue>&interval=1s|1m&limit=N` as JSON, plus `/stream`, every quote update
+/// as newline-delimited JSON, and `/clients`, the stream clients' delivery counts
+pub struct ApiServer {
+    addr: SocketAddr,
+}
+
+impl ApiServer {
+    /// Bind `listen` and serve in the background until the process exits. `/prices` starts from
+    /// `prices`, the feed's book at startup, and follows `updates` quote by quote rather than
+    /// waiting for the next cycle. `/stream` clients each get their own queue under `flow`.
+    pub fn spawn(listen: &str, updates: broadcast::Receiver<QuoteUpdate>, prices: AggregatedPrices, simulator: Simulator, flow: FlowControl) -> Result<Self> {
+        let addr: SocketAddr = listen.parse().with_context(|| format!("invalid API listen address '{}'", listen))?;
+        let state = ApiState {
+            prices: Arc::new(RwLock::new(prices)),
+            quotes: Arc::new(FanOut::new(flow)),
+            candles: Arc::new(Mutex::new(CandleBuilder::new())),
+            simulator,
+        };
+        spawn_price_book(updates, state.clone());
+
+        let server = Server::try_bind(&addr).with_context(|| format!("failed to bind API on {}", addr))?;
+        let make_service = make_service_fn(move |conn: &AddrStream| {
+            let state = state.clone();
+            let peer = conn.remote_addr();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let state = state.clone();
+                    async move { Ok::<_, Infallible>(route(&state, peer, request).await) }
+                }))
+            }
+        });
+        let server = server.serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(async move {
+            if let Err(e) = server.await {
+                error!(error = %e, "API server stopped");
+            }
+        });
+        Ok(Self { addr })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+fn spawn_price_book(mut updates: broadcast::Receiver<QuoteUpdate>, state: ApiState) {
+    tokio::spawn(async move {
+        loop {
+            match updates.recv().await {
+                Ok(update) => {
+                    state.prices.write().await.set(update.source, update.quote);
+                    state.candles.lock().unwrap().on_update(&update);
+                    state.quotes.publish(update);
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+async fn route(state: &ApiState, peer: SocketAddr, request: Request<Body>) -> Response<Body> {
+    if request.method() != Method::GET {
+        return error(StatusCode::METHOD_NOT_ALLOWED, "only GET is supported");
+    }
+    let tracker = state.simulator.tracker();
+    match request.uri().path() {
+        "/prices" => json(&*state.prices.read().await),
+        "/stats" => json(&tracker.get_stats().await),
+        "/trades" => match trade_limit(request.uri().query()) {
+            Ok(limit) => json(&tracker.get_recent_trades(limit).await),
+            Err(message) => error(StatusCode::BAD_REQUEST, &message),
+        },
+        "/pnl_distribution" => match pnl_bins(request.uri().query()) {
+            Ok(bins) => json(&tracker.pnl_distribution(bins).await),
+            Err(message) => error(StatusCode::BAD_REQUEST, &message),
+        },
+        "/position" => {
+            let position = state.simulator.engine().position();
+            let mid = state.prices.read().await.median_mid();
+            json(&PositionView {
+                position,
+                mid,
+                unrealized_pnl: mid.map(|mid| position.unrealized_pnl(mid)),
+            })
+        }
+        "/status" => {
+            let engine = state.simulator.engine();
+            let snapshot = engine.snapshot(&*state.prices.read().await);
+            let quorum = engine.quorum();
+            json(&StatusView {
+                mode: quorum.mode(&snapshot),
+                min_sources: quorum.min_sources,
+                max_quote_age_ms: quorum.max_quote_age_ms,
+                quotes: snapshot.sources().collect(),
+            })
+        }
+        "/strategy" => json(&StrategyInfo::of(state.simulator.engine())),
+        "/candles" => match CandleQuery::parse(request.uri().query()) {
+            Ok(query) => {
+                let candles = state.candles.lock().unwrap().recent(query.series, query.interval, query.limit);
+                json(&CandlesView { series: query.series, interval: query.interval.to_string(), candles })
+            }
+            Err(message) => error(StatusCode::BAD_REQUEST, &message),
+        },
+        "/stream" => stream(&state.quotes, peer),
+        "/clients" => json(&ClientsView {
+            flow_control: state.quotes.flow_control(),
+            disconnects: state.quotes.disconnects(),
+            clients: state.quotes.clients(),
+        }),
+        path => error(StatusCode::NOT_FOUND, &format!("no route for {}", path)),
+    }
+}
+
+#[derive(Serialize)]
+struct CandlesView {
+    series: CandleSeries,
+    interval: String,
+    candles: Vec<Candle>,
+}
+
+/// `/candles` query: the aggregate mid's 1m bars unless `series` and `interval` say otherwise
+struct CandleQuery {
+    series: CandleSeries,
+    interval: CandleInterval,
+    limit: usize,
+}
+
+impl CandleQuery {
+    fn parse(query: Option<&str>) -> std::result::Result<Self, String> {
+        let mut parsed = CandleQuery {
+            series: CandleSeries::Mid,
+            interval: CandleInterval::Minute,
+            limit: DEFAULT_CANDLE_LIMIT,
+        };
+        for pair in query.into_iter().flat_map(|q| q.split('&')) {
+            match pair.split_once('=') {
+                Some(("series", value)) => parsed.series = value.parse().map_err(|e| format!("{}", e))?,
+                Some(("interval", value)) => parsed.interval = value.parse().map_err(|e| format!("{}", e))?,
+                Some(("limit", value)) => {
+                    parsed.limit = value.parse().map_err(|_| format!("limit must be a non-negative integer, got '{}'", value))?
+                }
+                _ => {}
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// Stream clients and what flow control did to them
+#[derive(Serialize)]
+struct ClientsView {
+    flow_control: FlowControl,
+    disconnects: u64,
+    clients: Vec<ClientStats>,
+}
+
+/// Chunked response of one JSON quote update per line. A client that reads slower than quotes
+/// arrive only fills its own queue, and loses events or its connection as the flow control says.
+fn stream(quotes: &FanOut<QuoteUpdate>, peer: SocketAddr) -> Response<Body> {
+    let subscription = quotes.subscribe(peer.to_string());
+    let (mut sender, body) = Body::channel();
+    tokio::spawn(async move {
+        while let Some(update) = subscription.recv().await {
+            let Ok(mut line) = serde_json::to_vec(&update) else {
+                continue;
+            };
+            line.push(b'\n');
+            tokio::select! {
+                sent = sender.send_data(line.into()) => if sent.is_err() {
+                    return;
+                },
+                // The client stopped reading long enough to be cut off mid-send
+                _ = subscription.disconnected() => break,
+            }
+        }
+        sender.abort();
+    });
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/x-ndjson")
+        .body(body)
+        .expect("valid response")
+}
+
+/// `limit` from the query string, the most recent trades to return
+fn trade_limit(query: Option<&str>) -> std::result::Result<usize, String> {
+    let Some(value) = query.into_iter().flat_map(|q| q.split('&')).find_map(|pair| pair.strip_prefix("limit=")) else {
+        return Ok(DEFAULT_TRADE_LIMIT);
+    };
+    value.parse().map_err(|_| format!("limit must be a non-negative integer, got '{}'", value))
+}
+
+fn pnl_bins(query: Option<&str>) -> std::result::Result<usize, String> {
+    let Some(value) = query.into_iter().flat_map(|q| q.split('&')).find_map(|pair| pair.strip_prefix("bins=")) else {
+        return Ok(DEFAULT_PNL_BINS);
+    };
+    match value.parse() {
+        Ok(bins) if bins > 0 && bins <= MAX_PNL_BINS => Ok(bins),
+        _ => Err(format!("bins must be an integer from 1 to {}, got '{}'", MAX_PNL_BINS, value)),
+    }
+}
+
+fn json<T: Serialize>(value: &T) -> Response<Body> {
+    match serde_json::to_vec(value) {
+        Ok(body) => Response::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .expect("valid response"),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    }
+}
+
+fn error(status: StatusCode, message: &str) -> Response<Body> {
+    let body = serde_json::json!({ "error": message }).to_string();
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .expect("valid response")
+}
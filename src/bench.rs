@@ -0,0 +1,192 @@
+use crate::aggregator::PriceAggregator;
+use crate::clock::SimulatedClock;
+use crate::pnl_tracker::PnLTracker;
+use crate::recorder::Tick;
+use crate::trader::{TradeSide, TradingEngine};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// Latency samples for one pipeline stage
+#[derive(Debug, Clone, Default)]
+pub struct LatencyStats {
+    samples: Vec<Duration>,
+}
+
+impl LatencyStats {
+    pub fn record(&mut self, latency: Duration) {
+        self.samples.push(latency);
+    }
+
+    pub fn count(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Nearest-rank percentile, `p` in 0..=100
+    pub fn percentile(&self, p: f64) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+        let mut sorted = self.samples.clone();
+        sorted.sort_unstable();
+        let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+        sorted[rank.clamp(1, sorted.len()) - 1]
+    }
+
+    pub fn mean(&self) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+        self.samples.iter().sum::<Duration>() / self.samples.len() as u32
+    }
+}
+
+/// Stages timed for every message, in pipeline order
+pub const STAGES: [&str; 6] = ["ingest", "queue", "snapshot", "strategy", "tracker", "end-to-end"];
+
+/// Throughput and per-stage latency of one benchmark run
+#[derive(Debug, Clone)]
+pub struct FeedBenchReport {
+    pub messages: usize,
+    /// Messages the strategy stage saw; fewer than `messages` if the channel lagged
+    pub processed: usize,
+    pub lagged: u64,
+    pub elapsed: Duration,
+    pub trades: usize,
+    pub stages: Vec<(&'static str, LatencyStats)>,
+}
+
+impl FeedBenchReport {
+    pub fn messages_per_sec(&self) -> f64 {
+        self.messages as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+fn micros(d: Duration) -> String {
+    format!("{:.1}µs", d.as_secs_f64() * 1e6)
+}
+
+impl fmt::Display for FeedBenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Feed benchmark: {} messages in {:.3}s → {:.0} msg/s ({} processed, {} lagged, {} trades)",
+            self.messages, self.elapsed.as_secs_f64(), self.messages_per_sec(), self.processed, self.lagged, self.trades)?;
+        writeln!(f, "  {:<12} {:>10} {:>10} {:>10} {:>10} {:>10}", "STAGE", "P50", "P90", "P99", "MAX", "MEAN")?;
+        for (name, stats) in &self.stages {
+            writeln!(f, "  {:<12} {:>10} {:>10} {:>10} {:>10} {:>10}",
+                name,
+                micros(stats.percentile(50.0)),
+                micros(stats.percentile(90.0)),
+                micros(stats.percentile(99.0)),
+                micros(stats.percentile(100.0)),
+                micros(stats.mean()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Push `ticks` through aggregator → strategy → tracker as fast as possible, timing every stage.
+/// A producer task ingests ticks into the aggregator while this task consumes its update stream,
+/// snapshots prices, runs a buy and a sell decision every `strategy_every` messages and records the outcome.
+/// The engine's clock follows the recorded feed time, so quote ages are the recording's.
+pub async fn run_feed_bench(
+    ticks: Vec<Tick>,
+    engine: TradingEngine,
+    tracker: &PnLTracker,
+    strategy_every: usize,
+) -> FeedBenchReport {
+    let clock = Arc::new(SimulatedClock::default());
+    let engine = engine.with_clock(clock.clone());
+    let aggregator = Arc::new(PriceAggregator::new());
+    let mut updates = aggregator.subscribe();
+    let messages = ticks.len();
+    // Ingest start times by message index; the stream is FIFO from a single producer
+    let sent_at = Arc::new(Mutex::new(Vec::with_capacity(messages)));
+    let mut ingest = LatencyStats::default();
+
+    let started = Instant::now();
+    let producer = {
+        let aggregator = Arc::clone(&aggregator);
+        let sent_at = Arc::clone(&sent_at);
+        tokio::spawn(async move {
+            let mut ingest = LatencyStats::default();
+            for tick in ticks {
+                let start = Instant::now();
+                sent_at.lock().unwrap().push(start);
+                aggregator.ingest(tick.source, tick.quote()).await;
+                ingest.record(start.elapsed());
+                // Let the consumer run, as live sources would between updates
+                tokio::task::yield_now().await;
+            }
+            ingest
+        })
+    };
+
+    let mut stages: Vec<LatencyStats> = vec![LatencyStats::default(); STAGES.len() - 1];
+    let mut index = 0usize;
+    let mut processed = 0;
+    let mut lagged = 0;
+    let mut trades = 0;
+    while index < messages {
+        match updates.recv().await {
+            Ok(_) => {}
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                lagged += skipped;
+                index += skipped as usize;
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+        let Some(sent) = sent_at.lock().unwrap().get(index).copied() else {
+            break;
+        };
+        index += 1;
+        processed += 1;
+        stages[0].record(sent.elapsed());
+
+        let start = Instant::now();
+        let prices = aggregator.get_prices().await;
+        if let Some(now) = prices.quotes().map(|q| q.timestamp).max() {
+            clock.set(now);
+        }
+        stages[1].record(start.elapsed());
+
+        let start = Instant::now();
+        let attempts = if processed % strategy_every.max(1) == 0 {
+            [TradeSide::Buy, TradeSide::Sell].map(|side| engine.attempt(&prices, side))
+        } else {
+            [None, None]
+        };
+        stages[2].record(start.elapsed());
+
+        let start = Instant::now();
+        for attempt in attempts.into_iter().flatten() {
+            tracker.record_attempt(&attempt).await;
+            if let Some(trade) = attempt.trade {
+                tracker.record_trade(trade).await;
+                trades += 1;
+            }
+        }
+        for trade in engine.confirm_fills(&prices) {
+            tracker.record_trade(trade).await;
+            trades += 1;
+        }
+        stages[3].record(start.elapsed());
+        stages[4].record(sent.elapsed());
+    }
+    let elapsed = started.elapsed();
+    if let Ok(stats) = producer.await {
+        ingest = stats;
+    }
+
+    let mut all = vec![ingest];
+    all.extend(stages);
+    FeedBenchReport {
+        messages,
+        processed,
+        lagged,
+        elapsed,
+        trades,
+        stages: STAGES.into_iter().zip(all).collect(),
+    }
+}
@@ -0,0 +1,406 @@
+use crate::aggregator::{AggregatedPrices, PriceFeed, Quote, QuoteUpdate, Source, QUOTE_CHANNEL_CAPACITY};
+use crate::clock::{Clock, SimulatedClock};
+use crate::execution::{ExecutionModel, FillContext};
+use crate::reconcile::VenueAccount;
+use crate::replay::ReplaySpeed;
+use crate::synthetic::{SyntheticConfig, SyntheticSource};
+use crate::trader::{Trade, TradeSide};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Resting sizes below this count as an empty level
+const MIN_LEVEL_USD: f64 = 1.0;
+
+/// Shape of each simulated venue's book and the taker flow that trades against it
+#[derive(Debug, Clone)]
+pub struct BookSimConfig {
+    /// Fair value path every venue's book is centred on; `spread_bps` is the touch spread
+    pub fair: SyntheticConfig,
+    pub venues: Vec<Source>,
+    /// Price levels per side
+    pub levels: usize,
+    pub level_spacing_bps: f64,
+    /// Resting USD at each level when full
+    pub depth_per_level_usd: f64,
+    /// Seconds for an emptied level to fill back up
+    pub refill_secs: f64,
+    /// Market orders per second arriving at each venue, split evenly between buys and sells
+    pub taker_orders_per_sec: f64,
+    /// Mean USD size of a market order (exponentially distributed)
+    pub mean_taker_usd: f64,
+    /// Largest offset of a venue's book from fair value, in bps
+    pub basis_bps: f64,
+    /// Simulated session length
+    pub duration: Duration,
+    /// Window fill probabilities are measured over, normally the trade interval
+    pub horizon: Duration,
+}
+
+impl Default for BookSimConfig {
+    fn default() -> Self {
+        Self {
+            fair: SyntheticConfig::default(),
+            venues: Source::ALL.to_vec(),
+            levels: 10,
+            level_spacing_bps: 1.0,
+            depth_per_level_usd: 50_000.0,
+            refill_secs: 5.0,
+            taker_orders_per_sec: 2.0,
+            mean_taker_usd: 50_000.0,
+            basis_bps: 1.0,
+            duration: Duration::from_secs(300),
+            horizon: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Remaining USD at each level, best first
+#[derive(Debug, Clone)]
+struct BookSide {
+    sizes: Vec<f64>,
+}
+
+impl BookSide {
+    fn full(levels: usize, depth: f64) -> Self {
+        Self { sizes: vec![depth; levels.max(1)] }
+    }
+
+    fn best_level(&self) -> usize {
+        self.sizes.iter().position(|&s| s >= MIN_LEVEL_USD).unwrap_or(self.sizes.len() - 1)
+    }
+
+    /// Take `usd` of liquidity from the best level outward; returns levels swept
+    fn sweep(&mut self, mut usd: f64) -> usize {
+        let mut swept = 0;
+        for size in &mut self.sizes {
+            if usd <= 0.0 {
+                break;
+            }
+            let taken = usd.min(*size);
+            *size -= taken;
+            usd -= taken;
+            if *size < MIN_LEVEL_USD {
+                swept += 1;
+            }
+        }
+        swept
+    }
+
+    fn refill(&mut self, amount: f64, depth: f64) {
+        for size in &mut self.sizes {
+            *size = (*size + amount).min(depth);
+        }
+    }
+}
+
+/// One venue's book, priced off fair value plus its own basis
+#[derive(Debug, Clone)]
+struct VenueBook {
+    source: Source,
+    basis_bps: f64,
+    bids: BookSide,
+    asks: BookSide,
+}
+
+impl VenueBook {
+    fn center(&self, fair: f64) -> f64 {
+        fair * (1.0 + self.basis_bps / 10000.0)
+    }
+
+    fn level_price(&self, config: &BookSimConfig, fair: f64, side: TradeSide, level: usize) -> f64 {
+        let offset_bps = config.fair.spread_bps / 2.0 + level as f64 * config.level_spacing_bps;
+        match side {
+            TradeSide::Buy => self.center(fair) * (1.0 - offset_bps / 10000.0),
+            TradeSide::Sell => self.center(fair) * (1.0 + offset_bps / 10000.0),
+        }
+    }
+
+    fn quote(&self, config: &BookSimConfig, fair: f64, timestamp: i64) -> Quote {
+        Quote {
+            bid: self.level_price(config, fair, TradeSide::Buy, self.bids.best_level()),
+            ask: self.level_price(config, fair, TradeSide::Sell, self.asks.best_level()),
+            timestamp,
+        }
+    }
+
+    /// Resting USD a passive order at `price` would queue behind: everything at or better than it
+    fn queue_ahead(&self, config: &BookSimConfig, fair: f64, side: TradeSide, price: f64) -> f64 {
+        let book = match side {
+            TradeSide::Buy => &self.bids,
+            TradeSide::Sell => &self.asks,
+        };
+        book.sizes
+            .iter()
+            .enumerate()
+            .filter(|&(level, _)| {
+                let level_price = self.level_price(config, fair, side, level);
+                match side {
+                    TradeSide::Buy => level_price >= price,
+                    TradeSide::Sell => level_price <= price,
+                }
+            })
+            .map(|(_, &size)| size)
+            .sum()
+    }
+}
+
+/// Counts of simulated market activity
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BookSimStats {
+    pub steps: u64,
+    pub taker_orders: u64,
+    pub taker_volume_usd: f64,
+    /// Market orders that emptied at least one level
+    pub sweeps: u64,
+}
+
+struct BookState {
+    clock: i64,
+    fair: SyntheticSource,
+    fair_price: f64,
+    books: Vec<VenueBook>,
+    rng: StdRng,
+    prices: AggregatedPrices,
+    stats: BookSimStats,
+    account: VenueAccount,
+}
+
+/// Self-contained offline venue: one limit order book per source slot, centred on a GBM fair value
+/// and traded against by random market orders. It is both the price feed the trading loop reads
+/// and the execution model that prices passive fills off the simulated queue and taker flow.
+/// Everything is driven by simulated time and the fair value seed, so runs are reproducible.
+#[derive(Clone)]
+pub struct OrderBookVenue {
+    config: Arc<BookSimConfig>,
+    speed: ReplaySpeed,
+    start_time: i64,
+    /// Simulated time, mirrored from the book state for readers outside the lock
+    clock: Arc<SimulatedClock>,
+    state: Arc<Mutex<BookState>>,
+    events: broadcast::Sender<QuoteUpdate>,
+}
+
+impl OrderBookVenue {
+    /// Simulated time starts at `clock`'s now and only moves with `advance` from there
+    pub fn new(config: BookSimConfig, speed: ReplaySpeed, clock: &dyn Clock) -> Self {
+        let start_time = clock.now_ms();
+        // Flow gets its own stream so changing book parameters doesn't change the fair value path
+        let mut rng = StdRng::seed_from_u64(config.fair.seed.wrapping_add(1));
+        let books = config
+            .venues
+            .iter()
+            .map(|&source| VenueBook {
+                source,
+                basis_bps: (rng.random::<f64>() - 0.5) * 2.0 * config.basis_bps,
+                bids: BookSide::full(config.levels, config.depth_per_level_usd),
+                asks: BookSide::full(config.levels, config.depth_per_level_usd),
+            })
+            .collect();
+        let mut state = BookState {
+            clock: start_time,
+            fair: SyntheticSource::new(config.fair),
+            fair_price: config.fair.initial_price,
+            books,
+            rng,
+            prices: AggregatedPrices::default(),
+            stats: BookSimStats::default(),
+            account: VenueAccount::default(),
+        };
+        for book in &state.books {
+            state.prices.set(book.source, book.quote(&config, state.fair_price, start_time));
+        }
+
+        Self {
+            config: Arc::new(config),
+            speed,
+            start_time,
+            clock: Arc::new(SimulatedClock::new(start_time)),
+            state: Arc::new(Mutex::new(state)),
+            events: broadcast::channel(QUOTE_CHANNEL_CAPACITY).0,
+        }
+    }
+
+    pub fn config(&self) -> &BookSimConfig {
+        &self.config
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.config.duration
+    }
+
+    /// The venue's simulated time as a clock, for stamping trades and orders on the same timeline
+    pub fn sim_clock(&self) -> Arc<SimulatedClock> {
+        Arc::clone(&self.clock)
+    }
+
+    /// Simulated time elapsed since the start
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_millis((self.state.lock().unwrap().clock - self.start_time).max(0) as u64)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed() >= self.config.duration
+    }
+
+    pub fn stats(&self) -> BookSimStats {
+        self.state.lock().unwrap().stats
+    }
+
+    /// Move simulated time forward by `step`, pacing like a replay, in increments of the fair value
+    /// tick interval. Each increment moves fair value, refills books, runs taker flow and publishes
+    /// every venue's new touch. Returns false once the session is over.
+    pub async fn advance(&self, step: Duration) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+
+        match self.speed {
+            ReplaySpeed::RealTime => tokio::time::sleep(step).await,
+            ReplaySpeed::Multiplier(factor) => tokio::time::sleep(step.div_f64(factor)).await,
+            ReplaySpeed::AsFastAsPossible => tokio::task::yield_now().await,
+        }
+
+        let tick = self.config.fair.tick_interval.max(Duration::from_millis(1));
+        let end = self.state.lock().unwrap().clock + step.as_millis() as i64;
+        loop {
+            let mut state = self.state.lock().unwrap();
+            let next = state.clock + tick.as_millis() as i64;
+            if next > end {
+                state.clock = end;
+                self.clock.set(end);
+                break;
+            }
+            state.clock = next;
+            self.clock.set(next);
+            for update in self.step(&mut state, tick.as_secs_f64()) {
+                let _ = self.events.send(update);
+            }
+        }
+        true
+    }
+
+    fn step(&self, state: &mut BookState, dt: f64) -> Vec<QuoteUpdate> {
+        let config = &self.config;
+        let BookState { clock, fair, fair_price, books, rng, prices, stats, .. } = state;
+        let quote = fair.next_quote(*clock);
+        *fair_price = (quote.bid + quote.ask) / 2.0;
+        stats.steps += 1;
+
+        let refill = config.depth_per_level_usd * dt / config.refill_secs.max(f64::EPSILON);
+        let mut updates = Vec::with_capacity(books.len());
+        for book in books.iter_mut() {
+            book.bids.refill(refill, config.depth_per_level_usd);
+            book.asks.refill(refill, config.depth_per_level_usd);
+
+            for _ in 0..poisson(rng, config.taker_orders_per_sec * dt) {
+                let usd = -config.mean_taker_usd * rng.random_range(f64::EPSILON..1.0).ln();
+                // Market buys lift asks, market sells hit bids
+                let swept = if rng.random_bool(0.5) { book.asks.sweep(usd) } else { book.bids.sweep(usd) };
+                stats.taker_orders += 1;
+                stats.taker_volume_usd += usd;
+                if swept > 0 {
+                    stats.sweeps += 1;
+                }
+            }
+
+            let quote = book.quote(config, *fair_price, *clock);
+            prices.set(book.source, quote);
+            updates.push(QuoteUpdate {
+                source: book.source,
+                quote,
+            });
+        }
+        updates
+    }
+}
+
+/// Knuth's method; fine for the small means of per-tick order arrivals
+fn poisson(rng: &mut StdRng, mean: f64) -> u32 {
+    let limit = (-mean).exp();
+    let mut product: f64 = rng.random();
+    let mut count = 0;
+    while product > limit {
+        product *= rng.random::<f64>();
+        count += 1;
+    }
+    count
+}
+
+impl PriceFeed for OrderBookVenue {
+    async fn get_prices(&self) -> AggregatedPrices {
+        self.state.lock().unwrap().prices.clone()
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<QuoteUpdate> {
+        self.events.subscribe()
+    }
+}
+
+/// A passive order fills when a market order on the opposite side is big enough to clear the queue
+/// ahead of it plus its own size. With Poisson arrivals and exponential sizes that gives
+/// `1 - exp(-rate * horizon * exp(-(queue + notional) / mean_size))`, evaluated on the book of the
+/// venue whose touch the order competes with. Fills executed against it settle into the venue's own
+/// account, which reconciliation checks the local ledger against.
+impl ExecutionModel for OrderBookVenue {
+    fn name(&self) -> String {
+        "book".to_string()
+    }
+
+    fn describe(&self) -> String {
+        format!("BOOK SIM ({} venues, {:.1} takers/s)", self.config.venues.len(), self.config.taker_orders_per_sec)
+    }
+
+    fn probability(&self, ctx: &FillContext) -> f64 {
+        let state = self.state.lock().unwrap();
+        let Some(book) = state.books.iter().min_by(|a, b| {
+            let touch = |book: &VenueBook| {
+                let quote = book.quote(&self.config, state.fair_price, state.clock);
+                match ctx.side {
+                    TradeSide::Buy => (quote.bid - ctx.best_price).abs(),
+                    TradeSide::Sell => (quote.ask - ctx.best_price).abs(),
+                }
+            };
+            touch(a).total_cmp(&touch(b))
+        }) else {
+            return 0.0;
+        };
+
+        let queue = book.queue_ahead(&self.config, state.fair_price, ctx.side, ctx.our_price);
+        let rate = self.config.taker_orders_per_sec / 2.0;
+        let reach = (-(queue + ctx.notional) / self.config.mean_taker_usd.max(f64::EPSILON)).exp();
+        1.0 - (-rate * self.config.horizon.as_secs_f64() * reach).exp()
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        let config = &self.config;
+        let venues: Vec<&str> = config.venues.iter().map(|v| v.name()).collect();
+        serde_json::json!({
+            "venues": venues,
+            "levels": config.levels,
+            "level_spacing_bps": config.level_spacing_bps,
+            "depth_per_level_usd": config.depth_per_level_usd,
+            "refill_secs": config.refill_secs,
+            "taker_orders_per_sec": config.taker_orders_per_sec,
+            "mean_taker_usd": config.mean_taker_usd,
+            "basis_bps": config.basis_bps,
+            "horizon_secs": config.horizon.as_secs_f64(),
+            "seed": config.fair.seed,
+        })
+    }
+
+    fn on_fill(&self, trade: &Trade) {
+        self.state.lock().unwrap().account.apply(trade);
+    }
+
+    fn account(&self) -> Option<VenueAccount> {
+        Some(self.state.lock().unwrap().account)
+    }
+
+    /// Every market order counts towards the tape; our own passive fills don't
+    fn traded_volume_usd(&self) -> Option<f64> {
+        Some(self.state.lock().unwrap().stats.taker_volume_usd)
+    }
+}
@@ -0,0 +1,358 @@
+use crate::flow::{TapeEvent, TapeTrade};
+use crate::trader::{ExecutionAttempt, LiquidityRole, TradeSide};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Default width of the distance-from-touch buckets
+pub const DEFAULT_CALIBRATION_BUCKET_BPS: f64 = 1.0;
+
+/// Most tape trades a recording keeps; past it the oldest are dropped
+pub const MAX_TAPE_TRADES: usize = 1_000_000;
+
+/// The trade stream as recorded over a session: the latest `MAX_TAPE_TRADES` trades, and the
+/// outages between them, during which the tape can't say whether a quote was traded through
+#[derive(Debug, Clone, Default)]
+pub struct TapeRecording {
+    trades: VecDeque<TapeTrade>,
+    /// (dropped, first trade after) of every outage since the oldest retained trade
+    gaps: Vec<(i64, i64)>,
+    /// When the stream dropped, while no trade has arrived since
+    down_since: Option<i64>,
+}
+
+impl TapeRecording {
+    pub fn record(&mut self, event: TapeEvent) {
+        match event {
+            TapeEvent::Trade(trade) => {
+                if let Some(dropped) = self.down_since.take() {
+                    self.gaps.push((dropped, trade.timestamp));
+                }
+                if self.trades.len() == MAX_TAPE_TRADES {
+                    self.trades.pop_front();
+                    if let Some(oldest) = self.trades.front().map(|t| t.timestamp) {
+                        self.gaps.retain(|&(_, resumed)| resumed >= oldest);
+                    }
+                }
+                self.trades.push_back(trade);
+            }
+            TapeEvent::Dropped { timestamp } => {
+                self.down_since.get_or_insert(timestamp);
+            }
+        }
+    }
+
+    pub fn trades(&self) -> usize {
+        self.trades.len()
+    }
+
+    /// Outages between retained trades, as (dropped, first trade after)
+    pub fn gaps(&self) -> &[(i64, i64)] {
+        &self.gaps
+    }
+
+    /// Whether the stream was up from `from` through `until`, up to `observed_until`
+    fn covers(&self, from: i64, until: i64, observed_until: i64) -> bool {
+        let Some(first) = self.trades.front() else {
+            return false;
+        };
+        from >= first.timestamp
+            && until <= observed_until
+            && self.down_since.is_none_or(|dropped| until < dropped)
+            && !self.gaps.iter().any(|&(dropped, resumed)| dropped <= until && resumed >= from)
+    }
+}
+
+/// Maker quotes in one distance-from-touch bucket: what the model predicted, what the simulator
+/// filled, and how often the tape actually traded through the quote
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct CalibrationBucket {
+    /// Lower edge of the distance bucket, in bps behind the touch
+    pub distance_bps: f64,
+    pub quotes: u32,
+    pub mean_predicted: f64,
+    /// Fill rate the simulator's draws gave these quotes
+    pub simulated_rate: f64,
+    /// Quotes the tape traded through within the window
+    pub hits: u32,
+}
+
+impl CalibrationBucket {
+    pub fn hit_rate(&self) -> f64 {
+        if self.quotes > 0 {
+            self.hits as f64 / self.quotes as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Predicted minus empirical fill rate; positive when the model is too optimistic
+    pub fn error(&self) -> f64 {
+        self.mean_predicted - self.hit_rate()
+    }
+}
+
+/// The execution model's fill probabilities checked against real trade prints. A maker quote
+/// counts as hit when a tape trade printed through its price (below a bid, above an ask) within
+/// `window_ms` of quoting; a print at the price isn't enough, since the queue ahead may have
+/// taken it.
+#[derive(Debug, Clone, Serialize)]
+pub struct FillCalibration {
+    pub bucket_bps: f64,
+    pub window_ms: i64,
+    /// Tape trades the quotes were checked against
+    pub tape_trades: usize,
+    /// Stream outages in the recorded tape
+    pub tape_gaps: usize,
+    /// Quotes whose window fell outside the recorded tape or overlapped an outage, left out
+    pub unresolved: u32,
+    /// Mean squared error between predicted probability and the 0/1 tape outcome
+    pub brier_score: f64,
+    /// Non-empty buckets, by distance
+    pub buckets: Vec<CalibrationBucket>,
+}
+
+impl FillCalibration {
+    /// Check every quoted maker attempt against `recording`, which covers up to `observed_until`.
+    /// Quotes the recording can't vouch for, from before its oldest trade or across an outage, are
+    /// left out rather than counted as misses.
+    pub fn from_attempts<'a>(attempts: impl IntoIterator<Item = &'a ExecutionAttempt>, recording: &TapeRecording, window: Duration, observed_until: i64, bucket_bps: f64) -> Self {
+        let bucket_bps = if bucket_bps > 0.0 { bucket_bps } else { DEFAULT_CALIBRATION_BUCKET_BPS };
+        let window_ms = window.as_millis() as i64;
+        let mut tape: Vec<TapeTrade> = recording.trades.iter().copied().collect();
+        tape.sort_by_key(|t| t.timestamp);
+
+        // (quotes, predicted, simulated fills, hits) per bucket
+        let mut buckets: BTreeMap<i64, (u32, f64, u32, u32)> = BTreeMap::new();
+        let mut unresolved = 0;
+        let mut squared_error = 0.0;
+        for attempt in attempts.into_iter().filter(|a| a.quoted && a.role == LiquidityRole::Maker) {
+            let until = attempt.timestamp + window_ms;
+            if !recording.covers(attempt.timestamp, until, observed_until) {
+                unresolved += 1;
+                continue;
+            }
+            let start = tape.partition_point(|t| t.timestamp < attempt.timestamp);
+            let hit = tape[start..].iter().take_while(|t| t.timestamp <= until).any(|t| match attempt.side {
+                TradeSide::Buy => t.price < attempt.price,
+                TradeSide::Sell => t.price > attempt.price,
+            });
+            let outcome = if hit { 1.0 } else { 0.0 };
+            squared_error += (attempt.execution_prob - outcome).powi(2);
+
+            let bucket = buckets.entry((attempt.touch_distance_bps / bucket_bps).floor() as i64).or_default();
+            bucket.0 += 1;
+            bucket.1 += attempt.execution_prob;
+            bucket.2 += attempt.executed() as u32;
+            bucket.3 += hit as u32;
+        }
+
+        let buckets: Vec<CalibrationBucket> = buckets
+            .into_iter()
+            .map(|(bucket, (quotes, predicted, fills, hits))| CalibrationBucket {
+                distance_bps: bucket as f64 * bucket_bps,
+                quotes,
+                mean_predicted: predicted / quotes as f64,
+                simulated_rate: fills as f64 / quotes as f64,
+                hits,
+            })
+            .collect();
+        let quotes: u32 = buckets.iter().map(|b| b.quotes).sum();
+        Self {
+            bucket_bps,
+            window_ms,
+            tape_trades: tape.len(),
+            tape_gaps: recording.gaps.len(),
+            unresolved,
+            brier_score: if quotes > 0 { squared_error / quotes as f64 } else { 0.0 },
+            buckets,
+        }
+    }
+
+    pub fn quotes(&self) -> u32 {
+        self.buckets.iter().map(|b| b.quotes).sum()
+    }
+
+    pub fn hits(&self) -> u32 {
+        self.buckets.iter().map(|b| b.hits).sum()
+    }
+
+    /// Mean absolute gap between predicted and empirical fill rate, weighted by quotes per bucket
+    pub fn calibration_error(&self) -> f64 {
+        let quotes = self.quotes();
+        if quotes == 0 {
+            return 0.0;
+        }
+        self.buckets.iter().map(|b| b.error().abs() * b.quotes as f64).sum::<f64>() / quotes as f64
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("failed to serialize fill calibration")
+    }
+
+    pub fn write_json(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_json()?).with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+/// One row per distance bucket, then the overall calibration error
+impl fmt::Display for FillCalibration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Fill calibration against {} tape trades, {} gaps ({}ms window, {} bps buckets):",
+            self.tape_trades, self.tape_gaps, self.window_ms, self.bucket_bps)?;
+        writeln!(f, "  {:>14} {:>7} {:>10} {:>10} {:>9} {:>8}", "DISTANCE", "QUOTES", "PREDICTED", "SIMULATED", "TAPE HIT", "ERROR")?;
+        for b in &self.buckets {
+            writeln!(f, "  {:>14} {:>7} {:>9.1}% {:>9.1}% {:>8.1}% {:>+7.1}%",
+                format!("[{:+.1}, {:+.1})", b.distance_bps, b.distance_bps + self.bucket_bps),
+                b.quotes,
+                b.mean_predicted * 100.0,
+                b.simulated_rate * 100.0,
+                b.hit_rate() * 100.0,
+                b.error() * 100.0)?;
+        }
+        write!(f, "  {} quotes, {} hit │ calibration error {:.1}% │ brier {:.4}",
+            self.quotes(), self.hits(), self.calibration_error() * 100.0, self.brier_score)?;
+        if self.unresolved > 0 {
+            write!(f, " │ {} unresolved (off the tape or across a gap)", self.unresolved)?;
+        }
+        Ok(())
+    }
+}
+
+/// Collect the tape in the background for a calibration at the end of the session
+pub fn spawn_tape_recorder(mut events: broadcast::Receiver<TapeEvent>) -> Arc<Mutex<TapeRecording>> {
+    let tape = Arc::new(Mutex::new(TapeRecording::default()));
+    let handle = Arc::clone(&tape);
+
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => handle.lock().unwrap().record(event),
+                // Trades were skipped; mark it like a drop so the quotes around it stay unresolved
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    let mut tape = handle.lock().unwrap();
+                    let last = tape.trades.back().map(|t| t.timestamp);
+                    if let Some(timestamp) = last {
+                        tape.record(TapeEvent::Dropped { timestamp });
+                    }
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    tape
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_millis(1000);
+
+    fn trade(timestamp: i64, price: f64) -> TapeEvent {
+        TapeEvent::Trade(TapeTrade { timestamp, price, amount_eth: 0.5, aggressor: TradeSide::Sell })
+    }
+
+    fn quote(side: TradeSide, price: f64, timestamp: i64, execution_prob: f64, touch_distance_bps: f64) -> ExecutionAttempt {
+        ExecutionAttempt {
+            side,
+            price,
+            execution_prob,
+            model_predictions: Vec::new(),
+            timestamp,
+            feed_time: timestamp,
+            role: LiquidityRole::Maker,
+            expected_edge_bps: 1.0,
+            quoted: true,
+            draw: 0.5,
+            degraded: false,
+            touch_distance_bps,
+            order_notional: 100.0,
+            trade: None,
+            pending: false,
+        }
+    }
+
+    /// Trades at 3000 every second from 0 to 17s, with an outage from 12.2s to 14s, a print
+    /// through 2995 at 5.5s and the stream down again from 17.2s
+    fn recording() -> TapeRecording {
+        let mut tape = TapeRecording::default();
+        for t in (0..=17_000).step_by(1000).filter(|&t| t != 13_000) {
+            tape.record(trade(t, 3000.0));
+            if t == 5000 {
+                tape.record(trade(5500, 2990.0));
+            }
+            if t == 12_000 {
+                tape.record(TapeEvent::Dropped { timestamp: 12_200 });
+                // A lagged recorder reporting the same outage doesn't move its start
+                tape.record(TapeEvent::Dropped { timestamp: 12_600 });
+            }
+        }
+        tape.record(TapeEvent::Dropped { timestamp: 17_200 });
+        tape
+    }
+
+    #[test]
+    fn recording_tracks_outages() {
+        let tape = recording();
+        assert_eq!(tape.trades(), 18);
+        assert_eq!(tape.gaps(), &[(12_200, 14_000)]);
+        assert!(tape.covers(0, 1000, 20_000));
+        assert!(!tape.covers(11_500, 12_500, 20_000), "overlaps the outage");
+        assert!(!tape.covers(-500, 500, 20_000), "starts before the tape");
+        assert!(!tape.covers(16_500, 17_500, 20_000), "runs into the open outage");
+        assert!(!tape.covers(2000, 3000, 2500), "runs past what was observed");
+        assert!(!TapeRecording::default().covers(0, 1, 10));
+    }
+
+    #[test]
+    fn quotes_score_against_trades_through_them() {
+        let attempts = [
+            quote(TradeSide::Buy, 2995.0, 5000, 0.6, 0.5),
+            quote(TradeSide::Buy, 2995.0, 8000, 0.4, 0.5),
+            quote(TradeSide::Sell, 3005.0, 2000, 0.2, 0.5),
+            quote(TradeSide::Buy, 2995.0, 14_500, 0.5, 0.5),
+            // Improving on the touch; the 3000 print at 16s trades through it
+            quote(TradeSide::Buy, 3001.0, 15_500, 0.7, -0.5),
+            // Unresolved: before the tape, across the outage, into the open outage
+            quote(TradeSide::Buy, 2995.0, -500, 0.5, 0.5),
+            quote(TradeSide::Buy, 2995.0, 11_500, 0.5, 0.5),
+            quote(TradeSide::Buy, 2995.0, 16_500, 0.5, 0.5),
+        ];
+        let mut taker = quote(TradeSide::Buy, 3001.0, 3000, 0.9, -1.0);
+        taker.role = LiquidityRole::Taker;
+        let mut withheld = quote(TradeSide::Buy, 2995.0, 3000, 0.9, 0.5);
+        withheld.quoted = false;
+
+        let calibration = FillCalibration::from_attempts(attempts.iter().chain([&taker, &withheld]), &recording(), WINDOW, 20_000, 1.0);
+        assert_eq!((calibration.quotes(), calibration.hits(), calibration.unresolved), (5, 2, 3));
+        assert_eq!((calibration.tape_trades, calibration.tape_gaps), (18, 1));
+        assert!((calibration.brier_score - 0.14).abs() < 1e-12);
+
+        let [inside, behind] = calibration.buckets.as_slice() else {
+            panic!("expected two buckets, got {:?}", calibration.buckets);
+        };
+        assert_eq!((inside.distance_bps, inside.quotes, inside.hits), (-1.0, 1, 1));
+        assert_eq!((behind.distance_bps, behind.quotes, behind.hits), (0.0, 4, 1));
+        assert!((behind.mean_predicted - 0.425).abs() < 1e-12);
+        assert!((behind.error() - 0.175).abs() < 1e-12);
+        assert_eq!(behind.simulated_rate, 0.0);
+        assert!((calibration.calibration_error() - (0.3 + 4.0 * 0.175) / 5.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn print_at_the_quote_is_not_a_hit() {
+        let mut tape = TapeRecording::default();
+        tape.record(trade(0, 3000.0));
+        tape.record(trade(500, 2995.0));
+        let calibration = FillCalibration::from_attempts([&quote(TradeSide::Buy, 2995.0, 100, 0.5, 0.5)], &tape, WINDOW, 5000, 1.0);
+        assert_eq!((calibration.quotes(), calibration.hits()), (1, 0));
+    }
+}
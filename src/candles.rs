@@ -0,0 +1,214 @@
+use crate::aggregator::{AggregatedPrices, QuoteUpdate, Source};
+use anyhow::{bail, Result};
+use serde::{Serialize, Serializer};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Bars kept per series and interval: 10 minutes of 1s bars, 10 hours of 1m bars
+pub const DEFAULT_CANDLE_CAPACITY: usize = 600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CandleInterval {
+    Second,
+    Minute,
+}
+
+impl CandleInterval {
+    pub const ALL: [CandleInterval; 2] = [CandleInterval::Second, CandleInterval::Minute];
+
+    pub fn millis(&self) -> i64 {
+        match self {
+            CandleInterval::Second => 1_000,
+            CandleInterval::Minute => 60_000,
+        }
+    }
+
+    /// Start of the bar `timestamp` falls in
+    pub fn bar_start(&self, timestamp: i64) -> i64 {
+        timestamp - timestamp.rem_euclid(self.millis())
+    }
+}
+
+impl FromStr for CandleInterval {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "1s" => Ok(CandleInterval::Second),
+            "1m" => Ok(CandleInterval::Minute),
+            other => bail!("unknown candle interval '{}' (expected 1s or 1m)", other),
+        }
+    }
+}
+
+impl fmt::Display for CandleInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CandleInterval::Second => "1s",
+            CandleInterval::Minute => "1m",
+        })
+    }
+}
+
+/// What a bar series tracks: one venue's mid, or the aggregate median mid the engine quotes around
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CandleSeries {
+    Venue(Source),
+    Mid,
+}
+
+impl FromStr for CandleSeries {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "mid" => Ok(CandleSeries::Mid),
+            venue => Ok(CandleSeries::Venue(venue.parse()?)),
+        }
+    }
+}
+
+impl fmt::Display for CandleSeries {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CandleSeries::Venue(source) => f.write_str(source.name()),
+            CandleSeries::Mid => f.write_str("mid"),
+        }
+    }
+}
+
+impl Serialize for CandleSeries {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// One OHLCV bar of mid prices
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Candle {
+    /// Bar start, Unix milliseconds, aligned to the interval
+    pub start: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Quote updates in the bar (tick volume); the feeds carry no traded size
+    pub volume: u64,
+}
+
+impl Candle {
+    fn open_at(start: i64, price: f64) -> Self {
+        Self { start, open: price, high: price, low: price, close: price, volume: 1 }
+    }
+
+    fn update(&mut self, price: f64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume += 1;
+    }
+}
+
+/// Builds 1s and 1m OHLCV bars per venue and for the aggregate mid from quote updates, keeping
+/// the most recent ones. Bars are stamped in feed time, so replays build the bars of the recording.
+/// An interval without quotes has no bar.
+#[derive(Debug)]
+pub struct CandleBuilder {
+    capacity: usize,
+    prices: AggregatedPrices,
+    bars: HashMap<(CandleSeries, CandleInterval), VecDeque<Candle>>,
+}
+
+impl Default for CandleBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CandleBuilder {
+    pub fn new() -> Self {
+        Self {
+            capacity: DEFAULT_CANDLE_CAPACITY,
+            prices: AggregatedPrices::default(),
+            bars: HashMap::new(),
+        }
+    }
+
+    /// Keep up to `capacity` bars per series and interval
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    pub fn on_update(&mut self, update: &QuoteUpdate) {
+        let quote = update.quote;
+        self.record(CandleSeries::Venue(update.source), (quote.bid + quote.ask) / 2.0, quote.timestamp);
+
+        // A slow venue's quote can be older than the others'; the mid moves at the newest feed time
+        self.prices.set(update.source, quote);
+        let now = self.prices.quotes().map(|q| q.timestamp).max().unwrap_or(quote.timestamp);
+        if let Some(mid) = self.prices.median_mid() {
+            self.record(CandleSeries::Mid, mid, now);
+        }
+    }
+
+    fn record(&mut self, series: CandleSeries, price: f64, timestamp: i64) {
+        for interval in CandleInterval::ALL {
+            let start = interval.bar_start(timestamp);
+            let bars = self.bars.entry((series, interval)).or_default();
+            match bars.back_mut() {
+                Some(bar) if bar.start == start => bar.update(price),
+                // Too late for a bar that has already closed
+                Some(bar) if bar.start > start => {}
+                _ => {
+                    bars.push_back(Candle::open_at(start, price));
+                    if bars.len() > self.capacity {
+                        bars.pop_front();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Up to `limit` most recent bars, oldest first; the last is still forming
+    pub fn recent(&self, series: CandleSeries, interval: CandleInterval, limit: usize) -> Vec<Candle> {
+        let Some(bars) = self.bars.get(&(series, interval)) else {
+            return Vec::new();
+        };
+        bars.iter().skip(bars.len().saturating_sub(limit)).copied().collect()
+    }
+
+    /// The bar currently forming
+    pub fn latest(&self, series: CandleSeries, interval: CandleInterval) -> Option<Candle> {
+        self.bars.get(&(series, interval))?.back().copied()
+    }
+
+    /// Series with at least one bar, the aggregate mid first
+    pub fn series(&self) -> Vec<CandleSeries> {
+        std::iter::once(CandleSeries::Mid)
+            .chain(Source::ALL.into_iter().map(CandleSeries::Venue))
+            .filter(|&s| self.bars.contains_key(&(s, CandleInterval::Second)))
+            .collect()
+    }
+}
+
+/// Build bars from a quote subscription in the background
+pub fn spawn_candle_builder(mut updates: broadcast::Receiver<QuoteUpdate>, builder: CandleBuilder) -> Arc<Mutex<CandleBuilder>> {
+    let builder = Arc::new(Mutex::new(builder));
+    let handle = Arc::clone(&builder);
+
+    tokio::spawn(async move {
+        loop {
+            match updates.recv().await {
+                Ok(update) => handle.lock().unwrap().on_update(&update),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    builder
+}
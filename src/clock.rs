@@ -0,0 +1,87 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Where the engine and feeds read "now" from: wall-clock for live sessions, the feed's own simulated
+/// time for replays and the offline venue, so trades, orders and marks all share one timeline
+pub trait Clock: Send + Sync {
+    /// Current time in epoch milliseconds
+    fn now_ms(&self) -> i64;
+}
+
+/// Wall-clock time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> i64 {
+        chrono::Utc::now().timestamp_millis()
+    }
+}
+
+/// Wall-clock time as of creation, then moved by tokio's clock, so `tokio::time::pause` and
+/// `advance` (or a paused runtime's auto-advance) move it too
+#[derive(Debug, Clone, Copy)]
+pub struct TokioClock {
+    origin_ms: i64,
+    origin: tokio::time::Instant,
+}
+
+impl TokioClock {
+    pub fn new() -> Self {
+        Self::starting_at(chrono::Utc::now().timestamp_millis())
+    }
+
+    /// Read `start_ms` now and follow tokio's clock from there
+    pub fn starting_at(start_ms: i64) -> Self {
+        Self {
+            origin_ms: start_ms,
+            origin: tokio::time::Instant::now(),
+        }
+    }
+}
+
+impl Default for TokioClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for TokioClock {
+    fn now_ms(&self) -> i64 {
+        self.origin_ms + self.origin.elapsed().as_millis() as i64
+    }
+}
+
+/// Time that only moves when told to, e.g. by a replay stepping through recorded time
+#[derive(Debug, Default)]
+pub struct SimulatedClock {
+    now_ms: AtomicI64,
+}
+
+impl SimulatedClock {
+    pub fn new(start_ms: i64) -> Self {
+        Self {
+            now_ms: AtomicI64::new(start_ms),
+        }
+    }
+
+    pub fn set(&self, now_ms: i64) {
+        self.now_ms.store(now_ms, Ordering::Relaxed);
+    }
+
+    pub fn advance(&self, step: Duration) {
+        self.now_ms.fetch_add(step.as_millis() as i64, Ordering::Relaxed);
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now_ms(&self) -> i64 {
+        self.now_ms.load(Ordering::Relaxed)
+    }
+}
+
+/// The default for anything not handed a clock
+pub fn system() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
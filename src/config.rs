@@ -0,0 +1,1730 @@
+use crate::alerts::AlertRule;
+use crate::aggregation::{AggregationPolicy, DEFAULT_FRESHNESS_HALF_LIFE_MS};
+use crate::aggregator::Source;
+use crate::book_sim::BookSimConfig;
+use crate::fees::{FeeSchedule, VenueFees};
+use crate::fanout::FlowControl;
+use crate::execution::{AdvancedModel, BasicModel, EnsembleMethod, EnsembleModel, ExecutionModel, PoissonModel, QueueModel};
+use crate::export::HttpExportConfig;
+use crate::funding::FundingRates;
+use crate::hedger::HedgePolicy;
+use crate::http::HttpPolicy;
+use crate::liquidity::LiquidityProfile;
+use crate::market::MarketConfig;
+use crate::notify::NotifyPolicy;
+use crate::orders::OrderManager;
+use crate::position::CostBasis;
+use crate::publish::PublishTarget;
+use crate::paper::{TestnetAccount, TESTNET_API_URL};
+use crate::participation::ParticipationPolicy;
+use crate::quiet::QuietMarketPolicy;
+use crate::quorum::QuorumPolicy;
+use crate::quoting::{parse_quoting_strategy, FixedSpread, OffsetQuote, QuotingStrategy};
+use crate::reconcile::{ReconcileTolerance, Reconciler, DEFAULT_RECONCILE_INTERVAL};
+use crate::risk::{BreachAction, RiskLimits, RiskManager};
+use crate::schedule::{Calendar, SessionWindow};
+use crate::sizing::{parse_sizing_rule, InventorySkew, SizingRule, VolTarget, DEFAULT_VOL_WINDOW_MS};
+use crate::synthetic::SyntheticConfig;
+use crate::tokens::{self, Token, TokenRegistry, TradingPair};
+use crate::trade_history::TradeHistory;
+use crate::two_sided::TwoSidedPolicy;
+use crate::trader::{EdgeRequirement, TakerPolicy, TradingEngine};
+use crate::uniswap::{UniswapConfig, DEFAULT_FEE_TIERS};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::warn;
+
+/// Prefix of environment variables that override config values, e.g. `MM_NOTIONAL_PER_TRADE`
+pub const ENV_PREFIX: &str = "MM_";
+
+/// Every runtime parameter of a simulation run, loadable from TOML.
+/// Missing keys fall back to the defaults below.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub simulation: SimulationConfig,
+    pub execution: ExecutionConfig,
+    pub sources: SourcesConfig,
+    pub synthetic: SyntheticSettings,
+    pub book_sim: BookSimSettings,
+    pub quoting: QuotingConfig,
+    pub sizing: SizingConfig,
+    pub quorum: QuorumConfig,
+    pub aggregation: AggregationConfig,
+    pub orders: OrdersConfig,
+    pub paper: PaperConfig,
+    pub tokens: TokensConfig,
+    pub risk: RiskConfig,
+    pub hedge: HedgeConfig,
+    pub funding: FundingConfig,
+    pub quiet: QuietConfig,
+    pub participation: ParticipationConfig,
+    pub reconcile: ReconcileConfig,
+    pub schedule: ScheduleConfig,
+    pub export: ExportConfig,
+    pub api: ApiConfig,
+    pub websocket: WebSocketConfig,
+    pub alerts: AlertsConfig,
+    pub notify: NotifyConfig,
+    pub publish: PublishConfig,
+    pub fees: FeesConfig,
+    pub markets: MarketsConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationConfig {
+    pub notional_per_trade: f64,
+    pub duration_secs: u64,
+    pub trade_interval_secs: u64,
+    /// How long live feeds get to deliver their first prices before trading starts
+    pub warmup_secs: u64,
+    /// How closing fills realize PnL against open inventory: `average` or `fifo`
+    pub cost_basis: String,
+    /// Feed time between points on the mark-to-market equity time series
+    pub mark_interval_secs: u64,
+    /// Label stamped on every trade of the session, e.g. the strategy variant under test
+    pub tag: Option<String>,
+    /// Trades kept in memory; older ones spill to `trade_spill_path`. 0 keeps every trade in memory.
+    pub max_trades_in_memory: usize,
+    /// Where spilled trades go, `sessions/<session-id>-trades.jsonl` by default
+    pub trade_spill_path: Option<String>,
+}
+
+impl SimulationConfig {
+    pub fn cost_basis(&self) -> Result<CostBasis> {
+        self.cost_basis.parse()
+    }
+
+    pub fn mark_interval(&self) -> Duration {
+        Duration::from_secs(self.mark_interval_secs.max(1))
+    }
+
+    /// Trade history for a session, spilling to `trade_spill_path` or `default_spill_path` when
+    /// `max_trades_in_memory` is set
+    pub fn trade_history(&self, default_spill_path: impl Into<PathBuf>) -> Result<TradeHistory> {
+        if self.max_trades_in_memory == 0 {
+            return Ok(TradeHistory::unbounded());
+        }
+        let path = self.trade_spill_path.as_ref().map_or_else(|| default_spill_path.into(), PathBuf::from);
+        TradeHistory::spilling(path, self.max_trades_in_memory)
+    }
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            notional_per_trade: 100_000.0,
+            duration_secs: 600,
+            trade_interval_secs: 5,
+            warmup_secs: 10,
+            cost_basis: "average".to_string(),
+            mark_interval_secs: 5,
+            tag: None,
+            max_trades_in_memory: 0,
+            trade_spill_path: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecutionConfig {
+    /// `basic`, `advanced`, `poisson`, `queue`, or a comma separated ensemble such as `basic,queue`
+    pub model: String,
+    pub ensemble_method: String,
+    pub basic_probability: f64,
+    pub min_probability: f64,
+    pub max_probability: f64,
+    pub poisson_arrival_rate: f64,
+    pub poisson_decay_per_bps: f64,
+    pub queue_touch_usd: f64,
+    pub queue_depth_per_bps_usd: f64,
+    pub queue_flow_usd_per_sec: f64,
+    pub queue_improvement_flow_per_bps: f64,
+    /// Size the queue model's taker flow from the Binance tape instead of `queue_flow_usd_per_sec`
+    pub queue_tape_flow: bool,
+    /// Cross the spread when the signal reaches this many bps; passive only when unset
+    pub taker_signal_bps: Option<f64>,
+    /// Taker fee for every venue, overriding the fee schedule
+    pub taker_fee_bps: Option<f64>,
+    pub taker_slippage_bps: f64,
+    /// Only quote when the expected edge clears `min_edge_bps`
+    pub require_min_edge: bool,
+    pub min_edge_bps: f64,
+    /// Maker fee for every venue, overriding the fee schedule
+    pub maker_fee_bps: Option<f64>,
+    pub adverse_selection_bps: f64,
+    /// Delay between a fill decision and its confirmation; fills are marked at confirmation. 0 confirms instantly
+    pub fill_latency_ms: u64,
+    /// Intraday liquidity profile (JSON from the `liquidity` command) scaling fill probabilities and slippage by hour
+    pub liquidity_profile: Option<String>,
+}
+
+impl Default for ExecutionConfig {
+    fn default() -> Self {
+        let queue = QueueModel::new(0.0);
+        Self {
+            model: "basic".to_string(),
+            ensemble_method: "mean".to_string(),
+            basic_probability: 0.70,
+            min_probability: 0.20,
+            max_probability: 0.90,
+            poisson_arrival_rate: 0.5,
+            poisson_decay_per_bps: 0.3,
+            queue_touch_usd: queue.touch_queue_usd,
+            queue_depth_per_bps_usd: queue.depth_per_bps_usd,
+            queue_flow_usd_per_sec: queue.flow_usd_per_sec,
+            queue_improvement_flow_per_bps: queue.improvement_flow_per_bps,
+            queue_tape_flow: queue.tape_flow,
+            taker_signal_bps: None,
+            taker_fee_bps: None,
+            taker_slippage_bps: 2.0,
+            require_min_edge: true,
+            min_edge_bps: 0.0,
+            maker_fee_bps: None,
+            adverse_selection_bps: 0.5,
+            fill_latency_ms: 0,
+            liquidity_profile: None,
+        }
+    }
+}
+
+impl ExecutionConfig {
+    /// Build the configured model; Poisson and queue fills are measured over `horizon_secs`
+    pub fn execution_model(&self, horizon_secs: f64) -> Result<Box<dyn ExecutionModel>> {
+        let mut models = self
+            .model
+            .split(',')
+            .map(|name| self.single_model(name, horizon_secs))
+            .collect::<Result<Vec<_>>>()?;
+
+        if models.len() == 1 {
+            return Ok(models.remove(0));
+        }
+        Ok(Box::new(EnsembleModel {
+            models,
+            method: self.ensemble_method.parse::<EnsembleMethod>()?,
+        }))
+    }
+
+    fn single_model(&self, name: &str, horizon_secs: f64) -> Result<Box<dyn ExecutionModel>> {
+        Ok(match name.trim().to_ascii_lowercase().as_str() {
+            "basic" => Box::new(BasicModel {
+                probability: self.basic_probability,
+            }),
+            "advanced" => Box::new(AdvancedModel {
+                min_probability: self.min_probability,
+                max_probability: self.max_probability,
+            }),
+            "poisson" => Box::new(PoissonModel {
+                arrival_rate: self.poisson_arrival_rate,
+                decay_per_bps: self.poisson_decay_per_bps,
+                horizon_secs,
+            }),
+            "queue" => Box::new(QueueModel {
+                touch_queue_usd: self.queue_touch_usd,
+                depth_per_bps_usd: self.queue_depth_per_bps_usd,
+                flow_usd_per_sec: self.queue_flow_usd_per_sec,
+                improvement_flow_per_bps: self.queue_improvement_flow_per_bps,
+                tape_flow: self.queue_tape_flow,
+                ..QueueModel::new(horizon_secs)
+            }),
+            other => bail!("unknown execution model '{}' (expected basic, advanced, poisson or queue)", other),
+        })
+    }
+
+    pub fn edge_requirement(&self) -> Option<EdgeRequirement> {
+        self.require_min_edge.then_some(EdgeRequirement {
+            min_edge_bps: self.min_edge_bps,
+            adverse_selection_bps: self.adverse_selection_bps,
+        })
+    }
+
+    pub fn fill_latency(&self) -> Option<Duration> {
+        (self.fill_latency_ms > 0).then(|| Duration::from_millis(self.fill_latency_ms))
+    }
+
+    pub fn taker_policy(&self) -> Option<TakerPolicy> {
+        self.taker_signal_bps.map(|signal_bps| TakerPolicy {
+            signal_bps,
+            slippage_bps: self.taker_slippage_bps,
+        })
+    }
+}
+
+/// `url` with `{symbol}` replaced by `pair`'s lowercase Binance symbol. A URL without the placeholder
+/// has to name that symbol's stream already, so a changed pair can't quietly read another market.
+fn binance_stream_url(key: &str, url: &str, pair: &TradingPair) -> Result<String> {
+    let symbol = pair.binance_symbol()?.to_ascii_lowercase();
+    if url.contains("{symbol}") {
+        return Ok(url.replace("{symbol}", &symbol));
+    }
+    if !url.to_ascii_lowercase().contains(&format!("/{}@", symbol)) {
+        bail!("{} {} doesn't stream {} ({}); put {{symbol}} in the URL to follow the pair", key, url, pair, symbol);
+    }
+    Ok(url.to_string())
+}
+
+/// Range a source's request rate limit can be set to, besides 0 for none
+pub const MIN_MAX_RPS: f64 = 0.01;
+pub const MAX_MAX_RPS: f64 = 1000.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SourcesConfig {
+    /// Binance bookTicker stream; `{symbol}` is filled in with the pair's Binance symbol
+    pub binance_ws_url: String,
+    /// Binance aggTrade stream feeding the order-flow imbalance signal, with the same `{symbol}`;
+    /// empty turns it off
+    pub binance_trades_ws_url: String,
+    /// Window the flow imbalance in each price snapshot covers, at most 300
+    pub flow_window_secs: u64,
+    pub jupiter_api_url: String,
+    pub cowswap_api_url: String,
+    pub jupiter_poll_secs: u64,
+    pub cowswap_poll_secs: u64,
+    /// USD size DEX quotes are priced at; defaults to the trade notional
+    pub quote_notional: Option<f64>,
+    /// Ethereum JSON-RPC endpoint; the Uniswap source only runs when set
+    pub uniswap_rpc_url: Option<String>,
+    pub uniswap_fee_tiers: Vec<u32>,
+    pub uniswap_poll_secs: u64,
+    /// Most requests per second sent to Jupiter; a poll makes three
+    pub jupiter_max_rps: f64,
+    /// Most requests per second sent to CowSwap; a poll makes two
+    pub cowswap_max_rps: f64,
+    pub http_timeout_ms: u64,
+    /// Retries of a throttled, timed out or 5xx request before the poll counts as failed
+    pub http_max_retries: u32,
+    /// Backoff before the first retry, doubling up to `http_retry_max_ms`
+    pub http_retry_base_ms: u64,
+    /// Longest retry backoff; a longer `Retry-After` fails the poll and delays the next one
+    pub http_retry_max_ms: u64,
+}
+
+impl Default for SourcesConfig {
+    fn default() -> Self {
+        Self {
+            binance_ws_url: "wss://stream.binance.com:9443/ws/{symbol}@bookTicker".to_string(),
+            binance_trades_ws_url: "wss://stream.binance.com:9443/ws/{symbol}@aggTrade".to_string(),
+            flow_window_secs: 30,
+            jupiter_api_url: "https://lite-api.jup.ag".to_string(),
+            cowswap_api_url: "https://api.cow.fi/mainnet".to_string(),
+            jupiter_poll_secs: 2,
+            cowswap_poll_secs: 3,
+            quote_notional: None,
+            uniswap_rpc_url: None,
+            uniswap_fee_tiers: DEFAULT_FEE_TIERS.to_vec(),
+            uniswap_poll_secs: 12,
+            jupiter_max_rps: 2.0,
+            cowswap_max_rps: 5.0,
+            http_timeout_ms: 10_000,
+            http_max_retries: 2,
+            http_retry_base_ms: 250,
+            http_retry_max_ms: 5_000,
+        }
+    }
+}
+
+impl SourcesConfig {
+    /// Binance bookTicker stream URL for `pair`
+    pub fn binance_stream(&self, pair: &TradingPair) -> Result<String> {
+        binance_stream_url("sources.binance_ws_url", &self.binance_ws_url, pair)
+    }
+
+    /// Binance aggTrade stream URL for `pair`; `None` when the stream is turned off
+    pub fn binance_trades_stream(&self, pair: &TradingPair) -> Result<Option<String>> {
+        if self.binance_trades_ws_url.is_empty() {
+            return Ok(None);
+        }
+        binance_stream_url("sources.binance_trades_ws_url", &self.binance_trades_ws_url, pair).map(Some)
+    }
+
+    /// Uniswap pools of `pair`, when an RPC URL is set
+    pub fn uniswap(&self, notional_usd: f64, pair: &TradingPair) -> Result<Option<UniswapConfig>> {
+        let Some(rpc_url) = self.uniswap_rpc_url.as_ref() else {
+            return Ok(None);
+        };
+        let mut config = UniswapConfig::new(rpc_url.clone(), notional_usd)
+            .with_fee_tiers(self.uniswap_fee_tiers.clone())
+            .with_pair(pair)?;
+        config.poll_interval = Duration::from_secs(self.uniswap_poll_secs);
+        Ok(Some(config))
+    }
+
+    /// Timeout, retries and rate limit for requests to `source`
+    pub fn http_policy(&self, source: Source) -> HttpPolicy {
+        let policy = HttpPolicy {
+            timeout: Duration::from_millis(self.http_timeout_ms),
+            max_retries: self.http_max_retries,
+            retry_base: Duration::from_millis(self.http_retry_base_ms),
+            retry_max: Duration::from_millis(self.http_retry_max_ms),
+            ..HttpPolicy::default()
+        };
+        match source {
+            Source::Jupiter => policy.with_rate_limit(self.jupiter_max_rps),
+            Source::CowSwap => policy.with_rate_limit(self.cowswap_max_rps),
+            Source::Binance | Source::Uniswap => policy,
+        }
+    }
+}
+
+/// Where the engine places its bid and ask
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuotingConfig {
+    /// `median`, `fixed-spread` or `offset`
+    pub strategy: String,
+    /// Total bid/ask spread around mid for `fixed-spread`
+    pub spread_bps: f64,
+    /// What `offset` measures from: `median-mid`, `weighted-mid`, `median` or `touch`
+    pub reference: String,
+    /// How far below the reference `offset` bids; negative bids inside it
+    pub bid_offset_bps: f64,
+    /// How far above the reference `offset` asks; negative asks inside it
+    pub ask_offset_bps: f64,
+    /// Show bid and ask together each cycle and draw their fills jointly
+    pub two_sided: bool,
+    /// Correlation of a two-sided quote's bid and ask fill draws, from -1 to 1
+    pub fill_correlation: f64,
+    /// Let at most one side of a two-sided quote fill per cycle
+    pub one_fill_per_cycle: bool,
+}
+
+impl Default for QuotingConfig {
+    fn default() -> Self {
+        Self {
+            strategy: "median".to_string(),
+            spread_bps: FixedSpread::default().spread_bps,
+            reference: "median-mid".to_string(),
+            bid_offset_bps: OffsetQuote::default().bid_offset_bps,
+            ask_offset_bps: OffsetQuote::default().ask_offset_bps,
+            two_sided: false,
+            fill_correlation: 0.0,
+            one_fill_per_cycle: false,
+        }
+    }
+}
+
+impl QuotingConfig {
+    pub fn strategy(&self) -> Result<Box<dyn QuotingStrategy>> {
+        parse_quoting_strategy(&self.strategy, self.spread_bps, || {
+            let offset = OffsetQuote {
+                reference: self.reference.parse()?,
+                bid_offset_bps: self.bid_offset_bps,
+                ask_offset_bps: self.ask_offset_bps,
+            };
+            offset.validate().context("invalid quoting offsets")?;
+            Ok(offset)
+        })
+    }
+
+    /// Joint fill policy when `two_sided` is on
+    pub fn two_sided(&self) -> Result<Option<TwoSidedPolicy>> {
+        if !self.two_sided {
+            return Ok(None);
+        }
+        TwoSidedPolicy::new(self.fill_correlation, self.one_fill_per_cycle).map(Some)
+    }
+}
+
+/// How order size reacts to inventory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SizingConfig {
+    /// `fixed`, `inventory` or `vol-target`
+    pub rule: String,
+    pub max_inventory_eth: f64,
+    pub skew: f64,
+    pub min_fraction: f64,
+    /// Annualized volatility `vol-target` quotes base size at
+    pub target_vol: f64,
+    pub max_multiplier: f64,
+    /// Trailing window realized volatility is measured over
+    pub vol_window_secs: u64,
+}
+
+impl Default for SizingConfig {
+    fn default() -> Self {
+        let skew = InventorySkew::default();
+        let vol = VolTarget::default();
+        Self {
+            rule: "fixed".to_string(),
+            max_inventory_eth: skew.max_inventory_eth,
+            skew: skew.skew,
+            min_fraction: skew.min_fraction,
+            target_vol: vol.target_vol,
+            max_multiplier: vol.max_multiplier,
+            vol_window_secs: (DEFAULT_VOL_WINDOW_MS / 1000) as u64,
+        }
+    }
+}
+
+impl SizingConfig {
+    pub fn rule(&self) -> Result<Box<dyn SizingRule>> {
+        let skew = InventorySkew {
+            max_inventory_eth: self.max_inventory_eth,
+            skew: self.skew,
+            min_fraction: self.min_fraction,
+        };
+        let vol = VolTarget {
+            target_vol: self.target_vol,
+            max_multiplier: self.max_multiplier,
+            min_fraction: self.min_fraction,
+        };
+        parse_sizing_rule(&self.rule, skew, vol)
+    }
+
+    pub fn vol_window(&self) -> Duration {
+        Duration::from_secs(self.vol_window_secs)
+    }
+}
+
+/// Source quorum, and how to trade when fewer sources are fresh
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuorumConfig {
+    pub min_sources: usize,
+    pub max_quote_age_secs: f64,
+    /// Trade off the remaining sources in degraded mode instead of halting
+    pub allow_degraded: bool,
+    pub degraded_spread_bps: f64,
+    pub degraded_size_factor: f64,
+}
+
+impl Default for QuorumConfig {
+    fn default() -> Self {
+        let policy = QuorumPolicy::default();
+        Self {
+            min_sources: policy.min_sources,
+            max_quote_age_secs: policy.max_quote_age_ms as f64 / 1000.0,
+            allow_degraded: policy.allow_degraded,
+            degraded_spread_bps: policy.degraded_spread_bps,
+            degraded_size_factor: policy.degraded_size_factor,
+        }
+    }
+}
+
+impl QuorumConfig {
+    pub fn policy(&self) -> QuorumPolicy {
+        QuorumPolicy {
+            min_sources: self.min_sources,
+            max_quote_age_ms: (self.max_quote_age_secs * 1000.0) as i64,
+            allow_degraded: self.allow_degraded,
+            degraded_spread_bps: self.degraded_spread_bps,
+            degraded_size_factor: self.degraded_size_factor,
+        }
+    }
+}
+
+/// Reference price the engine trades around
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AggregationConfig {
+    /// `median`, `bbo`, `liquidity-weighted` or `freshness-weighted`
+    pub policy: String,
+    /// How fast a quote's weight halves with age under `freshness-weighted`
+    pub freshness_half_life_ms: i64,
+}
+
+impl Default for AggregationConfig {
+    fn default() -> Self {
+        Self {
+            policy: AggregationPolicy::default().name().to_string(),
+            freshness_half_life_ms: DEFAULT_FRESHNESS_HALF_LIFE_MS,
+        }
+    }
+}
+
+impl AggregationConfig {
+    pub fn policy(&self) -> Result<AggregationPolicy> {
+        AggregationPolicy::parse(&self.policy, self.freshness_half_life_ms)
+    }
+}
+
+/// Risk limits, all off unless set, and what a tripped loss limit does
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskConfig {
+    pub max_inventory_eth: Option<f64>,
+    pub max_drawdown_usd: Option<f64>,
+    pub max_loss_per_hour_usd: Option<f64>,
+    pub max_consecutive_losses: Option<u32>,
+    /// `halt` or `reduce-only`
+    pub breach_action: String,
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            max_inventory_eth: None,
+            max_drawdown_usd: None,
+            max_loss_per_hour_usd: None,
+            max_consecutive_losses: None,
+            breach_action: "halt".to_string(),
+        }
+    }
+}
+
+impl RiskConfig {
+    pub fn limits(&self) -> RiskLimits {
+        RiskLimits {
+            max_inventory_eth: self.max_inventory_eth,
+            max_drawdown_usd: self.max_drawdown_usd,
+            max_loss_per_hour_usd: self.max_loss_per_hour_usd,
+            max_consecutive_losses: self.max_consecutive_losses,
+        }
+    }
+
+    /// `None` when no limit is set
+    pub fn manager(&self) -> Result<Option<RiskManager>> {
+        let limits = self.limits();
+        if limits.is_empty() {
+            return Ok(None);
+        }
+        let action: BreachAction = self.breach_action.parse()?;
+        Ok(Some(RiskManager::new(limits).with_action(action)))
+    }
+}
+
+/// Automatic inventory hedging; off unless a threshold is set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HedgeConfig {
+    pub threshold_eth: Option<f64>,
+    /// Absolute inventory left after a hedge
+    pub target_eth: f64,
+}
+
+impl HedgeConfig {
+    pub fn policy(&self) -> Option<HedgePolicy> {
+        self.threshold_eth.map(|threshold_eth| HedgePolicy {
+            threshold_eth,
+            target_eth: self.target_eth,
+        })
+    }
+}
+
+/// Carry on open inventory, as annualized rates on its value at the mid; off while both are 0
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FundingConfig {
+    /// Charged on long ETH inventory, e.g. 0.05 for 5% a year
+    pub long_rate: f64,
+    /// Charged on short ETH inventory
+    pub short_rate: f64,
+}
+
+/// Largest annualized carry rate either way, 200% a year; past it, the rate was most likely given
+/// as a percentage
+pub const MAX_FUNDING_RATE: f64 = 2.0;
+
+impl FundingConfig {
+    pub fn rates(&self) -> Option<FundingRates> {
+        (self.long_rate != 0.0 || self.short_rate != 0.0).then(|| FundingRates::new(self.long_rate, self.short_rate))
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (key, rate) in [("funding.long_rate", self.long_rate), ("funding.short_rate", self.short_rate)] {
+            if !(-MAX_FUNDING_RATE..=MAX_FUNDING_RATE).contains(&rate) {
+                bail!("{} must be an annualized rate between -{} and {} (0.05 for 5%), got {}", key, MAX_FUNDING_RATE, MAX_FUNDING_RATE, rate);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Quiet-market detection; off unless a spread threshold is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuietConfig {
+    pub max_spread_bps: Option<f64>,
+    /// Annualized, e.g. 0.2 for 20%
+    pub max_volatility: f64,
+    pub min_quiet_secs: u64,
+    pub vol_window_secs: u64,
+    /// Stop quoting while the market is quiet instead of only reporting it
+    pub pause: bool,
+}
+
+impl Default for QuietConfig {
+    fn default() -> Self {
+        let policy = QuietMarketPolicy::default();
+        Self {
+            max_spread_bps: None,
+            max_volatility: policy.max_volatility,
+            min_quiet_secs: (policy.min_quiet_ms / 1000) as u64,
+            vol_window_secs: (policy.vol_window_ms / 1000) as u64,
+            pause: policy.pause,
+        }
+    }
+}
+
+impl QuietConfig {
+    pub fn policy(&self) -> Option<QuietMarketPolicy> {
+        self.max_spread_bps.map(|max_spread_bps| QuietMarketPolicy {
+            max_spread_bps,
+            max_volatility: self.max_volatility,
+            min_quiet_ms: self.min_quiet_secs as i64 * 1000,
+            vol_window_ms: self.vol_window_secs as i64 * 1000,
+            pause: self.pause,
+        })
+    }
+}
+
+/// Percent-of-volume cap on fills; off unless a percentage is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParticipationConfig {
+    pub max_pct: Option<f64>,
+    /// Trailing window the market volume and our fills are summed over
+    pub interval_secs: u64,
+}
+
+impl Default for ParticipationConfig {
+    fn default() -> Self {
+        Self {
+            max_pct: None,
+            interval_secs: 60,
+        }
+    }
+}
+
+impl ParticipationConfig {
+    pub fn policy(&self) -> Option<ParticipationPolicy> {
+        self.max_pct.map(|max_pct| ParticipationPolicy {
+            max_pct,
+            interval_ms: self.interval_secs as i64 * 1000,
+        })
+    }
+}
+
+/// Checks of the local ledger against the execution venue's account, when the venue keeps one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReconcileConfig {
+    pub enabled: bool,
+    /// Feed time between checks
+    pub interval_secs: u64,
+    pub position_tolerance_eth: f64,
+    pub cash_tolerance_usd: f64,
+}
+
+impl Default for ReconcileConfig {
+    fn default() -> Self {
+        let tolerance = ReconcileTolerance::default();
+        Self {
+            enabled: true,
+            interval_secs: DEFAULT_RECONCILE_INTERVAL.as_secs(),
+            position_tolerance_eth: tolerance.position_eth,
+            cash_tolerance_usd: tolerance.cash_usd,
+        }
+    }
+}
+
+impl ReconcileConfig {
+    pub fn reconciler(&self) -> Option<Reconciler> {
+        self.enabled.then(|| {
+            Reconciler::new(
+                Duration::from_secs(self.interval_secs),
+                ReconcileTolerance {
+                    position_eth: self.position_tolerance_eth,
+                    cash_usd: self.cash_tolerance_usd,
+                },
+            )
+        })
+    }
+}
+
+/// Recurring sessions run by `schedule run`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScheduleConfig {
+    pub sessions: Vec<ScheduledSessionConfig>,
+}
+
+/// One recurring window, times in UTC
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledSessionConfig {
+    pub name: String,
+    /// `daily`, `weekdays`, `weekends` or a list such as `mon-wed,fri`
+    pub days: String,
+    /// `HH:MM`
+    pub start: String,
+    pub end: String,
+}
+
+impl ScheduleConfig {
+    pub fn calendar(&self) -> Result<Calendar> {
+        let windows = self
+            .sessions
+            .iter()
+            .map(|s| SessionWindow::new(&s.name, &s.days, &s.start, &s.end))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Calendar::new(windows))
+    }
+}
+
+/// Resting limit orders instead of per-cycle fill decisions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrdersConfig {
+    pub resting: bool,
+    /// Re-price a resting order only when the quote moves further than this
+    pub amend_threshold_bps: f64,
+}
+
+impl Default for OrdersConfig {
+    fn default() -> Self {
+        Self {
+            resting: false,
+            amend_threshold_bps: OrderManager::default().amend_threshold_bps(),
+        }
+    }
+}
+
+impl OrdersConfig {
+    pub fn manager(&self) -> Option<OrderManager> {
+        self.resting.then(|| OrderManager::new(self.amend_threshold_bps))
+    }
+}
+
+/// Paper trading on Binance Spot Testnet: resting orders are placed there and fill only when the
+/// testnet fills them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaperConfig {
+    pub enabled: bool,
+    pub api_url: String,
+    /// Credentials are read but never written back out, e.g. by `--print-config`
+    #[serde(skip_serializing)]
+    pub api_key: Option<String>,
+    #[serde(skip_serializing)]
+    pub api_secret: Option<String>,
+    pub symbol: String,
+    /// How long after signing the testnet still accepts a request
+    pub recv_window_ms: u64,
+}
+
+impl Default for PaperConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            api_url: TESTNET_API_URL.to_string(),
+            api_key: None,
+            api_secret: None,
+            symbol: "ETHUSDT".to_string(),
+            recv_window_ms: 5_000,
+        }
+    }
+}
+
+impl PaperConfig {
+    /// The testnet account to trade on, when paper trading is on
+    pub fn account(&self) -> Result<Option<TestnetAccount>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        let (Some(api_key), Some(api_secret)) = (&self.api_key, &self.api_secret) else {
+            bail!("paper trading needs testnet API keys (paper.api_key / paper.api_secret or MM_PAPER_API_KEY / MM_PAPER_API_SECRET)");
+        };
+        Ok(Some(TestnetAccount {
+            api_url: self.api_url.trim_end_matches('/').to_string(),
+            api_key: api_key.clone(),
+            api_secret: api_secret.clone(),
+            symbol: self.symbol.to_ascii_uppercase(),
+            recv_window_ms: self.recv_window_ms,
+        }))
+    }
+}
+
+/// Pair the DEX sources quote, and tokens added to the built-in registry (ETH, USDC, USDT, SOL)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokensConfig {
+    pub base: String,
+    pub quote: String,
+    /// EVM chain the CowSwap and Uniswap legs trade on
+    pub chain: String,
+    /// Extra tokens, or replacements for built-in ones with the same symbol
+    pub assets: Vec<Token>,
+}
+
+impl Default for TokensConfig {
+    fn default() -> Self {
+        Self {
+            base: "ETH".to_string(),
+            quote: "USDC".to_string(),
+            chain: tokens::ETHEREUM.to_string(),
+            assets: Vec::new(),
+        }
+    }
+}
+
+impl TokensConfig {
+    pub fn registry(&self) -> TokenRegistry {
+        self.assets.iter().cloned().fold(TokenRegistry::builtin(), TokenRegistry::with_token)
+    }
+
+    pub fn pair(&self) -> Result<TradingPair> {
+        self.registry().pair(&self.base, &self.quote, &self.chain)
+    }
+}
+
+/// Streaming trades to an HTTP endpoint; disabled without a URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportConfig {
+    pub http_url: Option<String>,
+    /// Full `Authorization` header value, e.g. `Bearer <token>`
+    pub http_authorization: Option<String>,
+    pub batch_size: usize,
+    pub flush_interval_ms: u64,
+    pub max_retries: u32,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        let http = HttpExportConfig::new("");
+        Self {
+            http_url: None,
+            http_authorization: None,
+            batch_size: http.batch_size,
+            flush_interval_ms: http.flush_interval.as_millis() as u64,
+            max_retries: http.max_retries,
+        }
+    }
+}
+
+impl ExportConfig {
+    pub fn http(&self) -> Option<HttpExportConfig> {
+        let url = self.http_url.as_ref()?;
+        Some(HttpExportConfig {
+            authorization: self.http_authorization.clone(),
+            batch_size: self.batch_size,
+            flush_interval: Duration::from_millis(self.flush_interval_ms),
+            max_retries: self.max_retries,
+            ..HttpExportConfig::new(url.clone())
+        })
+    }
+}
+
+/// Embedded HTTP API over the running session; disabled without a listen address.
+/// Needs a build with the `api` feature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// Address to serve on, e.g. `127.0.0.1:8080`
+    pub listen: Option<String>,
+    /// Events queued per streaming client before `slow_consumer` applies
+    pub stream_buffer: usize,
+    /// Full client queues: `drop_oldest`, `drop_newest` or `disconnect`
+    pub slow_consumer: String,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        let flow = FlowControl::default();
+        Self {
+            listen: None,
+            stream_buffer: flow.buffer,
+            slow_consumer: flow.policy.to_string(),
+        }
+    }
+}
+
+impl ApiConfig {
+    pub fn flow_control(&self) -> Result<FlowControl> {
+        if self.stream_buffer == 0 {
+            bail!("api.stream_buffer must be at least 1");
+        }
+        Ok(FlowControl {
+            buffer: self.stream_buffer,
+            policy: self.slow_consumer.parse()?,
+        })
+    }
+}
+
+/// Webhook notifications of drawdowns, feed outages, risk breaches and the session summary;
+/// disabled without a webhook URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotifyConfig {
+    /// Slack or Discord incoming webhook
+    pub webhook_url: Option<String>,
+    /// Equity this far below its peak notifies; off when unset
+    pub drawdown_usd: Option<f64>,
+    /// No quote from any source for this long notifies; 0 turns it off
+    pub sources_down_secs: u64,
+    pub risk_breaches: bool,
+    pub session_end: bool,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        let policy = NotifyPolicy::new("");
+        Self {
+            webhook_url: None,
+            drawdown_usd: policy.drawdown_usd,
+            sources_down_secs: policy.sources_down.map_or(0, |d| d.as_secs()),
+            risk_breaches: policy.risk_breaches,
+            session_end: policy.session_end,
+        }
+    }
+}
+
+impl NotifyConfig {
+    pub fn policy(&self) -> Result<Option<NotifyPolicy>> {
+        let Some(url) = self.webhook_url.as_ref() else {
+            return Ok(None);
+        };
+        if let Some(usd) = self.drawdown_usd {
+            if !(usd.is_finite() && usd > 0.0) {
+                bail!("notify.drawdown_usd must be a positive amount, got {}", usd);
+            }
+        }
+        Ok(Some(NotifyPolicy {
+            drawdown_usd: self.drawdown_usd,
+            sources_down: (self.sources_down_secs > 0).then(|| Duration::from_secs(self.sources_down_secs)),
+            risk_breaches: self.risk_breaches,
+            session_end: self.session_end,
+            ..NotifyPolicy::new(url.clone())
+        }))
+    }
+}
+
+/// Trades and quote updates published to Kafka or NATS; disabled without a bus URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PublishConfig {
+    /// `kafka://host:9092[,host:9092]` or `nats://host:4222`
+    pub url: Option<String>,
+    /// Kafka topic or NATS subject for trades
+    pub trades_topic: String,
+    /// Kafka topic or NATS subject for quote updates; empty leaves quotes off the bus
+    pub quotes_topic: String,
+}
+
+impl Default for PublishConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            trades_topic: "mm.trades".to_string(),
+            quotes_topic: "mm.quotes".to_string(),
+        }
+    }
+}
+
+impl PublishConfig {
+    pub fn target(&self) -> Result<Option<PublishTarget>> {
+        let Some(url) = &self.url else {
+            return Ok(None);
+        };
+        Ok(Some(PublishTarget {
+            url: url.parse()?,
+            trades_topic: self.trades_topic.clone(),
+            quotes_topic: (!self.quotes_topic.is_empty()).then(|| self.quotes_topic.clone()),
+        }))
+    }
+}
+
+/// Websocket stream of quotes, trades and stats; disabled without a listen address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebSocketConfig {
+    /// Address to serve on, e.g. `127.0.0.1:9001`
+    pub listen: Option<String>,
+    /// File the bound address is written to once serving, for a listen address on port 0
+    pub addr_file: Option<String>,
+    /// How often clients get a stats event
+    pub stats_interval_secs: u64,
+    /// Events queued per client before `slow_consumer` applies
+    pub buffer: usize,
+    /// Full client queues: `drop_oldest`, `drop_newest` or `disconnect`
+    pub slow_consumer: String,
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        let flow = FlowControl::default();
+        Self {
+            listen: None,
+            addr_file: None,
+            stats_interval_secs: 5,
+            buffer: flow.buffer,
+            slow_consumer: flow.policy.to_string(),
+        }
+    }
+}
+
+impl WebSocketConfig {
+    pub fn flow_control(&self) -> Result<FlowControl> {
+        if self.buffer == 0 {
+            bail!("websocket.buffer must be at least 1");
+        }
+        Ok(FlowControl {
+            buffer: self.buffer,
+            policy: self.slow_consumer.parse()?,
+        })
+    }
+
+    pub fn stats_interval(&self) -> Duration {
+        Duration::from_secs(self.stats_interval_secs)
+    }
+}
+
+/// Per-venue overrides of the default fee schedule, as `[fees.<venue>]` tables
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeesConfig {
+    pub binance: VenueFeesConfig,
+    pub jupiter: VenueFeesConfig,
+    pub cowswap: VenueFeesConfig,
+    pub uniswap: VenueFeesConfig,
+}
+
+/// Unset keys keep the venue's default
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VenueFeesConfig {
+    pub maker_bps: Option<f64>,
+    pub taker_bps: Option<f64>,
+    pub gas_usd: Option<f64>,
+    pub slippage_bps_per_million: Option<f64>,
+}
+
+impl VenueFeesConfig {
+    fn apply(&self, fees: &mut VenueFees) {
+        fees.maker_bps = self.maker_bps.unwrap_or(fees.maker_bps);
+        fees.taker_bps = self.taker_bps.unwrap_or(fees.taker_bps);
+        fees.gas_usd = self.gas_usd.unwrap_or(fees.gas_usd);
+        fees.slippage_bps_per_million = self.slippage_bps_per_million.unwrap_or(fees.slippage_bps_per_million);
+    }
+}
+
+impl FeesConfig {
+    pub fn venue(&self, source: Source) -> &VenueFeesConfig {
+        match source {
+            Source::Binance => &self.binance,
+            Source::Jupiter => &self.jupiter,
+            Source::CowSwap => &self.cowswap,
+            Source::Uniswap => &self.uniswap,
+        }
+    }
+}
+
+/// User-defined alert rules over the aggregated feed, as `[[alerts.rules]]` tables
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertsConfig {
+    pub rules: Vec<AlertRule>,
+}
+
+/// Pairs to run side by side in one session, as `[[markets.pairs]]` tables; empty runs the single `[tokens]` market
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarketsConfig {
+    pub pairs: Vec<MarketConfig>,
+}
+
+/// Parameters for synthetic price slots
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyntheticSettings {
+    pub initial_price: f64,
+    pub drift: f64,
+    pub volatility: f64,
+    pub spread_bps: f64,
+    pub tick_interval_ms: u64,
+    pub seed: u64,
+}
+
+impl Default for SyntheticSettings {
+    fn default() -> Self {
+        let defaults = SyntheticConfig::default();
+        Self {
+            initial_price: defaults.initial_price,
+            drift: defaults.drift,
+            volatility: defaults.volatility,
+            spread_bps: defaults.spread_bps,
+            tick_interval_ms: defaults.tick_interval.as_millis() as u64,
+            seed: defaults.seed,
+        }
+    }
+}
+
+impl SyntheticSettings {
+    pub fn config(&self) -> SyntheticConfig {
+        SyntheticConfig {
+            initial_price: self.initial_price,
+            drift: self.drift,
+            volatility: self.volatility,
+            spread_bps: self.spread_bps,
+            tick_interval: Duration::from_millis(self.tick_interval_ms),
+            seed: self.seed,
+        }
+    }
+}
+
+/// The offline order book venue (`--offline`); fair value follows the `[synthetic]` path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BookSimSettings {
+    pub venues: Vec<Source>,
+    pub levels: usize,
+    pub level_spacing_bps: f64,
+    pub depth_per_level_usd: f64,
+    pub refill_secs: f64,
+    /// Market orders per second at each venue
+    pub taker_orders_per_sec: f64,
+    pub mean_taker_usd: f64,
+    pub basis_bps: f64,
+}
+
+impl Default for BookSimSettings {
+    fn default() -> Self {
+        let defaults = BookSimConfig::default();
+        Self {
+            venues: defaults.venues,
+            levels: defaults.levels,
+            level_spacing_bps: defaults.level_spacing_bps,
+            depth_per_level_usd: defaults.depth_per_level_usd,
+            refill_secs: defaults.refill_secs,
+            taker_orders_per_sec: defaults.taker_orders_per_sec,
+            mean_taker_usd: defaults.mean_taker_usd,
+            basis_bps: defaults.basis_bps,
+        }
+    }
+}
+
+impl BookSimSettings {
+    /// Venue settings for a run of `simulation` around the `synthetic` fair value path
+    pub fn config(&self, synthetic: &SyntheticSettings, simulation: &SimulationConfig) -> BookSimConfig {
+        BookSimConfig {
+            fair: synthetic.config(),
+            venues: self.venues.clone(),
+            levels: self.levels,
+            level_spacing_bps: self.level_spacing_bps,
+            depth_per_level_usd: self.depth_per_level_usd,
+            refill_secs: self.refill_secs,
+            taker_orders_per_sec: self.taker_orders_per_sec,
+            mean_taker_usd: self.mean_taker_usd,
+            basis_bps: self.basis_bps,
+            duration: Duration::from_secs(simulation.duration_secs),
+            horizon: Duration::from_secs(simulation.trade_interval_secs),
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::load_with_origins(path)?.0)
+    }
+
+    /// Load a TOML file, noting which keys it set
+    pub fn load_with_origins(path: impl AsRef<Path>) -> Result<(Self, ConfigOrigins)> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read config {}", path.display()))?;
+        let config = toml::from_str(&data).with_context(|| format!("failed to parse config {}", path.display()))?;
+        let table: toml::Table = toml::from_str(&data).with_context(|| format!("failed to parse config {}", path.display()))?;
+
+        let mut origins = ConfigOrigins::default();
+        for (key, _) in flatten(&table) {
+            origins.set(&key, ConfigOrigin::File(path.to_path_buf()));
+        }
+        Ok((config, origins))
+    }
+
+    /// The trading engine as configured, before any command-line extras like draw replay or venue splitting
+    pub fn base_engine(&self, execution_model: Box<dyn ExecutionModel>) -> Result<TradingEngine> {
+        let engine = TradingEngine::with_execution_model(self.simulation.notional_per_trade, execution_model)
+            .with_edge_requirement(self.execution.edge_requirement())
+            .with_fees(self.fee_schedule())
+            .with_quoting(self.quoting.strategy()?)
+            .with_sizing(self.sizing.rule()?)
+            .with_vol_window(self.sizing.vol_window())
+            .with_quorum(self.quorum.policy())
+            .with_aggregation(self.aggregation.policy()?);
+        let engine = match &self.simulation.tag {
+            Some(tag) => engine.with_tag(tag),
+            None => engine,
+        };
+        let engine = match &self.execution.liquidity_profile {
+            Some(path) => engine.with_liquidity_profile(LiquidityProfile::load(path)?),
+            None => engine,
+        };
+        Ok(match self.execution.fill_latency() {
+            Some(latency) => engine.with_fill_latency(latency),
+            None => engine,
+        })
+    }
+
+    /// `base_engine` plus the config-driven taker, hedging, risk and resting-order layers of a trading session
+    pub fn session_engine(&self, execution_model: Box<dyn ExecutionModel>) -> Result<TradingEngine> {
+        let mut engine = self.base_engine(execution_model)?;
+        if let Some(policy) = self.execution.taker_policy() {
+            engine = engine.with_taker(policy);
+        }
+        if let Some(policy) = self.quoting.two_sided()? {
+            engine = engine.with_two_sided(policy);
+        }
+        if let Some(policy) = self.hedge.policy() {
+            engine = engine.with_hedger(policy);
+        }
+        if let Some(policy) = self.quiet.policy() {
+            engine = engine.with_quiet_market(policy);
+        }
+        if let Some(policy) = self.participation.policy() {
+            if engine.execution_model().traded_volume_usd().is_none() {
+                bail!("a participation cap needs a trade tape to measure market volume; only the offline venue (--offline) has one");
+            }
+            engine = engine.with_participation(policy);
+        }
+        if let Some(risk) = self.risk.manager()? {
+            engine = engine.with_risk(risk);
+        }
+        if let Some(orders) = self.orders.manager() {
+            engine = engine.with_order_manager(orders);
+        }
+        if self.paper.enabled {
+            if !engine.resting_orders() {
+                bail!("paper trading works resting limit orders on the testnet; turn them on with --resting-orders");
+            }
+            if engine.hedger().is_some() {
+                warn!("hedging is off while paper trading, the testnet only sees the resting orders");
+            }
+            engine = engine.with_external_fills();
+        }
+        Ok(engine)
+    }
+
+    /// Defaults, then the TOML file if given, then `MM_*` environment overrides
+    pub fn resolve(path: Option<&str>) -> Result<Self> {
+        Ok(Self::resolve_with_origins(path)?.0)
+    }
+
+    /// Like `resolve`, also returning where each value came from
+    pub fn resolve_with_origins(path: Option<&str>) -> Result<(Self, ConfigOrigins)> {
+        let (mut config, mut origins) = match path {
+            Some(path) => Self::load_with_origins(path)?,
+            None => (Self::default(), ConfigOrigins::default()),
+        };
+        config.apply_env_with_origins(&mut origins)?;
+        Ok((config, origins))
+    }
+
+    /// Apply one `section.key=value` assignment. The value is parsed as TOML, falling back to a plain string.
+    pub fn set_value(&mut self, assignment: &str) -> Result<()> {
+        let (key, raw) = assignment
+            .split_once('=')
+            .with_context(|| format!("override '{}' should look like section.key=value", assignment))?;
+        let key = key.trim();
+        let raw = raw.trim();
+        let value = toml::from_str::<toml::Table>(&format!("value = {}", raw))
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+            .unwrap_or_else(|| toml::Value::String(raw.to_string()));
+
+        let mut root = toml::Table::try_from(&*self).context("failed to serialize config")?;
+        let (parents, leaf) = match key.rsplit_once('.') {
+            Some((parents, leaf)) => (Some(parents), leaf),
+            None => (None, key),
+        };
+        let mut table = &mut root;
+        for part in parents.into_iter().flat_map(|p| p.split('.')) {
+            table = match table.entry(part).or_insert_with(|| toml::Value::Table(toml::Table::new())) {
+                toml::Value::Table(inner) => inner,
+                _ => bail!("'{}' in '{}' is not a config section", part, key),
+            };
+        }
+        table.insert(leaf.to_string(), value);
+
+        let updated: Config = toml::Value::Table(root)
+            .try_into()
+            .with_context(|| format!("invalid value for {}", key))?;
+        // Unknown keys are dropped on the way back in, so check the key survived
+        let round_trip = toml::Table::try_from(&updated).context("failed to serialize config")?;
+        if !flatten(&round_trip).iter().any(|(path, _)| path == key) {
+            bail!("unknown config key '{}'", key);
+        }
+        *self = updated;
+        Ok(())
+    }
+
+    /// Override values from `MM_*` environment variables
+    pub fn apply_env(&mut self) -> Result<()> {
+        self.apply_env_with_origins(&mut ConfigOrigins::default())
+    }
+
+    fn apply_env_with_origins(&mut self, origins: &mut ConfigOrigins) -> Result<()> {
+        let mut env = EnvOverrides { origins };
+
+        let sim = &mut self.simulation;
+        env.set(&mut sim.notional_per_trade, "NOTIONAL_PER_TRADE", "simulation.notional_per_trade")?;
+        env.set(&mut sim.duration_secs, "DURATION_SECS", "simulation.duration_secs")?;
+        env.set(&mut sim.trade_interval_secs, "TRADE_INTERVAL_SECS", "simulation.trade_interval_secs")?;
+        env.set(&mut sim.warmup_secs, "WARMUP_SECS", "simulation.warmup_secs")?;
+        env.set(&mut sim.cost_basis, "COST_BASIS", "simulation.cost_basis")?;
+        env.set(&mut sim.mark_interval_secs, "MARK_INTERVAL_SECS", "simulation.mark_interval_secs")?;
+        env.set_opt(&mut sim.tag, "TRADE_TAG", "simulation.tag")?;
+        env.set(&mut sim.max_trades_in_memory, "MAX_TRADES_IN_MEMORY", "simulation.max_trades_in_memory")?;
+        env.set_opt(&mut sim.trade_spill_path, "TRADE_SPILL_PATH", "simulation.trade_spill_path")?;
+
+        let exec = &mut self.execution;
+        env.set(&mut exec.model, "EXECUTION_MODEL", "execution.model")?;
+        env.set(&mut exec.ensemble_method, "ENSEMBLE_METHOD", "execution.ensemble_method")?;
+        env.set(&mut exec.basic_probability, "BASIC_PROBABILITY", "execution.basic_probability")?;
+        env.set(&mut exec.min_probability, "MIN_PROBABILITY", "execution.min_probability")?;
+        env.set(&mut exec.queue_tape_flow, "QUEUE_TAPE_FLOW", "execution.queue_tape_flow")?;
+        env.set(&mut exec.max_probability, "MAX_PROBABILITY", "execution.max_probability")?;
+        env.set_opt(&mut exec.taker_signal_bps, "TAKER_SIGNAL_BPS", "execution.taker_signal_bps")?;
+        env.set_opt(&mut exec.taker_fee_bps, "TAKER_FEE_BPS", "execution.taker_fee_bps")?;
+        env.set(&mut exec.taker_slippage_bps, "TAKER_SLIPPAGE_BPS", "execution.taker_slippage_bps")?;
+        env.set(&mut exec.require_min_edge, "REQUIRE_MIN_EDGE", "execution.require_min_edge")?;
+        env.set(&mut exec.min_edge_bps, "MIN_EDGE_BPS", "execution.min_edge_bps")?;
+        env.set_opt(&mut exec.maker_fee_bps, "MAKER_FEE_BPS", "execution.maker_fee_bps")?;
+        env.set(&mut exec.adverse_selection_bps, "ADVERSE_SELECTION_BPS", "execution.adverse_selection_bps")?;
+        env.set(&mut exec.fill_latency_ms, "FILL_LATENCY_MS", "execution.fill_latency_ms")?;
+        env.set_opt(&mut exec.liquidity_profile, "LIQUIDITY_PROFILE", "execution.liquidity_profile")?;
+
+        let sources = &mut self.sources;
+        env.set(&mut sources.binance_ws_url, "BINANCE_WS_URL", "sources.binance_ws_url")?;
+        env.set(&mut sources.binance_trades_ws_url, "BINANCE_TRADES_WS_URL", "sources.binance_trades_ws_url")?;
+        env.set(&mut sources.flow_window_secs, "FLOW_WINDOW_SECS", "sources.flow_window_secs")?;
+        env.set(&mut sources.jupiter_api_url, "JUPITER_API_URL", "sources.jupiter_api_url")?;
+        env.set(&mut sources.cowswap_api_url, "COWSWAP_API_URL", "sources.cowswap_api_url")?;
+        env.set_opt(&mut sources.quote_notional, "QUOTE_NOTIONAL", "sources.quote_notional")?;
+        env.set(&mut sources.jupiter_max_rps, "JUPITER_MAX_RPS", "sources.jupiter_max_rps")?;
+        env.set(&mut sources.cowswap_max_rps, "COWSWAP_MAX_RPS", "sources.cowswap_max_rps")?;
+        env.set(&mut sources.http_timeout_ms, "HTTP_TIMEOUT_MS", "sources.http_timeout_ms")?;
+        env.set(&mut sources.http_max_retries, "HTTP_MAX_RETRIES", "sources.http_max_retries")?;
+        env.set(&mut sources.http_retry_base_ms, "HTTP_RETRY_BASE_MS", "sources.http_retry_base_ms")?;
+        env.set(&mut sources.http_retry_max_ms, "HTTP_RETRY_MAX_MS", "sources.http_retry_max_ms")?;
+        env.set_opt(&mut sources.uniswap_rpc_url, "UNISWAP_RPC_URL", "sources.uniswap_rpc_url")?;
+        if sources.uniswap_rpc_url.is_none() {
+            if let Ok(url) = std::env::var("ETH_RPC_URL") {
+                sources.uniswap_rpc_url = Some(url);
+                env.origins.set("sources.uniswap_rpc_url", ConfigOrigin::Env("ETH_RPC_URL".to_string()));
+            }
+        }
+
+        let quoting = &mut self.quoting;
+        env.set(&mut quoting.strategy, "QUOTING_STRATEGY", "quoting.strategy")?;
+        env.set(&mut quoting.spread_bps, "QUOTING_SPREAD_BPS", "quoting.spread_bps")?;
+        env.set(&mut quoting.reference, "QUOTING_REFERENCE", "quoting.reference")?;
+        env.set(&mut quoting.bid_offset_bps, "QUOTING_BID_OFFSET_BPS", "quoting.bid_offset_bps")?;
+        env.set(&mut quoting.ask_offset_bps, "QUOTING_ASK_OFFSET_BPS", "quoting.ask_offset_bps")?;
+        env.set(&mut quoting.two_sided, "TWO_SIDED", "quoting.two_sided")?;
+        env.set(&mut quoting.fill_correlation, "FILL_CORRELATION", "quoting.fill_correlation")?;
+        env.set(&mut quoting.one_fill_per_cycle, "ONE_FILL_PER_CYCLE", "quoting.one_fill_per_cycle")?;
+
+        let sizing = &mut self.sizing;
+        env.set(&mut sizing.rule, "SIZING_RULE", "sizing.rule")?;
+        env.set(&mut sizing.max_inventory_eth, "MAX_INVENTORY_ETH", "sizing.max_inventory_eth")?;
+        env.set(&mut sizing.skew, "SIZING_SKEW", "sizing.skew")?;
+        env.set(&mut sizing.target_vol, "SIZING_TARGET_VOL", "sizing.target_vol")?;
+
+        let quorum = &mut self.quorum;
+        env.set(&mut quorum.min_sources, "MIN_SOURCES", "quorum.min_sources")?;
+        env.set(&mut quorum.max_quote_age_secs, "MAX_QUOTE_AGE_SECS", "quorum.max_quote_age_secs")?;
+        env.set(&mut quorum.allow_degraded, "ALLOW_DEGRADED", "quorum.allow_degraded")?;
+        env.set(&mut quorum.degraded_spread_bps, "DEGRADED_SPREAD_BPS", "quorum.degraded_spread_bps")?;
+        env.set(&mut quorum.degraded_size_factor, "DEGRADED_SIZE_FACTOR", "quorum.degraded_size_factor")?;
+
+        let aggregation = &mut self.aggregation;
+        env.set(&mut aggregation.policy, "AGGREGATION_POLICY", "aggregation.policy")?;
+        env.set(&mut aggregation.freshness_half_life_ms, "FRESHNESS_HALF_LIFE_MS", "aggregation.freshness_half_life_ms")?;
+
+        let orders = &mut self.orders;
+        env.set(&mut orders.resting, "RESTING_ORDERS", "orders.resting")?;
+        env.set(&mut orders.amend_threshold_bps, "AMEND_THRESHOLD_BPS", "orders.amend_threshold_bps")?;
+
+        let paper = &mut self.paper;
+        env.set(&mut paper.enabled, "PAPER", "paper.enabled")?;
+        env.set(&mut paper.api_url, "PAPER_API_URL", "paper.api_url")?;
+        env.set_opt(&mut paper.api_key, "PAPER_API_KEY", "paper.api_key")?;
+        env.set_opt(&mut paper.api_secret, "PAPER_API_SECRET", "paper.api_secret")?;
+        env.set(&mut paper.symbol, "PAPER_SYMBOL", "paper.symbol")?;
+
+        let tokens = &mut self.tokens;
+        env.set(&mut tokens.base, "BASE_TOKEN", "tokens.base")?;
+        env.set(&mut tokens.quote, "QUOTE_TOKEN", "tokens.quote")?;
+        env.set(&mut tokens.chain, "TOKEN_CHAIN", "tokens.chain")?;
+
+        let risk = &mut self.risk;
+        env.set_opt(&mut risk.max_inventory_eth, "RISK_MAX_INVENTORY_ETH", "risk.max_inventory_eth")?;
+        env.set_opt(&mut risk.max_drawdown_usd, "RISK_MAX_DRAWDOWN_USD", "risk.max_drawdown_usd")?;
+        env.set_opt(&mut risk.max_loss_per_hour_usd, "RISK_MAX_LOSS_PER_HOUR_USD", "risk.max_loss_per_hour_usd")?;
+        env.set_opt(&mut risk.max_consecutive_losses, "RISK_MAX_CONSECUTIVE_LOSSES", "risk.max_consecutive_losses")?;
+        env.set(&mut risk.breach_action, "RISK_BREACH_ACTION", "risk.breach_action")?;
+
+        let hedge = &mut self.hedge;
+        env.set_opt(&mut hedge.threshold_eth, "HEDGE_THRESHOLD_ETH", "hedge.threshold_eth")?;
+        env.set(&mut hedge.target_eth, "HEDGE_TARGET_ETH", "hedge.target_eth")?;
+
+        let funding = &mut self.funding;
+        env.set(&mut funding.long_rate, "FUNDING_LONG_RATE", "funding.long_rate")?;
+        env.set(&mut funding.short_rate, "FUNDING_SHORT_RATE", "funding.short_rate")?;
+
+        let quiet = &mut self.quiet;
+        env.set_opt(&mut quiet.max_spread_bps, "QUIET_MAX_SPREAD_BPS", "quiet.max_spread_bps")?;
+        env.set(&mut quiet.max_volatility, "QUIET_MAX_VOLATILITY", "quiet.max_volatility")?;
+        env.set(&mut quiet.min_quiet_secs, "QUIET_MIN_SECS", "quiet.min_quiet_secs")?;
+        env.set(&mut quiet.vol_window_secs, "QUIET_VOL_WINDOW_SECS", "quiet.vol_window_secs")?;
+        env.set(&mut quiet.pause, "QUIET_PAUSE", "quiet.pause")?;
+
+        let participation = &mut self.participation;
+        env.set_opt(&mut participation.max_pct, "PARTICIPATION_MAX_PCT", "participation.max_pct")?;
+        env.set(&mut participation.interval_secs, "PARTICIPATION_INTERVAL_SECS", "participation.interval_secs")?;
+
+        let reconcile = &mut self.reconcile;
+        env.set(&mut reconcile.enabled, "RECONCILE", "reconcile.enabled")?;
+        env.set(&mut reconcile.interval_secs, "RECONCILE_INTERVAL_SECS", "reconcile.interval_secs")?;
+
+        let export = &mut self.export;
+        env.set_opt(&mut export.http_url, "EXPORT_URL", "export.http_url")?;
+        env.set_opt(&mut export.http_authorization, "EXPORT_AUTHORIZATION", "export.http_authorization")?;
+        env.set(&mut export.batch_size, "EXPORT_BATCH_SIZE", "export.batch_size")?;
+        let api = &mut self.api;
+        env.set_opt(&mut api.listen, "API_LISTEN", "api.listen")?;
+        env.set(&mut api.stream_buffer, "API_STREAM_BUFFER", "api.stream_buffer")?;
+        env.set(&mut api.slow_consumer, "API_SLOW_CONSUMER", "api.slow_consumer")?;
+
+        let websocket = &mut self.websocket;
+        env.set_opt(&mut websocket.listen, "WS_LISTEN", "websocket.listen")?;
+        env.set(&mut websocket.stats_interval_secs, "WS_STATS_INTERVAL_SECS", "websocket.stats_interval_secs")?;
+        env.set(&mut websocket.buffer, "WS_BUFFER", "websocket.buffer")?;
+        env.set(&mut websocket.slow_consumer, "WS_SLOW_CONSUMER", "websocket.slow_consumer")?;
+
+        let notify = &mut self.notify;
+        env.set_opt(&mut notify.webhook_url, "NOTIFY_WEBHOOK_URL", "notify.webhook_url")?;
+        env.set_opt(&mut notify.drawdown_usd, "NOTIFY_DRAWDOWN_USD", "notify.drawdown_usd")?;
+        env.set(&mut notify.sources_down_secs, "NOTIFY_SOURCES_DOWN_SECS", "notify.sources_down_secs")?;
+        env.set(&mut notify.risk_breaches, "NOTIFY_RISK_BREACHES", "notify.risk_breaches")?;
+        env.set(&mut notify.session_end, "NOTIFY_SESSION_END", "notify.session_end")?;
+
+        let publish = &mut self.publish;
+        env.set_opt(&mut publish.url, "PUBLISH_URL", "publish.url")?;
+        env.set(&mut publish.trades_topic, "PUBLISH_TRADES_TOPIC", "publish.trades_topic")?;
+        env.set(&mut publish.quotes_topic, "PUBLISH_QUOTES_TOPIC", "publish.quotes_topic")?;
+
+        let synthetic = &mut self.synthetic;
+        env.set(&mut synthetic.initial_price, "SYNTHETIC_PRICE", "synthetic.initial_price")?;
+        env.set(&mut synthetic.volatility, "SYNTHETIC_VOLATILITY", "synthetic.volatility")?;
+        env.set(&mut synthetic.seed, "SEED", "synthetic.seed")?;
+
+        let book_sim = &mut self.book_sim;
+        env.set(&mut book_sim.depth_per_level_usd, "BOOK_DEPTH_PER_LEVEL_USD", "book_sim.depth_per_level_usd")?;
+        env.set(&mut book_sim.taker_orders_per_sec, "BOOK_TAKER_ORDERS_PER_SEC", "book_sim.taker_orders_per_sec")?;
+        env.set(&mut book_sim.mean_taker_usd, "BOOK_MEAN_TAKER_USD", "book_sim.mean_taker_usd")?;
+        Ok(())
+    }
+
+    /// Reject values that would stall or panic the session loop, checked once every layer is applied
+    pub fn validate(&self) -> Result<()> {
+        for (key, value) in [
+            ("simulation.trade_interval_secs", self.simulation.trade_interval_secs),
+            ("sources.jupiter_poll_secs", self.sources.jupiter_poll_secs),
+            ("sources.cowswap_poll_secs", self.sources.cowswap_poll_secs),
+            ("sources.uniswap_poll_secs", self.sources.uniswap_poll_secs),
+            ("synthetic.tick_interval_ms", self.synthetic.tick_interval_ms),
+            ("export.flush_interval_ms", self.export.flush_interval_ms),
+            ("sources.http_timeout_ms", self.sources.http_timeout_ms),
+            ("websocket.stats_interval_secs", self.websocket.stats_interval_secs),
+        ] {
+            if value == 0 {
+                bail!("{} must be at least 1", key);
+            }
+        }
+        for (key, rps) in [
+            ("sources.jupiter_max_rps", self.sources.jupiter_max_rps),
+            ("sources.cowswap_max_rps", self.sources.cowswap_max_rps),
+        ] {
+            if !(rps == 0.0 || (MIN_MAX_RPS..=MAX_MAX_RPS).contains(&rps)) {
+                bail!("{} must be 0 (unlimited) or between {} and {}, got {}", key, MIN_MAX_RPS, MAX_MAX_RPS, rps);
+            }
+        }
+        self.quoting.strategy()?;
+        self.funding.validate()?;
+        MarketConfig::validate_all(&self.markets.pairs)?;
+        Ok(())
+    }
+
+    /// Copy with credentials and URLs that tend to embed them blanked, for sharing
+    pub fn redacted(&self) -> Config {
+        let mut config = self.clone();
+        for secret in [
+            &mut config.paper.api_key,
+            &mut config.paper.api_secret,
+            &mut config.export.http_authorization,
+            &mut config.notify.webhook_url,
+            &mut config.publish.url,
+            &mut config.sources.uniswap_rpc_url,
+        ] {
+            if secret.is_some() {
+                *secret = Some("<redacted>".to_string());
+            }
+        }
+        config
+    }
+
+    /// The resolved config as TOML, each value annotated with where it came from
+    pub fn render_with_origins(&self, origins: &ConfigOrigins) -> Result<String> {
+        let table = toml::Table::try_from(self).context("failed to serialize config")?;
+        let mut out = String::new();
+        render_table(&mut out, "", &table, origins);
+        Ok(out)
+    }
+
+    /// Default venue fees with `[fees.<venue>]` overrides, then the execution-wide maker/taker overrides
+    pub fn fee_schedule(&self) -> FeeSchedule {
+        Source::ALL.iter().fold(FeeSchedule::new(), |schedule, &source| {
+            let mut fees = VenueFees::default_for(source);
+            self.fees.venue(source).apply(&mut fees);
+            fees.maker_bps = self.execution.maker_fee_bps.unwrap_or(fees.maker_bps);
+            fees.taker_bps = self.execution.taker_fee_bps.unwrap_or(fees.taker_bps);
+            schedule.with_venue(source, fees)
+        })
+    }
+
+    pub fn quote_notional(&self) -> f64 {
+        self.sources.quote_notional.unwrap_or(self.simulation.notional_per_trade)
+    }
+}
+
+/// Where a resolved config value came from, lowest precedence first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigOrigin {
+    Default,
+    File(PathBuf),
+    Env(String),
+    Cli,
+}
+
+impl fmt::Display for ConfigOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigOrigin::Default => write!(f, "default"),
+            ConfigOrigin::File(path) => write!(f, "file {}", path.display()),
+            ConfigOrigin::Env(name) => write!(f, "env {}", name),
+            ConfigOrigin::Cli => write!(f, "command line"),
+        }
+    }
+}
+
+/// Origin of every config value that didn't come from the defaults, keyed by dotted path (`execution.min_edge_bps`)
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOrigins {
+    origins: BTreeMap<String, ConfigOrigin>,
+}
+
+impl ConfigOrigins {
+    pub fn get(&self, key: &str) -> &ConfigOrigin {
+        self.origins.get(key).unwrap_or(&ConfigOrigin::Default)
+    }
+
+    pub fn set(&mut self, key: &str, origin: ConfigOrigin) {
+        self.origins.insert(key.to_string(), origin);
+    }
+
+    /// Attribute every value that differs between `before` and `after` to `origin`
+    pub fn record_changes(&mut self, before: &Config, after: &Config, origin: ConfigOrigin) -> Result<()> {
+        let before: BTreeMap<String, toml::Value> = flatten(&toml::Table::try_from(before)?).into_iter().collect();
+        for (key, value) in flatten(&toml::Table::try_from(after)?) {
+            if before.get(&key) != Some(&value) {
+                self.set(&key, origin.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Applies `MM_*` overrides, recording the keys they set
+struct EnvOverrides<'a> {
+    origins: &'a mut ConfigOrigins,
+}
+
+impl EnvOverrides<'_> {
+    fn set<T>(&mut self, field: &mut T, name: &str, key: &str) -> Result<()>
+    where
+        T: FromStr,
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        if let Some(value) = self.lookup(name, key)? {
+            *field = value;
+        }
+        Ok(())
+    }
+
+    fn set_opt<T>(&mut self, field: &mut Option<T>, name: &str, key: &str) -> Result<()>
+    where
+        T: FromStr,
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        if let Some(value) = self.lookup(name, key)? {
+            *field = Some(value);
+        }
+        Ok(())
+    }
+
+    fn lookup<T>(&mut self, name: &str, key: &str) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        let var = format!("{}{}", ENV_PREFIX, name);
+        let Ok(value) = std::env::var(&var) else {
+            return Ok(None);
+        };
+        let parsed = value
+            .parse()
+            .with_context(|| format!("invalid value '{}' for {}", value, var))?;
+        self.origins.set(key, ConfigOrigin::Env(var));
+        Ok(Some(parsed))
+    }
+}
+
+/// Leaf values of a TOML table by dotted key; arrays count as leaves
+pub(crate) fn flatten(table: &toml::Table) -> Vec<(String, toml::Value)> {
+    let mut leaves = Vec::new();
+    for (key, value) in table {
+        match value {
+            toml::Value::Table(inner) => {
+                leaves.extend(flatten(inner).into_iter().map(|(k, v)| (format!("{}.{}", key, k), v)));
+            }
+            _ => leaves.push((key.clone(), value.clone())),
+        }
+    }
+    leaves
+}
+
+fn render_table(out: &mut String, prefix: &str, table: &toml::Table, origins: &ConfigOrigins) {
+    let leaves: Vec<(String, String)> = table
+        .iter()
+        .filter(|(_, value)| !value.is_table())
+        .map(|(key, value)| (key.clone(), format!("{} = {}", key, value)))
+        .collect();
+    if !leaves.is_empty() {
+        if !prefix.is_empty() {
+            out.push_str(&format!("\n[{}]\n", prefix));
+        }
+        let width = leaves.iter().map(|(_, line)| line.len()).max().unwrap_or(0);
+        for (key, line) in leaves {
+            let path = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+            out.push_str(&format!("{:<width$}  # {}\n", line, origins.get(&path), width = width));
+        }
+    }
+    for (key, value) in table {
+        if let toml::Value::Table(inner) = value {
+            let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+            render_table(out, &path, inner, origins);
+        }
+    }
+}
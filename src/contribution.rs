@@ -0,0 +1,161 @@
+use crate::aggregator::{AggregatedPrices, Quote, Source};
+use crate::recorder::Tick;
+use std::fmt;
+
+/// Default spacing of the snapshots the analysis samples, in feed time
+pub const DEFAULT_SAMPLE_INTERVAL_MS: i64 = 1_000;
+
+/// How one source shaped the aggregate over a recording
+#[derive(Debug, Clone, Copy)]
+pub struct SourceContribution {
+    pub source: Source,
+    /// Samples in which the source held a quote
+    pub present: usize,
+    pub best_bid: usize,
+    pub best_ask: usize,
+    pub median_bid: usize,
+    pub median_ask: usize,
+    /// Samples with at least one other source, where dropping this one can be measured
+    pub compared: usize,
+    /// Mean change in the best bid/ask spread without this source, in bps; positive means wider
+    pub best_spread_change_bps: f64,
+    /// Mean change in the median bid/ask spread without this source, in bps
+    pub median_spread_change_bps: f64,
+    /// Mean absolute move of the median mid without this source, in bps
+    pub median_mid_shift_bps: f64,
+}
+
+impl SourceContribution {
+    fn new(source: Source) -> Self {
+        Self {
+            source,
+            present: 0,
+            best_bid: 0,
+            best_ask: 0,
+            median_bid: 0,
+            median_ask: 0,
+            compared: 0,
+            best_spread_change_bps: 0.0,
+            median_spread_change_bps: 0.0,
+            median_mid_shift_bps: 0.0,
+        }
+    }
+}
+
+/// Per-source contribution to the aggregated book, sampled on a fixed feed-time grid
+#[derive(Debug, Clone)]
+pub struct ContributionReport {
+    pub samples: usize,
+    pub interval_ms: i64,
+    pub span_ms: i64,
+    pub sources: Vec<SourceContribution>,
+}
+
+fn spread_bps(quote: &Quote) -> f64 {
+    let mid = (quote.bid + quote.ask) / 2.0;
+    (quote.ask - quote.bid) / mid * 10_000.0
+}
+
+/// Replay `ticks` into a book and, every `interval_ms` of feed time, record which source set the
+/// best and median bid/ask and how the aggregate changes with each source left out.
+/// Ties credit every source quoting the winning price.
+pub fn analyze(ticks: &[Tick], interval_ms: i64) -> ContributionReport {
+    let interval_ms = interval_ms.max(1);
+    let mut book = AggregatedPrices::default();
+    let mut sources: Vec<SourceContribution> = Source::ALL.iter().map(|&s| SourceContribution::new(s)).collect();
+    let mut samples = 0;
+    let mut next_sample = None;
+
+    let mut sample = |book: &AggregatedPrices, sources: &mut [SourceContribution]| {
+        let (Some(best), Some(median), Some(mid)) = (book.best_quote(), book.median_quote(), book.median_mid()) else {
+            return;
+        };
+        samples += 1;
+        let present: Vec<Source> = Source::ALL.iter().copied().filter(|&s| book.get(s).is_some()).collect();
+        for entry in sources.iter_mut() {
+            let Some(quote) = book.get(entry.source) else {
+                continue;
+            };
+            entry.present += 1;
+            entry.best_bid += (quote.bid == best.bid) as usize;
+            entry.best_ask += (quote.ask == best.ask) as usize;
+            entry.median_bid += (quote.bid == median.bid) as usize;
+            entry.median_ask += (quote.ask == median.ask) as usize;
+
+            let others: Vec<Source> = present.iter().copied().filter(|&s| s != entry.source).collect();
+            let without = book.restricted_to(&others);
+            let (Some(best_without), Some(median_without), Some(mid_without)) =
+                (without.best_quote(), without.median_quote(), without.median_mid())
+            else {
+                continue;
+            };
+            entry.compared += 1;
+            entry.best_spread_change_bps += spread_bps(&best_without) - spread_bps(&best);
+            entry.median_spread_change_bps += spread_bps(&median_without) - spread_bps(&median);
+            entry.median_mid_shift_bps += (mid_without - mid).abs() / mid * 10_000.0;
+        }
+    };
+
+    for tick in ticks {
+        let due = *next_sample.get_or_insert(tick.timestamp);
+        if tick.timestamp >= due {
+            sample(&book, &mut sources);
+            // Skip whole intervals a feed gap left empty rather than sampling the same book repeatedly
+            let missed = (tick.timestamp - due) / interval_ms;
+            next_sample = Some(due + (missed + 1) * interval_ms);
+        }
+        book.set(tick.source, tick.quote());
+    }
+
+    for entry in &mut sources {
+        if entry.compared > 0 {
+            let n = entry.compared as f64;
+            entry.best_spread_change_bps /= n;
+            entry.median_spread_change_bps /= n;
+            entry.median_mid_shift_bps /= n;
+        }
+    }
+    sources.retain(|entry| entry.present > 0);
+
+    let span_ms = match (ticks.first(), ticks.last()) {
+        (Some(first), Some(last)) => last.timestamp - first.timestamp,
+        _ => 0,
+    };
+    ContributionReport {
+        samples,
+        interval_ms,
+        span_ms,
+        sources,
+    }
+}
+
+impl fmt::Display for ContributionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Spread contribution: {} samples every {}ms over {:.1} min",
+            self.samples, self.interval_ms, self.span_ms as f64 / 60_000.0)?;
+        writeln!(f, "  {:<10} {:>8} {:>9} {:>9} {:>9} {:>9} {:>12} {:>12} {:>10}",
+            "SOURCE", "PRESENT", "BEST BID", "BEST ASK", "MED BID", "MED ASK", "Δ BEST bps", "Δ MED bps", "MID bps")?;
+        let pct = |count: usize| 100.0 * count as f64 / self.samples.max(1) as f64;
+        for entry in &self.sources {
+            let (best, median, shift) = if entry.compared > 0 {
+                (format!("{:+.2}", entry.best_spread_change_bps),
+                 format!("{:+.2}", entry.median_spread_change_bps),
+                 format!("{:.2}", entry.median_mid_shift_bps))
+            } else {
+                ("-".to_string(), "-".to_string(), "-".to_string())
+            };
+            writeln!(f, "  {:<10} {:>7.1}% {:>8.1}% {:>8.1}% {:>8.1}% {:>8.1}% {:>12} {:>12} {:>10}",
+                entry.source.name(),
+                pct(entry.present),
+                pct(entry.best_bid),
+                pct(entry.best_ask),
+                pct(entry.median_bid),
+                pct(entry.median_ask),
+                best,
+                median,
+                shift)?;
+        }
+        writeln!(f, "  Δ columns: mean change in the aggregate spread without the source (+ wider, - tighter);")?;
+        writeln!(f, "  MID: mean absolute move of the median mid without it")
+    }
+}
@@ -0,0 +1,206 @@
+use crate::aggregator::{AggregatedPrices, Source};
+use crate::introspect::StrategyInfo;
+use crate::pnl_tracker::PnLTracker;
+use crate::portfolio::PortfolioSnapshot;
+use crate::trader::TradeSide;
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+/// Requests sent to a running session from outside the trading loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlCommand {
+    /// Dump an interim summary without stopping the session
+    Summary,
+    /// Dump the active strategy, execution model and risk limits with their current parameters
+    Strategy,
+    /// Dump the portfolio snapshot of the last completed cycle
+    Portfolio,
+    /// End the session now, with the usual final summary
+    Stop,
+}
+
+impl ControlCommand {
+    fn parse(line: &str) -> Option<Self> {
+        match line.trim().to_ascii_lowercase().as_str() {
+            "s" | "summary" => Some(ControlCommand::Summary),
+            "strategy" => Some(ControlCommand::Strategy),
+            "p" | "portfolio" => Some(ControlCommand::Portfolio),
+            "q" | "quit" | "stop" => Some(ControlCommand::Stop),
+            _ => None,
+        }
+    }
+}
+
+/// Cleanups a second interrupt runs before exiting, since exiting skips destructors
+static BEFORE_FORCED_EXIT: Mutex<Vec<fn()>> = Mutex::new(Vec::new());
+
+/// Run `cleanup` if a second interrupt exits the process without finishing the session, e.g. to
+/// hand back the terminal
+pub fn before_forced_exit(cleanup: fn()) {
+    BEFORE_FORCED_EXIT.lock().unwrap_or_else(PoisonError::into_inner).push(cleanup);
+}
+
+fn forced_exit() -> ! {
+    for cleanup in BEFORE_FORCED_EXIT.lock().unwrap_or_else(PoisonError::into_inner).iter() {
+        cleanup();
+    }
+    std::process::exit(130)
+}
+
+/// Listen for control commands on SIGUSR1 (unix, summary only), Ctrl+C and SIGTERM (stop) and, with `stdin`, on stdin
+/// (`summary`, `strategy`, `portfolio` or `stop` + Enter). The sender is for front ends that own the terminal instead,
+/// like the dashboard.
+pub fn spawn_control_listener(stdin: bool) -> (mpsc::UnboundedSender<ControlCommand>, mpsc::UnboundedReceiver<ControlCommand>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let sender = tx.clone();
+
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let mut usr1 = match signal(SignalKind::user_defined1()) {
+                Ok(s) => s,
+                Err(e) => {
+                    error!(error = %e, "failed to install SIGUSR1 handler");
+                    return;
+                }
+            };
+            while usr1.recv().await.is_some() {
+                if tx.send(ControlCommand::Summary).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    // The first interrupt ends the session like `stop`, so the summary, exports and flushes still happen;
+    // a second one exits at once in case shutdown is stuck
+    let interrupts = tx.clone();
+    tokio::spawn(async move {
+        let mut stopping = false;
+        loop {
+            if let Err(e) = interrupted().await {
+                error!(error = %e, "failed to install interrupt handler");
+                return;
+            }
+            if stopping {
+                forced_exit();
+            }
+            info!("interrupted, finishing the session; interrupt again to exit immediately");
+            if interrupts.send(ControlCommand::Stop).is_err() {
+                forced_exit();
+            }
+            stopping = true;
+        }
+    });
+
+    // A plain thread rather than tokio's stdin, whose blocking read would hold up runtime shutdown until Enter
+    if stdin {
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if let Some(cmd) = ControlCommand::parse(&line) {
+                    if tx.send(cmd).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    (sender, rx)
+}
+
+/// Wait for Ctrl+C or, on unix, SIGTERM
+async fn interrupted() -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate = signal(SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await
+}
+
+/// Write stats, top trades and source status to `<dir>/<prefix>-interim-<timestamp>.txt`. `now_ms` is the
+/// session clock, so replays and offline runs stamp the summary and age quotes in their own time; the file
+/// name keeps the wall-clock time it was written at.
+pub async fn write_interim_summary(
+    dir: &Path,
+    prefix: &str,
+    tracker: &PnLTracker,
+    prices: &AggregatedPrices,
+    now_ms: i64,
+    elapsed_secs: u64,
+) -> Result<PathBuf> {
+    let timezone = tracker.timezone();
+    let stats = tracker.get_stats().await;
+    let mut out = String::new();
+
+    writeln!(out, "Interim summary for session {}", prefix)?;
+    writeln!(out, "Written at {} ({}s into the session)",
+        timezone.format_datetime(now_ms), elapsed_secs)?;
+    write!(out, "{}", stats)?;
+
+    writeln!(out, "Top 5 Trades:")?;
+    for trade in tracker.get_top_trades(5).await {
+        let side = match trade.side {
+            TradeSide::Buy => "BUY ",
+            TradeSide::Sell => "SELL",
+        };
+        writeln!(out, "  {} │ ${:.2} │ {:.4} ETH │ PnL: ${:.2}",
+            side, trade.price, trade.amount_eth, trade.pnl)?;
+    }
+
+    writeln!(out, "\nSource Status:")?;
+    for source in Source::ALL {
+        match prices.get(source) {
+            Some(q) => writeln!(out, "  {:<8} bid ${:.2} ask ${:.2} (age {}ms)",
+                source.name(), q.bid, q.ask, (now_ms - q.timestamp).max(0))?,
+            None => writeln!(out, "  {:<8} no data", source.name())?,
+        }
+    }
+
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create {}", dir.display()))?;
+    let path = dir.join(format!("{}-interim-{}.txt", prefix, chrono::Utc::now().format("%Y%m%d-%H%M%S")));
+    std::fs::write(&path, out)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+
+    Ok(path)
+}
+
+/// Write the active strategy as JSON to `<dir>/<prefix>-strategy-<timestamp>.json`
+pub fn write_strategy_info(dir: &Path, prefix: &str, info: &StrategyInfo) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create {}", dir.display()))?;
+    let path = dir.join(format!("{}-strategy-{}.json", prefix, chrono::Utc::now().format("%Y%m%d-%H%M%S")));
+    std::fs::write(&path, info.to_json()?)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+
+    Ok(path)
+}
+
+/// Write a portfolio snapshot as JSON to `<dir>/<prefix>-portfolio-<timestamp>.json`
+pub fn write_portfolio(dir: &Path, prefix: &str, snapshot: &PortfolioSnapshot) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create {}", dir.display()))?;
+    let path = dir.join(format!("{}-portfolio-{}.json", prefix, chrono::Utc::now().format("%Y%m%d-%H%M%S")));
+    std::fs::write(&path, snapshot.to_json()?)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+
+    Ok(path)
+}
@@ -0,0 +1,291 @@
+use crate::aggregator::{Quote, QuoteUpdate, Source};
+use crate::fees::FeeSchedule;
+use crate::trader::LiquidityRole;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::debug;
+
+/// A period during which one venue's bid stood above another venue's ask by more than the cost
+/// of taking both sides
+#[derive(Debug, Clone, Serialize)]
+pub struct Dislocation {
+    /// Venue whose ask was lifted
+    pub buy: Source,
+    /// Venue whose bid was hit
+    pub sell: Source,
+    pub start: i64,
+    pub end: i64,
+    /// Largest bid-over-ask gap during the window, before costs
+    pub peak_gross_bps: f64,
+    /// Largest gap net of taker fees, gas and slippage on both legs
+    pub peak_net_bps: f64,
+    /// Peak net edge applied to the trade notional
+    pub pnl_usd: f64,
+}
+
+impl Dislocation {
+    pub fn duration_ms(&self) -> i64 {
+        self.end - self.start
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PairArbStats {
+    pub buy: Source,
+    pub sell: Source,
+    pub dislocations: u32,
+    pub total_duration_ms: i64,
+    pub max_duration_ms: i64,
+    pub avg_net_bps: f64,
+    pub max_net_bps: f64,
+    pub theoretical_pnl_usd: f64,
+}
+
+impl PairArbStats {
+    pub fn avg_duration_ms(&self) -> f64 {
+        if self.dislocations > 0 {
+            self.total_duration_ms as f64 / self.dislocations as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrossArbReport {
+    pub notional: f64,
+    /// Venues that quoted at least once
+    pub venues: Vec<Source>,
+    /// Buy/sell venue pairs that dislocated at least once, most profitable first
+    pub pairs: Vec<PairArbStats>,
+    pub theoretical_pnl_usd: f64,
+}
+
+impl CrossArbReport {
+    pub fn dislocations(&self) -> u32 {
+        self.pairs.iter().map(|p| p.dislocations).sum()
+    }
+}
+
+impl fmt::Display for CrossArbReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Cross-Venue Arbitrage (net of taker costs, ${:.0} notional): {} dislocations │ theoretical PnL ${:.2}",
+            self.notional, self.dislocations(), self.theoretical_pnl_usd)?;
+        for p in &self.pairs {
+            writeln!(f, "  buy {:<8} sell {:<8} │ {:>4} windows │ avg {:>6.0}ms │ max {:>6}ms │ avg net {:>5.1} bps │ max {:>5.1} bps │ PnL ${:>10.2}",
+                p.buy.name(),
+                p.sell.name(),
+                p.dislocations,
+                p.avg_duration_ms(),
+                p.max_duration_ms,
+                p.avg_net_bps,
+                p.max_net_bps,
+                p.theoretical_pnl_usd
+            )?;
+        }
+        if self.venues.len() < 2 {
+            writeln!(f, "  fewer than two venues quoted, nothing to compare")?;
+        }
+        Ok(())
+    }
+}
+
+/// Quotes older than this are dropped before comparing venues
+pub const DEFAULT_ARB_QUOTE_AGE: Duration = Duration::from_secs(30);
+
+/// Closed windows kept for `dislocations`; the report's figures cover every window
+pub const MAX_DISLOCATIONS: usize = 1000;
+
+struct OpenDislocation {
+    start: i64,
+    peak_gross_bps: f64,
+    peak_net_bps: f64,
+}
+
+/// Compares every pair of venues on each quote update for a bid above another venue's ask that
+/// would still pay after taking both sides. A venue's quote counts until it's `max_quote_age` old;
+/// windows it was part of close at that point, so a dead venue can't hold one open.
+pub struct CrossArbDetector {
+    fees: FeeSchedule,
+    notional: f64,
+    max_quote_age_ms: i64,
+    quotes: HashMap<Source, Quote>,
+    /// Every venue that quoted, including ones whose quote has since gone stale
+    venues: Vec<Source>,
+    open: HashMap<(Source, Source), OpenDislocation>,
+    /// The latest closed windows, oldest first
+    dislocations: VecDeque<Dislocation>,
+    pairs: HashMap<(Source, Source), PairArbStats>,
+    last_time: i64,
+}
+
+impl CrossArbDetector {
+    pub fn new(fees: FeeSchedule, notional: f64) -> Self {
+        Self {
+            fees,
+            notional,
+            max_quote_age_ms: DEFAULT_ARB_QUOTE_AGE.as_millis() as i64,
+            quotes: HashMap::new(),
+            venues: Vec::new(),
+            open: HashMap::new(),
+            dislocations: VecDeque::new(),
+            pairs: HashMap::new(),
+            last_time: 0,
+        }
+    }
+
+    pub fn with_max_quote_age(mut self, max_age: Duration) -> Self {
+        self.max_quote_age_ms = max_age.as_millis() as i64;
+        self
+    }
+
+    /// Cost of taking both legs, in bps of the notional
+    fn round_trip_bps(&self, buy: Source, sell: Source) -> f64 {
+        let mut cost = self.fees.cost(buy, LiquidityRole::Taker, self.notional);
+        cost += self.fees.cost(sell, LiquidityRole::Taker, self.notional);
+        cost.bps_of(self.notional)
+    }
+
+    pub fn on_update(&mut self, update: &QuoteUpdate) {
+        let now = update.quote.timestamp;
+        self.last_time = self.last_time.max(now);
+        if !self.venues.contains(&update.source) {
+            self.venues.push(update.source);
+        }
+        self.quotes.insert(update.source, update.quote);
+        self.expire(now);
+
+        // Only pairs with the updated venue on one side can have changed
+        let others: Vec<Source> = self.quotes.keys().copied().filter(|&s| s != update.source).collect();
+        for other in others {
+            self.evaluate(update.source, other, now);
+            self.evaluate(other, update.source, now);
+        }
+    }
+
+    fn evaluate(&mut self, buy: Source, sell: Source, now: i64) {
+        let ask = self.quotes[&buy].ask;
+        let bid = self.quotes[&sell].bid;
+        let mid = (ask + bid) / 2.0;
+        let gross_bps = (bid - ask) / mid * 10000.0;
+        let net_bps = gross_bps - self.round_trip_bps(buy, sell);
+
+        match self.open.get_mut(&(buy, sell)) {
+            Some(open) if net_bps > 0.0 => {
+                open.peak_gross_bps = open.peak_gross_bps.max(gross_bps);
+                open.peak_net_bps = open.peak_net_bps.max(net_bps);
+            }
+            Some(_) => self.close(buy, sell, now),
+            None if net_bps > 0.0 => {
+                debug!("Dislocation opened: {} ask ${:.2} below {} bid ${:.2}, {:.1} bps net",
+                    buy.name(), ask, sell.name(), bid, net_bps);
+                self.open.insert((buy, sell), OpenDislocation {
+                    start: now,
+                    peak_gross_bps: gross_bps,
+                    peak_net_bps: net_bps,
+                });
+            }
+            None => {}
+        }
+    }
+
+    /// Drop quotes that are `max_quote_age` old by `now`, closing their windows when they went stale
+    fn expire(&mut self, now: i64) {
+        let stale: Vec<(Source, i64)> = self.quotes.iter()
+            .map(|(&source, quote)| (source, quote.timestamp + self.max_quote_age_ms))
+            .filter(|&(_, stale_at)| stale_at < now)
+            .collect();
+        for (source, stale_at) in stale {
+            self.quotes.remove(&source);
+            let pairs: Vec<(Source, Source)> = self.open.keys().copied().filter(|&(buy, sell)| buy == source || sell == source).collect();
+            for (buy, sell) in pairs {
+                debug!("Dislocation closed: {} quote went stale", source.name());
+                self.close(buy, sell, stale_at);
+            }
+        }
+    }
+
+    fn close(&mut self, buy: Source, sell: Source, now: i64) {
+        let Some(open) = self.open.remove(&(buy, sell)) else {
+            return;
+        };
+        let dislocation = Dislocation {
+            buy,
+            sell,
+            start: open.start,
+            end: now.max(open.start),
+            peak_gross_bps: open.peak_gross_bps,
+            peak_net_bps: open.peak_net_bps,
+            pnl_usd: open.peak_net_bps / 10000.0 * self.notional,
+        };
+        let stats = self.pairs.entry((buy, sell)).or_insert_with(|| PairArbStats {
+            buy,
+            sell,
+            dislocations: 0,
+            total_duration_ms: 0,
+            max_duration_ms: 0,
+            avg_net_bps: 0.0,
+            max_net_bps: 0.0,
+            theoretical_pnl_usd: 0.0,
+        });
+        stats.dislocations += 1;
+        stats.total_duration_ms += dislocation.duration_ms();
+        stats.max_duration_ms = stats.max_duration_ms.max(dislocation.duration_ms());
+        stats.avg_net_bps += (dislocation.peak_net_bps - stats.avg_net_bps) / stats.dislocations as f64;
+        stats.max_net_bps = stats.max_net_bps.max(dislocation.peak_net_bps);
+        stats.theoretical_pnl_usd += dislocation.pnl_usd;
+
+        if self.dislocations.len() == MAX_DISLOCATIONS {
+            self.dislocations.pop_front();
+        }
+        self.dislocations.push_back(dislocation);
+    }
+
+    /// Close any dislocations still open at the last observed time, or when a quote went stale
+    pub fn finish(&mut self) {
+        self.expire(self.last_time);
+        let pairs: Vec<(Source, Source)> = self.open.keys().copied().collect();
+        for (buy, sell) in pairs {
+            self.close(buy, sell, self.last_time);
+        }
+    }
+
+    /// The latest `MAX_DISLOCATIONS` closed windows, oldest first
+    pub fn dislocations(&self) -> impl Iterator<Item = &Dislocation> {
+        self.dislocations.iter()
+    }
+
+    pub fn report(&self) -> CrossArbReport {
+        let mut pairs: Vec<PairArbStats> = self.pairs.values().cloned().collect();
+        pairs.sort_by(|a, b| b.theoretical_pnl_usd.total_cmp(&a.theoretical_pnl_usd));
+
+        CrossArbReport {
+            notional: self.notional,
+            venues: Source::ALL.into_iter().filter(|s| self.venues.contains(s)).collect(),
+            theoretical_pnl_usd: pairs.iter().map(|p| p.theoretical_pnl_usd).sum(),
+            pairs,
+        }
+    }
+}
+
+/// Run a detector over a quote subscription in the background
+pub fn spawn_cross_arb_detector(mut updates: broadcast::Receiver<QuoteUpdate>, detector: CrossArbDetector) -> Arc<Mutex<CrossArbDetector>> {
+    let detector = Arc::new(Mutex::new(detector));
+    let handle = Arc::clone(&detector);
+
+    tokio::spawn(async move {
+        loop {
+            match updates.recv().await {
+                Ok(update) => handle.lock().unwrap().on_update(&update),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    detector
+}
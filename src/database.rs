@@ -0,0 +1,231 @@
+use crate::aggregator::{AggregatedPrices, Source};
+use crate::session::SessionRecord;
+use crate::trade_history::HistoryReader;
+use crate::trader::{LiquidityRole, Trade, TradeSide};
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+use std::sync::Mutex;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    id TEXT PRIMARY KEY,
+    name TEXT,
+    started_at INTEGER NOT NULL,
+    ended_at INTEGER NOT NULL,
+    execution_model TEXT NOT NULL,
+    notional_per_trade REAL NOT NULL,
+    cycles INTEGER NOT NULL,
+    total_trades INTEGER NOT NULL,
+    total_pnl REAL NOT NULL,
+    realized_pnl REAL NOT NULL,
+    unrealized_pnl REAL NOT NULL,
+    total_fees REAL NOT NULL,
+    config TEXT NOT NULL,
+    record TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS trades (
+    session_id TEXT NOT NULL,
+    id INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    side TEXT NOT NULL,
+    role TEXT NOT NULL,
+    hedge INTEGER NOT NULL,
+    price REAL NOT NULL,
+    amount_eth REAL NOT NULL,
+    notional_usd REAL NOT NULL,
+    pnl REAL NOT NULL,
+    fees_usd REAL NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS trades_session ON trades (session_id);
+CREATE TABLE IF NOT EXISTS quotes (
+    session_id TEXT NOT NULL,
+    cycle INTEGER NOT NULL,
+    source TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    bid REAL NOT NULL,
+    ask REAL NOT NULL
+);
+CREATE INDEX IF NOT EXISTS quotes_session ON quotes (session_id, cycle);
+";
+
+/// Trades of one session on one side and role, summed
+#[derive(Debug, Clone)]
+pub struct TradeSummary {
+    pub side: String,
+    pub role: String,
+    pub hedge: bool,
+    pub trades: u64,
+    pub notional_usd: f64,
+    pub pnl: f64,
+    pub fees_usd: f64,
+}
+
+/// SQLite database of sessions, their trades and the quote snapshot of every cycle, keyed by session id.
+/// Many sessions can share one file, so runs spread over days can be queried and compared later.
+pub struct Database {
+    conn: Mutex<Connection>,
+}
+
+impl Database {
+    /// Open or create the database at `path`
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let conn = Connection::open(path).with_context(|| format!("failed to open database {}", path.display()))?;
+        conn.execute_batch(SCHEMA)
+            .with_context(|| format!("failed to create tables in {}", path.display()))?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    /// Store the quote of every source in `prices` as the snapshot of `cycle`
+    pub fn record_quotes(&self, session_id: &str, cycle: u64, prices: &AggregatedPrices) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let mut insert = conn.prepare_cached(
+            "INSERT INTO quotes (session_id, cycle, source, timestamp, bid, ask) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for source in Source::ALL {
+            if let Some(quote) = prices.get(source) {
+                insert.execute(params![session_id, cycle as i64, source.name(), quote.timestamp, quote.bid, quote.ask])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Store a finished session with its trades and the config it ran with, replacing any earlier
+    /// copy of the same session
+    pub fn save_session(&self, record: &SessionRecord, trades: &HistoryReader, config: &str) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let stats = &record.stats;
+        tx.execute(
+            "INSERT OR REPLACE INTO sessions (id, name, started_at, ended_at, execution_model, notional_per_trade,
+                cycles, total_trades, total_pnl, realized_pnl, unrealized_pnl, total_fees, config, record)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            params![
+                record.id,
+                record.name,
+                record.started_at,
+                record.ended_at,
+                record.execution_model,
+                record.notional_per_trade,
+                record.cycles as i64,
+                stats.total_trades,
+                stats.total_pnl,
+                stats.realized_pnl,
+                stats.unrealized_pnl,
+                stats.total_fees,
+                config,
+                serde_json::to_string(record)?,
+            ],
+        )?;
+        tx.execute("DELETE FROM trades WHERE session_id = ?1", params![record.id])?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO trades (session_id, id, timestamp, side, role, hedge, price, amount_eth, notional_usd, pnl, fees_usd, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            )?;
+            trades.try_for_each(|trade| {
+                let side = match trade.side {
+                    TradeSide::Buy => "buy",
+                    TradeSide::Sell => "sell",
+                };
+                let role = match trade.role {
+                    LiquidityRole::Maker => "maker",
+                    LiquidityRole::Taker => "taker",
+                };
+                insert.execute(params![
+                    record.id,
+                    trade.id as i64,
+                    trade.timestamp,
+                    side,
+                    role,
+                    trade.hedge,
+                    trade.price,
+                    trade.amount_eth,
+                    trade.notional_usd,
+                    trade.pnl,
+                    trade.fees_usd,
+                    serde_json::to_string(trade)?,
+                ])?;
+                Ok(())
+            })?;
+        }
+        tx.commit().context("failed to save session")
+    }
+
+    /// Every stored session, oldest first
+    pub fn sessions(&self) -> Result<Vec<SessionRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut query = conn.prepare("SELECT record FROM sessions ORDER BY started_at")?;
+        let records = query.query_map([], |row| row.get::<_, String>(0))?;
+        records
+            .map(|record| Ok(serde_json::from_str(&record?)?))
+            .collect::<Result<Vec<_>>>()
+            .context("failed to read sessions")
+    }
+
+    /// A session by id or name
+    pub fn find(&self, key: &str) -> Result<Option<SessionRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let record: Option<String> = conn
+            .query_row(
+                "SELECT record FROM sessions WHERE id = ?1 OR name = ?1 ORDER BY started_at DESC LIMIT 1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()?;
+        record.map(|record| serde_json::from_str(&record).context("failed to read session")).transpose()
+    }
+
+    /// The config a session ran with, as TOML
+    pub fn config(&self, session_id: &str) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn
+            .query_row("SELECT config FROM sessions WHERE id = ?1", params![session_id], |row| row.get(0))
+            .optional()?)
+    }
+
+    /// A session's trades, in the order they were recorded
+    pub fn trades(&self, session_id: &str) -> Result<Vec<Trade>> {
+        let conn = self.conn.lock().unwrap();
+        let mut query = conn.prepare("SELECT data FROM trades WHERE session_id = ?1 ORDER BY rowid")?;
+        let trades = query.query_map(params![session_id], |row| row.get::<_, String>(0))?;
+        trades
+            .map(|trade| Ok(serde_json::from_str(&trade?)?))
+            .collect::<Result<Vec<_>>>()
+            .context("failed to read trades")
+    }
+
+    /// A session's trades summed by side, role and whether they were hedges
+    pub fn trade_summary(&self, session_id: &str) -> Result<Vec<TradeSummary>> {
+        let conn = self.conn.lock().unwrap();
+        let mut query = conn.prepare(
+            "SELECT side, role, hedge, COUNT(*), SUM(notional_usd), SUM(pnl), SUM(fees_usd)
+             FROM trades WHERE session_id = ?1 GROUP BY side, role, hedge ORDER BY side, role, hedge",
+        )?;
+        let rows = query.query_map(params![session_id], |row| {
+            Ok(TradeSummary {
+                side: row.get(0)?,
+                role: row.get(1)?,
+                hedge: row.get(2)?,
+                trades: row.get::<_, i64>(3)? as u64,
+                notional_usd: row.get(4)?,
+                pnl: row.get(5)?,
+                fees_usd: row.get(6)?,
+            })
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>().context("failed to summarize trades")
+    }
+
+    /// Cycles and quotes stored for a session
+    pub fn quote_counts(&self, session_id: &str) -> Result<(u64, u64)> {
+        let conn = self.conn.lock().unwrap();
+        let (cycles, quotes): (i64, i64) = conn.query_row(
+            "SELECT COUNT(DISTINCT cycle), COUNT(*) FROM quotes WHERE session_id = ?1",
+            params![session_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        Ok((cycles as u64, quotes as u64))
+    }
+}
@@ -0,0 +1,189 @@
+use crate::aggregator::AggregatedPrices;
+use crate::orders::RestingOrder;
+use crate::trader::{EdgeRequirement, ExecutionAttempt, LiquidityRole, Trade, TradeSide, TradingEngine};
+use std::fmt::Write;
+
+/// Seed of the offline venue and fill draws under `--demo`, so every demo run tells the same story
+pub const DEMO_SEED: u64 = 2024;
+/// Simulated time a demo covers
+pub const DEMO_DURATION_SECS: u64 = 120;
+
+/// Plain-language notes on each step of a session for `--demo`: where the quotes went and why,
+/// why they filled or not, and how each fill's PnL adds up. Every note is a few `↳` lines meant to
+/// sit right under the log line it explains.
+pub struct Narrator {
+    strategy: &'static str,
+    edge: Option<EdgeRequirement>,
+    taker_signal_bps: Option<f64>,
+}
+
+impl Narrator {
+    pub fn new(engine: &TradingEngine) -> Self {
+        Self {
+            strategy: engine.quoting().name(),
+            edge: engine.edge_requirement().copied(),
+            taker_signal_bps: engine.taker().map(|policy| policy.signal_bps),
+        }
+    }
+
+    /// What the demo is about to show, printed before the first cycle
+    pub fn intro(&self, seed: u64, interval_secs: u64) -> String {
+        let mut out = String::from("[DEMO] How to read this session:\n");
+        note(&mut out, &format!("Prices come from simulated order books on an offline venue (seed {}), so every run is identical", seed));
+        note(&mut out, &format!("Every {}s cycle we read the market, then offer to buy (our bid) and to sell (our ask)", interval_secs));
+        note(&mut out, &format!("Where they go is up to the '{}' quoting strategy: {}", self.strategy, strategy_summary(self.strategy)));
+        note(&mut out, "A quote fills when a random draw lands below the fill probability the execution models give it");
+        note(&mut out, "Each fill is marked against the market right away: that difference, less fees, is its PnL");
+        out
+    }
+
+    /// Where the fair value and spread in the `[MARKET]` line come from
+    pub fn market(&self, prices: &AggregatedPrices) -> String {
+        let mut out = String::new();
+        let (Some(mid), Some(best)) = (prices.median_mid(), prices.best_quote()) else {
+            return out;
+        };
+        note(&mut out, &format!("Fair value is the median of {} venue mids, ${:.2}; every quote below is measured from it",
+            prices.quotes().count(), mid));
+        note(&mut out, &format!("The best bid ${:.2} and best ask ${:.2} are the tightest prices any venue shows, the touch we compete with",
+            best.bid, best.ask));
+        out
+    }
+
+    /// Why a buy or sell attempt was priced where it was, and why it filled or not
+    pub fn attempt(&self, attempt: &ExecutionAttempt) -> String {
+        let mut out = String::new();
+        let side = quote_name(attempt.side);
+        if attempt.role == LiquidityRole::Taker {
+            // A buy takes the ask, a sell hits the bid
+            let opposite = match attempt.side {
+                TradeSide::Buy => "ask",
+                TradeSide::Sell => "bid",
+            };
+            note(&mut out, &format!("Why take: a venue's {} sat through fair value by more than the {:.1} bps taker signal, so we crossed the spread at ${:.2} instead of waiting",
+                opposite, self.taker_signal_bps.unwrap_or_default(), attempt.price));
+            if attempt.executed() || attempt.pending {
+                note(&mut out, "Why it filled: market orders trade against resting liquidity outright, no draw needed");
+            } else {
+                note(&mut out, "Why it didn't fill: the participation cap had no room left in its window");
+            }
+            return out;
+        }
+
+        let placement = if attempt.touch_distance_bps < 0.0 {
+            format!("{:.2} bps better than the best {}", -attempt.touch_distance_bps, side)
+        } else {
+            format!("{:.2} bps behind the best {}", attempt.touch_distance_bps, side)
+        };
+        note(&mut out, &format!("Why this quote: '{}' put our {} at ${:.2}, {}", self.strategy, side, attempt.price, placement));
+        match &self.edge {
+            Some(edge) => {
+                let verdict = if attempt.quoted { "clears" } else { "misses" };
+                note(&mut out, &format!("Expected edge {:.2} bps = distance from fair value − fees − {:.1} bps expected adverse move; {} the {:.1} bps minimum",
+                    attempt.expected_edge_bps, edge.adverse_selection_bps, verdict, edge.min_edge_bps));
+            }
+            None => note(&mut out, &format!("Expected edge {:.2} bps after fees; no minimum edge is set, so it's quoted either way",
+                attempt.expected_edge_bps)),
+        }
+        if !attempt.quoted {
+            note(&mut out, "So the quote was withheld: filling it would be expected to lose money");
+            return out;
+        }
+
+        let models = attempt.model_predictions.iter()
+            .map(|p| format!("{} {:.1}%", p.model, p.probability * 100.0))
+            .collect::<Vec<_>>();
+        let odds = if models.len() > 1 {
+            format!("{:.1}% ({})", attempt.execution_prob * 100.0, models.join(", "))
+        } else {
+            format!("{:.1}%", attempt.execution_prob * 100.0)
+        };
+        if attempt.executed() || attempt.pending {
+            note(&mut out, &format!("Why it filled: fill probability {}, the draw {:.3} came in under it", odds, attempt.draw));
+        } else {
+            note(&mut out, &format!("Why no fill: fill probability {}, the draw {:.3} came in over it, so nobody traded with us", odds, attempt.draw));
+        }
+        if let Some(trade) = &attempt.trade {
+            if trade.notional_usd + 1e-9 < attempt.order_notional {
+                note(&mut out, &format!("Only ${:.2} of the ${:.2} order filled: the participation cap limits us to a share of market volume",
+                    trade.notional_usd, attempt.order_notional));
+            }
+        }
+        if attempt.pending {
+            note(&mut out, "The fill is only confirmed after the fill latency, and marked at the prices of that moment");
+        }
+        out
+    }
+
+    /// Why a resting order went where it did
+    pub fn placed(&self, order: &RestingOrder) -> String {
+        let mut out = String::new();
+        let side = quote_name(order.side);
+        note(&mut out, &format!("Our {} rests on the book at ${:.2} ('{}' quoting, {:.2} bps expected edge) until the market trades into it or it's re-priced",
+            side, order.price, self.strategy, order.expected_edge_bps));
+        out
+    }
+
+    /// How a fill's PnL adds up, from the mark it was taken against down to the net after fees
+    pub fn pnl(&self, trade: &Trade) -> String {
+        let mut out = String::new();
+        if trade.amount_eth <= 0.0 {
+            return out;
+        }
+        // Buys mark at the best bid, what we could sell back at; sells at the best ask
+        let (mark, formula) = match trade.side {
+            TradeSide::Buy => {
+                let mark = trade.price + trade.gross_pnl / trade.amount_eth;
+                (mark, format!("(best bid ${:.2} − paid ${:.2})", mark, trade.price))
+            }
+            TradeSide::Sell => {
+                let mark = trade.price - trade.gross_pnl / trade.amount_eth;
+                (mark, format!("(sold ${:.2} − best ask ${:.2})", trade.price, mark))
+            }
+        };
+        let unwind = match trade.side {
+            TradeSide::Buy => "sell it straight back",
+            TradeSide::Sell => "buy it straight back",
+        };
+        note(&mut out, &format!("How PnL was computed: if we {} at ${:.2}, {} × {:.4} ETH = ${:.2} gross",
+            unwind, mark, formula, trade.amount_eth, trade.gross_pnl));
+        note(&mut out, &format!("Less ${:.2} venue fee, ${:.2} gas and ${:.2} slippage = ${:.2} net",
+            trade.fees.venue_fee_usd, trade.fees.gas_usd, trade.fees.slippage_usd, trade.pnl));
+        if trade.adverse_selection_usd.abs() > 1e-9 {
+            note(&mut out, &format!("${:.2} of gross was lost to the market moving while the fill was confirming", trade.adverse_selection_usd));
+        }
+        if trade.hedge {
+            note(&mut out, "This was a hedge: it pays to cross the spread to bring inventory back under its limit");
+        }
+        out
+    }
+
+    /// How the closing numbers relate to the fills above
+    pub fn outro(&self) -> String {
+        let mut out = String::from("[DEMO] Reading the summary:\n");
+        note(&mut out, "Total PnL is the sum of every fill's net PnL shown above, each marked at the moment it filled");
+        note(&mut out, "The final position is the ETH we were left holding, valued at the last fair value as unrealized PnL");
+        note(&mut out, "Run it again with a different --seed or --quoting fixed-spread --quote-spread-bps 10 and compare");
+        out
+    }
+}
+
+fn note(out: &mut String, text: &str) {
+    let _ = writeln!(out, "  ↳ {}", text);
+}
+
+/// Our quote on `side`, and the side of the book it competes with
+fn quote_name(side: TradeSide) -> &'static str {
+    match side {
+        TradeSide::Buy => "bid",
+        TradeSide::Sell => "ask",
+    }
+}
+
+fn strategy_summary(name: &str) -> &'static str {
+    match name {
+        "median" => "join the median bid and ask across venues, never worse than the typical venue",
+        "fixed-spread" => "a fixed spread centred on fair value",
+        _ => "a custom rule",
+    }
+}
@@ -0,0 +1,104 @@
+use anyhow::{Context, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use tracing::warn;
+
+/// Where the uniform draws behind fill decisions come from.
+///
+/// Every execution decision consumes exactly one draw, whether or not it quotes,
+/// so two strategies replaying the same ticks see the same luck decision for decision.
+#[derive(Default)]
+pub enum DrawSource {
+    #[default]
+    Random,
+    /// Reproducible draws from a fixed seed, for fully deterministic offline runs
+    Seeded(Box<Mutex<StdRng>>),
+    /// Fresh random draws, kept so they can be saved for a later run
+    Recording(Mutex<Vec<f64>>),
+    /// Draws from a previous run, falling back to random ones when exhausted
+    Replaying {
+        draws: Vec<f64>,
+        cursor: AtomicUsize,
+        exhausted: AtomicBool,
+    },
+}
+
+impl DrawSource {
+    pub fn recording() -> Self {
+        DrawSource::Recording(Mutex::new(Vec::new()))
+    }
+
+    pub fn seeded(seed: u64) -> Self {
+        DrawSource::Seeded(Box::new(Mutex::new(StdRng::seed_from_u64(seed))))
+    }
+
+    pub fn replaying(draws: Vec<f64>) -> Self {
+        DrawSource::Replaying {
+            draws,
+            cursor: AtomicUsize::new(0),
+            exhausted: AtomicBool::new(false),
+        }
+    }
+
+    /// Load draws saved by `save`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read draws {}", path.display()))?;
+        let draws: Vec<f64> = serde_json::from_str(&data)
+            .with_context(|| format!("failed to parse draws {}", path.display()))?;
+        Ok(Self::replaying(draws))
+    }
+
+    pub fn next(&self) -> f64 {
+        match self {
+            DrawSource::Random => rand::rng().random(),
+            DrawSource::Seeded(rng) => rng.lock().unwrap().random(),
+            DrawSource::Recording(draws) => {
+                let draw = rand::rng().random();
+                draws.lock().unwrap().push(draw);
+                draw
+            }
+            DrawSource::Replaying {
+                draws,
+                cursor,
+                exhausted,
+            } => {
+                let index = cursor.fetch_add(1, Ordering::Relaxed);
+                match draws.get(index) {
+                    Some(&draw) => draw,
+                    None => {
+                        if !exhausted.swap(true, Ordering::Relaxed) {
+                            warn!(decisions = draws.len(), "recorded draws exhausted, continuing with random draws");
+                        }
+                        rand::rng().random()
+                    }
+                }
+            }
+        }
+    }
+
+    /// Draws handed out so far (all recorded draws when recording)
+    pub fn consumed(&self) -> usize {
+        match self {
+            DrawSource::Random | DrawSource::Seeded(_) => 0,
+            DrawSource::Recording(draws) => draws.lock().unwrap().len(),
+            DrawSource::Replaying { cursor, .. } => cursor.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Write recorded draws as a JSON array; a no-op unless recording
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<usize> {
+        let DrawSource::Recording(draws) = self else {
+            return Ok(0);
+        };
+        let path = path.as_ref();
+        let draws = draws.lock().unwrap();
+        std::fs::write(path, serde_json::to_string(&*draws)?)
+            .with_context(|| format!("failed to write draws {}", path.display()))?;
+        Ok(draws.len())
+    }
+}
@@ -0,0 +1,406 @@
+use crate::aggregator::Source;
+use crate::amount;
+use crate::trader::{LiquidityRole, Trade, TradeSide};
+use anyhow::{Context, Result};
+use chrono::{TimeZone, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Instrument every report is for
+pub const SYMBOL: &str = "ETH/USDC";
+
+/// What happened to the order (FIX ExecType, tag 150)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecType {
+    New,
+    Replaced,
+    Canceled,
+    Trade,
+    Expired,
+}
+
+impl ExecType {
+    fn fix(self) -> &'static str {
+        match self {
+            ExecType::New => "0",
+            ExecType::Canceled => "4",
+            ExecType::Replaced => "5",
+            ExecType::Expired => "C",
+            ExecType::Trade => "F",
+        }
+    }
+}
+
+/// State of the order after the event (FIX OrdStatus, tag 39)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrdStatus {
+    New,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    Expired,
+}
+
+impl OrdStatus {
+    fn fix(self) -> &'static str {
+        match self {
+            OrdStatus::New => "0",
+            OrdStatus::PartiallyFilled => "1",
+            OrdStatus::Filled => "2",
+            OrdStatus::Canceled => "4",
+            OrdStatus::Expired => "C",
+        }
+    }
+}
+
+/// FIX OrdType, tag 40
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrdType {
+    Market,
+    Limit,
+}
+
+/// FIX TimeInForce, tag 59: resting orders stay until cancelled, everything else fills at once or expires
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeInForce {
+    GoodTillCancel,
+    ImmediateOrCancel,
+}
+
+/// One execution report: an order's state after a single event, with quantities in ETH
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionReport {
+    pub seq: u64,
+    pub session_id: String,
+    pub exec_id: String,
+    pub order_id: String,
+    pub exec_type: ExecType,
+    pub ord_status: OrdStatus,
+    pub symbol: &'static str,
+    pub side: TradeSide,
+    pub ord_type: OrdType,
+    pub time_in_force: TimeInForce,
+    /// Limit price; absent for market orders
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<f64>,
+    pub order_qty: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_qty: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_px: Option<f64>,
+    pub cum_qty: f64,
+    pub leaves_qty: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trade_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub liquidity: Option<LiquidityRole>,
+    /// Venue whose quote filled the order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_mkt: Option<Source>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub hedge: bool,
+    pub transact_time: i64,
+}
+
+impl ExecutionReport {
+    /// As a FIX 4.4 ExecutionReport (35=8), SOH-delimited with body length and checksum
+    pub fn to_fix(&self) -> String {
+        let mut fields = vec![
+            ("35", "8".to_string()),
+            ("49", "MARKET-MAKER".to_string()),
+            ("56", "DROP-COPY".to_string()),
+            ("34", self.seq.to_string()),
+            ("52", fix_time(self.transact_time)),
+            ("37", self.order_id.clone()),
+            ("11", self.order_id.clone()),
+            ("17", self.exec_id.clone()),
+            ("150", self.exec_type.fix().to_string()),
+            ("39", self.ord_status.fix().to_string()),
+            ("55", self.symbol.to_string()),
+            ("54", match self.side {
+                TradeSide::Buy => "1",
+                TradeSide::Sell => "2",
+            }
+            .to_string()),
+            ("40", match self.ord_type {
+                OrdType::Market => "1",
+                OrdType::Limit => "2",
+            }
+            .to_string()),
+            ("59", match self.time_in_force {
+                TimeInForce::GoodTillCancel => "1",
+                TimeInForce::ImmediateOrCancel => "3",
+            }
+            .to_string()),
+        ];
+        if let Some(price) = self.price {
+            fields.push(("44", format!("{:.6}", price)));
+        }
+        fields.push(("38", format!("{:.8}", self.order_qty)));
+        if let (Some(qty), Some(px)) = (self.last_qty, self.last_px) {
+            fields.push(("32", format!("{:.8}", qty)));
+            fields.push(("31", format!("{:.6}", px)));
+        }
+        fields.push(("14", format!("{:.8}", self.cum_qty)));
+        fields.push(("151", format!("{:.8}", self.leaves_qty)));
+        if let Some(venue) = self.last_mkt {
+            fields.push(("30", venue.name().to_string()));
+        }
+        if let Some(role) = self.liquidity {
+            // LastLiquidityInd: 1 added liquidity, 2 removed it
+            fields.push(("851", match role {
+                LiquidityRole::Maker => "1",
+                LiquidityRole::Taker => "2",
+            }
+            .to_string()));
+        }
+        if let Some(id) = self.trade_id {
+            fields.push(("880", id.to_string()));
+        }
+        fields.push(("60", fix_time(self.transact_time)));
+
+        let body: String = fields.iter().map(|(tag, value)| format!("{}={}\x01", tag, value)).collect();
+        let head = format!("8=FIX.4.4\x019={}\x01{}", body.len(), body);
+        let checksum = head.bytes().map(u32::from).sum::<u32>() % 256;
+        format!("{}10={:03}\x01", head, checksum)
+    }
+}
+
+/// FIX UTCTimestamp with milliseconds
+fn fix_time(timestamp: i64) -> String {
+    Utc.timestamp_millis_opt(timestamp)
+        .single()
+        .map(|time| time.format("%Y%m%d-%H:%M:%S%.3f").to_string())
+        .unwrap_or_default()
+}
+
+/// An order still live on the drop copy, for cumulative and leaves quantities
+#[derive(Debug, Clone)]
+struct LiveOrder {
+    side: TradeSide,
+    ord_type: OrdType,
+    time_in_force: TimeInForce,
+    price: Option<f64>,
+    /// Price quantities are converted at: the limit price, or the expected fill price of a market order
+    qty_price: f64,
+    notional: f64,
+    filled_notional: f64,
+    order_qty: f64,
+    cum_qty: f64,
+}
+
+impl LiveOrder {
+    /// ETH still to fill: the unfilled notional at the order's price
+    fn leaves_qty(&self) -> f64 {
+        let leaves = self.notional - self.filled_notional;
+        if leaves <= self.notional * 1e-9 {
+            return 0.0;
+        }
+        leaves / self.qty_price
+    }
+}
+
+/// Lifecycle event to report
+enum Event<'a> {
+    New,
+    Replaced,
+    Canceled,
+    Expired,
+    Fill { trade: &'a Trade, venue: Option<Source> },
+}
+
+struct DropCopyState {
+    writer: BufWriter<File>,
+    seq: u64,
+    next_order: u64,
+    orders: HashMap<String, LiveOrder>,
+    /// First write failure, surfaced by `flush` since the engine can't stop trading over it
+    error: Option<String>,
+}
+
+/// FIX drop-copy style log of every order event of a session: one execution report per new order,
+/// replace, cancel, fill and expiry, with order and execution ids, for post-trade tooling.
+/// Written as JSON lines, or as FIX tag=value messages when the path ends in `.fix`.
+pub struct DropCopy {
+    session_id: String,
+    fix: bool,
+    state: Mutex<DropCopyState>,
+}
+
+impl DropCopy {
+    pub fn create(path: impl AsRef<Path>, session_id: &str) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path).with_context(|| format!("failed to create drop copy {}", path.display()))?;
+        Ok(Self {
+            session_id: session_id.to_string(),
+            fix: path.extension().is_some_and(|ext| ext == "fix"),
+            state: Mutex::new(DropCopyState {
+                writer: BufWriter::new(file),
+                seq: 0,
+                next_order: 1,
+                orders: HashMap::new(),
+                error: None,
+            }),
+        })
+    }
+
+    /// Whether reports are written as FIX messages rather than JSON
+    pub fn is_fix(&self) -> bool {
+        self.fix
+    }
+
+    /// Reports written so far
+    pub fn reports(&self) -> u64 {
+        self.state.lock().unwrap().seq
+    }
+
+    /// Order id for an order the simulator doesn't number itself: `prefix` plus a session-wide counter
+    pub fn next_order_id(&self, prefix: &str) -> String {
+        let mut state = self.state.lock().unwrap();
+        let id = format!("{}{}", prefix, state.next_order);
+        state.next_order += 1;
+        id
+    }
+
+    /// A new order for `notional` USD at `price`; market orders report no price and are sized at
+    /// the price they're expected to fill at
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_order(
+        &self,
+        order_id: &str,
+        side: TradeSide,
+        ord_type: OrdType,
+        time_in_force: TimeInForce,
+        price: f64,
+        notional: f64,
+        timestamp: i64,
+    ) {
+        let order = LiveOrder {
+            side,
+            ord_type,
+            time_in_force,
+            price: (ord_type == OrdType::Limit).then_some(price),
+            qty_price: price,
+            notional,
+            filled_notional: 0.0,
+            order_qty: notional / price,
+            cum_qty: 0.0,
+        };
+        let mut state = self.state.lock().unwrap();
+        state.orders.insert(order_id.to_string(), order);
+        self.report(&mut state, order_id, Event::New, timestamp);
+    }
+
+    /// A live order re-priced to `price` with `leaves_notional` USD still to fill
+    pub fn replace(&self, order_id: &str, price: f64, leaves_notional: f64, timestamp: i64) {
+        let mut state = self.state.lock().unwrap();
+        let Some(order) = state.orders.get_mut(order_id) else {
+            return;
+        };
+        order.price = Some(price);
+        order.qty_price = price;
+        order.notional = order.filled_notional + leaves_notional;
+        order.order_qty = order.cum_qty + leaves_notional / price;
+        self.report(&mut state, order_id, Event::Replaced, timestamp);
+    }
+
+    /// A fill of a live order; it stays live while any of its notional is left
+    pub fn fill(&self, order_id: &str, trade: &Trade, venue: Option<Source>) {
+        let mut state = self.state.lock().unwrap();
+        let Some(order) = state.orders.get_mut(order_id) else {
+            return;
+        };
+        order.filled_notional = amount::add(order.filled_notional, trade.notional_usd);
+        order.cum_qty += trade.amount_eth;
+        self.report(&mut state, order_id, Event::Fill { trade, venue }, trade.timestamp);
+    }
+
+    /// We pulled a live order
+    pub fn cancel(&self, order_id: &str, timestamp: i64) {
+        let mut state = self.state.lock().unwrap();
+        self.report(&mut state, order_id, Event::Canceled, timestamp);
+    }
+
+    /// A live order ran out of time with whatever it hadn't filled; nothing for orders already done
+    pub fn expire(&self, order_id: &str, timestamp: i64) {
+        let mut state = self.state.lock().unwrap();
+        self.report(&mut state, order_id, Event::Expired, timestamp);
+    }
+
+    /// Write buffered reports, and fail with the first write error of the session, if any
+    pub fn flush(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(error) = state.error.take() {
+            anyhow::bail!("failed to write drop copy: {}", error);
+        }
+        state.writer.flush().context("failed to flush drop copy")
+    }
+
+    fn report(&self, state: &mut DropCopyState, order_id: &str, event: Event<'_>, timestamp: i64) {
+        let Some(order) = state.orders.get(order_id).cloned() else {
+            return;
+        };
+        let leaves = order.leaves_qty();
+        let (exec_type, ord_status, leaves_qty) = match event {
+            Event::New => (ExecType::New, OrdStatus::New, leaves),
+            Event::Replaced if order.cum_qty > 0.0 => (ExecType::Replaced, OrdStatus::PartiallyFilled, leaves),
+            Event::Replaced => (ExecType::Replaced, OrdStatus::New, leaves),
+            Event::Canceled => (ExecType::Canceled, OrdStatus::Canceled, 0.0),
+            Event::Expired if leaves <= 0.0 => return,
+            Event::Expired => (ExecType::Expired, OrdStatus::Expired, 0.0),
+            Event::Fill { .. } if leaves > 0.0 => (ExecType::Trade, OrdStatus::PartiallyFilled, leaves),
+            Event::Fill { .. } => (ExecType::Trade, OrdStatus::Filled, 0.0),
+        };
+        if leaves_qty <= 0.0 {
+            state.orders.remove(order_id);
+        }
+
+        state.seq += 1;
+        let (trade, venue) = match event {
+            Event::Fill { trade, venue } => (Some(trade), venue),
+            _ => (None, None),
+        };
+        let report = ExecutionReport {
+            seq: state.seq,
+            session_id: self.session_id.clone(),
+            exec_id: format!("{}-{}", self.session_id, state.seq),
+            order_id: order_id.to_string(),
+            exec_type,
+            ord_status,
+            symbol: SYMBOL,
+            side: order.side,
+            ord_type: order.ord_type,
+            time_in_force: order.time_in_force,
+            price: order.price,
+            order_qty: order.order_qty,
+            last_qty: trade.map(|t| t.amount_eth),
+            last_px: trade.map(|t| t.price),
+            cum_qty: order.cum_qty,
+            leaves_qty,
+            trade_id: trade.map(|t| t.id),
+            liquidity: trade.map(|t| t.role),
+            last_mkt: venue,
+            hedge: trade.is_some_and(|t| t.hedge),
+            transact_time: timestamp,
+        };
+
+        let written = if self.fix {
+            writeln!(state.writer, "{}", report.to_fix()).map_err(anyhow::Error::from)
+        } else {
+            serde_json::to_writer(&mut state.writer, &report)
+                .map_err(anyhow::Error::from)
+                .and_then(|_| Ok(state.writer.write_all(b"\n")?))
+        };
+        if let Err(e) = written {
+            state.error.get_or_insert_with(|| e.to_string());
+        }
+    }
+}
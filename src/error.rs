@@ -0,0 +1,109 @@
+use crate::aggregator::Source;
+use crate::http::RateLimited;
+use serde::Serialize;
+use std::fmt;
+use std::num::{ParseFloatError, ParseIntError};
+use std::time::Duration;
+
+/// Why a price source failed, as reported on the aggregator's event channel
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MarketMakerError {
+    /// The source couldn't be reached, or the connection to it dropped
+    Connection { source: Source, message: String },
+    /// The source answered, but with something that couldn't be read as a quote
+    Parse { source: Source, message: String },
+    /// The source turned requests away with HTTP 429 and retries ran out
+    RateLimited { source: Source, retry_after_ms: Option<u64> },
+    /// The source's last quote is older than the staleness limit
+    StaleData { source: Source, age_ms: i64, max_age_ms: i64 },
+    /// The source's trade stream dropped or sent a trade that couldn't be read; its quotes are unaffected
+    TradeStream { source: Source, message: String },
+}
+
+impl MarketMakerError {
+    pub fn connection(source: Source, error: impl fmt::Display) -> Self {
+        MarketMakerError::Connection { source, message: error.to_string() }
+    }
+
+    pub fn parse(source: Source, error: impl fmt::Display) -> Self {
+        MarketMakerError::Parse { source, message: error.to_string() }
+    }
+
+    pub fn trade_stream(source: Source, error: impl fmt::Display) -> Self {
+        MarketMakerError::TradeStream { source, message: error.to_string() }
+    }
+
+    pub fn stale(source: Source, age_ms: i64, max_age: Duration) -> Self {
+        MarketMakerError::StaleData { source, age_ms, max_age_ms: max_age.as_millis() as i64 }
+    }
+
+    /// Classify a failed fetch from `source` by what's in its error chain. Errors the chain
+    /// doesn't explain, like a quote that fails validation, count as parse errors.
+    pub fn from_fetch(source: Source, error: &anyhow::Error) -> Self {
+        for cause in error.chain() {
+            if let Some(limited) = cause.downcast_ref::<RateLimited>() {
+                return MarketMakerError::RateLimited {
+                    source,
+                    retry_after_ms: limited.retry_after.map(|d| d.as_millis() as u64),
+                };
+            }
+            if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+                return if e.is_decode() {
+                    Self::parse(source, error)
+                } else {
+                    Self::connection(source, error)
+                };
+            }
+            if cause.is::<tokio_tungstenite::tungstenite::Error>() || cause.is::<std::io::Error>() {
+                return Self::connection(source, error);
+            }
+            if cause.is::<serde_json::Error>() || cause.is::<ParseFloatError>() || cause.is::<ParseIntError>() {
+                return Self::parse(source, error);
+            }
+        }
+        // Keep the context chain, e.g. which Uniswap fee tier failed
+        Self::parse(source, format!("{:#}", error))
+    }
+
+    /// The price source that failed
+    pub fn feed(&self) -> Source {
+        match self {
+            MarketMakerError::Connection { source, .. }
+            | MarketMakerError::Parse { source, .. }
+            | MarketMakerError::RateLimited { source, .. }
+            | MarketMakerError::StaleData { source, .. }
+            | MarketMakerError::TradeStream { source, .. } => *source,
+        }
+    }
+
+    /// Short name of the variant, for counting errors by kind
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MarketMakerError::Connection { .. } => "connection",
+            MarketMakerError::Parse { .. } => "parse",
+            MarketMakerError::RateLimited { .. } => "rate limited",
+            MarketMakerError::StaleData { .. } => "stale",
+            MarketMakerError::TradeStream { .. } => "trade stream",
+        }
+    }
+}
+
+impl fmt::Display for MarketMakerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarketMakerError::Connection { source, message } => write!(f, "{} connection error: {}", source.name(), message),
+            MarketMakerError::Parse { source, message } => write!(f, "{} sent an unreadable response: {}", source.name(), message),
+            MarketMakerError::RateLimited { source, retry_after_ms: Some(ms) } => {
+                write!(f, "{} rate limited us (HTTP 429), retry after {}ms", source.name(), ms)
+            }
+            MarketMakerError::RateLimited { source, retry_after_ms: None } => write!(f, "{} rate limited us (HTTP 429)", source.name()),
+            MarketMakerError::StaleData { source, age_ms, max_age_ms } => {
+                write!(f, "{} quote is {}ms old, over the {}ms limit", source.name(), age_ms, max_age_ms)
+            }
+            MarketMakerError::TradeStream { source, message } => write!(f, "{} trade stream error: {}", source.name(), message),
+        }
+    }
+}
+
+impl std::error::Error for MarketMakerError {}
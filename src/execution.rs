@@ -0,0 +1,369 @@
+use crate::flow::FlowImbalance;
+use crate::reconcile::VenueAccount;
+use crate::trader::{Trade, TradeSide};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// Prices an execution model sees when estimating a fill probability
+#[derive(Debug, Clone, Copy)]
+pub struct FillContext {
+    pub our_price: f64,
+    /// Reference price on our side under the engine's aggregation policy; the median by default
+    pub median_price: f64,
+    pub best_price: f64,
+    pub side: TradeSide,
+    /// USD size of the quote
+    pub notional: f64,
+    /// Taker flow on the tape, when a trade stream is running
+    pub flow: Option<FlowImbalance>,
+}
+
+impl FillContext {
+    /// How far our price sits behind the best market price, in bps (0 at or through the touch)
+    pub fn distance_from_best_bps(&self) -> f64 {
+        if self.best_price <= 0.0 {
+            return 0.0;
+        }
+        let behind = match self.side {
+            TradeSide::Buy => self.best_price - self.our_price,
+            TradeSide::Sell => self.our_price - self.best_price,
+        };
+        (behind / self.best_price * 10000.0).max(0.0)
+    }
+
+    /// How far our price improves on the best market price, in bps (0 at or behind the touch)
+    pub fn improvement_bps(&self) -> f64 {
+        if self.best_price <= 0.0 {
+            return 0.0;
+        }
+        let ahead = match self.side {
+            TradeSide::Buy => self.our_price - self.best_price,
+            TradeSide::Sell => self.best_price - self.our_price,
+        };
+        (ahead / self.best_price * 10000.0).max(0.0)
+    }
+
+    /// Observed taker flow per second that trades against our side, if the tape is running
+    pub fn hitting_flow_usd_per_sec(&self) -> Option<f64> {
+        Some(self.flow?.hitting_usd_per_sec(self.side))
+    }
+}
+
+/// A single model's fill probability for one attempt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPrediction {
+    pub model: String,
+    pub probability: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnsembleMethod {
+    Mean,
+    Max,
+    Min,
+}
+
+impl std::str::FromStr for EnsembleMethod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "mean" | "average" | "avg" => Ok(EnsembleMethod::Mean),
+            "max" => Ok(EnsembleMethod::Max),
+            "min" => Ok(EnsembleMethod::Min),
+            other => bail!("unknown ensemble method '{}' (expected mean, max or min)", other),
+        }
+    }
+}
+
+/// Estimates how likely a passive quote is to fill. Implement this to plug in custom fill logic.
+pub trait ExecutionModel: Send + Sync {
+    fn name(&self) -> String;
+
+    /// Label with parameters for the startup banner
+    fn describe(&self) -> String {
+        self.name().to_uppercase()
+    }
+
+    fn probability(&self, ctx: &FillContext) -> f64;
+
+    /// Each component model's prediction (a single entry for non-ensemble models)
+    fn predictions(&self, ctx: &FillContext) -> Vec<ModelPrediction> {
+        vec![ModelPrediction {
+            model: self.name(),
+            probability: self.probability(ctx),
+        }]
+    }
+
+    /// Current parameter values, for introspection
+    fn parameters(&self) -> serde_json::Value {
+        serde_json::json!({})
+    }
+
+    /// Told about every fill executed against this model, for venues that keep our account
+    fn on_fill(&self, _trade: &Trade) {}
+
+    /// Balances the venue reports for our account; `None` for models that only price fills
+    fn account(&self) -> Option<VenueAccount> {
+        None
+    }
+
+    /// Running total of USD the market has traded, for venues with a trade tape
+    fn traded_volume_usd(&self) -> Option<f64> {
+        None
+    }
+}
+
+/// Fixed fill probability (70% by default)
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct BasicModel {
+    pub probability: f64,
+}
+
+impl Default for BasicModel {
+    fn default() -> Self {
+        Self { probability: 0.70 }
+    }
+}
+
+impl ExecutionModel for BasicModel {
+    fn name(&self) -> String {
+        "basic".to_string()
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    fn describe(&self) -> String {
+        format!("BASIC ({:.0}% fixed)", self.probability * 100.0)
+    }
+
+    fn probability(&self, _ctx: &FillContext) -> f64 {
+        self.probability
+    }
+}
+
+/// Linear interpolation between `min_probability` at the median and
+/// `max_probability` at the best price (20% and 90% by default)
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct AdvancedModel {
+    pub min_probability: f64,
+    pub max_probability: f64,
+}
+
+impl Default for AdvancedModel {
+    fn default() -> Self {
+        Self {
+            min_probability: 0.20,
+            max_probability: 0.90,
+        }
+    }
+}
+
+impl ExecutionModel for AdvancedModel {
+    fn name(&self) -> String {
+        "advanced".to_string()
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    fn describe(&self) -> String {
+        format!("ADVANCED ({:.0}%-{:.0}%)", self.min_probability * 100.0, self.max_probability * 100.0)
+    }
+
+    fn probability(&self, ctx: &FillContext) -> f64 {
+        advanced_probability(ctx, self.min_probability, self.max_probability)
+    }
+}
+
+/// Taker arrivals as a Poisson process whose intensity decays with distance
+/// from the touch: P = 1 - exp(-A * exp(-k * distance_bps) * horizon)
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PoissonModel {
+    pub arrival_rate: f64,
+    pub decay_per_bps: f64,
+    pub horizon_secs: f64,
+}
+
+impl PoissonModel {
+    pub fn new(horizon_secs: f64) -> Self {
+        Self {
+            arrival_rate: 0.5,
+            decay_per_bps: 0.3,
+            horizon_secs,
+        }
+    }
+}
+
+impl ExecutionModel for PoissonModel {
+    fn name(&self) -> String {
+        "poisson".to_string()
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    fn probability(&self, ctx: &FillContext) -> f64 {
+        let intensity = self.arrival_rate * (-self.decay_per_bps * ctx.distance_from_best_bps()).exp();
+        1.0 - (-intensity * self.horizon_secs).exp()
+    }
+}
+
+/// Queue position and price improvement.
+///
+/// Joining at or behind the touch puts us behind resting depth that grows with distance
+/// from the best price; improving on the touch puts us at the front of the queue and draws
+/// extra flow. Taker flow over the horizon is exponentially distributed, so the fill
+/// probability is the chance it exceeds the queue ahead plus our own size.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct QueueModel {
+    /// Resting size already at the best price when we join it
+    pub touch_queue_usd: f64,
+    /// Additional resting size per bp behind the best price
+    pub depth_per_bps_usd: f64,
+    /// Mean taker flow hitting our side
+    pub flow_usd_per_sec: f64,
+    /// Relative increase in flow per bp of price improvement
+    pub improvement_flow_per_bps: f64,
+    /// Use the flow observed on the tape when there is one, falling back to `flow_usd_per_sec`
+    pub tape_flow: bool,
+    pub max_probability: f64,
+    pub horizon_secs: f64,
+}
+
+impl QueueModel {
+    pub fn new(horizon_secs: f64) -> Self {
+        Self {
+            touch_queue_usd: 250_000.0,
+            depth_per_bps_usd: 100_000.0,
+            flow_usd_per_sec: 50_000.0,
+            improvement_flow_per_bps: 0.25,
+            tape_flow: false,
+            max_probability: 0.95,
+            horizon_secs,
+        }
+    }
+
+    /// Size resting ahead of our quote
+    pub fn queue_ahead_usd(&self, ctx: &FillContext) -> f64 {
+        if ctx.improvement_bps() > 0.0 {
+            0.0
+        } else {
+            self.touch_queue_usd + self.depth_per_bps_usd * ctx.distance_from_best_bps()
+        }
+    }
+}
+
+impl ExecutionModel for QueueModel {
+    fn name(&self) -> String {
+        "queue".to_string()
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    fn probability(&self, ctx: &FillContext) -> f64 {
+        let flow_usd_per_sec = match ctx.hitting_flow_usd_per_sec() {
+            Some(observed) if self.tape_flow => observed,
+            _ => self.flow_usd_per_sec,
+        };
+        let expected_flow = flow_usd_per_sec
+            * self.horizon_secs
+            * (1.0 + self.improvement_flow_per_bps * ctx.improvement_bps());
+        if expected_flow <= 0.0 {
+            return 0.0;
+        }
+        let needed = self.queue_ahead_usd(ctx) + ctx.notional;
+        (-needed / expected_flow).exp().min(self.max_probability)
+    }
+}
+
+/// Combine several models' probabilities
+pub struct EnsembleModel {
+    pub models: Vec<Box<dyn ExecutionModel>>,
+    pub method: EnsembleMethod,
+}
+
+impl ExecutionModel for EnsembleModel {
+    fn name(&self) -> String {
+        let names: Vec<String> = self.models.iter().map(|m| m.name()).collect();
+        format!("{:?}({})", self.method, names.join("+")).to_lowercase()
+    }
+
+    fn probability(&self, ctx: &FillContext) -> f64 {
+        let probs = self.models.iter().map(|m| m.probability(ctx));
+        match self.method {
+            EnsembleMethod::Mean => probs.sum::<f64>() / self.models.len().max(1) as f64,
+            EnsembleMethod::Max => probs.fold(0.0, f64::max),
+            EnsembleMethod::Min => probs.fold(1.0, f64::min),
+        }
+    }
+
+    fn predictions(&self, ctx: &FillContext) -> Vec<ModelPrediction> {
+        self.models.iter().flat_map(|m| m.predictions(ctx)).collect()
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        let models: Vec<_> = self
+            .models
+            .iter()
+            .map(|m| serde_json::json!({ "name": m.name(), "parameters": m.parameters() }))
+            .collect();
+        serde_json::json!({ "method": format!("{:?}", self.method).to_lowercase(), "models": models })
+    }
+}
+
+fn advanced_probability(ctx: &FillContext, min_probability: f64, max_probability: f64) -> f64 {
+    let FillContext {
+        our_price,
+        median_price,
+        best_price,
+        side,
+        ..
+    } = *ctx;
+
+    // Interpolate between the minimum and maximum probability
+    match side {
+        TradeSide::Buy => {
+            // For buying: higher price = more likely to get filled
+            // Best price = highest bid in market
+            if our_price >= best_price {
+                max_probability
+            } else if our_price <= median_price {
+                min_probability
+            } else {
+                // Linear interpolation
+                let range = best_price - median_price;
+                if range > 0.0 {
+                    let position = (our_price - median_price) / range;
+                    min_probability + position * (max_probability - min_probability)
+                } else {
+                    min_probability
+                }
+            }
+        }
+        TradeSide::Sell => {
+            // For selling: lower price = more likely to get filled
+            // Best price = lowest ask in market
+            if our_price <= best_price {
+                max_probability
+            } else if our_price >= median_price {
+                min_probability
+            } else {
+                // Linear interpolation
+                let range = median_price - best_price;
+                if range > 0.0 {
+                    let position = (median_price - our_price) / range;
+                    min_probability + position * (max_probability - min_probability)
+                } else {
+                    min_probability
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::health::ReconnectPolicy;
+use crate::trader::Trade;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{error, warn};
+
+/// Where and how trades are POSTed
+#[derive(Debug, Clone)]
+pub struct HttpExportConfig {
+    pub url: String,
+    /// Sent as the `Authorization` header when set
+    pub authorization: Option<String>,
+    pub batch_size: usize,
+    /// Send a partial batch after this long without filling it
+    pub flush_interval: Duration,
+    /// Retries per batch before it is dropped
+    pub max_retries: u32,
+    pub retry: ReconnectPolicy,
+    pub timeout: Duration,
+}
+
+impl HttpExportConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            authorization: None,
+            batch_size: 20,
+            flush_interval: Duration::from_secs(2),
+            max_retries: 5,
+            retry: ReconnectPolicy::default(),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Delivery counts once the exporter has drained
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExportStats {
+    pub sent: usize,
+    pub dropped: usize,
+    pub batches: usize,
+    pub retries: usize,
+}
+
+#[derive(Serialize)]
+struct TradeBatch<'a> {
+    session: &'a str,
+    trades: &'a [Trade],
+}
+
+/// Streams trades to an HTTP endpoint as JSON batches from a background task
+pub struct HttpTradeExporter {
+    tx: Mutex<Option<mpsc::UnboundedSender<Trade>>>,
+    handle: Mutex<Option<JoinHandle<ExportStats>>>,
+}
+
+impl HttpTradeExporter {
+    pub fn spawn(config: HttpExportConfig, session: String) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let handle = tokio::spawn(export_loop(config, session, rx));
+        Self {
+            tx: Mutex::new(Some(tx)),
+            handle: Mutex::new(Some(handle)),
+        }
+    }
+
+    /// Queue a trade for export; ignored after `finish`
+    pub fn send(&self, trade: &Trade) {
+        if let Some(tx) = self.tx.lock().unwrap().as_ref() {
+            let _ = tx.send(trade.clone());
+        }
+    }
+
+    /// Stop accepting trades and wait for queued ones to be delivered or dropped. Fails if the
+    /// export task panicked, since its counts are lost with it.
+    pub async fn finish(&self) -> Result<ExportStats> {
+        self.tx.lock().unwrap().take();
+        let handle = self.handle.lock().unwrap().take();
+        match handle {
+            Some(handle) => handle.await.context("trade export task failed"),
+            None => Ok(ExportStats::default()),
+        }
+    }
+}
+
+async fn export_loop(config: HttpExportConfig, session: String, mut rx: mpsc::UnboundedReceiver<Trade>) -> ExportStats {
+    let client = reqwest::Client::builder()
+        .timeout(config.timeout)
+        .build()
+        .unwrap_or_default();
+    let mut stats = ExportStats::default();
+    let mut batch = Vec::with_capacity(config.batch_size);
+    let mut ticker = tokio::time::interval(config.flush_interval);
+    ticker.tick().await;
+
+    loop {
+        tokio::select! {
+            trade = rx.recv() => match trade {
+                Some(trade) => {
+                    batch.push(trade);
+                    if batch.len() < config.batch_size.max(1) {
+                        continue;
+                    }
+                }
+                None => break,
+            },
+            _ = ticker.tick() => {
+                if batch.is_empty() {
+                    continue;
+                }
+            }
+        }
+        post_batch(&client, &config, &session, &mut batch, &mut stats).await;
+    }
+
+    if !batch.is_empty() {
+        post_batch(&client, &config, &session, &mut batch, &mut stats).await;
+    }
+    stats
+}
+
+async fn post_batch(client: &reqwest::Client, config: &HttpExportConfig, session: &str, batch: &mut Vec<Trade>, stats: &mut ExportStats) {
+    let body = TradeBatch { session, trades: batch };
+    let mut attempt = 0;
+    loop {
+        let mut request = client.post(&config.url).json(&body);
+        if let Some(auth) = &config.authorization {
+            request = request.header(reqwest::header::AUTHORIZATION, auth);
+        }
+
+        let error = match request.send().await {
+            Ok(resp) if resp.status().is_success() => {
+                stats.sent += batch.len();
+                stats.batches += 1;
+                batch.clear();
+                return;
+            }
+            // Other client errors won't succeed on retry
+            Ok(resp) if resp.status().is_client_error() && resp.status() != reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                error!(url = %config.url, trades = batch.len(), status = %resp.status(), "trade export batch rejected");
+                break;
+            }
+            Ok(resp) => format!("HTTP {}", resp.status()),
+            Err(e) => e.to_string(),
+        };
+
+        attempt += 1;
+        if attempt > config.max_retries {
+            warn!(trades = batch.len(), retries = config.max_retries, %error, "giving up on trade export batch");
+            break;
+        }
+        stats.retries += 1;
+        tokio::time::sleep(config.retry.delay(attempt)).await;
+    }
+
+    stats.dropped += batch.len();
+    batch.clear();
+}
@@ -0,0 +1,259 @@
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, Notify};
+
+/// What to do when a client's buffer is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlowConsumerPolicy {
+    /// Make room by discarding the client's oldest undelivered event
+    DropOldest,
+    /// Discard the new event; the client keeps what it has queued
+    DropNewest,
+    /// Close the client's stream
+    Disconnect,
+}
+
+impl FromStr for SlowConsumerPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().replace('-', "_").as_str() {
+            "drop_oldest" => Ok(SlowConsumerPolicy::DropOldest),
+            "drop_newest" => Ok(SlowConsumerPolicy::DropNewest),
+            "disconnect" => Ok(SlowConsumerPolicy::Disconnect),
+            other => bail!("unknown slow consumer policy '{}' (expected drop_oldest, drop_newest or disconnect)", other),
+        }
+    }
+}
+
+impl fmt::Display for SlowConsumerPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlowConsumerPolicy::DropOldest => write!(f, "drop_oldest"),
+            SlowConsumerPolicy::DropNewest => write!(f, "drop_newest"),
+            SlowConsumerPolicy::Disconnect => write!(f, "disconnect"),
+        }
+    }
+}
+
+/// Per-client buffering for streamed events
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FlowControl {
+    /// Most events queued for one client before the policy applies
+    pub buffer: usize,
+    pub policy: SlowConsumerPolicy,
+}
+
+impl Default for FlowControl {
+    fn default() -> Self {
+        Self {
+            buffer: 1024,
+            policy: SlowConsumerPolicy::DropOldest,
+        }
+    }
+}
+
+/// Delivery counts of one streaming client
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientStats {
+    pub id: u64,
+    pub label: String,
+    pub connected_at: i64,
+    pub delivered: u64,
+    pub dropped: u64,
+    /// Events waiting to be sent
+    pub queued: usize,
+}
+
+#[derive(Debug)]
+struct ClientState<T> {
+    queue: VecDeque<T>,
+    delivered: u64,
+    dropped: u64,
+    disconnected: bool,
+    /// The receiving end went away
+    closed: bool,
+}
+
+#[derive(Debug)]
+struct Client<T> {
+    id: u64,
+    label: String,
+    connected_at: i64,
+    state: Mutex<ClientState<T>>,
+    ready: Notify,
+    cut_off: Notify,
+}
+
+impl<T> Client<T> {
+    fn stats(&self) -> ClientStats {
+        let state = self.state.lock().unwrap();
+        ClientStats {
+            id: self.id,
+            label: self.label.clone(),
+            connected_at: self.connected_at,
+            delivered: state.delivered,
+            dropped: state.dropped,
+            queued: state.queue.len(),
+        }
+    }
+}
+
+/// Receiving end of one client's stream
+pub struct Subscription<T> {
+    client: Arc<Client<T>>,
+}
+
+impl<T> Subscription<T> {
+    pub fn id(&self) -> u64 {
+        self.client.id
+    }
+
+    /// Next event for this client; `None` once the disconnect policy has cut it off
+    pub async fn recv(&self) -> Option<T> {
+        loop {
+            {
+                let mut state = self.client.state.lock().unwrap();
+                if state.disconnected {
+                    return None;
+                }
+                if let Some(event) = state.queue.pop_front() {
+                    state.delivered += 1;
+                    return Some(event);
+                }
+            }
+            self.client.ready.notified().await;
+        }
+    }
+
+    /// Resolves once the disconnect policy cuts this client off, e.g. to abort a send that's
+    /// stuck on a client that stopped reading
+    pub async fn disconnected(&self) {
+        while !self.client.state.lock().unwrap().disconnected {
+            self.client.cut_off.notified().await;
+        }
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        let mut state = self.client.state.lock().unwrap();
+        state.closed = true;
+        state.queue.clear();
+    }
+}
+
+/// Fans events out to streaming clients, each through its own bounded queue, so publishing never
+/// waits on a client. A stalled client only loses its own events, as its flow control says,
+/// instead of back-pressuring the feed or the other clients.
+pub struct FanOut<T> {
+    flow: FlowControl,
+    clients: Mutex<Vec<Arc<Client<T>>>>,
+    next_id: AtomicU64,
+    /// Clients cut off by the disconnect policy over the fan-out's lifetime
+    disconnects: AtomicU64,
+}
+
+impl<T: Clone + Send + 'static> FanOut<T> {
+    pub fn new(flow: FlowControl) -> Self {
+        Self {
+            flow,
+            clients: Mutex::new(Vec::new()),
+            next_id: AtomicU64::new(1),
+            disconnects: AtomicU64::new(0),
+        }
+    }
+
+    pub fn flow_control(&self) -> FlowControl {
+        self.flow
+    }
+
+    /// Register a client, e.g. by its peer address, receiving every event published from now on
+    pub fn subscribe(&self, label: impl Into<String>) -> Subscription<T> {
+        let client = Arc::new(Client {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            label: label.into(),
+            connected_at: chrono::Utc::now().timestamp_millis(),
+            state: Mutex::new(ClientState {
+                queue: VecDeque::new(),
+                delivered: 0,
+                dropped: 0,
+                disconnected: false,
+                closed: false,
+            }),
+            ready: Notify::new(),
+            cut_off: Notify::new(),
+        });
+        self.clients.lock().unwrap().push(Arc::clone(&client));
+        Subscription { client }
+    }
+
+    /// Queue `event` for every connected client, applying the slow-consumer policy to full queues
+    pub fn publish(&self, event: T) {
+        let mut clients = self.clients.lock().unwrap();
+        clients.retain(|client| {
+            let mut state = client.state.lock().unwrap();
+            if state.closed || state.disconnected {
+                return false;
+            }
+            if state.queue.len() >= self.flow.buffer {
+                match self.flow.policy {
+                    SlowConsumerPolicy::DropOldest => {
+                        state.queue.pop_front();
+                        state.dropped += 1;
+                    }
+                    SlowConsumerPolicy::DropNewest => {
+                        state.dropped += 1;
+                        return true;
+                    }
+                    SlowConsumerPolicy::Disconnect => {
+                        state.dropped += state.queue.len() as u64 + 1;
+                        state.queue.clear();
+                        state.disconnected = true;
+                        self.disconnects.fetch_add(1, Ordering::Relaxed);
+                        drop(state);
+                        client.ready.notify_one();
+                        client.cut_off.notify_one();
+                        return false;
+                    }
+                }
+            }
+            state.queue.push_back(event.clone());
+            drop(state);
+            client.ready.notify_one();
+            true
+        });
+    }
+
+    /// Connected clients and their delivery counts
+    pub fn clients(&self) -> Vec<ClientStats> {
+        self.clients.lock().unwrap().iter().map(|client| client.stats()).collect()
+    }
+
+    pub fn disconnects(&self) -> u64 {
+        self.disconnects.load(Ordering::Relaxed)
+    }
+
+    /// Publish everything from a broadcast stream, e.g. a feed's quote updates, until it closes
+    pub fn spawn_from<U>(self: &Arc<Self>, mut updates: broadcast::Receiver<U>, convert: impl Fn(U) -> T + Send + 'static)
+    where
+        U: Clone + Send + 'static,
+    {
+        let fanout = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                match updates.recv().await {
+                    Ok(update) => fanout.publish(convert(update)),
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,127 @@
+use crate::aggregator::Source;
+use crate::amount;
+use crate::trader::LiquidityRole;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::AddAssign;
+
+/// Trading costs of one venue
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VenueFees {
+    pub maker_bps: f64,
+    pub taker_bps: f64,
+    /// Flat cost per fill: settlement gas, protocol fees
+    pub gas_usd: f64,
+    /// Price impact per $1M of notional, growing linearly with size (DEX pools)
+    pub slippage_bps_per_million: f64,
+}
+
+impl VenueFees {
+    pub fn default_for(source: Source) -> Self {
+        let (maker_bps, taker_bps, gas_usd, slippage_bps_per_million) = match source {
+            Source::Binance => (1.0, 4.0, 0.0, 0.0),
+            Source::Jupiter => (0.0, 0.0, 0.01, 2.0),
+            Source::CowSwap => (0.0, 0.0, 2.0, 1.0),
+            Source::Uniswap => (0.0, 0.0, 4.0, 3.0),
+        };
+        Self {
+            maker_bps,
+            taker_bps,
+            gas_usd,
+            slippage_bps_per_million,
+        }
+    }
+
+    pub fn zero() -> Self {
+        Self {
+            maker_bps: 0.0,
+            taker_bps: 0.0,
+            gas_usd: 0.0,
+            slippage_bps_per_million: 0.0,
+        }
+    }
+
+    pub fn cost(&self, role: LiquidityRole, notional: f64) -> FeeBreakdown {
+        let fee_bps = match role {
+            LiquidityRole::Maker => self.maker_bps,
+            LiquidityRole::Taker => self.taker_bps,
+        };
+        let slippage_bps = self.slippage_bps_per_million * notional / 1_000_000.0;
+        FeeBreakdown {
+            venue_fee_usd: notional * fee_bps / 10000.0,
+            gas_usd: self.gas_usd,
+            slippage_usd: notional * slippage_bps / 10000.0,
+        }
+    }
+}
+
+/// Costs of a fill, by kind
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeeBreakdown {
+    pub venue_fee_usd: f64,
+    pub gas_usd: f64,
+    pub slippage_usd: f64,
+}
+
+impl FeeBreakdown {
+    pub fn total(&self) -> f64 {
+        amount::sum([self.venue_fee_usd, self.gas_usd, self.slippage_usd])
+    }
+
+    /// Total as bps of `notional`
+    pub fn bps_of(&self, notional: f64) -> f64 {
+        if notional > 0.0 {
+            self.total() / notional * 10000.0
+        } else {
+            0.0
+        }
+    }
+}
+
+impl AddAssign for FeeBreakdown {
+    fn add_assign(&mut self, other: Self) {
+        self.venue_fee_usd = amount::add(self.venue_fee_usd, other.venue_fee_usd);
+        self.gas_usd = amount::add(self.gas_usd, other.gas_usd);
+        self.slippage_usd = amount::add(self.slippage_usd, other.slippage_usd);
+    }
+}
+
+/// Fees, gas and slippage for every venue a fill can land on
+#[derive(Debug, Clone)]
+pub struct FeeSchedule {
+    venues: HashMap<Source, VenueFees>,
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FeeSchedule {
+    pub fn new() -> Self {
+        Self {
+            venues: Source::ALL.iter().map(|&s| (s, VenueFees::default_for(s))).collect(),
+        }
+    }
+
+    /// No costs anywhere
+    pub fn zero() -> Self {
+        Self {
+            venues: Source::ALL.iter().map(|&s| (s, VenueFees::zero())).collect(),
+        }
+    }
+
+    pub fn with_venue(mut self, source: Source, fees: VenueFees) -> Self {
+        self.venues.insert(source, fees);
+        self
+    }
+
+    pub fn venue(&self, source: Source) -> VenueFees {
+        self.venues.get(&source).copied().unwrap_or_else(VenueFees::zero)
+    }
+
+    pub fn cost(&self, source: Source, role: LiquidityRole, notional: f64) -> FeeBreakdown {
+        self.venue(source).cost(role, notional)
+    }
+}
@@ -0,0 +1,102 @@
+use crate::trader::TradeSide;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// How far back tape trades are kept; the longest window `FlowTracker::imbalance` can cover
+pub const FLOW_HISTORY: Duration = Duration::from_secs(300);
+
+/// One aggressive trade off the tape
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TapeTrade {
+    pub timestamp: i64,
+    pub price: f64,
+    pub amount_eth: f64,
+    /// Side of the taker that crossed the spread
+    pub aggressor: TradeSide,
+}
+
+impl TapeTrade {
+    pub fn notional(&self) -> f64 {
+        self.price * self.amount_eth
+    }
+}
+
+/// What the trade stream delivers: its trades, and where it dropped
+#[derive(Debug, Clone, Copy)]
+pub enum TapeEvent {
+    Trade(TapeTrade),
+    /// The stream went down; trades are missing until the next one arrives
+    Dropped { timestamp: i64 },
+}
+
+/// Taker buy and sell volume over a window
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct FlowImbalance {
+    pub window_ms: i64,
+    pub buy_usd: f64,
+    pub sell_usd: f64,
+    pub trades: u32,
+    /// (buy - sell) / (buy + sell): +1 when takers only bought, -1 when they only sold, 0 without volume
+    pub imbalance: f64,
+}
+
+impl FlowImbalance {
+    pub fn volume_usd(&self) -> f64 {
+        self.buy_usd + self.sell_usd
+    }
+
+    /// Taker flow per second that trades against a resting quote on `side`: sellers hit bids, buyers lift asks
+    pub fn hitting_usd_per_sec(&self, side: TradeSide) -> f64 {
+        if self.window_ms <= 0 {
+            return 0.0;
+        }
+        let usd = match side {
+            TradeSide::Buy => self.sell_usd,
+            TradeSide::Sell => self.buy_usd,
+        };
+        usd / (self.window_ms as f64 / 1000.0)
+    }
+}
+
+/// Rolling record of the tape's taker trades
+#[derive(Debug, Default)]
+pub struct FlowTracker {
+    trades: VecDeque<TapeTrade>,
+}
+
+impl FlowTracker {
+    pub fn record(&mut self, trade: TapeTrade) {
+        self.trades.push_back(trade);
+        let cutoff = trade.timestamp - FLOW_HISTORY.as_millis() as i64;
+        while self.trades.front().is_some_and(|t| t.timestamp < cutoff) {
+            self.trades.pop_front();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trades.is_empty()
+    }
+
+    /// Buy/sell volume of the trades in the `window` up to `now`
+    pub fn imbalance(&self, now: i64, window: Duration) -> FlowImbalance {
+        let window_ms = window.min(FLOW_HISTORY).as_millis() as i64;
+        let since = now - window_ms;
+        let mut flow = FlowImbalance {
+            window_ms,
+            ..FlowImbalance::default()
+        };
+        for trade in self.trades.iter().rev().take_while(|t| t.timestamp > since) {
+            match trade.aggressor {
+                TradeSide::Buy => flow.buy_usd += trade.notional(),
+                TradeSide::Sell => flow.sell_usd += trade.notional(),
+            }
+            flow.trades += 1;
+        }
+        let volume = flow.volume_usd();
+        if volume > 0.0 {
+            flow.imbalance = (flow.buy_usd - flow.sell_usd) / volume;
+        }
+        flow
+    }
+}
@@ -0,0 +1,67 @@
+use crate::amount::{self, Exact};
+use serde::{Deserialize, Serialize};
+use std::ops::AddAssign;
+
+const MS_PER_YEAR: f64 = 365.0 * 24.0 * 3600.0 * 1000.0;
+
+/// Carry on open inventory: what holding ETH long (borrowed USD or the return given up) and short
+/// (borrowed ETH) costs, as annualized rates on the inventory's value at the mid
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct FundingRates {
+    /// Annualized rate charged on long inventory, e.g. 0.05 for 5%; negative earns
+    pub long_rate: f64,
+    /// Annualized rate charged on short inventory
+    pub short_rate: f64,
+}
+
+impl FundingRates {
+    pub fn new(long_rate: f64, short_rate: f64) -> Self {
+        Self { long_rate, short_rate }
+    }
+
+    /// Rate that applies to `net_eth` of inventory
+    pub fn rate(&self, net_eth: f64) -> f64 {
+        if net_eth >= 0.0 { self.long_rate } else { self.short_rate }
+    }
+
+    /// Cost in USD of holding `net_eth` at `mid` for `elapsed_ms`; negative when the rate pays
+    pub fn cost(&self, net_eth: f64, mid: f64, elapsed_ms: i64) -> f64 {
+        if net_eth == 0.0 || elapsed_ms <= 0 {
+            return 0.0;
+        }
+        net_eth.abs() * mid * self.rate(net_eth) * elapsed_ms as f64 / MS_PER_YEAR
+    }
+}
+
+/// Carry charged over a session, split by the side of the inventory it was charged on
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct FundingCost {
+    pub long_usd: f64,
+    pub short_usd: f64,
+    /// Exact sums behind `long_usd` and `short_usd`
+    #[serde(skip)]
+    exact: [Exact; 2],
+}
+
+impl FundingCost {
+    pub fn total(&self) -> f64 {
+        amount::add(self.long_usd, self.short_usd)
+    }
+
+    /// Charge `rates` on holding `net_eth` at `mid` for `elapsed_ms`
+    pub fn accrue(&mut self, rates: &FundingRates, net_eth: f64, mid: f64, elapsed_ms: i64) {
+        let cost = rates.cost(net_eth, mid, elapsed_ms);
+        if net_eth > 0.0 {
+            self.exact[0].add(&mut self.long_usd, cost);
+        } else if net_eth < 0.0 {
+            self.exact[1].add(&mut self.short_usd, cost);
+        }
+    }
+}
+
+impl AddAssign for FundingCost {
+    fn add_assign(&mut self, other: Self) {
+        self.exact[0].merge(&mut self.long_usd, other.exact[0], other.long_usd);
+        self.exact[1].merge(&mut self.short_usd, other.exact[1], other.short_usd);
+    }
+}
@@ -0,0 +1,456 @@
+use crate::aggregator::QuoteUpdate;
+use crate::position::Position;
+use crate::session::SessionStore;
+use crate::trader::{LiquidityRole, Trade, TradeSide};
+use anyhow::{Context, Result};
+use futures_util::{Stream, StreamExt};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tonic::service::Interceptor;
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+pub mod proto {
+    tonic::include_proto!("marketmaker.v1");
+}
+
+use proto::market_maker_server::{MarketMaker, MarketMakerServer};
+use proto::{SessionInfo, SessionState, SessionStats, StrategyInfo};
+
+/// Flags the daemon sets on every session itself
+const RESERVED_FLAGS: [&str; 5] = ["--name", "--ws", "--ws-addr-file", "--tui", "--session-dir"];
+
+/// Environment variable holding the daemon's client token, kept from the sessions it starts
+const TOKEN_VAR: &str = "MM_GRPC_TOKEN";
+
+/// Events buffered per stream before a slow client starts missing them
+const EVENT_BUFFER: usize = 4096;
+
+/// What the daemon keeps about one of its sessions
+struct ManagedSession {
+    info: SessionInfo,
+    /// Console input of the session, taken when it is asked to stop
+    stdin: Option<ChildStdin>,
+    /// Dropped when the session ends, which ends its event streams
+    events: Option<broadcast::Sender<proto::Event>>,
+    stats: Option<SessionStats>,
+    /// The session's latest strategy event, kept after it ends
+    strategy: Option<StrategyInfo>,
+}
+
+struct Inner {
+    exe: PathBuf,
+    store: SessionStore,
+    /// Flags every session gets after its own, e.g. the daemon's `--config`
+    base_args: Vec<String>,
+    sessions: Mutex<HashMap<String, ManagedSession>>,
+    supervisors: Mutex<Vec<JoinHandle<()>>>,
+}
+
+/// gRPC daemon running each session as a child simulator process, saved to the session history
+/// like any other. Events are read from the session's websocket stream, and stopping it writes
+/// `stop` to its console.
+#[derive(Clone)]
+pub struct SessionDaemon {
+    inner: Arc<Inner>,
+    /// Bearer token every request has to carry; any client is served without one
+    token: Option<Arc<str>>,
+}
+
+impl SessionDaemon {
+    pub fn new(exe: PathBuf, store: SessionStore, base_args: Vec<String>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                exe,
+                store,
+                base_args,
+                sessions: Mutex::new(HashMap::new()),
+                supervisors: Mutex::new(Vec::new()),
+            }),
+            token: None,
+        }
+    }
+
+    /// Reject requests without an `authorization: Bearer <token>` header
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(Arc::from(token.into()));
+        self
+    }
+
+
+    /// Serve until interrupted, then stop running sessions and wait for their summaries
+    pub async fn serve(&self, addr: SocketAddr) -> Result<()> {
+        info!("gRPC daemon on {}", addr);
+        tonic::transport::Server::builder()
+            .add_service(MarketMakerServer::with_interceptor(self.clone(), TokenAuth(self.token.clone())))
+            .serve_with_shutdown(addr, async {
+                let _ = tokio::signal::ctrl_c().await;
+            })
+            .await
+            .with_context(|| format!("gRPC server on {} failed", addr))?;
+
+        let running: Vec<String> = self.inner.sessions.lock().unwrap().iter()
+            .filter(|(_, session)| session.stdin.is_some())
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in running {
+            let _ = self.stop(&name).await;
+        }
+        let supervisors = std::mem::take(&mut *self.inner.supervisors.lock().unwrap());
+        for supervisor in supervisors {
+            let _ = supervisor.await;
+        }
+        Ok(())
+    }
+
+    fn session<T>(&self, name: &str, read: impl FnOnce(&mut ManagedSession) -> T) -> Option<T> {
+        self.inner.sessions.lock().unwrap().get_mut(name).map(read)
+    }
+
+    async fn start(&self, request: proto::StartSessionRequest) -> Result<SessionInfo, Status> {
+        if let Some(flag) = request.args.iter().find(|arg| RESERVED_FLAGS.contains(&arg.as_str())) {
+            return Err(Status::invalid_argument(format!("{} is set by the daemon; leave it out of the session's args", flag)));
+        }
+        let name = match request.name.trim() {
+            "" => format!("grpc-{}", chrono::Utc::now().format("%Y%m%d-%H%M%S%3f")),
+            name => name.to_string(),
+        };
+        if self.inner.sessions.lock().unwrap().contains_key(&name) {
+            return Err(Status::already_exists(format!("session '{}' already exists", name)));
+        }
+
+        let dir = self.inner.store.dir().join("grpc");
+        let slug = name.replace(|c: char| !c.is_alphanumeric() && c != '-', "_");
+        let log = dir.join(format!("{}.log", slug));
+        // The session binds its event stream to a free port itself and writes the address here
+        let addr_file = dir.join(format!("{}.addr", slug));
+        let mut child = spawn_session(&self.inner, &request.args, &name, &addr_file, &log)
+            .map_err(|e| Status::internal(format!("{:#}", e)))?;
+
+        let info = SessionInfo {
+            name: name.clone(),
+            state: SessionState::Running as i32,
+            args: request.args,
+            started_at: chrono::Utc::now().timestamp_millis(),
+            log: log.display().to_string(),
+            ..SessionInfo::default()
+        };
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+        self.inner.sessions.lock().unwrap().insert(name.clone(), ManagedSession {
+            info: info.clone(),
+            stdin: child.stdin.take(),
+            events: Some(events.clone()),
+            stats: None,
+            strategy: None,
+        });
+        info!(session = %name, log = %log.display(), "started session");
+
+        let supervisor = tokio::spawn(supervise(self.clone(), name, child, addr_file, events));
+        self.inner.supervisors.lock().unwrap().push(supervisor);
+        Ok(info)
+    }
+
+    async fn stop(&self, name: &str) -> Result<SessionInfo, Status> {
+        let Some(mut stdin) = self.session(name, |session| session.stdin.take()).ok_or_else(|| unknown(name))? else {
+            return Err(Status::failed_precondition(format!("session '{}' is already stopping or has ended", name)));
+        };
+        if let Err(e) = stdin.write_all(b"stop\n").await {
+            warn!(session = name, error = %e, "failed to ask the session to stop");
+        }
+        self.session(name, |session| {
+            if session.info.state == SessionState::Running as i32 {
+                session.info.state = SessionState::Stopping as i32;
+            }
+            session.info.clone()
+        }).ok_or_else(|| unknown(name))
+    }
+
+    /// Figures of a finished session from the session history
+    fn final_stats(&self, name: &str) -> Option<(String, SessionStats)> {
+        let record = self.inner.store.find(name).ok()??;
+        let stats = &record.stats;
+        Some((record.id.clone(), SessionStats {
+            name: name.to_string(),
+            inventory_eth: stats.inventory_eth,
+            total_trades: stats.total_trades,
+            total_pnl: stats.total_pnl,
+            realized_pnl: stats.realized_pnl,
+            unrealized_pnl: stats.unrealized_pnl,
+            total_fees: stats.total_fees,
+            r#final: true,
+            ..SessionStats::default()
+        }))
+    }
+}
+
+fn unknown(name: &str) -> Status {
+    Status::not_found(format!("no session '{}'", name))
+}
+
+/// Checks each request's bearer token against the daemon's, if it has one
+#[derive(Clone)]
+struct TokenAuth(Option<Arc<str>>);
+
+impl Interceptor for TokenAuth {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let Some(token) = &self.0 else {
+            return Ok(request);
+        };
+        let presented = request.metadata().get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match presented {
+            Some(presented) if constant_time_eq(presented.as_bytes(), token.as_bytes()) => Ok(request),
+            Some(_) => Err(Status::unauthenticated("invalid token")),
+            None => Err(Status::unauthenticated("missing bearer token")),
+        }
+    }
+}
+
+/// Compare without returning early, so timing doesn't reveal how much of a token matched
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn spawn_session(inner: &Inner, args: &[String], name: &str, addr_file: &Path, log: &Path) -> Result<Child> {
+    if let Some(dir) = log.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    // A name reused after a session ended would otherwise leave its old address behind
+    match std::fs::remove_file(addr_file) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            return Err(e).with_context(|| format!("failed to remove {}", addr_file.display()));
+        }
+        _ => {}
+    }
+    let log_file = std::fs::File::create(log).with_context(|| format!("failed to create {}", log.display()))?;
+    Command::new(&inner.exe)
+        .args(args)
+        .args(&inner.base_args)
+        .arg("--session-dir")
+        .arg(inner.store.dir())
+        .arg("--name")
+        .arg(name)
+        .arg("--ws")
+        .arg("127.0.0.1:0")
+        .arg("--ws-addr-file")
+        .arg(addr_file)
+        .env_remove(TOKEN_VAR)
+        .stdin(Stdio::piped())
+        .stdout(log_file.try_clone()?)
+        .stderr(log_file)
+        .kill_on_drop(true)
+        .spawn()
+        .context("failed to start session")
+}
+
+/// Forward the session's events until it exits, then record how it ended
+async fn supervise(daemon: SessionDaemon, name: String, mut child: Child, addr_file: PathBuf, events: broadcast::Sender<proto::Event>) {
+    let mut forwarder = tokio::spawn(forward_events(daemon.clone(), name.clone(), addr_file.clone(), events));
+    let status = child.wait().await;
+    // The stream closes with the process; give the last events a moment to arrive
+    if tokio::time::timeout(Duration::from_secs(2), &mut forwarder).await.is_err() {
+        forwarder.abort();
+    }
+
+    let exit_code = status.as_ref().ok().and_then(|status| status.code());
+    let succeeded = status.as_ref().is_ok_and(|status| status.success());
+    let finished = daemon.final_stats(&name);
+    let _ = daemon.session(&name, |session| {
+        session.stdin = None;
+        session.events = None;
+        session.info.ended_at = chrono::Utc::now().timestamp_millis();
+        session.info.exit_code = exit_code;
+        session.info.state = if succeeded { SessionState::Finished } else { SessionState::Failed } as i32;
+        if let Some((id, stats)) = finished {
+            session.info.session_id = id;
+            session.stats = Some(stats);
+        }
+    });
+    let _ = std::fs::remove_file(&addr_file);
+    match succeeded {
+        true => info!(session = %name, "session finished"),
+        false => warn!(session = %name, ?exit_code, "session failed"),
+    }
+}
+
+/// The websocket stream's events, as far as the daemon reads them
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SessionEvent {
+    Quote(QuoteUpdate),
+    Trade(Box<Trade>),
+    Stats(Box<SnapshotFigures>),
+    /// Forwarded as JSON, so the daemon doesn't track every field of it
+    Strategy(serde_json::Value),
+}
+
+#[derive(Deserialize)]
+struct SnapshotFigures {
+    cycle: u64,
+    timestamp: i64,
+    mid: Option<f64>,
+    position: Position,
+    total_trades: u32,
+    total_pnl: f64,
+    realized_pnl: f64,
+    unrealized_pnl: f64,
+    total_fees: f64,
+}
+
+async fn forward_events(daemon: SessionDaemon, name: String, addr_file: PathBuf, events: broadcast::Sender<proto::Event>) {
+    // The session writes its stream's address once it's serving; keep checking until then
+    let addr: SocketAddr = loop {
+        match std::fs::read_to_string(&addr_file).ok().and_then(|addr| addr.trim().parse().ok()) {
+            Some(addr) => break addr,
+            None => tokio::time::sleep(Duration::from_millis(200)).await,
+        }
+    };
+    let url = format!("ws://{}", addr);
+    let mut stream = loop {
+        match tokio_tungstenite::connect_async(url.as_str()).await {
+            Ok((stream, _)) => break stream,
+            Err(_) => tokio::time::sleep(Duration::from_millis(200)).await,
+        }
+    };
+
+    while let Some(Ok(message)) = stream.next().await {
+        let Message::Text(text) = message else {
+            continue;
+        };
+        let event = match serde_json::from_str(&text) {
+            Ok(event) => event,
+            Err(e) => {
+                warn!(session = %name, error = %e, "unreadable session event");
+                continue;
+            }
+        };
+        let event = match event {
+            SessionEvent::Quote(update) => proto::event::Event::Quote(proto::Quote {
+                source: update.source.name().to_string(),
+                bid: update.quote.bid,
+                ask: update.quote.ask,
+                timestamp: update.quote.timestamp,
+            }),
+            SessionEvent::Trade(trade) => proto::event::Event::Trade(trade_message(&trade)),
+            SessionEvent::Stats(figures) => {
+                let stats = SessionStats {
+                    name: name.clone(),
+                    cycle: figures.cycle,
+                    timestamp: figures.timestamp,
+                    mid: figures.mid,
+                    inventory_eth: figures.position.net_eth,
+                    total_trades: figures.total_trades,
+                    total_pnl: figures.total_pnl,
+                    realized_pnl: figures.realized_pnl,
+                    unrealized_pnl: figures.unrealized_pnl,
+                    total_fees: figures.total_fees,
+                    r#final: false,
+                };
+                let _ = daemon.session(&name, |session| session.stats = Some(stats));
+                continue;
+            }
+            SessionEvent::Strategy(info) => {
+                let strategy = StrategyInfo { name: name.clone(), json: info.to_string() };
+                let _ = daemon.session(&name, |session| session.strategy = Some(strategy));
+                continue;
+            }
+        };
+        // No subscribers is fine; events are only kept for streams that are open
+        let _ = events.send(proto::Event {
+            session: name.clone(),
+            event: Some(event),
+        });
+    }
+}
+
+fn trade_message(trade: &Trade) -> proto::Trade {
+    proto::Trade {
+        id: trade.id,
+        side: match trade.side {
+            TradeSide::Buy => "buy",
+            TradeSide::Sell => "sell",
+        }.to_string(),
+        price: trade.price,
+        amount_eth: trade.amount_eth,
+        notional_usd: trade.notional_usd,
+        pnl: trade.pnl,
+        fees_usd: trade.fees_usd,
+        timestamp: trade.timestamp,
+        role: match trade.role {
+            LiquidityRole::Maker => "maker",
+            LiquidityRole::Taker => "taker",
+        }.to_string(),
+        venue: trade.venue.map(|venue| venue.name().to_string()).unwrap_or_default(),
+        hedge: trade.hedge,
+        tag: trade.tag.clone().unwrap_or_default(),
+    }
+}
+
+type EventStream = Pin<Box<dyn Stream<Item = Result<proto::Event, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl MarketMaker for SessionDaemon {
+    async fn start_session(&self, request: Request<proto::StartSessionRequest>) -> Result<Response<SessionInfo>, Status> {
+        Ok(Response::new(self.start(request.into_inner()).await?))
+    }
+
+    async fn stop_session(&self, request: Request<proto::SessionRef>) -> Result<Response<SessionInfo>, Status> {
+        Ok(Response::new(self.stop(&request.into_inner().name).await?))
+    }
+
+    async fn list_sessions(&self, _request: Request<proto::ListSessionsRequest>) -> Result<Response<proto::ListSessionsResponse>, Status> {
+        let mut sessions: Vec<SessionInfo> = self.inner.sessions.lock().unwrap().values().map(|session| session.info.clone()).collect();
+        sessions.sort_by_key(|info| info.started_at);
+        Ok(Response::new(proto::ListSessionsResponse { sessions }))
+    }
+
+    async fn get_stats(&self, request: Request<proto::SessionRef>) -> Result<Response<SessionStats>, Status> {
+        let name = request.into_inner().name;
+        match self.session(&name, |session| session.stats.clone()).ok_or_else(|| unknown(&name))? {
+            Some(stats) => Ok(Response::new(stats)),
+            None => Err(Status::unavailable(format!("session '{}' hasn't completed a cycle yet", name))),
+        }
+    }
+
+    async fn get_strategy(&self, request: Request<proto::SessionRef>) -> Result<Response<StrategyInfo>, Status> {
+        let name = request.into_inner().name;
+        match self.session(&name, |session| session.strategy.clone()).ok_or_else(|| unknown(&name))? {
+            Some(strategy) => Ok(Response::new(strategy)),
+            None => Err(Status::unavailable(format!("session '{}' hasn't reported its strategy yet", name))),
+        }
+    }
+
+    type StreamEventsStream = EventStream;
+
+    async fn stream_events(&self, request: Request<proto::StreamEventsRequest>) -> Result<Response<EventStream>, Status> {
+        let request = request.into_inner();
+        let rx = self.session(&request.name, |session| session.events.as_ref().map(|events| events.subscribe()));
+        let Some(rx) = rx.ok_or_else(|| unknown(&request.name))? else {
+            return Err(Status::failed_precondition(format!("session '{}' has ended", request.name)));
+        };
+        let trades_only = request.trades_only;
+        let stream = futures_util::stream::unfold(rx, move |mut rx| async move {
+            loop {
+                match rx.recv().await {
+                    Ok(event) if trades_only && matches!(event.event, Some(proto::event::Event::Quote(_))) => continue,
+                    Ok(event) => return Some((Ok(event), rx)),
+                    Err(broadcast::error::RecvError::Lagged(n)) => warn!(missed = n, "gRPC event stream fell behind"),
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
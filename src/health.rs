@@ -0,0 +1,90 @@
+use rand::Rng;
+use std::time::Duration;
+
+/// Exponential backoff with jitter, shared by every source's reconnect/retry loop
+#[derive(Debug, Clone, Copy)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: f64,
+    /// Fraction of the delay randomized in either direction (0.2 = ±20%)
+    pub jitter: f64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(60),
+            multiplier: 2.0,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Delay before retry number `attempt` (1-based)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(32) as i32;
+        let base = self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent);
+        let capped = base.min(self.max_delay.as_secs_f64());
+
+        let spread = capped * self.jitter;
+        let jittered = if spread > 0.0 {
+            capped + rand::rng().random_range(-spread..=spread)
+        } else {
+            capped
+        };
+        Duration::from_secs_f64(jittered.max(0.0))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthState {
+    Connected,
+    Degraded,
+    Down,
+}
+
+/// Connection health of a single price source
+#[derive(Debug, Clone)]
+pub struct SourceHealth {
+    pub state: HealthState,
+    pub consecutive_failures: u32,
+    pub last_success: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+impl Default for SourceHealth {
+    fn default() -> Self {
+        Self {
+            state: HealthState::Down,
+            consecutive_failures: 0,
+            last_success: None,
+            last_error: None,
+        }
+    }
+}
+
+impl SourceHealth {
+    /// Failures in a row before a source is considered down
+    pub const DOWN_AFTER_FAILURES: u32 = 3;
+
+    pub fn record_success(&mut self, timestamp: i64) {
+        self.state = HealthState::Connected;
+        self.consecutive_failures = 0;
+        self.last_success = Some(timestamp);
+    }
+
+    pub fn record_failure(&mut self, error: impl Into<String>) {
+        self.consecutive_failures += 1;
+        self.last_error = Some(error.into());
+        self.state = if self.consecutive_failures >= Self::DOWN_AFTER_FAILURES
+            || self.last_success.is_none()
+        {
+            HealthState::Down
+        } else {
+            HealthState::Degraded
+        };
+    }
+}
@@ -0,0 +1,138 @@
+use crate::timezone::ReportTimezone;
+use crate::trader::ExecutionAttempt;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// Default width of the distance-from-touch buckets
+pub const DEFAULT_HEATMAP_BUCKET_BPS: f64 = 1.0;
+
+/// Attempts and fills in one (distance from touch, hour of day) bucket
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct HeatmapCell {
+    /// Lower edge of the distance bucket, in bps behind the touch
+    pub distance_bps: f64,
+    pub hour: u32,
+    pub attempts: u32,
+    pub fills: u32,
+}
+
+impl HeatmapCell {
+    pub fn fill_rate(&self) -> f64 {
+        if self.attempts > 0 {
+            self.fills as f64 / self.attempts as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Fills as a 2D histogram of distance from the touch against hour of day, with the attempts
+/// behind them so each cell also gives a fill rate. Hours are in feed time. Withheld quotes aren't
+/// counted.
+#[derive(Debug, Clone, Serialize)]
+pub struct FillHeatmap {
+    pub bucket_bps: f64,
+    /// Timezone the hours are in
+    pub timezone: String,
+    /// Non-empty cells, by distance then hour
+    pub cells: Vec<HeatmapCell>,
+}
+
+impl FillHeatmap {
+    pub fn from_attempts<'a>(attempts: impl IntoIterator<Item = &'a ExecutionAttempt>, bucket_bps: f64, timezone: &ReportTimezone) -> Self {
+        let bucket_bps = if bucket_bps > 0.0 { bucket_bps } else { DEFAULT_HEATMAP_BUCKET_BPS };
+        let mut cells: BTreeMap<(i64, u32), (u32, u32)> = BTreeMap::new();
+        for attempt in attempts.into_iter().filter(|a| a.quoted) {
+            let Some(hour) = timezone.hour_of(attempt.feed_time) else {
+                continue;
+            };
+            let bucket = (attempt.touch_distance_bps / bucket_bps).floor() as i64;
+            let cell = cells.entry((bucket, hour)).or_default();
+            cell.0 += 1;
+            cell.1 += attempt.executed() as u32;
+        }
+        Self {
+            bucket_bps,
+            timezone: timezone.name(),
+            cells: cells
+                .into_iter()
+                .map(|((bucket, hour), (attempts, fills))| HeatmapCell {
+                    distance_bps: bucket as f64 * bucket_bps,
+                    hour,
+                    attempts,
+                    fills,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.cells.iter().map(|c| c.attempts).sum()
+    }
+
+    pub fn fills(&self) -> u32 {
+        self.cells.iter().map(|c| c.fills).sum()
+    }
+
+    /// Hours that saw attempts, in order
+    pub fn hours(&self) -> Vec<u32> {
+        let mut hours: Vec<u32> = self.cells.iter().map(|c| c.hour).collect();
+        hours.sort_unstable();
+        hours.dedup();
+        hours
+    }
+
+    /// Distance buckets that saw attempts, in order
+    pub fn distances(&self) -> Vec<f64> {
+        let mut distances: Vec<f64> = self.cells.iter().map(|c| c.distance_bps).collect();
+        distances.dedup();
+        distances
+    }
+
+    pub fn cell(&self, distance_bps: f64, hour: u32) -> Option<&HeatmapCell> {
+        self.cells.iter().find(|c| c.distance_bps == distance_bps && c.hour == hour)
+    }
+
+    /// Fill rate over every hour of one distance bucket
+    pub fn distance_fill_rate(&self, distance_bps: f64) -> f64 {
+        let row = self.cells.iter().filter(|c| c.distance_bps == distance_bps);
+        let (attempts, fills) = row.fold((0, 0), |(attempts, fills), c| (attempts + c.attempts, fills + c.fills));
+        fills as f64 / attempts.max(1) as f64
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("failed to serialize fill heatmap")
+    }
+
+    pub fn write_json(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_json()?).with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+/// Fills per cell, one row per distance bucket and one column per hour that saw attempts
+impl fmt::Display for FillHeatmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hours = self.hours();
+        writeln!(f, "Fills by distance behind the touch (bps, {} buckets) and hour ({}):", self.bucket_bps, self.timezone)?;
+        write!(f, "  {:>14}", "DISTANCE")?;
+        for hour in &hours {
+            write!(f, " {:>5}", format!("{:02}h", hour))?;
+        }
+        writeln!(f, " {:>7}", "RATE")?;
+        for distance in self.distances() {
+            write!(f, "  {:>14}", format!("[{:+.1}, {:+.1})", distance, distance + self.bucket_bps))?;
+            for &hour in &hours {
+                match self.cell(distance, hour) {
+                    Some(cell) => write!(f, " {:>5}", cell.fills)?,
+                    None => write!(f, " {:>5}", "·")?,
+                }
+            }
+            writeln!(f, " {:>6.1}%", 100.0 * self.distance_fill_rate(distance))?;
+        }
+        Ok(())
+    }
+}
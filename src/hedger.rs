@@ -0,0 +1,30 @@
+use crate::trader::TradeSide;
+use serde::Serialize;
+
+/// When and how far to hedge inventory back towards flat
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct HedgePolicy {
+    /// Hedge once absolute net inventory exceeds this
+    pub threshold_eth: f64,
+    /// Absolute inventory left after a hedge; 0 hedges to flat
+    pub target_eth: f64,
+}
+
+impl HedgePolicy {
+    pub fn new(threshold_eth: f64) -> Self {
+        Self {
+            threshold_eth,
+            target_eth: 0.0,
+        }
+    }
+
+    /// Side and ETH amount of the hedge needed at `net_eth`, if over the threshold
+    pub fn hedge_order(&self, net_eth: f64) -> Option<(TradeSide, f64)> {
+        if net_eth.abs() <= self.threshold_eth {
+            return None;
+        }
+        let side = if net_eth > 0.0 { TradeSide::Sell } else { TradeSide::Buy };
+        let amount = net_eth.abs() - self.target_eth.clamp(0.0, self.threshold_eth);
+        (amount > 0.0).then_some((side, amount))
+    }
+}
@@ -0,0 +1,212 @@
+use anyhow::Result;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::warn;
+
+/// Request limits for one REST source
+#[derive(Debug, Clone, Copy)]
+pub struct HttpPolicy {
+    /// Most requests per second sent to the source; 0 leaves it unlimited
+    pub max_requests_per_sec: f64,
+    pub timeout: Duration,
+    /// Retries after the first attempt on 429, 5xx, timeouts and connection errors
+    pub max_retries: u32,
+    /// Backoff before the first retry, doubling on each one after
+    pub retry_base: Duration,
+    /// Longest backoff; a `Retry-After` beyond it fails the request instead of waiting
+    pub retry_max: Duration,
+}
+
+impl Default for HttpPolicy {
+    fn default() -> Self {
+        Self {
+            max_requests_per_sec: 0.0,
+            timeout: Duration::from_secs(10),
+            max_retries: 2,
+            retry_base: Duration::from_millis(250),
+            retry_max: Duration::from_secs(5),
+        }
+    }
+}
+
+impl HttpPolicy {
+    pub fn with_rate_limit(mut self, max_requests_per_sec: f64) -> Self {
+        self.max_requests_per_sec = max_requests_per_sec;
+        self
+    }
+
+    /// Minimum spacing between requests
+    fn spacing(&self) -> Duration {
+        if self.max_requests_per_sec > 0.0 {
+            Duration::from_secs_f64(1.0 / self.max_requests_per_sec)
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    /// Backoff before retry number `attempt` (0-based)
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.retry_base.saturating_mul(1 << attempt.min(16)).min(self.retry_max)
+    }
+}
+
+/// What a source's requests ran into, so throttling shows up instead of silently missing quotes
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct HttpStats {
+    /// Attempts sent, retries included
+    pub requests: u64,
+    pub retries: u64,
+    /// Responses with HTTP 429
+    pub rate_limited: u64,
+    pub timeouts: u64,
+    /// Requests that still failed after their retries
+    pub failures: u64,
+}
+
+impl fmt::Display for HttpStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} requests │ {} rate-limited │ {} retries │ {} timeouts │ {} failed",
+            self.requests, self.rate_limited, self.retries, self.timeouts, self.failures)
+    }
+}
+
+/// A request the source turned away with HTTP 429 until its retries ran out
+#[derive(Debug)]
+pub struct RateLimited {
+    pub host: String,
+    /// The server's `Retry-After`, when it sent one
+    pub retry_after: Option<Duration>,
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rate limited (HTTP 429) by {}", self.host)
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// HTTP client for one source: spaces requests to its rate limit, times them out, and retries
+/// throttled or transient failures with backoff, honouring `Retry-After`
+pub struct HttpClient {
+    client: reqwest::Client,
+    policy: HttpPolicy,
+    /// Earliest time the next request may go out
+    next_slot: tokio::sync::Mutex<Instant>,
+    stats: Mutex<HttpStats>,
+}
+
+impl HttpClient {
+    pub fn new(policy: HttpPolicy) -> Result<Self> {
+        Ok(Self {
+            client: reqwest::Client::builder().timeout(policy.timeout).build()?,
+            policy,
+            next_slot: tokio::sync::Mutex::new(Instant::now()),
+            stats: Mutex::new(HttpStats::default()),
+        })
+    }
+
+    pub fn policy(&self) -> &HttpPolicy {
+        &self.policy
+    }
+
+    /// The underlying client, for callers that do their own request handling
+    pub fn inner(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    pub fn stats(&self) -> HttpStats {
+        *self.stats.lock().unwrap()
+    }
+
+    /// Wait for this request's slot under the rate limit
+    async fn acquire(&self) {
+        let at = {
+            let mut next = self.next_slot.lock().await;
+            let at = (*next).max(Instant::now());
+            *next = at + self.policy.spacing();
+            at
+        };
+        tokio::time::sleep_until(at).await;
+    }
+
+    /// Hold every request to this source until `until`
+    async fn defer(&self, until: Instant) {
+        let mut next = self.next_slot.lock().await;
+        *next = (*next).max(until);
+    }
+
+    /// Send the request `build` makes, rebuilding it for each retry. Client errors other than 429
+    /// are returned straight away.
+    pub async fn send(&self, build: impl Fn(&reqwest::Client) -> RequestBuilder) -> Result<Response> {
+        Ok(self.send_raw(build).await?.error_for_status()?)
+    }
+
+    /// Like `send`, but a client error response comes back as is, for APIs that explain errors in the body
+    pub async fn send_raw(&self, build: impl Fn(&reqwest::Client) -> RequestBuilder) -> Result<Response> {
+        self.send_retrying(build, self.policy.max_retries).await
+    }
+
+    /// Like `send_raw`, but never retried, for requests that aren't safe to repeat such as placing
+    /// an order: a timeout or 5xx may have gone through
+    pub async fn send_once(&self, build: impl Fn(&reqwest::Client) -> RequestBuilder) -> Result<Response> {
+        self.send_retrying(build, 0).await
+    }
+
+    async fn send_retrying(&self, build: impl Fn(&reqwest::Client) -> RequestBuilder, max_retries: u32) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            self.acquire().await;
+            self.stats.lock().unwrap().requests += 1;
+
+            let (error, retry_after) = match build(&self.client).send().await {
+                Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                    self.stats.lock().unwrap().rate_limited += 1;
+                    let wait = retry_after(&response);
+                    let host = response.url().host_str().unwrap_or("source").to_string();
+                    (RateLimited { host, retry_after: wait }.into(), wait)
+                }
+                Ok(response) if response.status().is_server_error() => {
+                    (response.error_for_status().unwrap_err().into(), None)
+                }
+                Ok(response) => return Ok(response),
+                Err(e) if e.is_timeout() => {
+                    self.stats.lock().unwrap().timeouts += 1;
+                    (e.into(), None)
+                }
+                Err(e) if e.is_connect() || e.is_request() => (e.into(), None),
+                Err(e) => return Err(e.into()),
+            };
+
+            let delay = retry_after.unwrap_or_else(|| self.policy.backoff(attempt));
+            if let Some(wait) = retry_after {
+                // Later polls respect the server's wait even if this request gives up
+                self.defer(Instant::now() + wait).await;
+            }
+            if attempt >= max_retries || delay > self.policy.retry_max {
+                self.stats.lock().unwrap().failures += 1;
+                return Err(error);
+            }
+
+            attempt += 1;
+            self.stats.lock().unwrap().retries += 1;
+            warn!(attempt, retry_in_ms = delay.as_millis() as u64, error = %error, "retrying request");
+            self.defer(Instant::now() + delay).await;
+        }
+    }
+}
+
+/// `Retry-After` as either delay seconds or an HTTP date
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = at.signed_duration_since(chrono::Utc::now()).to_std().unwrap_or(Duration::ZERO);
+    Some(wait)
+}
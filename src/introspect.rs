@@ -0,0 +1,108 @@
+use crate::aggregation::AggregationPolicy;
+use crate::hedger::HedgePolicy;
+use crate::participation::ParticipationPolicy;
+use crate::quiet::QuietMarketPolicy;
+use crate::quorum::QuorumPolicy;
+use crate::risk::{BreachAction, RiskLimits, RiskState};
+use crate::trader::{EdgeRequirement, TakerPolicy, TradingEngine};
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+/// A pluggable piece of the strategy and its current parameter values
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentInfo {
+    pub name: String,
+    pub parameters: serde_json::Value,
+}
+
+/// Configured risk limits and what a breach does
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct RiskInfo {
+    pub limits: RiskLimits,
+    pub breach_action: BreachAction,
+    pub state: RiskState,
+}
+
+/// Everything that currently shapes trading decisions, as structured data for dashboards and tooling
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyInfo {
+    pub notional_per_trade: f64,
+    pub quoting: ComponentInfo,
+    pub sizing: ComponentInfo,
+    pub execution_model: ComponentInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edge_requirement: Option<EdgeRequirement>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub taker: Option<TakerPolicy>,
+    pub quorum: QuorumPolicy,
+    pub aggregation: AggregationPolicy,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub risk: Option<RiskInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hedge: Option<HedgePolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quiet: Option<QuietMarketPolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub participation: Option<ParticipationPolicy>,
+    /// Fill probability and slippage scaling by UTC hour, from the liquidity profile
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub liquidity_factors: Option<Vec<f64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fill_latency_ms: Option<u64>,
+    pub resting_orders: bool,
+    pub smart_routing: bool,
+    /// Annualized realized volatility the sizing rule currently sees
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub realized_vol: Option<f64>,
+}
+
+impl StrategyInfo {
+    pub fn of(engine: &TradingEngine) -> Self {
+        let model = engine.execution_model();
+        Self {
+            notional_per_trade: engine.notional_per_trade(),
+            quoting: ComponentInfo {
+                name: engine.quoting().name().to_string(),
+                parameters: engine.quoting().parameters(),
+            },
+            sizing: ComponentInfo {
+                name: engine.sizing().name().to_string(),
+                parameters: engine.sizing().parameters(),
+            },
+            execution_model: ComponentInfo {
+                name: model.name(),
+                parameters: model.parameters(),
+            },
+            edge_requirement: engine.edge_requirement().copied(),
+            taker: engine.taker().copied(),
+            quorum: *engine.quorum(),
+            aggregation: engine.aggregation(),
+            risk: engine.risk_limits().map(|(limits, breach_action)| RiskInfo {
+                limits,
+                breach_action,
+                state: engine.risk_state(),
+            }),
+            hedge: engine.hedger().copied(),
+            quiet: engine.quiet_policy(),
+            participation: engine.participation_policy(),
+            liquidity_factors: engine.liquidity_profile().map(|profile| profile.hours.iter().map(|h| h.factor).collect()),
+            fill_latency_ms: engine.fill_latency().map(|latency| latency.as_millis() as u64),
+            resting_orders: engine.resting_orders(),
+            smart_routing: engine.router().is_some(),
+            realized_vol: engine.realized_volatility(),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("failed to serialize strategy info")
+    }
+
+    /// TOML under a top-level `[strategy]` table, to follow `--print-config` output
+    pub fn to_toml(&self) -> Result<String> {
+        let wrapped = toml::Table::from_iter([(
+            "strategy".to_string(),
+            toml::Value::try_from(self).context("failed to serialize strategy info")?,
+        )]);
+        toml::to_string(&wrapped).context("failed to serialize strategy info")
+    }
+}
@@ -0,0 +1,250 @@
+use crate::aggregator::{Quote, QuoteUpdate, Source};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Venue treated as the price leader; every other source is a lagging DEX
+pub const CEX_SOURCE: Source = Source::Binance;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArbDirection {
+    /// DEX ask below CEX bid: buy on the DEX, sell on the CEX
+    BuyDexSellCex,
+    /// DEX bid above CEX ask: sell on the DEX, buy on the CEX
+    SellDexBuyCex,
+}
+
+/// A period during which a stale DEX quote was crossed against the CEX
+#[derive(Debug, Clone)]
+pub struct ArbWindow {
+    pub venue: Source,
+    pub direction: ArbDirection,
+    pub start: i64,
+    pub end: i64,
+    pub peak_edge_bps: f64,
+    /// How old the DEX quote already was when the window opened
+    pub dex_age_at_open_ms: i64,
+}
+
+impl ArbWindow {
+    pub fn duration_ms(&self) -> i64 {
+        self.end - self.start
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct VenueArbStats {
+    pub windows: u32,
+    pub time_in_window_ms: i64,
+    pub observed_ms: i64,
+    pub avg_peak_edge_bps: f64,
+    pub max_edge_bps: f64,
+    pub avg_dex_age_ms: f64,
+    /// Sum of peak edges applied to the configured notional
+    pub theoretical_capture_usd: f64,
+}
+
+impl VenueArbStats {
+    pub fn time_in_window_pct(&self) -> f64 {
+        if self.observed_ms > 0 {
+            self.time_in_window_ms as f64 / self.observed_ms as f64 * 100.0
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LatencyArbReport {
+    pub notional: f64,
+    pub min_edge_bps: f64,
+    pub venues: Vec<(Source, VenueArbStats)>,
+}
+
+impl fmt::Display for LatencyArbReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Latency Arbitrage vs {} (edge ≥ {:.1} bps, ${:.0} notional):",
+            CEX_SOURCE.name(), self.min_edge_bps, self.notional)?;
+        for (venue, s) in &self.venues {
+            writeln!(f, "  {:<8} windows {:>4} │ in-window {:>5.1}% │ avg edge {:>5.1} bps │ max {:>5.1} bps │ avg quote age {:>6.0}ms │ capture ${:>10.2}",
+                venue.name(),
+                s.windows,
+                s.time_in_window_pct(),
+                s.avg_peak_edge_bps,
+                s.max_edge_bps,
+                s.avg_dex_age_ms,
+                s.theoretical_capture_usd
+            )?;
+        }
+        Ok(())
+    }
+}
+
+struct OpenWindow {
+    direction: ArbDirection,
+    start: i64,
+    peak_edge_bps: f64,
+    dex_age_at_open_ms: i64,
+}
+
+/// Watches quote updates for moments where a DEX quote lags the CEX enough to be crossed
+pub struct LatencyArbDetector {
+    notional: f64,
+    min_edge_bps: f64,
+    cex: Option<Quote>,
+    dex: HashMap<Source, Quote>,
+    open: HashMap<Source, OpenWindow>,
+    windows: Vec<ArbWindow>,
+    first_seen: HashMap<Source, i64>,
+    last_time: i64,
+}
+
+impl LatencyArbDetector {
+    pub fn new(notional: f64, min_edge_bps: f64) -> Self {
+        Self {
+            notional,
+            min_edge_bps,
+            cex: None,
+            dex: HashMap::new(),
+            open: HashMap::new(),
+            windows: Vec::new(),
+            first_seen: HashMap::new(),
+            last_time: 0,
+        }
+    }
+
+    pub fn on_update(&mut self, update: &QuoteUpdate) {
+        let now = update.quote.timestamp;
+        self.last_time = self.last_time.max(now);
+
+        if update.source == CEX_SOURCE {
+            self.cex = Some(update.quote);
+        } else {
+            self.dex.insert(update.source, update.quote);
+        }
+
+        let Some(cex) = self.cex else {
+            return;
+        };
+
+        let venues: Vec<Source> = self.dex.keys().copied().collect();
+        for venue in venues {
+            self.first_seen.entry(venue).or_insert(now);
+            self.evaluate(venue, cex, now);
+        }
+    }
+
+    fn evaluate(&mut self, venue: Source, cex: Quote, now: i64) {
+        let dex = self.dex[&venue];
+        let mid = (cex.bid + cex.ask) / 2.0;
+        let buy_dex_edge = (cex.bid - dex.ask) / mid * 10000.0;
+        let sell_dex_edge = (dex.bid - cex.ask) / mid * 10000.0;
+
+        let crossed = if buy_dex_edge >= self.min_edge_bps {
+            Some((ArbDirection::BuyDexSellCex, buy_dex_edge))
+        } else if sell_dex_edge >= self.min_edge_bps {
+            Some((ArbDirection::SellDexBuyCex, sell_dex_edge))
+        } else {
+            None
+        };
+
+        match (crossed, self.open.get_mut(&venue)) {
+            (Some((direction, edge)), Some(window)) if window.direction == direction => {
+                window.peak_edge_bps = window.peak_edge_bps.max(edge);
+            }
+            (crossed, _) => {
+                self.close(venue, now);
+                if let Some((direction, edge)) = crossed {
+                    self.open.insert(venue, OpenWindow {
+                        direction,
+                        start: now,
+                        peak_edge_bps: edge,
+                        dex_age_at_open_ms: now - dex.timestamp,
+                    });
+                }
+            }
+        }
+    }
+
+    fn close(&mut self, venue: Source, now: i64) {
+        if let Some(window) = self.open.remove(&venue) {
+            self.windows.push(ArbWindow {
+                venue,
+                direction: window.direction,
+                start: window.start,
+                end: now,
+                peak_edge_bps: window.peak_edge_bps,
+                dex_age_at_open_ms: window.dex_age_at_open_ms,
+            });
+        }
+    }
+
+    /// Close any windows still open at the last observed time
+    pub fn finish(&mut self) {
+        let venues: Vec<Source> = self.open.keys().copied().collect();
+        for venue in venues {
+            self.close(venue, self.last_time);
+        }
+    }
+
+    pub fn windows(&self) -> &[ArbWindow] {
+        &self.windows
+    }
+
+    pub fn report(&self) -> LatencyArbReport {
+        let mut venues: Vec<(Source, VenueArbStats)> = Vec::new();
+
+        for &venue in Source::ALL.iter().filter(|&&s| s != CEX_SOURCE) {
+            let Some(&first_seen) = self.first_seen.get(&venue) else {
+                continue;
+            };
+            let mut stats = VenueArbStats {
+                observed_ms: self.last_time - first_seen,
+                ..Default::default()
+            };
+
+            for w in self.windows.iter().filter(|w| w.venue == venue) {
+                stats.windows += 1;
+                stats.time_in_window_ms += w.duration_ms();
+                stats.avg_peak_edge_bps += w.peak_edge_bps;
+                stats.max_edge_bps = stats.max_edge_bps.max(w.peak_edge_bps);
+                stats.avg_dex_age_ms += w.dex_age_at_open_ms as f64;
+                stats.theoretical_capture_usd += w.peak_edge_bps / 10000.0 * self.notional;
+            }
+            if stats.windows > 0 {
+                stats.avg_peak_edge_bps /= stats.windows as f64;
+                stats.avg_dex_age_ms /= stats.windows as f64;
+            }
+            venues.push((venue, stats));
+        }
+
+        LatencyArbReport {
+            notional: self.notional,
+            min_edge_bps: self.min_edge_bps,
+            venues,
+        }
+    }
+}
+
+/// Run a detector over a quote subscription in the background
+pub fn spawn_latency_arb_detector(
+    mut updates: broadcast::Receiver<QuoteUpdate>,
+    notional: f64,
+    min_edge_bps: f64,
+) -> Arc<Mutex<LatencyArbDetector>> {
+    let detector = Arc::new(Mutex::new(LatencyArbDetector::new(notional, min_edge_bps)));
+    let handle = Arc::clone(&detector);
+
+    tokio::spawn(async move {
+        loop {
+            match updates.recv().await {
+                Ok(update) => handle.lock().unwrap().on_update(&update),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    detector
+}
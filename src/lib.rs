@@ -1,7 +1,145 @@
+pub mod aggregation;
 pub mod aggregator;
+pub mod alerts;
+pub mod amount;
+#[cfg(feature = "api")]
+pub mod api;
+pub mod book_sim;
+pub mod calibration;
+pub mod candles;
+pub mod clock;
+pub mod bench;
+pub mod config;
+pub mod contribution;
+pub mod cross_arb;
+#[cfg(feature = "sqlite")]
+pub mod database;
+pub mod control;
+pub mod demo;
+pub mod draws;
+pub mod dropcopy;
+pub mod error;
+pub mod execution;
+pub mod export;
+pub mod fanout;
+pub mod fees;
+pub mod flow;
+pub mod funding;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod health;
+pub mod heatmap;
+pub mod hedger;
+pub mod http;
+pub mod introspect;
+pub mod latency_arb;
+pub mod liquidity;
+pub mod market;
+pub mod markout;
+pub mod metrics;
+pub mod montecarlo;
+pub mod notify;
+pub mod orders;
+pub mod output;
+pub mod paper;
 pub mod trader;
+pub mod participation;
+pub mod performance;
+pub mod pnl_distribution;
 pub mod pnl_tracker;
+pub mod portfolio;
+pub mod position;
+pub mod publish;
+pub mod quiet;
+pub mod quorum;
+pub mod quoting;
+pub mod reconcile;
+pub mod recorder;
+pub mod replay;
+pub mod report;
+pub mod risk;
+pub mod router;
+pub mod sandbox;
+pub mod schedule;
+pub mod session;
+pub mod sizing;
+pub mod source_diff;
+pub mod sweep;
+pub mod synthetic;
+pub mod timezone;
+pub mod tokens;
+pub mod trade_history;
+pub mod two_sided;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod uniswap;
+pub mod units;
+pub mod whatif;
+pub mod ws;
 
-pub use aggregator::{AggregatedPrices, PriceAggregator, Quote};
-pub use trader::{Trade, TradeSide, TradingEngine, MarketSummary};
-pub use pnl_tracker::{PnLTracker, PnLStats};
\ No newline at end of file
+pub use config::{Config, ConfigOrigin, ConfigOrigins};
+pub use alerts::{Alert, AlertCondition, AlertEngine, AlertRule, AlertSink};
+pub use contribution::{ContributionReport, SourceContribution};
+pub use bench::{FeedBenchReport, LatencyStats};
+pub use book_sim::{BookSimConfig, BookSimStats, OrderBookVenue};
+pub use calibration::{CalibrationBucket, FillCalibration};
+pub use candles::{Candle, CandleBuilder, CandleInterval, CandleSeries};
+pub use clock::{Clock, SimulatedClock, SystemClock, TokioClock};
+pub use aggregation::AggregationPolicy;
+pub use aggregator::{AggregatedPrices, FeedEvent, PriceAggregator, PriceFeed, PriceSnapshot, Quote, QuoteUpdate, Source, SourceQuote};
+pub use error::MarketMakerError;
+pub use export::{ExportStats, HttpExportConfig, HttpTradeExporter};
+pub use fanout::{ClientStats, FanOut, FlowControl, SlowConsumerPolicy, Subscription};
+pub use fees::{FeeBreakdown, FeeSchedule, VenueFees};
+pub use flow::{FlowImbalance, FlowTracker, TapeEvent, TapeTrade};
+pub use funding::{FundingCost, FundingRates};
+pub use heatmap::{FillHeatmap, HeatmapCell};
+pub use health::{HealthState, ReconnectPolicy, SourceHealth};
+pub use hedger::HedgePolicy;
+pub use http::{HttpClient, HttpPolicy, HttpStats, RateLimited};
+pub use liquidity::{HourLiquidity, LiquidityProfile};
+pub use metrics::SourceMetrics;
+pub use paper::{PaperStats, PaperTrader, TestnetAccount, VenueFill};
+pub use montecarlo::{Distribution, MonteCarloReport, MonteCarloRun};
+pub use notify::{Notification, NotifyPolicy, NotifyStats, WebhookNotifier};
+pub use publish::{BusUrl, EventPublisher, PublishTarget, PublishStats};
+pub use orders::{OrderAction, OrderId, OrderManager, OrderRequest, OrderState, OrderStats, RestingOrder};
+pub use introspect::{ComponentInfo, RiskInfo, StrategyInfo};
+pub use latency_arb::{LatencyArbDetector, LatencyArbReport};
+pub use cross_arb::{CrossArbDetector, CrossArbReport, Dislocation};
+pub use demo::Narrator;
+pub use draws::DrawSource;
+pub use dropcopy::{DropCopy, ExecType, ExecutionReport, OrdStatus, OrdType, TimeInForce};
+pub use execution::{AdvancedModel, BasicModel, EnsembleMethod, EnsembleModel, ExecutionModel, FillContext, ModelPrediction, PoissonModel, QueueModel};
+pub use trader::{Decision, EdgeRequirement, ExecutionAttempt, LiquidityRole, Requote, RequoteDecision, TakerPolicy, Trade, TradeSide, TradingEngine, MarketSummary};
+pub use market::{MarketConfig, MarketResult, MarketsReport};
+pub use participation::{ParticipationLimiter, ParticipationPolicy, ParticipationStats};
+pub use performance::PerformanceStats;
+pub use pnl_distribution::{PnLBin, PnLDistribution};
+pub use pnl_tracker::{EquityPoint, PnLBucket, PnLTracker, PnLStats, VenueAttribution};
+pub use markout::{MarkoutStats, TradeMarkout};
+pub use portfolio::{PortfolioSnapshot, Simulator};
+pub use position::{CostBasis, InventoryLedger, Position};
+pub use quiet::{QuietEvent, QuietInterval, QuietMarketDetector, QuietMarketPolicy};
+pub use quorum::{QuorumPolicy, TradingMode};
+pub use quoting::{FixedSpread, MedianQuote, OffsetQuote, QuotePair, QuoteReference, QuotingStrategy};
+pub use reconcile::{ReconcileTolerance, Reconciler, Reconciliation, VenueAccount};
+pub use recorder::{Tick, TickRecorder};
+pub use router::{ChildFill, SmartOrderRouter, VenueProfile};
+pub use replay::{ReplaySource, ReplaySpeed};
+pub use report::{ReportFormat, SessionReport};
+pub use tokens::{EvmToken, SplToken, Token, TokenRegistry, TradingPair};
+pub use risk::{BreachAction, RiskEvent, RiskLimit, RiskLimits, RiskManager, RiskState};
+pub use schedule::{Calendar, Occurrence, SessionManifest, SessionWindow};
+pub use session::{SessionRecord, SessionStore};
+pub use sizing::{FixedSize, InventorySkew, RealizedVolatility, SizingContext, SizingRule, VolTarget};
+pub use units::{ConversionSnapshot, DisplayUnit};
+pub use synthetic::{SyntheticConfig, SyntheticSource};
+pub use sweep::{SweepAxis, SweepPoint, SweepReport, SweepResult};
+pub use sandbox::{BudgetViolation, StrategySandbox};
+pub use timezone::ReportTimezone;
+pub use trade_history::{HistoryReader, TradeHistory};
+pub use two_sided::{RoundTrip, TwoSidedAttempt, TwoSidedDecision, TwoSidedPolicy, TwoSidedStats};
+pub use uniswap::UniswapConfig;
+pub use whatif::{CycleRecorder, CycleSnapshot, WhatIfReport, WhatIfRun};
+pub use ws::{StreamEvent, WsServer};
@@ -0,0 +1,143 @@
+use crate::recorder::Tick;
+use anyhow::{bail, Context, Result};
+use chrono::{TimeZone, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+/// Bounds on how far an hour can scale fills away from the all-day average
+pub const MIN_LIQUIDITY_FACTOR: f64 = 0.25;
+pub const MAX_LIQUIDITY_FACTOR: f64 = 4.0;
+
+/// How liquid one UTC hour of the day was in the recordings the profile was built from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct HourLiquidity {
+    pub hour: u32,
+    pub ticks: u64,
+    /// Distinct minutes with at least one tick, which the tick rate is taken over
+    pub minutes: u32,
+    pub median_spread_bps: f64,
+    /// Liquidity relative to the all-day average: above 1 easier and cheaper to fill, below 1 harder
+    pub factor: f64,
+}
+
+/// Intraday liquidity by UTC hour, from the quote spreads and update rates of recorded ticks.
+/// An hour's factor is the geometric mean of how much tighter its median spread is and how much
+/// faster quotes update than over the whole recording. Hours the recordings don't cover stay at 1.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidityProfile {
+    pub hours: Vec<HourLiquidity>,
+}
+
+impl LiquidityProfile {
+    pub fn from_ticks(ticks: &[Tick]) -> Self {
+        let mut spreads: Vec<Vec<f64>> = vec![Vec::new(); 24];
+        let mut minutes: Vec<HashSet<i64>> = vec![HashSet::new(); 24];
+        for tick in ticks {
+            let mid = (tick.bid + tick.ask) / 2.0;
+            let Some(time) = Utc.timestamp_millis_opt(tick.timestamp).single() else {
+                continue;
+            };
+            if mid <= 0.0 {
+                continue;
+            }
+            let hour = time.hour() as usize;
+            spreads[hour].push((tick.ask - tick.bid) / mid * 10_000.0);
+            minutes[hour].insert(tick.timestamp.div_euclid(60_000));
+        }
+
+        let mut all: Vec<f64> = spreads.iter().flatten().copied().collect();
+        let overall_spread = median(&mut all);
+        let total_minutes: usize = minutes.iter().map(HashSet::len).sum();
+        let overall_rate = all.len() as f64 / total_minutes.max(1) as f64;
+
+        let hours = (0..24)
+            .map(|hour| {
+                let ticks = spreads[hour].len();
+                let minutes = minutes[hour].len();
+                let median_spread_bps = median(&mut spreads[hour]);
+                let factor = if ticks > 0 && median_spread_bps > 0.0 && overall_rate > 0.0 {
+                    let tighter = overall_spread / median_spread_bps;
+                    let faster = ticks as f64 / minutes as f64 / overall_rate;
+                    (tighter * faster).sqrt().clamp(MIN_LIQUIDITY_FACTOR, MAX_LIQUIDITY_FACTOR)
+                } else {
+                    1.0
+                };
+                HourLiquidity {
+                    hour: hour as u32,
+                    ticks: ticks as u64,
+                    minutes: minutes as u32,
+                    median_spread_bps,
+                    factor,
+                }
+            })
+            .collect();
+        Self { hours }
+    }
+
+    /// Liquidity factor for the UTC hour of `timestamp`
+    pub fn factor_at(&self, timestamp: i64) -> f64 {
+        let Some(time) = Utc.timestamp_millis_opt(timestamp).single() else {
+            return 1.0;
+        };
+        self.hours.iter().find(|h| h.hour == time.hour()).map_or(1.0, |h| h.factor)
+    }
+
+    /// Hours the recordings covered
+    pub fn covered_hours(&self) -> usize {
+        self.hours.iter().filter(|h| h.ticks > 0).count()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+        let profile: Self = serde_json::from_str(&data).with_context(|| format!("failed to parse liquidity profile {}", path.display()))?;
+        // A hand-edited profile mustn't turn fill probabilities or slippage negative or NaN
+        for hour in &profile.hours {
+            if hour.hour > 23 {
+                bail!("liquidity profile {} has hour {}, expected 0-23", path.display(), hour.hour);
+            }
+            if !(hour.factor.is_finite() && hour.factor > 0.0) {
+                bail!("liquidity profile {} has factor {} for hour {}, expected a positive number", path.display(), hour.factor, hour.hour);
+            }
+        }
+        Ok(profile)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let data = serde_json::to_string_pretty(self).context("failed to serialize liquidity profile")?;
+        std::fs::write(path, data).with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+impl fmt::Display for LiquidityProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Intraday liquidity profile ({} of 24 UTC hours covered):", self.covered_hours())?;
+        writeln!(f, "  {:>4} {:>9} {:>8} {:>11} {:>7}", "HOUR", "TICKS", "MINUTES", "SPREAD bps", "FACTOR")?;
+        for hour in &self.hours {
+            if hour.ticks == 0 {
+                writeln!(f, "  {:>3}h {:>9} {:>8} {:>11} {:>7.2}", format!("{:02}", hour.hour), "-", "-", "-", hour.factor)?;
+                continue;
+            }
+            let bar = "█".repeat((hour.factor * 10.0).round() as usize);
+            writeln!(f, "  {:>3}h {:>9} {:>8} {:>11.2} {:>7.2} {}",
+                format!("{:02}", hour.hour), hour.ticks, hour.minutes, hour.median_spread_bps, hour.factor, bar)?;
+        }
+        Ok(())
+    }
+}
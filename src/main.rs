@@ -1,141 +1,2557 @@
-use anyhow::Result;
-use market_maker_simulator::{PnLTracker, PriceAggregator, TradeSide, TradingEngine};
-use std::time::Duration;
-use tokio::time::{interval, sleep};
-
-const NOTIONAL_PER_TRADE: f64 = 100_000.0;
-const SIMULATION_DURATION_SECS: u64 = 600; // 10 minutes
-const TRADE_INTERVAL_SECS: u64 = 5; // Execute trades every 5 seconds
+use anyhow::{bail, Context, Result};
+use market_maker_simulator::alerts::{spawn_alert_engine, AlertEngine, AlertRule, ConsoleAlertSink};
+use market_maker_simulator::bench;
+use market_maker_simulator::clock::{Clock, SimulatedClock, SystemClock};
+use market_maker_simulator::config::ENV_PREFIX;
+use market_maker_simulator::contribution;
+#[cfg(feature = "api")]
+use market_maker_simulator::api::ApiServer;
+#[cfg(feature = "sqlite")]
+use market_maker_simulator::database::Database;
+#[cfg(feature = "tui")]
+use market_maker_simulator::tui::Dashboard;
+use market_maker_simulator::control::{self, ControlCommand};
+use market_maker_simulator::demo::{DEMO_DURATION_SECS, DEMO_SEED};
+use market_maker_simulator::heatmap::DEFAULT_HEATMAP_BUCKET_BPS;
+use market_maker_simulator::calibration::{spawn_tape_recorder, DEFAULT_CALIBRATION_BUCKET_BPS};
+#[cfg(feature = "grpc")]
+use market_maker_simulator::grpc::SessionDaemon;
+use market_maker_simulator::latency_arb::spawn_latency_arb_detector;
+use market_maker_simulator::cross_arb::{spawn_cross_arb_detector, CrossArbDetector};
+use market_maker_simulator::output;
+use market_maker_simulator::pnl_distribution::DEFAULT_PNL_BINS;
+use market_maker_simulator::pnl_tracker::{spawn_mark_to_market, stop_mark_to_market};
+use market_maker_simulator::recorder;
+use market_maker_simulator::report::{ReportFormat, SessionReport};
+use market_maker_simulator::sandbox::{StrategySandbox, DEFAULT_STRATEGY_BUDGET};
+use market_maker_simulator::session::{self, DEFAULT_SESSION_DIR};
+use market_maker_simulator::source_diff::{self, DiffReport, SourceFetcher, SourceImpl};
+use market_maker_simulator::sweep;
+use market_maker_simulator::whatif;
+use market_maker_simulator::say;
+use market_maker_simulator::{
+    AggregatedPrices, Calendar, Config, ConfigOrigin, CycleRecorder, CycleSnapshot, DisplayUnit, DrawSource, DropCopy, EventPublisher, ExecutionAttempt, ExecutionModel, FeedEvent, HttpTradeExporter, LiquidityProfile, MarketConfig, MarketResult, MarketSummary, MarketsReport, MonteCarloReport, MonteCarloRun, Narrator, OrderAction, OrderBookVenue, PaperTrader, PnLTracker, ReportTimezone, PriceAggregator, PriceFeed, PriceSnapshot, PublishTarget, QuietEvent, QuoteUpdate, Reconciler, ReplaySource, ReplaySpeed,
+    Requote, RiskState, SessionManifest, SessionRecord, SessionStore, Simulator, SmartOrderRouter, Source, StrategyInfo, SweepAxis, SweepResult, SweepReport, SyntheticConfig, TickRecorder, Trade, TradeSide, TradingEngine, TradingMode, UniswapConfig, WebhookNotifier, WhatIfReport, WsServer,
+};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::{interval, sleep, Interval};
+use tracing::{error, info, warn, Instrument};
+use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // Parse command line arguments
     let args: Vec<String> = std::env::args().collect();
-    let use_advanced_model = args.iter().any(|arg| arg == "--advanced");
-
-    println!("\n╔════════════════════════════════════════════════════════════════════╗");
-    println!("║              MARKET MAKER SIMULATOR - ETH/USDC                     ║");
-    println!("╠════════════════════════════════════════════════════════════════════╣");
-    println!("║ Notional per Trade:    ${}                                  ║", NOTIONAL_PER_TRADE.separated_string());
-    println!("║ Simulation Duration:   {} minutes                                 ║", SIMULATION_DURATION_SECS / 60);
-    println!("║ Trade Interval:        {} seconds                                 ║", TRADE_INTERVAL_SECS);
-    println!("║ Execution Model:       {}                              ║", 
-        if use_advanced_model { "ADVANCED (20%-90%)" } else { "BASIC (70% fixed) " });
-    println!("╚════════════════════════════════════════════════════════════════════╝\n");
+    init_logging(args.iter().any(|arg| arg == "--json"))?;
+    let (mut config, mut config_origins) = Config::resolve_with_origins(arg_value(&args, "--config").as_deref())?;
+    let before_cli = config.clone();
+    // The demo is an offline session with a fixed story: the same seed, short, and slow enough to read
+    let demo = args.iter().any(|arg| arg == "--demo");
+    let offline = demo || args.iter().any(|arg| arg == "--offline");
+
+    // Command line flags take precedence over the config file and environment
+    if args.iter().any(|arg| arg == "--advanced") {
+        config.execution.model = "advanced".to_string();
+    }
+    if let Some(list) = arg_value(&args, "--ensemble") {
+        config.execution.model = list;
+    }
+    if let Some(method) = arg_value(&args, "--ensemble-method") {
+        config.execution.ensemble_method = method;
+    }
+    if let Some(signal_bps) = arg_value(&args, "--taker-signal-bps") {
+        config.execution.taker_signal_bps = Some(signal_bps.parse()?);
+    }
+    if let Some(fee) = arg_value(&args, "--taker-fee-bps") {
+        config.execution.taker_fee_bps = Some(fee.parse()?);
+    }
+    if let Some(slippage) = arg_value(&args, "--taker-slippage-bps") {
+        config.execution.taker_slippage_bps = slippage.parse()?;
+    }
+    if let Some(min_edge) = arg_value(&args, "--min-edge-bps") {
+        config.execution.min_edge_bps = min_edge.parse()?;
+    }
+    if args.iter().any(|arg| arg == "--no-min-edge") {
+        config.execution.require_min_edge = false;
+    }
+    if let Some(limit) = arg_value(&args, "--max-net-inventory") {
+        config.risk.max_inventory_eth = Some(limit.parse()?);
+    }
+    if let Some(limit) = arg_value(&args, "--max-drawdown") {
+        config.risk.max_drawdown_usd = Some(limit.parse()?);
+    }
+    if let Some(limit) = arg_value(&args, "--max-hourly-loss") {
+        config.risk.max_loss_per_hour_usd = Some(limit.parse()?);
+    }
+    if let Some(limit) = arg_value(&args, "--max-losing-streak") {
+        config.risk.max_consecutive_losses = Some(limit.parse()?);
+    }
+    if let Some(interval) = arg_value(&args, "--mark-interval-secs") {
+        config.simulation.mark_interval_secs = interval.parse()?;
+    }
+    if let Some(tag) = arg_value(&args, "--tag") {
+        config.simulation.tag = Some(tag);
+    }
+    if let Some(basis) = arg_value(&args, "--cost-basis") {
+        config.simulation.cost_basis = basis;
+    }
+    if let Some(max) = arg_value(&args, "--max-trades-in-memory") {
+        config.simulation.max_trades_in_memory = max.parse()?;
+    }
+    if let Some(action) = arg_value(&args, "--risk-action") {
+        config.risk.breach_action = action;
+    }
+    if let Some(threshold) = arg_value(&args, "--hedge-threshold") {
+        config.hedge.threshold_eth = Some(threshold.parse()?);
+    }
+    if let Some(spread) = arg_value(&args, "--quiet-spread-bps") {
+        config.quiet.max_spread_bps = Some(spread.parse()?);
+    }
+    if args.iter().any(|arg| arg == "--quiet-pause") {
+        config.quiet.pause = true;
+    }
+    if let Some(pct) = arg_value(&args, "--max-participation") {
+        config.participation.max_pct = Some(pct.parse()?);
+    }
+    if let Some(target) = arg_value(&args, "--hedge-target") {
+        config.hedge.target_eth = target.parse()?;
+    }
+    if let Some(path) = arg_value(&args, "--liquidity-profile") {
+        config.execution.liquidity_profile = Some(path);
+    }
+    if let Some(latency) = arg_value(&args, "--fill-latency-ms") {
+        config.execution.fill_latency_ms = latency.parse()?;
+    }
+    if args.iter().any(|arg| arg == "--resting-orders") {
+        config.orders.resting = true;
+    }
+    if args.iter().any(|arg| arg == "--paper") {
+        config.paper.enabled = true;
+    }
+    if let Some(strategy) = arg_value(&args, "--quoting") {
+        config.quoting.strategy = strategy;
+    }
+    if let Some(spread) = arg_value(&args, "--quote-spread-bps") {
+        config.quoting.spread_bps = spread.parse()?;
+    }
+    if let Some(reference) = arg_value(&args, "--quote-reference") {
+        config.quoting.reference = reference;
+    }
+    if let Some(offset) = arg_value(&args, "--bid-offset-bps") {
+        config.quoting.bid_offset_bps = offset.parse()?;
+    }
+    if let Some(offset) = arg_value(&args, "--ask-offset-bps") {
+        config.quoting.ask_offset_bps = offset.parse()?;
+    }
+    if let Some(rule) = arg_value(&args, "--sizing") {
+        config.sizing.rule = rule;
+    }
+    if let Some(policy) = arg_value(&args, "--aggregation") {
+        config.aggregation.policy = policy;
+    }
+    if let Some(min_sources) = arg_value(&args, "--min-sources") {
+        config.quorum.min_sources = min_sources.parse()?;
+    }
+    if args.iter().any(|arg| arg == "--no-degraded") {
+        config.quorum.allow_degraded = false;
+    }
+    if args.iter().any(|arg| arg == "--two-sided") {
+        config.quoting.two_sided = true;
+    }
+    if let Some(correlation) = arg_value(&args, "--fill-correlation") {
+        config.quoting.two_sided = true;
+        config.quoting.fill_correlation = correlation.parse()?;
+    }
+    if args.iter().any(|arg| arg == "--one-fill-per-cycle") {
+        config.quoting.two_sided = true;
+        config.quoting.one_fill_per_cycle = true;
+    }
+    if let Some(rules) = arg_value(&args, "--alerts") {
+        for rule in rules.split(',') {
+            config.alerts.rules.push(AlertRule::new(rule.parse()?));
+        }
+    }
+    if let Some(url) = arg_value(&args, "--export-url") {
+        config.export.http_url = Some(url);
+    }
+    if let Some(url) = arg_value(&args, "--webhook") {
+        config.notify.webhook_url = Some(url);
+    }
+    if let Some(drawdown) = arg_value(&args, "--notify-drawdown") {
+        config.notify.drawdown_usd = Some(drawdown.parse()?);
+    }
+    if let Some(url) = arg_value(&args, "--publish") {
+        config.publish.url = Some(url);
+    }
+    if let Some(listen) = arg_value(&args, "--api") {
+        config.api.listen = Some(listen);
+    }
+    if let Some(listen) = arg_value(&args, "--ws") {
+        config.websocket.listen = Some(listen);
+    }
+    if let Some(path) = arg_value(&args, "--ws-addr-file") {
+        config.websocket.addr_file = Some(path);
+    }
+    if let Some(rpc_url) = arg_value(&args, "--uniswap-rpc") {
+        config.sources.uniswap_rpc_url = Some(rpc_url);
+    }
+    if let Some(tiers) = arg_value(&args, "--uniswap-fee-tiers") {
+        config.sources.uniswap_fee_tiers = UniswapConfig::parse_fee_tiers(&tiers)?;
+    }
+    if demo {
+        config.synthetic.seed = DEMO_SEED;
+        // A duration from the config file or environment still wins
+        if *config_origins.get("simulation.duration_secs") == ConfigOrigin::Default {
+            config.simulation.duration_secs = DEMO_DURATION_SECS;
+        }
+    }
+    if let Some(seed) = arg_value(&args, "--seed") {
+        config.synthetic.seed = seed.parse()?;
+    }
+    if let Some(price) = arg_value(&args, "--synthetic-price") {
+        config.synthetic.initial_price = price.parse()?;
+    }
+    if args.iter().any(|arg| arg == "--no-reconcile") {
+        config.reconcile.enabled = false;
+    }
+    if let Some(list) = arg_value(&args, "--markets") {
+        config.markets.pairs = MarketConfig::parse_list(&list)?;
+    }
+    config_origins.record_changes(&before_cli, &config, ConfigOrigin::Cli)?;
+    config.validate()?;
+
+    if args.iter().any(|arg| arg == "--print-config") {
+        print!("{}", config.redacted().render_with_origins(&config_origins)?);
+        let execution_model: Box<dyn ExecutionModel> = if offline {
+            Box::new(OrderBookVenue::new(config.book_sim.config(&config.synthetic, &config.simulation), ReplaySpeed::AsFastAsPossible, &SystemClock))
+        } else {
+            config.execution.execution_model(config.simulation.trade_interval_secs as f64)?
+        };
+        let mut engine = config.session_engine(execution_model)?;
+        if args.iter().any(|arg| arg == "--split-venues") {
+            engine = engine.with_router(SmartOrderRouter::new());
+        }
+        print!("\n# Active strategy, as built from the config above\n{}", StrategyInfo::of(&engine).to_toml()?);
+        return Ok(());
+    }
+
+    let notional_per_trade = config.simulation.notional_per_trade;
+    let trade_interval = Duration::from_secs(config.simulation.trade_interval_secs);
+    let execution_model = config.execution.execution_model(trade_interval.as_secs_f64())?;
+    let timezone: ReportTimezone = match arg_value(&args, "--timezone") {
+        Some(tz) => tz.parse()?,
+        None => ReportTimezone::default(),
+    };
+    let session_store = SessionStore::new(
+        arg_value(&args, "--session-dir").unwrap_or_else(|| DEFAULT_SESSION_DIR.to_string()),
+    );
+
+    match args.get(1).map(String::as_str) {
+        Some("sessions") => return run_sessions_command(&session_store, &args[2..], &timezone),
+        Some("convert") => return run_convert_command(&args[2..]),
+        Some("diff") => return run_diff_command(&config, &args[2..]).await,
+        Some("schedule") => return run_schedule_command(&config, &session_store, &args[2..]).await,
+        Some("grpc") => return run_grpc_command(&session_store, &args[2..]).await,
+        Some("bench-feed") => return run_bench_command(&config, execution_model, &args[2..]).await,
+        Some("whatif") => return run_whatif_command(&args[2..]).await,
+        Some("sweep") => return run_sweep_command(&config, &args[2..]).await,
+        Some("contribution") => return run_contribution_command(&args[2..]),
+        Some("liquidity") => return run_liquidity_command(&args[2..]),
+        Some("report") => return run_report_command(&args[2..], &timezone),
+        _ => {}
+    }
+
+    let session_id = session::new_session_id();
+    let session_name = arg_value(&args, "--name");
+    let record_path = arg_value(&args, "--record");
+    let heatmap_path = arg_value(&args, "--heatmap");
+    let calibration_path = arg_value(&args, "--calibrate-fills");
+    let report_path = arg_value(&args, "--report");
+    if let Some(path) = &report_path {
+        ReportFormat::from_path(path)?;
+    }
+    let output_dir = arg_value(&args, "--output-dir");
+    let db_path = arg_value(&args, "--db");
+    #[cfg(feature = "sqlite")]
+    let database = db_path.as_deref().map(Database::open).transpose()?;
+    #[cfg(not(feature = "sqlite"))]
+    if db_path.is_some() {
+        bail!("--db needs a build with the sqlite feature (cargo build --features sqlite)");
+    }
+    #[cfg(not(feature = "api"))]
+    if config.api.listen.is_some() {
+        bail!("the HTTP API needs a build with the api feature (cargo build --features api)");
+    }
+    let publish_target = config.publish.target()?;
+    #[cfg(not(feature = "kafka"))]
+    if publish_target.as_ref().is_some_and(|target| matches!(target.url, market_maker_simulator::BusUrl::Kafka { .. })) {
+        bail!("--publish kafka://... needs a build with the kafka feature (cargo build --features kafka)");
+    }
+    #[cfg(not(feature = "nats"))]
+    if publish_target.as_ref().is_some_and(|target| matches!(target.url, market_maker_simulator::BusUrl::Nats { .. })) {
+        bail!("--publish nats://... needs a build with the nats feature (cargo build --features nats)");
+    }
+    let tui = args.iter().any(|arg| arg == "--tui");
+    #[cfg(not(feature = "tui"))]
+    if tui {
+        bail!("--tui needs a build with the tui feature (cargo build --features tui)");
+    }
+    if tui && !std::io::stdout().is_terminal() {
+        bail!("--tui needs a terminal; leave it off for headless runs");
+    }
+    if args.iter().any(|arg| arg == "--json") {
+        if tui {
+            bail!("--json and --tui both take over stdout; pick one");
+        }
+        output::set_json(true);
+    }
+    let pnl_bucket = arg_value(&args, "--pnl-buckets")
+        .map(|secs| secs.parse().map(Duration::from_secs))
+        .transpose()?;
+    let heatmap_bucket_bps: f64 = arg_value(&args, "--heatmap-bucket-bps")
+        .map(|s| s.parse())
+        .transpose()?
+        .unwrap_or(DEFAULT_HEATMAP_BUCKET_BPS);
+    let calibration_window = arg_value(&args, "--calibration-window-secs")
+        .map(|secs| secs.parse().map(Duration::from_secs))
+        .transpose()?
+        .unwrap_or(trade_interval);
+    let calibration_bucket_bps: f64 = arg_value(&args, "--calibration-bucket-bps")
+        .map(|s| s.parse())
+        .transpose()?
+        .unwrap_or(DEFAULT_CALIBRATION_BUCKET_BPS);
+    let replay_path = arg_value(&args, "--replay");
+    let replay_speed = match arg_value(&args, "--speed") {
+        Some(speed) => speed.parse()?,
+        None if demo => ReplaySpeed::RealTime,
+        None => ReplaySpeed::AsFastAsPossible,
+    };
+    if replay_path.is_some() && offline {
+        bail!("--offline (or --demo) and --replay are separate price feeds, pick one");
+    }
+    if config.paper.enabled && (replay_path.is_some() || offline) {
+        bail!("paper trading quotes the live market; it can't run with --offline, --demo or --replay");
+    }
+    if calibration_path.is_some() && (replay_path.is_some() || offline) {
+        bail!("--calibrate-fills checks quotes against the live Binance trade stream; it can't run with --offline, --demo or --replay");
+    }
+    let offline_venue = offline.then(|| OrderBookVenue::new(config.book_sim.config(&config.synthetic, &config.simulation), replay_speed, &SystemClock));
+    // Offline, fills come from the simulated books' queues and taker flow
+    let execution_model = match &offline_venue {
+        Some(venue) => Box::new(venue.clone()),
+        None => execution_model,
+    };
+    let synthetic_slots = match arg_value(&args, "--synthetic").as_deref() {
+        Some("all") => Source::ALL.to_vec(),
+        Some(list) => list.split(',').map(str::parse).collect::<Result<Vec<Source>>>()?,
+        None => Vec::new(),
+    };
+    let synthetic_config = config.synthetic.config();
+    let latency_arb_bps = if args.iter().any(|arg| arg == "--latency-arb") {
+        Some(arg_value(&args, "--latency-arb-bps").map(|s| s.parse()).transpose()?.unwrap_or(1.0))
+    } else {
+        None
+    };
+    if calibration_path.is_some() && (synthetic_slots.contains(&Source::Binance) || config.sources.binance_trades_ws_url.is_empty()) {
+        bail!("--calibrate-fills needs the live Binance trade stream (sources.binance_trades_ws_url, Binance not synthetic)");
+    }
+    let pair = config.tokens.pair()?;
+    let uniswap_config = config.sources.uniswap(config.quote_notional(), &pair)?;
+    let strategy_budget = arg_value(&args, "--strategy-budget-ms")
+        .map(|s| s.parse().map(Duration::from_millis))
+        .transpose()?
+        .unwrap_or(DEFAULT_STRATEGY_BUDGET);
+    let display_units = match arg_value(&args, "--cost-units") {
+        Some(list) => DisplayUnit::parse_list(&list)?,
+        None => Vec::new(),
+    };
+
+    if !config.markets.pairs.is_empty() {
+        let options = MarketsRun {
+            offline,
+            session_name,
+            store: &session_store,
+            speed: replay_speed,
+            timezone,
+            strategy_budget,
+            split_venues: args.iter().any(|arg| arg == "--split-venues"),
+            output_dir,
+        };
+        return run_markets(&config, &args, options).await;
+    }
+    if let Some(runs) = arg_value(&args, "--runs") {
+        let Some(path) = &replay_path else {
+            bail!("--runs repeats the session over the same recorded prices; pass them with --replay <ticks>");
+        };
+        let csv = output_dir.map(|dir| PathBuf::from(dir).join(format!("{}-runs.csv", session_id)));
+        return run_batch(&config, path, runs.parse()?, csv).await;
+    }
+
+    let replay = match &replay_path {
+        Some(path) => {
+            info!("Loading recorded ticks from {}...", path);
+            Some(ReplaySource::load(path, replay_speed)?)
+        }
+        None => None,
+    };
+    // Replays and the offline venue stamp trades and orders in their own simulated time
+    let session_clock: Option<Arc<dyn Clock>> = match (&replay, &offline_venue) {
+        (Some(replay), _) => Some(replay.sim_clock()),
+        (None, Some(venue)) => Some(venue.sim_clock()),
+        (None, None) => None,
+    };
+
+    let price_feed = match (&replay_path, &offline_venue) {
+        (Some(path), _) => format!("REPLAY {}", path),
+        (None, Some(venue)) => format!("OFFLINE BOOK (seed {})", venue.config().fair.seed),
+        (None, None) => "LIVE".to_string(),
+    };
+    if output::json() {
+        output::emit("start", &serde_json::json!({
+            "session_id": session_id,
+            "session_name": session_name,
+            "pair": pair.to_string(),
+            "notional_per_trade": notional_per_trade,
+            "duration_secs": config.simulation.duration_secs,
+            "trade_interval_secs": config.simulation.trade_interval_secs,
+            "execution_model": execution_model.describe(),
+            "quoting": config.quoting.strategy,
+            "price_feed": price_feed,
+            "timezone": timezone.name(),
+        }));
+    } else {
+        println!("\n╔════════════════════════════════════════════════════════════════════╗");
+        println!("║{:^68}║", format!("MARKET MAKER SIMULATOR - {}", pair));
+        println!("╠════════════════════════════════════════════════════════════════════╣");
+        println!("║ Notional per Trade:    {:<44}║", format!("${}", notional_per_trade.separated_string()));
+        println!("║ Simulation Duration:   {:<44}║", format!("{} minutes", config.simulation.duration_secs / 60));
+        println!("║ Trade Interval:        {:<44}║", format!("{} seconds", config.simulation.trade_interval_secs));
+        println!("║ Execution Model:       {:<44}║", execution_model.describe());
+        println!("║ Quoting Strategy:      {:<44}║", config.quoting.strategy);
+        println!("║ Price Feed:            {:<44}║", price_feed);
+        println!("║ Session:               {:<44}║", session_name.as_deref().unwrap_or(&session_id));
+        println!("║ Report Timezone:       {:<44}║", timezone.name());
+        println!("╚════════════════════════════════════════════════════════════════════╝\n");
+    }
 
     // Initialize components
-    println!("[INIT] Starting price aggregator...");
-    let aggregator = PriceAggregator::new();
-    aggregator.start().await?;
+    let mut trading_engine = config.session_engine(execution_model)?;
+    if let Some(clock) = session_clock {
+        trading_engine = trading_engine.with_clock(clock);
+    }
+    let record_draws_path = arg_value(&args, "--record-draws");
+    if let Some(path) = arg_value(&args, "--replay-draws") {
+        let draws = DrawSource::load(&path)?;
+        info!("Replaying fill decision draws from {}", path);
+        trading_engine = trading_engine.with_draws(draws);
+    } else if record_draws_path.is_some() {
+        trading_engine = trading_engine.with_draws(DrawSource::recording());
+    } else if let Some(venue) = &offline_venue {
+        trading_engine = trading_engine.with_draws(DrawSource::seeded(venue.config().fair.seed));
+    }
+    if args.iter().any(|arg| arg == "--split-venues") {
+        trading_engine = trading_engine.with_router(SmartOrderRouter::new());
+    }
+    if let Some(policy) = trading_engine.quiet_policy() {
+        info!("Quiet market below {} bps spread and {:.0}% volatility for {}s: {}",
+            policy.max_spread_bps, policy.max_volatility * 100.0, policy.min_quiet_ms / 1000,
+            if policy.pause { "pausing quotes" } else { "reporting only" });
+    }
+    if let Some(profile) = trading_engine.liquidity_profile() {
+        let factors = profile.hours.iter().map(|h| h.factor);
+        info!("Fill odds and slippage scaled by a liquidity profile of {} UTC hours ({:.2}x to {:.2}x)",
+            profile.covered_hours(), factors.clone().fold(f64::INFINITY, f64::min), factors.fold(0.0, f64::max));
+    }
+    if let Some(policy) = trading_engine.participation_policy() {
+        info!("Fills capped at {}% of market volume over {}s", policy.max_pct, policy.interval_ms / 1000);
+    }
+    if let Some(policy) = trading_engine.hedger() {
+        info!("Hedging inventory above {} ETH back to {} ETH", policy.threshold_eth, policy.target_eth);
+    }
+    if let Some(latency) = trading_engine.fill_latency() {
+        info!("Fills confirmed {} ms after the decision and marked to market then", latency.as_millis());
+    }
+    if trading_engine.resting_orders() {
+        info!("Resting limit orders, re-priced when the quote moves more than {} bps", config.orders.amend_threshold_bps);
+    }
+    let drop_copy_path = arg_value(&args, "--drop-copy");
+    if let Some(path) = &drop_copy_path {
+        let drop_copy = DropCopy::create(path, &session_id)?;
+        info!("Drop copy of every order event to {} ({})", path, if drop_copy.is_fix() { "FIX tag=value" } else { "JSON lines" });
+        trading_engine = trading_engine.with_drop_copy(drop_copy);
+    }
+    let trading_engine = Arc::new(trading_engine);
+    // Only venues that keep our account have balances to reconcile against
+    let reconciler = match trading_engine.execution_model().account() {
+        Some(_) => config.reconcile.reconciler(),
+        None => None,
+    };
+    if let Some(reconciler) = &reconciler {
+        info!("Reconciling position and cash against the {} venue account every {}s",
+            trading_engine.execution_model().name(), reconciler.interval().as_secs());
+    }
+    let cycle_recorder = match arg_value(&args, "--record-cycles") {
+        Some(path) => {
+            info!("Recording cycle snapshots to {} for what-if re-simulation", path);
+            Some(CycleRecorder::create(path, &config)?)
+        }
+        None => None,
+    };
+    let narrator = demo.then(|| Narrator::new(&trading_engine));
+    let notifier = config.notify.policy()?.map(|policy| {
+        info!("Sending notifications to a webhook{}{}",
+            policy.drawdown_usd.map_or(String::new(), |usd| format!(" │ drawdown over ${}", usd)),
+            policy.sources_down.map_or(String::new(), |d| format!(" │ sources down {}s", d.as_secs())));
+        WebhookNotifier::spawn(policy, session_id.clone())
+    });
+    let audit = Audit {
+        reconciler: reconciler.as_ref(),
+        cycles: cycle_recorder.as_ref(),
+        narrator: narrator.as_ref(),
+        notifier: notifier.as_ref(),
+    };
+    let sandbox = StrategySandbox::new(strategy_budget);
+    let trade_history = config.simulation.trade_history(session_store.dir().join(format!("{}-trades.jsonl", session_id)))?;
+    if let Some(path) = trade_history.spill_path() {
+        info!("Keeping the latest {} trades in memory, older ones in {}", config.simulation.max_trades_in_memory, path.display());
+    }
+    let mut pnl_tracker = PnLTracker::new()
+        .with_cost_basis(config.simulation.cost_basis()?)
+        .with_mark_interval(config.simulation.mark_interval())
+        .with_display_units(display_units)
+        .with_timezone(timezone)
+        .with_history(trade_history);
+    if let Some(rates) = config.funding.rates() {
+        info!("Charging carry on inventory: {:.2}% a year long, {:.2}% short", rates.long_rate * 100.0, rates.short_rate * 100.0);
+        pnl_tracker = pnl_tracker.with_funding(rates);
+    }
+    let exporter = config.export.http().map(|http| {
+        info!("Exporting trades to {} in batches of {}", http.url, http.batch_size);
+        Arc::new(HttpTradeExporter::spawn(http, session_id.clone()))
+    });
+    if let Some(exporter) = &exporter {
+        pnl_tracker = pnl_tracker.with_exporter(Arc::clone(exporter));
+    }
+    let started_at = chrono::Utc::now().timestamp_millis();
+    let mut recorder = None;
+    let mut arb_detector = None;
+    let mut tape = None;
+    let cross_arb;
+    let mark_to_market;
+    let mut alert_engine = None;
+    let ws_server;
+    let publisher;
+    let new_cross_arb = || CrossArbDetector::new(trading_engine.fees().clone(), notional_per_trade)
+        .with_max_quote_age(trading_engine.quorum().max_quote_age());
+    let new_alert_engine = || AlertEngine::new(config.alerts.rules.clone()).with_sink(Box::new(ConsoleAlertSink));
+    // The dashboard reads keys itself, so stdin commands only work with the plain log
+    let paper = match config.paper.account()? {
+        Some(account) => {
+            info!("Paper trading {} on Binance Spot Testnet at {}", account.symbol, account.api_url);
+            Some(PaperTrader::connect(account, &session_id).await?)
+        }
+        None => None,
+    };
+    let (command_sender, commands) = control::spawn_control_listener(!tui);
+    #[cfg(not(feature = "tui"))]
+    drop(command_sender);
+    let mut control = Control {
+        commands,
+        dir: session_store.dir().to_path_buf(),
+        session_id: session_id.clone(),
+        portfolio: Simulator::new(Arc::clone(&trading_engine), pnl_tracker.clone()),
+        paper,
+        #[cfg(feature = "sqlite")]
+        database,
+        #[cfg(feature = "tui")]
+        dashboard: tui.then(|| (session_store.dir().join(format!("{}.log", session_id)), command_sender)),
+    };
 
-    println!("[INIT] Waiting 10 seconds for initial price data...");
-    sleep(Duration::from_secs(10)).await;
+    let outcome = if let Some(replay) = &replay {
+        info!("Replaying {} ticks spanning {}s at {:?}",
+            replay.tick_count(), replay.duration().as_secs(), replay_speed);
 
-    println!("[INIT] Initializing trading engine and PnL tracker...");
-    let trading_engine = TradingEngine::new(NOTIONAL_PER_TRADE, use_advanced_model);
-    let pnl_tracker = PnLTracker::new();
+        if let Some(min_edge_bps) = latency_arb_bps {
+            arb_detector = Some(spawn_latency_arb_detector(replay.subscribe(), notional_per_trade, min_edge_bps));
+        }
+        cross_arb = spawn_cross_arb_detector(replay.subscribe(), new_cross_arb());
+        let prices = replay.get_prices().await;
+        mark_to_market = spawn_mark_to_market(replay.subscribe(), prices.clone(), &pnl_tracker);
+        start_api(&config, replay.subscribe(), prices, &control.portfolio)?;
+        ws_server = start_ws(&config, replay.subscribe(), &control.portfolio).await?;
+        publisher = start_publisher(publish_target.clone(), replay.subscribe(), &pnl_tracker, &session_id).await?;
+        if !config.alerts.rules.is_empty() {
+            alert_engine = Some(spawn_alert_engine(replay.subscribe(), new_alert_engine()));
+        }
 
-    println!("[START] Beginning market making session...\n");
+        say!("[START] Beginning backtest session...\n");
+        let mut schedule = Schedule::Replay {
+            source: replay,
+            step: trade_interval,
+        };
+        run_trading_loop(replay, &mut schedule, &mut control, &trading_engine, &sandbox, &pnl_tracker, &audit).await
+    } else if let Some(venue) = &offline_venue {
+        let book = venue.config();
+        info!("Simulating {} order books: {} levels of ${} every {} bps, {} market orders/s averaging ${}",
+            book.venues.len(), book.levels, book.depth_per_level_usd.separated_string(), book.level_spacing_bps,
+            book.taker_orders_per_sec, book.mean_taker_usd.separated_string());
 
-    // Trading loop
-    let mut trade_interval = interval(Duration::from_secs(TRADE_INTERVAL_SECS));
-    let start_time = std::time::Instant::now();
-    let mut cycle_count = 0;
+        if let Some(min_edge_bps) = latency_arb_bps {
+            arb_detector = Some(spawn_latency_arb_detector(venue.subscribe(), notional_per_trade, min_edge_bps));
+        }
+        cross_arb = spawn_cross_arb_detector(venue.subscribe(), new_cross_arb());
+        let prices = venue.get_prices().await;
+        mark_to_market = spawn_mark_to_market(venue.subscribe(), prices.clone(), &pnl_tracker);
+        start_api(&config, venue.subscribe(), prices, &control.portfolio)?;
+        ws_server = start_ws(&config, venue.subscribe(), &control.portfolio).await?;
+        publisher = start_publisher(publish_target.clone(), venue.subscribe(), &pnl_tracker, &session_id).await?;
+        if !config.alerts.rules.is_empty() {
+            alert_engine = Some(spawn_alert_engine(venue.subscribe(), new_alert_engine()));
+        }
 
-    loop {
-        trade_interval.tick().await;
+        if let Some(narrator) = &narrator {
+            print_notes(&narrator.intro(book.fair.seed, trade_interval.as_secs()));
+        }
+        say!("[START] Beginning offline session...\n");
+        let mut schedule = Schedule::Offline {
+            venue,
+            step: trade_interval,
+        };
+        run_trading_loop(venue, &mut schedule, &mut control, &trading_engine, &sandbox, &pnl_tracker, &audit).await
+    } else {
+        info!("Starting price aggregator...");
+        let mut aggregator = PriceAggregator::new()
+            .with_sources(config.sources.clone())
+            .with_pair(pair.clone())
+            .with_quote_notional(config.quote_notional())
+            .with_stale_after(trading_engine.quorum().max_quote_age());
+        for (i, &source) in synthetic_slots.iter().enumerate() {
+            info!("Using synthetic prices for {}", source.name());
+            let config = SyntheticConfig {
+                seed: synthetic_config.seed.wrapping_add(i as u64),
+                ..synthetic_config
+            };
+            aggregator = aggregator.with_synthetic(source, config);
+        }
+        if let Some(config) = uniswap_config {
+            info!("Quoting Uniswap V3 fee tiers {:?} via {}", config.fee_tiers, config.rpc_url);
+            aggregator = aggregator.with_uniswap(config);
+        }
+        if let Some(path) = &record_path {
+            info!("Recording ticks to {}", path);
+            let tick_recorder = Arc::new(TickRecorder::create(path)?);
+            aggregator = aggregator.with_recorder(Arc::clone(&tick_recorder));
+            recorder = Some(tick_recorder);
+        }
+        if let Some(min_edge_bps) = latency_arb_bps {
+            arb_detector = Some(spawn_latency_arb_detector(aggregator.subscribe(), notional_per_trade, min_edge_bps));
+        }
+        cross_arb = spawn_cross_arb_detector(aggregator.subscribe(), new_cross_arb());
+        let prices = aggregator.get_prices().await;
+        mark_to_market = spawn_mark_to_market(aggregator.subscribe(), prices.clone(), &pnl_tracker);
+        start_api(&config, aggregator.subscribe(), prices, &control.portfolio)?;
+        ws_server = start_ws(&config, aggregator.subscribe(), &control.portfolio).await?;
+        publisher = start_publisher(publish_target.clone(), aggregator.subscribe(), &pnl_tracker, &session_id).await?;
+        if !config.alerts.rules.is_empty() {
+            info!("Watching {} alert rules", config.alerts.rules.len());
+            alert_engine = Some(spawn_alert_engine(aggregator.subscribe(), new_alert_engine()));
+        }
+        if calibration_path.is_some() {
+            tape = Some(spawn_tape_recorder(aggregator.subscribe_tape()));
+        }
+        aggregator.start().await?;
+
+        info!("Waiting {} seconds for initial price data...", config.simulation.warmup_secs);
+        sleep(Duration::from_secs(config.simulation.warmup_secs)).await;
+
+        say!("[START] Beginning market making session...\n");
+        let mut schedule = Schedule::Live {
+            interval: interval(trade_interval),
+            duration: config.simulation.duration_secs,
+            start: tokio::time::Instant::now(),
+        };
+        let outcome = run_trading_loop(&aggregator, &mut schedule, &mut control, &trading_engine, &sandbox, &pnl_tracker, &audit).await;
+        // Nothing keeps polling the venues while the summary and exports are written
+        aggregator.stop();
+        if let Some(paper) = &control.paper {
+            // Fills that landed before the cancels went through still count
+            paper.cancel_all().await;
+            record_venue_fills(&trading_engine, &pnl_tracker, audit.narrator, paper, &aggregator.get_prices().await).await;
+            say!("{}\n", paper.stats());
+        }
+        for (source, stats) in aggregator.http_stats().await {
+            say!("{} HTTP: {}\n", source.name(), stats);
+        }
+        outcome
+    };
+    stop_mark_to_market(mark_to_market).await;
+    if let Err(e) = pnl_tracker.flush_trades().await {
+        error!("{:#}", e);
+    }
+
+    // Final summary
+    if !output::json() {
+        println!("\n");
+        println!("╔════════════════════════════════════════════════════════════════════╗");
+        println!("║                     SIMULATION COMPLETE                            ║");
+        println!("╚════════════════════════════════════════════════════════════════════╝");
+    }
 
-        let elapsed = start_time.elapsed().as_secs();
-        if elapsed >= SIMULATION_DURATION_SECS {
-            break;
+    pnl_tracker.print_summary().await;
+    print_equity_summary(&pnl_tracker).await;
+    if let Some(bucket) = pnl_bucket {
+        print_pnl_buckets(&pnl_tracker, bucket).await;
+    }
+
+    if let Some(report) = trading_engine.risk_report(&pnl_tracker.timezone()) {
+        say!("{}", report);
+    }
+
+    if let Some(stats) = trading_engine.order_stats() {
+        let pulled = trading_engine.cancel_resting();
+        say!("{} │ {} still open at the end, cancelled\n", stats, pulled.len());
+    }
+
+    if let Some(stats) = trading_engine.participation_stats() {
+        say!("{}\n", stats);
+    }
+
+    if let (Some(drop_copy), Some(path)) = (trading_engine.drop_copy(), &drop_copy_path) {
+        drop_copy.flush()?;
+        say!("[DROP COPY] {} execution reports written to {}\n", drop_copy.reports(), path);
+    }
+
+    if let Some(venue) = &offline_venue {
+        let stats = venue.stats();
+        say!("Offline venue: {} market orders │ ${} traded │ {} swept a level\n",
+            stats.taker_orders, stats.taker_volume_usd.separated_string(), stats.sweeps);
+    }
+
+    if let Some(path) = &heatmap_path {
+        let heatmap = pnl_tracker.fill_heatmap(heatmap_bucket_bps).await;
+        say!("{}", heatmap.to_string().trim_end());
+        heatmap.write_json(path)?;
+        say!("[HEATMAP] {} fills in {} cells written to {}\n", heatmap.fills(), heatmap.cells.len(), path);
+    }
+
+    if let (Some(path), Some(tape)) = (&calibration_path, &tape) {
+        let tape = tape.lock().unwrap().clone();
+        let observed_until = chrono::Utc::now().timestamp_millis();
+        let calibration = pnl_tracker.fill_calibration(&tape, calibration_window, observed_until, calibration_bucket_bps).await;
+        say!("{}", calibration);
+        calibration.write_json(path)?;
+        say!("[CALIBRATION] {} quotes in {} buckets written to {}\n", calibration.quotes(), calibration.buckets.len(), path);
+    }
+
+    let cross_arb_report = {
+        let mut detector = cross_arb.lock().unwrap();
+        detector.finish();
+        detector.report()
+    };
+    let position = trading_engine.position();
+    if output::json() {
+        let daily_pnl = pnl_tracker.daily_pnl().await.into_iter()
+            .map(|(date, pnl, trades)| serde_json::json!({ "date": date.to_string(), "pnl": pnl, "trades": trades }))
+            .collect::<Vec<_>>();
+        output::emit("summary", &serde_json::json!({
+            "session_id": session_id,
+            "cycles": outcome.cycles,
+            "stats": pnl_tracker.get_stats().await,
+            "position": position,
+            "last_mid": outcome.last_mid,
+            "unrealized_pnl": outcome.last_mid.map(|mid| position.unrealized_pnl(mid)),
+            "daily_pnl": daily_pnl,
+            "model_calibration": pnl_tracker.model_calibration().await,
+            "venue_routing": pnl_tracker.venue_routing().await,
+            "pnl_distribution": pnl_tracker.pnl_distribution(DEFAULT_PNL_BINS).await,
+            "cross_venue_arb": cross_arb_report,
+        }));
+    } else {
+        match outcome.last_mid {
+            Some(mid) => println!("Final Position: {} │ Unrealized: ${:.2} (mark ${:.2})\n",
+                position, position.unrealized_pnl(mid), mid),
+            None => println!("Final Position: {}\n", position),
+        }
+    }
+    if let Some(path) = &report_path {
+        let report = SessionReport::collect(&session_id, &config, &pnl_tracker).await?
+            .with_name(session_name.clone())
+            .with_cycles(outcome.cycles)
+            .with_position(position, outcome.last_mid)
+            .with_heatmap(pnl_tracker.fill_heatmap(heatmap_bucket_bps).await);
+        report.write(path)?;
+        say!("[REPORT] Session report with {} trades written to {}\n", report.trades.len(), path);
+    }
+    if let Some(narrator) = &narrator {
+        say!("{}", narrator.outro());
+    }
+
+    if let Some(detector) = &arb_detector {
+        let mut detector = detector.lock().unwrap();
+        detector.finish();
+        say!("{}", detector.report());
+    }
+
+    say!("{}", cross_arb_report);
+
+    if let Some(engine) = &alert_engine {
+        say!("{}", engine.lock().unwrap().report());
+    }
+
+    if let Some(server) = &ws_server {
+        let clients = server.clients();
+        let dropped: u64 = clients.iter().map(|c| c.dropped).sum();
+        say!("[WS] {} clients still connected │ {} events dropped for them │ {} disconnected as slow consumers\n",
+            clients.len(), dropped, server.disconnects());
+    }
+
+    if let Some(reconciler) = &reconciler {
+        say!("{}", reconciler.report(&pnl_tracker.timezone()));
+    }
+
+    sandbox.print_report();
+
+    if let Some(recorder) = &recorder {
+        recorder.flush()?;
+    }
+
+    if let Some(exporter) = &exporter {
+        let stats = exporter.finish().await?;
+        say!("Trade export: {} sent in {} batches │ {} retries │ {} dropped\n",
+            stats.sent, stats.batches, stats.retries, stats.dropped);
+    }
+
+    if let Some(publisher) = &publisher {
+        let stats = publisher.finish().await;
+        say!("Bus publishing: {} trades │ {} quote updates │ {} failed │ {} missed while behind\n",
+            stats.trades, stats.quotes, stats.failed, stats.lagged);
+    }
+
+    if let Some(notifier) = &notifier {
+        notifier.session_complete(session_name.as_deref().unwrap_or(&session_id), outcome.cycles, pnl_tracker.get_stats().await);
+        let stats = notifier.finish().await;
+        say!("Webhook notifications: {} sent │ {} failed\n", stats.sent, stats.failed);
+    }
+
+    if let Some(dir) = &output_dir {
+        std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir))?;
+        let csv = PathBuf::from(dir).join(format!("{}-trades.csv", session_id));
+        let json = PathBuf::from(dir).join(format!("{}-trades.json", session_id));
+        pnl_tracker.export_csv(&csv).await?;
+        pnl_tracker.export_json(&json).await?;
+        say!("Exported trades and stats to {} (+ -stats.csv) and {}\n", csv.display(), json.display());
+    }
+
+    if let Some(path) = &record_draws_path {
+        let count = trading_engine.draws().save(path)?;
+        say!("Saved {} fill decision draws to {}\n", count, path);
+    }
+
+    // Show last few trades; under --json every one was already its own `trade` event
+    if !output::json() {
+        println!("Last 5 Trades:");
+        println!("─────────────────────────────────────────────────────────────────────");
+        let recent_trades = pnl_tracker.get_recent_trades(5).await;
+        for trade in recent_trades.iter().rev() {
+            let side = match trade.side {
+                TradeSide::Buy => "BUY ",
+                TradeSide::Sell => "SELL",
+            };
+            println!("  {} │ ${:.2} │ {:.4} ETH │ PnL: ${:.2}",
+                side, trade.price, trade.amount_eth, trade.pnl);
+        }
+        println!("─────────────────────────────────────────────────────────────────────\n");
+    }
+
+    let record = SessionRecord {
+        id: session_id,
+        name: session_name,
+        started_at,
+        ended_at: chrono::Utc::now().timestamp_millis(),
+        execution_model: trading_engine.execution_model().name(),
+        notional_per_trade,
+        cycles: outcome.cycles,
+        stats: pnl_tracker.get_stats().await,
+    };
+    session_store.save(&record)?;
+    say!("[SESSION] Saved session '{}' to {}", record.label(), session_store.dir().display());
+    #[cfg(feature = "sqlite")]
+    if let (Some(database), Some(path)) = (&control.database, &db_path) {
+        let trades = pnl_tracker.trade_history().await;
+        database.save_session(&record, &trades, &toml::to_string(&config.redacted())?)?;
+        say!("[DB] Saved session '{}' with {} trades to {}", record.label(), trades.len(), path);
+    }
+
+    Ok(())
+}
+
+/// Drives the trading loop cadence: wall-clock for live feeds, recorded time for replays
+enum Schedule<'a> {
+    Live { interval: Interval, start: tokio::time::Instant, duration: u64 },
+    Replay { source: &'a ReplaySource, step: Duration },
+    Offline { venue: &'a OrderBookVenue, step: Duration },
+}
+
+impl Schedule<'_> {
+    /// Wait for the next cycle, returning (elapsed, remaining) seconds, or None when done
+    async fn next(&mut self) -> Option<(u64, u64)> {
+        match self {
+            Schedule::Live { interval, start, duration } => {
+                interval.tick().await;
+                let elapsed = start.elapsed().as_secs();
+                if elapsed >= *duration {
+                    return None;
+                }
+                Some((elapsed, *duration - elapsed))
+            }
+            Schedule::Replay { source, step } => {
+                if !source.advance(*step).await {
+                    return None;
+                }
+                let elapsed = source.elapsed().as_secs();
+                let remaining = source.duration().as_secs().saturating_sub(elapsed);
+                Some((elapsed, remaining))
+            }
+            Schedule::Offline { venue, step } => {
+                if !venue.advance(*step).await {
+                    return None;
+                }
+                let elapsed = venue.elapsed().as_secs();
+                Some((elapsed, venue.duration().as_secs().saturating_sub(elapsed)))
+            }
         }
+    }
+}
+
+/// What the trading loop hands back once the session ends
+struct LoopOutcome {
+    cycles: u64,
+    /// Last median mid seen, for marking the final position
+    last_mid: Option<f64>,
+}
+
+/// Optional checks and recordings that run alongside the trading loop
+struct Audit<'a> {
+    reconciler: Option<&'a Reconciler>,
+    cycles: Option<&'a CycleRecorder>,
+    /// Explains each step under `--demo`
+    narrator: Option<&'a Narrator>,
+    /// Posts drawdowns, feed outages and risk breaches to a webhook
+    notifier: Option<&'a WebhookNotifier>,
+}
+
+/// External commands (SIGUSR1 / stdin) and where their output goes
+struct Control {
+    commands: mpsc::UnboundedReceiver<ControlCommand>,
+    dir: PathBuf,
+    session_id: String,
+    /// Published at every cycle boundary for the `portfolio` command
+    portfolio: Simulator,
+    /// Under `--paper`, works the resting orders on Binance Spot Testnet and reports its fills
+    paper: Option<PaperTrader>,
+    /// Stores every cycle's quotes and, at the end, the session and its trades
+    #[cfg(feature = "sqlite")]
+    database: Option<Database>,
+    /// For `--tui`: the file the log goes to while the dashboard holds the terminal, and where its keys send commands
+    #[cfg(feature = "tui")]
+    dashboard: Option<(PathBuf, mpsc::UnboundedSender<ControlCommand>)>,
+}
+
+async fn run_trading_loop<F: PriceFeed>(
+    feed: &F,
+    schedule: &mut Schedule<'_>,
+    control: &mut Control,
+    engine: &Arc<TradingEngine>,
+    sandbox: &StrategySandbox,
+    tracker: &PnLTracker,
+    audit: &Audit<'_>,
+) -> LoopOutcome {
+    let mut cycle_count: u64 = 0;
+    let mut last_mid = None;
+    let mut last_elapsed = 0;
+    // Resting orders fill, and pending fills confirm, off every quote update between cycles, not just the cycle snapshot
+    let mut updates = (engine.resting_orders() || engine.fill_latency().is_some()).then(|| feed.subscribe());
+    let mut book = feed.get_prices().await;
+    // Source errors since the last cycle, for the risk manager and the JSON log
+    let mut feed_events = feed.subscribe_events();
+    let mut risk_events_seen = 0;
+    // Recorded once the cycle is over, with the draws its decisions used
+    let mut snapshot: Option<CycleSnapshot> = None;
+    // Prices the last cycle traded on, for its portfolio snapshot
+    let mut cycle_prices: Option<AggregatedPrices> = None;
+    #[cfg(feature = "tui")]
+    let dashboard = match control.dashboard.take() {
+        Some((log, commands)) => match Dashboard::start(&log, feed.subscribe(), control.portfolio.clone(), commands) {
+            Ok(dashboard) => Some((dashboard, log)),
+            Err(e) => {
+                error!("Failed to start the dashboard, keeping the plain log: {:#}", e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    loop {
+        let (elapsed, remaining) = tokio::select! {
+            next = schedule.next() => match next {
+                Some(times) => times,
+                None => break,
+            },
+            Some(command) = control.commands.recv() => {
+                match command {
+                    ControlCommand::Summary => {
+                        let prices = feed.get_prices().await;
+                        match control::write_interim_summary(
+                            &control.dir, &control.session_id, tracker, &prices, engine.now_ms(), last_elapsed,
+                        ).await {
+                            Ok(path) => say!("[CONTROL] Interim summary written to {}", path.display()),
+                            Err(e) => error!("Failed to write interim summary: {}", e),
+                        }
+                    }
+                    ControlCommand::Strategy => {
+                        match control::write_strategy_info(&control.dir, &control.session_id, &StrategyInfo::of(engine)) {
+                            Ok(path) => say!("[CONTROL] Active strategy written to {}", path.display()),
+                            Err(e) => error!("Failed to write strategy info: {}", e),
+                        }
+                    }
+                    ControlCommand::Portfolio => match control.portfolio.portfolio().await {
+                        Some(portfolio) => match control::write_portfolio(&control.dir, &control.session_id, &portfolio) {
+                            Ok(path) => say!("[CONTROL] Portfolio after cycle #{} written to {}", portfolio.cycle, path.display()),
+                            Err(e) => error!("Failed to write portfolio: {}", e),
+                        },
+                        None => say!("[CONTROL] No completed cycle yet, portfolio not written"),
+                    },
+                    ControlCommand::Stop => {
+                        say!("[CONTROL] Stopping the session early");
+                        break;
+                    }
+                }
+                continue;
+            }
+        };
 
+        // Everything the previous cycle did has landed and nothing of this one has started
+        if let Some(prices) = &cycle_prices {
+            control.portfolio.publish(cycle_count, prices).await;
+        }
         cycle_count += 1;
-        let remaining = SIMULATION_DURATION_SECS - elapsed;
+        last_elapsed = elapsed;
 
-        println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-        println!("Cycle #{} │ Elapsed: {}s │ Remaining: {}s", cycle_count, elapsed, remaining);
-        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        if let Some(events) = &mut feed_events {
+            loop {
+                match events.try_recv() {
+                    Ok(event) => {
+                        engine.on_feed_event(&event);
+                        output::emit("feed", &event);
+                    }
+                    Err(broadcast::error::TryRecvError::Lagged(missed)) => {
+                        warn!(missed, "fell behind the feed event channel");
+                        engine.on_feed_events_missed(missed);
+                    }
+                    Err(_) => break,
+                }
+            }
+        }
+        let feed_snapshot = feed.snapshot().await;
+        let prices = feed_snapshot.prices.clone();
+        print_cycle_header(tracker, &feed_snapshot, engine.quorum().max_quote_age(), engine.get_market_summary(&prices), cycle_count, elapsed, remaining);
+        last_mid = prices.median_mid().or(last_mid);
+        cycle_prices = Some(prices.clone());
+        #[cfg(feature = "sqlite")]
+        if let Some(database) = &control.database {
+            if let Err(e) = database.record_quotes(&control.session_id, cycle_count, &prices) {
+                error!("Failed to store quotes: {}", e);
+            }
+        }
+        tracker.mark_to_market(&prices).await;
+        if let Some(notifier) = audit.notifier {
+            notifier.check(engine.now_ms(), &prices, &tracker.get_stats().await);
+        }
+        match engine.observe_quiet(&prices) {
+            Some(event @ QuietEvent::Entered { .. }) => {
+                let action = if engine.quiet_paused() { "pausing quotes" } else { "still quoting" };
+                say!("[QUIET] {} │ {}", event, action);
+            }
+            Some(event @ QuietEvent::Resumed(interval)) => {
+                say!("[QUIET] {}", event);
+                tracker.record_quiet_interval(interval).await;
+            }
+            None => {}
+        }
+        if audit.cycles.is_some() {
+            let next = CycleSnapshot {
+                cycle: cycle_count,
+                elapsed_secs: elapsed,
+                prices: prices.clone(),
+                buy_draw: None,
+                sell_draw: None,
+            };
+            record_cycle(audit, snapshot.replace(next));
+        }
 
-        let prices = aggregator.get_prices().await;
+        if let Some(narrator) = audit.narrator {
+            print_notes(&narrator.market(&prices));
+        }
 
-        if let Some(summary) = trading_engine.get_market_summary(&prices) {
-            println!("[MARKET] Median: ${:.2} │ Spread: {:.1} bps │ Best Bid: ${:.2} │ Best Ask: ${:.2}",
-                summary.median_mid,
-                summary.spread_bps,
-                summary.best_bid,
-                summary.best_ask
-            );
+        if let Some(updates) = &mut updates {
+            loop {
+                let update = match updates.try_recv() {
+                    Ok(update) => update,
+                    Err(broadcast::error::TryRecvError::Lagged(missed)) => {
+                        // Crosses in the lost updates are gone; carry on from the current quotes
+                        warn!(missed, "fell behind the quote channel");
+                        engine.on_quote_updates_missed(missed);
+                        book = feed.snapshot().await.prices;
+                        continue;
+                    }
+                    Err(_) => break,
+                };
+                book.set(update.source, update.quote);
+                for trade in engine.on_quote(&update, &book) {
+                    say!("[FILL] Resting {} crossed by {} at ${:.2}",
+                        side_label(trade.side), update.source.name(), trade.price);
+                    tracker.print_trade(&trade).await;
+                    narrate_pnl(audit.narrator, &trade);
+                    tracker.record_trade(trade).await;
+                }
+                record_confirmed(tracker, audit.narrator, engine.confirm_fills(&book)).await;
+            }
+        }
+        if let Some(paper) = &control.paper {
+            record_venue_fills(engine, tracker, audit.narrator, paper, &book).await;
         }
 
-        // Display source status
-        print!("[SOURCES] ");
-        if prices.binance.is_some() {
-            print!("Binance ✓ │ ");
-        } else {
-            print!("Binance ✗ │ ");
+        if let Some(reconciler) = audit.reconciler {
+            if reconciler.is_due(feed_time(&prices)) {
+                reconcile(engine, tracker, reconciler, &prices).await;
+            }
         }
-        if prices.jupiter.is_some() {
-            print!("Jupiter ✓ │ ");
-        } else {
-            print!("Jupiter ✗ │ ");
+
+        let quorum = engine.quorum();
+        match quorum.mode(&feed_snapshot) {
+            TradingMode::Normal => {}
+            TradingMode::Degraded { sources } => {
+                let names: Vec<&str> = sources.iter().map(|s| s.name()).collect();
+                say!("[DEGRADED] Below quorum ({}/{} fresh sources): trading off {} only │ Spread +{:.1} bps │ Size ×{:.2} │ Taker off",
+                    sources.len(), quorum.min_sources, names.join(", "),
+                    quorum.degraded_spread_bps, quorum.degraded_size_factor);
+            }
+            TradingMode::Halted if quorum.fresh(&feed_snapshot).is_crossed() => {
+                say!("[HALTED] Fresh quotes are crossed (median bid above median ask), not quoting this cycle");
+                cancel_resting(engine, control.paper.as_ref()).await;
+                continue;
+            }
+            TradingMode::Halted => {
+                say!("[HALTED] Below quorum of {} fresh sources, not quoting this cycle", quorum.min_sources);
+                cancel_resting(engine, control.paper.as_ref()).await;
+                continue;
+            }
         }
-        if prices.cowswap.is_some() {
-            println!("CowSwap ✓");
-        } else {
-            println!("CowSwap ✗");
+
+        if let Some(trade) = engine.hedge(&prices) {
+            say!("[HEDGE] Inventory over {:.4} ETH, hedged {:.4} ETH", engine.hedger().map_or(0.0, |h| h.threshold_eth), trade.amount_eth);
+            tracker.print_trade(&trade).await;
+            narrate_pnl(audit.narrator, &trade);
+            tracker.record_trade(trade).await;
         }
 
-        // Attempt buy trade
-        if let Some(trade) = trading_engine.attempt_trade(&prices, TradeSide::Buy) {
-            pnl_tracker.print_trade(&trade).await;
-            pnl_tracker.record_trade(trade).await;
-        } else {
-            println!("[SKIP] Buy trade not executed (probability miss)");
+        let risk_events = engine.risk_events();
+        for event in &risk_events[risk_events_seen..] {
+            say!("[RISK] {} breached: {}", event.limit, event.message);
+            if let Some(notifier) = audit.notifier {
+                notifier.risk_breach(event);
+            }
+        }
+        risk_events_seen = risk_events.len();
+        let risk = engine.risk_state();
+        match risk {
+            RiskState::Normal => {}
+            RiskState::ReduceOnly { limit } => {
+                say!("[RISK] Reduce-only ({}): quoting only the side that reduces {:+.4} ETH", limit, engine.position().net_eth);
+            }
+            RiskState::Halted { limit } => {
+                say!("[RISK] Kill switch ({}): not quoting", limit);
+                cancel_resting(engine, control.paper.as_ref()).await;
+                continue;
+            }
         }
 
-        // Attempt sell trade
-        if let Some(trade) = trading_engine.attempt_trade(&prices, TradeSide::Sell) {
-            pnl_tracker.print_trade(&trade).await;
-            pnl_tracker.record_trade(trade).await;
-        } else {
-            println!("[SKIP] Sell trade not executed (probability miss)");
+        if engine.quiet_paused() {
+            say!("[QUIET] Paused: market too flat and tight to quote");
+            cancel_resting(engine, control.paper.as_ref()).await;
+            print_running_stats(engine, tracker, cycle_count).await;
+            continue;
+        }
+
+        if engine.resting_orders() {
+            for side in [TradeSide::Buy, TradeSide::Sell] {
+                requote_side(engine, sandbox, tracker, audit.narrator, &prices, side, cycle_count).await;
+            }
+            if let Some(paper) = &control.paper {
+                paper.sync(&engine.open_orders()).await;
+            }
+            print_running_stats(engine, tracker, cycle_count).await;
+            continue;
         }
 
-        // Show current stats every 10 cycles
-        if cycle_count % 10 == 0 {
-            let stats = pnl_tracker.get_stats().await;
-            println!("\n[STATS] Running Total: {} trades │ PnL: ${:.2} │ Avg per trade: ${:.2}",
-                stats.total_trades,
-                stats.total_pnl,
-                stats.avg_pnl_per_trade()
-            );
+        // A two-sided quote decides both sides together, up front, off the same position
+        let quoted_from = engine.position();
+        let joint = match engine.two_sided() {
+            Some(_) => {
+                let prices = prices.clone();
+                let decide = Arc::clone(engine);
+                let decision = sandbox.run("Two-sided", cycle_count, move || decide.decide_two_sided(&prices)).await;
+                // Only a decision that made its budget is acted on
+                Some(decision.map(|decision| engine.commit_two_sided(decision)))
+            }
+            None => None,
+        };
+
+        // Attempt a buy and a sell trade
+        for (side, label) in [(TradeSide::Buy, "Buy"), (TradeSide::Sell, "Sell")] {
+            // A two-sided quote was risk-checked when it was decided; only explain a side it left out
+            let blocked = match &joint {
+                Some(Some(joint)) => joint.side(side).is_none() && !risk.allows(side, &quoted_from),
+                Some(None) => false,
+                None => !risk.allows(side, &engine.position()),
+            };
+            if blocked {
+                print_skip(cycle_count, side, "risk", None, format!("[SKIP] {} blocked by risk limits", label));
+                continue;
+            }
+            let decision = match &joint {
+                Some(joint) => joint.as_ref().map(|joint| joint.side(side).cloned()),
+                None => {
+                    let prices = prices.clone();
+                    let decide = Arc::clone(engine);
+                    let decision = sandbox.run(label, cycle_count, move || decide.decide_attempt(&prices, side)).await;
+                    decision.map(|decision| decision.map(|decision| engine.commit(decision)))
+                }
+            };
+            let Some(attempt) = decision else {
+                continue;
+            };
+            // Printed under the attempt's own log line below
+            let notes = audit.narrator.zip(attempt.as_ref()).map(|(narrator, attempt)| {
+                narrator.attempt(attempt) + &attempt.trade.as_ref().map_or(String::new(), |trade| narrator.pnl(trade))
+            });
+            if let Some(attempt) = &attempt {
+                tracker.record_attempt(attempt).await;
+                if let Some(snapshot) = &mut snapshot {
+                    match side {
+                        TradeSide::Buy => snapshot.buy_draw = Some(attempt.draw),
+                        TradeSide::Sell => snapshot.sell_draw = Some(attempt.draw),
+                    }
+                }
+            }
+
+            match attempt {
+                Some(ExecutionAttempt { trade: Some(trade), .. }) => {
+                    tracker.print_trade(&trade).await;
+                    tracker.record_trade(trade).await;
+                }
+                Some(attempt) if attempt.pending => {
+                    say!("[PENDING] {} filled at ${:.2}, confirming in {} ms",
+                        label, attempt.price, engine.fill_latency().map_or(0, |l| l.as_millis()));
+                }
+                Some(attempt) if !attempt.quoted => {
+                    print_skip(cycle_count, side, "not_quoted", Some(&attempt),
+                        format!("[SKIP] {} not quoted (expected edge {:.2} bps below minimum)", label, attempt.expected_edge_bps));
+                }
+                attempt => print_skip(cycle_count, side, "probability_miss", attempt.as_ref(),
+                    format!("[SKIP] {} trade not executed (probability miss)", label)),
+            }
+            if let Some(notes) = notes {
+                print_notes(&notes);
+            }
         }
+        if let Some(Some(joint)) = &joint {
+            tracker.record_two_sided(joint).await;
+            if let Some(trip) = joint.round_trip() {
+                say!("[ROUND TRIP] Bought and sold {:.4} ETH at ${:.2} / ${:.2} │ Captured ${:.2} ({:.1} bps) before fees",
+                    trip.amount_eth, trip.buy_price, trip.sell_price, trip.capture_usd(), trip.capture_bps());
+            }
+        }
+
+        print_running_stats(engine, tracker, cycle_count).await;
     }
 
-    // Final summary
-    println!("\n");
-    println!("╔════════════════════════════════════════════════════════════════════╗");
-    println!("║                     SIMULATION COMPLETE                            ║");
-    println!("╚════════════════════════════════════════════════════════════════════╝");
-    
-    pnl_tracker.print_summary().await;
+    // Fills still inside their latency when the session ends are confirmed at the final prices
+    let prices = feed.get_prices().await;
+    record_confirmed(tracker, audit.narrator, engine.confirm_all(&prices)).await;
+    tracker.close_marks(&prices).await;
+    if let Some(interval) = engine.close_quiet(feed_time(&prices)) {
+        tracker.record_quiet_interval(interval).await;
+    }
+    if let Some(reconciler) = audit.reconciler {
+        reconcile(engine, tracker, reconciler, &prices).await;
+    }
+    control.portfolio.publish(cycle_count, &prices).await;
+    record_cycle(audit, snapshot);
+    if let Some(Err(e)) = audit.cycles.map(CycleRecorder::flush) {
+        error!("Failed to flush cycle recording: {}", e);
+    }
+    if let Some(Err(e)) = engine.drop_copy().map(DropCopy::flush) {
+        error!("Failed to flush drop copy: {}", e);
+    }
+    #[cfg(feature = "tui")]
+    if let Some((dashboard, log)) = dashboard {
+        if let Err(e) = dashboard.stop().await {
+            error!("Dashboard failed: {:#}", e);
+        }
+        say!("[TUI] Session log written to {}", log.display());
+    }
 
-    // Show last few trades
-    println!("Last 5 Trades:");
-    println!("─────────────────────────────────────────────────────────────────────");
-    let recent_trades = pnl_tracker.get_recent_trades(5).await;
-    for trade in recent_trades.iter().rev() {
-        let side = match trade.side {
-            TradeSide::Buy => "BUY ",
-            TradeSide::Sell => "SELL",
-        };
-        println!("  {} │ ${:.2} │ {:.4} ETH │ PnL: ${:.2}",
-            side, trade.price, trade.amount_eth, trade.pnl);
+    LoopOutcome {
+        cycles: cycle_count,
+        last_mid,
     }
-    println!("─────────────────────────────────────────────────────────────────────\n");
+}
 
-    Ok(())
+/// Flags that drive a single market's feed, books or side outputs, refused with `--markets`
+const SINGLE_MARKET_FLAGS: [&str; 14] = [
+    "--replay", "--tui", "--demo", "--record", "--record-cycles", "--record-draws", "--replay-draws",
+    "--drop-copy", "--db", "--heatmap", "--calibrate-fills", "--report", "--latency-arb", "--synthetic",
+];
+
+/// Session-wide settings shared by every market of `run_markets`
+struct MarketsRun<'a> {
+    /// Each market on its own simulated books rather than the live sources
+    offline: bool,
+    session_name: Option<String>,
+    store: &'a SessionStore,
+    speed: ReplaySpeed,
+    timezone: ReportTimezone,
+    strategy_budget: Duration,
+    split_venues: bool,
+    output_dir: Option<String>,
+}
+
+/// One pair's books, engine and PnL inside a multi-market session
+struct MarketSession {
+    market: MarketConfig,
+    config: Config,
+    feed: MarketFeed,
+    engine: Arc<TradingEngine>,
+    tracker: PnLTracker,
+    reconciler: Option<Reconciler>,
+    control: Control,
+}
+
+/// Where one market of a multi-market session gets its prices
+enum MarketFeed {
+    /// Its own simulated books, started at the session's shared instant
+    Offline(OrderBookVenue),
+    /// The live sources, quoting the market's pair
+    Live(Box<PriceAggregator>),
+}
+
+impl PriceFeed for MarketFeed {
+    async fn get_prices(&self) -> AggregatedPrices {
+        match self {
+            MarketFeed::Offline(venue) => venue.get_prices().await,
+            MarketFeed::Live(aggregator) => aggregator.get_prices().await,
+        }
+    }
+
+    async fn snapshot(&self) -> PriceSnapshot {
+        match self {
+            MarketFeed::Offline(venue) => venue.snapshot().await,
+            MarketFeed::Live(aggregator) => aggregator.snapshot().await,
+        }
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<QuoteUpdate> {
+        match self {
+            MarketFeed::Offline(venue) => venue.subscribe(),
+            MarketFeed::Live(aggregator) => aggregator.subscribe(),
+        }
+    }
+
+    fn subscribe_events(&self) -> Option<broadcast::Receiver<FeedEvent>> {
+        match self {
+            MarketFeed::Offline(venue) => venue.subscribe_events(),
+            MarketFeed::Live(aggregator) => Some(aggregator.subscribe_events()),
+        }
+    }
+}
+
+/// `[[markets.pairs]]` / `--markets`: a session per pair, run side by side on their own feeds, engines and
+/// trackers, then a cross-market summary. Offline each pair gets its own simulated books; live each gets a
+/// price aggregator quoting its pair. Stop and summary commands reach every market.
+async fn run_markets(config: &Config, args: &[String], run: MarketsRun<'_>) -> Result<()> {
+    if let Some(flag) = SINGLE_MARKET_FLAGS.iter().find(|&&flag| args.iter().any(|arg| arg == flag)) {
+        bail!("{} covers a single market; leave it off when running several", flag);
+    }
+    if config.api.listen.is_some() || config.websocket.listen.is_some() || config.export.http_url.is_some()
+        || config.notify.webhook_url.is_some() || config.publish.url.is_some() {
+        bail!("the HTTP API, websocket stream, trade export, webhook notifications and bus publishing cover a single market; leave them off when running several");
+    }
+    if config.paper.enabled {
+        bail!("paper trading covers a single market; leave it off when running several");
+    }
+
+    let session_id = session::new_session_id();
+    let started_at = chrono::Utc::now().timestamp_millis();
+    let sandbox = StrategySandbox::new(run.strategy_budget);
+    let (_command_sender, mut commands) = control::spawn_control_listener(true);
+    let mut senders = Vec::new();
+    let mut sessions = Vec::new();
+    let mut mark_to_market = Vec::new();
+    // Every market's book starts at the same instant, so their timelines line up
+    let start = SimulatedClock::new(SystemClock.now_ms());
+    for (i, market) in config.markets.pairs.iter().enumerate() {
+        let config = market.apply(config, i);
+        let (feed, mut engine) = if run.offline {
+            let venue = OrderBookVenue::new(config.book_sim.config(&config.synthetic, &config.simulation), run.speed, &start);
+            let engine = config.session_engine(Box::new(venue.clone()))?
+                .with_draws(DrawSource::seeded(venue.config().fair.seed))
+                .with_clock(venue.sim_clock());
+            (MarketFeed::Offline(venue), engine)
+        } else {
+            let engine = config.session_engine(config.execution.execution_model(config.simulation.trade_interval_secs as f64)?)?;
+            let pair = config.tokens.pair().with_context(|| format!("market {}", market.pair))?;
+            let mut aggregator = PriceAggregator::new()
+                .with_sources(config.sources.clone())
+                .with_pair(pair.clone())
+                .with_quote_notional(config.quote_notional())
+                .with_stale_after(engine.quorum().max_quote_age());
+            if let Some(uniswap) = config.sources.uniswap(config.quote_notional(), &pair)? {
+                aggregator = aggregator.with_uniswap(uniswap);
+            }
+            (MarketFeed::Live(Box::new(aggregator)), engine)
+        };
+        if run.split_venues {
+            engine = engine.with_router(SmartOrderRouter::new());
+        }
+        let engine = Arc::new(engine);
+        let spill_path = run.store.dir().join(format!("{}-{}-trades.jsonl", session_id, market.slug()));
+        let mut tracker = PnLTracker::new()
+            .with_cost_basis(config.simulation.cost_basis()?)
+            .with_mark_interval(config.simulation.mark_interval())
+            .with_timezone(run.timezone)
+            .with_history(config.simulation.trade_history(spill_path)?);
+        if let Some(rates) = config.funding.rates() {
+            tracker = tracker.with_funding(rates);
+        }
+        mark_to_market.push(spawn_mark_to_market(feed.subscribe(), feed.get_prices().await, &tracker));
+        let (sender, market_commands) = mpsc::unbounded_channel();
+        senders.push(sender);
+        let control = Control {
+            commands: market_commands,
+            dir: run.store.dir().to_path_buf(),
+            session_id: format!("{}-{}", session_id, market.slug()),
+            portfolio: Simulator::new(Arc::clone(&engine), tracker.clone()),
+            paper: None,
+            #[cfg(feature = "sqlite")]
+            database: None,
+            #[cfg(feature = "tui")]
+            dashboard: None,
+        };
+        sessions.push(MarketSession {
+            market: market.clone(),
+            reconciler: config.reconcile.reconciler(),
+            config,
+            feed,
+            engine,
+            tracker,
+            control,
+        });
+    }
+    // Stdin and signal commands apply to the whole session, so every market gets a copy
+    tokio::spawn(async move {
+        while let Some(command) = commands.recv().await {
+            for sender in &senders {
+                let _ = sender.send(command);
+            }
+        }
+    });
+
+    let pairs: Vec<&str> = sessions.iter().map(|s| s.market.pair.as_str()).collect();
+    if output::json() {
+        output::emit("start", &serde_json::json!({
+            "session_id": session_id,
+            "session_name": run.session_name,
+            "markets": pairs,
+            "duration_secs": config.simulation.duration_secs,
+            "trade_interval_secs": config.simulation.trade_interval_secs,
+            "quoting": config.quoting.strategy,
+            "timezone": run.timezone.name(),
+        }));
+    } else {
+        println!("\n╔════════════════════════════════════════════════════════════════════╗");
+        println!("║              MARKET MAKER SIMULATOR - {:<29}║", format!("{} MARKETS", sessions.len()));
+        println!("╠════════════════════════════════════════════════════════════════════╣");
+        for session in &sessions {
+            let notional = session.config.simulation.notional_per_trade.separated_string();
+            let detail = match &session.feed {
+                MarketFeed::Offline(venue) => {
+                    let fair = &venue.config().fair;
+                    format!("${}/trade │ from ${} │ seed {}", notional, fair.initial_price.separated_string(), fair.seed)
+                }
+                MarketFeed::Live(_) => format!("${}/trade │ live", notional),
+            };
+            println!("║ {:<22} {:<44}║", session.market.pair, detail);
+        }
+        println!("║ Simulation Duration:   {:<44}║", format!("{} minutes", config.simulation.duration_secs / 60));
+        println!("║ Trade Interval:        {:<44}║", format!("{} seconds", config.simulation.trade_interval_secs));
+        println!("║ Quoting Strategy:      {:<44}║", config.quoting.strategy);
+        println!("║ Session:               {:<44}║", run.session_name.as_deref().unwrap_or(&session_id));
+        println!("╚════════════════════════════════════════════════════════════════════╝\n");
+    }
+
+    if run.offline {
+        say!("[START] Beginning offline session across {}...\n", pairs.join(", "));
+    } else {
+        info!("Starting a price aggregator per market...");
+        for session in &sessions {
+            if let MarketFeed::Live(aggregator) = &session.feed {
+                aggregator.start().await?;
+            }
+        }
+        info!("Waiting {} seconds for initial price data...", config.simulation.warmup_secs);
+        sleep(Duration::from_secs(config.simulation.warmup_secs)).await;
+        say!("[START] Beginning market making session across {}...\n", pairs.join(", "));
+    }
+    let sandbox = &sandbox;
+    let loops = sessions.iter_mut().map(|session| {
+        let span = tracing::info_span!("market", pair = %session.market.pair);
+        let pair = session.market.pair.clone();
+        let step = Duration::from_secs(session.config.simulation.trade_interval_secs);
+        output::in_market(pair, async move {
+            let audit = Audit {
+                reconciler: session.reconciler.as_ref(),
+                cycles: None,
+                narrator: None,
+                notifier: None,
+            };
+            let mut schedule = match &session.feed {
+                MarketFeed::Offline(venue) => Schedule::Offline { venue, step },
+                MarketFeed::Live(_) => Schedule::Live {
+                    interval: interval(step),
+                    duration: session.config.simulation.duration_secs,
+                    start: tokio::time::Instant::now(),
+                },
+            };
+            run_trading_loop(&session.feed, &mut schedule, &mut session.control, &session.engine, sandbox,
+                &session.tracker, &audit).await
+        }.instrument(span))
+    });
+    let outcomes = futures_util::future::join_all(loops).await;
+    for session in &sessions {
+        if let MarketFeed::Live(aggregator) = &session.feed {
+            aggregator.stop();
+        }
+    }
+    for task in mark_to_market {
+        stop_mark_to_market(task).await;
+    }
+
+    if !output::json() {
+        println!("\n");
+        println!("╔════════════════════════════════════════════════════════════════════╗");
+        println!("║                     SIMULATION COMPLETE                            ║");
+        println!("╚════════════════════════════════════════════════════════════════════╝");
+    }
+    let mut results = Vec::new();
+    for (session, outcome) in sessions.iter().zip(outcomes) {
+        if let Err(e) = session.tracker.flush_trades().await {
+            error!("{:#}", e);
+        }
+        let pair = session.market.pair.clone();
+        let position = session.engine.position();
+        if !output::json() {
+            println!("\n── {} ──", pair);
+        }
+        // Under its own header, so the lines need no pair label
+        session.tracker.print_summary().await;
+        print_equity_summary(&session.tracker).await;
+        if let Some(report) = session.engine.risk_report(&session.tracker.timezone()) {
+            say!("{}", report);
+        }
+        if let Some(stats) = session.engine.order_stats() {
+            let pulled = session.engine.cancel_resting();
+            say!("{} │ {} still open at the end, cancelled\n", stats, pulled.len());
+        }
+        if let Some(reconciler) = &session.reconciler {
+            say!("{}", reconciler.report(&session.tracker.timezone()));
+        }
+        if let MarketFeed::Live(aggregator) = &session.feed {
+            for (source, stats) in aggregator.http_stats().await {
+                say!("{} HTTP: {}\n", source.name(), stats);
+            }
+        }
+        match outcome.last_mid {
+            Some(mid) => say!("Final Position: {} │ Unrealized: ${:.2} (mark ${:.2})\n",
+                position, position.unrealized_pnl(mid), mid),
+            None => say!("Final Position: {}\n", position),
+        }
+
+        if let Some(dir) = &run.output_dir {
+            std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir))?;
+            let csv = PathBuf::from(dir).join(format!("{}-trades.csv", session.control.session_id));
+            let json = PathBuf::from(dir).join(format!("{}-trades.json", session.control.session_id));
+            session.tracker.export_csv(&csv).await?;
+            session.tracker.export_json(&json).await?;
+            say!("Exported {} trades and stats to {} (+ -stats.csv) and {}\n", pair, csv.display(), json.display());
+        }
+
+        let stats = session.tracker.get_stats().await;
+        let record = SessionRecord {
+            id: session.control.session_id.clone(),
+            name: Some(match &run.session_name {
+                Some(name) => format!("{} {}", name, pair),
+                None => pair.clone(),
+            }),
+            started_at,
+            ended_at: chrono::Utc::now().timestamp_millis(),
+            execution_model: session.engine.execution_model().name(),
+            notional_per_trade: session.config.simulation.notional_per_trade,
+            cycles: outcome.cycles,
+            stats: stats.clone(),
+        };
+        run.store.save(&record)?;
+        results.push(MarketResult {
+            pair,
+            session_id: record.id,
+            cycles: outcome.cycles,
+            stats,
+            position,
+            last_mid: outcome.last_mid,
+        });
+    }
+    sandbox.print_report();
+
+    let report = MarketsReport { markets: results };
+    if output::json() {
+        output::emit("summary", &serde_json::json!({
+            "session_id": session_id,
+            "markets": report.markets,
+            "total_pnl": report.total_pnl(),
+            "total_trades": report.total_trades(),
+            "total_notional": report.total_notional(),
+            "total_fees": report.total_fees(),
+            "total_unrealized_pnl": report.total_unrealized(),
+            "gross_exposure": report.gross_exposure(),
+        }));
+    } else {
+        println!("{}", report);
+    }
+    say!("[SESSION] Saved {} market sessions ({}-<pair>) to {}", report.markets.len(), session_id, run.store.dir().display());
+    Ok(())
+}
+
+/// Serve the HTTP API over the session when a listen address is configured
+#[cfg(feature = "api")]
+fn start_api(config: &Config, updates: broadcast::Receiver<QuoteUpdate>, prices: AggregatedPrices, simulator: &Simulator) -> Result<()> {
+    let Some(listen) = &config.api.listen else {
+        return Ok(());
+    };
+    let flow = config.api.flow_control()?;
+    let server = ApiServer::spawn(listen, updates, prices, simulator.clone(), flow)?;
+    info!("HTTP API on http://{} (/prices, /stats, /trades?limit=N, /position, /status, /strategy, /candles, /stream, /clients)", server.addr());
+    info!("Stream clients buffer {} events each, then {}", flow.buffer, flow.policy);
+    Ok(())
+}
+
+#[cfg(not(feature = "api"))]
+fn start_api(_config: &Config, _updates: broadcast::Receiver<QuoteUpdate>, _prices: AggregatedPrices, _simulator: &Simulator) -> Result<()> {
+    Ok(())
+}
+
+/// Stream quotes, trades and stats over a websocket when a listen address is configured
+async fn start_ws(config: &Config, updates: broadcast::Receiver<QuoteUpdate>, simulator: &Simulator) -> Result<Option<WsServer>> {
+    let Some(listen) = &config.websocket.listen else {
+        return Ok(None);
+    };
+    let flow = config.websocket.flow_control()?;
+    let server = WsServer::spawn(listen, updates, simulator.clone(), flow, config.websocket.stats_interval()).await?;
+    info!("Websocket stream on ws://{} (quotes, trades, stats every {}s; {} events buffered per client, then {})",
+        server.addr(), config.websocket.stats_interval().as_secs(), flow.buffer, flow.policy);
+    if let Some(path) = &config.websocket.addr_file {
+        // Written whole and renamed into place, so a reader never sees half an address
+        let partial = format!("{}.partial", path);
+        std::fs::write(&partial, server.addr().to_string()).with_context(|| format!("failed to write {}", partial))?;
+        std::fs::rename(&partial, path).with_context(|| format!("failed to write {}", path))?;
+    }
+    Ok(Some(server))
+}
+
+/// Publish trades and quote updates to Kafka or NATS when a bus URL is configured
+async fn start_publisher(target: Option<PublishTarget>, updates: broadcast::Receiver<QuoteUpdate>, tracker: &PnLTracker, session_id: &str) -> Result<Option<EventPublisher>> {
+    let Some(target) = target else {
+        return Ok(None);
+    };
+    info!("Publishing trades to {} on {}{}", target.trades_topic, target.url,
+        target.quotes_topic.as_ref().map_or(String::new(), |topic| format!(", quote updates to {}", topic)));
+    let publisher = EventPublisher::spawn(target, session_id.to_string(), updates, tracker.subscribe_trades()).await?;
+    Ok(Some(publisher))
+}
+
+/// Print and record fills confirmed after the fill latency
+async fn record_confirmed(tracker: &PnLTracker, narrator: Option<&Narrator>, trades: Vec<Trade>) {
+    for trade in trades {
+        say!("[CONFIRM] {} #{} confirmed │ Adverse selection: ${:.2}",
+            side_label(trade.side), trade.id, trade.adverse_selection_usd);
+        tracker.print_trade(&trade).await;
+        narrate_pnl(narrator, &trade);
+        tracker.record_trade(trade).await;
+    }
+}
+
+/// Book every fill the testnet reported since the last poll
+async fn record_venue_fills(engine: &TradingEngine, tracker: &PnLTracker, narrator: Option<&Narrator>, paper: &PaperTrader, prices: &AggregatedPrices) {
+    for fill in paper.poll_fills().await {
+        let Some(trade) = engine.on_venue_fill(&fill, prices) else {
+            continue;
+        };
+        say!("[FILL] Resting {} #{} filled on testnet {} at ${:.2} │ {:.4} ETH{}",
+            side_label(trade.side), fill.order_id, paper.symbol(), trade.price, trade.amount_eth,
+            if fill.complete { "" } else { " (partial)" });
+        tracker.print_trade(&trade).await;
+        narrate_pnl(narrator, &trade);
+        tracker.record_trade(trade).await;
+    }
+}
+
+/// Pull every resting order, on the testnet too when paper trading
+async fn cancel_resting(engine: &TradingEngine, paper: Option<&PaperTrader>) {
+    for order in engine.cancel_resting() {
+        say!("[ORDER] #{} {} cancelled", order.id, side_label(order.side));
+    }
+    if let Some(paper) = paper {
+        paper.cancel_all().await;
+    }
+}
+
+/// Narrator notes under the line they explain; a step with nothing to explain prints nothing
+fn print_notes(notes: &str) {
+    if !notes.is_empty() {
+        say!("{}", notes.trim_end());
+    }
+}
+
+/// The cycle banner, stamped with the snapshot's session time, market line and venue status, or one
+/// `market` event under `--json`
+fn print_cycle_header(
+    tracker: &PnLTracker,
+    snapshot: &PriceSnapshot,
+    max_age: Duration,
+    summary: Option<MarketSummary>,
+    cycle_count: u64,
+    elapsed: u64,
+    remaining: u64,
+) {
+    if output::json() {
+        output::emit("market", &serde_json::json!({
+            "cycle": cycle_count,
+            "elapsed_secs": elapsed,
+            "remaining_secs": remaining,
+            "summary": summary,
+            "prices": snapshot.prices,
+            "ages_ms": snapshot.sources().map(|s| (s.source.name(), s.age_ms)).collect::<BTreeMap<_, _>>(),
+        }));
+        return;
+    }
+    say!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    say!("Cycle #{} │ {} │ Elapsed: {}s │ Remaining: {}s",
+        cycle_count,
+        tracker.timezone().format_millis(snapshot.taken_at, "%H:%M:%S %Z"),
+        elapsed,
+        remaining
+    );
+    say!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    if let Some(summary) = summary {
+        say!("[MARKET] Median: ${:.2} │ Spread: {:.1} bps │ Best Bid: ${:.2} │ Best Ask: ${:.2}",
+            summary.median_mid,
+            summary.spread_bps,
+            summary.best_bid,
+            summary.best_ask
+        );
+    }
+
+    // Uniswap only runs with an RPC URL, so leave it out until it reports
+    let max_age_ms = max_age.as_millis() as i64;
+    let sources: Vec<String> = Source::ALL
+        .iter()
+        .filter(|&&source| source != Source::Uniswap || snapshot.prices.uniswap.is_some())
+        .map(|&source| {
+            let name = match source {
+                Source::Binance => "Binance",
+                Source::Jupiter => "Jupiter",
+                Source::CowSwap => "CowSwap",
+                Source::Uniswap => "Uniswap",
+            };
+            match snapshot.age_ms(source) {
+                Some(age) if age <= max_age_ms => format!("{} ✓ {:.1}s", name, age as f64 / 1000.0),
+                Some(age) => format!("{} ⚠ {:.1}s stale", name, age as f64 / 1000.0),
+                None => format!("{} ✗", name),
+            }
+        })
+        .collect();
+    say!("[SOURCES] {}", sources.join(" │ "));
+}
+
+/// A side that didn't trade this cycle: a `[SKIP]` line, or a `skip` event with the attempt's odds under `--json`
+fn print_skip(cycle_count: u64, side: TradeSide, reason: &str, attempt: Option<&ExecutionAttempt>, message: String) {
+    if !output::json() {
+        say!("{}", message);
+        return;
+    }
+    output::emit("skip", &serde_json::json!({
+        "cycle": cycle_count,
+        "side": side,
+        "reason": reason,
+        "price": attempt.map(|a| a.price),
+        "execution_prob": attempt.map(|a| a.execution_prob),
+        "draw": attempt.map(|a| a.draw),
+        "expected_edge_bps": attempt.map(|a| a.expected_edge_bps),
+    }));
+}
+
+fn narrate_pnl(narrator: Option<&Narrator>, trade: &Trade) {
+    if let Some(narrator) = narrator {
+        print_notes(&narrator.pnl(trade));
+    }
+}
+
+fn record_cycle(audit: &Audit<'_>, snapshot: Option<CycleSnapshot>) {
+    if let (Some(recorder), Some(snapshot)) = (audit.cycles, snapshot) {
+        if let Err(e) = recorder.record(snapshot) {
+            error!("Failed to record cycle: {}", e);
+        }
+    }
+}
+
+/// Latest quote time in `prices`, or the wall clock before any quote
+fn feed_time(prices: &AggregatedPrices) -> i64 {
+    prices.quotes().map(|q| q.timestamp).max().unwrap_or_else(|| chrono::Utc::now().timestamp_millis())
+}
+
+/// High, low and final equity over the session's mark-to-market time series
+async fn print_equity_summary(tracker: &PnLTracker) {
+    let series = tracker.equity_series().await;
+    let Some(last) = series.last() else {
+        return;
+    };
+    let high = series.iter().map(|p| p.equity()).fold(f64::MIN, f64::max);
+    let low = series.iter().map(|p| p.equity()).fold(f64::MAX, f64::min);
+    say!("Equity: {} marks every {}s │ High ${:.2} │ Low ${:.2} │ Final ${:.2} (mark ${:.2})\n",
+        series.len(), tracker.mark_interval().as_secs(), high, low, last.equity(), last.mark_price);
+}
+
+/// PnL and trades per time bucket, with a bar per bucket and how concentrated the PnL was
+async fn print_pnl_buckets(tracker: &PnLTracker, bucket: Duration) {
+    let buckets = tracker.equity_curve(bucket).await;
+    if buckets.is_empty() {
+        return;
+    }
+    let timezone = tracker.timezone();
+    let widest = buckets.iter().map(|b| b.pnl.abs()).fold(0.0, f64::max).max(f64::EPSILON);
+    say!("PnL per {}s bucket ({}):", bucket.as_secs(), timezone);
+    say!("  {:<10} {:>7} {:>12} {:>12} {:>12}", "START", "TRADES", "PNL", "CUMULATIVE", "EQUITY");
+    for b in &buckets {
+        let bar = "█".repeat((b.pnl.abs() / widest * 20.0).round() as usize);
+        say!("  {:<10} {:>7} {:>12.2} {:>12.2} {:>12} {}{}",
+            timezone.format_time(b.start), b.trades, b.pnl, b.cumulative_pnl,
+            b.equity.map_or("-".to_string(), |e| format!("{:.2}", e)),
+            if b.pnl < 0.0 { "-" } else { "+" }, bar);
+    }
+
+    // Share of the winning buckets' PnL that came from the best tenth of all buckets
+    let mut wins: Vec<f64> = buckets.iter().map(|b| b.pnl).filter(|&pnl| pnl > 0.0).collect();
+    wins.sort_by(|a, b| b.total_cmp(a));
+    let top = buckets.len().div_ceil(10);
+    let total: f64 = wins.iter().sum();
+    if total > 0.0 {
+        let share = wins.iter().take(top).sum::<f64>() / total;
+        say!("  Best {} of {} buckets made {:.0}% of the winning buckets' PnL\n", top, buckets.len(), share * 100.0);
+    } else {
+        say!();
+    }
+}
+
+/// Check the tracker's ledger, plus fills the venue executed that are still confirming, against the venue's account
+async fn reconcile(engine: &TradingEngine, tracker: &PnLTracker, reconciler: &Reconciler, prices: &AggregatedPrices) {
+    let Some(venue) = engine.execution_model().account() else {
+        return;
+    };
+    let mut local = tracker.position().await;
+    let mut fills = tracker.get_stats().await.total_trades as u64;
+    for trade in engine.in_flight_fills() {
+        local.apply(&trade);
+        fills += 1;
+    }
+    if let Some(reconciliation) = reconciler.check(feed_time(prices), local, fills, venue) {
+        say!("[RECONCILE] Local ledger diverged from the venue: {}", reconciliation);
+    }
+}
+
+/// Show current stats every 10 cycles
+async fn print_running_stats(engine: &TradingEngine, tracker: &PnLTracker, cycle_count: u64) {
+    if cycle_count.is_multiple_of(10) {
+        let stats = tracker.get_stats().await;
+        if output::json() {
+            output::emit("stats", &serde_json::json!({
+                "cycle": cycle_count,
+                "stats": stats,
+                "position": engine.position(),
+            }));
+            return;
+        }
+        say!("\n[STATS] Running Total: {} trades │ PnL: ${:.2} │ Avg per trade: ${:.2}",
+            stats.total_trades,
+            stats.total_pnl,
+            stats.avg_pnl_per_trade()
+        );
+        say!("[POSITION] {}", engine.position());
+    }
+}
+
+/// Place, re-price or pull our resting order on one side
+async fn requote_side(
+    engine: &Arc<TradingEngine>,
+    sandbox: &StrategySandbox,
+    tracker: &PnLTracker,
+    narrator: Option<&Narrator>,
+    prices: &AggregatedPrices,
+    side: TradeSide,
+    cycle_count: u64,
+) {
+    let label = side_label(side);
+    let decision = {
+        let engine = Arc::clone(engine);
+        let prices = prices.clone();
+        sandbox.run(label, cycle_count, move || engine.decide_requote(&prices, side)).await
+    };
+    // Only a decision that made its budget reaches the order book
+    let Some(decision) = decision else {
+        return;
+    };
+    let requote = decision.and_then(|decision| engine.commit_requote(side, decision));
+
+    match requote {
+        Some(Requote::Taken(attempt)) => {
+            tracker.record_attempt(&attempt).await;
+            if let Some(narrator) = narrator {
+                print_notes(&narrator.attempt(&attempt));
+            }
+            if let Some(trade) = attempt.trade {
+                tracker.print_trade(&trade).await;
+                narrate_pnl(narrator, &trade);
+                tracker.record_trade(trade).await;
+            }
+        }
+        Some(Requote::Order(OrderAction::Placed(order))) => {
+            say!("[ORDER] #{} {} placed @ ${:.2} │ ${} │ edge {:.2} bps",
+                order.id, label, order.price, order.notional.separated_string(), order.expected_edge_bps);
+            if let Some(narrator) = narrator {
+                print_notes(&narrator.placed(&order));
+            }
+        }
+        Some(Requote::Order(OrderAction::Amended { order, previous_price })) => {
+            say!("[ORDER] #{} {} amended ${:.2} → ${:.2}", order.id, label, previous_price, order.price);
+        }
+        Some(Requote::Order(OrderAction::Kept(order))) => {
+            say!("[ORDER] #{} {} resting @ ${:.2}", order.id, label, order.price);
+        }
+        Some(Requote::Order(OrderAction::Cancelled(order))) => {
+            say!("[ORDER] #{} {} cancelled, no longer quoting", order.id, label);
+        }
+        None => print_skip(cycle_count, side, "not_quoted", None, format!("[SKIP] {} not quoted", label)),
+    }
+}
+
+fn side_label(side: TradeSide) -> &'static str {
+    match side {
+        TradeSide::Buy => "Buy",
+        TradeSide::Sell => "Sell",
+    }
+}
+
+/// Diagnostics go to stderr through `tracing`, filtered by `MM_LOG` (env-filter syntax, `info` by default),
+/// as JSON lines under `--json`. The session log itself stays on stdout.
+fn init_logging(json: bool) -> Result<()> {
+    let filter = EnvFilter::try_from_env(format!("{}LOG", ENV_PREFIX)).or_else(|_| EnvFilter::try_new("info"))?;
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::stderr().is_terminal())
+        .with_target(false);
+    let result = if json { subscriber.json().try_init() } else { subscriber.try_init() };
+    result.map_err(|e| anyhow::anyhow!(e)).context("failed to install the log subscriber")
+}
+
+/// Value following a `--flag` on the command line, if present
+fn arg_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+}
+
+/// Positive number of seconds following a `--flag`, or `default` without it
+fn arg_secs(args: &[String], flag: &str, default: f64) -> Result<Duration> {
+    let Some(value) = arg_value(args, flag) else {
+        return Ok(Duration::from_secs_f64(default));
+    };
+    let secs: f64 = value.parse().with_context(|| format!("{} takes seconds, got '{}'", flag, value))?;
+    match Duration::try_from_secs_f64(secs) {
+        Ok(duration) if secs > 0.0 => Ok(duration),
+        _ => bail!("{} must be a positive number of seconds, got '{}'", flag, value),
+    }
+}
+
+/// `convert <input> <output>`: re-encode a tick file, format chosen by extension
+fn run_convert_command(args: &[String]) -> Result<()> {
+    let (input, output) = match args {
+        [input, output, ..] => (input, output),
+        _ => bail!("usage: convert <input.{{csv,jsonl,bin}}> <output.{{csv,jsonl,bin}}>"),
+    };
+
+    let count = recorder::convert_ticks(input, output)?;
+    let size = |path: &str| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    println!("Converted {} ticks: {} ({} bytes) -> {} ({} bytes)",
+        count, input, size(input), output, size(output));
+    Ok(())
+}
+
+fn run_contribution_command(args: &[String]) -> Result<()> {
+    let path = match args.first() {
+        Some(path) if !path.starts_with("--") => path,
+        _ => bail!("usage: contribution <file.{{csv,jsonl,bin}}> [--interval-ms N]"),
+    };
+    let interval_ms: i64 = arg_value(args, "--interval-ms")
+        .map(|s| s.parse())
+        .transpose()?
+        .unwrap_or(contribution::DEFAULT_SAMPLE_INTERVAL_MS);
+
+    let ticks = recorder::read_ticks(path)?;
+    if ticks.is_empty() {
+        bail!("no ticks in {}", path);
+    }
+    print!("{}", contribution::analyze(&ticks, interval_ms));
+    Ok(())
+}
+
+/// `liquidity <file> [--out profile.json]`: build an intraday liquidity profile from a tick recording
+fn run_liquidity_command(args: &[String]) -> Result<()> {
+    let path = match args.first() {
+        Some(path) if !path.starts_with("--") => path,
+        _ => bail!("usage: liquidity <file.{{csv,jsonl,bin}}> [--out profile.json]"),
+    };
+    let ticks = recorder::read_ticks(path)?;
+    if ticks.is_empty() {
+        bail!("no ticks in {}", path);
+    }
+    let profile = LiquidityProfile::from_ticks(&ticks);
+    print!("{}", profile);
+    if let Some(out) = arg_value(args, "--out") {
+        profile.save(&out)?;
+        println!("Profile written to {}", out);
+    }
+    Ok(())
+}
+
+/// `whatif <cycles.jsonl> [--config alt.toml] [--set key=value]...`: re-run only the decision layer over a
+/// session's recorded cycle snapshots, with its recorded parameters and with an alternate set, and compare
+async fn run_whatif_command(args: &[String]) -> Result<()> {
+    let path = match args.first() {
+        Some(path) if !path.starts_with("--") => path,
+        _ => bail!("usage: whatif <cycles.jsonl> [--config alt.toml] [--set section.key=value]..."),
+    };
+    let (recorded, cycles) = whatif::read_cycles(path)?;
+    let mut alternate = match arg_value(args, "--config") {
+        Some(config) => Config::load(config)?,
+        None => recorded.clone(),
+    };
+    for pair in args.windows(2).filter(|pair| pair[0] == "--set") {
+        alternate.set_value(&pair[1])?;
+    }
+    alternate.validate()?;
+    if recorded.orders.resting || alternate.orders.resting {
+        bail!("resting orders fill off quote updates between cycles, which a cycle recording doesn't capture");
+    }
+    println!("Re-simulating {} cycles from {}...\n", cycles.len(), path);
+
+    let engine = |config: &Config| -> Result<TradingEngine> {
+        let model = config.execution.execution_model(config.simulation.trade_interval_secs as f64)?;
+        // Decisions without a recorded draw get the same fallback draws in both runs
+        Ok(config.session_engine(model)?.with_draws(DrawSource::seeded(recorded.synthetic.seed)))
+    };
+    let baseline = whatif::resimulate(engine(&recorded)?, &cycles, recorded.funding.rates()).await;
+    let alternate_run = whatif::resimulate(engine(&alternate)?, &cycles, alternate.funding.rates()).await;
+    let report = WhatIfReport {
+        cycles: cycles.len(),
+        baseline,
+        alternate: alternate_run,
+        changes: whatif::config_changes(&recorded, &alternate)?,
+    };
+    print!("{}", report);
+    Ok(())
+}
+
+/// `sweep <ticks> --vary section.key=v1,v2 [--vary ...] [--out sweep.csv]`: replay one tick recording through every
+/// combination of the varied values on top of the resolved config, each run with the same seeded fill draws
+async fn run_sweep_command(config: &Config, args: &[String]) -> Result<()> {
+    let path = match args.first() {
+        Some(path) if !path.starts_with("--") => path,
+        _ => bail!("usage: sweep <ticks.{{csv,jsonl,bin}}> --vary section.key=v1,v2 [--vary ...] [--out sweep.csv]"),
+    };
+    let axes = args
+        .windows(2)
+        .filter(|pair| pair[0] == "--vary")
+        .map(|pair| pair[1].parse())
+        .collect::<Result<Vec<SweepAxis>>>()?;
+    if axes.is_empty() {
+        bail!("nothing to sweep; add at least one --vary section.key=v1,v2");
+    }
+    let ticks = recorder::read_ticks(path)?;
+    if ticks.is_empty() {
+        bail!("tick file {} is empty", path);
+    }
+    let points = sweep::grid(config, &axes)?;
+    for point in &points {
+        if point.config.orders.resting {
+            bail!("resting orders fill off quote updates between cycles, which a sweep doesn't replay");
+        }
+        if point.config.simulation.trade_interval_secs == 0 {
+            bail!("simulation.trade_interval_secs must be at least 1 to step through the recording");
+        }
+    }
+    println!("Sweeping {} parameter sets over {} ticks from {}...\n", points.len(), ticks.len(), path);
+
+    // Points share their cycle snapshots unless they change the trade interval
+    let mut snapshots: HashMap<u64, Vec<CycleSnapshot>> = HashMap::new();
+    let mut results = Vec::with_capacity(points.len());
+    for point in points {
+        let interval = point.config.simulation.trade_interval_secs;
+        if let Entry::Vacant(entry) = snapshots.entry(interval) {
+            entry.insert(sweep::cycles(&ticks, Duration::from_secs(interval)).await);
+        }
+        let cycles = &snapshots[&interval];
+        let model = point.config.execution.execution_model(interval as f64)?;
+        let engine = point.config.session_engine(model)?.with_draws(DrawSource::seeded(point.config.synthetic.seed));
+        results.push(SweepResult {
+            assignments: point.assignments,
+            cycles: cycles.len(),
+            run: whatif::resimulate(engine, cycles, point.config.funding.rates()).await,
+        });
+    }
+    let report = SweepReport::new(ticks.len(), &axes, results);
+    print!("{}", report);
+    if let Some(out) = arg_value(args, "--out") {
+        report.write_csv(&out)?;
+        println!("\nWrote {} runs to {}", report.results.len(), out);
+    }
+    Ok(())
+}
+
+/// `--runs N --replay <ticks>`: N simulations over the same recorded prices, each with its fill draws seeded
+/// differently (`--seed`, `--seed`+1, ...), and the distribution of their results
+async fn run_batch(config: &Config, path: &str, runs: u64, csv: Option<PathBuf>) -> Result<()> {
+    if runs == 0 {
+        bail!("--runs needs at least one run");
+    }
+    if config.orders.resting {
+        bail!("resting orders fill off quote updates between cycles, which batch runs don't replay");
+    }
+    let interval = config.simulation.trade_interval_secs;
+    if interval == 0 {
+        bail!("simulation.trade_interval_secs must be at least 1 to step through the recording");
+    }
+    let ticks = recorder::read_ticks(path)?;
+    if ticks.is_empty() {
+        bail!("no ticks in {}", path);
+    }
+    let cycles = sweep::cycles(&ticks, Duration::from_secs(interval)).await;
+    info!("Running {} simulations over {} cycles of {}", runs, cycles.len(), path);
+
+    let mut results = Vec::with_capacity(runs as usize);
+    for i in 0..runs {
+        let seed = config.synthetic.seed.wrapping_add(i);
+        let model = config.execution.execution_model(interval as f64)?;
+        let engine = config.session_engine(model)?.with_draws(DrawSource::seeded(seed));
+        results.push(MonteCarloRun {
+            seed,
+            run: whatif::resimulate(engine, &cycles, config.funding.rates()).await,
+        });
+    }
+    let report = MonteCarloReport {
+        cycles: cycles.len(),
+        runs: results,
+    };
+    if output::json() {
+        output::emit("montecarlo", &serde_json::json!({
+            "runs": report.runs.len(),
+            "cycles": report.cycles,
+            "net_pnl": report.net_pnl(),
+            "equity": report.equity(),
+            "trades": report.trades(),
+            "max_drawdown": report.max_drawdown(),
+            "probability_of_loss": report.probability_of_loss(),
+        }));
+    } else {
+        print!("\n{}", report);
+    }
+    if let Some(path) = &csv {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        report.write_csv(path)?;
+        say!("Wrote {} runs to {}", report.runs.len(), path.display());
+    }
+    Ok(())
+}
+
+/// `bench-feed <file> [--repeat N] [--every N]`: replay a recording through aggregator, strategy and tracker
+/// with no pacing and report throughput and per-stage latency
+async fn run_bench_command(config: &Config, execution_model: Box<dyn ExecutionModel>, args: &[String]) -> Result<()> {
+    let path = match args.first() {
+        Some(path) if !path.starts_with("--") => path,
+        _ => bail!("usage: bench-feed <file.{{csv,jsonl,bin}}> [--repeat N] [--every N]"),
+    };
+    let repeat: usize = arg_value(args, "--repeat").map(|s| s.parse()).transpose()?.unwrap_or(1);
+    let every: usize = arg_value(args, "--every").map(|s| s.parse()).transpose()?.unwrap_or(1);
+
+    let load_started = Instant::now();
+    let recorded = recorder::read_ticks(path)?;
+    let load_time = load_started.elapsed();
+    if recorded.is_empty() {
+        bail!("no ticks in {}", path);
+    }
+    let ticks: Vec<_> = std::iter::repeat_n(recorded.as_slice(), repeat.max(1)).flatten().cloned().collect();
+    println!("Loaded {} ticks from {} in {:.3}s; benchmarking {} messages, strategy every {}...\n",
+        recorded.len(), path, load_time.as_secs_f64(), ticks.len(), every.max(1));
+
+    let engine = config.base_engine(execution_model)?;
+    let tracker = PnLTracker::new();
+    let report = bench::run_feed_bench(ticks, engine, &tracker, every).await;
+    print!("{}", report);
+    Ok(())
+}
+
+#[cfg(feature = "grpc")]
+const DEFAULT_GRPC_LISTEN: &str = "127.0.0.1:50051";
+
+/// Flags only the scheduler reads, stripped before passing the rest on to each session run
+const SCHEDULER_FLAGS: [&str; 4] = ["--window", "--count", "--out", "--max-sessions"];
+
+/// `grpc [--listen addr]`: long-lived daemon starting, stopping and streaming sessions for external
+/// orchestration. The remaining flags apply to every session it starts. Clients must send the
+/// `MM_GRPC_TOKEN` bearer token when it's set, and it has to be set to listen beyond loopback.
+#[cfg(feature = "grpc")]
+async fn run_grpc_command(store: &SessionStore, args: &[String]) -> Result<()> {
+    let listen = arg_value(args, "--listen").unwrap_or_else(|| DEFAULT_GRPC_LISTEN.to_string());
+    let addr: std::net::SocketAddr = listen.parse().with_context(|| format!("invalid --listen address '{}'", listen))?;
+    let token = std::env::var("MM_GRPC_TOKEN").ok().filter(|token| !token.is_empty());
+    if token.is_none() && !addr.ip().is_loopback() {
+        bail!("the gRPC daemon starts sessions for any client; set MM_GRPC_TOKEN to listen on {}", addr);
+    }
+    let exe = std::env::current_exe().context("failed to locate the simulator binary")?;
+    let mut base_args = Vec::new();
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        if arg == "--listen" || arg == "--session-dir" {
+            rest.next();
+        } else {
+            base_args.push(arg.clone());
+        }
+    }
+    println!("[GRPC] Serving on {}{}, sessions saved to {} with logs in {}",
+        addr, if token.is_some() { " with token auth" } else { "" }, store.dir().display(), store.dir().join("grpc").display());
+    let mut daemon = SessionDaemon::new(exe, SessionStore::new(store.dir()), base_args);
+    if let Some(token) = token {
+        daemon = daemon.with_token(token);
+    }
+    daemon.serve(addr).await
+}
+
+#[cfg(not(feature = "grpc"))]
+async fn run_grpc_command(_store: &SessionStore, _args: &[String]) -> Result<()> {
+    bail!("the gRPC daemon needs a build with the grpc feature (cargo build --features grpc)")
+}
+
+/// `schedule list|ics|run`: recurring sessions from `[[schedule.sessions]]` and `--window` specs
+async fn run_schedule_command(config: &Config, store: &SessionStore, args: &[String]) -> Result<()> {
+    let mut windows = config.schedule.calendar()?.windows().to_vec();
+    if let Some(specs) = arg_value(args, "--window") {
+        for spec in specs.split(';') {
+            windows.push(spec.parse()?);
+        }
+    }
+    let calendar = Calendar::new(windows);
+    if calendar.is_empty() {
+        bail!("no sessions scheduled: add [[schedule.sessions]] to the config or pass --window name=mon-fri@14:00-16:00");
+    }
+
+    match args.first().map(String::as_str) {
+        None | Some("list") => {
+            for window in calendar.windows() {
+                println!("{:<20} {}", window.name, window);
+            }
+            let count = arg_value(args, "--count").map(|c| c.parse()).transpose()?.unwrap_or(10);
+            println!("\nUpcoming:");
+            for occurrence in calendar.upcoming(chrono::Utc::now(), count) {
+                println!("  {} → {}  {}",
+                    occurrence.start.format("%a %Y-%m-%d %H:%M"),
+                    occurrence.end.format("%H:%M UTC"),
+                    occurrence.window.name);
+            }
+        }
+        Some("ics") => {
+            let ics = calendar.to_ics(chrono::Utc::now());
+            match arg_value(args, "--out") {
+                Some(path) => {
+                    std::fs::write(&path, ics).with_context(|| format!("failed to write {}", path))?;
+                    println!("Wrote {} scheduled sessions to {}", calendar.windows().len(), path);
+                }
+                None => print!("{}", ics),
+            }
+        }
+        Some("run") => run_scheduler(config, store, &calendar, args).await?,
+        Some(other) => bail!("unknown schedule command '{}' (expected list, ics or run)", other),
+    }
+    Ok(())
+}
+
+/// Daemon mode: wait for each scheduled window and run a full session in it as a child process,
+/// keeping its console output as a log and recording a manifest next to it
+async fn run_scheduler(config: &Config, store: &SessionStore, calendar: &Calendar, args: &[String]) -> Result<()> {
+    let max_sessions: Option<usize> = arg_value(args, "--max-sessions").map(|n| n.parse()).transpose()?;
+    let exe = std::env::current_exe().context("failed to locate the simulator binary")?;
+    let mut passthrough = Vec::new();
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        if SCHEDULER_FLAGS.contains(&arg.as_str()) || arg == "--name" {
+            rest.next();
+        } else {
+            passthrough.push(arg.clone());
+        }
+    }
+    let dir = store.dir().join("scheduled");
+    let warmup = config.simulation.warmup_secs as i64;
+
+    println!("[SCHEDULE] Running {} recurring sessions, logs and manifests in {}", calendar.windows().len(), dir.display());
+    let mut completed = 0;
+    let mut cursor = chrono::Utc::now();
+    while max_sessions.is_none_or(|max| completed < max) {
+        let now = chrono::Utc::now().max(cursor);
+        let Some(next) = calendar.next_after(now) else {
+            bail!("no upcoming sessions in the schedule");
+        };
+        cursor = next.end;
+        if next.start > now {
+            println!("[SCHEDULE] Next: '{}' {} → {} (in {})",
+                next.window.name,
+                next.start.format("%a %Y-%m-%d %H:%M"),
+                next.end.format("%H:%M UTC"),
+                format_wait(next.start - now));
+            sleep((next.start - now).to_std().unwrap_or_default()).await;
+        }
+
+        // Trading starts after the warm-up, so take it off the session length to finish with the window
+        let started_at = chrono::Utc::now();
+        let duration = (next.end - started_at).num_seconds() - warmup;
+        if duration < 1 {
+            println!("[SCHEDULE] Skipping '{}': too little of the window left", next.window.name);
+            continue;
+        }
+
+        let stem = format!("{}-{}", next.start.format("%Y%m%d-%H%M"), next.window.slug());
+        let session_name = format!("{} {}", next.window.name, next.start.format("%Y-%m-%d %H:%M"));
+        std::fs::create_dir_all(&dir)?;
+        let log = dir.join(format!("{}.log", stem));
+        let log_file = std::fs::File::create(&log).with_context(|| format!("failed to create {}", log.display()))?;
+        println!("[SCHEDULE] Starting '{}' for {}s, logging to {}", session_name, duration, log.display());
+
+        let status = tokio::process::Command::new(&exe)
+            .args(&passthrough)
+            .arg("--name")
+            .arg(&session_name)
+            .env(format!("{}DURATION_SECS", ENV_PREFIX), duration.to_string())
+            .stdin(std::process::Stdio::null())
+            .stdout(log_file.try_clone()?)
+            .stderr(log_file)
+            .status()
+            .await
+            .context("failed to start session")?;
+
+        let manifest = SessionManifest {
+            schedule: next.window.name.clone(),
+            window: next.window.to_string(),
+            scheduled_start: next.start.timestamp_millis(),
+            scheduled_end: next.end.timestamp_millis(),
+            started_at: started_at.timestamp_millis(),
+            ended_at: chrono::Utc::now().timestamp_millis(),
+            session_id: store.find(&session_name)?.map(|record| record.id),
+            session_name,
+            exit_code: status.code(),
+            log,
+        };
+        let path = manifest.save(&dir, &stem)?;
+        match store.find(&manifest.session_name)? {
+            Some(record) if manifest.succeeded() => println!("[SCHEDULE] '{}' done: {} trades │ PnL ${:.2} │ manifest {}",
+                record.label(), record.stats.total_trades, record.stats.total_pnl, path.display()),
+            _ => eprintln!("[SCHEDULE] '{}' failed ({}), see {}", manifest.session_name, status, manifest.log.display()),
+        }
+        completed += 1;
+    }
+    Ok(())
+}
+
+fn format_wait(wait: chrono::Duration) -> String {
+    let secs = wait.num_seconds().max(0);
+    match secs {
+        s if s >= 86400 => format!("{}d {}h", s / 86400, s % 86400 / 3600),
+        s if s >= 3600 => format!("{}h {}m", s / 3600, s % 3600 / 60),
+        s => format!("{}m {}s", s / 60, s % 60),
+    }
+}
+
+/// `diff <a> <b> [--duration secs] [--interval secs] [--tolerance-bps N]`: compare two source implementations
+async fn run_diff_command(config: &Config, args: &[String]) -> Result<()> {
+    let (a, b) = match args {
+        [a, b, ..] if !a.starts_with("--") && !b.starts_with("--") => (a.parse::<SourceImpl>()?, b.parse::<SourceImpl>()?),
+        _ => bail!("usage: diff <a> <b> [--duration secs] [--interval secs] [--tolerance-bps N]\n\
+            implementations: binance-rest, jupiter-quote, jupiter-price, cowswap, uniswap, synthetic[:seed]"),
+    };
+    let duration = arg_secs(args, "--duration", 60.0)?;
+    let interval = arg_secs(args, "--interval", 2.0)?;
+    let tolerance_bps = arg_value(args, "--tolerance-bps").map(|s| s.parse()).transpose()?.unwrap_or(5.0);
+
+    let fetcher_a = SourceFetcher::new(a, config)?;
+    let fetcher_b = SourceFetcher::new(b, config)?;
+    println!("Comparing {} (A) against {} (B) every {:?} for {:?}...\n",
+        a.name(), b.name(), interval, duration);
+
+    let samples = source_diff::run_diff(&fetcher_a, &fetcher_b, duration, interval).await;
+    print!("{}", DiffReport::from_samples(&a.name(), &b.name(), &samples, tolerance_bps));
+    Ok(())
+}
+
+/// `sessions list | show <id> | delete <id>`
+fn run_sessions_command(
+    store: &SessionStore,
+    args: &[String],
+    timezone: &ReportTimezone,
+) -> Result<()> {
+    match args.first().map(String::as_str) {
+        None | Some("list") => {
+            let sessions = store.list()?;
+            if sessions.is_empty() {
+                println!("No sessions recorded in {}", store.dir().display());
+                return Ok(());
+            }
+
+            println!("{:<17} {:<20} {:>8} {:>8} {:>14} {:>10}",
+                "ID", "NAME", "MODEL", "TRADES", "PNL", "DURATION");
+            for s in &sessions {
+                println!("{:<17} {:<20} {:>8} {:>8} {:>14.2} {:>9}s",
+                    s.id,
+                    s.name.as_deref().unwrap_or("-"),
+                    s.execution_model,
+                    s.stats.total_trades,
+                    s.stats.total_pnl,
+                    s.duration_secs()
+                );
+            }
+        }
+        Some("show") => {
+            let key = args.get(1).context("usage: sessions show <id|name>")?;
+            match store.find(key)? {
+                Some(record) => record.print(timezone),
+                None => println!("No session matching '{}'", key),
+            }
+        }
+        Some("delete") => {
+            let key = args.get(1).context("usage: sessions delete <id|name>")?;
+            if store.delete(key)? {
+                println!("Deleted session '{}'", key);
+            } else {
+                println!("No session matching '{}'", key);
+            }
+        }
+        Some(other) => bail!("unknown sessions command '{}' (expected list, show or delete)", other),
+    }
+
+    Ok(())
+}
+
+/// `report <db> [--session <id|name>] [--last N]`: compare the sessions stored with `--db`, or break one down
+#[cfg(feature = "sqlite")]
+fn run_report_command(args: &[String], timezone: &ReportTimezone) -> Result<()> {
+    let path = match args.first() {
+        Some(path) if !path.starts_with("--") => path,
+        _ => bail!("usage: report <db> [--session <id|name>] [--last N]"),
+    };
+    if !std::path::Path::new(path).exists() {
+        bail!("no database at {}", path);
+    }
+    let database = Database::open(path)?;
+
+    if let Some(key) = arg_value(args, "--session") {
+        let Some(record) = database.find(&key)? else {
+            println!("No session matching '{}'", key);
+            return Ok(());
+        };
+        record.print(timezone);
+        let (cycles, quotes) = database.quote_counts(&record.id)?;
+        println!("Quote snapshots:     {} quotes over {} cycles\n", quotes, cycles);
+        println!("{:<5} {:<6} {:<6} {:>8} {:>16} {:>12} {:>10}",
+            "SIDE", "ROLE", "HEDGE", "TRADES", "NOTIONAL", "PNL", "FEES");
+        for row in database.trade_summary(&record.id)? {
+            println!("{:<5} {:<6} {:<6} {:>8} {:>16.2} {:>12.2} {:>10.2}",
+                row.side, row.role, if row.hedge { "yes" } else { "no" }, row.trades, row.notional_usd, row.pnl, row.fees_usd);
+        }
+        return Ok(());
+    }
+
+    let mut sessions = database.sessions()?;
+    if let Some(last) = arg_value(args, "--last") {
+        let last: usize = last.parse()?;
+        sessions.drain(..sessions.len().saturating_sub(last));
+    }
+    if sessions.is_empty() {
+        println!("No sessions stored in {}", path);
+        return Ok(());
+    }
+
+    println!("{:<17} {:<16} {:<24} {:>8} {:>8} {:>12} {:>12} {:>10} {:>7} {:>10} {:>9}",
+        "ID", "NAME", "STARTED", "MODEL", "TRADES", "PNL", "REALIZED", "FEES", "SHARPE", "MAX DD", "DURATION");
+    for s in &sessions {
+        let sharpe = s.stats.performance.sharpe_ratio().map_or("-".to_string(), |sharpe| format!("{:.2}", sharpe));
+        println!("{:<17} {:<16} {:<24} {:>8} {:>8} {:>12.2} {:>12.2} {:>10.2} {:>7} {:>10.2} {:>8}s",
+            s.id,
+            s.name.as_deref().unwrap_or("-"),
+            timezone.format_datetime(s.started_at),
+            s.execution_model,
+            s.stats.total_trades,
+            s.stats.total_pnl,
+            s.stats.realized_pnl,
+            s.stats.total_fees,
+            sharpe,
+            s.stats.performance.max_drawdown,
+            s.duration_secs()
+        );
+    }
+    let total: f64 = sessions.iter().map(|s| s.stats.total_pnl).sum();
+    let trades: u32 = sessions.iter().map(|s| s.stats.total_trades).sum();
+    let winning = sessions.iter().filter(|s| s.stats.total_pnl > 0.0).count();
+    println!("\n{} sessions │ {} trades │ total PnL ${:.2} │ mean ${:.2} per session │ {} of {} profitable",
+        sessions.len(), trades, total, total / sessions.len() as f64, winning, sessions.len());
+    Ok(())
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn run_report_command(_args: &[String], _timezone: &ReportTimezone) -> Result<()> {
+    bail!("report needs a build with the sqlite feature (cargo build --features sqlite)")
 }
 
 // Helper trait for formatting numbers with separators
@@ -150,7 +2566,7 @@ impl FormattedNumber for f64 {
         let chars: Vec<char> = s.chars().collect();
         
         for (i, c) in chars.iter().enumerate() {
-            if i > 0 && (chars.len() - i) % 3 == 0 {
+            if i > 0 && (chars.len() - i).is_multiple_of(3) {
                 result.push(',');
             }
             result.push(*c);
@@ -0,0 +1,178 @@
+use crate::config::Config;
+use crate::pnl_tracker::PnLStats;
+use crate::position::Position;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// One market of a multi-market session: a pair and what sets it apart from the shared config
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarketConfig {
+    /// `BASE/QUOTE`, e.g. `BTC/USDC`
+    pub pair: String,
+    /// Starting fair price of the pair's synthetic path; `[synthetic] initial_price` when unset
+    pub initial_price: Option<f64>,
+    /// Overrides `[simulation] notional_per_trade` for this pair
+    pub notional_per_trade: Option<f64>,
+    /// Overrides `[synthetic] volatility` for this pair
+    pub volatility: Option<f64>,
+    /// Seed of the pair's books and fill draws; the shared seed plus the pair's position when unset
+    pub seed: Option<u64>,
+}
+
+impl MarketConfig {
+    /// Lowercase pair with the slash dropped, for file names and session ids (`btc-usdc`)
+    pub fn slug(&self) -> String {
+        self.pair.to_ascii_lowercase().replace('/', "-")
+    }
+
+    /// The shared config with this market's overrides applied; `index` is its position in the session
+    pub fn apply(&self, config: &Config, index: usize) -> Config {
+        let mut config = config.clone();
+        if let Some((base, quote)) = self.pair.split_once('/') {
+            config.tokens.base = base.trim().to_string();
+            config.tokens.quote = quote.trim().to_string();
+        }
+        config.synthetic.seed = self.seed.unwrap_or(config.synthetic.seed.wrapping_add(index as u64));
+        if let Some(price) = self.initial_price {
+            config.synthetic.initial_price = price;
+        }
+        if let Some(volatility) = self.volatility {
+            config.synthetic.volatility = volatility;
+        }
+        if let Some(notional) = self.notional_per_trade {
+            config.simulation.notional_per_trade = notional;
+        }
+        config
+    }
+
+    /// Parse a `--markets` list: `PAIR[@PRICE]` entries separated by commas
+    pub fn parse_list(list: &str) -> Result<Vec<MarketConfig>> {
+        list.split(',').map(str::parse).collect()
+    }
+
+    /// Check a pair that came from a `[[markets.pairs]]` table the way `--markets` parsing does
+    pub fn validate(&self) -> Result<()> {
+        if !is_pair(&self.pair) {
+            bail!("market '{}' is not a BASE/QUOTE pair", self.pair);
+        }
+        Ok(())
+    }
+
+    /// Check every market, and that no two share a pair: they'd share a slug, and with it their
+    /// session and spill files
+    pub fn validate_all(markets: &[MarketConfig]) -> Result<()> {
+        let mut slugs = HashSet::new();
+        for market in markets {
+            market.validate()?;
+            if !slugs.insert(market.slug()) {
+                bail!("market {} is listed more than once", market.pair);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `BASE/QUOTE` with neither side empty
+fn is_pair(pair: &str) -> bool {
+    pair.split_once('/').is_some_and(|(base, quote)| !base.trim().is_empty() && !quote.trim().is_empty())
+}
+
+impl FromStr for MarketConfig {
+    type Err = anyhow::Error;
+
+    /// `BTC/USDC` or `BTC/USDC@60000`
+    fn from_str(s: &str) -> Result<Self> {
+        let (pair, price) = match s.trim().split_once('@') {
+            Some((pair, price)) => {
+                let price = price.parse().with_context(|| format!("invalid initial price in market '{}'", s))?;
+                (pair, Some(price))
+            }
+            None => (s.trim(), None),
+        };
+        if !is_pair(pair) {
+            bail!("market '{}' is not a BASE/QUOTE pair", s);
+        }
+        Ok(MarketConfig {
+            pair: pair.to_ascii_uppercase(),
+            initial_price: price,
+            ..MarketConfig::default()
+        })
+    }
+}
+
+/// How one market of a multi-market session ended
+#[derive(Debug, Clone, Serialize)]
+pub struct MarketResult {
+    pub pair: String,
+    pub session_id: String,
+    pub cycles: u64,
+    pub stats: PnLStats,
+    pub position: Position,
+    /// Last median mid, for marking the open inventory
+    pub last_mid: Option<f64>,
+}
+
+impl MarketResult {
+    pub fn unrealized_pnl(&self) -> f64 {
+        self.last_mid.map_or(0.0, |mid| self.position.unrealized_pnl(mid))
+    }
+
+    /// Open inventory valued at the last mid, in the quote asset
+    pub fn exposure(&self) -> f64 {
+        self.last_mid.map_or(0.0, |mid| self.position.net_eth * mid)
+    }
+}
+
+/// Cross-market summary: one row per market and their totals. Amounts add up across markets on the
+/// assumption that every pair is quoted in the same (USD) asset.
+#[derive(Debug, Clone, Serialize)]
+pub struct MarketsReport {
+    pub markets: Vec<MarketResult>,
+}
+
+impl MarketsReport {
+    pub fn total_pnl(&self) -> f64 {
+        self.markets.iter().map(|m| m.stats.total_pnl).sum()
+    }
+
+    pub fn total_trades(&self) -> u32 {
+        self.markets.iter().map(|m| m.stats.total_trades).sum()
+    }
+
+    pub fn total_notional(&self) -> f64 {
+        self.markets.iter().map(|m| m.stats.total_notional).sum()
+    }
+
+    pub fn total_fees(&self) -> f64 {
+        self.markets.iter().map(|m| m.stats.total_fees).sum()
+    }
+
+    pub fn total_unrealized(&self) -> f64 {
+        self.markets.iter().map(MarketResult::unrealized_pnl).sum()
+    }
+
+    /// Absolute open exposure summed over markets
+    pub fn gross_exposure(&self) -> f64 {
+        self.markets.iter().map(|m| m.exposure().abs()).sum()
+    }
+}
+
+impl fmt::Display for MarketsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Cross-Market Summary ({} markets):", self.markets.len())?;
+        let row = |f: &mut fmt::Formatter<'_>, pair: &str, trades: u32, notional: f64, fees: f64, pnl: f64, unrealized: f64, exposure: f64| {
+            writeln!(f, "  {:<10} trades {:>5} │ notional ${:>14.2} │ fees ${:>9.2} │ PnL ${:>10.2} │ unrealized ${:>9.2} │ exposure ${:>11.2}",
+                pair, trades, notional, fees, pnl, unrealized, exposure)
+        };
+        for market in &self.markets {
+            row(f, &market.pair, market.stats.total_trades, market.stats.total_notional, market.stats.total_fees,
+                market.stats.total_pnl, market.unrealized_pnl(), market.exposure())?;
+        }
+        row(f, "TOTAL", self.total_trades(), self.total_notional(), self.total_fees(),
+            self.total_pnl(), self.total_unrealized(), self.gross_exposure())
+    }
+}
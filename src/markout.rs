@@ -0,0 +1,151 @@
+use crate::amount::{self, Exact};
+use crate::trader::{Trade, TradeSide};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How long after a fill its markouts are taken, in feed time
+pub const MARKOUT_HORIZONS_MS: [i64; 3] = [1_000, 5_000, 30_000];
+
+/// Fills with every horizon taken that stay around for lookups and exports; older ones only
+/// live on in `MarkoutStats`
+pub const MAX_RESOLVED_MARKOUTS: usize = 10_000;
+
+/// The median mid at each markout horizon after one fill
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeMarkout {
+    pub trade_id: u64,
+    pub side: TradeSide,
+    pub price: f64,
+    pub amount_eth: f64,
+    /// Session time of the fill
+    pub filled_at: i64,
+    /// Mid at each of `MARKOUT_HORIZONS_MS`, `None` until the feed reaches it
+    pub mids: [Option<f64>; 3],
+}
+
+impl TradeMarkout {
+    pub fn new(trade: &Trade) -> Self {
+        Self {
+            trade_id: trade.id,
+            side: trade.side,
+            price: trade.price,
+            amount_eth: trade.amount_eth,
+            filled_at: trade.timestamp,
+            mids: [None; 3],
+        }
+    }
+
+    /// What the fill was worth at `mid`, before fees: positive when the market moved our way
+    pub fn pnl_at(&self, mid: f64) -> f64 {
+        match self.side {
+            TradeSide::Buy => amount::value_of(mid, self.price, self.amount_eth),
+            TradeSide::Sell => amount::value_of(self.price, mid, self.amount_eth),
+        }
+    }
+
+    /// Markout PnL at each horizon reached so far
+    pub fn pnl(&self) -> [Option<f64>; 3] {
+        self.mids.map(|mid| mid.map(|mid| self.pnl_at(mid)))
+    }
+
+    fn resolved(&self) -> bool {
+        self.mids.iter().all(Option::is_some)
+    }
+}
+
+/// Markouts of every fill at one horizon
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct MarkoutStats {
+    pub horizon_ms: i64,
+    /// Fills the feed has run past the horizon for
+    pub trades: u32,
+    /// Of those, fills the market moved in favour of
+    pub positive: u32,
+    pub pnl_usd: f64,
+    pub notional_usd: f64,
+    /// Exact sums behind `pnl_usd` and `notional_usd`
+    #[serde(skip)]
+    exact: [Exact; 2],
+}
+
+impl MarkoutStats {
+    pub fn avg_bps(&self) -> f64 {
+        if self.notional_usd > 0.0 {
+            self.pnl_usd / self.notional_usd * 10000.0
+        } else {
+            0.0
+        }
+    }
+
+    pub fn positive_rate(&self) -> f64 {
+        if self.trades > 0 {
+            self.positive as f64 / self.trades as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Markouts of the session's fills by trade id, taken from the mids the tracker is marked at.
+/// Fills move out of `pending` once the feed passes their longest horizon, and only the latest
+/// `MAX_RESOLVED_MARKOUTS` of those are kept.
+#[derive(Debug, Default)]
+pub struct MarkoutBook {
+    pending: BTreeMap<u64, TradeMarkout>,
+    resolved: BTreeMap<u64, TradeMarkout>,
+}
+
+impl MarkoutBook {
+    pub fn push(&mut self, markout: TradeMarkout) {
+        self.pending.insert(markout.trade_id, markout);
+    }
+
+    /// Take the mid for every horizon `timestamp` has reached, adding them to `stats`
+    pub fn observe(&mut self, timestamp: i64, mid: f64, stats: &mut [MarkoutStats]) {
+        for markout in self.pending.values_mut() {
+            for (i, &horizon) in MARKOUT_HORIZONS_MS.iter().enumerate() {
+                if markout.mids[i].is_some() || timestamp < markout.filled_at + horizon {
+                    continue;
+                }
+                markout.mids[i] = Some(mid);
+                let pnl = markout.pnl_at(mid);
+                let entry = &mut stats[i];
+                entry.trades += 1;
+                entry.positive += u32::from(pnl > 0.0);
+                entry.exact[0].add(&mut entry.pnl_usd, pnl);
+                entry.exact[1].add(&mut entry.notional_usd, amount::value_of(markout.price, 0.0, markout.amount_eth));
+            }
+        }
+        let done: Vec<u64> = self.pending.values().filter(|m| m.resolved()).map(|m| m.trade_id).collect();
+        for trade_id in done {
+            if let Some(markout) = self.pending.remove(&trade_id) {
+                self.resolved.insert(trade_id, markout);
+            }
+        }
+        while self.resolved.len() > MAX_RESOLVED_MARKOUTS {
+            self.resolved.pop_first();
+        }
+    }
+
+    pub fn get(&self, trade_id: u64) -> Option<&TradeMarkout> {
+        self.pending.get(&trade_id).or_else(|| self.resolved.get(&trade_id))
+    }
+
+    /// Every markout still held, by trade id
+    pub fn all(&self) -> Vec<TradeMarkout> {
+        let mut all: Vec<TradeMarkout> = self.resolved.values().chain(self.pending.values()).cloned().collect();
+        all.sort_by_key(|m| m.trade_id);
+        all
+    }
+}
+
+/// Empty stats for each of `MARKOUT_HORIZONS_MS`
+pub fn markout_stats() -> Vec<MarkoutStats> {
+    MARKOUT_HORIZONS_MS
+        .iter()
+        .map(|&horizon_ms| MarkoutStats {
+            horizon_ms,
+            ..MarkoutStats::default()
+        })
+        .collect()
+}
@@ -0,0 +1,91 @@
+use crate::http::HttpStats;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Window over which updates per second are measured
+const RATE_WINDOW_MS: i64 = 60_000;
+/// Weight of the newest sample in the latency moving average
+const LATENCY_EWMA_ALPHA: f64 = 0.2;
+
+/// Feed quality numbers for a single price source
+#[derive(Debug, Clone, Default)]
+pub struct SourceMetrics {
+    pub last_update: Option<i64>,
+    pub update_count: u64,
+    /// Updates per second over the last minute
+    pub updates_per_sec: f64,
+    /// Round-trip time of the most recent HTTP poll
+    pub last_latency_ms: Option<f64>,
+    /// Exponentially weighted average round-trip time
+    pub avg_latency_ms: Option<f64>,
+    pub reconnects: u32,
+    /// Requests, throttling and retries of a REST source's HTTP client
+    pub http: Option<HttpStats>,
+}
+
+impl SourceMetrics {
+    pub fn time_since_update_ms(&self, now: i64) -> Option<i64> {
+        self.last_update.map(|ts| now - ts)
+    }
+
+    /// True if the source has never updated or not within `max_age`
+    pub fn is_stale(&self, now: i64, max_age: Duration) -> bool {
+        self.time_since_update_ms(now)
+            .is_none_or(|age| age > max_age.as_millis() as i64)
+    }
+}
+
+/// Mutable bookkeeping behind `SourceMetrics`
+#[derive(Debug, Default)]
+pub(crate) struct MetricsState {
+    metrics: SourceMetrics,
+    recent: VecDeque<i64>,
+    connected_once: bool,
+}
+
+impl MetricsState {
+    pub(crate) fn record_update(&mut self, timestamp: i64) {
+        self.metrics.last_update = Some(timestamp);
+        self.metrics.update_count += 1;
+        self.recent.push_back(timestamp);
+        self.trim(timestamp);
+    }
+
+    pub(crate) fn record_latency(&mut self, latency: Duration) {
+        let ms = latency.as_secs_f64() * 1000.0;
+        self.metrics.last_latency_ms = Some(ms);
+        self.metrics.avg_latency_ms = Some(match self.metrics.avg_latency_ms {
+            Some(avg) => avg + LATENCY_EWMA_ALPHA * (ms - avg),
+            None => ms,
+        });
+    }
+
+    pub(crate) fn http(&self) -> Option<HttpStats> {
+        self.metrics.http
+    }
+
+    pub(crate) fn record_http(&mut self, stats: HttpStats) {
+        self.metrics.http = Some(stats);
+    }
+
+    /// Count every connection after the first as a reconnect
+    pub(crate) fn record_connect(&mut self) {
+        if self.connected_once {
+            self.metrics.reconnects += 1;
+        }
+        self.connected_once = true;
+    }
+
+    fn trim(&mut self, now: i64) {
+        while self.recent.front().is_some_and(|&ts| now - ts > RATE_WINDOW_MS) {
+            self.recent.pop_front();
+        }
+    }
+
+    pub(crate) fn snapshot(&mut self, now: i64) -> SourceMetrics {
+        self.trim(now);
+        let mut metrics = self.metrics.clone();
+        metrics.updates_per_sec = self.recent.len() as f64 / (RATE_WINDOW_MS as f64 / 1000.0);
+        metrics
+    }
+}
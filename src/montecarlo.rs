@@ -0,0 +1,145 @@
+use crate::whatif::WhatIfRun;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Spread of one statistic over the runs of a batch
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Distribution {
+    pub mean: f64,
+    /// Sample standard deviation (0 for a single run)
+    pub std_dev: f64,
+    pub min: f64,
+    pub p5: f64,
+    pub p25: f64,
+    pub median: f64,
+    pub p75: f64,
+    pub p95: f64,
+    pub max: f64,
+}
+
+impl Distribution {
+    pub fn of(values: &[f64]) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let n = sorted.len() as f64;
+        let mean = sorted.iter().sum::<f64>() / n;
+        let variance = if sorted.len() > 1 {
+            sorted.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)
+        } else {
+            0.0
+        };
+        Self {
+            mean,
+            std_dev: variance.sqrt(),
+            min: sorted[0],
+            p5: percentile(&sorted, 5.0),
+            p25: percentile(&sorted, 25.0),
+            median: percentile(&sorted, 50.0),
+            p75: percentile(&sorted, 75.0),
+            p95: percentile(&sorted, 95.0),
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+/// Linearly interpolated percentile of already sorted values
+fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    let rank = pct / 100.0 * (sorted.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+/// One simulation of the batch and the seed its fill draws came from
+#[derive(Debug, Clone)]
+pub struct MonteCarloRun {
+    pub seed: u64,
+    pub run: WhatIfRun,
+}
+
+impl MonteCarloRun {
+    /// Realized plus unrealized PnL at the end of the run, less carry
+    pub fn equity(&self) -> f64 {
+        self.run.stats.realized_pnl + self.run.unrealized_pnl() - self.run.stats.funding.total()
+    }
+}
+
+/// Independent runs over the same prices, differing only in their fill draws
+#[derive(Debug, Clone)]
+pub struct MonteCarloReport {
+    pub cycles: usize,
+    pub runs: Vec<MonteCarloRun>,
+}
+
+impl MonteCarloReport {
+    fn distribution(&self, stat: impl Fn(&MonteCarloRun) -> f64) -> Distribution {
+        Distribution::of(&self.runs.iter().map(stat).collect::<Vec<_>>())
+    }
+
+    /// Net PnL at fill time, after fees
+    pub fn net_pnl(&self) -> Distribution {
+        self.distribution(|r| r.run.stats.total_pnl)
+    }
+
+    /// Realized plus unrealized PnL at the last mid
+    pub fn equity(&self) -> Distribution {
+        self.distribution(MonteCarloRun::equity)
+    }
+
+    pub fn trades(&self) -> Distribution {
+        self.distribution(|r| r.run.stats.total_trades as f64)
+    }
+
+    pub fn max_drawdown(&self) -> Distribution {
+        self.distribution(|r| r.run.stats.performance.max_drawdown)
+    }
+
+    /// Share of runs whose equity, marked at the last mid, ended below zero
+    pub fn probability_of_loss(&self) -> f64 {
+        if self.runs.is_empty() {
+            return 0.0;
+        }
+        self.runs.iter().filter(|r| r.equity() < 0.0).count() as f64 / self.runs.len() as f64
+    }
+
+    /// One row per run, in seed order
+    pub fn write_csv(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+        let mut out = BufWriter::new(file);
+        writeln!(out, "seed,trades,notional_usd,fees_usd,net_pnl,realized_pnl,unrealized_pnl,inventory_eth,max_drawdown")?;
+        for run in &self.runs {
+            let stats = &run.run.stats;
+            writeln!(out, "{},{},{:.2},{:.4},{:.4},{:.4},{:.4},{:.6},{:.4}",
+                run.seed, stats.total_trades, stats.total_notional, stats.total_fees, stats.total_pnl,
+                stats.realized_pnl, run.run.unrealized_pnl(), run.run.position.net_eth, stats.performance.max_drawdown)?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for MonteCarloReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Monte Carlo: {} runs over the same {} cycles, seeds {}..={}", self.runs.len(), self.cycles,
+            self.runs.first().map_or(0, |r| r.seed), self.runs.last().map_or(0, |r| r.seed))?;
+        writeln!(f, "  {:<16} {:>11} {:>11} {:>11} {:>11} {:>11} {:>11} {:>11}",
+            "", "MEAN", "STD", "P5", "P25", "MEDIAN", "P75", "P95")?;
+        let mut row = |label: &str, d: Distribution| {
+            writeln!(f, "  {:<16} {:>11.2} {:>11.2} {:>11.2} {:>11.2} {:>11.2} {:>11.2} {:>11.2}",
+                label, d.mean, d.std_dev, d.p5, d.p25, d.median, d.p75, d.p95)
+        };
+        row("Net PnL ($)", self.net_pnl())?;
+        row("Equity ($)", self.equity())?;
+        row("Trades", self.trades())?;
+        row("Max drawdown ($)", self.max_drawdown())?;
+        let equity = self.equity();
+        writeln!(f, "  Equity range ${:.2} to ${:.2} │ P(loss) {:.1}%", equity.min, equity.max, self.probability_of_loss() * 100.0)
+    }
+}
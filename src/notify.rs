@@ -0,0 +1,269 @@
+use crate::aggregator::AggregatedPrices;
+use crate::health::ReconnectPolicy;
+use crate::pnl_tracker::PnLStats;
+use crate::risk::RiskEvent;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{error, warn};
+
+/// Discord rejects messages longer than 2000 characters
+const MAX_MESSAGE_CHARS: usize = 1900;
+
+/// Where notifications are POSTed and what triggers them
+#[derive(Debug, Clone)]
+pub struct NotifyPolicy {
+    pub webhook_url: String,
+    /// Notify once equity falls this far below its peak; off when unset
+    pub drawdown_usd: Option<f64>,
+    /// Notify once no source has quoted for this long; off when unset
+    pub sources_down: Option<Duration>,
+    pub risk_breaches: bool,
+    pub session_end: bool,
+    /// Retries per message before it is dropped
+    pub max_retries: u32,
+    pub retry: ReconnectPolicy,
+    pub timeout: Duration,
+}
+
+impl NotifyPolicy {
+    pub fn new(webhook_url: impl Into<String>) -> Self {
+        Self {
+            webhook_url: webhook_url.into(),
+            drawdown_usd: None,
+            sources_down: Some(Duration::from_secs(30)),
+            risk_breaches: true,
+            session_end: true,
+            max_retries: 3,
+            retry: ReconnectPolicy::default(),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Something worth telling the desk about
+#[derive(Debug, Clone)]
+pub enum Notification {
+    Drawdown { drawdown_usd: f64, threshold_usd: f64, equity: f64 },
+    SourcesDown { down_for: Duration },
+    SourcesRestored { down_for: Duration },
+    RiskBreach(RiskEvent),
+    SessionComplete { session: String, cycles: u64, stats: Box<PnLStats> },
+}
+
+impl Notification {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Notification::Drawdown { .. } => "drawdown",
+            Notification::SourcesDown { .. } => "sources_down",
+            Notification::SourcesRestored { .. } => "sources_restored",
+            Notification::RiskBreach(_) => "risk_breach",
+            Notification::SessionComplete { .. } => "session_complete",
+        }
+    }
+
+    /// Message text, in the markdown subset Slack and Discord both render
+    pub fn text(&self) -> String {
+        match self {
+            Notification::Drawdown { drawdown_usd, threshold_usd, equity } => format!(
+                ":warning: *Drawdown* ${:.2} below peak equity (threshold ${:.2}), marked equity now ${:.2}",
+                drawdown_usd, threshold_usd, equity),
+            Notification::SourcesDown { down_for } => format!(
+                ":rotating_light: *All price sources down* for {}s, no quotes coming in", down_for.as_secs()),
+            Notification::SourcesRestored { down_for } => format!(
+                ":white_check_mark: *Price sources back* after {}s without quotes", down_for.as_secs()),
+            Notification::RiskBreach(event) => format!(
+                ":octagonal_sign: *Risk limit breached* ({}): {}", event.limit, event.message),
+            Notification::SessionComplete { session, cycles, stats } => {
+                let perf = &stats.performance;
+                let cost = stats.funding.total();
+                let carry = if cost != 0.0 { format!("Carry         ${:.2}\n", -cost) } else { String::new() };
+                format!(
+                    ":checkered_flag: *Session {} complete* after {} cycles\n```\nTrades        {} ({} buy / {} sell)\nNotional      ${:.2}\nNet PnL       ${:.2}\nFees          ${:.2}\nRealized      ${:.2}\nUnrealized    ${:.2} ({:+.4} ETH)\n{}Max drawdown  ${:.2}\nSharpe        {}\n```",
+                    session, cycles, stats.total_trades, stats.buy_trades, stats.sell_trades, stats.total_notional,
+                    stats.total_pnl, stats.total_fees, stats.realized_pnl, stats.unrealized_pnl, stats.inventory_eth,
+                    carry, perf.max_drawdown,
+                    perf.sharpe_ratio().map_or("-".to_string(), |s| format!("{:.2}", s)))
+            }
+        }
+    }
+}
+
+/// `text` for Slack incoming webhooks, `content` for Discord; each ignores the other's field
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    text: &'a str,
+    content: &'a str,
+    username: &'a str,
+    event: &'a str,
+    session: &'a str,
+}
+
+/// Delivery counts once the notifier has drained
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NotifyStats {
+    pub sent: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Default)]
+struct WatchState {
+    /// Peak equity the last drawdown notification was measured from; the next needs a new peak
+    drawdown_peak: Option<f64>,
+    /// Feed time of the newest quote when every source went quiet
+    down_since: Option<i64>,
+    down_notified: bool,
+    /// Limits already notified; an inventory limit can clear and trip again every few cycles
+    breached: HashSet<String>,
+}
+
+/// Watches the session for notable events and POSTs them to a webhook from a background task
+pub struct WebhookNotifier {
+    policy: NotifyPolicy,
+    state: Mutex<WatchState>,
+    tx: Mutex<Option<mpsc::UnboundedSender<Notification>>>,
+    handle: Mutex<Option<JoinHandle<NotifyStats>>>,
+}
+
+impl WebhookNotifier {
+    pub fn spawn(policy: NotifyPolicy, session: String) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let handle = tokio::spawn(notify_loop(policy.clone(), session, rx));
+        Self {
+            policy,
+            state: Mutex::new(WatchState::default()),
+            tx: Mutex::new(Some(tx)),
+            handle: Mutex::new(Some(handle)),
+        }
+    }
+
+    pub fn policy(&self) -> &NotifyPolicy {
+        &self.policy
+    }
+
+    /// Queue a notification; ignored after `finish`
+    pub fn send(&self, notification: Notification) {
+        if let Some(tx) = self.tx.lock().unwrap().as_ref() {
+            let _ = tx.send(notification);
+        }
+    }
+
+    /// Check drawdown and source health at `now_ms`. A drawdown notifies once per equity peak, an
+    /// outage once until quotes come back.
+    pub fn check(&self, now_ms: i64, prices: &AggregatedPrices, stats: &PnLStats) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(threshold_usd) = self.policy.drawdown_usd {
+            let peak = stats.performance.peak_equity.max(0.0);
+            let equity = stats.marked_pnl();
+            let drawdown_usd = peak - equity;
+            if drawdown_usd >= threshold_usd && state.drawdown_peak.is_none_or(|notified| peak > notified) {
+                self.send(Notification::Drawdown { drawdown_usd, threshold_usd, equity });
+                state.drawdown_peak = Some(peak);
+            }
+        }
+
+        let Some(limit) = self.policy.sources_down else {
+            return;
+        };
+        let newest = prices.quotes().map(|q| q.timestamp).max();
+        let quiet_for = |since: i64| Duration::from_millis((now_ms - since).max(0) as u64);
+        match newest {
+            Some(newest) if quiet_for(newest) < limit => {
+                if let (Some(since), true) = (state.down_since, state.down_notified) {
+                    self.send(Notification::SourcesRestored { down_for: quiet_for(since) });
+                }
+                state.down_since = None;
+                state.down_notified = false;
+            }
+            // Before the first quote, count from the first check
+            _ => {
+                let since = *state.down_since.get_or_insert(newest.unwrap_or(now_ms));
+                if !state.down_notified && quiet_for(since) >= limit {
+                    self.send(Notification::SourcesDown { down_for: quiet_for(since) });
+                    state.down_notified = true;
+                }
+            }
+        }
+    }
+
+    /// Notify the first breach of each limit
+    pub fn risk_breach(&self, event: &RiskEvent) {
+        if self.policy.risk_breaches && self.state.lock().unwrap().breached.insert(event.limit.to_string()) {
+            self.send(Notification::RiskBreach(event.clone()));
+        }
+    }
+
+    pub fn session_complete(&self, session: &str, cycles: u64, stats: PnLStats) {
+        if self.policy.session_end {
+            self.send(Notification::SessionComplete {
+                session: session.to_string(),
+                cycles,
+                stats: Box::new(stats),
+            });
+        }
+    }
+
+    /// Stop accepting notifications and wait for queued ones to be delivered or dropped
+    pub async fn finish(&self) -> NotifyStats {
+        self.tx.lock().unwrap().take();
+        let handle = self.handle.lock().unwrap().take();
+        match handle {
+            Some(handle) => handle.await.unwrap_or_default(),
+            None => NotifyStats::default(),
+        }
+    }
+}
+
+async fn notify_loop(policy: NotifyPolicy, session: String, mut rx: mpsc::UnboundedReceiver<Notification>) -> NotifyStats {
+    let client = reqwest::Client::builder()
+        .timeout(policy.timeout)
+        .build()
+        .unwrap_or_default();
+    let mut stats = NotifyStats::default();
+    while let Some(notification) = rx.recv().await {
+        if post(&client, &policy, &session, &notification).await {
+            stats.sent += 1;
+        } else {
+            stats.failed += 1;
+        }
+    }
+    stats
+}
+
+async fn post(client: &reqwest::Client, policy: &NotifyPolicy, session: &str, notification: &Notification) -> bool {
+    let mut text = notification.text();
+    if text.len() > MAX_MESSAGE_CHARS {
+        let cut = (0..=MAX_MESSAGE_CHARS).rev().find(|&i| text.is_char_boundary(i)).unwrap_or(0);
+        text.truncate(cut);
+    }
+    let body = WebhookPayload {
+        text: &text,
+        content: &text,
+        username: "market-maker",
+        event: notification.kind(),
+        session,
+    };
+    let mut attempt = 0;
+    loop {
+        let error = match client.post(&policy.webhook_url).json(&body).send().await {
+            Ok(resp) if resp.status().is_success() => return true,
+            // Other client errors won't succeed on retry
+            Ok(resp) if resp.status().is_client_error() && resp.status() != reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                error!(event = notification.kind(), status = %resp.status(), "webhook rejected notification");
+                return false;
+            }
+            Ok(resp) => format!("HTTP {}", resp.status()),
+            Err(e) => e.to_string(),
+        };
+
+        attempt += 1;
+        if attempt > policy.max_retries {
+            warn!(event = notification.kind(), retries = policy.max_retries, %error, "giving up on webhook notification");
+            return false;
+        }
+        tokio::time::sleep(policy.retry.delay(attempt)).await;
+    }
+}
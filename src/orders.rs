@@ -0,0 +1,275 @@
+use crate::aggregator::{Quote, Source};
+use crate::trader::TradeSide;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+pub type OrderId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderState {
+    Open,
+    Filled,
+    Cancelled,
+}
+
+impl fmt::Display for OrderState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderState::Open => write!(f, "open"),
+            OrderState::Filled => write!(f, "filled"),
+            OrderState::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+/// A passive limit order resting until the market trades through it or we pull it
+#[derive(Debug, Clone, Serialize)]
+pub struct RestingOrder {
+    pub id: OrderId,
+    pub side: TradeSide,
+    pub price: f64,
+    pub notional: f64,
+    pub state: OrderState,
+    pub placed_at: i64,
+    /// Last placement, amendment, fill or cancellation
+    pub updated_at: i64,
+    pub amendments: u32,
+    /// Fill model estimate when the order was last (re)priced
+    pub execution_prob: f64,
+    pub expected_edge_bps: f64,
+    /// Placed below source quorum
+    pub degraded: bool,
+    /// Venue whose quote crossed the order, once filled
+    pub filled_by: Option<Source>,
+}
+
+impl RestingOrder {
+    pub fn amount_eth(&self) -> f64 {
+        self.notional / self.price
+    }
+
+    /// Whether `quote` trades through this order: an ask at or below our bid, or a bid at or above our ask
+    pub fn crossed_by(&self, quote: &Quote) -> bool {
+        match self.side {
+            TradeSide::Buy => quote.ask <= self.price,
+            TradeSide::Sell => quote.bid >= self.price,
+        }
+    }
+}
+
+/// Price, size and economics for placing or re-pricing an order
+#[derive(Debug, Clone, Copy)]
+pub struct OrderRequest {
+    pub side: TradeSide,
+    pub price: f64,
+    pub notional: f64,
+    pub execution_prob: f64,
+    pub expected_edge_bps: f64,
+    pub degraded: bool,
+}
+
+/// What re-quoting one side did to its resting order
+#[derive(Debug, Clone)]
+pub enum OrderAction {
+    Placed(RestingOrder),
+    Amended { order: RestingOrder, previous_price: f64 },
+    /// Existing order is within the amend threshold and keeps its place
+    Kept(RestingOrder),
+    Cancelled(RestingOrder),
+}
+
+/// Lifetime counts for the session summary
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OrderStats {
+    pub placed: u64,
+    pub amended: u64,
+    pub cancelled: u64,
+    pub filled: u64,
+}
+
+impl fmt::Display for OrderStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fill_rate = if self.placed > 0 {
+            self.filled as f64 / self.placed as f64 * 100.0
+        } else {
+            0.0
+        };
+        write!(f, "Resting orders: {} placed │ {} amended │ {} cancelled │ {} filled ({:.1}% of placed)",
+            self.placed, self.amended, self.cancelled, self.filled, fill_rate)
+    }
+}
+
+/// Tracks at most one resting order per side, and fills them off incoming quotes
+#[derive(Debug)]
+pub struct OrderManager {
+    /// Re-price an open order only when the new price is this far from it, in bps
+    amend_threshold_bps: f64,
+    orders: BTreeMap<OrderId, RestingOrder>,
+    next_id: OrderId,
+    stats: OrderStats,
+}
+
+impl Default for OrderManager {
+    fn default() -> Self {
+        Self::new(0.5)
+    }
+}
+
+impl OrderManager {
+    pub fn new(amend_threshold_bps: f64) -> Self {
+        Self {
+            amend_threshold_bps,
+            orders: BTreeMap::new(),
+            next_id: 1,
+            stats: OrderStats::default(),
+        }
+    }
+
+    pub fn amend_threshold_bps(&self) -> f64 {
+        self.amend_threshold_bps
+    }
+
+    pub fn stats(&self) -> OrderStats {
+        self.stats
+    }
+
+    pub fn get(&self, id: OrderId) -> Option<&RestingOrder> {
+        self.orders.get(&id)
+    }
+
+    /// Every order of the session, oldest first
+    pub fn orders(&self) -> impl Iterator<Item = &RestingOrder> {
+        self.orders.values()
+    }
+
+    pub fn open_order(&self, side: TradeSide) -> Option<&RestingOrder> {
+        self.orders.values().find(|o| o.side == side && o.state == OrderState::Open)
+    }
+
+    /// Place, re-price or keep the order on `request.side` so it rests at the requested price
+    pub fn requote(&mut self, request: OrderRequest, timestamp: i64) -> OrderAction {
+        let open_id = self.open_order(request.side).map(|o| o.id);
+        let Some(id) = open_id else {
+            return OrderAction::Placed(self.place(request, timestamp));
+        };
+
+        let threshold = self.amend_threshold_bps;
+        let order = self.orders.get_mut(&id).expect("open order exists");
+        let moved_bps = (request.price - order.price).abs() / order.price * 10000.0;
+        let resized = (request.notional - order.notional).abs() > 1e-9;
+        if moved_bps <= threshold && !resized {
+            return OrderAction::Kept(order.clone());
+        }
+
+        let previous_price = order.price;
+        order.price = request.price;
+        order.notional = request.notional;
+        order.execution_prob = request.execution_prob;
+        order.expected_edge_bps = request.expected_edge_bps;
+        order.degraded = request.degraded;
+        order.amendments += 1;
+        order.updated_at = timestamp;
+        self.stats.amended += 1;
+        OrderAction::Amended {
+            order: order.clone(),
+            previous_price,
+        }
+    }
+
+    pub fn place(&mut self, request: OrderRequest, timestamp: i64) -> RestingOrder {
+        let id = self.next_id;
+        self.next_id += 1;
+        let order = RestingOrder {
+            id,
+            side: request.side,
+            price: request.price,
+            notional: request.notional,
+            state: OrderState::Open,
+            placed_at: timestamp,
+            updated_at: timestamp,
+            amendments: 0,
+            execution_prob: request.execution_prob,
+            expected_edge_bps: request.expected_edge_bps,
+            degraded: request.degraded,
+            filled_by: None,
+        };
+        self.orders.insert(id, order.clone());
+        self.stats.placed += 1;
+        order
+    }
+
+    /// Cancel an open order; `None` if it isn't open
+    pub fn cancel(&mut self, id: OrderId, timestamp: i64) -> Option<RestingOrder> {
+        let order = self.orders.get_mut(&id).filter(|o| o.state == OrderState::Open)?;
+        order.state = OrderState::Cancelled;
+        order.updated_at = timestamp;
+        self.stats.cancelled += 1;
+        Some(order.clone())
+    }
+
+    pub fn cancel_side(&mut self, side: TradeSide, timestamp: i64) -> Option<RestingOrder> {
+        let id = self.open_order(side)?.id;
+        self.cancel(id, timestamp)
+    }
+
+    pub fn cancel_all(&mut self, timestamp: i64) -> Vec<RestingOrder> {
+        let open: Vec<OrderId> = self
+            .orders
+            .values()
+            .filter(|o| o.state == OrderState::Open)
+            .map(|o| o.id)
+            .collect();
+        open.into_iter().filter_map(|id| self.cancel(id, timestamp)).collect()
+    }
+
+    /// Put a just-filled order back on the book with `notional` left to fill, for a fill held back
+    /// in full or in part
+    pub fn reopen(&mut self, id: OrderId, notional: f64) -> Option<RestingOrder> {
+        let order = self.orders.get_mut(&id).filter(|o| o.state == OrderState::Filled)?;
+        // A fill held back in full never happened; one cut down still filled in part
+        if notional >= order.notional {
+            self.stats.filled -= 1;
+        }
+        order.state = OrderState::Open;
+        order.notional = notional;
+        order.filled_by = None;
+        Some(order.clone())
+    }
+
+    /// Take `notional` off an order on a venue's fill report, closing it as filled once `complete`.
+    /// Cancelled orders are found too, since the venue may fill one before our cancel reaches it.
+    /// Returns the order as it stood for the fill, sized to the filled notional.
+    pub fn fill(&mut self, id: OrderId, notional: f64, complete: bool, source: Source, timestamp: i64) -> Option<RestingOrder> {
+        let order = self.orders.get_mut(&id)?;
+        let mut fill = order.clone();
+        fill.notional = notional;
+        fill.filled_by = Some(source);
+        if order.state == OrderState::Open {
+            order.notional = (order.notional - notional).max(0.0);
+            order.updated_at = timestamp;
+            if complete {
+                order.state = OrderState::Filled;
+                order.filled_by = Some(source);
+                self.stats.filled += 1;
+            }
+        }
+        Some(fill)
+    }
+
+    /// Fill engine: every open order `quote` from `source` trades through is filled in full
+    pub fn on_quote(&mut self, source: Source, quote: &Quote) -> Vec<RestingOrder> {
+        let mut filled = Vec::new();
+        for order in self.orders.values_mut() {
+            if order.state == OrderState::Open && order.crossed_by(quote) {
+                order.state = OrderState::Filled;
+                order.updated_at = quote.timestamp;
+                order.filled_by = Some(source);
+                self.stats.filled += 1;
+                filled.push(order.clone());
+            }
+        }
+        filled
+    }
+}
@@ -0,0 +1,104 @@
+use serde::Serialize;
+use std::future::Future;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static JSON: AtomicBool = AtomicBool::new(false);
+
+tokio::task_local! {
+    /// Pair whose session is writing, when several markets share the log
+    static MARKET: String;
+}
+
+/// Switch the session log to JSON lines (`--json`) for the rest of the process
+pub fn set_json(enabled: bool) {
+    JSON.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether the session log is JSON lines rather than decorated text
+pub fn json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+/// Run `session` with every line it writes labelled with `market`: a `[PAIR]` prefix in text mode,
+/// a `market` field under `--json`
+pub async fn in_market<F: Future>(market: String, session: F) -> F::Output {
+    MARKET.scope(market, session).await
+}
+
+fn market() -> Option<String> {
+    MARKET.try_with(Clone::clone).ok()
+}
+
+/// One line of `--json` output: what happened, when, and its fields
+#[derive(Serialize)]
+struct JsonLine<'a, T: Serialize> {
+    event: &'a str,
+    /// Wall-clock time the line was written, in ms
+    ts: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    market: Option<String>,
+    #[serde(flatten)]
+    fields: &'a T,
+}
+
+/// A `log` event: a text line with its `[TAG]` split off
+#[derive(Serialize)]
+struct LogLine<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tag: Option<&'a str>,
+    message: &'a str,
+}
+
+/// Write `fields`, which must serialize as a map, as one JSON line of kind `event`. Does nothing in text mode,
+/// where the decorated log already covers it.
+pub fn emit<T: Serialize>(event: &str, fields: &T) {
+    if !json() {
+        return;
+    }
+    let line = JsonLine {
+        event,
+        ts: chrono::Utc::now().timestamp_millis(),
+        market: market(),
+        fields,
+    };
+    match serde_json::to_string(&line) {
+        Ok(line) => {
+            let _ = writeln!(std::io::stdout().lock(), "{}", line);
+        }
+        Err(e) => eprintln!("[ERROR] Failed to serialize {} event: {}", event, e),
+    }
+}
+
+/// A line of the session log. Printed as is in text mode. With `--json`, blank and box-drawing lines are
+/// dropped and the rest become `log` events with the `[TAG]` prefix split off; a multi-line block stays
+/// one event.
+pub fn line(text: &str) {
+    if !json() {
+        match market() {
+            Some(market) if !text.trim().is_empty() => println!("[{}] {}", market, text.trim_start_matches('\n')),
+            _ => println!("{}", text),
+        }
+        return;
+    }
+    let text = text.trim();
+    if text.is_empty() || text.chars().all(|c| c.is_whitespace() || ('\u{2500}'..='\u{257f}').contains(&c)) {
+        return;
+    }
+    let (tag, message) = match text.strip_prefix('[').and_then(|rest| rest.split_once(']')) {
+        Some((tag, message)) if !tag.is_empty() && tag.len() <= 16 => (Some(tag), message.trim()),
+        _ => (None, text),
+    };
+    emit("log", &LogLine { tag, message });
+}
+
+/// `println!` for the session log: the text as is, or a `log` event under `--json`
+#[macro_export]
+macro_rules! say {
+    () => {
+        $crate::output::line("")
+    };
+    ($($arg:tt)*) => {
+        $crate::output::line(&format!($($arg)*))
+    };
+}
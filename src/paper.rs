@@ -0,0 +1,530 @@
+use crate::http::{HttpClient, HttpPolicy};
+use crate::orders::{OrderId, RestingOrder};
+use crate::trader::TradeSide;
+use anyhow::{bail, Context, Result};
+use reqwest::Method;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use tracing::{debug, info, warn};
+
+/// Binance Spot Testnet REST API
+pub const TESTNET_API_URL: &str = "https://testnet.binance.vision";
+
+/// Binance error codes the order sync handles rather than reports
+const ORDER_REJECTED: i64 = -2010;
+const UNKNOWN_ORDER: i64 = -2011;
+const NO_SUCH_ORDER: i64 = -2013;
+
+/// Where and as whom to paper trade
+#[derive(Debug, Clone)]
+pub struct TestnetAccount {
+    pub api_url: String,
+    pub api_key: String,
+    pub api_secret: String,
+    /// Spot symbol the orders go to, e.g. `ETHUSDT`
+    pub symbol: String,
+    pub recv_window_ms: u64,
+}
+
+/// An error the Binance API explained in its response body
+#[derive(Debug)]
+pub struct BinanceError {
+    pub code: i64,
+    pub msg: String,
+}
+
+impl fmt::Display for BinanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Binance error {}: {}", self.code, self.msg)
+    }
+}
+
+impl std::error::Error for BinanceError {}
+
+fn binance_code(error: &anyhow::Error) -> Option<i64> {
+    error.downcast_ref::<BinanceError>().map(|e| e.code)
+}
+
+/// One execution the testnet reported for an order we placed
+#[derive(Debug, Clone)]
+pub struct VenueFill {
+    /// Our resting order the venue order was placed for
+    pub order_id: OrderId,
+    pub venue_trade_id: u64,
+    pub side: TradeSide,
+    pub price: f64,
+    pub amount_eth: f64,
+    /// Commission in USD when it was charged in the base or quote asset
+    pub commission_usd: Option<f64>,
+    pub maker: bool,
+    /// Exchange time of the execution
+    pub timestamp: i64,
+    /// Nothing of the venue order is left open
+    pub complete: bool,
+}
+
+impl VenueFill {
+    pub fn notional(&self) -> f64 {
+        self.price * self.amount_eth
+    }
+}
+
+/// Lifetime counts of the testnet order flow
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PaperStats {
+    pub placed: u64,
+    pub cancelled: u64,
+    /// Post-only orders the venue refused because they would have crossed
+    pub rejected: u64,
+    pub fills: u64,
+    /// Requests that failed for any other reason
+    pub errors: u64,
+}
+
+impl fmt::Display for PaperStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Binance testnet: {} orders placed │ {} cancelled │ {} rejected as crossing │ {} fills │ {} errors",
+            self.placed, self.cancelled, self.rejected, self.fills, self.errors)
+    }
+}
+
+/// Price and size increments the symbol trades in
+#[derive(Debug, Clone)]
+struct SymbolRules {
+    base_asset: String,
+    quote_asset: String,
+    tick_size: f64,
+    step_size: f64,
+    min_qty: f64,
+    min_notional: f64,
+}
+
+impl SymbolRules {
+    /// Bids round down and asks up, so rounding never moves an order towards the touch
+    fn price(&self, side: TradeSide, price: f64) -> f64 {
+        let ticks = price / self.tick_size;
+        let ticks = match side {
+            TradeSide::Buy => (ticks + 1e-9).floor(),
+            TradeSide::Sell => (ticks - 1e-9).ceil(),
+        };
+        ticks * self.tick_size
+    }
+
+    fn quantity(&self, amount_eth: f64) -> f64 {
+        (amount_eth / self.step_size + 1e-9).floor() * self.step_size
+    }
+
+    fn tradable(&self, price: f64, quantity: f64) -> bool {
+        quantity >= self.min_qty && price * quantity >= self.min_notional
+    }
+}
+
+/// Tick, lot and minimum notional rules of the symbol from `exchangeInfo`
+async fn symbol_rules(http: &HttpClient, account: &TestnetAccount) -> Result<SymbolRules> {
+    let url = format!("{}/api/v3/exchangeInfo?symbol={}", account.api_url, account.symbol);
+    let info: serde_json::Value = http.send(|c| c.get(&url)).await?.json().await?;
+    let symbol = &info["symbols"][0];
+    let filter = |kind: &str, key: &str| -> Option<f64> {
+        symbol["filters"]
+            .as_array()?
+            .iter()
+            .find(|f| f["filterType"] == kind)?[key]
+            .as_str()?
+            .parse()
+            .ok()
+    };
+    let rules = SymbolRules {
+        base_asset: symbol["baseAsset"].as_str().context("symbol not listed on the testnet")?.to_string(),
+        quote_asset: symbol["quoteAsset"].as_str().unwrap_or_default().to_string(),
+        tick_size: filter("PRICE_FILTER", "tickSize").context("no PRICE_FILTER for symbol")?,
+        step_size: filter("LOT_SIZE", "stepSize").context("no LOT_SIZE for symbol")?,
+        min_qty: filter("LOT_SIZE", "minQty").unwrap_or_default(),
+        min_notional: filter("NOTIONAL", "minNotional")
+            .or_else(|| filter("MIN_NOTIONAL", "minNotional"))
+            .unwrap_or_default(),
+    };
+    if rules.tick_size <= 0.0 || rules.step_size <= 0.0 {
+        bail!("testnet returned zero tick or step size for {}", account.symbol);
+    }
+    Ok(rules)
+}
+
+/// Fixed-point text for `value` in increments of `step`
+fn format_step(value: f64, step: f64) -> String {
+    let decimals = (-step.log10()).ceil().max(0.0) as usize;
+    format!("{:.*}", decimals, value)
+}
+
+/// An order placement whose outcome is unknown (timeout, 5xx), until a lookup by client order id settles it
+#[derive(Debug, Clone)]
+struct UnresolvedOrder {
+    client_order_id: String,
+    id: OrderId,
+    side: TradeSide,
+    price: f64,
+    quantity: f64,
+}
+
+/// A venue order working one of our resting orders
+#[derive(Debug, Clone, Copy)]
+struct LiveOrder {
+    venue_id: u64,
+    price: f64,
+    /// Left open on the venue
+    quantity: f64,
+}
+
+#[derive(Debug, Default)]
+struct PaperState {
+    live: HashMap<OrderId, LiveOrder>,
+    /// Every venue order of the session, so fills arriving after a cancel still find their order
+    owners: HashMap<u64, (OrderId, TradeSide)>,
+    /// Placements to look up before trusting the venue's view; fills aren't read until they're settled
+    unresolved: Vec<UnresolvedOrder>,
+    /// Next testnet trade id to ask for
+    from_trade_id: u64,
+    submissions: u64,
+    stats: PaperStats,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountTrade {
+    id: u64,
+    order_id: u64,
+    price: String,
+    qty: String,
+    commission: String,
+    commission_asset: String,
+    time: i64,
+    is_maker: bool,
+}
+
+/// Mirrors the engine's resting orders onto Binance Spot Testnet as post-only limit orders and
+/// reads back what actually filled
+pub struct PaperTrader {
+    http: HttpClient,
+    account: TestnetAccount,
+    rules: SymbolRules,
+    /// Prefix of our client order ids, so the session's orders are recognisable on the venue
+    prefix: String,
+    state: Mutex<PaperState>,
+}
+
+impl PaperTrader {
+    /// Check the keys, load the symbol's trading rules and start reading fills from the newest trade
+    pub async fn connect(account: TestnetAccount, session_id: &str) -> Result<Self> {
+        let http = HttpClient::new(HttpPolicy::default().with_rate_limit(10.0))?;
+        let rules = symbol_rules(&http, &account).await?;
+        let trader = Self {
+            http,
+            rules,
+            prefix: format!("mm{}", session_id.replace(|c: char| !c.is_ascii_alphanumeric(), "")),
+            account,
+            state: Mutex::new(PaperState::default()),
+        };
+        trader
+            .signed(Method::GET, "/api/v3/account", Vec::new())
+            .await
+            .context("testnet account check failed, are the API key and secret for the Spot Testnet?")?;
+
+        let latest = trader
+            .signed(Method::GET, "/api/v3/myTrades", vec![("symbol", trader.account.symbol.clone()), ("limit", "1".to_string())])
+            .await?;
+        let latest: Vec<AccountTrade> = serde_json::from_value(latest)?;
+        trader.state.lock().unwrap().from_trade_id = latest.last().map_or(0, |t| t.id + 1);
+        info!(symbol = %trader.account.symbol, tick = trader.rules.tick_size, step = trader.rules.step_size, "connected to Binance Spot Testnet");
+        Ok(trader)
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.account.symbol
+    }
+
+    pub fn stats(&self) -> PaperStats {
+        self.state.lock().unwrap().stats
+    }
+
+    /// A signed request, with Binance's error body turned into a `BinanceError`
+    async fn signed(&self, method: Method, path: &str, params: Vec<(&str, String)>) -> Result<serde_json::Value> {
+        self.signed_request(method, path, params, true).await
+    }
+
+    async fn signed_request(&self, method: Method, path: &str, params: Vec<(&str, String)>, retry: bool) -> Result<serde_json::Value> {
+        let url = format!("{}{}", self.account.api_url, path);
+        let build = |client: &reqwest::Client| {
+                // Signed fresh on every attempt so a retry carries a current timestamp
+                let mut query: Vec<String> = params.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+                query.push(format!("recvWindow={}", self.account.recv_window_ms));
+                query.push(format!("timestamp={}", chrono::Utc::now().timestamp_millis()));
+                let query = query.join("&");
+                let signature = hex(&hmac_sha256(self.account.api_secret.as_bytes(), query.as_bytes()));
+                client
+                    .request(method.clone(), format!("{}?{}&signature={}", url, query, signature))
+                    .header("X-MBX-APIKEY", &self.account.api_key)
+        };
+        let response = if retry { self.http.send_raw(build).await? } else { self.http.send_once(build).await? };
+
+        let status = response.status();
+        let body: serde_json::Value = response.json().await.with_context(|| format!("{} {}: unreadable response", method, path))?;
+        if !status.is_success() {
+            return Err(BinanceError {
+                code: body["code"].as_i64().unwrap_or_default(),
+                msg: body["msg"].as_str().unwrap_or(status.as_str()).to_string(),
+            }
+            .into());
+        }
+        Ok(body)
+    }
+
+    /// Bring the venue in line with `open`: cancel venue orders whose resting order is gone or was
+    /// re-priced or resized, and place the ones missing. Failures are logged and retried next sync.
+    pub async fn sync(&self, open: &[RestingOrder]) {
+        self.resolve_unknown().await;
+        let desired: HashMap<OrderId, (TradeSide, f64, f64)> = open
+            .iter()
+            .map(|order| {
+                let price = self.rules.price(order.side, order.price);
+                (order.id, (order.side, price, self.rules.quantity(order.notional / price)))
+            })
+            .collect();
+
+        let stale: Vec<(OrderId, LiveOrder)> = {
+            let state = self.state.lock().unwrap();
+            state
+                .live
+                .iter()
+                .filter(|(id, live)| match desired.get(id) {
+                    Some(&(_, price, quantity)) => {
+                        (live.price - price).abs() > self.rules.tick_size / 2.0
+                            || (live.quantity - quantity).abs() > self.rules.step_size / 2.0
+                    }
+                    None => true,
+                })
+                .map(|(&id, &live)| (id, live))
+                .collect()
+        };
+        for (id, live) in stale {
+            self.cancel(id, live).await;
+        }
+
+        let missing: Vec<(OrderId, (TradeSide, f64, f64))> = {
+            let state = self.state.lock().unwrap();
+            // An order still being looked up may be resting already, so it isn't placed twice
+            desired
+                .into_iter()
+                .filter(|(id, _)| !state.live.contains_key(id) && !state.unresolved.iter().any(|u| u.id == *id))
+                .collect()
+        };
+        for (id, (side, price, quantity)) in missing {
+            if !self.rules.tradable(price, quantity) {
+                debug!(order = id, quantity, "order below the symbol's minimum size, not placed");
+                continue;
+            }
+            self.place(id, side, price, quantity).await;
+        }
+    }
+
+    async fn place(&self, id: OrderId, side: TradeSide, price: f64, quantity: f64) {
+        let client_order_id = {
+            let mut state = self.state.lock().unwrap();
+            state.submissions += 1;
+            format!("{}-{}-{}", self.prefix, id, state.submissions)
+        };
+        let params = vec![
+            ("symbol", self.account.symbol.clone()),
+            ("side", match side {
+                TradeSide::Buy => "BUY",
+                TradeSide::Sell => "SELL",
+            }.to_string()),
+            ("type", "LIMIT_MAKER".to_string()),
+            ("quantity", format_step(quantity, self.rules.step_size)),
+            ("price", format_step(price, self.rules.tick_size)),
+            ("newClientOrderId", client_order_id.clone()),
+            ("newOrderRespType", "ACK".to_string()),
+        ];
+
+        // Never retried: a retry of an order that did go in would only be refused as a duplicate
+        let result = self.signed_request(Method::POST, "/api/v3/order", params, false).await;
+        let order = UnresolvedOrder { client_order_id, id, side, price, quantity };
+        match result {
+            Err(e) if binance_code(&e).is_none() => {
+                warn!(order = id, error = %e, "testnet order outcome unknown, looking it up");
+                self.state.lock().unwrap().unresolved.push(order);
+                self.resolve_unknown().await;
+            }
+            result => self.settle(&order, result),
+        }
+    }
+
+    /// Record the outcome of placing `order`, from its order response or its lookup
+    fn settle(&self, order: &UnresolvedOrder, result: Result<serde_json::Value>) {
+        let id = order.id;
+        let mut state = self.state.lock().unwrap();
+        match result.and_then(|body| Ok((body["orderId"].as_u64().context("no orderId in order response")?, body))) {
+            Ok((venue_id, body)) => {
+                debug!(order = id, venue_id, price = order.price, quantity = order.quantity, "testnet order placed");
+                // A looked-up order may have filled or been cancelled already; its fills still count
+                if matches!(body["status"].as_str(), None | Some("NEW") | Some("PARTIALLY_FILLED")) {
+                    state.live.insert(id, LiveOrder { venue_id, price: order.price, quantity: order.quantity });
+                }
+                state.owners.insert(venue_id, (id, order.side));
+                state.stats.placed += 1;
+            }
+            Err(e) if binance_code(&e) == Some(ORDER_REJECTED) && e.to_string().contains("immediately match") => {
+                debug!(order = id, price = order.price, error = %e, "post-only order would cross, not placed");
+                state.stats.rejected += 1;
+            }
+            Err(e) if binance_code(&e) == Some(NO_SUCH_ORDER) => {
+                warn!(order = id, "testnet order never reached the book");
+                state.stats.errors += 1;
+            }
+            Err(e) => {
+                warn!(order = id, error = %e, "testnet order failed");
+                state.stats.errors += 1;
+            }
+        }
+    }
+
+    /// Look up placements whose outcome is unknown by their client order id. Ones the lookup
+    /// can't settle either stay for the next try.
+    async fn resolve_unknown(&self) {
+        let pending = std::mem::take(&mut self.state.lock().unwrap().unresolved);
+        for order in pending {
+            let params = vec![("symbol", self.account.symbol.clone()), ("origClientOrderId", order.client_order_id.clone())];
+            match self.signed(Method::GET, "/api/v3/order", params).await {
+                Err(e) if binance_code(&e).is_none() => {
+                    debug!(order = order.id, error = %e, "order lookup failed, retrying next sync");
+                    self.state.lock().unwrap().unresolved.push(order);
+                }
+                result => self.settle(&order, result),
+            }
+        }
+    }
+
+    async fn cancel(&self, id: OrderId, live: LiveOrder) {
+        let params = vec![("symbol", self.account.symbol.clone()), ("orderId", live.venue_id.to_string())];
+        let result = self.signed(Method::DELETE, "/api/v3/order", params).await;
+        let mut state = self.state.lock().unwrap();
+        match result {
+            Ok(_) => {
+                state.live.remove(&id);
+                state.stats.cancelled += 1;
+            }
+            // Already filled or cancelled on the venue; any fill still arrives through `poll_fills`
+            Err(e) if binance_code(&e) == Some(UNKNOWN_ORDER) => {
+                state.live.remove(&id);
+            }
+            Err(e) => {
+                warn!(order = id, error = %e, "testnet cancel failed");
+                state.stats.errors += 1;
+            }
+        }
+    }
+
+    /// Pull every order of the session off the venue
+    pub async fn cancel_all(&self) {
+        self.sync(&[]).await;
+    }
+
+    /// Executions of our venue orders since the last poll, oldest first
+    pub async fn poll_fills(&self) -> Vec<VenueFill> {
+        // Trades of an order not known to be ours yet would be skipped for good
+        self.resolve_unknown().await;
+        if !self.state.lock().unwrap().unresolved.is_empty() {
+            return Vec::new();
+        }
+        let from_id = self.state.lock().unwrap().from_trade_id;
+        let params = vec![
+            ("symbol", self.account.symbol.clone()),
+            ("fromId", from_id.to_string()),
+            ("limit", "1000".to_string()),
+        ];
+        let trades: Vec<AccountTrade> = match self.signed(Method::GET, "/api/v3/myTrades", params).await {
+            Ok(body) => match serde_json::from_value(body) {
+                Ok(trades) => trades,
+                Err(e) => {
+                    warn!(error = %e, "unexpected myTrades response");
+                    return Vec::new();
+                }
+            },
+            Err(e) => {
+                warn!(error = %e, "polling testnet fills failed");
+                self.state.lock().unwrap().stats.errors += 1;
+                return Vec::new();
+            }
+        };
+
+        let mut state = self.state.lock().unwrap();
+        let mut fills = Vec::new();
+        for trade in trades {
+            state.from_trade_id = state.from_trade_id.max(trade.id + 1);
+            // Someone else's order on the same account
+            let Some(&(order_id, side)) = state.owners.get(&trade.order_id) else {
+                continue;
+            };
+            let (Ok(price), Ok(amount_eth)) = (trade.price.parse::<f64>(), trade.qty.parse::<f64>()) else {
+                continue;
+            };
+            let commission: f64 = trade.commission.parse().unwrap_or_default();
+            let commission_usd = if trade.commission_asset == self.rules.quote_asset {
+                Some(commission)
+            } else if trade.commission_asset == self.rules.base_asset {
+                Some(commission * price)
+            } else {
+                None
+            };
+
+            let complete = match state.live.get_mut(&order_id).filter(|live| live.venue_id == trade.order_id) {
+                Some(live) => {
+                    live.quantity -= amount_eth;
+                    live.quantity < self.rules.step_size / 2.0
+                }
+                // The venue order was cancelled or replaced, nothing of it rests any more
+                None => false,
+            };
+            if complete {
+                state.live.remove(&order_id);
+            }
+            state.stats.fills += 1;
+            fills.push(VenueFill {
+                order_id,
+                venue_trade_id: trade.id,
+                side,
+                price,
+                amount_eth,
+                commission_usd,
+                maker: trade.is_maker,
+                timestamp: trade.time,
+                complete,
+            });
+        }
+        fills
+    }
+}
+
+/// HMAC-SHA256 (RFC 2104), the signature Binance expects on private endpoints
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK: usize = 64;
+    let mut block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(message);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,240 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Cap on our share of the market's traded volume
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ParticipationPolicy {
+    /// Most of the observed market volume our fills may add up to, in percent
+    pub max_pct: f64,
+    /// Trailing feed-time window both volumes are measured over
+    pub interval_ms: i64,
+}
+
+/// What the cap did over a session
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ParticipationStats {
+    /// Fills that went through at full size
+    pub allowed: u64,
+    /// Fills cut down to what the window had left
+    pub shrunk: u64,
+    /// Fills held back because the window had nothing left
+    pub deferred: u64,
+    /// Notional the cap took off fills
+    pub withheld_usd: f64,
+    /// Notional we filled
+    pub filled_usd: f64,
+    /// Market volume seen on the tape
+    pub market_usd: f64,
+}
+
+impl ParticipationStats {
+    /// Our filled notional as a share of the market volume seen, in percent
+    pub fn participation_pct(&self) -> f64 {
+        if self.market_usd > 0.0 {
+            self.filled_usd / self.market_usd * 100.0
+        } else {
+            0.0
+        }
+    }
+}
+
+impl fmt::Display for ParticipationStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Participation: {:.2}% of ${:.0} market volume │ {} fills in full │ {} shrunk │ {} deferred │ ${:.0} held back",
+            self.participation_pct(), self.market_usd, self.allowed, self.shrunk, self.deferred, self.withheld_usd)
+    }
+}
+
+/// Keeps our fills within `max_pct` of the volume on the trade tape over a trailing window.
+/// The tape is sampled as a running total, and the window's market volume counts only from its
+/// oldest sample, so sparse sampling understates it rather than letting older volume leak in.
+pub struct ParticipationLimiter {
+    policy: ParticipationPolicy,
+    /// (feed time, cumulative market volume) samples inside the window
+    tape: VecDeque<(i64, f64)>,
+    /// (feed time, notional) of our fills inside the window
+    fills: VecDeque<(i64, f64)>,
+    stats: ParticipationStats,
+}
+
+impl ParticipationLimiter {
+    pub fn new(policy: ParticipationPolicy) -> Self {
+        Self {
+            policy,
+            tape: VecDeque::new(),
+            fills: VecDeque::new(),
+            stats: ParticipationStats::default(),
+        }
+    }
+
+    pub fn policy(&self) -> &ParticipationPolicy {
+        &self.policy
+    }
+
+    pub fn stats(&self) -> ParticipationStats {
+        self.stats
+    }
+
+    /// Sample the tape's running total of traded USD at `timestamp`; samples no newer than the last are ignored
+    pub fn observe(&mut self, timestamp: i64, cumulative_usd: f64) {
+        if let Some(&(last_at, last_usd)) = self.tape.back() {
+            if timestamp <= last_at {
+                return;
+            }
+            self.stats.market_usd += (cumulative_usd - last_usd).max(0.0);
+        }
+        self.tape.push_back((timestamp, cumulative_usd));
+        self.expire(timestamp);
+    }
+
+    /// Market volume in the current window
+    pub fn market_volume(&self) -> f64 {
+        match (self.tape.front(), self.tape.back()) {
+            (Some(first), Some(last)) => (last.1 - first.1).max(0.0),
+            _ => 0.0,
+        }
+    }
+
+    /// Notional still allowed in the current window
+    pub fn remaining(&self) -> f64 {
+        let ours: f64 = self.fills.iter().map(|&(_, usd)| usd).sum();
+        (self.market_volume() * self.policy.max_pct / 100.0 - ours).max(0.0)
+    }
+
+    /// Size a fill of `notional` at `timestamp` may take: the full amount, what's left of the
+    /// window's allowance, or `None` to defer it. The returned size is counted as filled.
+    pub fn allow(&mut self, timestamp: i64, notional: f64) -> Option<f64> {
+        self.expire(timestamp);
+        let allowed = self.check(timestamp, notional, 0.0);
+        self.record(timestamp, notional, allowed);
+        allowed
+    }
+
+    /// What `allow` would let a fill of `notional` at `timestamp` take, without counting it.
+    /// `reserved` is notional already promised to fills checked but not yet recorded.
+    pub fn check(&self, timestamp: i64, notional: f64, reserved: f64) -> Option<f64> {
+        let cutoff = timestamp - self.policy.interval_ms;
+        let ours = reserved + self.fills.iter().filter(|&&(at, _)| at >= cutoff).map(|&(_, usd)| usd).sum::<f64>();
+        let remaining = (self.market_volume() * self.policy.max_pct / 100.0 - ours).max(0.0);
+        let allowed = notional.min(remaining);
+        // Slivers aren't worth a fill of their own; wait for the window to free up more
+        (allowed >= notional * 0.01).then_some(allowed)
+    }
+
+    /// Count a fill of `notional` that was checked earlier: `allowed` as `check` returned it,
+    /// `None` when it was deferred
+    pub fn record(&mut self, timestamp: i64, notional: f64, allowed: Option<f64>) {
+        self.expire(timestamp);
+        let Some(allowed) = allowed else {
+            self.stats.deferred += 1;
+            self.stats.withheld_usd += notional;
+            return;
+        };
+        if allowed < notional {
+            self.stats.shrunk += 1;
+            self.stats.withheld_usd += notional - allowed;
+        } else {
+            self.stats.allowed += 1;
+        }
+        self.stats.filled_usd += allowed;
+        self.fills.push_back((timestamp, allowed));
+    }
+
+    /// Like `allow`, for fills that can't be cut down: all of `notional` or nothing
+    pub fn allow_whole(&mut self, timestamp: i64, notional: f64) -> bool {
+        self.expire(timestamp);
+        if self.remaining() < notional {
+            self.stats.deferred += 1;
+            self.stats.withheld_usd += notional;
+            return false;
+        }
+        self.stats.allowed += 1;
+        self.stats.filled_usd += notional;
+        self.fills.push_back((timestamp, notional));
+        true
+    }
+
+    fn expire(&mut self, timestamp: i64) {
+        let cutoff = timestamp - self.policy.interval_ms;
+        while self.tape.len() > 1 && self.tape[0].0 < cutoff {
+            self.tape.pop_front();
+        }
+        while self.fills.front().is_some_and(|&(at, _)| at < cutoff) {
+            self.fills.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 10% of the volume over a minute, with $10,000 traded by t = 1s: a $1,000 allowance
+    fn limiter() -> ParticipationLimiter {
+        let mut limiter = ParticipationLimiter::new(ParticipationPolicy {
+            max_pct: 10.0,
+            interval_ms: 60_000,
+        });
+        limiter.observe(0, 0.0);
+        limiter.observe(1_000, 10_000.0);
+        limiter
+    }
+
+    #[test]
+    fn fills_go_through_then_shrink_then_defer() {
+        let mut limiter = limiter();
+        assert_eq!(limiter.allow(1_000, 400.0), Some(400.0));
+        assert_eq!(limiter.allow(1_000, 1_000.0), Some(600.0));
+        assert_eq!(limiter.allow(1_000, 100.0), None);
+
+        let stats = limiter.stats();
+        assert_eq!((stats.allowed, stats.shrunk, stats.deferred), (1, 1, 1));
+        assert_eq!(stats.filled_usd, 1_000.0);
+        assert_eq!(stats.withheld_usd, 500.0);
+        assert_eq!(stats.participation_pct(), 10.0);
+    }
+
+    #[test]
+    fn reserved_notional_counts_against_the_allowance() {
+        let mut limiter = limiter();
+        assert_eq!(limiter.check(1_000, 500.0, 800.0), Some(200.0));
+        assert_eq!(limiter.check(1_000, 500.0, 1_000.0), None);
+
+        // Checking doesn't count anything until the fill is recorded
+        let allowed = limiter.check(1_000, 700.0, 0.0);
+        assert_eq!(allowed, Some(700.0));
+        limiter.record(1_000, 700.0, allowed);
+        assert_eq!(limiter.check(1_000, 500.0, 0.0), Some(300.0));
+    }
+
+    #[test]
+    fn slivers_under_one_percent_are_deferred() {
+        let mut limiter = limiter();
+        limiter.allow(1_000, 995.0);
+        assert_eq!(limiter.check(1_000, 1_000.0, 0.0), None);
+        assert_eq!(limiter.check(1_000, 400.0, 0.0), Some(5.0));
+    }
+
+    #[test]
+    fn volume_and_fills_leave_the_window() {
+        let mut limiter = limiter();
+        assert_eq!(limiter.allow(1_000, 1_000.0), Some(1_000.0));
+        limiter.observe(30_000, 15_000.0);
+        limiter.observe(62_000, 20_000.0);
+
+        // Only the $5,000 traded since t = 30s counts, and the fill at t = 1s has aged out
+        assert_eq!(limiter.market_volume(), 5_000.0);
+        assert_eq!(limiter.allow(62_000, 1_000.0), Some(500.0));
+        assert_eq!(limiter.stats().market_usd, 20_000.0);
+    }
+
+    #[test]
+    fn samples_no_newer_than_the_last_are_ignored() {
+        let mut limiter = limiter();
+        limiter.observe(1_000, 12_000.0);
+        limiter.observe(500, 15_000.0);
+        assert_eq!(limiter.market_volume(), 10_000.0);
+        assert_eq!(limiter.stats().market_usd, 10_000.0);
+    }
+}
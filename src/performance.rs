@@ -0,0 +1,133 @@
+use crate::amount::Exact;
+use serde::{Deserialize, Serialize};
+
+const MILLIS_PER_YEAR: f64 = 365.0 * 24.0 * 3600.0 * 1000.0;
+
+/// Risk-adjusted performance built up from trade outcomes and the mark-to-market equity series
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PerformanceStats {
+    pub winning_trades: u32,
+    pub losing_trades: u32,
+    /// Sum of net PnL over winning trades
+    pub gross_profit: f64,
+    /// Sum of net PnL over losing trades, as a positive amount
+    pub gross_loss: f64,
+    /// Equity change per mark interval: count, mean and sum of squared deviations (Welford)
+    pub intervals: u32,
+    pub mean_return: f64,
+    m2_return: f64,
+    /// Mean spacing of the equity series the returns are taken over. Marks follow feed time, so
+    /// they land at irregular gaps of at least the mark interval.
+    pub interval_ms: i64,
+    first_at: Option<i64>,
+    pub peak_equity: f64,
+    peak_at: Option<i64>,
+    last_equity: Option<f64>,
+    /// Largest fall from a running equity peak
+    pub max_drawdown: f64,
+    /// Longest time spent below a previous equity peak
+    pub max_drawdown_ms: i64,
+    /// Exact sums behind `gross_profit` and `gross_loss`
+    #[serde(skip)]
+    exact: [Exact; 2],
+}
+
+impl PerformanceStats {
+    pub fn record_trade(&mut self, pnl: f64) {
+        if pnl > 0.0 {
+            self.winning_trades += 1;
+            self.exact[0].add(&mut self.gross_profit, pnl);
+        } else if pnl < 0.0 {
+            self.losing_trades += 1;
+            self.exact[1].add(&mut self.gross_loss, -pnl);
+        }
+    }
+
+    /// Forget the equity series, to rebuild it from a revised one
+    pub fn reset_equity(&mut self) {
+        *self = Self {
+            winning_trades: self.winning_trades,
+            losing_trades: self.losing_trades,
+            gross_profit: self.gross_profit,
+            gross_loss: self.gross_loss,
+            exact: self.exact,
+            ..Self::default()
+        };
+    }
+
+    /// Add the next point of the equity series
+    pub fn record_equity(&mut self, timestamp: i64, equity: f64) {
+        let first_at = *self.first_at.get_or_insert(timestamp);
+        if let Some(previous) = self.last_equity {
+            let change = equity - previous;
+            self.intervals += 1;
+            let delta = change - self.mean_return;
+            self.mean_return += delta / self.intervals as f64;
+            self.m2_return += delta * (change - self.mean_return);
+            self.interval_ms = (timestamp - first_at) / self.intervals as i64;
+        }
+        self.last_equity = Some(equity);
+
+        let peak_at = *self.peak_at.get_or_insert(timestamp);
+        if equity >= self.peak_equity {
+            self.peak_equity = equity;
+            self.peak_at = Some(timestamp);
+        } else {
+            self.max_drawdown = self.max_drawdown.max(self.peak_equity - equity);
+            self.max_drawdown_ms = self.max_drawdown_ms.max(timestamp - peak_at);
+        }
+    }
+
+    /// Winning share of trades that made or lost money
+    pub fn hit_rate(&self) -> f64 {
+        let decided = self.winning_trades + self.losing_trades;
+        if decided > 0 {
+            self.winning_trades as f64 / decided as f64
+        } else {
+            0.0
+        }
+    }
+
+    pub fn avg_win(&self) -> f64 {
+        if self.winning_trades > 0 {
+            self.gross_profit / self.winning_trades as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Mean loss per losing trade, as a positive amount
+    pub fn avg_loss(&self) -> f64 {
+        if self.losing_trades > 0 {
+            self.gross_loss / self.losing_trades as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Gross profit over gross loss; `None` without any losing trade
+    pub fn profit_factor(&self) -> Option<f64> {
+        (self.gross_loss > 0.0).then(|| self.gross_profit / self.gross_loss)
+    }
+
+    /// Standard deviation of the per-interval equity changes
+    pub fn return_std_dev(&self) -> f64 {
+        if self.intervals > 1 {
+            (self.m2_return / (self.intervals - 1) as f64).sqrt()
+        } else {
+            0.0
+        }
+    }
+
+    /// Mean over standard deviation of the per-interval equity changes, scaled to a year of
+    /// round-the-clock trading at the series' mean spacing; `None` until there is variance to measure
+    pub fn sharpe_ratio(&self) -> Option<f64> {
+        let std_dev = self.return_std_dev();
+        if std_dev <= 0.0 || self.interval_ms <= 0 {
+            return None;
+        }
+        let periods_per_year = MILLIS_PER_YEAR / self.interval_ms as f64;
+        Some(self.mean_return / std_dev * periods_per_year.sqrt())
+    }
+}
@@ -0,0 +1,189 @@
+use crate::montecarlo::Distribution;
+use crate::trader::Trade;
+use serde::Serialize;
+use std::fmt;
+
+/// Default number of equal-width histogram bins
+pub const DEFAULT_PNL_BINS: usize = 20;
+
+/// Share of trades at each end of the distribution the tail stats cover
+const TAIL_SHARE: f64 = 0.05;
+
+/// Trades whose net PnL fell in `[lower, upper)` (the last bin includes `upper`)
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PnLBin {
+    pub lower: f64,
+    pub upper: f64,
+    pub trades: u32,
+    pub pnl: f64,
+}
+
+/// Shape of the per-trade net PnL of the market-making fills (hedges left out), so a few large
+/// losers can't hide behind a positive average
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PnLDistribution {
+    pub trades: u32,
+    pub summary: Distribution,
+    /// Sample skewness; negative when the left tail is the long one
+    pub skewness: f64,
+    /// Sample kurtosis less 3; positive when outliers are more common than under a normal distribution
+    pub excess_kurtosis: f64,
+    /// Mean PnL of the worst 5% of trades, i.e. the expected shortfall
+    pub worst_tail_avg: f64,
+    /// Mean PnL of the best 5% of trades
+    pub best_tail_avg: f64,
+    /// Share of all losses that came from the worst 5% of trades
+    pub worst_tail_loss_share: f64,
+    /// Share of all gains that came from the best 5% of trades
+    pub best_tail_gain_share: f64,
+    pub bins: Vec<PnLBin>,
+}
+
+impl PnLDistribution {
+    pub fn from_trades(trades: &[Trade], bins: usize) -> Self {
+        let pnls: Vec<f64> = trades.iter().filter(|t| !t.hedge).map(|t| t.pnl).collect();
+        Self::of(&pnls, bins)
+    }
+
+    pub fn of(pnls: &[f64], bins: usize) -> Self {
+        if pnls.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = pnls.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let summary = Distribution::of(&sorted);
+        let n = sorted.len() as f64;
+
+        // Population central moments, with the usual small-sample corrections below
+        let m2 = sorted.iter().map(|v| (v - summary.mean).powi(2)).sum::<f64>() / n;
+        let m3 = sorted.iter().map(|v| (v - summary.mean).powi(3)).sum::<f64>() / n;
+        let m4 = sorted.iter().map(|v| (v - summary.mean).powi(4)).sum::<f64>() / n;
+        let skewness = if n >= 3.0 && m2 > 0.0 {
+            m3 / m2.powf(1.5) * (n * (n - 1.0)).sqrt() / (n - 2.0)
+        } else {
+            0.0
+        };
+        let excess_kurtosis = if n >= 4.0 && m2 > 0.0 {
+            let g2 = m4 / (m2 * m2) - 3.0;
+            (n - 1.0) / ((n - 2.0) * (n - 3.0)) * ((n + 1.0) * g2 + 6.0)
+        } else {
+            0.0
+        };
+
+        let tail = ((n * TAIL_SHARE).ceil() as usize).max(1);
+        let worst = &sorted[..tail];
+        let best = &sorted[sorted.len() - tail..];
+        let losses: f64 = sorted.iter().filter(|&&v| v < 0.0).sum();
+        let gains: f64 = sorted.iter().filter(|&&v| v > 0.0).sum();
+        let worst_losses: f64 = worst.iter().filter(|&&v| v < 0.0).sum();
+        let best_gains: f64 = best.iter().filter(|&&v| v > 0.0).sum();
+
+        Self {
+            trades: sorted.len() as u32,
+            summary,
+            skewness,
+            excess_kurtosis,
+            worst_tail_avg: worst.iter().sum::<f64>() / tail as f64,
+            best_tail_avg: best.iter().sum::<f64>() / tail as f64,
+            worst_tail_loss_share: if losses < 0.0 { worst_losses / losses } else { 0.0 },
+            best_tail_gain_share: if gains > 0.0 { best_gains / gains } else { 0.0 },
+            bins: histogram(&sorted, bins.max(1)),
+        }
+    }
+}
+
+/// Equal-width bins from the smallest to the largest value
+fn histogram(sorted: &[f64], bins: usize) -> Vec<PnLBin> {
+    let (min, max) = (sorted[0], sorted[sorted.len() - 1]);
+    let bins = if max > min { bins } else { 1 };
+    let width = (max - min) / bins as f64;
+    let mut histogram: Vec<PnLBin> = (0..bins)
+        .map(|i| PnLBin {
+            lower: min + width * i as f64,
+            upper: if i + 1 == bins { max } else { min + width * (i + 1) as f64 },
+            trades: 0,
+            pnl: 0.0,
+        })
+        .collect();
+    for &value in sorted {
+        let index = if width > 0.0 { (((value - min) / width) as usize).min(bins - 1) } else { 0 };
+        histogram[index].trades += 1;
+        histogram[index].pnl += value;
+    }
+    histogram
+}
+
+/// Tail and moment stats, then one bar per bin scaled to the fullest
+impl fmt::Display for PnLDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = &self.summary;
+        writeln!(f, "Per-Trade PnL Distribution ({} trades, hedges excluded):", self.trades)?;
+        writeln!(f, "  mean ${:.4} │ std ${:.4} │ skew {:+.2} │ excess kurtosis {:+.2}",
+            s.mean, s.std_dev, self.skewness, self.excess_kurtosis)?;
+        writeln!(f, "  min ${:.4} │ p5 ${:.4} │ median ${:.4} │ p95 ${:.4} │ max ${:.4}",
+            s.min, s.p5, s.median, s.p95, s.max)?;
+        writeln!(f, "  worst 5%: avg ${:.4}, {:.1}% of losses │ best 5%: avg ${:.4}, {:.1}% of gains",
+            self.worst_tail_avg, self.worst_tail_loss_share * 100.0,
+            self.best_tail_avg, self.best_tail_gain_share * 100.0)?;
+        let fullest = self.bins.iter().map(|b| b.trades).max().unwrap_or(0).max(1);
+        for bin in &self.bins {
+            // Any non-empty bin gets at least one block so sparse tails stay visible
+            let width = ((bin.trades as f64 / fullest as f64 * 40.0).round() as usize).max(usize::from(bin.trades > 0));
+            let bar = "█".repeat(width);
+            writeln!(f, "  {:>12.4} … {:>12.4} │ {:>5} │ {}", bin.lower, bin.upper, bin.trades, bar)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symmetric_sample_has_no_skew() {
+        let dist = PnLDistribution::of(&[3.0, 1.0, 5.0, 2.0, 4.0], 4);
+        assert_eq!(dist.trades, 5);
+        assert!((dist.summary.mean - 3.0).abs() < 1e-12);
+        assert!(dist.skewness.abs() < 1e-12);
+        // Matches the bias-corrected sample kurtosis of spreadsheets' KURT
+        assert!((dist.excess_kurtosis + 1.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn long_right_tail_skews_positive() {
+        let dist = PnLDistribution::of(&[1.0, 2.0, 3.0, 10.0], 4);
+        assert!((dist.skewness - 1.7636).abs() < 1e-3, "skewness {}", dist.skewness);
+        let dist = PnLDistribution::of(&[-10.0, 1.0, 2.0, 3.0], 4);
+        assert!(dist.skewness < -1.7);
+    }
+
+    #[test]
+    fn tails_take_the_worst_and_best_five_percent() {
+        let mut pnls: Vec<f64> = (1..=19).map(|v| v as f64).collect();
+        pnls.push(-10.0);
+        let dist = PnLDistribution::of(&pnls, DEFAULT_PNL_BINS);
+        assert_eq!((dist.worst_tail_avg, dist.best_tail_avg), (-10.0, 19.0));
+        assert_eq!(dist.worst_tail_loss_share, 1.0);
+        assert!((dist.best_tail_gain_share - 19.0 / 190.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn histogram_counts_every_trade_once() {
+        let pnls = [-2.0, -1.0, 0.0, 0.5, 1.0, 2.0];
+        let dist = PnLDistribution::of(&pnls, 4);
+        assert_eq!(dist.bins.len(), 4);
+        assert_eq!(dist.bins.iter().map(|b| b.trades).sum::<u32>(), 6);
+        assert_eq!((dist.bins[0].lower, dist.bins[3].upper), (-2.0, 2.0));
+        assert_eq!(dist.bins[3].trades, 2, "the top bin includes the maximum");
+    }
+
+    #[test]
+    fn degenerate_samples() {
+        assert_eq!(PnLDistribution::of(&[], 10).trades, 0);
+        let flat = PnLDistribution::of(&[1.5; 8], 10);
+        assert_eq!((flat.skewness, flat.excess_kurtosis), (0.0, 0.0));
+        assert_eq!(flat.bins.len(), 1);
+        assert_eq!(flat.bins[0].trades, 8);
+    }
+}
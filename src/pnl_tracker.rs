@@ -1,8 +1,76 @@
-use crate::trader::{Trade, TradeSide};
+use crate::aggregator::{AggregatedPrices, QuoteUpdate, Source};
+use crate::amount::{self, Exact};
+use crate::calibration::{FillCalibration, TapeRecording};
+use crate::export::HttpTradeExporter;
+use crate::fees::FeeBreakdown;
+use crate::funding::{FundingCost, FundingRates};
+use crate::heatmap::FillHeatmap;
+use crate::markout::{markout_stats, MarkoutBook, MarkoutStats, TradeMarkout};
+use crate::output;
+use crate::performance::PerformanceStats;
+use crate::pnl_distribution::{PnLDistribution, DEFAULT_PNL_BINS};
+use crate::position::{CostBasis, InventoryLedger, Position};
+use crate::say;
+use crate::quiet::QuietInterval;
+use crate::trader::{ExecutionAttempt, LiquidityRole, Trade, TradeSide};
+use crate::timezone::ReportTimezone;
+use crate::trade_history::{HistoryReader, TradeHistory};
+use crate::two_sided::{TwoSidedAttempt, TwoSidedStats};
+use crate::units::DisplayUnit;
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+use tracing::error;
 
-#[derive(Debug, Clone)]
+/// Default spacing of the points on the equity time series, in feed time
+pub const DEFAULT_MARK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Execution attempts kept for the fill heatmap, fill calibration and model calibration; older
+/// ones are dropped so a long session's memory stays flat
+pub const MAX_ATTEMPTS: usize = 100_000;
+
+/// Trading over one time bucket of the session
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PnLBucket {
+    /// Feed time the bucket starts at
+    pub start: i64,
+    pub trades: u32,
+    /// Net PnL of the trades in the bucket
+    pub pnl: f64,
+    /// Net trade PnL from the start of the session to the end of the bucket
+    pub cumulative_pnl: f64,
+    /// Marked equity at the last mark before the bucket ends, if any
+    pub equity: Option<f64>,
+}
+
+/// Session equity at one mark: realized PnL plus open inventory revalued at the mid, less carry
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct EquityPoint {
+    pub timestamp: i64,
+    pub mark_price: f64,
+    pub inventory_eth: f64,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    /// Inventory carry charged up to this mark
+    #[serde(default)]
+    pub funding_cost: f64,
+}
+
+impl EquityPoint {
+    pub fn equity(&self) -> f64 {
+        self.realized_pnl + self.unrealized_pnl - self.funding_cost
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PnLStats {
     pub total_pnl: f64,
     pub total_trades: u32,
@@ -12,6 +80,116 @@ pub struct PnLStats {
     pub sell_pnl: f64,
     pub total_notional: f64,
     pub avg_execution_prob: f64,
+    #[serde(default)]
+    pub maker_trades: u32,
+    #[serde(default)]
+    pub taker_trades: u32,
+    #[serde(default)]
+    pub total_fees: f64,
+    /// PnL before fees, gas and slippage; `total_pnl` is net
+    #[serde(default)]
+    pub gross_pnl: f64,
+    #[serde(default)]
+    pub fee_breakdown: FeeBreakdown,
+    /// Fills made below source quorum
+    #[serde(default)]
+    pub degraded_trades: u32,
+    /// Inventory hedges, included in the totals above
+    #[serde(default)]
+    pub hedge_trades: u32,
+    #[serde(default)]
+    pub hedge_pnl: f64,
+    #[serde(default)]
+    pub hedge_fees: f64,
+    /// Gross PnL lost between fill decisions and their confirmation under a fill latency
+    #[serde(default)]
+    pub adverse_selection: f64,
+    /// PnL locked in by closed round trips under `cost_basis`, net of all fees
+    #[serde(default)]
+    pub realized_pnl: f64,
+    /// Open inventory marked to `mark_price`
+    #[serde(default)]
+    pub unrealized_pnl: f64,
+    #[serde(default)]
+    pub inventory_eth: f64,
+    #[serde(default)]
+    pub avg_entry_price: f64,
+    /// Median mid the inventory was last marked at (0 before the first mark)
+    #[serde(default)]
+    pub mark_price: f64,
+    /// Carry charged on open inventory each mark interval, not included in `total_pnl`
+    #[serde(default)]
+    pub funding: FundingCost,
+    #[serde(default)]
+    pub cost_basis: CostBasis,
+    #[serde(default)]
+    pub performance: PerformanceStats,
+    /// Flat, tight spells found by quiet-market detection, in feed time
+    #[serde(default)]
+    pub quiet_intervals: Vec<QuietInterval>,
+    /// Fills and PnL per venue, in `Source::ALL` order
+    #[serde(default)]
+    pub venues: Vec<VenueAttribution>,
+    /// Mid move after each non-hedge fill, one entry per markout horizon
+    #[serde(default)]
+    pub markouts: Vec<MarkoutStats>,
+    /// Fill outcomes and round-trip capture of two-sided quotes
+    #[serde(default)]
+    pub two_sided: TwoSidedStats,
+    #[serde(skip)]
+    exact: ExactStats,
+}
+
+/// Exact sums behind the USD totals of `PnLStats`
+#[derive(Debug, Clone, Copy, Default)]
+struct ExactStats {
+    total_pnl: Exact,
+    buy_pnl: Exact,
+    sell_pnl: Exact,
+    total_notional: Exact,
+    total_fees: Exact,
+    gross_pnl: Exact,
+    venue_fee: Exact,
+    gas: Exact,
+    slippage: Exact,
+    hedge_pnl: Exact,
+    hedge_fees: Exact,
+    adverse_selection: Exact,
+}
+
+/// Fills priced off one venue and fills marked against its touch, with their net PnL. A routed
+/// fill counts once for each venue it was split across, with that venue's share of the PnL.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct VenueAttribution {
+    pub venue: Source,
+    pub fills: u32,
+    pub fill_notional_usd: f64,
+    pub fill_pnl: f64,
+    pub marks: u32,
+    pub mark_pnl: f64,
+    /// Exact sums behind `fill_notional_usd`, `fill_pnl` and `mark_pnl`
+    #[serde(skip)]
+    exact: [Exact; 3],
+}
+
+impl VenueAttribution {
+    fn new(venue: Source) -> Self {
+        Self {
+            venue,
+            fills: 0,
+            fill_notional_usd: 0.0,
+            fill_pnl: 0.0,
+            marks: 0,
+            mark_pnl: 0.0,
+            exact: Default::default(),
+        }
+    }
+}
+
+impl Default for PnLStats {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PnLStats {
@@ -25,9 +203,66 @@ impl PnLStats {
             sell_pnl: 0.0,
             total_notional: 0.0,
             avg_execution_prob: 0.0,
+            maker_trades: 0,
+            taker_trades: 0,
+            total_fees: 0.0,
+            gross_pnl: 0.0,
+            fee_breakdown: FeeBreakdown::default(),
+            degraded_trades: 0,
+            hedge_trades: 0,
+            hedge_pnl: 0.0,
+            hedge_fees: 0.0,
+            adverse_selection: 0.0,
+            realized_pnl: 0.0,
+            unrealized_pnl: 0.0,
+            inventory_eth: 0.0,
+            avg_entry_price: 0.0,
+            mark_price: 0.0,
+            funding: FundingCost::default(),
+            cost_basis: CostBasis::default(),
+            performance: PerformanceStats::default(),
+            quiet_intervals: Vec::new(),
+            venues: Vec::new(),
+            markouts: markout_stats(),
+            two_sided: TwoSidedStats::default(),
+            exact: ExactStats::default(),
         }
     }
 
+    /// Attribution for `venue`, added in `Source::ALL` order on first use
+    fn venue_mut(&mut self, venue: Source) -> &mut VenueAttribution {
+        let rank = |source: Source| Source::ALL.iter().position(|&s| s == source);
+        let idx = match self.venues.iter().position(|v| v.venue == venue) {
+            Some(idx) => idx,
+            None => {
+                let idx = self.venues.iter().take_while(|v| rank(v.venue) < rank(venue)).count();
+                self.venues.insert(idx, VenueAttribution::new(venue));
+                idx
+            }
+        };
+        &mut self.venues[idx]
+    }
+
+    /// Credit a fill to the venues it was priced off and the venue it was marked against
+    fn attribute(&mut self, trade: &Trade) {
+        for (venue, share) in trade.fill_venues() {
+            let entry = self.venue_mut(venue);
+            entry.fills += 1;
+            entry.exact[0].add(&mut entry.fill_notional_usd, amount::value_of(trade.notional_usd, 0.0, share));
+            entry.exact[1].add(&mut entry.fill_pnl, amount::value_of(trade.pnl, 0.0, share));
+        }
+        if let Some(venue) = trade.mark_venue {
+            let entry = self.venue_mut(venue);
+            entry.marks += 1;
+            entry.exact[2].add(&mut entry.mark_pnl, trade.pnl);
+        }
+    }
+
+    /// Net PnL of market-making fills alone, excluding hedges
+    pub fn market_making_pnl(&self) -> f64 {
+        self.total_pnl - self.hedge_pnl
+    }
+
     pub fn avg_pnl_per_trade(&self) -> f64 {
         if self.total_trades > 0 {
             self.total_pnl / self.total_trades as f64
@@ -36,6 +271,16 @@ impl PnLStats {
         }
     }
 
+    /// Total time quoting was paused for quiet markets
+    pub fn quiet_paused_time(&self) -> Duration {
+        self.quiet_intervals.iter().filter(|i| i.paused).map(QuietInterval::duration).sum()
+    }
+
+    /// Realized plus unrealized, less carry: what the session is worth at the last mark
+    pub fn marked_pnl(&self) -> f64 {
+        self.realized_pnl + self.unrealized_pnl - self.funding.total()
+    }
+
     pub fn pnl_per_notional_bps(&self) -> f64 {
         if self.total_notional > 0.0 {
             (self.total_pnl / self.total_notional) * 10000.0
@@ -43,94 +288,1020 @@ impl PnLStats {
             0.0
         }
     }
+
+    pub fn print_summary(&self) {
+        print!("{}", self);
+    }
 }
 
+impl std::fmt::Display for PnLStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let stats = self;
+
+        writeln!(f, "\n╔════════════════════════════════════════════════════════════════════╗")?;
+        writeln!(f, "║                    TRADING SESSION SUMMARY                         ║")?;
+        writeln!(f, "╠════════════════════════════════════════════════════════════════════╣")?;
+        writeln!(f, "║ Total Trades:          {:>8}                                    ║", stats.total_trades)?;
+        writeln!(f, "║   - Buy Trades:        {:>8}                                    ║", stats.buy_trades)?;
+        writeln!(f, "║   - Sell Trades:       {:>8}                                    ║", stats.sell_trades)?;
+        writeln!(f, "║                                                                    ║")?;
+        writeln!(f, "║ Gross PnL:             ${:>12.2}                             ║", stats.gross_pnl)?;
+        writeln!(f, "║ Total PnL (net):       ${:>12.2}                             ║", stats.total_pnl)?;
+        if stats.adverse_selection != 0.0 {
+            writeln!(f, "║   - Adverse Selection: ${:>12.2}   (from fill latency)       ║", -stats.adverse_selection)?;
+        }
+        writeln!(f, "║   - Buy PnL:           ${:>12.2}                             ║", stats.buy_pnl)?;
+        writeln!(f, "║   - Sell PnL:          ${:>12.2}                             ║", stats.sell_pnl)?;
+        if stats.hedge_trades > 0 {
+            writeln!(f, "║   - Market Making:     ${:>12.2}                             ║", stats.market_making_pnl())?;
+            writeln!(f, "║   - Hedging:           ${:>12.2}                             ║", stats.hedge_pnl)?;
+            writeln!(f, "║ Hedge Trades:          {:<42}║", format!("{:>8}   (fees ${:.2})", stats.hedge_trades, stats.hedge_fees))?;
+        }
+        writeln!(f, "║                                                                    ║")?;
+        writeln!(f, "║ Realized PnL:          ${:>12.2}{:<29}║", stats.realized_pnl, format!("   ({})", stats.cost_basis.label()))?;
+        let marked_at = if stats.mark_price > 0.0 {
+            format!("   ({:+.4} ETH @ ${:.2})", stats.inventory_eth, stats.mark_price)
+        } else {
+            format!("   ({:+.4} ETH, unmarked)", stats.inventory_eth)
+        };
+        writeln!(f, "║ Unrealized PnL:        ${:>12.2}{:<29}║", stats.unrealized_pnl, marked_at)?;
+        if stats.funding.total() != 0.0 {
+            writeln!(f, "║ Inventory Carry:       ${:>12.2}{:<29}║", -stats.funding.total(),
+                format!("   (L ${:.2} / S ${:.2})", stats.funding.long_usd, stats.funding.short_usd))?;
+            writeln!(f, "║ Marked, Net of Carry:  ${:>12.2}                             ║", stats.marked_pnl())?;
+        } else {
+            writeln!(f, "║ Realized + Unrealized: ${:>12.2}                             ║", stats.marked_pnl())?;
+        }
+        writeln!(f, "║                                                                    ║")?;
+        let perf = &stats.performance;
+        let optional = |value: Option<f64>| value.map_or("-".to_string(), |v| format!("{:.2}", v));
+        writeln!(f, "║ Sharpe (annualized):   {:>8}{:<36}║", optional(perf.sharpe_ratio()),
+            format!("   ({} intervals, {:.1}s apart)", perf.intervals, perf.interval_ms as f64 / 1000.0))?;
+        writeln!(f, "║ Max Drawdown:          ${:>12.2}{:<29}║", perf.max_drawdown,
+            format!("   (longest {:.1} min)", perf.max_drawdown_ms as f64 / 60_000.0))?;
+        writeln!(f, "║ Hit Rate:              {:>7.1}%{:<36}║", perf.hit_rate() * 100.0,
+            format!("   ({} won / {} lost)", perf.winning_trades, perf.losing_trades))?;
+        writeln!(f, "║ Avg Win / Avg Loss:    ${:>12.2}{:<29}║", perf.avg_win(), format!(" / ${:.2}", perf.avg_loss()))?;
+        writeln!(f, "║ Profit Factor:         {:>8}                                    ║", optional(perf.profit_factor()))?;
+        writeln!(f, "║                                                                    ║")?;
+        writeln!(f, "║ Maker / Taker:         {:>8} / {:<8}                         ║", stats.maker_trades, stats.taker_trades)?;
+        let two_sided = &stats.two_sided;
+        if two_sided.quotes > 0 {
+            writeln!(f, "║ Two-Sided Quotes:      {:<42}║", format!("{:>8}   (both {} / one {} / none {})", two_sided.quotes,
+                two_sided.both_filled, two_sided.one_side_filled, two_sided.quotes - two_sided.both_filled - two_sided.one_side_filled))?;
+            writeln!(f, "║ Round-Trip Capture:    {:<42}║", format!("${:>12.2}   ({} trips, {:.4} ETH)", two_sided.capture_usd,
+                two_sided.round_trips, two_sided.round_trip_eth))?;
+        }
+        writeln!(f, "║ Fees Paid:             ${:>12.2}                             ║", stats.total_fees)?;
+        writeln!(f, "║   - Venue Fees:        ${:>12.2}                             ║", stats.fee_breakdown.venue_fee_usd)?;
+        writeln!(f, "║   - Gas / Protocol:    ${:>12.2}                             ║", stats.fee_breakdown.gas_usd)?;
+        writeln!(f, "║   - Slippage:          ${:>12.2}                             ║", stats.fee_breakdown.slippage_usd)?;
+        if !stats.quiet_intervals.is_empty() {
+            let total: Duration = stats.quiet_intervals.iter().map(QuietInterval::duration).sum();
+            let paused = stats.quiet_paused_time();
+            writeln!(f, "║ Quiet Markets:         {:>8}{:<36}║", stats.quiet_intervals.len(),
+                format!("   ({:.1} min, {:.1} paused)", total.as_secs_f64() / 60.0, paused.as_secs_f64() / 60.0))?;
+        }
+        if stats.degraded_trades > 0 {
+            writeln!(f, "║ Degraded Fills:        {:>8}   (below source quorum)           ║", stats.degraded_trades)?;
+        }
+        writeln!(f, "║                                                                    ║")?;
+        writeln!(f, "║ Avg PnL per Trade:     ${:>12.2}                             ║", stats.avg_pnl_per_trade())?;
+        writeln!(f, "║ Total Notional:        ${:>12.2}                             ║", stats.total_notional)?;
+        writeln!(f, "║ PnL / Notional:        {:>8.2} bps                            ║", stats.pnl_per_notional_bps())?;
+        writeln!(f, "║ Avg Execution Prob:    {:>7.1}%                                 ║", stats.avg_execution_prob * 100.0)?;
+        writeln!(f, "╚════════════════════════════════════════════════════════════════════╝\n")?;
+        Ok(())
+    }
+}
+
+/// Predicted vs realized fill rate for one execution model
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelCalibration {
+    pub model: String,
+    pub attempts: u32,
+    pub mean_predicted: f64,
+    pub realized_rate: f64,
+    /// Mean squared error between predicted probability and the 0/1 outcome
+    pub brier_score: f64,
+}
+
+/// Aggregate of the child fills routed to one venue
+#[derive(Debug, Clone, Serialize)]
+pub struct VenueRouting {
+    pub venue: Source,
+    pub fills: u32,
+    pub notional_usd: f64,
+    pub fees_usd: f64,
+    /// Exact sums behind `notional_usd` and `fees_usd`
+    #[serde(skip)]
+    exact: [Exact; 2],
+}
+
+/// Equity time series and the feed time of the latest mark
+#[derive(Debug, Default)]
+struct MarkState {
+    points: Vec<EquityPoint>,
+    /// (feed time, net PnL) of every recorded trade
+    trades: Vec<(i64, f64)>,
+    last_mark: i64,
+    closed: bool,
+    /// Carry accrued since the last equity point, booked with the next one
+    funding: FundingCost,
+    /// Time carry has been charged up to: the later of the last mark and the last fill
+    accrued_at: i64,
+    markouts: MarkoutBook,
+}
+
+/// Recorded trades, plus the breakdowns kept current as each one lands so the summary never has to
+/// read spilled trades back
+#[derive(Debug, Default)]
+struct TradeLog {
+    history: TradeHistory,
+    position: Position,
+    daily: Vec<(NaiveDate, f64, u32)>,
+    /// Exact sum behind each day's PnL in `daily`
+    daily_exact: Vec<Exact>,
+    routing: Vec<VenueRouting>,
+    /// Net PnL in each display unit, in `display_units` order, with its exact sum
+    native_pnl: Vec<(f64, Exact)>,
+}
+
+impl TradeLog {
+    fn record(&mut self, trade: &Trade, timezone: &ReportTimezone, units: &[DisplayUnit]) {
+        self.position.apply(trade);
+        if let Some(date) = timezone.date_of(trade.timestamp) {
+            if self.daily.last().is_none_or(|&(day, _, _)| day != date) {
+                self.daily.push((date, 0.0, 0));
+                self.daily_exact.push(Exact::default());
+            }
+            if let (Some((_, pnl, count)), Some(exact)) = (self.daily.last_mut(), self.daily_exact.last_mut()) {
+                exact.add(pnl, trade.pnl);
+                *count += 1;
+            }
+        }
+        for fill in &trade.fills {
+            let idx = match self.routing.iter().position(|r| r.venue == fill.venue) {
+                Some(idx) => idx,
+                None => {
+                    self.routing.push(VenueRouting {
+                        venue: fill.venue,
+                        fills: 0,
+                        notional_usd: 0.0,
+                        fees_usd: 0.0,
+                        exact: Default::default(),
+                    });
+                    self.routing.len() - 1
+                }
+            };
+            let entry = &mut self.routing[idx];
+            entry.fills += 1;
+            entry.exact[0].add(&mut entry.notional_usd, fill.notional_usd);
+            entry.exact[1].add(&mut entry.fees_usd, fill.fee_usd);
+        }
+        self.native_pnl.resize(units.len(), (0.0, Exact::default()));
+        for ((total, exact), &unit) in self.native_pnl.iter_mut().zip(units) {
+            exact.add(total, trade.conversion.convert(trade.pnl, unit).unwrap_or(0.0));
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct PnLTracker {
     stats: Arc<RwLock<PnLStats>>,
-    trades: Arc<RwLock<Vec<Trade>>>,
+    trades: Arc<RwLock<TradeLog>>,
+    ledger: Arc<RwLock<InventoryLedger>>,
+    marks: Arc<RwLock<MarkState>>,
+    mark_interval: Duration,
+    funding: Option<FundingRates>,
+    /// The latest `MAX_ATTEMPTS` attempts, oldest first
+    attempts: Arc<RwLock<VecDeque<ExecutionAttempt>>>,
+    display_units: Vec<DisplayUnit>,
+    timezone: ReportTimezone,
+    exporter: Option<Arc<HttpTradeExporter>>,
+    /// Every recorded trade, for streaming subscribers
+    trade_feed: broadcast::Sender<Trade>,
+}
+
+impl Default for PnLTracker {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PnLTracker {
     pub fn new() -> Self {
         Self {
             stats: Arc::new(RwLock::new(PnLStats::new())),
-            trades: Arc::new(RwLock::new(Vec::new())),
+            trades: Arc::new(RwLock::new(TradeLog::default())),
+            ledger: Arc::new(RwLock::new(InventoryLedger::default())),
+            marks: Arc::new(RwLock::new(MarkState::default())),
+            mark_interval: DEFAULT_MARK_INTERVAL,
+            funding: None,
+            attempts: Arc::new(RwLock::new(VecDeque::new())),
+            display_units: Vec::new(),
+            timezone: ReportTimezone::default(),
+            exporter: None,
+            trade_feed: broadcast::channel(1024).0,
         }
     }
 
+    /// Push-based stream of every trade as it is recorded
+    pub fn subscribe_trades(&self) -> broadcast::Receiver<Trade> {
+        self.trade_feed.subscribe()
+    }
+
+    /// Timezone for trade timestamps and daily boundaries in reports
+    pub fn with_timezone(mut self, timezone: ReportTimezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// How closing fills realize PnL against open inventory (average cost by default)
+    pub fn with_cost_basis(mut self, basis: CostBasis) -> Self {
+        self.ledger = Arc::new(RwLock::new(InventoryLedger::new(basis)));
+        self.stats = Arc::new(RwLock::new(PnLStats {
+            cost_basis: basis,
+            ..PnLStats::new()
+        }));
+        self
+    }
+
+    /// Feed time between points on the equity time series
+    pub fn with_mark_interval(mut self, interval: Duration) -> Self {
+        self.mark_interval = interval;
+        self
+    }
+
+    pub fn mark_interval(&self) -> Duration {
+        self.mark_interval
+    }
+
+    /// Charge carry on open inventory at `rates`, booked every mark interval
+    pub fn with_funding(mut self, rates: FundingRates) -> Self {
+        self.funding = Some(rates);
+        self
+    }
+
+    pub fn funding(&self) -> Option<FundingRates> {
+        self.funding
+    }
+
+    /// Where recorded trades are kept; every trade stays in memory unless `history` spills
+    pub fn with_history(mut self, history: TradeHistory) -> Self {
+        self.trades = Arc::new(RwLock::new(TradeLog {
+            history,
+            ..TradeLog::default()
+        }));
+        self
+    }
+
+    /// Stream every recorded trade to an HTTP endpoint
+    pub fn with_exporter(mut self, exporter: Arc<HttpTradeExporter>) -> Self {
+        self.exporter = Some(exporter);
+        self
+    }
+
+    pub fn timezone(&self) -> ReportTimezone {
+        self.timezone
+    }
+
+    /// Also show PnL in these native units, converted at each trade's own rates
+    pub fn with_display_units(mut self, units: Vec<DisplayUnit>) -> Self {
+        self.display_units = units;
+        self
+    }
+
     pub async fn record_trade(&self, trade: Trade) {
+        if let Some(exporter) = &self.exporter {
+            exporter.send(&trade);
+        }
+        let _ = self.trade_feed.send(trade.clone());
+        if let Some(decided_at) = trade.decided_at {
+            // A fill confirmed after the fill latency: its attempt counts as executed from now on
+            let mut attempts = self.attempts.write().await;
+            let decided = attempts.iter_mut().rev().find(|a| a.timestamp == decided_at && a.side == trade.side && a.trade.is_none());
+            if let Some(attempt) = decided {
+                attempt.trade = Some(trade.clone());
+                attempt.pending = false;
+            }
+        }
         let mut stats = self.stats.write().await;
         let mut trades = self.trades.write().await;
+        let mut ledger = self.ledger.write().await;
+        {
+            // Feed time of the latest mark, so the trade lands on the equity series' own timeline
+            let mut marks = self.marks.write().await;
+            if let (Some(rates), true) = (&self.funding, marks.accrued_at > 0) {
+                // Carry on the inventory held up to this fill, at the last mark, before the fill changes it
+                let filled_at = trade.timestamp.max(marks.accrued_at);
+                let elapsed_ms = filled_at - marks.accrued_at;
+                marks.funding.accrue(rates, ledger.net_eth(), stats.mark_price, elapsed_ms);
+                marks.accrued_at = filled_at;
+            }
+            let at = if marks.last_mark > 0 { marks.last_mark } else { trade.timestamp };
+            marks.trades.push((at, trade.pnl));
+            if !trade.hedge {
+                marks.markouts.push(TradeMarkout::new(&trade));
+            }
+        }
+
+        ledger.apply(&trade);
+        stats.realized_pnl = ledger.realized_pnl();
+        stats.inventory_eth = ledger.net_eth();
+        stats.avg_entry_price = ledger.avg_entry_price();
+        if stats.mark_price > 0.0 {
+            stats.unrealized_pnl = ledger.unrealized_pnl(stats.mark_price);
+        }
 
-        stats.total_pnl += trade.pnl;
+        let stats = &mut *stats;
+        let exact = &mut stats.exact;
+        exact.total_pnl.add(&mut stats.total_pnl, trade.pnl);
+        stats.performance.record_trade(trade.pnl);
         stats.total_trades += 1;
-        stats.total_notional += trade.notional_usd;
-        
+        exact.total_notional.add(&mut stats.total_notional, trade.notional_usd);
+        exact.total_fees.add(&mut stats.total_fees, trade.fees_usd);
+        exact.gross_pnl.add(&mut stats.gross_pnl, trade.gross_pnl);
+        exact.adverse_selection.add(&mut stats.adverse_selection, trade.adverse_selection_usd);
+        exact.venue_fee.add(&mut stats.fee_breakdown.venue_fee_usd, trade.fees.venue_fee_usd);
+        exact.gas.add(&mut stats.fee_breakdown.gas_usd, trade.fees.gas_usd);
+        exact.slippage.add(&mut stats.fee_breakdown.slippage_usd, trade.fees.slippage_usd);
+        match trade.role {
+            LiquidityRole::Maker => stats.maker_trades += 1,
+            LiquidityRole::Taker => stats.taker_trades += 1,
+        }
+        if trade.degraded {
+            stats.degraded_trades += 1;
+        }
+        if trade.hedge {
+            stats.hedge_trades += 1;
+            exact.hedge_pnl.add(&mut stats.hedge_pnl, trade.pnl);
+            exact.hedge_fees.add(&mut stats.hedge_fees, trade.fees_usd);
+        }
+
         match trade.side {
             TradeSide::Buy => {
                 stats.buy_trades += 1;
-                stats.buy_pnl += trade.pnl;
+                exact.buy_pnl.add(&mut stats.buy_pnl, trade.pnl);
             }
             TradeSide::Sell => {
                 stats.sell_trades += 1;
-                stats.sell_pnl += trade.pnl;
+                exact.sell_pnl.add(&mut stats.sell_pnl, trade.pnl);
             }
         }
+        stats.attribute(&trade);
 
         // Update rolling average execution probability
         stats.avg_execution_prob = 
             (stats.avg_execution_prob * (stats.total_trades - 1) as f64 + trade.execution_prob) 
             / stats.total_trades as f64;
 
-        trades.push(trade);
+        trades.record(&trade, &self.timezone, &self.display_units);
+        if let Err(e) = trades.history.push(trade) {
+            error!("{:#}, keeping every trade in memory from here on", e);
+        }
+    }
+
+    /// Fold every trade into `init`, oldest first. With trades on disk, the history is read from
+    /// a blocking task without holding the tracker's lock.
+    async fn fold_trades<T: Send + 'static>(&self, init: T, mut visit: impl FnMut(&mut T, &Trade) + Send + 'static) -> T {
+        let history = {
+            let log = self.trades.read().await;
+            if log.history.on_disk() == 0 {
+                let mut acc = init;
+                log.history.in_memory().for_each(|t| visit(&mut acc, t));
+                return acc;
+            }
+            log.history.reader()
+        };
+        read_history(history, init, visit).await
+    }
+
+    /// The trade history as it stands, to read from a blocking task without the tracker's lock
+    pub async fn trade_history(&self) -> HistoryReader {
+        self.trades.read().await.history.reader()
+    }
+
+    /// Write out spilled trades still waiting for a full batch, so the spill file holds them all
+    pub async fn flush_trades(&self) -> Result<()> {
+        self.trades.write().await.history.flush()
+    }
+
+    /// Re-mark open inventory at the median mid of `prices`, updating unrealized PnL. Adds a point
+    /// to the equity time series once `mark_interval` of feed time has passed since the last one.
+    pub async fn mark_to_market(&self, prices: &AggregatedPrices) {
+        self.mark(prices, false).await;
+    }
+
+    /// Final mark of the session, always closing the equity time series with a point
+    pub async fn close_marks(&self, prices: &AggregatedPrices) {
+        self.mark(prices, true).await;
+    }
+
+    async fn mark(&self, prices: &AggregatedPrices, close: bool) {
+        let Some(mid) = prices.median_mid() else {
+            return;
+        };
+        let Some(timestamp) = prices.quotes().map(|q| q.timestamp).max() else {
+            return;
+        };
+        let mut stats = self.stats.write().await;
+        let ledger = self.ledger.read().await;
+        let mut marks = self.marks.write().await;
+        // Marks arrive from the trading loop and the background task; never step back to an older
+        // snapshot, and leave the closing mark as the last word
+        if marks.closed || timestamp < marks.last_mark {
+            return;
+        }
+        if let (Some(rates), true) = (&self.funding, marks.accrued_at > 0) {
+            // Inventory as it stands, held since the last fill or mark, whichever came later
+            let elapsed_ms = timestamp - marks.accrued_at;
+            marks.funding.accrue(rates, ledger.net_eth(), mid, elapsed_ms);
+        }
+        marks.accrued_at = marks.accrued_at.max(timestamp);
+        marks.last_mark = timestamp;
+        marks.closed = close;
+        marks.markouts.observe(timestamp, mid, &mut stats.markouts);
+        stats.mark_price = mid;
+        stats.unrealized_pnl = ledger.unrealized_pnl(mid);
+
+        let interval_ms = self.mark_interval.as_millis() as i64;
+        if close && marks.points.last().is_some_and(|last| last.timestamp == timestamp) {
+            // Trades recorded since that point at the same feed time belong in the closing one
+            marks.points.pop();
+            stats.performance.reset_equity();
+            for point in &marks.points {
+                stats.performance.record_equity(point.timestamp, point.equity());
+            }
+        }
+        let due = marks.points.last().is_none_or(|last| timestamp - last.timestamp >= interval_ms || close);
+        if due {
+            stats.funding += std::mem::take(&mut marks.funding);
+            let point = EquityPoint {
+                timestamp,
+                mark_price: mid,
+                inventory_eth: stats.inventory_eth,
+                realized_pnl: stats.realized_pnl,
+                unrealized_pnl: stats.unrealized_pnl,
+                funding_cost: stats.funding.total(),
+            };
+            stats.performance.record_equity(timestamp, point.equity());
+            marks.points.push(point);
+        }
+    }
+
+    /// Net PnL and trade counts per `bucket` of feed time over the session, with the equity at the
+    /// end of each, to show whether PnL accrues steadily or from a few moments of the run
+    pub async fn equity_curve(&self, bucket: Duration) -> Vec<PnLBucket> {
+        let marks = self.marks.read().await;
+        let width = (bucket.as_millis() as i64).max(1);
+        let times = marks.trades.iter().map(|&(at, _)| at).chain(marks.points.iter().map(|p| p.timestamp));
+        let (Some(first), Some(last)) = (times.clone().min(), times.max()) else {
+            return Vec::new();
+        };
+
+        let origin = first.div_euclid(width) * width;
+        let mut buckets: Vec<PnLBucket> = (0..=(last - origin) / width)
+            .map(|i| PnLBucket {
+                start: origin + i * width,
+                trades: 0,
+                pnl: 0.0,
+                cumulative_pnl: 0.0,
+                equity: None,
+            })
+            .collect();
+        let mut exact = vec![Exact::default(); buckets.len()];
+        for &(at, pnl) in &marks.trades {
+            let index = ((at - origin) / width) as usize;
+            let bucket = &mut buckets[index];
+            bucket.trades += 1;
+            exact[index].add(&mut bucket.pnl, pnl);
+        }
+        for point in &marks.points {
+            buckets[((point.timestamp - origin) / width) as usize].equity = Some(point.equity());
+        }
+        let (mut cumulative, mut cumulative_exact, mut equity) = (0.0, Exact::default(), None);
+        for (bucket, &bucket_exact) in buckets.iter_mut().zip(&exact) {
+            cumulative_exact.merge(&mut cumulative, bucket_exact, bucket.pnl);
+            bucket.cumulative_pnl = cumulative;
+            // Carry the last mark through buckets that had none
+            equity = bucket.equity.or(equity);
+            bucket.equity = equity;
+        }
+        buckets
+    }
+
+    /// Change in equity between consecutive points of the equity series
+    pub async fn returns(&self) -> Vec<f64> {
+        let marks = self.marks.read().await;
+        marks.points.windows(2).map(|pair| pair[1].equity() - pair[0].equity()).collect()
+    }
+
+    /// Equity marked every `mark_interval` over the session so far
+    pub async fn equity_series(&self) -> Vec<EquityPoint> {
+        self.marks.read().await.points.clone()
+    }
+
+    /// Mid at 1s, 5s and 30s of feed time after each non-hedge fill, by trade id: every fill still
+    /// waiting for a horizon and the latest `MAX_RESOLVED_MARKOUTS` of the rest
+    pub async fn markouts(&self) -> Vec<TradeMarkout> {
+        self.marks.read().await.markouts.all()
+    }
+
+    pub async fn markout(&self, trade_id: u64) -> Option<TradeMarkout> {
+        self.marks.read().await.markouts.get(trade_id).cloned()
+    }
+
+    /// Count a two-sided quote's fill outcome, and its round trip once both sides' trades are booked
+    pub async fn record_two_sided(&self, attempt: &TwoSidedAttempt) {
+        self.stats.write().await.two_sided.record(attempt);
+    }
+
+    /// Keep a finished quiet-market spell in the session stats
+    pub async fn record_quiet_interval(&self, interval: QuietInterval) {
+        self.stats.write().await.quiet_intervals.push(interval);
+    }
+
+    /// Keep an execution attempt (filled or not) for model comparison, dropping the oldest past
+    /// `MAX_ATTEMPTS`
+    pub async fn record_attempt(&self, attempt: &ExecutionAttempt) {
+        let mut attempts = self.attempts.write().await;
+        if attempts.len() == MAX_ATTEMPTS {
+            attempts.pop_front();
+        }
+        attempts.push_back(attempt.clone());
+    }
+
+    /// The latest `MAX_ATTEMPTS` attempts, oldest first
+    pub async fn get_attempts(&self) -> Vec<ExecutionAttempt> {
+        self.attempts.read().await.iter().cloned().collect()
+    }
+
+    /// Write every trade as a CSV row to `path`, and the stats as `key,value` rows next to it
+    /// in `<stem>-stats.csv`. Child fills are counted; `export_json` keeps them in full.
+    pub async fn export_csv(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref().to_path_buf();
+        let history = self.trade_history().await;
+        let trades_path = path.clone();
+        let written = tokio::task::spawn_blocking(move || -> Result<()> {
+            let file = File::create(&trades_path)?;
+            let mut out = BufWriter::new(file);
+            writeln!(out, "{}", TRADE_CSV_HEADER)?;
+            history.try_for_each(|trade| Ok(writeln!(out, "{}", trade_csv_row(trade))?))?;
+            Ok(out.flush()?)
+        });
+        join_blocking(written.await).with_context(|| format!("failed to write {}", path.display()))?;
+
+        let stats = serde_json::to_value(self.get_stats().await)?;
+        let mut rows = String::from("key,value\n");
+        for (key, value) in flatten_json("", &stats) {
+            rows.push_str(&format!("{},{}\n", key, csv_field(&value)));
+        }
+        let stats_path = path.with_file_name(format!(
+            "{}-stats.csv",
+            path.file_stem().and_then(|s| s.to_str()).unwrap_or("trades")
+        ));
+        std::fs::write(&stats_path, rows).with_context(|| format!("failed to write {}", stats_path.display()))
+    }
+
+    /// Write `{"stats": ..., "trades": [...], "markouts": [...]}` with every field of every trade to `path`
+    /// Trades are streamed to the file one per line rather than collected first.
+    pub async fn export_json(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref().to_path_buf();
+        let stats = serde_json::to_string_pretty(&self.get_stats().await)?;
+        let markouts = serde_json::to_string_pretty(&self.markouts().await)?;
+        let history = self.trade_history().await;
+        let export_path = path.clone();
+        let written = tokio::task::spawn_blocking(move || -> Result<()> {
+            let mut out = BufWriter::new(File::create(&export_path)?);
+            write!(out, "{{\n\"stats\": {},\n\"trades\": [", stats)?;
+            let mut first = true;
+            history.try_for_each(|trade| {
+                out.write_all(if first { b"\n" } else { b",\n" })?;
+                serde_json::to_writer(&mut out, trade)?;
+                first = false;
+                Ok(())
+            })?;
+            write!(out, "\n],\n\"markouts\": {}\n}}\n", markouts)?;
+            Ok(out.flush()?)
+        });
+        join_blocking(written.await).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Fills by distance from the touch and hour of day, in the report timezone
+    pub async fn fill_heatmap(&self, bucket_bps: f64) -> FillHeatmap {
+        FillHeatmap::from_attempts(self.attempts.read().await.iter(), bucket_bps, &self.timezone)
+    }
+
+    /// The model's fill probabilities for every maker quote, checked against `tape` trading through them
+    pub async fn fill_calibration(&self, tape: &TapeRecording, window: Duration, observed_until: i64, bucket_bps: f64) -> FillCalibration {
+        FillCalibration::from_attempts(self.attempts.read().await.iter(), tape, window, observed_until, bucket_bps)
+    }
+
+    /// Histogram, moments and tails of the per-trade net PnL over `bins` equal-width bins
+    pub async fn pnl_distribution(&self, bins: usize) -> PnLDistribution {
+        let pnls = self.fold_trades(Vec::new(), |pnls, t| if !t.hedge { pnls.push(t.pnl) }).await;
+        PnLDistribution::of(&pnls, bins)
+    }
+
+    /// Compare each model's predictions against realized fills, in first-seen order
+    pub async fn model_calibration(&self) -> Vec<ModelCalibration> {
+        let attempts = self.attempts.read().await;
+        let mut results: Vec<ModelCalibration> = Vec::new();
+
+        for attempt in attempts.iter().filter(|a| a.quoted) {
+            let outcome = if attempt.executed() { 1.0 } else { 0.0 };
+            for prediction in &attempt.model_predictions {
+                let idx = match results.iter().position(|c| c.model == prediction.model) {
+                    Some(idx) => idx,
+                    None => {
+                        results.push(ModelCalibration {
+                            model: prediction.model.clone(),
+                            attempts: 0,
+                            mean_predicted: 0.0,
+                            realized_rate: 0.0,
+                            brier_score: 0.0,
+                        });
+                        results.len() - 1
+                    }
+                };
+                let entry = &mut results[idx];
+                entry.attempts += 1;
+                entry.mean_predicted += prediction.probability;
+                entry.realized_rate += outcome;
+                entry.brier_score += (prediction.probability - outcome).powi(2);
+            }
+        }
+
+        for entry in &mut results {
+            let n = entry.attempts as f64;
+            entry.mean_predicted /= n;
+            entry.realized_rate /= n;
+            entry.brier_score /= n;
+        }
+        results
+    }
+
+    /// PnL and trade count per calendar day in the report timezone
+    pub async fn daily_pnl(&self) -> Vec<(NaiveDate, f64, u32)> {
+        self.trades.read().await.daily.clone()
+    }
+
+    /// Child fills, notional and fees per venue across all routed trades
+    pub async fn venue_routing(&self) -> Vec<VenueRouting> {
+        self.trades.read().await.routing.clone()
+    }
+
+    /// Position built up from every recorded trade
+    pub async fn position(&self) -> Position {
+        self.trades.read().await.position
+    }
+
+    /// PnL of the open inventory marked at `mid` rather than the last mark
+    pub async fn unrealized_pnl_at(&self, mid: f64) -> f64 {
+        self.ledger.read().await.unrealized_pnl(mid)
     }
 
     pub async fn get_stats(&self) -> PnLStats {
         self.stats.read().await.clone()
     }
 
+    /// The `n` trades with the highest PnL, best first
+    pub async fn get_top_trades(&self, n: usize) -> Vec<Trade> {
+        self.best_n(n).await
+    }
+
+    /// The `n` trades with the highest net PnL, best first
+    pub async fn best_n(&self, n: usize) -> Vec<Trade> {
+        self.ranked_n(n, |a, b| b.pnl.total_cmp(&a.pnl)).await
+    }
+
+    /// The `n` trades with the lowest net PnL, worst first
+    pub async fn worst_n(&self, n: usize) -> Vec<Trade> {
+        self.ranked_n(n, |a, b| a.pnl.total_cmp(&b.pnl)).await
+    }
+
+    /// The first `n` trades in `order`, holding no more than `2n` at a time
+    async fn ranked_n(&self, n: usize, order: fn(&Trade, &Trade) -> std::cmp::Ordering) -> Vec<Trade> {
+        let mut ranked = self
+            .fold_trades(Vec::new(), move |ranked, t| {
+                ranked.push(t.clone());
+                if ranked.len() >= 2 * n.max(1) {
+                    ranked.sort_by(order);
+                    ranked.truncate(n);
+                }
+            })
+            .await;
+        ranked.sort_by(order);
+        ranked.truncate(n);
+        ranked
+    }
+
+    /// The trade with `id`, looked up in memory before the spill file is read
+    pub async fn trade(&self, id: u64) -> Option<Trade> {
+        let on_disk = {
+            let log = self.trades.read().await;
+            if let Some(trade) = log.history.in_memory().find(|t| t.id == id) {
+                return Some(trade.clone());
+            }
+            log.history.spill_reader()
+        };
+        if on_disk.is_empty() {
+            return None;
+        }
+        read_history(on_disk, None, move |found, t| if found.is_none() && t.id == id { *found = Some(t.clone()) }).await
+    }
+
+    /// Trades stamped from `t0` up to but excluding `t1`, Unix milliseconds, oldest first
+    pub async fn trades_between(&self, t0: i64, t1: i64) -> Vec<Trade> {
+        self.trades_where(move |t| t.timestamp >= t0 && t.timestamp < t1).await
+    }
+
+    pub async fn trades_by_side(&self, side: TradeSide) -> Vec<Trade> {
+        self.trades_where(move |t| t.side == side).await
+    }
+
+    /// Trades made by an engine labelled `tag`
+    pub async fn trades_by_tag(&self, tag: &str) -> Vec<Trade> {
+        let tag = tag.to_string();
+        self.trades_where(move |t| t.tag.as_deref() == Some(tag.as_str())).await
+    }
+
+    async fn trades_where(&self, keep: impl Fn(&Trade) -> bool + Send + 'static) -> Vec<Trade> {
+        self.fold_trades(Vec::new(), move |trades, t| if keep(t) { trades.push(t.clone()) }).await
+    }
+
+    /// Every recorded trade, oldest first, spilled ones read back from disk
+    pub async fn get_trades(&self) -> Vec<Trade> {
+        self.fold_trades(Vec::new(), |trades, t| trades.push(t.clone())).await
+    }
+
+    /// The latest `n` trades, oldest first
     pub async fn get_recent_trades(&self, n: usize) -> Vec<Trade> {
-        let trades = self.trades.read().await;
-        let start = trades.len().saturating_sub(n);
-        trades[start..].to_vec()
+        let history = {
+            let log = self.trades.read().await;
+            if let Some(trades) = log.history.recent_in_memory(n) {
+                return trades;
+            }
+            log.history.reader()
+        };
+        let read = tokio::task::spawn_blocking(move || {
+            history.recent(n).unwrap_or_else(|e| {
+                error!("Spilled trades left out: {:#}", e);
+                history.recent_in_memory(n)
+            })
+        });
+        join_blocking(read.await)
     }
 
+
+    /// The closing stats and breakdowns; under `--json` they're part of the session's `summary` event instead
     pub async fn print_summary(&self) {
-        let stats = self.get_stats().await;
-        
-        println!("\n╔════════════════════════════════════════════════════════════════════╗");
-        println!("║                    TRADING SESSION SUMMARY                         ║");
-        println!("╠════════════════════════════════════════════════════════════════════╣");
-        println!("║ Total Trades:          {:>8}                                    ║", stats.total_trades);
-        println!("║   - Buy Trades:        {:>8}                                    ║", stats.buy_trades);
-        println!("║   - Sell Trades:       {:>8}                                    ║", stats.sell_trades);
-        println!("║                                                                    ║");
-        println!("║ Total PnL:             ${:>12.2}                             ║", stats.total_pnl);
-        println!("║   - Buy PnL:           ${:>12.2}                             ║", stats.buy_pnl);
-        println!("║   - Sell PnL:          ${:>12.2}                             ║", stats.sell_pnl);
-        println!("║                                                                    ║");
-        println!("║ Avg PnL per Trade:     ${:>12.2}                             ║", stats.avg_pnl_per_trade());
-        println!("║ Total Notional:        ${:>12.2}                             ║", stats.total_notional);
-        println!("║ PnL / Notional:        {:>8.2} bps                            ║", stats.pnl_per_notional_bps());
-        println!("║ Avg Execution Prob:    {:>7.1}%                                 ║", stats.avg_execution_prob * 100.0);
-        println!("╚════════════════════════════════════════════════════════════════════╝\n");
+        if output::json() {
+            return;
+        }
+        self.get_stats().await.print_summary();
+
+        {
+            let log = self.trades.read().await;
+            if let (Some(path), true) = (log.history.spill_path(), log.history.spilled() > 0) {
+                println!("Trade History: {} trades, the oldest {} spilled to {}\n", log.history.len(), log.history.spilled(), path.display());
+            }
+        }
+
+        let days = self.daily_pnl().await;
+        if days.len() > 1 {
+            println!("Daily PnL ({}):", self.timezone);
+            for (date, pnl, count) in &days {
+                println!("  {} │ {:>5} trades │ PnL: ${:>12.2}", date, count, pnl);
+            }
+            println!();
+        }
+
+        let calibration = self.model_calibration().await;
+        if calibration.len() > 1 {
+            println!("Execution Model Comparison (predicted vs realized fill rate):");
+            for c in &calibration {
+                println!("  {:<10} attempts {:>5} │ predicted {:>5.1}% │ realized {:>5.1}% │ brier {:.4}",
+                    c.model,
+                    c.attempts,
+                    c.mean_predicted * 100.0,
+                    c.realized_rate * 100.0,
+                    c.brier_score
+                );
+            }
+            println!();
+        }
+
+        let venues = self.get_stats().await.venues;
+        if !venues.is_empty() {
+            println!("Venue Attribution (fills priced off each venue │ fills marked against its touch):");
+            for v in &venues {
+                println!("  {:<8} fills {:>5} │ notional ${:>14.2} │ PnL ${:>10.2} │ marks {:>5} │ PnL ${:>10.2}",
+                    v.venue.name(),
+                    v.fills,
+                    v.fill_notional_usd,
+                    v.fill_pnl,
+                    v.marks,
+                    v.mark_pnl
+                );
+            }
+            println!();
+        }
+
+        let markouts = self.get_stats().await.markouts;
+        if markouts.iter().any(|m| m.trades > 0) {
+            println!("Markouts (mid move after each fill, before fees):");
+            for m in &markouts {
+                println!("  T+{:<4} fills {:>5} │ PnL ${:>10.2} │ {:>+6.2} bps │ {:>5.1}% positive",
+                    format!("{}s", m.horizon_ms / 1000),
+                    m.trades,
+                    m.pnl_usd,
+                    m.avg_bps(),
+                    m.positive_rate() * 100.0
+                );
+            }
+            println!();
+        }
+
+        let distribution = self.pnl_distribution(DEFAULT_PNL_BINS).await;
+        if distribution.trades > 1 {
+            println!("{}", distribution);
+        }
+
+        let routing = self.venue_routing().await;
+        if !routing.is_empty() {
+            let total: f64 = routing.iter().map(|r| r.notional_usd).sum();
+            println!("Order Routing (child fills per venue):");
+            for r in &routing {
+                println!("  {:<8} fills {:>5} │ notional ${:>14.2} ({:>5.1}%) │ fees ${:>10.2}",
+                    r.venue.name(),
+                    r.fills,
+                    r.notional_usd,
+                    r.notional_usd / total * 100.0,
+                    r.fees_usd
+                );
+            }
+            println!();
+        }
+
+        if self.display_units.is_empty() {
+            return;
+        }
+
+        let trades = self.trades.read().await;
+        println!("PnL in Native Units (converted at trade time):");
+        for (i, &unit) in self.display_units.iter().enumerate() {
+            let total = trades.native_pnl.get(i).map_or(0.0, |&(total, _)| total);
+            println!("  {:<10} {:>20.0}", unit.symbol(), total);
+        }
+        println!();
     }
 
     pub async fn print_trade(&self, trade: &Trade) {
         let stats = self.get_stats().await;
+        if output::json() {
+            output::emit("trade", &serde_json::json!({ "trade": trade, "total_pnl": stats.total_pnl }));
+            return;
+        }
         let side_str = match trade.side {
             TradeSide::Buy => "BUY ",
             TradeSide::Sell => "SELL",
         };
 
-        println!(
-            "[TRADE] {} │ Price: ${:>8.2} │ Amount: {:>8.4} ETH │ Prob: {:>5.1}% │ PnL: ${:>8.2} │ Total PnL: ${:>10.2}",
+        let side_str = match trade.role {
+            LiquidityRole::Maker => side_str.to_string(),
+            LiquidityRole::Taker => format!("{} TAKE", side_str.trim_end()),
+        };
+        let side_str = if trade.degraded {
+            format!("{} DEGRADED", side_str.trim_end())
+        } else {
+            side_str
+        };
+        let side_str = if trade.hedge {
+            format!("{} HEDGE", side_str.trim_end())
+        } else {
+            side_str
+        };
+
+        say!(
+            "[TRADE] {} │ {} │ Price: ${:>8.2} │ Amount: {:>8.4} ETH │ Prob: {:>5.1}% │ PnL: ${:>8.2} (fees ${:.2}) │ Total PnL: ${:>10.2}",
+            self.timezone.format_time(trade.timestamp),
             side_str,
             trade.price,
             trade.amount_eth,
             trade.execution_prob * 100.0,
             trade.pnl,
+            trade.fees_usd,
             stats.total_pnl
         );
+
+        for fill in &trade.fills {
+            say!("        └ #{} {:<8} ${:>10.2} @ ${:>8.2} │ fee ${:.2}",
+                fill.parent_id, fill.venue.name(), fill.notional_usd, fill.price, fill.fee_usd);
+        }
+
+        if !self.display_units.is_empty() {
+            let native: Vec<String> = self
+                .display_units
+                .iter()
+                .map(|&unit| trade.conversion.format(trade.pnl, unit))
+                .collect();
+            say!("        └ PnL: {}", native.join(" │ "));
+        }
     }
-}
\ No newline at end of file
+}
+
+/// Fold `history` into `init` oldest first on a blocking task, logging a spill file that can't be read
+async fn read_history<T: Send + 'static>(history: HistoryReader, init: T, mut visit: impl FnMut(&mut T, &Trade) + Send + 'static) -> T {
+    let read = tokio::task::spawn_blocking(move || {
+        let mut acc = init;
+        if let Err(e) = history.for_each(|t| visit(&mut acc, t)) {
+            error!("Spilled trades left out: {:#}", e);
+        }
+        acc
+    });
+    join_blocking(read.await)
+}
+
+/// Result of a blocking task, re-raising its panic if it had one
+fn join_blocking<T>(joined: std::result::Result<T, tokio::task::JoinError>) -> T {
+    joined.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+}
+
+/// Keep `tracker` marked to market on every quote update in the background, so the equity time
+/// series keeps its spacing between trading cycles. The book starts from `seed`, the feed's prices
+/// when the task starts, rather than filling in one source at a time. The task shares the
+/// tracker's state; hand it to `stop_mark_to_market` once the session is over.
+pub fn spawn_mark_to_market(mut updates: broadcast::Receiver<QuoteUpdate>, seed: AggregatedPrices, tracker: &PnLTracker) -> JoinHandle<()> {
+    let tracker = tracker.clone();
+    tokio::spawn(async move {
+        let mut book = seed;
+        loop {
+            match updates.recv().await {
+                Ok(update) => {
+                    book.set(update.source, update.quote);
+                    tracker.mark_to_market(&book).await;
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    })
+}
+
+/// Stop a `spawn_mark_to_market` task and wait it out, so no mark lands while the summary and
+/// exports read the tracker. The feed outlives the session, so the task wouldn't end by itself.
+pub async fn stop_mark_to_market(task: JoinHandle<()>) {
+    task.abort();
+    if let Err(e) = task.await {
+        if e.is_panic() {
+            std::panic::resume_unwind(e.into_panic());
+        }
+    }
+}
+
+const TRADE_CSV_HEADER: &str = "id,timestamp,side,role,price,amount_eth,notional_usd,gross_pnl,pnl,fees_usd,\
+venue_fee_usd,gas_usd,slippage_usd,execution_prob,degraded,hedge,adverse_selection_usd,child_fills,eth_usd,sol_usd,venue,mark_venue,tag";
+
+fn trade_csv_row(trade: &Trade) -> String {
+    let side = match trade.side {
+        TradeSide::Buy => "buy",
+        TradeSide::Sell => "sell",
+    };
+    let role = match trade.role {
+        LiquidityRole::Maker => "maker",
+        LiquidityRole::Taker => "taker",
+    };
+    format!("{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+        trade.id, trade.timestamp, side, role, trade.price, trade.amount_eth, trade.notional_usd,
+        trade.gross_pnl, trade.pnl, trade.fees_usd, trade.fees.venue_fee_usd, trade.fees.gas_usd,
+        trade.fees.slippage_usd, trade.execution_prob, trade.degraded, trade.hedge,
+        trade.adverse_selection_usd, trade.fills.len(), trade.conversion.eth_usd,
+        trade.conversion.sol_usd.map_or(String::new(), |v| v.to_string()),
+        trade.venue.map_or("", |v| v.name()), trade.mark_venue.map_or("", |v| v.name()),
+        csv_field(&serde_json::Value::from(trade.tag.clone().unwrap_or_default())))
+}
+
+/// `a.b.c` keys for every leaf of a JSON value; arrays keep their index as a key segment
+fn flatten_json(prefix: &str, value: &serde_json::Value) -> Vec<(String, serde_json::Value)> {
+    let join = |key: &str| if prefix.is_empty() { key.to_string() } else { format!("{}.{}", prefix, key) };
+    match value {
+        serde_json::Value::Object(map) => map.iter().flat_map(|(k, v)| flatten_json(&join(k), v)).collect(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .flat_map(|(i, v)| flatten_json(&join(&i.to_string()), v))
+            .collect(),
+        leaf => vec![(prefix.to_string(), leaf.clone())],
+    }
+}
+
+fn csv_field(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) if s.contains([',', '"', '\n']) => format!("\"{}\"", s.replace('"', "\"\"")),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
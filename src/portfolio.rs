@@ -0,0 +1,114 @@
+use crate::aggregator::AggregatedPrices;
+use crate::orders::RestingOrder;
+use crate::pnl_tracker::PnLTracker;
+use crate::position::Position;
+use crate::risk::RiskState;
+use crate::trader::TradingEngine;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Quotes, inventory, open orders, PnL and risk state of a running session, all as of the same
+/// cycle boundary
+#[derive(Debug, Clone, Serialize)]
+pub struct PortfolioSnapshot {
+    /// Cycles completed when the snapshot was taken
+    pub cycle: u64,
+    /// Feed time of `prices`
+    pub timestamp: i64,
+    pub prices: AggregatedPrices,
+    pub mid: Option<f64>,
+    pub position: Position,
+    pub open_orders: Vec<RestingOrder>,
+    /// Filled, still waiting out the fill latency
+    pub pending_fills: usize,
+    pub total_trades: u32,
+    /// Net PnL of all trades
+    pub total_pnl: f64,
+    pub realized_pnl: f64,
+    /// Open inventory marked at `mid`
+    pub unrealized_pnl: f64,
+    pub total_fees: f64,
+    pub risk: RiskState,
+    /// Quorum trading mode at `prices`
+    pub trading_mode: String,
+    pub quiet_paused: bool,
+}
+
+impl PortfolioSnapshot {
+    pub fn marked_pnl(&self) -> f64 {
+        self.realized_pnl + self.unrealized_pnl
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("failed to serialize portfolio snapshot")
+    }
+}
+
+/// A running session's engine and tracker, publishing a portfolio snapshot at every cycle
+/// boundary so readers get one coherent view instead of stitching separate calls together
+/// while the trading loop moves underneath them. Clones share the same session.
+#[derive(Clone)]
+pub struct Simulator {
+    engine: Arc<TradingEngine>,
+    tracker: PnLTracker,
+    latest: Arc<RwLock<Option<PortfolioSnapshot>>>,
+}
+
+impl Simulator {
+    pub fn new(engine: Arc<TradingEngine>, tracker: PnLTracker) -> Self {
+        Self {
+            engine,
+            tracker,
+            latest: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub fn engine(&self) -> &Arc<TradingEngine> {
+        &self.engine
+    }
+
+    pub fn tracker(&self) -> &PnLTracker {
+        &self.tracker
+    }
+
+    /// Build a snapshot from the engine and tracker now. Only coherent when the trading loop
+    /// isn't mid-cycle, which is why it publishes through `publish` instead.
+    pub async fn capture(&self, cycle: u64, prices: &AggregatedPrices) -> PortfolioSnapshot {
+        let stats = self.tracker.get_stats().await;
+        let mid = prices.median_mid();
+        let unrealized_pnl = match mid {
+            Some(mid) => self.tracker.unrealized_pnl_at(mid).await,
+            None => stats.unrealized_pnl,
+        };
+        PortfolioSnapshot {
+            cycle,
+            timestamp: prices.quotes().map(|q| q.timestamp).max().unwrap_or_default(),
+            prices: prices.clone(),
+            mid,
+            position: self.engine.position(),
+            open_orders: self.engine.open_orders(),
+            pending_fills: self.engine.pending_fills(),
+            total_trades: stats.total_trades,
+            total_pnl: stats.total_pnl,
+            realized_pnl: stats.realized_pnl,
+            unrealized_pnl,
+            total_fees: stats.total_fees,
+            risk: self.engine.risk_state(),
+            trading_mode: self.engine.mode(prices).to_string(),
+            quiet_paused: self.engine.quiet_paused(),
+        }
+    }
+
+    /// Capture and publish the snapshot at a cycle boundary
+    pub async fn publish(&self, cycle: u64, prices: &AggregatedPrices) {
+        let snapshot = self.capture(cycle, prices).await;
+        *self.latest.write().await = Some(snapshot);
+    }
+
+    /// The portfolio as of the last completed cycle; `None` before the first one
+    pub async fn portfolio(&self) -> Option<PortfolioSnapshot> {
+        self.latest.read().await.clone()
+    }
+}
@@ -0,0 +1,201 @@
+use crate::amount::{self, Exact};
+use crate::trader::{Trade, TradeSide};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+/// Inventory accumulated from fills over a session
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Position {
+    /// Signed ETH inventory: positive long, negative short
+    pub net_eth: f64,
+    /// Average price of the open inventory (0 when flat)
+    pub avg_entry_price: f64,
+    /// USDC balance change from all fills, net of fees
+    pub cash_usd: f64,
+    /// PnL locked in by trades that reduced the position
+    pub realized_pnl: f64,
+    pub fees_usd: f64,
+    /// Exact sums behind `cash_usd`, `realized_pnl` and `fees_usd`
+    #[serde(skip)]
+    exact: [Exact; 3],
+}
+
+impl Position {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_flat(&self) -> bool {
+        self.net_eth.abs() < 1e-12
+    }
+
+    pub fn apply(&mut self, trade: &Trade) {
+        let signed_qty = match trade.side {
+            TradeSide::Buy => trade.amount_eth,
+            TradeSide::Sell => -trade.amount_eth,
+        };
+        let notional = amount::value_of(trade.price, 0.0, trade.amount_eth);
+        self.exact[0].add(&mut self.cash_usd, -signed_qty.signum() * notional);
+        self.exact[0].add(&mut self.cash_usd, -trade.fees_usd);
+        self.exact[2].add(&mut self.fees_usd, trade.fees_usd);
+        self.exact[1].add(&mut self.realized_pnl, -trade.fees_usd);
+
+        let same_direction = self.is_flat() || self.net_eth.signum() == signed_qty.signum();
+        if same_direction {
+            let new_net = self.net_eth + signed_qty;
+            self.avg_entry_price =
+                (self.avg_entry_price * self.net_eth.abs() + trade.price * trade.amount_eth) / new_net.abs();
+            self.net_eth = new_net;
+            return;
+        }
+
+        // Reducing (and possibly flipping) the position
+        let closed = trade.amount_eth.min(self.net_eth.abs());
+        let closed_pnl = amount::value_of(trade.price, self.avg_entry_price, closed * self.net_eth.signum());
+        self.exact[1].add(&mut self.realized_pnl, closed_pnl);
+        self.net_eth += signed_qty;
+
+        if self.is_flat() {
+            self.net_eth = 0.0;
+            self.avg_entry_price = 0.0;
+        } else if self.net_eth.signum() == signed_qty.signum() {
+            // Flipped through zero: the remainder opens at the trade price
+            self.avg_entry_price = trade.price;
+        }
+    }
+
+    /// PnL of the open inventory marked at `mark_price`
+    pub fn unrealized_pnl(&self, mark_price: f64) -> f64 {
+        (mark_price - self.avg_entry_price) * self.net_eth
+    }
+
+    /// Cash plus inventory value at `mark_price`
+    pub fn equity(&self, mark_price: f64) -> f64 {
+        self.cash_usd + self.net_eth * mark_price
+    }
+
+    pub fn notional(&self, mark_price: f64) -> f64 {
+        self.net_eth * mark_price
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:+.4} ETH @ ${:.2} │ Cash: ${:.2} │ Realized: ${:.2}",
+            self.net_eth, self.avg_entry_price, self.cash_usd, self.realized_pnl)
+    }
+}
+
+/// How closing fills are matched against open inventory to realize PnL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CostBasis {
+    /// Against the volume-weighted average price of the open inventory
+    #[default]
+    AverageCost,
+    /// Against the oldest open lots first
+    Fifo,
+}
+
+impl CostBasis {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CostBasis::AverageCost => "average cost",
+            CostBasis::Fifo => "FIFO",
+        }
+    }
+}
+
+impl FromStr for CostBasis {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "average" | "average-cost" | "average_cost" | "avg" => Ok(CostBasis::AverageCost),
+            "fifo" => Ok(CostBasis::Fifo),
+            other => bail!("unknown cost basis '{}' (expected average-cost or fifo)", other),
+        }
+    }
+}
+
+/// Open inventory as lots, splitting PnL into realized (closed round trips, net of all fees)
+/// and unrealized (open lots marked to a price)
+#[derive(Debug, Clone, Default)]
+pub struct InventoryLedger {
+    basis: CostBasis,
+    /// Signed ETH and entry price, oldest first; at most one lot under average cost
+    lots: VecDeque<(f64, f64)>,
+    realized_pnl: f64,
+    realized_exact: Exact,
+}
+
+impl InventoryLedger {
+    pub fn new(basis: CostBasis) -> Self {
+        Self {
+            basis,
+            ..Self::default()
+        }
+    }
+
+    pub fn basis(&self) -> CostBasis {
+        self.basis
+    }
+
+    pub fn apply(&mut self, trade: &Trade) {
+        let mut qty = match trade.side {
+            TradeSide::Buy => trade.amount_eth,
+            TradeSide::Sell => -trade.amount_eth,
+        };
+        self.realized_exact.add(&mut self.realized_pnl, -trade.fees_usd);
+
+        // Close opposite-signed lots first, oldest first
+        while qty.abs() > 1e-12 {
+            let Some(lot) = self.lots.front_mut().filter(|lot| lot.0.signum() != qty.signum()) else {
+                break;
+            };
+            let closed = qty.abs().min(lot.0.abs());
+            self.realized_exact.add(&mut self.realized_pnl, amount::value_of(trade.price, lot.1, closed * lot.0.signum()));
+            lot.0 -= closed * lot.0.signum();
+            qty -= closed * qty.signum();
+            if lot.0.abs() < 1e-12 {
+                self.lots.pop_front();
+            }
+        }
+        if qty.abs() <= 1e-12 {
+            return;
+        }
+
+        match (self.basis, self.lots.front_mut()) {
+            (CostBasis::AverageCost, Some(lot)) => {
+                let total = lot.0 + qty;
+                lot.1 = (lot.1 * lot.0.abs() + trade.price * qty.abs()) / total.abs();
+                lot.0 = total;
+            }
+            _ => self.lots.push_back((qty, trade.price)),
+        }
+    }
+
+    pub fn net_eth(&self) -> f64 {
+        self.lots.iter().map(|lot| lot.0).sum()
+    }
+
+    /// Weighted entry price of the open lots (0 when flat)
+    pub fn avg_entry_price(&self) -> f64 {
+        let size: f64 = self.lots.iter().map(|lot| lot.0.abs()).sum();
+        if size <= 1e-12 {
+            return 0.0;
+        }
+        self.lots.iter().map(|lot| lot.0.abs() * lot.1).sum::<f64>() / size
+    }
+
+    pub fn realized_pnl(&self) -> f64 {
+        self.realized_pnl
+    }
+
+    pub fn unrealized_pnl(&self, mark_price: f64) -> f64 {
+        self.lots.iter().map(|lot| (mark_price - lot.1) * lot.0).sum()
+    }
+}
@@ -0,0 +1,333 @@
+use crate::aggregator::QuoteUpdate;
+use crate::trader::Trade;
+use crate::ws::StreamEvent;
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+use tokio::sync::{broadcast, oneshot};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Message bus to publish to, from `kafka://broker:9092[,broker2:9092]` or `nats://host:4222`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BusUrl {
+    /// Comma-separated bootstrap servers
+    Kafka { brokers: String },
+    Nats { url: String },
+}
+
+impl FromStr for BusUrl {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once("://") {
+            Some(("kafka", brokers)) if !brokers.is_empty() => Ok(BusUrl::Kafka {
+                brokers: brokers.trim_end_matches('/').to_string(),
+            }),
+            Some(("nats" | "tls", rest)) if !rest.is_empty() => Ok(BusUrl::Nats { url: s.to_string() }),
+            _ => bail!("publish url '{}' should be kafka://host:port[,host:port] or nats://host:port", s),
+        }
+    }
+}
+
+impl fmt::Display for BusUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BusUrl::Kafka { brokers } => write!(f, "kafka://{}", brokers),
+            BusUrl::Nats { url } => write!(f, "{}", url),
+        }
+    }
+}
+
+/// Where events go: Kafka topics or NATS subjects, one for trades and one for quote updates
+#[derive(Debug, Clone)]
+pub struct PublishTarget {
+    pub url: BusUrl,
+    pub trades_topic: String,
+    /// Quote updates are left off the bus when unset
+    pub quotes_topic: Option<String>,
+}
+
+/// Delivery counts once the publisher has drained
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PublishStats {
+    pub trades: usize,
+    pub quotes: usize,
+    pub failed: usize,
+    /// Events the publisher fell too far behind on to read
+    pub lagged: u64,
+}
+
+/// Payload of every message: the websocket stream's event, tagged with the session
+#[derive(Serialize)]
+struct BusMessage<'a> {
+    session: &'a str,
+    #[serde(flatten)]
+    event: &'a StreamEvent,
+}
+
+/// Publishes every trade and quote update of the session to a message bus from a background task
+pub struct EventPublisher {
+    stop: Mutex<Option<oneshot::Sender<()>>>,
+    handle: Mutex<Option<JoinHandle<PublishStats>>>,
+}
+
+impl EventPublisher {
+    /// Connect to the bus and start publishing what arrives on `quotes` and `trades`
+    pub async fn spawn(
+        config: PublishTarget,
+        session: String,
+        quotes: broadcast::Receiver<QuoteUpdate>,
+        trades: broadcast::Receiver<Trade>,
+    ) -> Result<Self> {
+        let bus = Bus::connect(&config.url).await?;
+        let (stop, stopped) = oneshot::channel();
+        let handle = tokio::spawn(publish_loop(bus, config, session, quotes, trades, stopped));
+        Ok(Self {
+            stop: Mutex::new(Some(stop)),
+            handle: Mutex::new(Some(handle)),
+        })
+    }
+
+    /// Publish what has already arrived, flush the bus and stop
+    pub async fn finish(&self) -> PublishStats {
+        if let Some(stop) = self.stop.lock().unwrap().take() {
+            let _ = stop.send(());
+        }
+        let handle = self.handle.lock().unwrap().take();
+        match handle {
+            Some(handle) => handle.await.unwrap_or_default(),
+            None => PublishStats::default(),
+        }
+    }
+}
+
+async fn publish_loop(
+    mut bus: Bus,
+    config: PublishTarget,
+    session: String,
+    mut quotes: broadcast::Receiver<QuoteUpdate>,
+    mut trades: broadcast::Receiver<Trade>,
+    mut stopped: oneshot::Receiver<()>,
+) -> PublishStats {
+    let mut stats = PublishStats::default();
+    let mut quotes_open = config.quotes_topic.is_some();
+    let mut trades_open = true;
+    loop {
+        tokio::select! {
+            trade = trades.recv(), if trades_open => match trade {
+                Ok(trade) => publish_trade(&mut bus, &config, &session, trade, &mut stats).await,
+                Err(broadcast::error::RecvError::Lagged(n)) => stats.lagged += n,
+                Err(broadcast::error::RecvError::Closed) => trades_open = false,
+            },
+            update = quotes.recv(), if quotes_open => match update {
+                Ok(update) => publish_quote(&mut bus, &config, &session, update, &mut stats).await,
+                Err(broadcast::error::RecvError::Lagged(n)) => stats.lagged += n,
+                Err(broadcast::error::RecvError::Closed) => quotes_open = false,
+            },
+            _ = &mut stopped => break,
+        }
+    }
+
+    // Whatever was recorded before the session ended still goes out
+    while let Ok(trade) = trades.try_recv() {
+        publish_trade(&mut bus, &config, &session, trade, &mut stats).await;
+    }
+    if config.quotes_topic.is_some() {
+        while let Ok(update) = quotes.try_recv() {
+            publish_quote(&mut bus, &config, &session, update, &mut stats).await;
+        }
+    }
+    stats.failed += bus.flush().await;
+    stats
+}
+
+async fn publish_trade(bus: &mut Bus, config: &PublishTarget, session: &str, trade: Trade, stats: &mut PublishStats) {
+    let event = StreamEvent::Trade(Box::new(trade));
+    if publish(bus, &config.trades_topic, session, session, &event).await {
+        stats.trades += 1;
+    } else {
+        stats.failed += 1;
+    }
+    stats.failed += bus.failed_deliveries();
+}
+
+async fn publish_quote(bus: &mut Bus, config: &PublishTarget, session: &str, update: QuoteUpdate, stats: &mut PublishStats) {
+    let Some(topic) = &config.quotes_topic else {
+        return;
+    };
+    if publish(bus, topic, update.source.name(), session, &StreamEvent::Quote(update)).await {
+        stats.quotes += 1;
+    } else {
+        stats.failed += 1;
+    }
+    stats.failed += bus.failed_deliveries();
+}
+
+/// Serialize and send one event; `key` partitions Kafka messages (the session for trades, the
+/// source for quotes) and is ignored by NATS
+async fn publish(bus: &mut Bus, topic: &str, key: &str, session: &str, event: &StreamEvent) -> bool {
+    let payload = match serde_json::to_vec(&BusMessage { session, event }) {
+        Ok(payload) => payload,
+        Err(e) => {
+            warn!(error = %e, "failed to serialize bus event");
+            return false;
+        }
+    };
+    match bus.send(topic, key, payload).await {
+        Ok(()) => true,
+        Err(e) => {
+            warn!(topic, error = %e, "failed to publish event");
+            false
+        }
+    }
+}
+
+/// A connected bus client; each variant needs its crate feature
+enum Bus {
+    #[cfg(feature = "kafka")]
+    Kafka(kafka::KafkaBus),
+    #[cfg(feature = "nats")]
+    Nats(async_nats::Client),
+}
+
+impl Bus {
+    async fn connect(url: &BusUrl) -> Result<Self> {
+        match url {
+            #[cfg(feature = "kafka")]
+            BusUrl::Kafka { brokers } => Ok(Bus::Kafka(kafka::KafkaBus::connect(brokers)?)),
+            #[cfg(not(feature = "kafka"))]
+            BusUrl::Kafka { .. } => bail!("publishing to Kafka needs a build with the kafka feature (cargo build --features kafka)"),
+            #[cfg(feature = "nats")]
+            BusUrl::Nats { url } => {
+                use anyhow::Context;
+                let client = async_nats::connect(url.as_str()).await.with_context(|| format!("failed to connect to {}", url))?;
+                Ok(Bus::Nats(client))
+            }
+            #[cfg(not(feature = "nats"))]
+            BusUrl::Nats { .. } => bail!("publishing to NATS needs a build with the nats feature (cargo build --features nats)"),
+        }
+    }
+
+    #[allow(unused_variables)]
+    async fn send(&mut self, topic: &str, key: &str, payload: Vec<u8>) -> Result<()> {
+        match *self {
+            #[cfg(feature = "kafka")]
+            Bus::Kafka(ref mut bus) => bus.send(topic, key, payload).await,
+            #[cfg(feature = "nats")]
+            Bus::Nats(ref client) => Ok(client.publish(topic.to_string(), payload.into()).await?),
+        }
+    }
+
+    /// Earlier sends that failed to deliver since this was last asked; a send only reports whether
+    /// its own message was accepted
+    fn failed_deliveries(&mut self) -> usize {
+        match *self {
+            #[cfg(feature = "kafka")]
+            Bus::Kafka(ref mut bus) => bus.failed_deliveries(),
+            #[cfg(feature = "nats")]
+            Bus::Nats(_) => 0,
+        }
+    }
+
+    /// Wait for everything sent to be delivered, returning how many failed
+    async fn flush(&mut self) -> usize {
+        match *self {
+            #[cfg(feature = "kafka")]
+            Bus::Kafka(ref mut bus) => bus.flush().await,
+            #[cfg(feature = "nats")]
+            Bus::Nats(ref client) => match client.flush().await {
+                Ok(()) => 0,
+                Err(e) => {
+                    warn!(error = %e, "failed to flush NATS connection");
+                    1
+                }
+            },
+        }
+    }
+}
+
+#[cfg(feature = "kafka")]
+mod kafka {
+    use anyhow::{Context, Result};
+    use futures_util::stream::FuturesUnordered;
+    use futures_util::StreamExt;
+    use rdkafka::config::ClientConfig;
+    use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+    use rdkafka::producer::{DeliveryFuture, FutureProducer, FutureRecord};
+    use std::time::Duration;
+
+    /// Deliveries awaited at once; past this, sending waits for the oldest ones to land
+    const MAX_IN_FLIGHT: usize = 10_000;
+
+    /// Wait before retrying a send librdkafka's full queue turned away, when nothing of ours is in flight
+    const QUEUE_FULL_BACKOFF: Duration = Duration::from_millis(50);
+
+    pub struct KafkaBus {
+        producer: FutureProducer,
+        in_flight: FuturesUnordered<DeliveryFuture>,
+        /// Deliveries that failed since `failed_deliveries` last reported them
+        failed: usize,
+    }
+
+    impl KafkaBus {
+        pub fn connect(brokers: &str) -> Result<Self> {
+            let producer = ClientConfig::new()
+                .set("bootstrap.servers", brokers)
+                .set("message.timeout.ms", "10000")
+                .create()
+                .with_context(|| format!("failed to create Kafka producer for {}", brokers))?;
+            Ok(Self {
+                producer,
+                in_flight: FuturesUnordered::new(),
+                failed: 0,
+            })
+        }
+
+        pub async fn send(&mut self, topic: &str, key: &str, payload: Vec<u8>) -> Result<()> {
+            while self.in_flight.len() >= MAX_IN_FLIGHT {
+                self.reap_one().await;
+            }
+            let mut record = FutureRecord::to(topic).key(key).payload(&payload);
+            loop {
+                match self.producer.send_result(record) {
+                    Ok(delivery) => {
+                        self.in_flight.push(delivery);
+                        return Ok(());
+                    }
+                    // The queue drains as deliveries land or time out, so this doesn't wait forever
+                    Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), returned)) => {
+                        record = returned;
+                        if self.in_flight.is_empty() {
+                            tokio::time::sleep(QUEUE_FULL_BACKOFF).await;
+                        } else {
+                            self.reap_one().await;
+                        }
+                    }
+                    Err((e, _)) => anyhow::bail!(e),
+                }
+            }
+        }
+
+        pub fn failed_deliveries(&mut self) -> usize {
+            std::mem::take(&mut self.failed)
+        }
+
+        async fn reap_one(&mut self) {
+            if let Some(result) = self.in_flight.next().await {
+                if !matches!(result, Ok(Ok(_))) {
+                    self.failed += 1;
+                }
+            }
+        }
+
+        pub async fn flush(&mut self) -> usize {
+            while !self.in_flight.is_empty() {
+                self.reap_one().await;
+            }
+            std::mem::take(&mut self.failed)
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use crate::aggregator::AggregatedPrices;
+use crate::sizing::RealizedVolatility;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// When a market counts as too flat and tight to be worth quoting
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct QuietMarketPolicy {
+    /// Median bid/ask spread at or below which the market counts as tight
+    pub max_spread_bps: f64,
+    /// Annualized realized volatility at or below which it counts as flat
+    pub max_volatility: f64,
+    /// How long both have to hold before the market is declared quiet
+    pub min_quiet_ms: i64,
+    /// Trailing window the volatility is measured over
+    pub vol_window_ms: i64,
+    /// Stop quoting while quiet; otherwise quiet spells are only reported
+    pub pause: bool,
+}
+
+impl Default for QuietMarketPolicy {
+    fn default() -> Self {
+        Self {
+            max_spread_bps: 2.0,
+            max_volatility: 0.2,
+            min_quiet_ms: 60_000,
+            vol_window_ms: 60_000,
+            pause: false,
+        }
+    }
+}
+
+/// One quiet spell, in feed time
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct QuietInterval {
+    /// When `min_quiet_ms` of meeting the thresholds confirmed the market as quiet
+    pub start: i64,
+    pub end: i64,
+    /// Whether quoting was paused for it
+    pub paused: bool,
+}
+
+impl QuietInterval {
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis((self.end - self.start).max(0) as u64)
+    }
+}
+
+/// A change in whether the market is quiet
+#[derive(Debug, Clone, Copy)]
+pub enum QuietEvent {
+    /// Quiet as of `at`, with the spread and volatility that confirmed it
+    Entered { at: i64, spread_bps: f64, volatility: f64 },
+    /// Activity returned and ended this spell
+    Resumed(QuietInterval),
+}
+
+impl fmt::Display for QuietEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuietEvent::Entered { spread_bps, volatility, .. } => {
+                write!(f, "market quiet: spread {:.2} bps, volatility {:.1}%", spread_bps, volatility * 100.0)
+            }
+            QuietEvent::Resumed(interval) => {
+                write!(f, "activity returned after {:.1} min", interval.duration().as_secs_f64() / 60.0)
+            }
+        }
+    }
+}
+
+/// Tracks median spread and realized volatility against a `QuietMarketPolicy`
+pub struct QuietMarketDetector {
+    policy: QuietMarketPolicy,
+    volatility: RealizedVolatility,
+    /// When the thresholds were first met in the current run of quiet observations
+    candidate_since: Option<i64>,
+    /// When the current quiet spell was confirmed
+    quiet_since: Option<i64>,
+}
+
+impl QuietMarketDetector {
+    pub fn new(policy: QuietMarketPolicy) -> Self {
+        Self {
+            volatility: RealizedVolatility::new(policy.vol_window_ms),
+            policy,
+            candidate_since: None,
+            quiet_since: None,
+        }
+    }
+
+    pub fn policy(&self) -> &QuietMarketPolicy {
+        &self.policy
+    }
+
+    pub fn is_quiet(&self) -> bool {
+        self.quiet_since.is_some()
+    }
+
+    /// Feed the latest snapshot; returns the event when the market turns quiet or active again
+    pub fn observe(&mut self, prices: &AggregatedPrices) -> Option<QuietEvent> {
+        let median = prices.median_quote()?;
+        let mid = prices.median_mid()?;
+        let timestamp = median.timestamp;
+        self.volatility.observe(timestamp, mid);
+
+        let spread_bps = (median.ask - median.bid) / mid * 10_000.0;
+        // Without enough samples for a volatility reading, the market can't be judged flat
+        let volatility = self.volatility.annualized();
+        let meets = spread_bps <= self.policy.max_spread_bps
+            && volatility.is_some_and(|v| v <= self.policy.max_volatility);
+
+        if !meets {
+            self.candidate_since = None;
+            return self.close(timestamp).map(QuietEvent::Resumed);
+        }
+
+        let since = *self.candidate_since.get_or_insert(timestamp);
+        if self.is_quiet() || timestamp - since < self.policy.min_quiet_ms {
+            return None;
+        }
+        self.quiet_since = Some(timestamp);
+        Some(QuietEvent::Entered {
+            at: timestamp,
+            spread_bps,
+            volatility: volatility.unwrap_or_default(),
+        })
+    }
+
+    /// End the current spell at `timestamp`, e.g. when the session ends while still quiet
+    pub fn close(&mut self, timestamp: i64) -> Option<QuietInterval> {
+        let start = self.quiet_since.take()?;
+        Some(QuietInterval {
+            start,
+            end: timestamp,
+            paused: self.policy.pause,
+        })
+    }
+}
@@ -0,0 +1,88 @@
+use crate::aggregator::{PriceSnapshot, Source};
+use serde::Serialize;
+use std::fmt;
+use std::time::Duration;
+
+/// How many fresh sources trading needs, and what to do with fewer
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct QuorumPolicy {
+    pub min_sources: usize,
+    /// Quotes older than this when prices are read don't count
+    pub max_quote_age_ms: i64,
+    /// Keep trading below quorum in degraded mode instead of halting
+    pub allow_degraded: bool,
+    /// Extra distance from the touch for degraded quotes
+    pub degraded_spread_bps: f64,
+    /// Size multiplier for degraded quotes
+    pub degraded_size_factor: f64,
+}
+
+impl Default for QuorumPolicy {
+    fn default() -> Self {
+        Self {
+            min_sources: 2,
+            max_quote_age_ms: 30_000,
+            allow_degraded: true,
+            degraded_spread_bps: 5.0,
+            degraded_size_factor: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum TradingMode {
+    Normal,
+    /// Below quorum: trading off only these sources with wider spreads and smaller size
+    Degraded { sources: Vec<Source> },
+    /// No fresh source, fresh quotes crossed, or below quorum with degraded mode disabled
+    Halted,
+}
+
+impl TradingMode {
+    pub fn is_degraded(&self) -> bool {
+        matches!(self, TradingMode::Degraded { .. })
+    }
+}
+
+impl fmt::Display for TradingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TradingMode::Normal => write!(f, "normal"),
+            TradingMode::Degraded { sources } => {
+                let names: Vec<&str> = sources.iter().map(|s| s.name()).collect();
+                write!(f, "degraded ({})", names.join(", "))
+            }
+            TradingMode::Halted => write!(f, "halted"),
+        }
+    }
+}
+
+impl QuorumPolicy {
+    pub fn max_quote_age(&self) -> Duration {
+        Duration::from_millis(self.max_quote_age_ms.max(0) as u64)
+    }
+
+    /// The quotes at most `max_quote_age_ms` old when `snapshot` was taken. Ages run against the
+    /// snapshot's clock, so when every feed stalls they all go stale together.
+    pub fn fresh(&self, snapshot: &PriceSnapshot) -> PriceSnapshot {
+        snapshot.fresh(self.max_quote_age())
+    }
+
+    pub fn fresh_sources(&self, snapshot: &PriceSnapshot) -> Vec<Source> {
+        self.fresh(snapshot).sources().map(|s| s.source).collect()
+    }
+
+    pub fn mode(&self, snapshot: &PriceSnapshot) -> TradingMode {
+        let fresh = self.fresh(snapshot);
+        if fresh.is_tradeable(self.min_sources.max(1)) {
+            TradingMode::Normal
+        } else if fresh.is_empty() || fresh.is_crossed() {
+            TradingMode::Halted
+        } else if self.allow_degraded {
+            TradingMode::Degraded { sources: fresh.sources().map(|s| s.source).collect() }
+        } else {
+            TradingMode::Halted
+        }
+    }
+}
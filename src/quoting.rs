@@ -0,0 +1,180 @@
+use crate::aggregator::AggregatedPrices;
+use crate::position::Position;
+use anyhow::{bail, Result};
+use serde::Serialize;
+
+/// Our bid and ask for one decision
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuotePair {
+    pub bid: f64,
+    pub ask: f64,
+}
+
+/// Decides where to quote given the market snapshot and our inventory
+pub trait QuotingStrategy: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Bid and ask to quote, or `None` when the snapshot isn't enough to quote
+    fn quotes(&self, prices: &AggregatedPrices, position: &Position) -> Option<QuotePair>;
+
+    /// Current parameter values, for introspection
+    fn parameters(&self) -> serde_json::Value {
+        serde_json::json!({})
+    }
+}
+
+/// Join the median bid and median ask across sources, so we're never worse than the median venue
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MedianQuote;
+
+impl QuotingStrategy for MedianQuote {
+    fn name(&self) -> &'static str {
+        "median"
+    }
+
+    fn quotes(&self, prices: &AggregatedPrices, _position: &Position) -> Option<QuotePair> {
+        let median = prices.median_quote()?;
+        Some(QuotePair {
+            bid: median.bid,
+            ask: median.ask,
+        })
+    }
+}
+
+/// Quote a fixed total spread centred on the median mid
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FixedSpread {
+    /// Distance from our bid to our ask, in bps of mid
+    pub spread_bps: f64,
+}
+
+impl Default for FixedSpread {
+    fn default() -> Self {
+        Self { spread_bps: 4.0 }
+    }
+}
+
+impl QuotingStrategy for FixedSpread {
+    fn name(&self) -> &'static str {
+        "fixed-spread"
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    fn quotes(&self, prices: &AggregatedPrices, _position: &Position) -> Option<QuotePair> {
+        let mid = prices.median_mid()?;
+        let half = mid * self.spread_bps / 2.0 / 10000.0;
+        Some(QuotePair {
+            bid: mid - half,
+            ask: mid + half,
+        })
+    }
+}
+
+/// Price `OffsetQuote` measures its offsets from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuoteReference {
+    /// Median of the venues' mids, on both sides
+    MedianMid,
+    /// Venue mids weighted by how tight each venue's spread is, on both sides
+    WeightedMid,
+    /// Median bid for our bid, median ask for our ask
+    Median,
+    /// Best bid for our bid, best ask for our ask
+    Touch,
+}
+
+impl QuoteReference {
+    /// Reference prices for our bid and ask
+    pub fn prices(&self, prices: &AggregatedPrices) -> Option<(f64, f64)> {
+        match self {
+            QuoteReference::MedianMid => prices.median_mid().map(|mid| (mid, mid)),
+            QuoteReference::WeightedMid => prices.weighted_mid().map(|mid| (mid, mid)),
+            QuoteReference::Median => prices.median_quote().map(|q| (q.bid, q.ask)),
+            QuoteReference::Touch => prices.best_quote().map(|q| (q.bid, q.ask)),
+        }
+    }
+}
+
+impl std::str::FromStr for QuoteReference {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "median-mid" | "mid" => Ok(QuoteReference::MedianMid),
+            "weighted-mid" => Ok(QuoteReference::WeightedMid),
+            "median" => Ok(QuoteReference::Median),
+            "touch" | "best" => Ok(QuoteReference::Touch),
+            other => bail!("unknown quote reference '{}' (expected median-mid, weighted-mid, median or touch)", other),
+        }
+    }
+}
+
+/// Quote at bps offsets from a reference price, set separately for each side. Positive offsets
+/// sit further from the reference (less aggressive, fills less often), negative ones inside it.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct OffsetQuote {
+    pub reference: QuoteReference,
+    /// Our bid sits this far below the bid reference
+    pub bid_offset_bps: f64,
+    /// Our ask sits this far above the ask reference
+    pub ask_offset_bps: f64,
+}
+
+impl Default for OffsetQuote {
+    fn default() -> Self {
+        Self {
+            reference: QuoteReference::MedianMid,
+            bid_offset_bps: 2.0,
+            ask_offset_bps: 2.0,
+        }
+    }
+}
+
+impl OffsetQuote {
+    /// Each offset must be finite and under 100%, and the two mustn't cross the quotes: one side
+    /// can only quote inside the reference by as much as the other sits outside it
+    pub fn validate(&self) -> Result<()> {
+        for (side, bps) in [("bid", self.bid_offset_bps), ("ask", self.ask_offset_bps)] {
+            if !bps.is_finite() || bps >= 10000.0 {
+                bail!("{} offset must be a finite number of bps under 10000, got {}", side, bps);
+            }
+        }
+        if self.bid_offset_bps + self.ask_offset_bps < 0.0 {
+            bail!("bid offset {} bps and ask offset {} bps would cross the quotes", self.bid_offset_bps, self.ask_offset_bps);
+        }
+        Ok(())
+    }
+}
+
+impl QuotingStrategy for OffsetQuote {
+    fn name(&self) -> &'static str {
+        "offset"
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    fn quotes(&self, prices: &AggregatedPrices, _position: &Position) -> Option<QuotePair> {
+        let (bid, ask) = self.reference.prices(prices)?;
+        Some(QuotePair {
+            bid: bid * (1.0 - self.bid_offset_bps / 10000.0),
+            ask: ask * (1.0 + self.ask_offset_bps / 10000.0),
+        })
+    }
+}
+
+/// Build a quoting strategy by name (`median`, `fixed-spread` or `offset`); `offset` is only
+/// built when it's the one asked for
+pub fn parse_quoting_strategy(name: &str, spread_bps: f64, offset: impl FnOnce() -> Result<OffsetQuote>) -> Result<Box<dyn QuotingStrategy>> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "median" => Ok(Box::new(MedianQuote)),
+        "fixed-spread" | "fixed_spread" => Ok(Box::new(FixedSpread { spread_bps })),
+        "offset" => Ok(Box::new(offset()?)),
+        other => bail!("unknown quoting strategy '{}' (expected median, fixed-spread or offset)", other),
+    }
+}
@@ -0,0 +1,192 @@
+use crate::amount;
+use crate::position::Position;
+use crate::trader::{Trade, TradeSide};
+use crate::timezone::ReportTimezone;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Default time between reconciliations, in feed time
+pub const DEFAULT_RECONCILE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Balances and fill count an execution venue reports for our account
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VenueAccount {
+    /// Signed ETH balance change since the session started
+    pub base_eth: f64,
+    /// USDC balance change since the session started, net of fees
+    pub quote_usd: f64,
+    pub fees_usd: f64,
+    pub fills: u64,
+}
+
+impl VenueAccount {
+    /// Settle a fill the venue executed for us
+    pub fn apply(&mut self, trade: &Trade) {
+        let notional = amount::value_of(trade.price, 0.0, trade.amount_eth);
+        match trade.side {
+            TradeSide::Buy => {
+                self.base_eth += trade.amount_eth;
+                self.quote_usd = amount::sum([self.quote_usd, -notional, -trade.fees_usd]);
+            }
+            TradeSide::Sell => {
+                self.base_eth -= trade.amount_eth;
+                self.quote_usd = amount::sum([self.quote_usd, notional, -trade.fees_usd]);
+            }
+        }
+        self.fees_usd = amount::add(self.fees_usd, trade.fees_usd);
+        self.fills += 1;
+    }
+}
+
+/// How far local and venue balances may drift before it counts as a break
+#[derive(Debug, Clone, Copy)]
+pub struct ReconcileTolerance {
+    pub position_eth: f64,
+    pub cash_usd: f64,
+}
+
+impl Default for ReconcileTolerance {
+    fn default() -> Self {
+        Self {
+            position_eth: 1e-6,
+            cash_usd: 0.01,
+        }
+    }
+}
+
+/// One comparison of the local ledger against the venue's
+#[derive(Debug, Clone, Copy)]
+pub struct Reconciliation {
+    pub timestamp: i64,
+    /// Position built from recorded trades plus fills still confirming
+    pub local: Position,
+    pub local_fills: u64,
+    pub venue: VenueAccount,
+}
+
+impl Reconciliation {
+    /// Venue minus local ETH
+    pub fn position_gap(&self) -> f64 {
+        self.venue.base_eth - self.local.net_eth
+    }
+
+    /// Venue minus local USDC
+    pub fn cash_gap(&self) -> f64 {
+        self.venue.quote_usd - self.local.cash_usd
+    }
+
+    pub fn fill_gap(&self) -> i64 {
+        self.venue.fills as i64 - self.local_fills as i64
+    }
+
+    pub fn is_break(&self, tolerance: &ReconcileTolerance) -> bool {
+        self.position_gap().abs() > tolerance.position_eth
+            || self.cash_gap().abs() > tolerance.cash_usd
+            || self.fill_gap() != 0
+    }
+}
+
+impl fmt::Display for Reconciliation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "position local {:+.6} / venue {:+.6} ETH (Δ {:+.6}) │ cash local ${:.2} / venue ${:.2} (Δ {:+.2}) │ fills {} / {}",
+            self.local.net_eth, self.venue.base_eth, self.position_gap(),
+            self.local.cash_usd, self.venue.quote_usd, self.cash_gap(),
+            self.local_fills, self.venue.fills)
+    }
+}
+
+#[derive(Debug, Default)]
+struct ReconcilerState {
+    last_check: Option<i64>,
+    checks: u64,
+    breaks: Vec<Reconciliation>,
+    /// Whether the latest check found a break, so a persisting one is only logged once
+    diverged: bool,
+}
+
+/// Periodically checks locally tracked position and PnL against what the execution venue reports,
+/// keeping every divergence found
+pub struct Reconciler {
+    interval: Duration,
+    tolerance: ReconcileTolerance,
+    state: Mutex<ReconcilerState>,
+}
+
+impl Default for Reconciler {
+    fn default() -> Self {
+        Self::new(DEFAULT_RECONCILE_INTERVAL, ReconcileTolerance::default())
+    }
+}
+
+impl Reconciler {
+    pub fn new(interval: Duration, tolerance: ReconcileTolerance) -> Self {
+        Self {
+            interval,
+            tolerance,
+            state: Mutex::new(ReconcilerState::default()),
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn tolerance(&self) -> ReconcileTolerance {
+        self.tolerance
+    }
+
+    /// Whether a check is due at feed time `timestamp`
+    pub fn is_due(&self, timestamp: i64) -> bool {
+        match self.state.lock().unwrap().last_check {
+            Some(last) => timestamp - last >= self.interval.as_millis() as i64,
+            None => true,
+        }
+    }
+
+    /// Compare the local ledger against the venue's. Returns the reconciliation when it opens a new
+    /// break; a break that persists across checks is only returned the first time.
+    pub fn check(&self, timestamp: i64, local: Position, local_fills: u64, venue: VenueAccount) -> Option<Reconciliation> {
+        let reconciliation = Reconciliation {
+            timestamp,
+            local,
+            local_fills,
+            venue,
+        };
+        let mut state = self.state.lock().unwrap();
+        state.last_check = Some(timestamp);
+        state.checks += 1;
+
+        let diverged = reconciliation.is_break(&self.tolerance);
+        let opened = diverged && !state.diverged;
+        state.diverged = diverged;
+        if diverged {
+            state.breaks.push(reconciliation);
+        }
+        opened.then_some(reconciliation)
+    }
+
+    pub fn checks(&self) -> u64 {
+        self.state.lock().unwrap().checks
+    }
+
+    /// Every check that found a divergence
+    pub fn breaks(&self) -> Vec<Reconciliation> {
+        self.state.lock().unwrap().breaks.clone()
+    }
+
+    pub fn report(&self, timezone: &ReportTimezone) -> String {
+        let state = self.state.lock().unwrap();
+        let mut out = format!("Reconciliation: {} checks every {}s │ {} diverged",
+            state.checks, self.interval.as_secs(), state.breaks.len());
+        if state.breaks.is_empty() {
+            out.push_str(" │ venue and local ledgers agree\n");
+            return out;
+        }
+        out.push_str(if state.diverged { " │ still diverged at the last check\n" } else { " │ resolved by the last check\n" });
+        if let Some(worst) = state.breaks.iter().max_by(|a, b| a.position_gap().abs().total_cmp(&b.position_gap().abs())) {
+            out.push_str(&format!("  Largest at {}: {}\n", timezone.format_millis(worst.timestamp, "%H:%M:%S %Z"), worst));
+        }
+        out
+    }
+}
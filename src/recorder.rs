@@ -0,0 +1,327 @@
+use crate::aggregator::{Quote, Source};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// A single recorded quote update
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Tick {
+    pub timestamp: i64,
+    pub source: Source,
+    pub bid: f64,
+    pub ask: f64,
+}
+
+impl Tick {
+    pub fn new(source: Source, quote: &Quote) -> Self {
+        Self {
+            timestamp: quote.timestamp,
+            source,
+            bid: quote.bid,
+            ask: quote.ask,
+        }
+    }
+
+    pub fn quote(&self) -> Quote {
+        Quote {
+            bid: self.bid,
+            ask: self.ask,
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordFormat {
+    Csv,
+    Jsonl,
+    /// Compact delta-encoded binary, see `BinaryEncoder`
+    Binary,
+}
+
+impl RecordFormat {
+    /// Pick the format from a file extension (`.csv`, `.jsonl` / `.json`, `.bin`)
+    pub fn from_path(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("csv") => Ok(RecordFormat::Csv),
+            Some("jsonl") | Some("json") => Ok(RecordFormat::Jsonl),
+            Some("bin") => Ok(RecordFormat::Binary),
+            _ => bail!(
+                "cannot infer tick format from {} (use .csv, .jsonl or .bin)",
+                path.display()
+            ),
+        }
+    }
+}
+
+const BINARY_MAGIC: &[u8; 4] = b"MMT1";
+/// Prices are stored as integer multiples of 1e-6
+const PRICE_SCALE: f64 = 1_000_000.0;
+
+/// Previous values per source, which each binary record is delta-encoded against
+#[derive(Debug, Default, Clone, Copy)]
+struct DeltaState {
+    bid: i64,
+    spread: i64,
+}
+
+/// Binary tick encoding: every record is
+/// `varint(zigzag(Δtimestamp)) | source byte | varint(zigzag(Δbid)) | varint(zigzag(Δspread))`
+/// with bid/spread in 1e-6 units and deltas taken against the same source's previous tick.
+#[derive(Debug, Default)]
+struct BinaryEncoder {
+    last_timestamp: i64,
+    sources: [DeltaState; Source::ALL.len()],
+}
+
+impl BinaryEncoder {
+    fn encode(&mut self, tick: &Tick, out: &mut Vec<u8>) {
+        let idx = source_index(tick.source);
+        let bid = (tick.bid * PRICE_SCALE).round() as i64;
+        let spread = ((tick.ask - tick.bid) * PRICE_SCALE).round() as i64;
+        let prev = self.sources[idx];
+
+        write_varint(out, zigzag(tick.timestamp - self.last_timestamp));
+        out.push(idx as u8);
+        write_varint(out, zigzag(bid - prev.bid));
+        write_varint(out, zigzag(spread - prev.spread));
+
+        self.last_timestamp = tick.timestamp;
+        self.sources[idx] = DeltaState { bid, spread };
+    }
+
+    fn decode(&mut self, data: &[u8], pos: &mut usize) -> Result<Tick> {
+        let timestamp = self.last_timestamp.checked_add(unzigzag(read_varint(data, pos)?)).context("tick delta overflows")?;
+        let idx = *data.get(*pos).context("truncated tick record")? as usize;
+        *pos += 1;
+        let source = *Source::ALL
+            .get(idx)
+            .with_context(|| format!("unknown source index {}", idx))?;
+        let bid = self.sources[idx].bid.checked_add(unzigzag(read_varint(data, pos)?)).context("tick delta overflows")?;
+        let spread = self.sources[idx].spread.checked_add(unzigzag(read_varint(data, pos)?)).context("tick delta overflows")?;
+        let ask = bid.checked_add(spread).context("tick delta overflows")?;
+
+        self.last_timestamp = timestamp;
+        self.sources[idx] = DeltaState { bid, spread };
+
+        Ok(Tick {
+            timestamp,
+            source,
+            bid: bid as f64 / PRICE_SCALE,
+            ask: ask as f64 / PRICE_SCALE,
+        })
+    }
+}
+
+fn source_index(source: Source) -> usize {
+    Source::ALL.iter().position(|&s| s == source).unwrap()
+}
+
+fn zigzag(v: i64) -> u64 {
+    ((v << 1) ^ (v >> 63)) as u64
+}
+
+fn unzigzag(v: u64) -> i64 {
+    ((v >> 1) as i64) ^ -((v & 1) as i64)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+fn read_varint(data: &[u8], pos: &mut usize) -> Result<u64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = *data.get(*pos).context("truncated varint")?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+        if shift >= 64 {
+            bail!("varint overflow");
+        }
+    }
+}
+
+struct RecorderState {
+    writer: BufWriter<File>,
+    encoder: BinaryEncoder,
+    buf: Vec<u8>,
+}
+
+/// Appends every quote update to a tick file during a session
+pub struct TickRecorder {
+    state: Mutex<RecorderState>,
+    format: RecordFormat,
+}
+
+impl TickRecorder {
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let format = RecordFormat::from_path(path)?;
+        let file = File::create(path)
+            .with_context(|| format!("failed to create tick file {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+
+        match format {
+            RecordFormat::Csv => writeln!(writer, "timestamp,source,bid,ask")?,
+            RecordFormat::Binary => writer.write_all(BINARY_MAGIC)?,
+            RecordFormat::Jsonl => {}
+        }
+
+        Ok(Self {
+            state: Mutex::new(RecorderState {
+                writer,
+                encoder: BinaryEncoder::default(),
+                buf: Vec::with_capacity(32),
+            }),
+            format,
+        })
+    }
+
+    pub fn format(&self) -> RecordFormat {
+        self.format
+    }
+
+    pub fn record(&self, source: Source, quote: &Quote) -> Result<()> {
+        self.record_tick(&Tick::new(source, quote))
+    }
+
+    pub fn record_tick(&self, tick: &Tick) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let RecorderState {
+            writer,
+            encoder,
+            buf,
+        } = &mut *state;
+
+        match self.format {
+            RecordFormat::Csv => writeln!(
+                writer,
+                "{},{},{},{}",
+                tick.timestamp,
+                tick.source.name(),
+                tick.bid,
+                tick.ask
+            )?,
+            RecordFormat::Jsonl => {
+                serde_json::to_writer(&mut *writer, tick)?;
+                writeln!(writer)?;
+            }
+            RecordFormat::Binary => {
+                buf.clear();
+                encoder.encode(tick, buf);
+                writer.write_all(buf)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        self.state.lock().unwrap().writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Load a tick file written by `TickRecorder`, sorted by timestamp
+pub fn read_ticks(path: impl AsRef<Path>) -> Result<Vec<Tick>> {
+    let path = path.as_ref();
+    let format = RecordFormat::from_path(path)?;
+
+    if format == RecordFormat::Binary {
+        let data = std::fs::read(path)
+            .with_context(|| format!("failed to read tick file {}", path.display()))?;
+        let mut ticks = decode_binary(&data)
+            .with_context(|| format!("{}: invalid binary tick file", path.display()))?;
+        ticks.sort_by_key(|t| t.timestamp);
+        return Ok(ticks);
+    }
+
+    let data = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read tick file {}", path.display()))?;
+
+    let mut ticks = Vec::new();
+    for (i, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (format == RecordFormat::Csv && i == 0) {
+            continue;
+        }
+
+        let tick = match format {
+            RecordFormat::Csv => parse_csv_tick(line),
+            RecordFormat::Jsonl => serde_json::from_str(line).map_err(Into::into),
+            RecordFormat::Binary => unreachable!("binary files are decoded above"),
+        }
+        .and_then(checked_tick)
+        .with_context(|| format!("{}:{}: invalid tick", path.display(), i + 1))?;
+        ticks.push(tick);
+    }
+
+    ticks.sort_by_key(|t| t.timestamp);
+    Ok(ticks)
+}
+
+fn decode_binary(data: &[u8]) -> Result<Vec<Tick>> {
+    if !data.starts_with(BINARY_MAGIC) {
+        bail!("missing {:?} header", std::str::from_utf8(BINARY_MAGIC).unwrap());
+    }
+
+    let mut decoder = BinaryEncoder::default();
+    let mut pos = BINARY_MAGIC.len();
+    let mut ticks = Vec::new();
+    while pos < data.len() {
+        let tick = decoder.decode(data, &mut pos).and_then(checked_tick);
+        ticks.push(tick.with_context(|| format!("tick {}", ticks.len() + 1))?);
+    }
+    Ok(ticks)
+}
+
+/// `tick`, or an error when its quote can't have come from a venue: a bid or ask that isn't a
+/// positive finite price, or a bid above the ask
+fn checked_tick(tick: Tick) -> Result<Tick> {
+    for (side, price) in [("bid", tick.bid), ("ask", tick.ask)] {
+        if !price.is_finite() || price <= 0.0 {
+            bail!("{} {} is not a positive price", side, price);
+        }
+    }
+    if tick.bid > tick.ask {
+        bail!("crossed quote: bid {} above ask {}", tick.bid, tick.ask);
+    }
+    Ok(tick)
+}
+
+/// Rewrite a tick file in another format (chosen by the output extension)
+pub fn convert_ticks(input: impl AsRef<Path>, output: impl AsRef<Path>) -> Result<usize> {
+    let ticks = read_ticks(input)?;
+    let recorder = TickRecorder::create(output)?;
+    for tick in &ticks {
+        recorder.record_tick(tick)?;
+    }
+    recorder.flush()?;
+    Ok(ticks.len())
+}
+
+fn parse_csv_tick(line: &str) -> Result<Tick> {
+    let fields: Vec<&str> = line.split(',').collect();
+    if fields.len() != 4 {
+        bail!("expected 4 fields, got {}", fields.len());
+    }
+
+    Ok(Tick {
+        timestamp: fields[0].parse()?,
+        source: fields[1].parse()?,
+        bid: fields[2].parse()?,
+        ask: fields[3].parse()?,
+    })
+}
@@ -0,0 +1,150 @@
+use crate::aggregator::{AggregatedPrices, PriceFeed, QuoteUpdate, QUOTE_CHANNEL_CAPACITY};
+use crate::clock::{Clock, SimulatedClock};
+use crate::recorder::{read_ticks, Tick};
+use anyhow::{bail, Result};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// How fast recorded time is played back relative to wall-clock time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    RealTime,
+    Multiplier(f64),
+    AsFastAsPossible,
+}
+
+impl std::str::FromStr for ReplaySpeed {
+    type Err = anyhow::Error;
+
+    /// Accepts `realtime`, `max`, or a multiplier such as `10x` / `2.5`
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "realtime" | "1x" => Ok(ReplaySpeed::RealTime),
+            "max" | "fast" => Ok(ReplaySpeed::AsFastAsPossible),
+            other => {
+                let factor: f64 = other.trim_end_matches('x').parse()?;
+                if !(factor.is_finite() && factor > 0.0) {
+                    bail!("replay speed must be positive, got {}", s);
+                }
+                Ok(ReplaySpeed::Multiplier(factor))
+            }
+        }
+    }
+}
+
+struct ReplayState {
+    cursor: usize,
+    prices: AggregatedPrices,
+}
+
+/// Price feed that plays back a recorded tick file instead of live sources
+pub struct ReplaySource {
+    ticks: Vec<Tick>,
+    speed: ReplaySpeed,
+    start_time: i64,
+    /// Recorded time the replay has reached
+    clock: Arc<SimulatedClock>,
+    state: Mutex<ReplayState>,
+    events: broadcast::Sender<QuoteUpdate>,
+}
+
+impl ReplaySource {
+    pub fn load(path: impl AsRef<Path>, speed: ReplaySpeed) -> Result<Self> {
+        let ticks = read_ticks(path.as_ref())?;
+        if ticks.is_empty() {
+            bail!("tick file {} is empty", path.as_ref().display());
+        }
+        Ok(Self::from_ticks(ticks, speed))
+    }
+
+    pub fn from_ticks(mut ticks: Vec<Tick>, speed: ReplaySpeed) -> Self {
+        ticks.sort_by_key(|t| t.timestamp);
+        let start_time = ticks.first().map_or(0, |t| t.timestamp);
+
+        Self {
+            ticks,
+            speed,
+            start_time,
+            clock: Arc::new(SimulatedClock::new(start_time)),
+            state: Mutex::new(ReplayState {
+                cursor: 0,
+                prices: AggregatedPrices::default(),
+            }),
+            events: broadcast::channel(QUOTE_CHANNEL_CAPACITY).0,
+        }
+    }
+
+    pub fn tick_count(&self) -> usize {
+        self.ticks.len()
+    }
+
+    /// Recorded time span covered by the file
+    pub fn duration(&self) -> Duration {
+        let end = self.ticks.last().map_or(self.start_time, |t| t.timestamp);
+        Duration::from_millis((end - self.start_time).max(0) as u64)
+    }
+
+    /// Current replay time in epoch milliseconds
+    pub fn clock(&self) -> i64 {
+        self.clock.now_ms()
+    }
+
+    /// The replay's recorded time as a clock, for stamping trades and orders on the same timeline
+    pub fn sim_clock(&self) -> Arc<SimulatedClock> {
+        Arc::clone(&self.clock)
+    }
+
+    /// Recorded time elapsed since the first tick
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_millis((self.clock() - self.start_time).max(0) as u64)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state.lock().unwrap().cursor >= self.ticks.len()
+    }
+
+    /// Move the replay clock forward by `step`, pacing according to the replay
+    /// speed, and apply every tick up to the new time. Returns false once the
+    /// recording is exhausted.
+    pub async fn advance(&self, step: Duration) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+
+        match self.speed {
+            ReplaySpeed::RealTime => tokio::time::sleep(step).await,
+            ReplaySpeed::Multiplier(factor) => tokio::time::sleep(step.div_f64(factor)).await,
+            ReplaySpeed::AsFastAsPossible => tokio::task::yield_now().await,
+        }
+
+        let mut state = self.state.lock().unwrap();
+        self.clock.advance(step);
+        let clock = self.clock.now_ms();
+
+        while let Some(tick) = self.ticks.get(state.cursor) {
+            if tick.timestamp > clock {
+                break;
+            }
+            state.prices.set(tick.source, tick.quote());
+            state.cursor += 1;
+            let _ = self.events.send(QuoteUpdate {
+                source: tick.source,
+                quote: tick.quote(),
+            });
+        }
+
+        true
+    }
+}
+
+impl PriceFeed for ReplaySource {
+    async fn get_prices(&self) -> AggregatedPrices {
+        self.state.lock().unwrap().prices.clone()
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<QuoteUpdate> {
+        self.events.subscribe()
+    }
+}
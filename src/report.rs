@@ -0,0 +1,451 @@
+use crate::config::Config;
+use crate::heatmap::{FillHeatmap, DEFAULT_HEATMAP_BUCKET_BPS};
+use crate::pnl_tracker::{EquityPoint, PnLStats, PnLTracker, VenueRouting};
+use crate::position::Position;
+use crate::timezone::ReportTimezone;
+use crate::trader::{LiquidityRole, Trade, TradeSide};
+use anyhow::{bail, Context, Result};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Width of the Markdown equity sparkline, in characters
+const SPARKLINE_WIDTH: usize = 72;
+
+/// Most recent fills the report's blotter lists; the exports carry every one
+pub const REPORT_TRADES: usize = 1000;
+
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Html,
+    Markdown,
+}
+
+impl ReportFormat {
+    /// Format named by the file extension: `.html`/`.htm` or `.md`/`.markdown`
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+        match extension.as_str() {
+            "html" | "htm" => Ok(ReportFormat::Html),
+            "md" | "markdown" => Ok(ReportFormat::Markdown),
+            _ => bail!("can't tell the report format of {} (expected a .html or .md file)", path.display()),
+        }
+    }
+}
+
+/// Everything a finished session is shared with: the config it ran under, its stats, the equity
+/// curve, every fill, the fill heatmap and the per-venue breakdown, rendered as one standalone file
+#[derive(Debug, Clone)]
+pub struct SessionReport {
+    pub session_id: String,
+    pub name: Option<String>,
+    pub cycles: u64,
+    /// Resolved config as TOML, credentials redacted
+    pub config: String,
+    pub stats: PnLStats,
+    pub position: Position,
+    pub last_mid: Option<f64>,
+    pub equity: Vec<EquityPoint>,
+    /// The latest `REPORT_TRADES` fills, oldest first
+    pub trades: Vec<Trade>,
+    pub routing: Vec<VenueRouting>,
+    pub heatmap: FillHeatmap,
+    pub timezone: ReportTimezone,
+}
+
+/// Renderer-neutral piece of a report section
+enum Block {
+    /// Label and value rows
+    Fields(Vec<(&'static str, String)>),
+    Table { header: Vec<String>, rows: Vec<Vec<String>> },
+    Equity(Vec<(i64, f64)>),
+    Code(String),
+    Note(String),
+}
+
+impl SessionReport {
+    pub async fn collect(session_id: &str, config: &Config, tracker: &PnLTracker) -> Result<Self> {
+        let stats = tracker.get_stats().await;
+        Ok(Self {
+            session_id: session_id.to_string(),
+            name: None,
+            cycles: 0,
+            config: toml::to_string(&config.redacted()).context("failed to serialize config")?,
+            position: Position::default(),
+            last_mid: None,
+            equity: tracker.equity_series().await,
+            trades: tracker.get_recent_trades(REPORT_TRADES).await,
+            routing: tracker.venue_routing().await,
+            heatmap: tracker.fill_heatmap(DEFAULT_HEATMAP_BUCKET_BPS).await,
+            timezone: tracker.timezone(),
+            stats,
+        })
+    }
+
+    pub fn with_name(mut self, name: Option<String>) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn with_cycles(mut self, cycles: u64) -> Self {
+        self.cycles = cycles;
+        self
+    }
+
+    /// Inventory at the end of the session and the mid it was marked at
+    pub fn with_position(mut self, position: Position, last_mid: Option<f64>) -> Self {
+        self.position = position;
+        self.last_mid = last_mid;
+        self
+    }
+
+    /// Heatmap bucketed other than the default `DEFAULT_HEATMAP_BUCKET_BPS`
+    pub fn with_heatmap(mut self, heatmap: FillHeatmap) -> Self {
+        self.heatmap = heatmap;
+        self
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        let title = format!("Session {}", self.name.as_deref().unwrap_or(&self.session_id));
+        let sections = self.sections();
+        match format {
+            ReportFormat::Html => render_html(&title, &sections),
+            ReportFormat::Markdown => render_markdown(&title, &sections),
+        }
+    }
+
+    /// Render in the format the extension of `path` names and write it there
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<ReportFormat> {
+        let path = path.as_ref();
+        let format = ReportFormat::from_path(path)?;
+        std::fs::write(path, self.render(format)).with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(format)
+    }
+
+    fn sections(&self) -> Vec<(&'static str, Vec<Block>)> {
+        let mut sections = vec![
+            ("Session", vec![Block::Fields(self.session_fields())]),
+            ("Results", vec![Block::Fields(self.result_fields())]),
+        ];
+
+        let equity: Vec<(i64, f64)> = self.equity.iter().map(|p| (p.timestamp, p.equity())).collect();
+        sections.push(("Equity Curve", vec![if equity.len() > 1 {
+            Block::Equity(equity)
+        } else {
+            Block::Note("Too few marks for an equity curve.".to_string())
+        }]));
+
+        let mut venues = Vec::new();
+        if !self.stats.venues.is_empty() {
+            venues.push(Block::Table {
+                header: ["Venue", "Fills", "Notional", "Fill PnL", "Marks", "Mark PnL"].map(String::from).to_vec(),
+                rows: self
+                    .stats
+                    .venues
+                    .iter()
+                    .map(|v| vec![
+                        v.venue.name().to_string(),
+                        v.fills.to_string(),
+                        usd(v.fill_notional_usd),
+                        usd(v.fill_pnl),
+                        v.marks.to_string(),
+                        usd(v.mark_pnl),
+                    ])
+                    .collect(),
+            });
+        }
+        if !self.routing.is_empty() {
+            venues.push(Block::Table {
+                header: ["Routed to", "Child fills", "Notional", "Fees"].map(String::from).to_vec(),
+                rows: self
+                    .routing
+                    .iter()
+                    .map(|r| vec![r.venue.name().to_string(), r.fills.to_string(), usd(r.notional_usd), usd(r.fees_usd)])
+                    .collect(),
+            });
+        }
+        if venues.is_empty() {
+            venues.push(Block::Note("No fills.".to_string()));
+        }
+        sections.push(("Venues", venues));
+
+        sections.push(("Fill Heatmap", self.heatmap_blocks()));
+        sections.push(("Trades", self.blotter()));
+        sections.push(("Configuration", vec![Block::Code(self.config.clone())]));
+        sections
+    }
+
+    fn session_fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![("Session id", self.session_id.clone())];
+        if let Some(name) = &self.name {
+            fields.push(("Name", name.clone()));
+        }
+        if let (Some(first), Some(last)) = (self.equity.first(), self.equity.last()) {
+            fields.push(("Started", self.timezone.format_datetime(first.timestamp)));
+            fields.push(("Ended", self.timezone.format_datetime(last.timestamp)));
+        }
+        fields.push(("Cycles", self.cycles.to_string()));
+        fields.push(("Generated", self.timezone.format_datetime(chrono::Utc::now().timestamp_millis())));
+        fields
+    }
+
+    fn result_fields(&self) -> Vec<(&'static str, String)> {
+        let s = &self.stats;
+        let perf = &s.performance;
+        let optional = |value: Option<f64>| value.map_or("-".to_string(), |v| format!("{:.2}", v));
+        let mut fields = vec![
+            ("Trades", format!("{} ({} buy / {} sell, {} maker / {} taker)",
+                s.total_trades, s.buy_trades, s.sell_trades, s.maker_trades, s.taker_trades)),
+            ("Gross PnL", usd(s.gross_pnl)),
+            ("Net PnL", usd(s.total_pnl)),
+            ("Fees", usd(s.total_fees)),
+            ("Realized PnL", format!("{} ({})", usd(s.realized_pnl), s.cost_basis.label())),
+            ("Unrealized PnL", usd(s.unrealized_pnl)),
+            ("Marked PnL", usd(s.marked_pnl())),
+            ("Notional", usd(s.total_notional)),
+            ("PnL / notional", format!("{:.2} bps", s.pnl_per_notional_bps())),
+            ("Sharpe (annualized)", optional(perf.sharpe_ratio())),
+            ("Max drawdown", usd(perf.max_drawdown)),
+            ("Hit rate", format!("{:.1}%", perf.hit_rate() * 100.0)),
+            ("Profit factor", optional(perf.profit_factor())),
+            ("Avg execution prob", format!("{:.1}%", s.avg_execution_prob * 100.0)),
+        ];
+        if s.hedge_trades > 0 {
+            fields.push(("Hedges", format!("{} ({} PnL)", s.hedge_trades, usd(s.hedge_pnl))));
+        }
+        fields.push(("Final position", match self.last_mid {
+            Some(mid) => format!("{} (marked at ${:.2})", self.position, mid),
+            None => self.position.to_string(),
+        }));
+        fields
+    }
+
+    /// Fills per distance bucket and hour, as the console heatmap prints them
+    fn heatmap_blocks(&self) -> Vec<Block> {
+        let heatmap = &self.heatmap;
+        if heatmap.cells.is_empty() {
+            return vec![Block::Note("No quoted attempts.".to_string())];
+        }
+        let hours = heatmap.hours();
+        let mut header = vec!["Distance (bps)".to_string()];
+        header.extend(hours.iter().map(|hour| format!("{:02}h", hour)));
+        header.push("Fill rate".to_string());
+        let rows = heatmap
+            .distances()
+            .into_iter()
+            .map(|distance| {
+                let mut row = vec![format!("[{:+.1}, {:+.1})", distance, distance + heatmap.bucket_bps)];
+                row.extend(hours.iter().map(|&hour| heatmap.cell(distance, hour).map_or("·".to_string(), |c| c.fills.to_string())));
+                row.push(format!("{:.1}%", 100.0 * heatmap.distance_fill_rate(distance)));
+                row
+            })
+            .collect();
+        vec![
+            Block::Note(format!("{} fills from {} attempts, by distance behind the touch and hour ({}).",
+                heatmap.fills(), heatmap.attempts(), heatmap.timezone)),
+            Block::Table { header, rows },
+        ]
+    }
+
+    fn blotter(&self) -> Vec<Block> {
+        if self.trades.is_empty() {
+            return vec![Block::Note("No fills.".to_string())];
+        }
+        let tagged = self.trades.iter().any(|t| t.tag.is_some());
+        let mut header = ["ID", "Time", "Side", "Role", "Venue", "Price", "ETH", "Notional", "Fees", "PnL"].map(String::from).to_vec();
+        if tagged {
+            header.push("Tag".to_string());
+        }
+        let rows = self
+            .trades
+            .iter()
+            .map(|t| {
+                let side = match t.side {
+                    TradeSide::Buy => "buy",
+                    TradeSide::Sell => "sell",
+                };
+                let role = match t.role {
+                    LiquidityRole::Maker => "maker",
+                    LiquidityRole::Taker => "taker",
+                };
+                let mut row = vec![
+                    t.id.to_string(),
+                    self.timezone.format_time(t.timestamp),
+                    if t.hedge { format!("{} (hedge)", side) } else { side.to_string() },
+                    role.to_string(),
+                    t.venue.map_or("-", |v| v.name()).to_string(),
+                    format!("{:.2}", t.price),
+                    format!("{:.4}", t.amount_eth),
+                    usd(t.notional_usd),
+                    usd(t.fees_usd),
+                    usd(t.pnl),
+                ];
+                if tagged {
+                    row.push(t.tag.clone().unwrap_or_default());
+                }
+                row
+            })
+            .collect();
+        let table = Block::Table { header, rows };
+        let total = self.stats.total_trades as usize;
+        if total > self.trades.len() {
+            return vec![
+                Block::Note(format!("The latest {} of {} fills; the trade exports list every one.", self.trades.len(), total)),
+                table,
+            ];
+        }
+        vec![table]
+    }
+}
+
+fn usd(value: f64) -> String {
+    if value < 0.0 {
+        format!("-${:.2}", -value)
+    } else {
+        format!("${:.2}", value)
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn render_html(title: &str, sections: &[(&'static str, Vec<Block>)]) -> String {
+    let mut out = String::new();
+    let _ = write!(out, "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n", escape_html(title));
+    out.push_str("<style>\n\
+        body { font-family: system-ui, sans-serif; margin: 2em auto; max-width: 1100px; color: #222; }\n\
+        table { border-collapse: collapse; margin: 1em 0; font-size: 0.9em; }\n\
+        th, td { border: 1px solid #ddd; padding: 4px 8px; text-align: right; }\n\
+        th { background: #f4f4f4; } td:first-child, th:first-child { text-align: left; }\n\
+        pre { background: #f7f7f7; padding: 1em; overflow-x: auto; font-size: 0.85em; }\n\
+        .blotter { max-height: 600px; overflow-y: auto; display: inline-block; }\n\
+        </style>\n</head>\n<body>\n");
+    let _ = writeln!(out, "<h1>{}</h1>", escape_html(title));
+    for (heading, blocks) in sections {
+        let _ = writeln!(out, "<h2>{}</h2>", heading);
+        for block in blocks {
+            match block {
+                Block::Fields(fields) => {
+                    out.push_str("<table>\n");
+                    for (label, value) in fields {
+                        let _ = writeln!(out, "<tr><th>{}</th><td>{}</td></tr>", label, escape_html(value));
+                    }
+                    out.push_str("</table>\n");
+                }
+                Block::Table { header, rows } => {
+                    out.push_str("<div class=\"blotter\"><table>\n<tr>");
+                    for column in header {
+                        let _ = write!(out, "<th>{}</th>", escape_html(column));
+                    }
+                    out.push_str("</tr>\n");
+                    for row in rows {
+                        out.push_str("<tr>");
+                        for cell in row {
+                            let _ = write!(out, "<td>{}</td>", escape_html(cell));
+                        }
+                        out.push_str("</tr>\n");
+                    }
+                    out.push_str("</table></div>\n");
+                }
+                Block::Equity(points) => out.push_str(&equity_svg(points)),
+                Block::Code(code) => {
+                    let _ = writeln!(out, "<pre>{}</pre>", escape_html(code));
+                }
+                Block::Note(note) => {
+                    let _ = writeln!(out, "<p>{}</p>", escape_html(note));
+                }
+            }
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Inline SVG line chart of equity over time, with the zero line when the curve crosses it
+fn equity_svg(points: &[(i64, f64)]) -> String {
+    const WIDTH: f64 = 960.0;
+    const HEIGHT: f64 = 280.0;
+    const PAD: f64 = 50.0;
+    let (t0, t1) = (points[0].0 as f64, points[points.len() - 1].0 as f64);
+    let low = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let high = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+    let span = if high > low { high - low } else { 1.0 };
+    let x = |t: f64| PAD + (t - t0) / (t1 - t0).max(1.0) * (WIDTH - 2.0 * PAD);
+    let y = |v: f64| HEIGHT - PAD - (v - low) / span * (HEIGHT - 2.0 * PAD);
+
+    let mut svg = String::new();
+    let _ = writeln!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+        WIDTH, HEIGHT, WIDTH, HEIGHT);
+    let _ = writeln!(svg, "<rect x=\"{p}\" y=\"{p}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"#ccc\"/>",
+        WIDTH - 2.0 * PAD, HEIGHT - 2.0 * PAD, p = PAD);
+    if low < 0.0 && high > 0.0 {
+        let _ = writeln!(svg, "<line x1=\"{}\" y1=\"{z:.1}\" x2=\"{}\" y2=\"{z:.1}\" stroke=\"#999\" stroke-dasharray=\"4 4\"/>",
+            PAD, WIDTH - PAD, z = y(0.0));
+    }
+    let path: Vec<String> = points.iter().map(|&(t, v)| format!("{:.1},{:.1}", x(t as f64), y(v))).collect();
+    let _ = writeln!(svg, "<polyline fill=\"none\" stroke=\"#2a6fdb\" stroke-width=\"1.5\" points=\"{}\"/>", path.join(" "));
+    let _ = writeln!(svg, "<text x=\"4\" y=\"{:.1}\" font-size=\"11\">{}</text>", y(high) + 4.0, usd(high));
+    let _ = writeln!(svg, "<text x=\"4\" y=\"{:.1}\" font-size=\"11\">{}</text>", y(low) + 4.0, usd(low));
+    let _ = writeln!(svg, "<text x=\"{}\" y=\"{}\" font-size=\"11\">{:.1} min</text>",
+        WIDTH - PAD - 40.0, HEIGHT - PAD + 16.0, (t1 - t0) / 60_000.0);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+fn render_markdown(title: &str, sections: &[(&'static str, Vec<Block>)]) -> String {
+    let mut out = format!("# {}\n", escape_markdown(title));
+    for (heading, blocks) in sections {
+        let _ = writeln!(out, "\n## {}\n", heading);
+        for block in blocks {
+            match block {
+                Block::Fields(fields) => {
+                    out.push_str("| | |\n|---|---|\n");
+                    for (label, value) in fields {
+                        let _ = writeln!(out, "| {} | {} |", label, escape_markdown(value));
+                    }
+                }
+                Block::Table { header, rows } => {
+                    let _ = writeln!(out, "| {} |", header.join(" | "));
+                    let alignment: Vec<&str> = (0..header.len()).map(|i| if i == 0 { "---" } else { "---:" }).collect();
+                    let _ = writeln!(out, "|{}|", alignment.join("|"));
+                    for row in rows {
+                        let cells: Vec<String> = row.iter().map(|c| escape_markdown(c)).collect();
+                        let _ = writeln!(out, "| {} |", cells.join(" | "));
+                    }
+                }
+                Block::Equity(points) => out.push_str(&equity_sparkline(points)),
+                Block::Code(code) => {
+                    let _ = write!(out, "```toml\n{}```\n", code);
+                }
+                Block::Note(note) => {
+                    let _ = writeln!(out, "{}", note);
+                }
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Equity as a one-line sparkline, each character the last mark in its slice of the session
+fn equity_sparkline(points: &[(i64, f64)]) -> String {
+    let low = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let high = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+    let span = if high > low { high - low } else { 1.0 };
+    let width = points.len().min(SPARKLINE_WIDTH);
+    let line: String = (0..width)
+        .map(|i| {
+            let value = points[((i + 1) * points.len()).div_ceil(width) - 1].1;
+            SPARK_LEVELS[((value - low) / span * (SPARK_LEVELS.len() - 1) as f64).round() as usize]
+        })
+        .collect();
+    format!("```\n{}\n```\n\nLow {} │ high {} │ final {} over {} marks\n",
+        line, usd(low), usd(high), usd(points[points.len() - 1].1), points.len())
+}
@@ -0,0 +1,310 @@
+use crate::aggregator::FeedEvent;
+use crate::amount::Exact;
+use crate::position::Position;
+use crate::timezone::ReportTimezone;
+use crate::trader::{Trade, TradeSide};
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+const HOUR_MS: i64 = 3_600_000;
+
+/// Limits the risk manager enforces; `None` disables a limit
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct RiskLimits {
+    /// Largest absolute net inventory before only the reducing side is quoted
+    pub max_inventory_eth: Option<f64>,
+    /// Largest fall of session PnL from its peak
+    pub max_drawdown_usd: Option<f64>,
+    /// Largest loss over any rolling hour
+    pub max_loss_per_hour_usd: Option<f64>,
+    pub max_consecutive_losses: Option<u32>,
+}
+
+impl RiskLimits {
+    pub fn is_empty(&self) -> bool {
+        self.max_inventory_eth.is_none()
+            && self.max_drawdown_usd.is_none()
+            && self.max_loss_per_hour_usd.is_none()
+            && self.max_consecutive_losses.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLimit {
+    Inventory,
+    Drawdown,
+    HourlyLoss,
+    ConsecutiveLosses,
+}
+
+impl fmt::Display for RiskLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskLimit::Inventory => write!(f, "inventory limit"),
+            RiskLimit::Drawdown => write!(f, "drawdown limit"),
+            RiskLimit::HourlyLoss => write!(f, "hourly loss limit"),
+            RiskLimit::ConsecutiveLosses => write!(f, "losing streak limit"),
+        }
+    }
+}
+
+/// What a tripped loss limit does for the rest of the session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BreachAction {
+    /// Stop quoting entirely
+    #[default]
+    Halt,
+    /// Only quote the side that reduces inventory
+    ReduceOnly,
+}
+
+impl FromStr for BreachAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "halt" => Ok(BreachAction::Halt),
+            "reduce-only" | "reduce_only" => Ok(BreachAction::ReduceOnly),
+            other => bail!("unknown breach action '{}' (expected halt or reduce-only)", other),
+        }
+    }
+}
+
+/// Which quotes the risk manager currently permits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "kebab-case")]
+pub enum RiskState {
+    Normal,
+    ReduceOnly { limit: RiskLimit },
+    Halted { limit: RiskLimit },
+}
+
+impl RiskState {
+    /// Whether an order on `side` may be quoted with inventory `position`
+    pub fn allows(&self, side: TradeSide, position: &Position) -> bool {
+        match self {
+            RiskState::Normal => true,
+            RiskState::ReduceOnly { .. } => match side {
+                TradeSide::Buy => position.net_eth < 0.0,
+                TradeSide::Sell => position.net_eth > 0.0,
+            },
+            RiskState::Halted { .. } => false,
+        }
+    }
+}
+
+/// A limit breach, as it happened
+#[derive(Debug, Clone)]
+pub struct RiskEvent {
+    pub timestamp: i64,
+    pub limit: RiskLimit,
+    pub message: String,
+}
+
+/// Tracks session PnL and inventory against `RiskLimits`. Loss limits latch like a kill switch:
+/// once tripped they hold for the rest of the session. The inventory limit clears once inventory comes back under it.
+#[derive(Debug)]
+pub struct RiskManager {
+    limits: RiskLimits,
+    action: BreachAction,
+    pnl: f64,
+    pnl_exact: Exact,
+    peak_pnl: f64,
+    max_drawdown: f64,
+    /// (timestamp, pnl) of trades in the last hour
+    recent: VecDeque<(i64, f64)>,
+    hour_loss: f64,
+    worst_hour: f64,
+    losing_streak: u32,
+    longest_losing_streak: u32,
+    tripped: Option<RiskLimit>,
+    over_inventory: bool,
+    events: Vec<RiskEvent>,
+    /// Price feed errors by source and kind
+    feed_errors: BTreeMap<(&'static str, &'static str), u32>,
+    /// Feed events the trading loop fell too far behind to read, so they're in no count above
+    missed_feed_events: u64,
+    /// Quote updates lost the same way, whose crosses resting orders never saw
+    missed_quote_updates: u64,
+}
+
+impl RiskManager {
+    pub fn new(limits: RiskLimits) -> Self {
+        Self {
+            limits,
+            action: BreachAction::default(),
+            pnl: 0.0,
+            pnl_exact: Exact::default(),
+            peak_pnl: 0.0,
+            max_drawdown: 0.0,
+            recent: VecDeque::new(),
+            hour_loss: 0.0,
+            worst_hour: 0.0,
+            losing_streak: 0,
+            longest_losing_streak: 0,
+            tripped: None,
+            over_inventory: false,
+            events: Vec::new(),
+            feed_errors: BTreeMap::new(),
+            missed_feed_events: 0,
+            missed_quote_updates: 0,
+        }
+    }
+
+    pub fn with_action(mut self, action: BreachAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    pub fn limits(&self) -> &RiskLimits {
+        &self.limits
+    }
+
+    pub fn action(&self) -> BreachAction {
+        self.action
+    }
+
+    pub fn events(&self) -> &[RiskEvent] {
+        &self.events
+    }
+
+    /// Update with a fill; `position` already includes it
+    pub fn on_trade(&mut self, trade: &Trade, position: &Position) {
+        self.pnl_exact.add(&mut self.pnl, trade.pnl);
+        self.peak_pnl = self.peak_pnl.max(self.pnl);
+        let drawdown = self.peak_pnl - self.pnl;
+        self.max_drawdown = self.max_drawdown.max(drawdown);
+
+        self.recent.push_back((trade.timestamp, trade.pnl));
+        while self.recent.front().is_some_and(|&(ts, _)| ts <= trade.timestamp - HOUR_MS) {
+            self.recent.pop_front();
+        }
+        let hour_loss = -self.recent.iter().map(|&(_, pnl)| pnl).sum::<f64>();
+        self.hour_loss = hour_loss;
+        self.worst_hour = self.worst_hour.max(hour_loss);
+
+        // Hedges pay the spread by design, so they don't count towards the losing streak
+        if !trade.hedge {
+            self.losing_streak = if trade.pnl < 0.0 { self.losing_streak + 1 } else { 0 };
+        }
+        self.longest_losing_streak = self.longest_losing_streak.max(self.losing_streak);
+
+        if self.tripped.is_none() {
+            let breach = if let Some(limit) = self.limits.max_drawdown_usd.filter(|&l| drawdown > l) {
+                Some((RiskLimit::Drawdown, format!("drawdown ${:.2} from peak ${:.2} exceeds ${:.2}", drawdown, self.peak_pnl, limit)))
+            } else if let Some(limit) = self.limits.max_loss_per_hour_usd.filter(|&l| hour_loss > l) {
+                Some((RiskLimit::HourlyLoss, format!("lost ${:.2} in the last hour, limit ${:.2}", hour_loss, limit)))
+            } else {
+                self.limits
+                    .max_consecutive_losses
+                    .filter(|&l| self.losing_streak >= l)
+                    .map(|l| (RiskLimit::ConsecutiveLosses, format!("{} losing trades in a row, limit {}", self.losing_streak, l)))
+            };
+            if let Some((limit, message)) = breach {
+                self.tripped = Some(limit);
+                self.events.push(RiskEvent {
+                    timestamp: trade.timestamp,
+                    limit,
+                    message,
+                });
+            }
+        }
+
+        if let Some(limit) = self.limits.max_inventory_eth {
+            let over = position.net_eth.abs() >= limit;
+            if over && !self.over_inventory {
+                self.events.push(RiskEvent {
+                    timestamp: trade.timestamp,
+                    limit: RiskLimit::Inventory,
+                    message: format!("net inventory {:+.4} ETH at limit {:.4} ETH", position.net_eth, limit),
+                });
+            }
+            self.over_inventory = over;
+        }
+    }
+
+    /// Count a price feed error, so the report shows the outages the session traded through
+    pub fn on_feed_event(&mut self, event: &FeedEvent) {
+        if let FeedEvent::Error { error, .. } = event {
+            *self.feed_errors.entry((error.feed().name(), error.kind())).or_default() += 1;
+        }
+    }
+
+    /// Count feed events dropped because the event channel lagged
+    pub fn on_feed_events_missed(&mut self, missed: u64) {
+        self.missed_feed_events += missed;
+    }
+
+    /// Count quote updates dropped because the quote channel lagged
+    pub fn on_quote_updates_missed(&mut self, missed: u64) {
+        self.missed_quote_updates += missed;
+    }
+
+    /// Price feed errors seen this session
+    pub fn feed_errors(&self) -> u32 {
+        self.feed_errors.values().sum()
+    }
+
+    pub fn missed_feed_events(&self) -> u64 {
+        self.missed_feed_events
+    }
+
+    pub fn missed_quote_updates(&self) -> u64 {
+        self.missed_quote_updates
+    }
+
+    pub fn state(&self) -> RiskState {
+        match (self.tripped, self.action) {
+            (Some(limit), BreachAction::Halt) => RiskState::Halted { limit },
+            (Some(limit), BreachAction::ReduceOnly) => RiskState::ReduceOnly { limit },
+            (None, _) if self.over_inventory => RiskState::ReduceOnly {
+                limit: RiskLimit::Inventory,
+            },
+            (None, _) => RiskState::Normal,
+        }
+    }
+
+    /// Fraction of the tightest loss limit still unused as of the last fill:
+    /// 1.0 without drawdown or hourly limits, 0.0 once any loss limit has tripped
+    pub fn budget_remaining(&self) -> f64 {
+        if self.tripped.is_some() {
+            return 0.0;
+        }
+        let drawdown = self.peak_pnl - self.pnl;
+        let used = |loss: f64, limit: Option<f64>| limit.filter(|&l| l > 0.0).map_or(1.0, |l| 1.0 - loss.max(0.0) / l);
+        used(drawdown, self.limits.max_drawdown_usd)
+            .min(used(self.hour_loss, self.limits.max_loss_per_hour_usd))
+            .clamp(0.0, 1.0)
+    }
+
+    pub fn report(&self, timezone: &ReportTimezone) -> String {
+        let mut out = match self.tripped {
+            Some(limit) => format!("Risk: kill switch tripped on the {}\n", limit),
+            None => "Risk: no loss limit tripped\n".to_string(),
+        };
+        let limit = |value: Option<f64>| value.map(|v| format!("${:.2}", v)).unwrap_or_else(|| "none".to_string());
+        out.push_str(&format!("  Max drawdown:          ${:>10.2}  (limit {})\n", self.max_drawdown, limit(self.limits.max_drawdown_usd)));
+        out.push_str(&format!("  Worst hourly loss:     ${:>10.2}  (limit {})\n", self.worst_hour, limit(self.limits.max_loss_per_hour_usd)));
+        out.push_str(&format!("  Longest losing streak: {:>11}  (limit {})\n", self.longest_losing_streak,
+            self.limits.max_consecutive_losses.map(|l| l.to_string()).unwrap_or_else(|| "none".to_string())));
+        if !self.feed_errors.is_empty() {
+            let errors: Vec<String> = self.feed_errors.iter().map(|((source, kind), n)| format!("{} {} {}", source, kind, n)).collect();
+            out.push_str(&format!("  Feed errors:           {:>11}  ({})\n", self.feed_errors(), errors.join(", ")));
+        }
+        if self.missed_feed_events > 0 {
+            out.push_str(&format!("  Feed events missed:    {:>11}  (the loop fell behind the event channel)\n", self.missed_feed_events));
+        }
+        if self.missed_quote_updates > 0 {
+            out.push_str(&format!("  Quote updates missed:  {:>11}  (the loop fell behind the quote channel)\n", self.missed_quote_updates));
+        }
+        for event in &self.events {
+            out.push_str(&format!("  {} {}: {}\n", timezone.format_millis(event.timestamp, "%H:%M:%S"), event.limit, event.message));
+        }
+        out
+    }
+}
@@ -0,0 +1,137 @@
+use crate::aggregator::{AggregatedPrices, Source};
+use crate::fees::{FeeBreakdown, FeeSchedule};
+use crate::trader::{LiquidityRole, TradeSide};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Simulated depth of one venue; costs come from the fee schedule
+#[derive(Debug, Clone, Copy)]
+pub struct VenueProfile {
+    /// USD that can be filled at the venue's touch in a single trade
+    pub liquidity_usd: f64,
+}
+
+impl VenueProfile {
+    pub fn default_for(source: Source) -> Self {
+        let liquidity_usd = match source {
+            Source::Binance => 2_000_000.0,
+            Source::Jupiter => 250_000.0,
+            Source::CowSwap => 500_000.0,
+            Source::Uniswap => 1_000_000.0,
+        };
+        Self { liquidity_usd }
+    }
+
+    /// Routing weight: deeper and cheaper venues get more of the order
+    fn weight(&self, cost_bps: f64) -> f64 {
+        self.liquidity_usd / (1.0 + cost_bps.max(0.0))
+    }
+}
+
+/// The part of a parent trade filled on a single venue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChildFill {
+    pub parent_id: u64,
+    pub venue: Source,
+    pub price: f64,
+    pub amount_eth: f64,
+    pub notional_usd: f64,
+    /// Total of `fees`
+    pub fee_usd: f64,
+    #[serde(default)]
+    pub fees: FeeBreakdown,
+}
+
+/// Splits a trade's notional across venues in proportion to liquidity and costs
+#[derive(Debug, Clone)]
+pub struct SmartOrderRouter {
+    venues: HashMap<Source, VenueProfile>,
+}
+
+impl Default for SmartOrderRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SmartOrderRouter {
+    pub fn new() -> Self {
+        Self {
+            venues: Source::ALL.iter().map(|&s| (s, VenueProfile::default_for(s))).collect(),
+        }
+    }
+
+    pub fn with_venue(mut self, source: Source, profile: VenueProfile) -> Self {
+        self.venues.insert(source, profile);
+        self
+    }
+
+    pub fn venue(&self, source: Source) -> Option<&VenueProfile> {
+        self.venues.get(&source)
+    }
+
+    /// Allocate `notional` across venues that currently have a quote, resting at each
+    /// venue's touch on our side. Venues are capped at their liquidity with the excess
+    /// handed to the rest; whatever no venue can absorb is left unfilled.
+    /// Each child fill pays its venue's maker costs from `fees`.
+    pub fn route(&self, parent_id: u64, prices: &AggregatedPrices, side: TradeSide, notional: f64, fees: &FeeSchedule) -> Vec<ChildFill> {
+        // Weight on the cost of the whole order at each venue, so flat gas counts in bps
+        let cost_bps = |s: Source| fees.cost(s, LiquidityRole::Maker, notional).bps_of(notional);
+        let mut open: Vec<(Source, VenueProfile, f64)> = Source::ALL
+            .iter()
+            .filter_map(|&s| {
+                let quote = prices.get(s)?;
+                let price = match side {
+                    TradeSide::Buy => quote.bid,
+                    TradeSide::Sell => quote.ask,
+                };
+                let profile = *self.venues.get(&s)?;
+                (profile.liquidity_usd > 0.0 && price > 0.0).then_some((s, profile, price))
+            })
+            .collect();
+
+        let mut allocations: Vec<(Source, VenueProfile, f64, f64)> = Vec::new();
+        let mut remaining = notional;
+
+        // Water-fill: venues that would exceed their depth are capped and removed
+        while remaining > 0.0 && !open.is_empty() {
+            let total_weight: f64 = open.iter().map(|(s, p, _)| p.weight(cost_bps(*s))).sum();
+            let capped: Vec<usize> = open
+                .iter()
+                .enumerate()
+                .filter(|(_, (s, p, _))| remaining * p.weight(cost_bps(*s)) / total_weight > p.liquidity_usd)
+                .map(|(i, _)| i)
+                .collect();
+
+            if capped.is_empty() {
+                for (source, profile, price) in open.drain(..) {
+                    allocations.push((source, profile, price, remaining * profile.weight(cost_bps(source)) / total_weight));
+                }
+                break;
+            }
+
+            for i in capped.into_iter().rev() {
+                let (source, profile, price) = open.remove(i);
+                remaining -= profile.liquidity_usd;
+                allocations.push((source, profile, price, profile.liquidity_usd));
+            }
+        }
+
+        allocations
+            .into_iter()
+            .filter(|(_, _, _, usd)| *usd > 0.0)
+            .map(|(venue, _, price, notional_usd)| {
+                let fill_fees = fees.cost(venue, LiquidityRole::Maker, notional_usd);
+                ChildFill {
+                    parent_id,
+                    venue,
+                    price,
+                    amount_eth: notional_usd / price,
+                    notional_usd,
+                    fee_usd: fill_fees.total(),
+                    fees: fill_fees,
+                }
+            })
+            .collect()
+    }
+}
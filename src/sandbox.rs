@@ -0,0 +1,133 @@
+use crate::say;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Default per-cycle time budget for a strategy decision
+pub const DEFAULT_STRATEGY_BUDGET: Duration = Duration::from_millis(250);
+
+/// A strategy decision that overran its time budget and was skipped
+#[derive(Debug, Clone)]
+pub struct BudgetViolation {
+    pub strategy: String,
+    pub cycle: u64,
+    pub timestamp: i64,
+    pub budget: Duration,
+}
+
+impl fmt::Display for BudgetViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cycle #{} {} exceeded {}ms budget",
+            self.cycle, self.strategy, self.budget.as_millis())
+    }
+}
+
+/// Per-strategy timing collected by the sandbox
+#[derive(Debug, Clone, Default)]
+pub struct StrategyTiming {
+    pub runs: u64,
+    pub violations: u64,
+    pub max_elapsed: Duration,
+}
+
+/// Runs strategy decisions off the async runtime under a time budget, so a slow
+/// custom strategy skips its turn instead of stalling price updates
+pub struct StrategySandbox {
+    budget: Duration,
+    violations: Mutex<Vec<BudgetViolation>>,
+    timings: Mutex<Vec<(String, StrategyTiming)>>,
+}
+
+impl Default for StrategySandbox {
+    fn default() -> Self {
+        Self::new(DEFAULT_STRATEGY_BUDGET)
+    }
+}
+
+impl StrategySandbox {
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            budget,
+            violations: Mutex::new(Vec::new()),
+            timings: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    /// Run `decide` on a blocking thread; `None` if it panicked or missed the budget.
+    /// An overrunning decision keeps running in the background but its result is dropped.
+    pub async fn run<T, D>(&self, strategy: &str, cycle: u64, decide: D) -> Option<T>
+    where
+        T: Send + 'static,
+        D: FnOnce() -> T + Send + 'static,
+    {
+        let started = Instant::now();
+        let result = tokio::time::timeout(self.budget, tokio::task::spawn_blocking(decide)).await;
+        let elapsed = started.elapsed();
+
+        let timed_out = result.is_err();
+        self.record_timing(strategy, elapsed, timed_out);
+
+        match result {
+            Ok(Ok(value)) => Some(value),
+            Ok(Err(e)) => {
+                warn!(strategy, cycle, "strategy panicked: {}", e);
+                None
+            }
+            Err(_) => {
+                let violation = BudgetViolation {
+                    strategy: strategy.to_string(),
+                    cycle,
+                    timestamp: chrono::Utc::now().timestamp_millis(),
+                    budget: self.budget,
+                };
+                warn!("{}, decision skipped", violation);
+                self.violations.lock().unwrap().push(violation);
+                None
+            }
+        }
+    }
+
+    fn record_timing(&self, strategy: &str, elapsed: Duration, violated: bool) {
+        let mut timings = self.timings.lock().unwrap();
+        let index = match timings.iter().position(|(name, _)| name == strategy) {
+            Some(i) => i,
+            None => {
+                timings.push((strategy.to_string(), StrategyTiming::default()));
+                timings.len() - 1
+            }
+        };
+        let timing = &mut timings[index].1;
+        timing.runs += 1;
+        timing.max_elapsed = timing.max_elapsed.max(elapsed);
+        if violated {
+            timing.violations += 1;
+        }
+    }
+
+    pub fn violations(&self) -> Vec<BudgetViolation> {
+        self.violations.lock().unwrap().clone()
+    }
+
+    pub fn timings(&self) -> Vec<(String, StrategyTiming)> {
+        self.timings.lock().unwrap().clone()
+    }
+
+    pub fn print_report(&self) {
+        let timings = self.timings();
+        if timings.iter().all(|(_, t)| t.violations == 0) {
+            return;
+        }
+
+        let mut report = format!("\nStrategy Budget Violations ({}ms per decision):", self.budget.as_millis());
+        for (name, t) in &timings {
+            report.push_str(&format!("\n  {:<12} {:>4} of {:>4} decisions skipped │ slowest {:.1}ms",
+                name, t.violations, t.runs, t.max_elapsed.as_secs_f64() * 1000.0));
+        }
+        say!("{}", report);
+    }
+}
@@ -0,0 +1,271 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const ICS_PRODID: &str = "-//market-maker-simulator//schedule//EN";
+
+/// A recurring trading window in UTC, e.g. every weekday 14:00-16:00
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionWindow {
+    pub name: String,
+    /// Days the window starts on, Monday first
+    pub days: Vec<Weekday>,
+    pub start: NaiveTime,
+    /// Earlier than `start` for windows that run past midnight
+    pub end: NaiveTime,
+}
+
+impl SessionWindow {
+    pub fn new(name: impl Into<String>, days: &str, start: &str, end: &str) -> Result<Self> {
+        let name = name.into();
+        let start = parse_time(start)?;
+        let end = parse_time(end)?;
+        if start == end {
+            bail!("session '{}' starts and ends at {}", name, start.format("%H:%M"));
+        }
+        Ok(Self {
+            days: parse_days(days)?,
+            name,
+            start,
+            end,
+        })
+    }
+
+    /// Name for file names and calendar ids: lowercase letters, digits and single dashes, so a
+    /// name like `../eu open` can't leave the schedule directory (`eu-open`)
+    pub fn slug(&self) -> String {
+        let mapped: String = self.name.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' }).collect();
+        let slug = mapped.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-");
+        if slug.is_empty() { "session".to_string() } else { slug }
+    }
+
+    pub fn duration(&self) -> Duration {
+        let length = self.end - self.start;
+        if length > Duration::zero() {
+            length
+        } else {
+            length + Duration::days(1)
+        }
+    }
+
+    /// The window starting on `date`, whether or not it's a trading day
+    pub fn on(&self, date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+        let start = date.and_time(self.start).and_utc();
+        (start, start + self.duration())
+    }
+
+    /// The window in progress at `now`, or else the next one to start
+    pub fn next_after(&self, now: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        // Start a day early to catch a window that began yesterday and runs past midnight
+        (-1..=7)
+            .map(|offset| now.date_naive() + Duration::days(offset))
+            .filter(|date| self.days.contains(&date.weekday()))
+            .map(|date| self.on(date))
+            .find(|&(_, end)| end > now)
+    }
+
+    /// RFC 5545 BYDAY list, e.g. `MO,TU,WE`
+    fn ics_days(&self) -> String {
+        self.days
+            .iter()
+            .map(|day| day.to_string()[..2].to_ascii_uppercase())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+impl fmt::Display for SessionWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let days: Vec<String> = self.days.iter().map(|d| d.to_string()).collect();
+        write!(f, "{} {}-{} UTC", days.join(","), self.start.format("%H:%M"), self.end.format("%H:%M"))
+    }
+}
+
+/// Parse `[name=]days@HH:MM-HH:MM`, e.g. `afternoon=mon-fri@14:00-16:00`
+impl FromStr for SessionWindow {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, spec) = match s.split_once('=') {
+            Some((name, spec)) => (name.trim().to_string(), spec),
+            None => (s.trim().to_string(), s),
+        };
+        let (days, times) = spec
+            .split_once('@')
+            .with_context(|| format!("session window '{}' should look like days@HH:MM-HH:MM", s))?;
+        let (start, end) = times
+            .split_once('-')
+            .with_context(|| format!("session window '{}' should look like days@HH:MM-HH:MM", s))?;
+        Self::new(name, days, start, end)
+    }
+}
+
+fn parse_time(s: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M").with_context(|| format!("invalid time '{}' (expected HH:MM)", s))
+}
+
+fn parse_weekday(s: &str) -> Result<Weekday> {
+    s.trim().parse().map_err(|_| anyhow::anyhow!("unknown day '{}'", s))
+}
+
+/// `daily`, `weekdays`, `weekends`, or a comma list of days and ranges such as `mon-wed,sat`
+pub fn parse_days(s: &str) -> Result<Vec<Weekday>> {
+    let mut days = Vec::new();
+    for part in s.split(',') {
+        let part = part.trim().to_ascii_lowercase();
+        match part.as_str() {
+            "daily" => days.extend((0..7).filter_map(|i| Weekday::try_from(i as u8).ok())),
+            "weekdays" => days.extend((0..5).filter_map(|i| Weekday::try_from(i as u8).ok())),
+            "weekends" => days.extend([Weekday::Sat, Weekday::Sun]),
+            _ => match part.split_once('-') {
+                Some((from, to)) => {
+                    let mut day = parse_weekday(from)?;
+                    let to = parse_weekday(to)?;
+                    days.push(day);
+                    while day != to {
+                        day = day.succ();
+                        days.push(day);
+                    }
+                }
+                None => days.push(parse_weekday(&part)?),
+            },
+        }
+    }
+    if days.is_empty() {
+        bail!("no days in '{}'", s);
+    }
+    days.sort_by_key(|d| d.num_days_from_monday());
+    days.dedup();
+    Ok(days)
+}
+
+/// A set of recurring session windows
+#[derive(Debug, Clone, Default)]
+pub struct Calendar {
+    windows: Vec<SessionWindow>,
+}
+
+/// One concrete run of a session window
+#[derive(Debug, Clone)]
+pub struct Occurrence {
+    pub window: SessionWindow,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl Calendar {
+    pub fn new(windows: Vec<SessionWindow>) -> Self {
+        Self { windows }
+    }
+
+    pub fn windows(&self) -> &[SessionWindow] {
+        &self.windows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// The session in progress at `now`, or else the earliest one to start
+    pub fn next_after(&self, now: DateTime<Utc>) -> Option<Occurrence> {
+        self.windows
+            .iter()
+            .filter_map(|window| {
+                let (start, end) = window.next_after(now)?;
+                Some(Occurrence {
+                    window: window.clone(),
+                    start,
+                    end,
+                })
+            })
+            .min_by_key(|o| o.start)
+    }
+
+    /// The next `count` sessions from `now` in start order
+    pub fn upcoming(&self, now: DateTime<Utc>, count: usize) -> Vec<Occurrence> {
+        let mut out = Vec::new();
+        for window in &self.windows {
+            let mut cursor = now;
+            for _ in 0..count {
+                let Some((start, end)) = window.next_after(cursor) else {
+                    break;
+                };
+                out.push(Occurrence {
+                    window: window.clone(),
+                    start,
+                    end,
+                });
+                cursor = end;
+            }
+        }
+        out.sort_by_key(|o| o.start);
+        out.truncate(count);
+        out
+    }
+
+    /// iCalendar with one weekly recurring event per window, for importing into a calendar app
+    pub fn to_ics(&self, now: DateTime<Utc>) -> String {
+        let stamp = now.format("%Y%m%dT%H%M%SZ");
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            format!("PRODID:{}", ICS_PRODID),
+            "CALSCALE:GREGORIAN".to_string(),
+        ];
+        for window in &self.windows {
+            let Some((start, end)) = window.next_after(now) else {
+                continue;
+            };
+            lines.extend([
+                "BEGIN:VEVENT".to_string(),
+                format!("UID:{}@market-maker-simulator", window.slug()),
+                format!("DTSTAMP:{}", stamp),
+                format!("DTSTART:{}", start.format("%Y%m%dT%H%M%SZ")),
+                format!("DTEND:{}", end.format("%Y%m%dT%H%M%SZ")),
+                format!("RRULE:FREQ=WEEKLY;BYDAY={}", window.ics_days()),
+                format!("SUMMARY:Market maker session: {}", window.name),
+                format!("DESCRIPTION:{}", window),
+                "END:VEVENT".to_string(),
+            ]);
+        }
+        lines.push("END:VCALENDAR".to_string());
+        // RFC 5545 lines end in CRLF
+        lines.join("\r\n") + "\r\n"
+    }
+}
+
+/// What the scheduler records for each session it runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionManifest {
+    pub schedule: String,
+    pub window: String,
+    pub scheduled_start: i64,
+    pub scheduled_end: i64,
+    pub started_at: i64,
+    pub ended_at: i64,
+    /// Name the run's session record was saved under
+    pub session_name: String,
+    /// Id of that record, when the run got far enough to save one
+    pub session_id: Option<String>,
+    pub exit_code: Option<i32>,
+    /// Full console output of the run, including its summary
+    pub log: PathBuf,
+}
+
+impl SessionManifest {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    /// Write as `<stem>.json` next to the log
+    pub fn save(&self, dir: &Path, stem: &str) -> Result<PathBuf> {
+        std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        let path = dir.join(format!("{}.json", stem));
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write manifest {}", path.display()))?;
+        Ok(path)
+    }
+}
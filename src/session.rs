@@ -0,0 +1,120 @@
+use crate::pnl_tracker::PnLStats;
+use crate::timezone::ReportTimezone;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_SESSION_DIR: &str = "sessions";
+const INDEX_FILE: &str = "index.json";
+
+/// Summary of a finished run, as stored in the session index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub id: String,
+    pub name: Option<String>,
+    pub started_at: i64,
+    pub ended_at: i64,
+    pub execution_model: String,
+    pub notional_per_trade: f64,
+    pub cycles: u64,
+    pub stats: PnLStats,
+}
+
+impl SessionRecord {
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.id)
+    }
+
+    pub fn duration_secs(&self) -> i64 {
+        (self.ended_at - self.started_at) / 1000
+    }
+
+    pub fn print(&self, timezone: &ReportTimezone) {
+        let fmt_ts = |ts: i64| timezone.format_datetime(ts);
+
+        println!("\nSession:             {}", self.id);
+        if let Some(name) = &self.name {
+            println!("Name:                {}", name);
+        }
+        println!("Started:             {}", fmt_ts(self.started_at));
+        println!("Ended:               {}", fmt_ts(self.ended_at));
+        println!("Duration:            {}s ({} cycles)", self.duration_secs(), self.cycles);
+        println!("Execution Model:     {}", self.execution_model);
+        println!("Notional per Trade:  ${:.2}", self.notional_per_trade);
+        self.stats.print_summary();
+    }
+}
+
+/// Generate a sortable session id from the current UTC time
+pub fn new_session_id() -> String {
+    chrono::Utc::now().format("%Y%m%d-%H%M%S").to_string()
+}
+
+/// JSON-backed index of past sessions kept under a local directory
+pub struct SessionStore {
+    dir: PathBuf,
+}
+
+impl SessionStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.dir.join(INDEX_FILE)
+    }
+
+    pub fn list(&self) -> Result<Vec<SessionRecord>> {
+        let path = self.index_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let data = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read session index {}", path.display()))?;
+        serde_json::from_str(&data)
+            .with_context(|| format!("failed to parse session index {}", path.display()))
+    }
+
+    /// Look up a session by id or name
+    pub fn find(&self, key: &str) -> Result<Option<SessionRecord>> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .find(|s| s.id == key || s.name.as_deref() == Some(key)))
+    }
+
+    pub fn save(&self, record: &SessionRecord) -> Result<()> {
+        let mut sessions = self.list()?;
+        sessions.retain(|s| s.id != record.id);
+        sessions.push(record.clone());
+        self.write(&sessions)
+    }
+
+    /// Remove a session by id or name, returning whether anything was deleted
+    pub fn delete(&self, key: &str) -> Result<bool> {
+        let mut sessions = self.list()?;
+        let before = sessions.len();
+        sessions.retain(|s| s.id != key && s.name.as_deref() != Some(key));
+
+        if sessions.len() == before {
+            return Ok(false);
+        }
+        self.write(&sessions)?;
+        Ok(true)
+    }
+
+    fn write(&self, sessions: &[SessionRecord]) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create session dir {}", self.dir.display()))?;
+        let path = self.index_path();
+        let data = serde_json::to_string_pretty(sessions)?;
+        fs::write(&path, data)
+            .with_context(|| format!("failed to write session index {}", path.display()))
+    }
+}
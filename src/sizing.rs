@@ -0,0 +1,185 @@
+use crate::position::Position;
+use crate::trader::TradeSide;
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::collections::VecDeque;
+
+const MS_PER_YEAR: f64 = 365.0 * 24.0 * 3600.0 * 1000.0;
+
+/// Trailing window for realized volatility unless configured otherwise
+pub const DEFAULT_VOL_WINDOW_MS: i64 = 10 * 60 * 1000;
+
+/// What a sizing rule sees when sizing an order
+#[derive(Debug, Clone, Copy)]
+pub struct SizingContext {
+    pub position: Position,
+    pub mid: f64,
+    /// Annualized realized volatility of the median mid; `None` until there's enough history
+    pub realized_vol: Option<f64>,
+    /// Fraction of the tightest loss limit still unused: 1.0 with no limits, 0.0 once one trips
+    pub risk_budget: f64,
+}
+
+/// Decides how much notional to quote on each side
+pub trait SizingRule: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// USD notional for an order on `side`
+    fn notional(&self, side: TradeSide, ctx: &SizingContext, base_notional: f64) -> f64;
+
+    /// Current parameter values, for introspection
+    fn parameters(&self) -> serde_json::Value {
+        serde_json::json!({})
+    }
+}
+
+/// The same notional on both sides regardless of inventory
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FixedSize;
+
+impl SizingRule for FixedSize {
+    fn name(&self) -> &'static str {
+        "fixed"
+    }
+
+    fn notional(&self, _side: TradeSide, _ctx: &SizingContext, base_notional: f64) -> f64 {
+        base_notional
+    }
+}
+
+/// Scale size with inventory: larger on the side that reduces it, smaller on the side that adds to it.
+/// Also shrinks with the remaining risk budget.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct InventorySkew {
+    /// Inventory at which the skew is fully applied
+    pub max_inventory_eth: f64,
+    /// Fraction of base size moved between sides at full inventory
+    pub skew: f64,
+    /// Smallest size as a fraction of base, so the growing side keeps quoting
+    pub min_fraction: f64,
+}
+
+impl Default for InventorySkew {
+    fn default() -> Self {
+        Self {
+            max_inventory_eth: 100.0,
+            skew: 0.8,
+            min_fraction: 0.1,
+        }
+    }
+}
+
+impl SizingRule for InventorySkew {
+    fn name(&self) -> &'static str {
+        "inventory"
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    fn notional(&self, side: TradeSide, ctx: &SizingContext, base_notional: f64) -> f64 {
+        let ratio = if self.max_inventory_eth > 0.0 {
+            (ctx.position.net_eth / self.max_inventory_eth).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+        let multiplier = match side {
+            TradeSide::Buy => 1.0 - self.skew * ratio,
+            TradeSide::Sell => 1.0 + self.skew * ratio,
+        };
+        base_notional * (multiplier * ctx.risk_budget).max(self.min_fraction)
+    }
+}
+
+/// Scale size inversely with realized volatility so each quote carries roughly the same risk,
+/// shrinking further as the risk budget is used up. Base size until there's enough price history.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct VolTarget {
+    /// Annualized volatility at which base size is quoted (0.60 = 60%)
+    pub target_vol: f64,
+    /// Largest size as a multiple of base, for very quiet markets
+    pub max_multiplier: f64,
+    pub min_fraction: f64,
+}
+
+impl Default for VolTarget {
+    fn default() -> Self {
+        Self {
+            target_vol: 0.60,
+            max_multiplier: 2.0,
+            min_fraction: 0.1,
+        }
+    }
+}
+
+impl SizingRule for VolTarget {
+    fn name(&self) -> &'static str {
+        "vol-target"
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    fn notional(&self, _side: TradeSide, ctx: &SizingContext, base_notional: f64) -> f64 {
+        let multiplier = match ctx.realized_vol {
+            Some(vol) if vol > 0.0 => (self.target_vol / vol).min(self.max_multiplier),
+            _ => 1.0,
+        };
+        base_notional * (multiplier * ctx.risk_budget).max(self.min_fraction)
+    }
+}
+
+/// Annualized volatility of log returns over a trailing time window
+#[derive(Debug, Clone)]
+pub struct RealizedVolatility {
+    window_ms: i64,
+    /// (timestamp, mid), oldest first
+    samples: VecDeque<(i64, f64)>,
+}
+
+impl RealizedVolatility {
+    pub fn new(window_ms: i64) -> Self {
+        Self {
+            window_ms,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Add a mid; ignored unless newer than the last one
+    pub fn observe(&mut self, timestamp: i64, mid: f64) {
+        if mid <= 0.0 || self.samples.back().is_some_and(|&(ts, _)| ts >= timestamp) {
+            return;
+        }
+        self.samples.push_back((timestamp, mid));
+        while self.samples.front().is_some_and(|&(ts, _)| ts < timestamp - self.window_ms) {
+            self.samples.pop_front();
+        }
+    }
+
+    /// `None` with fewer than three samples
+    pub fn annualized(&self) -> Option<f64> {
+        if self.samples.len() < 3 {
+            return None;
+        }
+        let (first, last) = (self.samples.front()?.0, self.samples.back()?.0);
+        let variance: f64 = self
+            .samples
+            .iter()
+            .zip(self.samples.iter().skip(1))
+            .map(|(&(_, a), &(_, b))| (b / a).ln().powi(2))
+            .sum();
+        Some((variance / (last - first) as f64 * MS_PER_YEAR).sqrt())
+    }
+}
+
+/// Build a sizing rule by name (`fixed`, `inventory` or `vol-target`)
+pub fn parse_sizing_rule(name: &str, skew: InventorySkew, vol: VolTarget) -> Result<Box<dyn SizingRule>> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "fixed" => Ok(Box::new(FixedSize)),
+        "inventory" => Ok(Box::new(skew)),
+        "vol-target" | "vol_target" => Ok(Box::new(vol)),
+        other => bail!("unknown sizing rule '{}' (expected fixed, inventory or vol-target)", other),
+    }
+}
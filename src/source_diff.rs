@@ -0,0 +1,377 @@
+use crate::aggregator::{PriceAggregator, Quote, Source, BINANCE_REST_URL};
+use crate::config::Config;
+use crate::http::HttpClient;
+use crate::synthetic::{SyntheticConfig, SyntheticSource};
+use crate::tokens::TradingPair;
+use crate::uniswap::{self, UniswapConfig};
+use anyhow::{bail, Context, Result};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// One way of fetching a source's quote, so two can be compared during a migration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceImpl {
+    /// Binance top of book over REST (the simulator normally streams it over WebSocket)
+    BinanceRest,
+    /// Jupiter swap quotes at notional in both directions (what the aggregator uses)
+    JupiterQuote,
+    /// Jupiter price API: a single reference price with no spread
+    JupiterPrice,
+    CowSwap,
+    Uniswap,
+    /// Offline GBM prices, with an optional seed
+    Synthetic { seed: Option<u64> },
+}
+
+impl SourceImpl {
+    pub fn name(&self) -> String {
+        match self {
+            SourceImpl::BinanceRest => "binance-rest".to_string(),
+            SourceImpl::JupiterQuote => "jupiter-quote".to_string(),
+            SourceImpl::JupiterPrice => "jupiter-price".to_string(),
+            SourceImpl::CowSwap => "cowswap".to_string(),
+            SourceImpl::Uniswap => "uniswap".to_string(),
+            SourceImpl::Synthetic { seed: None } => "synthetic".to_string(),
+            SourceImpl::Synthetic { seed: Some(seed) } => format!("synthetic:{}", seed),
+        }
+    }
+
+    /// Venue whose HTTP limits apply to the implementation's requests
+    fn venue(&self) -> Source {
+        match self {
+            SourceImpl::BinanceRest | SourceImpl::Synthetic { .. } => Source::Binance,
+            SourceImpl::JupiterQuote | SourceImpl::JupiterPrice => Source::Jupiter,
+            SourceImpl::CowSwap => Source::CowSwap,
+            SourceImpl::Uniswap => Source::Uniswap,
+        }
+    }
+}
+
+impl FromStr for SourceImpl {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim().to_ascii_lowercase();
+        if let Some(seed) = s.strip_prefix("synthetic:") {
+            let seed = seed.parse().with_context(|| format!("invalid synthetic seed '{}'", seed))?;
+            return Ok(SourceImpl::Synthetic { seed: Some(seed) });
+        }
+        match s.as_str() {
+            "binance-rest" => Ok(SourceImpl::BinanceRest),
+            "jupiter" | "jupiter-quote" => Ok(SourceImpl::JupiterQuote),
+            "jupiter-price" => Ok(SourceImpl::JupiterPrice),
+            "cowswap" => Ok(SourceImpl::CowSwap),
+            "uniswap" => Ok(SourceImpl::Uniswap),
+            "synthetic" => Ok(SourceImpl::Synthetic { seed: None }),
+            other => bail!(
+                "unknown source implementation '{}' (expected binance-rest, jupiter-quote, jupiter-price, cowswap, uniswap or synthetic[:seed])",
+                other
+            ),
+        }
+    }
+}
+
+/// Fetches single quotes from one source implementation
+pub struct SourceFetcher {
+    source: SourceImpl,
+    client: HttpClient,
+    base_url: String,
+    notional: f64,
+    pair: TradingPair,
+    uniswap: Option<UniswapConfig>,
+    synthetic: Option<Mutex<SyntheticSource>>,
+}
+
+impl SourceFetcher {
+    /// Endpoints, notional and synthetic parameters come from `config`
+    pub fn new(source: SourceImpl, config: &Config) -> Result<Self> {
+        let notional = config.quote_notional();
+        let pair = config.tokens.pair()?;
+        let base_url = match source {
+            SourceImpl::BinanceRest => BINANCE_REST_URL.to_string(),
+            SourceImpl::JupiterQuote | SourceImpl::JupiterPrice => config.sources.jupiter_api_url.clone(),
+            SourceImpl::CowSwap => config.sources.cowswap_api_url.clone(),
+            SourceImpl::Uniswap | SourceImpl::Synthetic { .. } => String::new(),
+        };
+        let uniswap = match source {
+            SourceImpl::Uniswap => Some(
+                config
+                    .sources
+                    .uniswap(notional, &pair)?
+                    .context("uniswap needs an RPC URL (--uniswap-rpc or ETH_RPC_URL)")?,
+            ),
+            _ => None,
+        };
+        let synthetic = match source {
+            SourceImpl::Synthetic { seed } => {
+                let settings = config.synthetic.config();
+                Some(Mutex::new(SyntheticSource::new(SyntheticConfig {
+                    seed: seed.unwrap_or(settings.seed),
+                    ..settings
+                })))
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            source,
+            client: HttpClient::new(config.sources.http_policy(source.venue()))?,
+            base_url,
+            notional,
+            pair,
+            uniswap,
+            synthetic,
+        })
+    }
+
+    pub fn source(&self) -> SourceImpl {
+        self.source
+    }
+
+    pub async fn fetch(&self) -> Result<Quote> {
+        let client = &self.client;
+        match self.source {
+            SourceImpl::BinanceRest => PriceAggregator::fetch_binance_rest_once(client, &self.base_url, &self.pair).await,
+            SourceImpl::JupiterQuote => PriceAggregator::fetch_jupiter_once(client, &self.base_url, &self.pair, self.notional).await,
+            SourceImpl::JupiterPrice => PriceAggregator::fetch_jupiter_price_once(client, &self.base_url, &self.pair).await,
+            SourceImpl::CowSwap => PriceAggregator::fetch_cowswap_once(client, &self.base_url, &self.pair, self.notional).await,
+            SourceImpl::Uniswap => {
+                let config = self.uniswap.as_ref().context("uniswap is not configured")?;
+                Ok(uniswap::fetch_quote(client.inner(), config).await?.0)
+            }
+            SourceImpl::Synthetic { .. } => {
+                let synthetic = self.synthetic.as_ref().context("synthetic source is not configured")?;
+                Ok(synthetic.lock().unwrap().next_quote(chrono::Utc::now().timestamp_millis()))
+            }
+        }
+    }
+}
+
+/// Outcome of one implementation's fetch within a sample
+#[derive(Debug, Clone)]
+pub struct FetchResult {
+    pub quote: Option<Quote>,
+    pub latency: Duration,
+    pub error: Option<String>,
+}
+
+async fn timed_fetch(fetcher: &SourceFetcher) -> FetchResult {
+    let started = Instant::now();
+    let result = fetcher.fetch().await;
+    let latency = started.elapsed();
+    match result {
+        Ok(quote) => FetchResult { quote: Some(quote), latency, error: None },
+        Err(e) => FetchResult { quote: None, latency, error: Some(e.to_string()) },
+    }
+}
+
+/// Both implementations fetched at the same moment
+#[derive(Debug, Clone)]
+pub struct DiffSample {
+    pub timestamp: i64,
+    pub a: FetchResult,
+    pub b: FetchResult,
+}
+
+impl DiffSample {
+    /// `b`'s mid relative to `a`'s, in bps, when both succeeded
+    pub fn mid_diff_bps(&self) -> Option<f64> {
+        let a = mid(self.a.quote.as_ref()?);
+        let b = mid(self.b.quote.as_ref()?);
+        Some((b - a) / a * 10000.0)
+    }
+}
+
+fn mid(quote: &Quote) -> f64 {
+    (quote.bid + quote.ask) / 2.0
+}
+
+fn spread_bps(quote: &Quote) -> f64 {
+    (quote.ask - quote.bid) / mid(quote) * 10000.0
+}
+
+/// Poll both implementations side by side every `interval` for `duration`
+pub async fn run_diff(
+    a: &SourceFetcher,
+    b: &SourceFetcher,
+    duration: Duration,
+    interval: Duration,
+) -> Vec<DiffSample> {
+    let started = Instant::now();
+    let mut ticker = tokio::time::interval(interval);
+    let mut samples = Vec::new();
+
+    while started.elapsed() < duration {
+        ticker.tick().await;
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        let (a_result, b_result) = tokio::join!(timed_fetch(a), timed_fetch(b));
+        let sample = DiffSample {
+            timestamp,
+            a: a_result,
+            b: b_result,
+        };
+
+        match (&sample.a.quote, &sample.b.quote) {
+            (Some(qa), Some(qb)) => println!(
+                "[DIFF] #{:<4} {} ${:.2} ({} ms) │ {} ${:.2} ({} ms) │ Δ {:+.2} bps",
+                samples.len() + 1,
+                a.source().name(), mid(qa), sample.a.latency.as_millis(),
+                b.source().name(), mid(qb), sample.b.latency.as_millis(),
+                sample.mid_diff_bps().unwrap_or_default()
+            ),
+            _ => println!(
+                "[DIFF] #{:<4} {}: {} │ {}: {}",
+                samples.len() + 1,
+                a.source().name(), sample.a.error.as_deref().unwrap_or("ok"),
+                b.source().name(), sample.b.error.as_deref().unwrap_or("ok")
+            ),
+        }
+        samples.push(sample);
+    }
+
+    samples
+}
+
+/// Per-implementation figures over a diff run
+#[derive(Debug, Clone, Default)]
+pub struct ImplStats {
+    pub name: String,
+    pub successes: usize,
+    pub failures: usize,
+    pub mean_spread_bps: f64,
+    pub mean_latency_ms: f64,
+    pub p95_latency_ms: f64,
+}
+
+/// Statistical comparison of two implementations
+#[derive(Debug, Clone, Default)]
+pub struct DiffReport {
+    pub samples: usize,
+    pub paired: usize,
+    pub a: ImplStats,
+    pub b: ImplStats,
+    /// Mean of `b - a` mids, in bps of `a`: a persistent bias
+    pub mean_diff_bps: f64,
+    pub std_diff_bps: f64,
+    pub mean_abs_diff_bps: f64,
+    pub p95_abs_diff_bps: f64,
+    pub max_abs_diff_bps: f64,
+    /// Correlation of sample-to-sample mid changes; low values mean one side lags or is noisy
+    pub return_correlation: Option<f64>,
+    pub tolerance_bps: f64,
+}
+
+impl DiffReport {
+    pub fn from_samples(a_name: &str, b_name: &str, samples: &[DiffSample], tolerance_bps: f64) -> Self {
+        let diffs: Vec<f64> = samples.iter().filter_map(DiffSample::mid_diff_bps).collect();
+        let abs: Vec<f64> = diffs.iter().map(|d| d.abs()).collect();
+
+        let paired: Vec<(f64, f64)> = samples
+            .iter()
+            .filter_map(|s| Some((mid(s.a.quote.as_ref()?), mid(s.b.quote.as_ref()?))))
+            .collect();
+        let returns: Vec<(f64, f64)> = paired
+            .windows(2)
+            .map(|w| ((w[1].0 / w[0].0).ln(), (w[1].1 / w[0].1).ln()))
+            .collect();
+
+        Self {
+            samples: samples.len(),
+            paired: diffs.len(),
+            a: impl_stats(a_name, samples.iter().map(|s| &s.a)),
+            b: impl_stats(b_name, samples.iter().map(|s| &s.b)),
+            mean_diff_bps: mean(&diffs),
+            std_diff_bps: std_dev(&diffs),
+            mean_abs_diff_bps: mean(&abs),
+            p95_abs_diff_bps: percentile(&abs, 0.95),
+            max_abs_diff_bps: abs.iter().copied().fold(0.0, f64::max),
+            return_correlation: correlation(&returns),
+            tolerance_bps,
+        }
+    }
+
+    /// Mean absolute difference within tolerance and no worse availability than `a`
+    pub fn within_tolerance(&self) -> bool {
+        self.paired > 0 && self.mean_abs_diff_bps <= self.tolerance_bps && self.b.failures <= self.a.failures
+    }
+}
+
+impl fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "\nSource Diff: {} (A) vs {} (B)", self.a.name, self.b.name)?;
+        writeln!(f, "─────────────────────────────────────────────────────────────────────")?;
+        writeln!(f, "Samples:             {} ({} with both quotes)", self.samples, self.paired)?;
+        for stats in [&self.a, &self.b] {
+            writeln!(f, "  {:<16} ok {:>4} │ failed {:>4} │ spread {:>6.2} bps │ latency avg {:>6.0} ms, p95 {:>6.0} ms",
+                stats.name, stats.successes, stats.failures, stats.mean_spread_bps, stats.mean_latency_ms, stats.p95_latency_ms)?;
+        }
+        writeln!(f, "Mid B - A:           mean {:+.2} bps │ std {:.2} bps", self.mean_diff_bps, self.std_diff_bps)?;
+        writeln!(f, "|Mid B - A|:         mean {:.2} bps │ p95 {:.2} bps │ max {:.2} bps",
+            self.mean_abs_diff_bps, self.p95_abs_diff_bps, self.max_abs_diff_bps)?;
+        match self.return_correlation {
+            Some(c) => writeln!(f, "Return correlation:  {:.3}", c)?,
+            None => writeln!(f, "Return correlation:  n/a")?,
+        }
+        let verdict = if self.within_tolerance() { "WITHIN" } else { "OUTSIDE" };
+        writeln!(f, "Verdict:             {} tolerance ({:.2} bps mean absolute difference)", verdict, self.tolerance_bps)
+    }
+}
+
+fn impl_stats<'a>(name: &str, results: impl Iterator<Item = &'a FetchResult>) -> ImplStats {
+    let results: Vec<&FetchResult> = results.collect();
+    let spreads: Vec<f64> = results.iter().filter_map(|r| r.quote.as_ref()).map(spread_bps).collect();
+    let latencies: Vec<f64> = results
+        .iter()
+        .filter(|r| r.quote.is_some())
+        .map(|r| r.latency.as_secs_f64() * 1000.0)
+        .collect();
+    ImplStats {
+        name: name.to_string(),
+        successes: spreads.len(),
+        failures: results.len() - spreads.len(),
+        mean_spread_bps: mean(&spreads),
+        mean_latency_ms: mean(&latencies),
+        p95_latency_ms: percentile(&latencies, 0.95),
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let m = mean(values);
+    (values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / (values.len() - 1) as f64).sqrt()
+}
+
+fn percentile(values: &[f64], p: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let index = ((sorted.len() as f64 - 1.0) * p).round() as usize;
+    sorted[index]
+}
+
+fn correlation(pairs: &[(f64, f64)]) -> Option<f64> {
+    if pairs.len() < 3 {
+        return None;
+    }
+    let xs: Vec<f64> = pairs.iter().map(|p| p.0).collect();
+    let ys: Vec<f64> = pairs.iter().map(|p| p.1).collect();
+    let (mx, my) = (mean(&xs), mean(&ys));
+    let cov: f64 = pairs.iter().map(|(x, y)| (x - mx) * (y - my)).sum();
+    let vx: f64 = xs.iter().map(|x| (x - mx).powi(2)).sum();
+    let vy: f64 = ys.iter().map(|y| (y - my).powi(2)).sum();
+    (vx > 0.0 && vy > 0.0).then(|| cov / (vx * vy).sqrt())
+}
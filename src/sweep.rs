@@ -0,0 +1,188 @@
+use crate::aggregator::PriceFeed;
+use crate::config::Config;
+use crate::recorder::Tick;
+use crate::replay::{ReplaySource, ReplaySpeed};
+use crate::whatif::{CycleSnapshot, WhatIfRun};
+use anyhow::{bail, Context, Result};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// One swept parameter: a dotted config key and the values to try, from `key=v1,v2,...`
+#[derive(Debug, Clone)]
+pub struct SweepAxis {
+    pub key: String,
+    pub values: Vec<String>,
+}
+
+impl FromStr for SweepAxis {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (key, values) = s
+            .split_once('=')
+            .with_context(|| format!("sweep axis '{}' should look like section.key=value1,value2", s))?;
+        let values: Vec<String> = values.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect();
+        if values.is_empty() {
+            bail!("sweep axis '{}' has no values", s);
+        }
+        Ok(SweepAxis {
+            key: key.trim().to_string(),
+            values,
+        })
+    }
+}
+
+/// One combination of axis values and the config it produces
+#[derive(Debug, Clone)]
+pub struct SweepPoint {
+    /// `(key, value)` per axis, in axis order
+    pub assignments: Vec<(String, String)>,
+    pub config: Config,
+}
+
+/// Every combination of the axes' values applied on top of `base`, the last axis varying fastest
+pub fn grid(base: &Config, axes: &[SweepAxis]) -> Result<Vec<SweepPoint>> {
+    let mut points = vec![SweepPoint {
+        assignments: Vec::new(),
+        config: base.clone(),
+    }];
+    for axis in axes {
+        let mut next = Vec::with_capacity(points.len() * axis.values.len());
+        for point in &points {
+            for value in &axis.values {
+                let mut config = point.config.clone();
+                config.set_value(&format!("{}={}", axis.key, value))?;
+                config.validate().with_context(|| format!("sweep point {}={}", axis.key, value))?;
+                let mut assignments = point.assignments.clone();
+                assignments.push((axis.key.clone(), value.clone()));
+                next.push(SweepPoint { assignments, config });
+            }
+        }
+        points = next;
+    }
+    Ok(points)
+}
+
+/// The snapshots a replay of `ticks` hands the trading loop, one every `step` of recorded time
+pub async fn cycles(ticks: &[Tick], step: Duration) -> Vec<CycleSnapshot> {
+    let replay = ReplaySource::from_ticks(ticks.to_vec(), ReplaySpeed::AsFastAsPossible);
+    let mut cycles = Vec::new();
+    while replay.advance(step).await {
+        cycles.push(CycleSnapshot {
+            cycle: cycles.len() as u64 + 1,
+            elapsed_secs: replay.elapsed().as_secs(),
+            prices: replay.get_prices().await,
+            buy_draw: None,
+            sell_draw: None,
+        });
+    }
+    cycles
+}
+
+/// How one grid point did
+#[derive(Debug, Clone)]
+pub struct SweepResult {
+    pub assignments: Vec<(String, String)>,
+    pub cycles: usize,
+    pub run: WhatIfRun,
+}
+
+impl SweepResult {
+    fn value(&self, key: &str) -> &str {
+        self.assignments.iter().find(|(k, _)| k == key).map_or("", |(_, v)| v.as_str())
+    }
+}
+
+/// Grid results over one recording, best net PnL first
+#[derive(Debug, Clone)]
+pub struct SweepReport {
+    pub ticks: usize,
+    /// Swept keys, in axis order
+    pub keys: Vec<String>,
+    pub results: Vec<SweepResult>,
+}
+
+impl SweepReport {
+    pub fn new(ticks: usize, axes: &[SweepAxis], mut results: Vec<SweepResult>) -> Self {
+        results.sort_by(|a, b| b.run.stats.total_pnl.total_cmp(&a.run.stats.total_pnl));
+        Self {
+            ticks,
+            keys: axes.iter().map(|axis| axis.key.clone()).collect(),
+            results,
+        }
+    }
+
+    /// One row per grid point: the swept values, then the resulting stats
+    pub fn write_csv(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+        let mut out = BufWriter::new(file);
+        let mut header = self.keys.clone();
+        header.extend(
+            ["cycles", "trades", "maker_trades", "taker_trades", "notional_usd", "gross_pnl", "fees_usd", "net_pnl",
+             "inventory_eth", "unrealized_pnl", "max_drawdown", "sharpe"].map(String::from),
+        );
+        writeln!(out, "{}", header.join(","))?;
+        for result in &self.results {
+            let stats = &result.run.stats;
+            let mut row: Vec<String> = self.keys.iter().map(|key| csv_field(result.value(key))).collect();
+            row.extend([
+                result.cycles.to_string(),
+                stats.total_trades.to_string(),
+                stats.maker_trades.to_string(),
+                stats.taker_trades.to_string(),
+                format!("{:.2}", stats.total_notional),
+                format!("{:.4}", stats.gross_pnl),
+                format!("{:.4}", stats.total_fees),
+                format!("{:.4}", stats.total_pnl),
+                format!("{:.6}", result.run.position.net_eth),
+                format!("{:.4}", result.run.unrealized_pnl()),
+                format!("{:.4}", stats.performance.max_drawdown),
+                stats.performance.sharpe_ratio().map_or(String::new(), |s| format!("{:.4}", s)),
+            ]);
+            writeln!(out, "{}", row.join(","))?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// Quote a CSV field holding a comma, e.g. an ensemble model list
+fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for SweepReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Parameter sweep over {} ticks: {} runs, best net PnL first\n", self.ticks, self.results.len())?;
+        let widths: Vec<usize> = self
+            .keys
+            .iter()
+            .map(|key| self.results.iter().map(|r| r.value(key).len()).chain([key.len()]).max().unwrap_or(0))
+            .collect();
+        for (key, width) in self.keys.iter().zip(&widths) {
+            write!(f, "  {:<width$}", key, width = width)?;
+        }
+        writeln!(f, "  {:>7} {:>14} {:>10} {:>12} {:>11} {:>11} {:>10} {:>8}",
+            "TRADES", "NOTIONAL", "FEES", "NET PNL", "INV (ETH)", "MTM", "MAX DD", "SHARPE")?;
+        for result in &self.results {
+            for (key, width) in self.keys.iter().zip(&widths) {
+                write!(f, "  {:<width$}", result.value(key), width = width)?;
+            }
+            let stats = &result.run.stats;
+            writeln!(f, "  {:>7} {:>14.0} {:>10.2} {:>12.2} {:>+11.4} {:>11.2} {:>10.2} {:>8}",
+                stats.total_trades, stats.total_notional, stats.total_fees, stats.total_pnl,
+                result.run.position.net_eth, result.run.unrealized_pnl(), stats.performance.max_drawdown,
+                stats.performance.sharpe_ratio().map_or("-".to_string(), |s| format!("{:.2}", s)))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use crate::aggregator::Quote;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::Duration;
+
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 3600.0;
+
+/// Parameters for a geometric Brownian motion price path
+#[derive(Debug, Clone, Copy)]
+pub struct SyntheticConfig {
+    pub initial_price: f64,
+    /// Annualized drift (0.05 = 5% per year)
+    pub drift: f64,
+    /// Annualized volatility (0.60 = 60% per year)
+    pub volatility: f64,
+    /// Full bid/ask spread in basis points
+    pub spread_bps: f64,
+    pub tick_interval: Duration,
+    pub seed: u64,
+}
+
+impl Default for SyntheticConfig {
+    fn default() -> Self {
+        Self {
+            initial_price: 3000.0,
+            drift: 0.0,
+            volatility: 0.60,
+            spread_bps: 2.0,
+            tick_interval: Duration::from_secs(1),
+            seed: 42,
+        }
+    }
+}
+
+/// Deterministic GBM quote generator: the same seed always yields the same path
+pub struct SyntheticSource {
+    config: SyntheticConfig,
+    price: f64,
+    rng: StdRng,
+}
+
+impl SyntheticSource {
+    pub fn new(config: SyntheticConfig) -> Self {
+        Self {
+            price: config.initial_price,
+            rng: StdRng::seed_from_u64(config.seed),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &SyntheticConfig {
+        &self.config
+    }
+
+    /// Advance the path by one tick interval and return the new quote
+    pub fn next_quote(&mut self, timestamp: i64) -> Quote {
+        let dt = self.config.tick_interval.as_secs_f64() / SECONDS_PER_YEAR;
+        let sigma = self.config.volatility;
+        let z = self.standard_normal();
+
+        self.price *= ((self.config.drift - 0.5 * sigma * sigma) * dt + sigma * dt.sqrt() * z).exp();
+
+        let half_spread = self.price * self.config.spread_bps / 10000.0 / 2.0;
+        Quote {
+            bid: self.price - half_spread,
+            ask: self.price + half_spread,
+            timestamp,
+        }
+    }
+
+    // Box-Muller transform
+    fn standard_normal(&mut self) -> f64 {
+        let u1: f64 = self.rng.random_range(f64::EPSILON..1.0);
+        let u2: f64 = self.rng.random();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
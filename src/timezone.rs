@@ -0,0 +1,99 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, Timelike, Utc};
+use chrono_tz::Tz;
+
+/// Timezone used to display timestamps; everything is stored as UTC epoch millis
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ReportTimezone {
+    #[default]
+    Utc,
+    /// The machine's local timezone
+    Local,
+    /// An IANA zone such as `America/New_York`
+    Named(Tz),
+    /// A fixed offset such as `+09:00`
+    Fixed(FixedOffset),
+}
+
+impl std::str::FromStr for ReportTimezone {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "utc" | "UTC" | "Z" => Ok(ReportTimezone::Utc),
+            "local" => Ok(ReportTimezone::Local),
+            _ if s.starts_with('+') || s.starts_with('-') => s
+                .parse::<FixedOffset>()
+                .map(ReportTimezone::Fixed)
+                .map_err(|e| anyhow!("invalid UTC offset '{}': {}", s, e)),
+            _ => s
+                .parse::<Tz>()
+                .map(ReportTimezone::Named)
+                .map_err(|e| anyhow!("unknown timezone '{}': {}", s, e)),
+        }
+    }
+}
+
+impl ReportTimezone {
+    pub fn name(&self) -> String {
+        match self {
+            ReportTimezone::Utc => "UTC".to_string(),
+            ReportTimezone::Local => "local".to_string(),
+            ReportTimezone::Named(tz) => tz.name().to_string(),
+            ReportTimezone::Fixed(offset) => offset.to_string(),
+        }
+    }
+
+    fn in_zone(&self, utc: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            ReportTimezone::Utc => utc.fixed_offset(),
+            ReportTimezone::Local => utc.with_timezone(&Local).fixed_offset(),
+            ReportTimezone::Named(tz) => utc.with_timezone(tz).fixed_offset(),
+            ReportTimezone::Fixed(offset) => utc.with_timezone(offset),
+        }
+    }
+
+    /// Format epoch millis in this timezone using a chrono format string
+    pub fn format_millis(&self, timestamp: i64, fmt: &str) -> String {
+        match DateTime::from_timestamp_millis(timestamp) {
+            Some(utc) => match self {
+                // Keep zone abbreviations (e.g. EST/EDT) for named zones
+                ReportTimezone::Named(tz) => utc.with_timezone(tz).format(fmt).to_string(),
+                _ => self.in_zone(utc).format(fmt).to_string(),
+            },
+            None => "-".to_string(),
+        }
+    }
+
+    /// `2025-01-31 14:05:09 EST`
+    pub fn format_datetime(&self, timestamp: i64) -> String {
+        self.format_millis(timestamp, "%Y-%m-%d %H:%M:%S %Z")
+    }
+
+    /// `14:05:09`
+    pub fn format_time(&self, timestamp: i64) -> String {
+        self.format_millis(timestamp, "%H:%M:%S")
+    }
+
+    /// Calendar date of a timestamp in this timezone, for daily boundaries
+    pub fn date_of(&self, timestamp: i64) -> Option<NaiveDate> {
+        DateTime::from_timestamp_millis(timestamp).map(|utc| self.in_zone(utc).date_naive())
+    }
+
+    /// Hour of day (0-23) of a timestamp in this timezone
+    pub fn hour_of(&self, timestamp: i64) -> Option<u32> {
+        DateTime::from_timestamp_millis(timestamp).map(|utc| self.in_zone(utc).hour())
+    }
+
+    /// Current time in this timezone
+    pub fn now(&self) -> DateTime<FixedOffset> {
+        self.in_zone(Utc::now())
+    }
+}
+
+impl std::fmt::Display for ReportTimezone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
@@ -0,0 +1,250 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Chain the built-in EVM deployments are on
+pub const ETHEREUM: &str = "ethereum";
+
+/// Wrapped SOL, whose USD price is the SOL reference rate
+pub const WRAPPED_SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// Address CowSwap takes for buying a chain's native coin rather than its wrapped token
+const NATIVE_PLACEHOLDER: &str = "0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE";
+
+/// An ERC-20 token on one EVM chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvmToken {
+    pub address: String,
+    pub decimals: u32,
+    /// Address to buy the chain's native coin under instead of the wrapped token, if CowSwap should
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub native_address: Option<String>,
+}
+
+/// An SPL token Jupiter routes through
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SplToken {
+    pub mint: String,
+    pub decimals: u32,
+}
+
+/// One asset as each venue names it. A venue is only usable for a pair when both tokens have an
+/// entry for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Token {
+    pub symbol: String,
+    /// Asset code on Binance, e.g. `ETH`; a pair's symbol is the base code then the quote code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binance: Option<String>,
+    /// ERC-20 deployments by chain name, for CowSwap and Uniswap
+    #[serde(default)]
+    pub chains: BTreeMap<String, EvmToken>,
+    /// Solana mint, for Jupiter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solana: Option<SplToken>,
+}
+
+impl Token {
+    fn new(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            binance: Some(symbol.to_string()),
+            chains: BTreeMap::new(),
+            solana: None,
+        }
+    }
+
+    fn on(mut self, chain: &str, address: &str, decimals: u32) -> Self {
+        self.chains.insert(chain.to_string(), EvmToken {
+            address: address.to_string(),
+            decimals,
+            native_address: None,
+        });
+        self
+    }
+
+    fn with_solana(mut self, mint: &str, decimals: u32) -> Self {
+        self.solana = Some(SplToken { mint: mint.to_string(), decimals });
+        self
+    }
+
+    pub fn evm(&self, chain: &str) -> Result<&EvmToken> {
+        self.chains.get(chain).ok_or_else(|| anyhow!("token {} has no address on {}", self.symbol, chain))
+    }
+
+    pub fn spl(&self) -> Result<&SplToken> {
+        self.solana.as_ref().ok_or_else(|| anyhow!("token {} has no Solana mint", self.symbol))
+    }
+
+    pub fn binance_code(&self) -> Result<&str> {
+        self.binance.as_deref().ok_or_else(|| anyhow!("token {} has no Binance asset code", self.symbol))
+    }
+}
+
+/// Tokens by symbol. Starts with ETH, USDC, USDT and SOL; config entries add to or replace them.
+#[derive(Debug, Clone)]
+pub struct TokenRegistry {
+    tokens: BTreeMap<String, Token>,
+}
+
+impl Default for TokenRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl TokenRegistry {
+    pub fn builtin() -> Self {
+        let mut eth = Token::new("ETH")
+            .on(ETHEREUM, "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2", 18)
+            // Wormhole ETH
+            .with_solana("7vfCXTUXx5WJV5JADk17DUJ4ksgau7utNKj4b963voxs", 8);
+        if let Some(weth) = eth.chains.get_mut(ETHEREUM) {
+            weth.native_address = Some(NATIVE_PLACEHOLDER.to_string());
+        }
+        let tokens = [
+            eth,
+            Token::new("USDC")
+                .on(ETHEREUM, "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", 6)
+                .with_solana("EPjFWdd5AufqSSqeM2qN1xyybapC8G4wEGGkZwyTDt1v", 6),
+            Token::new("USDT")
+                .on(ETHEREUM, "0xdAC17F958D2ee523a2206206994597C13D831ec7", 6)
+                .with_solana("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", 6),
+            Token::new("SOL").with_solana(WRAPPED_SOL_MINT, 9),
+        ];
+        Self {
+            tokens: tokens.into_iter().map(|t| (t.symbol.to_ascii_uppercase(), t)).collect(),
+        }
+    }
+
+    /// Add `token`, replacing any token with the same symbol
+    pub fn with_token(mut self, token: Token) -> Self {
+        self.tokens.insert(token.symbol.to_ascii_uppercase(), token);
+        self
+    }
+
+    pub fn get(&self, symbol: &str) -> Result<&Token> {
+        self.tokens.get(&symbol.to_ascii_uppercase()).ok_or_else(|| {
+            let known: Vec<&str> = self.tokens.values().map(|t| t.symbol.as_str()).collect();
+            anyhow!("unknown token '{}' (registered: {})", symbol, known.join(", "))
+        })
+    }
+
+    pub fn tokens(&self) -> impl Iterator<Item = &Token> {
+        self.tokens.values()
+    }
+
+    /// The `base`/`quote` pair, with EVM venues on `chain`
+    pub fn pair(&self, base: &str, quote: &str, chain: &str) -> Result<TradingPair> {
+        Ok(TradingPair {
+            base: self.get(base)?.clone(),
+            quote: self.get(quote)?.clone(),
+            chain: chain.to_string(),
+        })
+    }
+}
+
+/// The asset quoted and the asset it's priced in, as each venue names them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradingPair {
+    pub base: Token,
+    pub quote: Token,
+    /// Chain the CowSwap and Uniswap legs trade on
+    pub chain: String,
+}
+
+impl Default for TradingPair {
+    /// ETH/USDC on Ethereum
+    fn default() -> Self {
+        TokenRegistry::builtin().pair("ETH", "USDC", ETHEREUM).expect("built-in ETH/USDC")
+    }
+}
+
+impl TradingPair {
+    /// Base then quote token on the pair's chain
+    pub fn evm(&self) -> Result<(&EvmToken, &EvmToken)> {
+        Ok((self.base.evm(&self.chain)?, self.quote.evm(&self.chain)?))
+    }
+
+    /// Base then quote Solana mint
+    pub fn spl(&self) -> Result<(&SplToken, &SplToken)> {
+        Ok((self.base.spl()?, self.quote.spl()?))
+    }
+
+    /// Spot symbol on Binance, e.g. `ETHUSDC`
+    pub fn binance_symbol(&self) -> Result<String> {
+        Ok(format!("{}{}", self.base.binance_code()?, self.quote.binance_code()?))
+    }
+}
+
+impl fmt::Display for TradingPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.base.symbol, self.quote.symbol)
+    }
+}
+
+/// Whole tokens to base units
+pub fn to_units(amount: f64, decimals: u32) -> u128 {
+    (amount * 10f64.powi(decimals as i32)) as u128
+}
+
+/// Base units to whole tokens
+pub fn from_units(amount: u128, decimals: u32) -> f64 {
+    amount as f64 / 10f64.powi(decimals as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn units_round_trip() {
+        assert_eq!(to_units(1.5, 18), 1_500_000_000_000_000_000);
+        assert_eq!(to_units(2500.123456, 6), 2_500_123_456);
+        assert_eq!(to_units(0.29, 6), 290_000);
+        assert_eq!(to_units(0.0, 9), 0);
+        assert_eq!(from_units(1_500_000, 6), 1.5);
+        assert_eq!(from_units(123_456_789, 8), 1.23456789);
+        for (amount, decimals) in [(0.1, 18), (3.7, 8), (42.000001, 6), (0.001, 9)] {
+            assert!((from_units(to_units(amount, decimals), decimals) - amount).abs() < 1e-12, "{} at {} decimals", amount, decimals);
+        }
+    }
+
+    #[test]
+    fn builtin_lookup_ignores_case() {
+        let registry = TokenRegistry::builtin();
+        assert_eq!(registry.get("usdc").unwrap().symbol, "USDC");
+        assert_eq!(registry.get("Eth").unwrap().evm(ETHEREUM).unwrap().decimals, 18);
+        let err = registry.get("DOGE").unwrap_err().to_string();
+        assert!(err.contains("unknown token 'DOGE'") && err.contains("USDC"), "{}", err);
+    }
+
+    #[test]
+    fn configured_tokens_add_to_and_replace_builtins() {
+        let arb = "arbitrum";
+        let registry = TokenRegistry::builtin()
+            .with_token(Token::new("USDC").on(arb, "0xaf88d065e77c8cC2239327C5EDb3A432268e5831", 6))
+            .with_token(Token::new("ARB").on(arb, "0x912CE59144191C1204E64559FE8253a0e49E6548", 18));
+        assert_eq!(registry.tokens().count(), 5);
+
+        let usdc = registry.get("USDC").unwrap();
+        assert!(usdc.evm(ETHEREUM).is_err() && usdc.solana.is_none(), "the override replaces the whole entry");
+        assert_eq!(usdc.evm(arb).unwrap().decimals, 6);
+
+        let pair = registry.pair("arb", "usdc", arb).unwrap();
+        assert_eq!(pair.to_string(), "ARB/USDC");
+        assert_eq!(pair.binance_symbol().unwrap(), "ARBUSDC");
+        assert!(pair.evm().is_ok() && pair.spl().is_err());
+    }
+
+    #[test]
+    fn default_pair_is_eth_usdc() {
+        let pair = TradingPair::default();
+        assert_eq!(pair.binance_symbol().unwrap(), "ETHUSDC");
+        let (base, quote) = pair.evm().unwrap();
+        assert_eq!((base.decimals, quote.decimals), (18, 6));
+        assert_eq!(base.native_address.as_deref(), Some(NATIVE_PLACEHOLDER));
+        assert_eq!(pair.spl().unwrap().1.mint, "EPjFWdd5AufqSSqeM2qN1xyybapC8G4wEGGkZwyTDt1v");
+    }
+}
@@ -0,0 +1,321 @@
+use crate::trader::Trade;
+use anyhow::{Context, Result};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Trades moved out of memory before they're written to the spill file and flushed as one batch
+pub const SPILL_BATCH: usize = 64;
+
+/// Append-only JSON Lines file the oldest trades are moved to
+#[derive(Debug)]
+struct Spill {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    /// Trades written and flushed; lines past these may be a batch that failed halfway
+    on_disk: usize,
+    /// Spilled trades waiting for the next batch, oldest first
+    pending: Vec<Trade>,
+}
+
+impl Spill {
+    fn append(&mut self, trade: Trade) -> Result<()> {
+        self.pending.push(trade);
+        if self.pending.len() >= SPILL_BATCH {
+            self.write_pending()?;
+        }
+        Ok(())
+    }
+
+    fn write_pending(&mut self) -> Result<()> {
+        for trade in &self.pending {
+            serde_json::to_writer(&mut self.writer, trade)?;
+            self.writer.write_all(b"\n")?;
+        }
+        self.writer.flush()?;
+        self.on_disk += self.pending.len();
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+/// Read the first `trades` lines of the spill file at `path`
+fn read_spill(path: &Path, trades: usize, mut visit: impl FnMut(Trade) -> Result<()>) -> Result<()> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    for (i, line) in BufReader::new(file).lines().take(trades).enumerate() {
+        let line = line.with_context(|| format!("failed to read {}", path.display()))?;
+        let trade = serde_json::from_str(&line).with_context(|| format!("bad trade on line {} of {}", i + 1, path.display()))?;
+        visit(trade)?;
+    }
+    Ok(())
+}
+
+/// Every recorded trade, oldest first: the latest `max_in_memory` in memory and the rest spilled to
+/// disk, so a long session's memory stays flat. Unbounded unless built with `spilling`.
+#[derive(Debug, Default)]
+pub struct TradeHistory {
+    recent: VecDeque<Trade>,
+    max_in_memory: Option<usize>,
+    spill: Option<Spill>,
+}
+
+impl TradeHistory {
+    /// Keep every trade in memory
+    pub fn unbounded() -> Self {
+        Self::default()
+    }
+
+    /// Keep the latest `max_in_memory` trades in memory and move older ones to `path`, which is
+    /// created or truncated. Spilled trades are written in batches of `SPILL_BATCH`.
+    pub fn spilling(path: impl Into<PathBuf>, max_in_memory: usize) -> Result<Self> {
+        let path = path.into();
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        let file = File::create(&path).with_context(|| format!("failed to create {}", path.display()))?;
+        Ok(Self {
+            recent: VecDeque::new(),
+            max_in_memory: Some(max_in_memory),
+            spill: Some(Spill { path, writer: BufWriter::new(file), on_disk: 0, pending: Vec::new() }),
+        })
+    }
+
+    /// Record `trade`, spilling the oldest in-memory trades past the window. If the spill file
+    /// can't be written, spilling stops and every trade stays in memory from then on.
+    pub fn push(&mut self, trade: Trade) -> Result<()> {
+        self.recent.push_back(trade);
+        let (Some(max), Some(spill)) = (self.max_in_memory, self.spill.as_mut()) else {
+            return Ok(());
+        };
+        let mut result = Ok(());
+        while self.recent.len() > max {
+            let oldest = self.recent.pop_front().expect("window over capacity");
+            if let Err(e) = spill.append(oldest) {
+                result = Err(e);
+                break;
+            }
+        }
+        if result.is_err() {
+            // The batch that failed to write goes back in memory, ahead of the window
+            for trade in spill.pending.drain(..).rev() {
+                self.recent.push_front(trade);
+            }
+            self.max_in_memory = None;
+        }
+        result.with_context(|| format!("failed to spill trades to {}", spill.path.display()))
+    }
+
+    /// Write out the batch of spilled trades that hasn't filled up yet, e.g. as the session ends
+    pub fn flush(&mut self) -> Result<()> {
+        let Some(spill) = self.spill.as_mut().filter(|s| !s.pending.is_empty()) else {
+            return Ok(());
+        };
+        spill.write_pending().with_context(|| format!("failed to spill trades to {}", spill.path.display()))
+    }
+
+    /// Trades recorded, in memory and on disk
+    pub fn len(&self) -> usize {
+        self.spilled() + self.recent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Trades moved out of the in-memory window, including a batch not yet written
+    pub fn spilled(&self) -> usize {
+        self.spill.as_ref().map_or(0, |s| s.on_disk + s.pending.len())
+    }
+
+    pub fn spill_path(&self) -> Option<&Path> {
+        self.spill.as_ref().map(|s| s.path.as_path())
+    }
+
+    /// Trades that aren't on disk, oldest first
+    pub fn in_memory(&self) -> impl Iterator<Item = &Trade> {
+        self.spill.iter().flat_map(|s| s.pending.iter()).chain(self.recent.iter())
+    }
+
+    /// Trades that have to be read back from the spill file
+    pub fn on_disk(&self) -> usize {
+        self.spill.as_ref().map_or(0, |s| s.on_disk)
+    }
+
+    /// The latest `n` trades, oldest first, when they're all in memory
+    pub fn recent_in_memory(&self, n: usize) -> Option<Vec<Trade>> {
+        let in_memory = self.len() - self.on_disk();
+        (n <= in_memory || self.on_disk() == 0).then(|| self.in_memory().skip(in_memory.saturating_sub(n)).cloned().collect())
+    }
+
+    /// The history as it stands, to read without holding on to it
+    pub fn reader(&self) -> HistoryReader {
+        HistoryReader {
+            in_memory: self.in_memory().cloned().collect(),
+            ..self.spill_reader()
+        }
+    }
+
+    /// Only the trades on disk, to read without holding on to the history
+    pub fn spill_reader(&self) -> HistoryReader {
+        HistoryReader {
+            spill: self.spill.as_ref().filter(|s| s.on_disk > 0).map(|s| (s.path.clone(), s.on_disk)),
+            in_memory: Vec::new(),
+        }
+    }
+}
+
+/// A trade history as of one moment: how much of the spill file to read and a copy of the trades
+/// that weren't on disk yet. The file is append-only, so a reader stays valid while trading
+/// continues. Reading the file blocks, so async callers read from a blocking task.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryReader {
+    spill: Option<(PathBuf, usize)>,
+    in_memory: Vec<Trade>,
+}
+
+impl HistoryReader {
+    pub fn len(&self) -> usize {
+        self.spill.as_ref().map_or(0, |(_, trades)| *trades) + self.in_memory.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Visit every trade oldest first, stopping at the first error `visit` returns
+    pub fn try_for_each(&self, mut visit: impl FnMut(&Trade) -> Result<()>) -> Result<()> {
+        if let Some((path, trades)) = &self.spill {
+            read_spill(path, *trades, |trade| visit(&trade))?;
+        }
+        self.in_memory.iter().try_for_each(visit)
+    }
+
+    /// Visit every trade oldest first. The in-memory trades are still visited if the spill file
+    /// fails to read.
+    pub fn for_each(&self, mut visit: impl FnMut(&Trade)) -> Result<()> {
+        let spilled = match &self.spill {
+            Some((path, trades)) => read_spill(path, *trades, |trade| {
+                visit(&trade);
+                Ok(())
+            }),
+            None => Ok(()),
+        };
+        self.in_memory.iter().for_each(visit);
+        spilled
+    }
+
+    /// The latest `n` trades, oldest first; only touches the disk when `n` reaches past memory
+    pub fn recent(&self, n: usize) -> Result<Vec<Trade>> {
+        let on_disk = self.spill.as_ref().map_or(0, |(_, trades)| *trades);
+        let from_disk = n.saturating_sub(self.in_memory.len()).min(on_disk);
+        let mut trades = Vec::with_capacity(n.min(self.len()));
+        if let (Some((path, _)), true) = (&self.spill, from_disk > 0) {
+            let skip = on_disk - from_disk;
+            let mut index = 0;
+            read_spill(path, on_disk, |trade| {
+                if index >= skip {
+                    trades.push(trade);
+                }
+                index += 1;
+                Ok(())
+            })?;
+        }
+        let start = self.in_memory.len().saturating_sub(n);
+        trades.extend(self.in_memory[start..].iter().cloned());
+        Ok(trades)
+    }
+
+    /// The latest `n` trades that were in memory, for when the spill file can't be read
+    pub fn recent_in_memory(&self, n: usize) -> Vec<Trade> {
+        self.in_memory[self.in_memory.len().saturating_sub(n)..].to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fees::FeeBreakdown;
+    use crate::trader::{LiquidityRole, TradeSide};
+    use crate::units::ConversionSnapshot;
+
+    fn trade(id: u64) -> Trade {
+        Trade {
+            id,
+            side: TradeSide::Buy,
+            price: 3000.0,
+            amount_eth: 0.1,
+            notional_usd: 300.0,
+            pnl: id as f64,
+            timestamp: id as i64 * 1000,
+            execution_prob: 0.5,
+            conversion: ConversionSnapshot::default(),
+            role: LiquidityRole::Maker,
+            fees_usd: 0.0,
+            gross_pnl: 0.0,
+            fees: FeeBreakdown::default(),
+            fills: Vec::new(),
+            degraded: false,
+            hedge: false,
+            adverse_selection_usd: 0.0,
+            venue: None,
+            mark_venue: None,
+            tag: None,
+            decided_at: None,
+        }
+    }
+
+    fn ids(trades: &[Trade]) -> Vec<u64> {
+        trades.iter().map(|t| t.id).collect()
+    }
+
+    fn spill_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("mm-trade-history-{}-{}.jsonl", name, std::process::id()))
+    }
+
+    #[test]
+    fn unbounded_keeps_everything_in_memory() {
+        let mut history = TradeHistory::unbounded();
+        for id in 0..100 {
+            history.push(trade(id)).unwrap();
+        }
+        assert_eq!((history.len(), history.spilled(), history.on_disk()), (100, 0, 0));
+        assert_eq!(ids(&history.recent_in_memory(3).unwrap()), vec![97, 98, 99]);
+        assert!(history.spill_path().is_none());
+    }
+
+    #[test]
+    fn spilled_trades_read_back_in_order() {
+        let path = spill_path("round-trip");
+        let mut history = TradeHistory::spilling(&path, 10).unwrap();
+        let total = 3 * SPILL_BATCH as u64 + 10 + 5;
+        for id in 0..total {
+            history.push(trade(id)).unwrap();
+        }
+        assert_eq!(history.len(), total as usize);
+        assert_eq!(history.on_disk(), 3 * SPILL_BATCH);
+        assert_eq!(history.spilled(), 3 * SPILL_BATCH + 5);
+        assert_eq!(ids(&history.in_memory().cloned().collect::<Vec<_>>()), (3 * SPILL_BATCH as u64..total).collect::<Vec<_>>());
+
+        let reader = history.reader();
+        let mut visited = Vec::new();
+        reader.for_each(|t| visited.push(t.id)).unwrap();
+        assert_eq!(visited, (0..total).collect::<Vec<_>>());
+
+        // Within memory, then reaching back into the spill file
+        assert_eq!(ids(&reader.recent(3).unwrap()), vec![total - 3, total - 2, total - 1]);
+        assert_eq!(ids(&reader.recent(20).unwrap()), (total - 20..total).collect::<Vec<_>>());
+        assert_eq!(reader.recent(1000).unwrap().len(), total as usize);
+        assert_eq!(history.recent_in_memory(15).map(|t| t.len()), Some(15));
+        assert!(history.recent_in_memory(16).is_none());
+        assert_eq!(history.spill_reader().len(), 3 * SPILL_BATCH);
+
+        history.flush().unwrap();
+        assert_eq!((history.on_disk(), history.spilled()), (3 * SPILL_BATCH + 5, 3 * SPILL_BATCH + 5));
+        let mut visited = Vec::new();
+        history.reader().for_each(|t| visited.push(t.id)).unwrap();
+        assert_eq!(visited, (0..total).collect::<Vec<_>>());
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,103 +1,773 @@
-use crate::aggregator::AggregatedPrices;
-use rand::Rng;
+use crate::aggregation::AggregationPolicy;
+use crate::aggregator::{AggregatedPrices, FeedEvent, PriceSnapshot, QuoteUpdate, Source};
+use crate::amount;
+use crate::clock::{self, Clock};
+use crate::draws::DrawSource;
+use crate::dropcopy::{DropCopy, OrdType, TimeInForce};
+use crate::fees::{FeeBreakdown, FeeSchedule};
+use crate::hedger::HedgePolicy;
+use crate::liquidity::LiquidityProfile;
+use crate::execution::{AdvancedModel, BasicModel, ExecutionModel, FillContext, ModelPrediction};
+use crate::participation::{ParticipationLimiter, ParticipationPolicy, ParticipationStats};
+use crate::paper::VenueFill;
+use crate::orders::{OrderAction, OrderId, OrderManager, OrderRequest, OrderState, OrderStats, RestingOrder};
+use crate::position::Position;
+use crate::quoting::{MedianQuote, QuotingStrategy};
+use crate::quiet::{QuietEvent, QuietInterval, QuietMarketDetector, QuietMarketPolicy};
+use crate::quorum::{QuorumPolicy, TradingMode};
+use crate::risk::{BreachAction, RiskEvent, RiskLimits, RiskManager, RiskState};
+use crate::router::{ChildFill, SmartOrderRouter};
+use crate::sizing::{FixedSize, RealizedVolatility, SizingContext, SizingRule, DEFAULT_VOL_WINDOW_MS};
+use crate::timezone::ReportTimezone;
+use crate::two_sided::{TwoSidedAttempt, TwoSidedDecision, TwoSidedPolicy};
+use crate::units::ConversionSnapshot;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, error, info, instrument, warn};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Trade {
+    pub id: u64,
     pub side: TradeSide,
     pub price: f64,
     pub amount_eth: f64,
     pub notional_usd: f64,
+    /// Net of fees, gas and slippage
     pub pnl: f64,
     pub timestamp: i64,
     pub execution_prob: f64,
+    pub conversion: ConversionSnapshot,
+    pub role: LiquidityRole,
+    /// Total of `fees`, already deducted from `pnl`
+    pub fees_usd: f64,
+    /// Mark-to-market PnL before costs
+    #[serde(default)]
+    pub gross_pnl: f64,
+    #[serde(default)]
+    pub fees: FeeBreakdown,
+    /// Per-venue child fills when the notional was split by the router
+    #[serde(default)]
+    pub fills: Vec<ChildFill>,
+    /// Filled while below source quorum, off a reduced set of feeds
+    #[serde(default)]
+    pub degraded: bool,
+    /// Inventory hedge rather than a market-making fill
+    #[serde(default)]
+    pub hedge: bool,
+    /// Gross PnL lost to the market moving between the fill decision and its confirmation
+    #[serde(default)]
+    pub adverse_selection_usd: f64,
+    /// Venue whose price the fill came from: the touch a quote competed with or a taker crossed,
+    /// or the venue that traded through a resting order. Routed fills also name theirs in `fills`.
+    #[serde(default)]
+    pub venue: Option<Source>,
+    /// Venue whose touch the fill was marked against
+    #[serde(default)]
+    pub mark_venue: Option<Source>,
+    /// Label of the strategy or experiment that made the fill, if the engine was given one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// When the fill was decided, for fills confirmed after the fill latency
+    #[serde(skip)]
+    pub decided_at: Option<i64>,
+}
+
+impl Trade {
+    /// Venues the fill's price came from, each with its share of the notional
+    pub fn fill_venues(&self) -> Vec<(Source, f64)> {
+        if self.fills.is_empty() || self.notional_usd <= 0.0 {
+            return self.venue.map(|venue| (venue, 1.0)).into_iter().collect();
+        }
+        self.fills.iter().map(|fill| (fill.venue, fill.notional_usd / self.notional_usd)).collect()
+    }
+}
+
+/// Whether a fill rested on the book or crossed the spread
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LiquidityRole {
+    Maker,
+    Taker,
+}
+
+/// When the engine crosses the spread instead of quoting passively.
+/// Taker fees come from the engine's fee schedule.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TakerPolicy {
+    /// Minimum distance of the best opposite touch through the median mid before crossing
+    pub signal_bps: f64,
+    /// Cost of crossing, charged with the fill's slippage
+    pub slippage_bps: f64,
+}
+
+/// Economics a passive quote has to clear before it is placed
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct EdgeRequirement {
+    pub min_edge_bps: f64,
+    /// Expected move against us after a fill, in bps
+    pub adverse_selection_bps: f64,
+}
+
+impl Default for EdgeRequirement {
+    fn default() -> Self {
+        Self {
+            min_edge_bps: 0.0,
+            adverse_selection_bps: 0.5,
+        }
+    }
+}
+
+impl EdgeRequirement {
+    /// Half-spread captured versus `mid`, minus costs (`cost_bps`) and adverse selection
+    pub fn expected_edge_bps(&self, side: TradeSide, our_price: f64, mid: f64, cost_bps: f64) -> f64 {
+        let half_spread_bps = match side {
+            TradeSide::Buy => (mid - our_price) / mid * 10000.0,
+            TradeSide::Sell => (our_price - mid) / mid * 10000.0,
+        };
+        half_spread_bps - cost_bps - self.adverse_selection_bps
+    }
+}
+
+impl TakerPolicy {
+    pub fn new(signal_bps: f64) -> Self {
+        Self {
+            signal_bps,
+            slippage_bps: 2.0,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TradeSide {
     Buy,
     Sell,
 }
 
+/// One quote-and-maybe-fill decision, whether or not it executed
+#[derive(Debug, Clone)]
+pub struct ExecutionAttempt {
+    pub side: TradeSide,
+    pub price: f64,
+    pub execution_prob: f64,
+    pub model_predictions: Vec<ModelPrediction>,
+    pub timestamp: i64,
+    /// Newest quote the decision saw, in exchange time; what per-hour breakdowns bucket by, so
+    /// live sessions line up with the marks and the tape rather than the local clock
+    pub feed_time: i64,
+    pub role: LiquidityRole,
+    /// Expected edge of the quote net of fees and adverse selection, in bps
+    pub expected_edge_bps: f64,
+    /// False when the quote was withheld for not clearing the minimum edge
+    pub quoted: bool,
+    /// Uniform draw the fill decision was made with
+    pub draw: f64,
+    /// Made in degraded mode with a widened quote and reduced size
+    pub degraded: bool,
+    /// How far the price sat behind our side's best touch, in bps of the median mid;
+    /// negative when improving on it, and for taker fills, which cross it
+    pub touch_distance_bps: f64,
+    /// Notional the order went out for, before the participation cap cut its fill down
+    pub order_notional: f64,
+    pub trade: Option<Trade>,
+    /// Filled, but the trade arrives from `confirm_fills` once the fill latency has passed
+    pub pending: bool,
+}
+
+impl ExecutionAttempt {
+    /// Whether the attempt has a confirmed fill; a pending fill counts once `confirm_fills` returns it
+    pub fn executed(&self) -> bool {
+        self.trade.is_some()
+    }
+}
+
+/// Venue whose touch marks a fill on `side`: the best bid for buys, the best ask for sells
+fn mark_source(prices: &AggregatedPrices, side: TradeSide) -> Option<Source> {
+    match side {
+        TradeSide::Buy => prices.best_bid_source(),
+        TradeSide::Sell => prices.best_ask_source(),
+    }
+}
+
+/// Distance of `price` behind the same-side `touch` in bps of `mid`: below the best bid for buys,
+/// above the best ask for sells
+fn touch_distance_bps(side: TradeSide, price: f64, touch: f64, mid: f64) -> f64 {
+    let behind = match side {
+        TradeSide::Buy => touch - price,
+        TradeSide::Sell => price - touch,
+    };
+    behind / mid * 10_000.0
+}
+
+/// Drop-copy order id of a resting order
+fn resting_order_id(id: OrderId) -> String {
+    format!("O{}", id)
+}
+
+/// A quote waiting out the fill latency
+#[derive(Debug, Clone)]
+struct PendingFill {
+    trade: Trade,
+    /// Feed time the fill is confirmed at
+    confirm_at: i64,
+    /// False for a quote that missed its fill draw; it only fills if the market runs through it before confirmation
+    filled: bool,
+    /// Drop-copy order the fill belongs to
+    order_id: Option<String>,
+}
+
+/// A participation cap check made while deciding, counted against the cap on commit
+#[derive(Debug, Clone, Copy)]
+struct CapCheck {
+    timestamp: i64,
+    notional: f64,
+    allowed: Option<f64>,
+}
+
+/// A fill decision the engine hasn't acted on. Nothing is booked, sent to the execution venue,
+/// counted against the participation cap or drop-copied until it's passed to `commit`, so a
+/// decision the strategy sandbox drops leaves no trace.
+#[derive(Debug, Clone)]
+pub struct Decision {
+    attempt: ExecutionAttempt,
+    /// Under a fill latency, the trade a quoted but unfilled attempt makes if the market runs through it
+    standby: Option<Trade>,
+    /// Feed time a fill is confirmed at under a fill latency
+    confirm_at: i64,
+    cap: Option<CapCheck>,
+}
+
+impl Decision {
+    pub fn attempt(&self) -> &ExecutionAttempt {
+        &self.attempt
+    }
+}
+
+/// What re-quoting one side decided in resting-order mode, before the order book, execution
+/// venue or drop copy hear of it
+#[derive(Debug, Clone)]
+pub enum RequoteDecision {
+    /// Pull whatever order rests on the side
+    Cancel,
+    /// Cross the spread, pulling any resting order on the side first
+    Take(Box<Decision>),
+    /// Place or re-price the side's order
+    Quote(OrderRequest),
+}
+
+/// What re-quoting one side did in resting-order mode
+#[derive(Debug, Clone)]
+pub enum Requote {
+    Order(OrderAction),
+    /// Crossed the spread instead; any resting order on that side was pulled first
+    Taken(Box<ExecutionAttempt>),
+}
+
+/// Our passive price on one side and its economics, before any fill decision
+struct PassiveQuote {
+    market_price: f64,
+    /// Venue whose touch the quote competes with, which unrouted fills are charged at
+    venue: Source,
+    expected_fees: FeeBreakdown,
+    expected_edge_bps: f64,
+    quoted: bool,
+    ctx: FillContext,
+}
+
 pub struct TradingEngine {
     notional_per_trade: f64,
-    use_advanced_model: bool,
+    execution_model: Box<dyn ExecutionModel>,
+    router: Option<SmartOrderRouter>,
+    fees: FeeSchedule,
+    taker: Option<TakerPolicy>,
+    edge_requirement: Option<EdgeRequirement>,
+    two_sided: Option<TwoSidedPolicy>,
+    quoting: Box<dyn QuotingStrategy>,
+    sizing: Box<dyn SizingRule>,
+    volatility: Mutex<RealizedVolatility>,
+    position: Mutex<Position>,
+    draws: DrawSource,
+    quorum: QuorumPolicy,
+    aggregation: AggregationPolicy,
+    orders: Option<Mutex<OrderManager>>,
+    risk: Option<Mutex<RiskManager>>,
+    hedger: Option<HedgePolicy>,
+    quiet: Option<Mutex<QuietMarketDetector>>,
+    participation: Option<Mutex<ParticipationLimiter>>,
+    liquidity: Option<LiquidityProfile>,
+    fill_latency: Option<Duration>,
+    pending: Mutex<Vec<PendingFill>>,
+    drop_copy: Option<DropCopy>,
+    clock: Arc<dyn Clock>,
+    next_trade_id: AtomicU64,
+    tag: Option<String>,
+    /// Resting orders fill only on a real venue's reports, never off the quotes crossing them
+    external_fills: bool,
 }
 
 impl TradingEngine {
     pub fn new(notional_per_trade: f64, use_advanced_model: bool) -> Self {
+        let execution_model: Box<dyn ExecutionModel> = if use_advanced_model {
+            Box::new(AdvancedModel::default())
+        } else {
+            Box::new(BasicModel::default())
+        };
+        Self::with_execution_model(notional_per_trade, execution_model)
+    }
+
+    pub fn with_execution_model(notional_per_trade: f64, execution_model: Box<dyn ExecutionModel>) -> Self {
         Self {
             notional_per_trade,
-            use_advanced_model,
+            execution_model,
+            router: None,
+            fees: FeeSchedule::new(),
+            taker: None,
+            edge_requirement: Some(EdgeRequirement::default()),
+            two_sided: None,
+            quoting: Box::new(MedianQuote),
+            sizing: Box::new(FixedSize),
+            volatility: Mutex::new(RealizedVolatility::new(DEFAULT_VOL_WINDOW_MS)),
+            position: Mutex::new(Position::new()),
+            draws: DrawSource::Random,
+            quorum: QuorumPolicy::default(),
+            aggregation: AggregationPolicy::default(),
+            orders: None,
+            risk: None,
+            hedger: None,
+            quiet: None,
+            participation: None,
+            liquidity: None,
+            fill_latency: None,
+            pending: Mutex::new(Vec::new()),
+            drop_copy: None,
+            clock: clock::system(),
+            next_trade_id: AtomicU64::new(1),
+            tag: None,
+            external_fills: false,
         }
     }
 
-    /// Calculate execution probability based on our price vs market
-    fn calculate_execution_probability(
-        &self,
-        our_price: f64,
-        median_price: f64,
-        best_price: f64,
-        side: TradeSide,
-    ) -> f64 {
-        if !self.use_advanced_model {
-            return 0.70; // Basic model: fixed 70%
+    /// Split each fill across venues instead of executing the whole notional at our quote
+    pub fn with_router(mut self, router: SmartOrderRouter) -> Self {
+        self.router = Some(router);
+        self
+    }
+
+    /// Label every trade with `tag`, e.g. the strategy variant under test
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
+    /// Fees, gas and slippage charged per venue
+    pub fn with_fees(mut self, fees: FeeSchedule) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    pub fn fees(&self) -> &FeeSchedule {
+        &self.fees
+    }
+
+    /// Cross the spread when the signal is strong enough rather than always quoting passively
+    pub fn with_taker(mut self, policy: TakerPolicy) -> Self {
+        self.taker = Some(policy);
+        self
+    }
+
+    /// Minimum expected edge before quoting; `None` quotes regardless of economics
+    pub fn with_edge_requirement(mut self, requirement: Option<EdgeRequirement>) -> Self {
+        self.edge_requirement = requirement;
+        self
+    }
+
+    pub fn edge_requirement(&self) -> Option<&EdgeRequirement> {
+        self.edge_requirement.as_ref()
+    }
+
+    pub fn notional_per_trade(&self) -> f64 {
+        self.notional_per_trade
+    }
+
+    pub fn taker(&self) -> Option<&TakerPolicy> {
+        self.taker.as_ref()
+    }
+
+    /// Show the bid and ask together each cycle and draw their fills jointly under `policy`,
+    /// instead of deciding each side on its own
+    pub fn with_two_sided(mut self, policy: TwoSidedPolicy) -> Self {
+        self.two_sided = Some(policy);
+        self
+    }
+
+    pub fn two_sided(&self) -> Option<&TwoSidedPolicy> {
+        self.two_sided.as_ref()
+    }
+
+    pub fn router(&self) -> Option<&SmartOrderRouter> {
+        self.router.as_ref()
+    }
+
+    /// Replace median-price quoting with another strategy
+    pub fn with_quoting(mut self, quoting: Box<dyn QuotingStrategy>) -> Self {
+        self.quoting = quoting;
+        self
+    }
+
+    pub fn quoting(&self) -> &dyn QuotingStrategy {
+        self.quoting.as_ref()
+    }
+
+    /// Replace the fixed per-side notional with a custom sizing rule
+    pub fn with_sizing(mut self, sizing: Box<dyn SizingRule>) -> Self {
+        self.sizing = sizing;
+        self
+    }
+
+    pub fn sizing(&self) -> &dyn SizingRule {
+        self.sizing.as_ref()
+    }
+
+    /// Trailing window realized volatility is measured over, for volatility-aware sizing
+    pub fn with_vol_window(mut self, window: Duration) -> Self {
+        self.volatility = Mutex::new(RealizedVolatility::new(window.as_millis() as i64));
+        self
+    }
+
+    /// Annualized realized volatility of the median mid seen so far
+    pub fn realized_volatility(&self) -> Option<f64> {
+        self.volatility.lock().unwrap().annualized()
+    }
+
+    /// Notional for the next order on `side` under the sizing rule
+    pub fn order_notional(&self, side: TradeSide, mid: f64) -> f64 {
+        let ctx = SizingContext {
+            position: self.position(),
+            mid,
+            realized_vol: self.realized_volatility(),
+            risk_budget: self.risk.as_ref().map_or(1.0, |risk| risk.lock().unwrap().budget_remaining()),
+        };
+        self.sizing.notional(side, &ctx, self.notional_per_trade)
+    }
+
+    /// Record or replay the random draws behind fill decisions
+    pub fn with_draws(mut self, draws: DrawSource) -> Self {
+        self.draws = draws;
+        self
+    }
+
+    pub fn draws(&self) -> &DrawSource {
+        &self.draws
+    }
+
+    /// How many fresh sources are needed to trade normally, and how to trade below that
+    pub fn with_quorum(mut self, quorum: QuorumPolicy) -> Self {
+        self.quorum = quorum;
+        self
+    }
+
+    pub fn quorum(&self) -> &QuorumPolicy {
+        &self.quorum
+    }
+
+    /// Reference price the engine judges quotes, edge and taker signals against (median by default)
+    pub fn with_aggregation(mut self, policy: AggregationPolicy) -> Self {
+        self.aggregation = policy;
+        self
+    }
+
+    pub fn aggregation(&self) -> AggregationPolicy {
+        self.aggregation
+    }
+
+    /// `prices` with every quote aged against the engine's clock
+    pub fn snapshot(&self, prices: &AggregatedPrices) -> PriceSnapshot {
+        PriceSnapshot::new(prices.clone(), self.clock.now_ms())
+    }
+
+    /// Whether the current prices support normal trading, degraded trading or none
+    pub fn mode(&self, prices: &AggregatedPrices) -> TradingMode {
+        self.quorum.mode(&self.snapshot(prices))
+    }
+
+    /// Inventory built up by every fill so far
+    pub fn position(&self) -> Position {
+        *self.position.lock().unwrap()
+    }
+
+    /// Rest limit orders across cycles, filled when later quotes trade through them, instead of
+    /// deciding each cycle's fill with the execution model
+    pub fn with_order_manager(mut self, orders: OrderManager) -> Self {
+        self.orders = Some(Mutex::new(orders));
+        self
+    }
+
+    /// Hedge inventory back towards flat whenever it exceeds the policy's threshold
+    pub fn with_hedger(mut self, policy: HedgePolicy) -> Self {
+        self.hedger = Some(policy);
+        self
+    }
+
+    pub fn hedger(&self) -> Option<&HedgePolicy> {
+        self.hedger.as_ref()
+    }
+
+    /// Watch for flat, tight markets, and with `policy.pause` stop quoting while they last
+    pub fn with_quiet_market(mut self, policy: QuietMarketPolicy) -> Self {
+        self.quiet = Some(Mutex::new(QuietMarketDetector::new(policy)));
+        self
+    }
+
+    pub fn quiet_policy(&self) -> Option<QuietMarketPolicy> {
+        self.quiet.as_ref().map(|quiet| *quiet.lock().unwrap().policy())
+    }
+
+    /// Update quiet-market detection with `prices`; returns the event when the market turns quiet or active
+    pub fn observe_quiet(&self, prices: &AggregatedPrices) -> Option<QuietEvent> {
+        self.quiet.as_ref()?.lock().unwrap().observe(prices)
+    }
+
+    /// Whether quoting is paused for a quiet market
+    pub fn quiet_paused(&self) -> bool {
+        self.quiet.as_ref().is_some_and(|quiet| {
+            let quiet = quiet.lock().unwrap();
+            quiet.is_quiet() && quiet.policy().pause
+        })
+    }
+
+    /// End a quiet spell still open at `timestamp`, e.g. at the end of the session
+    pub fn close_quiet(&self, timestamp: i64) -> Option<QuietInterval> {
+        self.quiet.as_ref()?.lock().unwrap().close(timestamp)
+    }
+
+    /// Keep fills within a share of the market volume on the execution venue's trade tape. Fills
+    /// beyond what the window allows are cut down, or deferred when nothing is left; hedges are exempt.
+    pub fn with_participation(mut self, policy: ParticipationPolicy) -> Self {
+        self.participation = Some(Mutex::new(ParticipationLimiter::new(policy)));
+        self
+    }
+
+    pub fn participation_policy(&self) -> Option<ParticipationPolicy> {
+        self.participation.as_ref().map(|limiter| *limiter.lock().unwrap().policy())
+    }
+
+    /// What the participation cap has done so far
+    pub fn participation_stats(&self) -> Option<ParticipationStats> {
+        self.participation.as_ref().map(|limiter| limiter.lock().unwrap().stats())
+    }
+
+    /// Scale fill probabilities up and slippage down by the liquidity of the hour the prices are
+    /// from, so thin hours fill less often and cost more than peak ones
+    pub fn with_liquidity_profile(mut self, profile: LiquidityProfile) -> Self {
+        self.liquidity = Some(profile);
+        self
+    }
+
+    pub fn liquidity_profile(&self) -> Option<&LiquidityProfile> {
+        self.liquidity.as_ref()
+    }
+
+    /// Report every order event to a drop copy
+    pub fn with_drop_copy(mut self, drop_copy: DropCopy) -> Self {
+        self.drop_copy = Some(drop_copy);
+        self
+    }
+
+    pub fn drop_copy(&self) -> Option<&DropCopy> {
+        self.drop_copy.as_ref()
+    }
+
+    /// Stamp trades and order events from `clock` instead of the wall clock, e.g. a replay's
+    /// recorded time
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Current time on the engine's clock, in epoch milliseconds
+    pub fn now_ms(&self) -> i64 {
+        self.clock.now_ms()
+    }
+
+    /// Liquidity factor at the feed time of `prices`; 1 without a profile
+    fn liquidity_factor(&self, prices: &AggregatedPrices) -> f64 {
+        match (&self.liquidity, prices.quotes().map(|q| q.timestamp).max()) {
+            (Some(profile), Some(timestamp)) => profile.factor_at(timestamp),
+            _ => 1.0,
         }
+    }
 
-        // Advanced model: interpolate between 20% and 90%
-        match side {
-            TradeSide::Buy => {
-                // For buying: higher price = more likely to get filled
-                // Best price = highest bid in market
-                if our_price >= best_price {
-                    0.90
-                } else if our_price <= median_price {
-                    0.20
-                } else {
-                    // Linear interpolation
-                    let range = best_price - median_price;
-                    if range > 0.0 {
-                        let position = (our_price - median_price) / range;
-                        0.20 + position * 0.70
-                    } else {
-                        0.20
-                    }
-                }
-            }
-            TradeSide::Sell => {
-                // For selling: lower price = more likely to get filled
-                // Best price = lowest ask in market
-                if our_price <= best_price {
-                    0.90
-                } else if our_price >= median_price {
-                    0.20
-                } else {
-                    // Linear interpolation
-                    let range = median_price - best_price;
-                    if range > 0.0 {
-                        let position = (median_price - our_price) / range;
-                        0.20 + position * 0.70
-                    } else {
-                        0.20
-                    }
-                }
-            }
+    /// Fill probability from the execution model, scaled by the hour's liquidity
+    fn fill_probability(&self, ctx: &FillContext, prices: &AggregatedPrices) -> f64 {
+        (self.execution_model.probability(ctx) * self.liquidity_factor(prices)).min(1.0)
+    }
+
+    /// Costs of a fill at `venue`, with slippage scaled by the hour's liquidity
+    fn fill_cost(&self, venue: Source, role: LiquidityRole, notional: f64, prices: &AggregatedPrices) -> FeeBreakdown {
+        let mut fees = self.fees.cost(venue, role, notional);
+        fees.slippage_usd /= self.liquidity_factor(prices);
+        fees
+    }
+
+    /// Sample the trade tape for the participation window
+    fn sample_tape(&self, timestamp: i64) {
+        let (Some(limiter), Some(volume)) = (&self.participation, self.execution_model.traded_volume_usd()) else {
+            return;
+        };
+        limiter.lock().unwrap().observe(timestamp, volume);
+    }
+
+    /// Notional a fill of `notional` may take under the participation cap; `None` defers it
+    fn participate(&self, timestamp: i64, notional: f64) -> Option<f64> {
+        let Some(limiter) = &self.participation else {
+            return Some(notional);
+        };
+        self.sample_tape(timestamp);
+        limiter.lock().unwrap().allow(timestamp, notional)
+    }
+
+    /// Check a fill of `notional` against the participation cap without counting it, net of
+    /// `reserved` notional other uncommitted decisions will take; `None` without a cap
+    fn check_participation(&self, timestamp: i64, notional: f64, reserved: f64) -> Option<CapCheck> {
+        let limiter = self.participation.as_ref()?;
+        self.sample_tape(timestamp);
+        let allowed = limiter.lock().unwrap().check(timestamp, notional, reserved);
+        Some(CapCheck { timestamp, notional, allowed })
+    }
+
+    /// Count a checked fill against the participation cap
+    fn record_participation(&self, cap: Option<CapCheck>) {
+        if let (Some(limiter), Some(cap)) = (&self.participation, cap) {
+            limiter.lock().unwrap().record(cap.timestamp, cap.notional, cap.allowed);
+        }
+    }
+
+    /// Confirm fills only after `latency`, marking them to market at the prices of that moment
+    /// instead of the snapshot they were decided on
+    pub fn with_fill_latency(mut self, latency: Duration) -> Self {
+        self.fill_latency = Some(latency);
+        self
+    }
+
+    pub fn fill_latency(&self) -> Option<Duration> {
+        self.fill_latency
+    }
+
+    /// Fills decided but not yet confirmed
+    pub fn pending_fills(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+
+    /// Pending fills the venue has already executed, i.e. excluding quotes that may still be run through
+    pub fn in_flight_fills(&self) -> Vec<Trade> {
+        self.pending.lock().unwrap().iter().filter(|fill| fill.filled).map(|fill| fill.trade.clone()).collect()
+    }
+
+    /// Enforce inventory and loss limits; a tripped limit stops quoting or restricts it to the reducing side
+    pub fn with_risk(mut self, risk: RiskManager) -> Self {
+        self.risk = Some(Mutex::new(risk));
+        self
+    }
+
+    /// What the risk manager currently permits; always `Normal` without one
+    pub fn risk_state(&self) -> RiskState {
+        match &self.risk {
+            Some(risk) => risk.lock().unwrap().state(),
+            None => RiskState::Normal,
+        }
+    }
+
+    /// Every limit breach so far
+    pub fn risk_events(&self) -> Vec<RiskEvent> {
+        match &self.risk {
+            Some(risk) => risk.lock().unwrap().events().to_vec(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Configured limits and breach action, when a risk manager is attached
+    pub fn risk_limits(&self) -> Option<(RiskLimits, BreachAction)> {
+        self.risk.as_ref().map(|risk| {
+            let risk = risk.lock().unwrap();
+            (*risk.limits(), risk.action())
+        })
+    }
+
+    /// Pass a price feed event on to the risk manager, if there is one
+    pub fn on_feed_event(&self, event: &FeedEvent) {
+        if let Some(risk) = &self.risk {
+            risk.lock().unwrap().on_feed_event(event);
+        }
+    }
+
+    /// Tell the risk manager how many feed events were lost to a lagging subscription
+    pub fn on_feed_events_missed(&self, missed: u64) {
+        if let Some(risk) = &self.risk {
+            risk.lock().unwrap().on_feed_events_missed(missed);
         }
     }
 
-    /// Calculate PnL for a trade
+    /// Tell the risk manager how many quote updates were lost to a lagging subscription
+    pub fn on_quote_updates_missed(&self, missed: u64) {
+        if let Some(risk) = &self.risk {
+            risk.lock().unwrap().on_quote_updates_missed(missed);
+        }
+    }
+
+    pub fn risk_report(&self, timezone: &ReportTimezone) -> Option<String> {
+        self.risk.as_ref().map(|risk| risk.lock().unwrap().report(timezone))
+    }
+
+    pub fn resting_orders(&self) -> bool {
+        self.orders.is_some()
+    }
+
+    /// Resting orders are worked on a real venue: they fill only through `on_venue_fill`, and the
+    /// engine never crosses the spread itself
+    pub fn with_external_fills(mut self) -> Self {
+        self.external_fills = true;
+        self
+    }
+
+    pub fn external_fills(&self) -> bool {
+        self.external_fills
+    }
+
+    pub fn execution_model(&self) -> &dyn ExecutionModel {
+        self.execution_model.as_ref()
+    }
+
+    /// Calculate gross and net PnL for a trade
     /// For buys: we buy at our_price, mark-to-market at best_bid
     /// For sells: we sell at our_price, mark-to-market at best_ask
-    fn calculate_pnl(&self, side: TradeSide, our_price: f64, market_price: f64, amount_eth: f64) -> f64 {
-        match side {
+    /// Net PnL deducts the fill's fees, gas and slippage
+    /// `None`, logged as an error, when either isn't finite: such a fill isn't booked
+    fn calculate_pnl(&self, side: TradeSide, our_price: f64, market_price: f64, amount_eth: f64, fees: &FeeBreakdown) -> Option<(f64, f64)> {
+        let gross = match side {
             TradeSide::Buy => {
                 // We bought ETH at our_price
                 // Current value if we sell at market best bid
-                (market_price - our_price) * amount_eth
+                amount::value_of(market_price, our_price, amount_eth)
             }
             TradeSide::Sell => {
                 // We sold ETH at our_price
                 // Current cost if we buy back at market best ask
-                (our_price - market_price) * amount_eth
+                amount::value_of(our_price, market_price, amount_eth)
+            }
+        };
+        let net = amount::add(gross, -fees.total());
+        match amount::finite("gross PnL", gross).and_then(|gross| Ok((gross, amount::finite("net PnL", net)?))) {
+            Ok(pnl) => Some(pnl),
+            Err(e) => {
+                error!("Not booking {:?} {} ETH at {} against {}: {:#}", side, amount_eth, our_price, market_price, e);
+                None
             }
         }
     }
@@ -108,60 +778,854 @@ impl TradingEngine {
         prices: &AggregatedPrices,
         side: TradeSide,
     ) -> Option<Trade> {
-        let median_quote = prices.median_quote()?;
+        self.attempt(prices, side)?.trade
+    }
+
+    /// How far the best opposite touch sits through the reference mid, in bps.
+    /// Positive means some venue is offering below (buy) or bidding above (sell) fair value.
+    pub fn taker_signal_bps(&self, prices: &AggregatedPrices, side: TradeSide) -> Option<f64> {
+        let mid = prices.reference_mid(self.aggregation, self.clock.now_ms())?;
+        let best = prices.best_quote()?;
+        Some(match side {
+            TradeSide::Buy => (mid - best.ask) / mid * 10000.0,
+            TradeSide::Sell => (best.bid - mid) / mid * 10000.0,
+        })
+    }
+
+    /// Like `attempt_trade`, but also returns the attempt record when no fill happened.
+    /// Fills are applied to the engine's position; with a fill latency they're held back as pending instead.
+    pub fn attempt(&self, prices: &AggregatedPrices, side: TradeSide) -> Option<ExecutionAttempt> {
+        Some(self.commit(self.decide(prices, side, None, 0.0)?))
+    }
+
+    /// Like `attempt`, deciding the fill with `draw` instead of the next one from the draw source
+    pub fn attempt_with_draw(&self, prices: &AggregatedPrices, side: TradeSide, draw: f64) -> Option<ExecutionAttempt> {
+        Some(self.commit(self.decide(prices, side, Some(draw), 0.0)?))
+    }
+
+    /// The first half of `attempt`: decide the quote and its fill without acting on either
+    pub fn decide_attempt(&self, prices: &AggregatedPrices, side: TradeSide) -> Option<Decision> {
+        self.decide(prices, side, None, 0.0)
+    }
+
+    /// Quote both sides at once, drawing their fills jointly under the two-sided policy (independent
+    /// without one). Still one draw per side, and each attempt carries the draw that decided it.
+    pub fn attempt_two_sided(&self, prices: &AggregatedPrices) -> TwoSidedAttempt {
+        self.commit_two_sided(self.decide_two_sided(prices))
+    }
+
+    /// The first half of `attempt_two_sided`: decide both sides off the same position without
+    /// acting on either, so neither is sized, skewed or risk-checked against the other's fill
+    pub fn decide_two_sided(&self, prices: &AggregatedPrices) -> TwoSidedDecision {
+        let policy = self.two_sided.unwrap_or_default();
+        let (bid, ask) = policy.joint_draws(self.draws.next(), self.draws.next());
+        let (bid, ask) = if policy.one_fill_per_cycle {
+            policy.one_fill((bid, self.fill_odds(prices, TradeSide::Buy)), (ask, self.fill_odds(prices, TradeSide::Sell)))
+        } else {
+            (bid, ask)
+        };
+        let buy = self.decide(prices, TradeSide::Buy, Some(bid), 0.0);
+        // Both fills count against the participation cap, so the sell gets what the buy leaves
+        let reserved = buy.as_ref().and_then(|buy| buy.cap?.allowed).unwrap_or(0.0);
+        let sell = self.decide(prices, TradeSide::Sell, Some(ask), reserved);
+        TwoSidedDecision { buy, sell }
+    }
+
+    pub fn commit_two_sided(&self, decision: TwoSidedDecision) -> TwoSidedAttempt {
+        TwoSidedAttempt {
+            buy: decision.buy.map(|buy| self.commit(buy)),
+            sell: decision.sell.map(|sell| self.commit(sell)),
+        }
+    }
+
+    /// Fill probability of a passive quote on `side`, `None` if nothing would be quoted
+    fn fill_odds(&self, prices: &AggregatedPrices, side: TradeSide) -> Option<f64> {
+        if !self.risk_allows(side) {
+            return None;
+        }
+        let (prices, degraded) = self.tradable_prices(prices)?;
+        let prices = prices.as_ref();
+        let mid = prices.reference_mid(self.aggregation, self.clock.now_ms())?;
+        let notional = self.sized_notional(prices, side, mid, degraded);
+        let quote = self.passive_quote(prices, side, notional, degraded)?;
+        quote.quoted.then(|| self.fill_probability(&quote.ctx, prices))
+    }
+
+    /// Act on a decision: count it against the participation cap, drop-copy it and book its fill,
+    /// or under a fill latency send the fill to the venue and hold it back as pending
+    #[instrument(level = "debug", skip_all, fields(side = ?decision.attempt.side))]
+    pub fn commit(&self, decision: Decision) -> ExecutionAttempt {
+        let Decision { mut attempt, standby, confirm_at, cap } = decision;
+        debug!(
+            price = attempt.price,
+            execution_prob = attempt.execution_prob,
+            draw = attempt.draw,
+            expected_edge_bps = attempt.expected_edge_bps,
+            quoted = attempt.quoted,
+            filled = attempt.trade.is_some(),
+            "attempt decided"
+        );
+        self.record_participation(cap);
+        if self.fill_latency.is_none() {
+            self.book_attempt(&mut attempt);
+            return attempt;
+        }
+
+        let order_id = self.report_new_attempt(&attempt);
+        let mut pending = self.pending.lock().unwrap();
+        if let Some(trade) = attempt.trade.take() {
+            // The venue executes now; only our view of the fill waits for confirmation
+            self.execution_model.on_fill(&trade);
+            pending.push(PendingFill { trade, confirm_at, filled: true, order_id });
+            attempt.pending = true;
+        } else if let Some(trade) = standby {
+            pending.push(PendingFill { trade, confirm_at, filled: false, order_id });
+        } else {
+            self.report_done(order_id.as_deref(), None, attempt.timestamp);
+        }
+        attempt
+    }
+
+    /// Drop-copy an attempt and book its fill straight away
+    fn book_attempt(&self, attempt: &mut ExecutionAttempt) {
+        let order_id = self.report_new_attempt(attempt);
+        if let Some(trade) = &mut attempt.trade {
+            self.assign_trade_id(trade);
+            self.book_fill(trade);
+        }
+        self.report_done(order_id.as_deref(), attempt.trade.as_ref(), attempt.timestamp);
+    }
+
+    /// Hand out the next trade id to a trade decided without one
+    fn assign_trade_id(&self, trade: &mut Trade) {
+        if trade.id == 0 {
+            trade.id = self.next_trade_id.fetch_add(1, Ordering::Relaxed);
+            for fill in &mut trade.fills {
+                fill.parent_id = trade.id;
+            }
+        }
+    }
+
+    /// Confirm pending fills whose latency has passed by the feed time of `prices`, re-marking each
+    /// to the touch at that moment, and apply them to the position. The difference from the mark
+    /// they were decided on is their adverse selection. Quotes that missed their fill draw still fill
+    /// if the market has moved through them by then, since a stale quote is exactly what gets picked off.
+    pub fn confirm_fills(&self, prices: &AggregatedPrices) -> Vec<Trade> {
+        let Some(now) = prices.quotes().map(|q| q.timestamp).max() else {
+            return Vec::new();
+        };
+        self.confirm_pending(prices, |fill| fill.confirm_at <= now)
+    }
+
+    /// Confirm every pending fill at `prices`, e.g. when the session ends before their latency is up
+    pub fn confirm_all(&self, prices: &AggregatedPrices) -> Vec<Trade> {
+        self.confirm_pending(prices, |_| true)
+    }
+
+    fn confirm_pending(&self, prices: &AggregatedPrices, due: impl Fn(&PendingFill) -> bool) -> Vec<Trade> {
+        let Some(best_quote) = prices.best_quote() else {
+            return Vec::new();
+        };
+        let ready: Vec<PendingFill> = {
+            let mut pending = self.pending.lock().unwrap();
+            let (ready, waiting) = pending.drain(..).partition(|fill| due(fill));
+            *pending = waiting;
+            ready
+        };
+
+        let now = prices.quotes().map(|q| q.timestamp).max().unwrap_or_default();
+        ready
+            .into_iter()
+            .filter(|fill| {
+                if fill.filled {
+                    return true;
+                }
+                let crossed = match fill.trade.side {
+                    TradeSide::Buy => best_quote.ask <= fill.trade.price,
+                    TradeSide::Sell => best_quote.bid >= fill.trade.price,
+                };
+                // A stale quote is picked off whole, so it only fills if the window has room for all of it
+                let picked_off = crossed
+                    && self.participation.as_ref().is_none_or(|limiter| {
+                        self.sample_tape(now);
+                        limiter.lock().unwrap().allow_whole(now, fill.trade.notional_usd)
+                    });
+                if !picked_off {
+                    self.report_done(fill.order_id.as_deref(), None, self.clock.now_ms());
+                }
+                picked_off
+            })
+            .filter_map(|PendingFill { mut trade, filled, order_id, .. }| {
+                // Same marks as at decision time: best bid for buys, best ask for sells
+                let market_price = match trade.side {
+                    TradeSide::Buy => best_quote.bid,
+                    TradeSide::Sell => best_quote.ask,
+                };
+                let Some((gross_pnl, pnl)) = self.calculate_pnl(trade.side, trade.price, market_price, trade.amount_eth, &trade.fees) else {
+                    self.report_done(order_id.as_deref(), None, self.clock.now_ms());
+                    return None;
+                };
+                self.assign_trade_id(&mut trade);
+                trade.adverse_selection_usd = trade.gross_pnl - gross_pnl;
+                trade.gross_pnl = gross_pnl;
+                trade.mark_venue = mark_source(prices, trade.side);
+                trade.pnl = pnl;
+                trade.decided_at = Some(trade.timestamp);
+                trade.timestamp = self.clock.now_ms();
+                if filled {
+                    self.apply_fill(&trade);
+                } else {
+                    self.book_fill(&trade);
+                }
+                self.report_done(order_id.as_deref(), Some(&trade), trade.timestamp);
+                debug!(trade = trade.id, side = ?trade.side, adverse_selection_usd = trade.adverse_selection_usd, "fill confirmed");
+                Some(trade)
+            })
+            .collect()
+    }
+
+    /// Prices to trade off under the quorum policy, and whether that's a degraded subset; `None` when halted
+    fn tradable_prices<'a>(&self, prices: &'a AggregatedPrices) -> Option<(Cow<'a, AggregatedPrices>, bool)> {
+        // Below quorum, trade only off the fresh sources, and stop entirely without any
+        match self.mode(prices) {
+            TradingMode::Normal => Some((Cow::Borrowed(prices), false)),
+            TradingMode::Degraded { sources } => Some((Cow::Owned(prices.restricted_to(&sources)), true)),
+            TradingMode::Halted => None,
+        }
+    }
+
+    /// Notional for the next order on `side`, scaled down when degraded
+    fn sized_notional(&self, prices: &AggregatedPrices, side: TradeSide, mid: f64, degraded: bool) -> f64 {
+        if let Some(timestamp) = prices.quotes().map(|q| q.timestamp).max() {
+            self.volatility.lock().unwrap().observe(timestamp, mid);
+            self.sample_tape(timestamp);
+        }
+        let notional = self.order_notional(side, mid);
+        if degraded {
+            notional * self.quorum.degraded_size_factor
+        } else {
+            notional
+        }
+    }
+
+    /// Cross the spread if the taker signal is strong enough; the attempt has no trade when the
+    /// participation cap defers it. A single feed can't tell a mispriced venue from a moving
+    /// market, so never cross when degraded.
+    fn taker_attempt(&self, prices: &AggregatedPrices, side: TradeSide, notional: f64, degraded: bool, reserved: f64) -> Option<Decision> {
+        let policy = self.taker.as_ref().filter(|_| !degraded && !self.external_fills)?;
+        let signal_bps = self.taker_signal_bps(prices, side)?;
+        if signal_bps < policy.signal_bps {
+            return None;
+        }
+        self.take(policy, side, notional, signal_bps, prices, reserved)
+    }
+
+    /// Where we'd quote passively on `side` and whether it clears the edge requirement
+    fn passive_quote(&self, prices: &AggregatedPrices, side: TradeSide, notional: f64, degraded: bool) -> Option<PassiveQuote> {
+        let reference = prices.reference_quote(self.aggregation, self.clock.now_ms())?;
         let best_quote = prices.best_quote()?;
+        let mid = prices.reference_mid(self.aggregation, self.clock.now_ms())?;
 
+        let quotes = self.quoting.quotes(prices, &self.position())?;
+        let widen = if degraded { self.quorum.degraded_spread_bps / 10000.0 } else { 0.0 };
         let (our_price, market_price) = match side {
             TradeSide::Buy => {
-                // We quote our buy price at median bid (ensures never worse than median)
+                // Our bid from the quoting strategy
                 // Mark-to-market at best bid in market
-                (median_quote.bid, best_quote.bid)
+                (quotes.bid * (1.0 - widen), best_quote.bid)
             }
             TradeSide::Sell => {
-                // We quote our sell price at median ask
+                // Our ask from the quoting strategy
                 // Mark-to-market at best ask in market
-                (median_quote.ask, best_quote.ask)
+                (quotes.ask * (1.0 + widen), best_quote.ask)
             }
         };
 
-        let amount_eth = self.notional_per_trade / our_price;
+        // Unrouted passive fills are charged at the venue whose touch we compete with
+        let venue = match side {
+            TradeSide::Buy => prices.best_bid_source()?,
+            TradeSide::Sell => prices.best_ask_source()?,
+        };
+        let expected_fees = self.fill_cost(venue, LiquidityRole::Maker, notional, prices);
+        let requirement = self.edge_requirement.unwrap_or(EdgeRequirement {
+            min_edge_bps: f64::NEG_INFINITY,
+            ..EdgeRequirement::default()
+        });
+        let expected_edge_bps = requirement.expected_edge_bps(side, our_price, mid, expected_fees.bps_of(notional));
+        let quoted = expected_edge_bps >= requirement.min_edge_bps;
 
         // Calculate execution probability
         let median_price = match side {
-            TradeSide::Buy => median_quote.bid,
-            TradeSide::Sell => median_quote.ask,
+            TradeSide::Buy => reference.bid,
+            TradeSide::Sell => reference.ask,
         };
         let best_price = match side {
             TradeSide::Buy => best_quote.bid,
             TradeSide::Sell => best_quote.ask,
         };
 
-        let execution_prob = self.calculate_execution_probability(
-            our_price,
-            median_price,
-            best_price,
-            side,
-        );
+        Some(PassiveQuote {
+            market_price,
+            venue,
+            expected_fees,
+            expected_edge_bps,
+            quoted,
+            ctx: FillContext {
+                our_price,
+                median_price,
+                best_price,
+                side,
+                notional,
+                flow: prices.flow,
+            },
+        })
+    }
+
+    /// Decide the quote on `side` and whether it fills, without acting on either. `reserved` is
+    /// participation-capped notional already promised to another uncommitted decision.
+    #[instrument(level = "debug", skip_all, fields(?side))]
+    fn decide(&self, prices: &AggregatedPrices, side: TradeSide, draw: Option<f64>, reserved: f64) -> Option<Decision> {
+        if !self.risk_allows(side) {
+            return None;
+        }
+        let (prices, degraded) = self.tradable_prices(prices)?;
+        let prices = prices.as_ref();
+
+        let conversion = ConversionSnapshot::from_prices(prices, self.clock.now_ms())?;
+        let mid = prices.reference_mid(self.aggregation, self.clock.now_ms())?;
+        let notional = self.sized_notional(prices, side, mid, degraded);
+        // Drawn before any branching so every decision consumes exactly one draw
+        let draw = draw.unwrap_or_else(|| self.draws.next());
+
+        if let Some(mut decision) = self.taker_attempt(prices, side, notional, degraded, reserved) {
+            decision.attempt.draw = draw;
+            return Some(decision);
+        }
+
+        let mut quote = self.passive_quote(prices, side, notional, degraded)?;
+        let our_price = quote.ctx.our_price;
+        let execution_prob = self.fill_probability(&quote.ctx, prices);
+        let model_predictions = self.execution_model.predictions(&quote.ctx);
 
         // Simulate execution
-        let mut rng = rand::rng();
-        let executed = rng.random::<f64>() < execution_prob;
+        let mut executed = quote.quoted && draw < execution_prob;
+        let order_notional = notional;
+        let mut notional = notional;
+        let mut deferred = false;
+        let feed_time = prices.quotes().map(|q| q.timestamp).max().unwrap_or_default();
+        let cap = if executed { self.check_participation(feed_time, notional, reserved) } else { None };
+        match cap.map(|cap| cap.allowed) {
+            Some(Some(allowed)) if allowed < notional => {
+                notional = allowed;
+                quote.expected_fees = self.fill_cost(quote.venue, LiquidityRole::Maker, notional, prices);
+            }
+            Some(None) => {
+                executed = false;
+                deferred = true;
+            }
+            _ => {}
+        }
+        let amount_eth = notional / our_price;
+        let timestamp = self.clock.now_ms();
+
+        let trade = if executed || (quote.quoted && self.fill_latency.is_some() && !deferred) {
+            // Ids are handed out on commit, or on confirmation under a fill latency, so dropped
+            // decisions and quotes don't leave gaps
+            let id = 0;
+            let fills = match &self.router {
+                Some(router) => router.route(id, prices, side, notional, &self.fees),
+                None => Vec::new(),
+            };
+
+            let (price, amount_eth, notional_usd, fees) = if fills.is_empty() {
+                (our_price, amount_eth, notional, quote.expected_fees)
+            } else {
+                let eth: f64 = fills.iter().map(|f| f.amount_eth).sum();
+                let usd: f64 = fills.iter().map(|f| f.notional_usd).sum();
+                let mut fees = FeeBreakdown::default();
+                for fill in &fills {
+                    fees += fill.fees;
+                }
+                (usd / eth, eth, usd, fees)
+            };
+            let (gross_pnl, pnl) = self.calculate_pnl(side, price, quote.market_price, amount_eth, &fees)?;
 
-        if executed {
-            let pnl = self.calculate_pnl(side, our_price, market_price, amount_eth);
-            
             Some(Trade {
+                id,
                 side,
-                price: our_price,
+                price,
                 amount_eth,
-                notional_usd: self.notional_per_trade,
+                notional_usd,
                 pnl,
-                timestamp: chrono::Utc::now().timestamp_millis(),
+                timestamp,
                 execution_prob,
+                conversion,
+                role: LiquidityRole::Maker,
+                fees_usd: fees.total(),
+                gross_pnl,
+                fees,
+                fills,
+                degraded,
+                hedge: false,
+                adverse_selection_usd: 0.0,
+                venue: Some(quote.venue),
+                mark_venue: mark_source(prices, side),
+                tag: self.tag.clone(),
+                decided_at: None,
             })
         } else {
             None
+        };
+
+        let (trade, standby) = if executed { (trade, None) } else { (None, trade) };
+        let confirm_at = feed_time + self.fill_latency.map_or(0, |latency| latency.as_millis() as i64);
+        Some(Decision {
+            attempt: ExecutionAttempt {
+                side,
+                price: our_price,
+                execution_prob,
+                model_predictions,
+                timestamp,
+                feed_time,
+                role: LiquidityRole::Maker,
+                expected_edge_bps: quote.expected_edge_bps,
+                quoted: quote.quoted,
+                draw,
+                degraded,
+                touch_distance_bps: touch_distance_bps(side, our_price, quote.market_price, mid),
+                order_notional,
+                trade,
+                pending: false,
+            },
+            standby,
+            confirm_at,
+            cap,
+        })
+    }
+
+    /// Resting-order mode: re-price our order on `side` to the current quote, pull it when we
+    /// shouldn't be quoting, or cross the spread when the taker signal fires.
+    /// `None` when resting orders are off or there was nothing to quote or cancel.
+    pub fn requote(&self, prices: &AggregatedPrices, side: TradeSide) -> Option<Requote> {
+        let decision = self.decide_requote(prices, side)?;
+        self.commit_requote(side, decision)
+    }
+
+    /// The first half of `requote`: decide what to do on `side` without touching the order book.
+    /// `None` when resting orders are off.
+    #[instrument(level = "debug", skip_all, fields(?side))]
+    pub fn decide_requote(&self, prices: &AggregatedPrices, side: TradeSide) -> Option<RequoteDecision> {
+        self.orders.as_ref()?;
+        if !self.risk_allows(side) {
+            return Some(RequoteDecision::Cancel);
+        }
+        let Some((prices, degraded)) = self.tradable_prices(prices) else {
+            return Some(RequoteDecision::Cancel);
+        };
+        let prices = prices.as_ref();
+        let Some(mid) = prices.reference_mid(self.aggregation, self.clock.now_ms()) else {
+            return Some(RequoteDecision::Cancel);
+        };
+        let notional = self.sized_notional(prices, side, mid, degraded);
+
+        if let Some(decision) = self.taker_attempt(prices, side, notional, degraded, 0.0) {
+            return Some(RequoteDecision::Take(Box::new(decision)));
+        }
+
+        Some(match self.passive_quote(prices, side, notional, degraded) {
+            Some(quote) if quote.quoted => RequoteDecision::Quote(OrderRequest {
+                side,
+                price: quote.ctx.our_price,
+                notional,
+                execution_prob: self.fill_probability(&quote.ctx, prices),
+                expected_edge_bps: quote.expected_edge_bps,
+                degraded,
+            }),
+            _ => RequoteDecision::Cancel,
+        })
+    }
+
+    /// Apply a re-quoting decision for `side` to the order book, committing a taker fill like any
+    /// other decision, so it's held pending under a fill latency. `None` when there was nothing to
+    /// quote or cancel.
+    pub fn commit_requote(&self, side: TradeSide, decision: RequoteDecision) -> Option<Requote> {
+        let orders = self.orders.as_ref()?;
+        let timestamp = self.clock.now_ms();
+        match decision {
+            RequoteDecision::Cancel => {
+                let order = orders.lock().unwrap().cancel_side(side, timestamp)?;
+                self.report_cancel(&order);
+                Some(Requote::Order(OrderAction::Cancelled(order)))
+            }
+            RequoteDecision::Take(decision) => {
+                if let Some(order) = orders.lock().unwrap().cancel_side(side, timestamp) {
+                    self.report_cancel(&order);
+                }
+                let attempt = self.commit(*decision);
+                Some(Requote::Taken(Box::new(attempt)))
+            }
+            RequoteDecision::Quote(request) => {
+                let action = orders.lock().unwrap().requote(request, timestamp);
+                self.report_action(&action);
+                Some(Requote::Order(action))
+            }
+        }
+    }
+
+    /// Fill engine for resting orders: fill every order `update` trades through, charging the crossing
+    /// venue's maker costs and marking to market against `prices`. Fills are applied to the position.
+    pub fn on_quote(&self, update: &QuoteUpdate, prices: &AggregatedPrices) -> Vec<Trade> {
+        let Some(orders) = &self.orders else {
+            return Vec::new();
+        };
+        self.sample_tape(update.quote.timestamp);
+        if self.external_fills {
+            return Vec::new();
+        }
+        let filled = orders.lock().unwrap().on_quote(update.source, &update.quote);
+        let best_quote = prices.best_quote().unwrap_or(update.quote);
+        let conversion = ConversionSnapshot::from_prices(prices, self.clock.now_ms()).unwrap_or_default();
+
+        let trades = filled
+            .into_iter()
+            .filter_map(|mut order| {
+                // Whatever the participation cap holds back stays on the book
+                let allowed = self.participate(update.quote.timestamp, order.notional);
+                let left = order.notional - allowed.unwrap_or_default();
+                if left > 0.0 {
+                    orders.lock().unwrap().reopen(order.id, left);
+                }
+                order.notional = allowed?;
+                Some(order)
+            })
+            .filter_map(|order| {
+                let market_price = match order.side {
+                    TradeSide::Buy => best_quote.bid,
+                    TradeSide::Sell => best_quote.ask,
+                };
+                let amount_eth = order.amount_eth();
+                let fees = self.fill_cost(update.source, LiquidityRole::Maker, order.notional, prices);
+                let (gross_pnl, pnl) = self.calculate_pnl(order.side, order.price, market_price, amount_eth, &fees)?;
+                let trade = Trade {
+                    id: self.next_trade_id.fetch_add(1, Ordering::Relaxed),
+                    side: order.side,
+                    price: order.price,
+                    amount_eth,
+                    notional_usd: order.notional,
+                    pnl,
+                    timestamp: self.clock.now_ms(),
+                    execution_prob: order.execution_prob,
+                    conversion,
+                    role: LiquidityRole::Maker,
+                    fees_usd: fees.total(),
+                    gross_pnl,
+                    fees,
+                    fills: Vec::new(),
+                    degraded: order.degraded,
+                    hedge: false,
+                    adverse_selection_usd: 0.0,
+                    venue: Some(update.source),
+                    mark_venue: mark_source(prices, order.side).or(Some(update.source)),
+                    tag: self.tag.clone(),
+                    decided_at: None,
+                };
+                self.book_fill(&trade);
+                if let Some(log) = &self.drop_copy {
+                    log.fill(&resting_order_id(order.id), &trade, Some(update.source));
+                }
+                Some(trade)
+            })
+            .collect();
+
+        // A fill can trip a limit between cycles; pull orders on sides it no longer allows
+        let state = self.risk_state();
+        let position = self.position();
+        let mut orders = orders.lock().unwrap();
+        for side in [TradeSide::Buy, TradeSide::Sell] {
+            if !state.allows(side, &position) {
+                if let Some(order) = orders.cancel_side(side, self.clock.now_ms()) {
+                    self.report_cancel(&order);
+                }
+            }
+        }
+        trades
+    }
+
+    /// Book an execution a real venue reported for one of our resting orders, charging the
+    /// commission it reported and marking to market against `prices`. `None` for an unknown order.
+    pub fn on_venue_fill(&self, fill: &VenueFill, prices: &AggregatedPrices) -> Option<Trade> {
+        let orders = self.orders.as_ref()?;
+        let order = orders.lock().unwrap().fill(fill.order_id, fill.notional(), fill.complete, Source::Binance, fill.timestamp)?;
+        let best_quote = prices.best_quote();
+        let market_price = match (fill.side, best_quote) {
+            (TradeSide::Buy, Some(quote)) => quote.bid,
+            (TradeSide::Sell, Some(quote)) => quote.ask,
+            (_, None) => fill.price,
+        };
+        let role = if fill.maker { LiquidityRole::Maker } else { LiquidityRole::Taker };
+        let fees = match fill.commission_usd {
+            Some(commission) => FeeBreakdown {
+                venue_fee_usd: commission,
+                ..FeeBreakdown::default()
+            },
+            // Charged in another asset (BNB); fall back to the configured schedule
+            None => self.fill_cost(Source::Binance, role, fill.notional(), prices),
+        };
+        let (gross_pnl, pnl) = self.calculate_pnl(fill.side, fill.price, market_price, fill.amount_eth, &fees)?;
+        let trade = Trade {
+            id: self.next_trade_id.fetch_add(1, Ordering::Relaxed),
+            side: fill.side,
+            price: fill.price,
+            amount_eth: fill.amount_eth,
+            notional_usd: fill.notional(),
+            pnl,
+            timestamp: fill.timestamp,
+            execution_prob: order.execution_prob,
+            conversion: ConversionSnapshot::from_prices(prices, self.clock.now_ms()).unwrap_or_default(),
+            role,
+            fees_usd: fees.total(),
+            gross_pnl,
+            fees,
+            fills: Vec::new(),
+            degraded: order.degraded,
+            hedge: false,
+            adverse_selection_usd: 0.0,
+            venue: Some(Source::Binance),
+            mark_venue: mark_source(prices, fill.side).or(Some(Source::Binance)),
+            tag: self.tag.clone(),
+            decided_at: None,
+        };
+        self.book_fill(&trade);
+        if let Some(log) = &self.drop_copy {
+            log.fill(&resting_order_id(order.id), &trade, Some(Source::Binance));
+        }
+        Some(trade)
+    }
+
+    /// Drop-copy a quoted attempt as a new immediate-or-cancel order; returns its order id
+    fn report_new_attempt(&self, attempt: &ExecutionAttempt) -> Option<String> {
+        let log = self.drop_copy.as_ref().filter(|_| attempt.quoted)?;
+        let (prefix, ord_type) = match attempt.role {
+            LiquidityRole::Maker => ("Q", OrdType::Limit),
+            LiquidityRole::Taker => ("T", OrdType::Market),
+        };
+        let order_id = log.next_order_id(prefix);
+        log.new_order(&order_id, attempt.side, ord_type, TimeInForce::ImmediateOrCancel,
+            attempt.price, attempt.order_notional, attempt.timestamp);
+        Some(order_id)
+    }
+
+    /// Drop-copy the end of an immediate-or-cancel order: its fill, if any, and the expiry of the rest
+    fn report_done(&self, order_id: Option<&str>, trade: Option<&Trade>, timestamp: i64) {
+        let (Some(log), Some(order_id)) = (&self.drop_copy, order_id) else {
+            return;
+        };
+        if let Some(trade) = trade {
+            log.fill(order_id, trade, trade.fills.first().filter(|_| trade.fills.len() == 1).map(|f| f.venue));
+        }
+        log.expire(order_id, trade.map_or(timestamp, |t| t.timestamp));
+    }
+
+    /// Drop-copy what re-quoting did to a resting order
+    fn report_action(&self, action: &OrderAction) {
+        let Some(log) = &self.drop_copy else {
+            return;
+        };
+        match action {
+            OrderAction::Placed(order) => log.new_order(&resting_order_id(order.id), order.side, OrdType::Limit,
+                TimeInForce::GoodTillCancel, order.price, order.notional, order.placed_at),
+            OrderAction::Amended { order, .. } => log.replace(&resting_order_id(order.id), order.price, order.notional, order.updated_at),
+            OrderAction::Kept(_) => {}
+            OrderAction::Cancelled(order) => self.report_cancel(order),
+        }
+    }
+
+    fn report_cancel(&self, order: &RestingOrder) {
+        if let Some(log) = &self.drop_copy {
+            log.cancel(&resting_order_id(order.id), order.updated_at);
+        }
+    }
+
+    /// Report a fill to the execution venue and apply it locally
+    fn book_fill(&self, trade: &Trade) {
+        self.execution_model.on_fill(trade);
+        self.apply_fill(trade);
+    }
+
+    /// Apply a fill to the position and the risk manager
+    fn apply_fill(&self, trade: &Trade) {
+        let mut position = self.position.lock().unwrap();
+        position.apply(trade);
+        if let Some(risk) = &self.risk {
+            let mut risk = risk.lock().unwrap();
+            let seen = risk.events().len();
+            risk.on_trade(trade, &position);
+            for event in &risk.events()[seen..] {
+                warn!(limit = %event.limit, "risk limit breached: {}", event.message);
+            }
+        }
+    }
+
+    fn risk_allows(&self, side: TradeSide) -> bool {
+        self.risk_state().allows(side, &self.position())
+    }
+
+    /// Pull every resting order, e.g. when halted or at the end of a session
+    pub fn cancel_resting(&self) -> Vec<RestingOrder> {
+        match &self.orders {
+            Some(orders) => {
+                let pulled = orders.lock().unwrap().cancel_all(self.clock.now_ms());
+                for order in &pulled {
+                    self.report_cancel(order);
+                }
+                pulled
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Orders currently resting, oldest first
+    pub fn open_orders(&self) -> Vec<RestingOrder> {
+        match &self.orders {
+            Some(orders) => orders.lock().unwrap().orders().filter(|o| o.state == OrderState::Open).cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Lifetime order counts, when resting orders are on
+    pub fn order_stats(&self) -> Option<OrderStats> {
+        self.orders.as_ref().map(|orders| orders.lock().unwrap().stats())
+    }
+
+    /// Cross the spread: fill for certain at the best opposite touch and pay that venue's taker
+    /// costs, with the policy's slippage charged once among them rather than also worsening the
+    /// price, unless the participation cap defers the order. Marked to market at the same-side
+    /// touch like passive fills.
+    fn take(&self, policy: &TakerPolicy, side: TradeSide, notional: f64, signal_bps: f64, prices: &AggregatedPrices, reserved: f64) -> Option<Decision> {
+        let best_quote = prices.best_quote()?;
+        let mid = prices.reference_mid(self.aggregation, self.clock.now_ms())?;
+        let conversion = ConversionSnapshot::from_prices(prices, self.clock.now_ms())?;
+        let feed_time = prices.quotes().map(|q| q.timestamp).max()?;
+        let slippage_bps = policy.slippage_bps / self.liquidity_factor(prices);
+        let (price, market_price, venue) = match side {
+            TradeSide::Buy => (best_quote.ask, best_quote.bid, prices.best_ask_source()?),
+            TradeSide::Sell => (best_quote.bid, best_quote.ask, prices.best_bid_source()?),
+        };
+        let order_notional = notional;
+        // Deferred by the participation cap: the taker order goes unfilled rather than turning passive
+        let cap = self.check_participation(feed_time, notional, reserved);
+        let allowed = cap.map_or(Some(notional), |cap| cap.allowed);
+        let notional = allowed.unwrap_or(order_notional);
+        let amount_eth = notional / price;
+        let mut fees = self.fill_cost(venue, LiquidityRole::Taker, notional, prices);
+        fees.slippage_usd += notional * slippage_bps / 10000.0;
+        let (gross_pnl, pnl) = self.calculate_pnl(side, price, market_price, amount_eth, &fees)?;
+        let timestamp = self.clock.now_ms();
+
+        let trade = allowed.map(|_| Trade {
+            id: 0,
+            side,
+            price,
+            amount_eth,
+            notional_usd: notional,
+            pnl,
+            timestamp,
+            execution_prob: 1.0,
+            conversion,
+            role: LiquidityRole::Taker,
+            fees_usd: fees.total(),
+            gross_pnl,
+            fees,
+            fills: Vec::new(),
+            degraded: false,
+            hedge: false,
+            adverse_selection_usd: 0.0,
+            venue: Some(venue),
+            mark_venue: mark_source(prices, side),
+            tag: self.tag.clone(),
+            decided_at: None,
+        });
+
+        let attempt = ExecutionAttempt {
+            side,
+            price,
+            execution_prob: 1.0,
+            // Taker fills don't depend on the fill model, so keep them out of calibration
+            model_predictions: Vec::new(),
+            timestamp,
+            feed_time,
+            role: LiquidityRole::Taker,
+            expected_edge_bps: signal_bps - fees.bps_of(notional),
+            quoted: true,
+            // Taker fills are certain; the caller attaches the decision's draw
+            draw: 0.0,
+            degraded: false,
+            touch_distance_bps: touch_distance_bps(side, price, market_price, mid),
+            order_notional,
+            trade,
+            pending: false,
+        };
+        Some(Decision {
+            attempt,
+            standby: None,
+            confirm_at: feed_time + self.fill_latency.map_or(0, |latency| latency.as_millis() as i64),
+            cap,
+        })
+    }
+
+    /// Hedge inventory over the hedger's threshold by crossing the spread at the best venue,
+    /// paying its taker costs. Marked to market at the same-side touch, so the PnL is the hedging cost.
+    /// Off while fills come from an outside venue, which the hedge would never reach, and while
+    /// the kill switch has tripped.
+    pub fn hedge(&self, prices: &AggregatedPrices) -> Option<Trade> {
+        if self.external_fills || matches!(self.risk_state(), RiskState::Halted { .. }) {
+            return None;
+        }
+        let policy = self.hedger.as_ref()?;
+        let (side, amount_eth) = policy.hedge_order(self.position().net_eth)?;
+        let (prices, degraded) = self.tradable_prices(prices)?;
+        let prices = prices.as_ref();
+        let best_quote = prices.best_quote()?;
+        let conversion = ConversionSnapshot::from_prices(prices, self.clock.now_ms())?;
+        let (price, market_price, venue) = match side {
+            TradeSide::Buy => (best_quote.ask, best_quote.bid, prices.best_ask_source()?),
+            TradeSide::Sell => (best_quote.bid, best_quote.ask, prices.best_bid_source()?),
+        };
+        let notional = amount_eth * price;
+        let fees = self.fill_cost(venue, LiquidityRole::Taker, notional, prices);
+        let (gross_pnl, pnl) = self.calculate_pnl(side, price, market_price, amount_eth, &fees)?;
+
+        let trade = Trade {
+            id: self.next_trade_id.fetch_add(1, Ordering::Relaxed),
+            side,
+            price,
+            amount_eth,
+            notional_usd: notional,
+            pnl,
+            timestamp: self.clock.now_ms(),
+            execution_prob: 1.0,
+            conversion,
+            role: LiquidityRole::Taker,
+            fees_usd: fees.total(),
+            gross_pnl,
+            fees,
+            fills: Vec::new(),
+            degraded,
+            hedge: true,
+            adverse_selection_usd: 0.0,
+            venue: Some(venue),
+            mark_venue: mark_source(prices, side),
+            tag: self.tag.clone(),
+            decided_at: None,
+        };
+        self.book_fill(&trade);
+        info!(side = ?side, amount_eth, price, net_eth = self.position().net_eth, "hedged inventory");
+        if let Some(log) = &self.drop_copy {
+            let order_id = log.next_order_id("H");
+            log.new_order(&order_id, side, OrdType::Market, TimeInForce::ImmediateOrCancel, price, notional, trade.timestamp);
+            log.fill(&order_id, &trade, Some(venue));
         }
+        Some(trade)
     }
 
     /// Get market summary for display
@@ -181,7 +1645,7 @@ impl TradingEngine {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct MarketSummary {
     pub median_bid: f64,
     pub median_ask: f64,
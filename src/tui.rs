@@ -0,0 +1,472 @@
+use crate::aggregator::{AggregatedPrices, QuoteUpdate, Source};
+use crate::control::{self, ControlCommand};
+use crate::pnl_tracker::EquityPoint;
+use crate::portfolio::Simulator;
+use crate::position::Position;
+use crate::risk::RiskState;
+use crate::trader::{LiquidityRole, MarketSummary, Trade, TradeSide};
+use crate::timezone::ReportTimezone;
+use crate::quorum::TradingMode;
+use anyhow::{bail, Context, Result};
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::crossterm::{cursor, execute};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Sparkline, Table};
+use ratatui::{Frame, Terminal};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
+use std::os::fd::{AsFd, AsRawFd, OwnedFd};
+use std::panic::PanicHookInfo;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Once};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+
+/// How often the panes are redrawn
+const REFRESH: Duration = Duration::from_millis(250);
+/// Mid samples kept for the sparkline, one per redraw
+const MID_HISTORY: usize = 480;
+/// Most recent trades kept for the blotter
+const BLOTTER_SIZE: usize = 200;
+
+/// Full-screen terminal dashboard over a running session (`--tui`): per-venue quotes and freshness,
+/// the market and spread, sparklines of the mid and of equity, inventory and PnL, and a trade blotter.
+/// While it holds the terminal, the plain log goes to a file instead. Keys issue control commands:
+/// `q` (or Esc / Ctrl-C) stops the session, `s` writes an interim summary and `p` the portfolio.
+pub struct Dashboard {
+    stopping: Arc<AtomicBool>,
+    render: JoinHandle<Result<()>>,
+    keys: std::thread::JoinHandle<()>,
+    terminal: TerminalGuard,
+    console: ConsoleRedirect,
+}
+
+impl Dashboard {
+    /// Take over the terminal, sending the console output to `log` until `stop`
+    pub fn start(log: &Path, updates: broadcast::Receiver<QuoteUpdate>, simulator: Simulator, commands: mpsc::UnboundedSender<ControlCommand>) -> Result<Self> {
+        let mut tty = File::options().write(true).open("/dev/tty").context("failed to open the terminal")?;
+        let console = ConsoleRedirect::to(log)?;
+        // From here on an early return hands the terminal back as the guard drops
+        let guard = TerminalGuard::take()?;
+        execute!(tty, EnterAlternateScreen, cursor::Hide)?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(tty))?;
+        terminal.clear()?;
+
+        let feed = Arc::new(Mutex::new(Feed::default()));
+        spawn_quotes(updates, Arc::clone(&feed));
+        spawn_trades(simulator.tracker().subscribe_trades(), Arc::clone(&feed));
+        let stopping = Arc::new(AtomicBool::new(false));
+        let keys = spawn_keys(commands, Arc::clone(&stopping));
+        let render = {
+            let stopping = Arc::clone(&stopping);
+            let log = log.display().to_string();
+            tokio::spawn(async move {
+                let drawn = render_loop(&mut terminal, &simulator, &feed, &stopping, &log).await;
+                restore_terminal();
+                drawn
+            })
+        };
+        Ok(Self { stopping, render, keys, terminal: guard, console })
+    }
+
+    /// Hand the terminal back, restoring the console output for the final summary
+    pub async fn stop(self) -> Result<()> {
+        self.stopping.store(true, Ordering::Relaxed);
+        let drawn = self.render.await.context("dashboard task failed");
+        let keys = self.keys;
+        let _ = tokio::task::spawn_blocking(move || keys.join()).await;
+        drop(self.terminal);
+        drop(self.console);
+        drawn?
+    }
+}
+
+/// What the panes show between redraws, kept current from the quote and trade streams
+#[derive(Default)]
+struct Feed {
+    prices: AggregatedPrices,
+    mids: VecDeque<f64>,
+    trades: VecDeque<Trade>,
+}
+
+/// One redraw's worth of session state
+struct View {
+    prices: AggregatedPrices,
+    fresh: Vec<Source>,
+    summary: Option<MarketSummary>,
+    mids: Vec<f64>,
+    equity: Vec<EquityPoint>,
+    trades: Vec<Trade>,
+    position: Position,
+    total_trades: u32,
+    total_pnl: f64,
+    total_fees: f64,
+    cycle: u64,
+    mode: TradingMode,
+    risk: RiskState,
+    timezone: ReportTimezone,
+}
+
+fn spawn_quotes(mut updates: broadcast::Receiver<QuoteUpdate>, feed: Arc<Mutex<Feed>>) {
+    tokio::spawn(async move {
+        loop {
+            match updates.recv().await {
+                Ok(update) => feed.lock().unwrap().prices.set(update.source, update.quote),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+fn spawn_trades(mut trades: broadcast::Receiver<Trade>, feed: Arc<Mutex<Feed>>) {
+    tokio::spawn(async move {
+        loop {
+            match trades.recv().await {
+                Ok(trade) => {
+                    let mut feed = feed.lock().unwrap();
+                    feed.trades.push_front(trade);
+                    feed.trades.truncate(BLOTTER_SIZE);
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+/// Read keys on a thread of their own, since the terminal's event reader blocks
+fn spawn_keys(commands: mpsc::UnboundedSender<ControlCommand>, stopping: Arc<AtomicBool>) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        while !stopping.load(Ordering::Relaxed) {
+            match event::poll(Duration::from_millis(100)) {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(_) => break,
+            }
+            let Ok(Event::Key(key)) = event::read() else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let command = match key.code {
+                // Raw mode swallows the interrupt, so Ctrl-C has to stop the session from here
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => ControlCommand::Stop,
+                KeyCode::Char('q') | KeyCode::Esc => ControlCommand::Stop,
+                KeyCode::Char('s') => ControlCommand::Summary,
+                KeyCode::Char('p') => ControlCommand::Portfolio,
+                _ => continue,
+            };
+            if commands.send(command).is_err() {
+                break;
+            }
+        }
+    })
+}
+
+async fn render_loop(
+    terminal: &mut Terminal<CrosstermBackend<File>>,
+    simulator: &Simulator,
+    feed: &Mutex<Feed>,
+    stopping: &AtomicBool,
+    log: &str,
+) -> Result<()> {
+    let mut ticker = tokio::time::interval(REFRESH);
+    while !stopping.load(Ordering::Relaxed) {
+        ticker.tick().await;
+        let view = capture(simulator, feed).await;
+        terminal.draw(|frame| draw(frame, &view, log))?;
+    }
+    Ok(())
+}
+
+async fn capture(simulator: &Simulator, feed: &Mutex<Feed>) -> View {
+    let (prices, mids, trades) = {
+        let mut feed = feed.lock().unwrap();
+        if let Some(mid) = feed.prices.median_mid() {
+            if feed.mids.len() == MID_HISTORY {
+                feed.mids.pop_front();
+            }
+            feed.mids.push_back(mid);
+        }
+        (feed.prices.clone(), feed.mids.iter().copied().collect(), feed.trades.iter().cloned().collect())
+    };
+    let engine = simulator.engine();
+    let tracker = simulator.tracker();
+    let stats = tracker.get_stats().await;
+    View {
+        fresh: engine.quorum().fresh_sources(&engine.snapshot(&prices)),
+        summary: engine.get_market_summary(&prices),
+        mode: engine.mode(&prices),
+        risk: engine.risk_state(),
+        position: engine.position(),
+        equity: tracker.equity_series().await,
+        total_trades: stats.total_trades,
+        total_pnl: stats.total_pnl,
+        total_fees: stats.total_fees,
+        cycle: simulator.portfolio().await.map_or(0, |p| p.cycle),
+        timezone: tracker.timezone(),
+        prices,
+        mids,
+        trades,
+    }
+}
+
+fn draw(frame: &mut Frame, view: &View, log: &str) {
+    let [top, charts, bottom, footer] = Layout::vertical([
+        Constraint::Length(Source::ALL.len() as u16 + 3),
+        Constraint::Length(8),
+        Constraint::Min(6),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [venues, market] = Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(top);
+    let [mid, equity] = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(charts);
+    let [inventory, blotter] = Layout::horizontal([Constraint::Length(40), Constraint::Min(40)]).areas(bottom);
+
+    draw_venues(frame, venues, view);
+    draw_market(frame, market, view);
+    let mids = &view.mids;
+    draw_sparkline(frame, mid, "Mid", mids);
+    let equity_values: Vec<f64> = view.equity.iter().map(EquityPoint::equity).collect();
+    draw_sparkline(frame, equity, "Equity", &equity_values);
+    draw_inventory(frame, inventory, view);
+    draw_blotter(frame, blotter, view);
+    frame.render_widget(
+        Line::from(format!(" q stop · s interim summary · p portfolio │ log: {}", log)).dark_gray(),
+        footer,
+    );
+}
+
+fn draw_venues(frame: &mut Frame, area: Rect, view: &View) {
+    let newest = view.prices.quotes().map(|q| q.timestamp).max().unwrap_or_default();
+    let rows = Source::ALL.iter().map(|&source| match view.prices.get(source) {
+        Some(quote) => {
+            let mid = (quote.bid + quote.ask) / 2.0;
+            let (status, color) = if view.fresh.contains(&source) {
+                ("● live", Color::Green)
+            } else {
+                ("● stale", Color::Yellow)
+            };
+            Row::new(vec![
+                source.name().to_string(),
+                format!("{:.2}", quote.bid),
+                format!("{:.2}", quote.ask),
+                format!("{:.1}", (quote.ask - quote.bid) / mid * 10_000.0),
+                format!("{:.1}s", (newest - quote.timestamp) as f64 / 1000.0),
+                status.to_string(),
+            ])
+            .style(Style::default().fg(color))
+        }
+        None => Row::new(vec![source.name().to_string(), "-".into(), "-".into(), "-".into(), "-".into(), "○ no quote".into()])
+            .style(Style::default().fg(Color::Red)),
+    });
+    let widths = [
+        Constraint::Length(9),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(8),
+        Constraint::Length(7),
+        Constraint::Min(10),
+    ];
+    let table = Table::new(rows, widths)
+        .header(Row::new(vec!["VENUE", "BID", "ASK", "BPS", "AGE", "STATUS"]).bold())
+        .block(Block::bordered().title(" Venues "));
+    frame.render_widget(table, area);
+}
+
+fn draw_market(frame: &mut Frame, area: Rect, view: &View) {
+    let mut lines = match &view.summary {
+        Some(summary) => vec![
+            Line::from(format!("Median mid  ${:.2}", summary.median_mid)).bold(),
+            Line::from(format!("Best bid    ${:.2}", summary.best_bid)),
+            Line::from(format!("Best ask    ${:.2}", summary.best_ask)),
+            Line::from(format!("Spread      {:.1} bps", summary.spread_bps)),
+        ],
+        None => vec![Line::from("Waiting for quotes...")],
+    };
+    let mode_color = match view.mode {
+        TradingMode::Normal => Color::Green,
+        TradingMode::Degraded { .. } => Color::Yellow,
+        TradingMode::Halted => Color::Red,
+    };
+    lines.push(Line::from(format!("Trading     {}", view.mode)).fg(mode_color));
+    let (risk, risk_color) = match view.risk {
+        RiskState::Normal => ("normal".to_string(), Color::Green),
+        RiskState::ReduceOnly { limit } => (format!("reduce-only ({})", limit), Color::Yellow),
+        RiskState::Halted { limit } => (format!("kill switch ({})", limit), Color::Red),
+    };
+    lines.push(Line::from(format!("Risk        {}", risk)).fg(risk_color));
+    let title = format!(" Market │ cycle #{} ", view.cycle);
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(title)), area);
+}
+
+/// The most recent values that fit the pane, scaled between their own low and high
+fn draw_sparkline(frame: &mut Frame, area: Rect, name: &str, values: &[f64]) {
+    let width = area.width.saturating_sub(2) as usize;
+    let recent = &values[values.len().saturating_sub(width)..];
+    let low = recent.iter().copied().fold(f64::INFINITY, f64::min);
+    let high = recent.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let title = match recent.last() {
+        Some(last) => format!(" {} ${:.2} │ low ${:.2} │ high ${:.2} ", name, last, low, high),
+        None => format!(" {} ", name),
+    };
+    let range = (high - low).max(f64::EPSILON);
+    // Offset by one so the low still shows as a bar
+    let data: Vec<u64> = recent.iter().map(|v| ((v - low) / range * 100.0).round() as u64 + 1).collect();
+    let sparkline = Sparkline::default()
+        .block(Block::bordered().title(title))
+        .data(&data)
+        .style(Style::default().fg(Color::Cyan));
+    frame.render_widget(sparkline, area);
+}
+
+fn draw_inventory(frame: &mut Frame, area: Rect, view: &View) {
+    let position = &view.position;
+    let unrealized = view.summary.as_ref().map(|s| position.unrealized_pnl(s.median_mid));
+    let pnl_color = if view.total_pnl < 0.0 { Color::Red } else { Color::Green };
+    let lines = vec![
+        Line::from(format!("Inventory   {:+.4} ETH", position.net_eth)).bold(),
+        Line::from(format!("Avg entry   ${:.2}", position.avg_entry_price)),
+        Line::from(format!("Cash        ${:.2}", position.cash_usd)),
+        Line::from(format!("Realized    ${:.2}", position.realized_pnl)),
+        Line::from(format!("Unrealized  {}", unrealized.map_or("-".to_string(), |u| format!("${:.2}", u)))),
+        Line::from(format!("Trades      {}", view.total_trades)),
+        Line::from(format!("Net PnL     ${:.2}", view.total_pnl)).fg(pnl_color),
+        Line::from(format!("Fees        ${:.2}", view.total_fees)),
+    ];
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(" Inventory & PnL ")), area);
+}
+
+fn draw_blotter(frame: &mut Frame, area: Rect, view: &View) {
+    let visible = area.height.saturating_sub(3) as usize;
+    let rows = view.trades.iter().take(visible).map(|trade| {
+        let mut side = match trade.side {
+            TradeSide::Buy => "BUY".to_string(),
+            TradeSide::Sell => "SELL".to_string(),
+        };
+        if trade.role == LiquidityRole::Taker {
+            side.push_str(" TAKE");
+        }
+        if trade.hedge {
+            side.push_str(" HEDGE");
+        }
+        let color = if trade.pnl < 0.0 { Color::Red } else { Color::Green };
+        Row::new(vec![
+            format!("#{}", trade.id),
+            view.timezone.format_time(trade.timestamp),
+            side,
+            format!("{:.2}", trade.price),
+            format!("{:.4}", trade.amount_eth),
+            format!("{:.2}", trade.pnl),
+        ])
+        .style(Style::default().fg(color))
+    });
+    let widths = [
+        Constraint::Length(6),
+        Constraint::Length(9),
+        Constraint::Length(11),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Min(8),
+    ];
+    let table = Table::new(rows, widths)
+        .header(Row::new(vec!["ID", "TIME", "SIDE", "PRICE", "ETH", "PNL"]).bold())
+        .block(Block::bordered().title(" Trades "));
+    frame.render_widget(table, area);
+}
+
+/// Whether the dashboard has the terminal in raw mode and on the alternate screen
+static TERMINAL_HELD: AtomicBool = AtomicBool::new(false);
+
+fn restore_terminal() {
+    if !TERMINAL_HELD.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let _ = terminal::disable_raw_mode();
+    if let Ok(mut tty) = File::options().write(true).open("/dev/tty") {
+        let _ = execute!(tty, LeaveAlternateScreen, cursor::Show);
+    }
+}
+
+type PanicHook = Box<dyn Fn(&PanicHookInfo<'_>) + Send + Sync>;
+
+/// Holds the terminal in raw mode until dropped, restoring it on every way out: a normal stop, an
+/// error, a panic or a second interrupt
+struct TerminalGuard {
+    /// The panic hook in place before the dashboard's, chained to and put back on drop
+    previous_hook: Arc<PanicHook>,
+}
+
+impl TerminalGuard {
+    fn take() -> Result<Self> {
+        static FORCED_EXIT: Once = Once::new();
+        FORCED_EXIT.call_once(|| control::before_forced_exit(restore_terminal));
+
+        terminal::enable_raw_mode().context("failed to put the terminal in raw mode")?;
+        TERMINAL_HELD.store(true, Ordering::SeqCst);
+        let previous_hook: Arc<PanicHook> = Arc::new(std::panic::take_hook());
+        let chained = Arc::clone(&previous_hook);
+        std::panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            chained(info);
+        }));
+        Ok(Self { previous_hook })
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+        let previous = Arc::clone(&self.previous_hook);
+        std::panic::set_hook(Box::new(move |info| previous(info)));
+    }
+}
+
+/// Points stdout and stderr at a log file while the dashboard holds the terminal, so the plain log
+/// keeps being written without drawing over the panes
+struct ConsoleRedirect {
+    stdout: OwnedFd,
+    stderr: OwnedFd,
+}
+
+impl ConsoleRedirect {
+    fn to(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        let log = File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+        std::io::stdout().flush()?;
+        // Saved copies, closed when dropped, including when redirecting fails halfway
+        let redirect = Self {
+            stdout: std::io::stdout().as_fd().try_clone_to_owned().context("failed to save stdout")?,
+            stderr: std::io::stderr().as_fd().try_clone_to_owned().context("failed to save stderr")?,
+        };
+        for fd in [libc::STDOUT_FILENO, libc::STDERR_FILENO] {
+            // SAFETY: points a standard descriptor at the open log file
+            if unsafe { libc::dup2(log.as_raw_fd(), fd) } < 0 {
+                bail!("failed to redirect the console: {}", std::io::Error::last_os_error());
+            }
+        }
+        Ok(redirect)
+    }
+}
+
+impl Drop for ConsoleRedirect {
+    fn drop(&mut self) {
+        let _ = std::io::stdout().flush();
+        // SAFETY: restores the descriptors saved in `to`, which close as the fields drop
+        unsafe {
+            libc::dup2(self.stdout.as_raw_fd(), libc::STDOUT_FILENO);
+            libc::dup2(self.stderr.as_raw_fd(), libc::STDERR_FILENO);
+        }
+    }
+}
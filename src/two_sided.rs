@@ -0,0 +1,285 @@
+use crate::amount::{self, Exact};
+use crate::trader::{Decision, ExecutionAttempt, Trade, TradeSide};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// How the bid and ask of a two-sided quote fill together. A fill draw below the side's fill
+/// probability fills it, so positive correlation makes both sides fill (and miss) together.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct TwoSidedPolicy {
+    /// Correlation of the two sides' fill draws, from -1 to 1; 0 keeps them independent
+    pub correlation: f64,
+    /// Let at most one side fill per cycle
+    pub one_fill_per_cycle: bool,
+}
+
+impl TwoSidedPolicy {
+    pub fn new(correlation: f64, one_fill_per_cycle: bool) -> Result<Self> {
+        if !(-1.0..=1.0).contains(&correlation) {
+            bail!("fill correlation must be between -1 and 1, got {}", correlation);
+        }
+        Ok(Self { correlation, one_fill_per_cycle })
+    }
+
+    /// Bid and ask fill draws from two independent uniform draws, joined through a Gaussian
+    /// copula so each stays uniform on its own
+    pub fn joint_draws(&self, bid: f64, ask: f64) -> (f64, f64) {
+        if self.correlation == 0.0 {
+            return (bid, ask);
+        }
+        let (z_bid, z_other) = (inverse_normal_cdf(bid), inverse_normal_cdf(ask));
+        let z_ask = self.correlation * z_bid + (1.0 - self.correlation.powi(2)).sqrt() * z_other;
+        (bid, normal_cdf(z_ask))
+    }
+
+    /// Under `one_fill_per_cycle`, when both draws land under their side's fill probability, only
+    /// the side that cleared its odds by the wider margin keeps its fill; the other's draw is
+    /// pushed to 1 so it misses. A side with no odds isn't quoted and never conflicts.
+    pub fn one_fill(&self, (bid, bid_odds): (f64, Option<f64>), (ask, ask_odds): (f64, Option<f64>)) -> (f64, f64) {
+        let (Some(bid_odds), Some(ask_odds), true) = (bid_odds, ask_odds, self.one_fill_per_cycle) else {
+            return (bid, ask);
+        };
+        if bid >= bid_odds || ask >= ask_odds {
+            return (bid, ask);
+        }
+        if bid / bid_odds <= ask / ask_odds {
+            (bid, 1.0)
+        } else {
+            (1.0, ask)
+        }
+    }
+}
+
+/// Both sides of one two-sided quote, decided off the same position but not yet acted on
+#[derive(Debug, Clone, Default)]
+pub struct TwoSidedDecision {
+    pub buy: Option<Decision>,
+    pub sell: Option<Decision>,
+}
+
+/// Both sides of one two-sided quote, decided together
+#[derive(Debug, Clone, Default)]
+pub struct TwoSidedAttempt {
+    pub buy: Option<ExecutionAttempt>,
+    pub sell: Option<ExecutionAttempt>,
+}
+
+impl TwoSidedAttempt {
+    pub fn side(&self, side: TradeSide) -> Option<&ExecutionAttempt> {
+        match side {
+            TradeSide::Buy => self.buy.as_ref(),
+            TradeSide::Sell => self.sell.as_ref(),
+        }
+    }
+
+    /// Both sides showed a quote
+    pub fn quoted(&self) -> bool {
+        [&self.buy, &self.sell].iter().all(|a| a.as_ref().is_some_and(|a| a.quoted))
+    }
+
+    /// Sides with a confirmed fill; fills still pending under a fill latency aren't counted
+    pub fn fills(&self) -> usize {
+        [&self.buy, &self.sell].iter().filter(|a| a.as_ref().is_some_and(ExecutionAttempt::executed)).count()
+    }
+
+    /// The buy and sell filled in the same cycle. Fills pending under a fill latency have no trade
+    /// yet, so they don't make a round trip.
+    pub fn round_trip(&self) -> Option<RoundTrip> {
+        let buy = self.buy.as_ref()?.trade.as_ref()?;
+        let sell = self.sell.as_ref()?.trade.as_ref()?;
+        Some(RoundTrip::of(buy, sell))
+    }
+}
+
+/// A buy and a sell filled off the same two-sided quote
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RoundTrip {
+    pub buy_id: u64,
+    pub sell_id: u64,
+    /// Size both sides filled; the rest of the larger fill is left as inventory
+    pub amount_eth: f64,
+    pub buy_price: f64,
+    pub sell_price: f64,
+}
+
+impl RoundTrip {
+    pub fn of(buy: &Trade, sell: &Trade) -> Self {
+        Self {
+            buy_id: buy.id,
+            sell_id: sell.id,
+            amount_eth: buy.amount_eth.min(sell.amount_eth),
+            buy_price: buy.price,
+            sell_price: sell.price,
+        }
+    }
+
+    /// Spread earned on the matched size, before fees
+    pub fn capture_usd(&self) -> f64 {
+        amount::value_of(self.sell_price, self.buy_price, self.amount_eth)
+    }
+
+    pub fn capture_bps(&self) -> f64 {
+        let mid = (self.buy_price + self.sell_price) / 2.0;
+        if mid > 0.0 {
+            (self.sell_price - self.buy_price) / mid * 10000.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// How often two-sided quotes filled on one side, both or neither, and the spread the round trips captured
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TwoSidedStats {
+    /// Cycles that showed a bid and an ask together
+    pub quotes: u32,
+    pub one_side_filled: u32,
+    pub both_filled: u32,
+    /// Both-filled cycles whose trades were booked at once, i.e. not pending under a fill latency
+    pub round_trips: u32,
+    pub round_trip_eth: f64,
+    /// Sell minus buy price on the matched size of each round trip, before fees
+    pub capture_usd: f64,
+    #[serde(skip)]
+    capture_exact: Exact,
+}
+
+impl TwoSidedStats {
+    pub fn record(&mut self, attempt: &TwoSidedAttempt) {
+        if !attempt.quoted() {
+            return;
+        }
+        self.quotes += 1;
+        match attempt.fills() {
+            1 => self.one_side_filled += 1,
+            2 => self.both_filled += 1,
+            _ => {}
+        }
+        if let Some(trip) = attempt.round_trip() {
+            self.round_trips += 1;
+            self.round_trip_eth += trip.amount_eth;
+            self.capture_exact.add(&mut self.capture_usd, trip.capture_usd());
+        }
+    }
+
+    pub fn round_trip_rate(&self) -> f64 {
+        if self.quotes > 0 {
+            self.both_filled as f64 / self.quotes as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Standard normal CDF, via the Abramowitz and Stegun 7.1.26 erf approximation (error under 1.5e-7)
+fn normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-x * x).exp();
+    if z >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
+/// Inverse standard normal CDF, by Acklam's rational approximation (relative error under 1.2e-9)
+fn inverse_normal_cdf(p: f64) -> f64 {
+    const A: [f64; 6] = [-39.69683028665376, 220.9460984245205, -275.9285104469687, 138.357751867269, -30.66479806614716, 2.506628277459239];
+    const B: [f64; 5] = [-54.47609879822406, 161.5858368580409, -155.6989798598866, 66.80131188771972, -13.28068155288572];
+    const C: [f64; 6] = [-0.007784894002430293, -0.3223964580411365, -2.400758277161838, -2.549732539343734, 4.374664141464968, 2.938163982698783];
+    const D: [f64; 4] = [0.007784695709041462, 0.3224671290700398, 2.445134137142996, 3.754408661907416];
+    const LOW: f64 = 0.02425;
+
+    let p = p.clamp(1e-12, 1.0 - 1e-12);
+    let tail = |q: f64| (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5]) / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0);
+    if p < LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Midpoints of an n x n grid over the unit square, standing in for independent uniform draws
+    fn grid(n: usize) -> impl Iterator<Item = (f64, f64)> {
+        let step = 1.0 / n as f64;
+        (0..n).flat_map(move |i| (0..n).map(move |j| ((i as f64 + 0.5) * step, (j as f64 + 0.5) * step)))
+    }
+
+    #[test]
+    fn normal_cdf_matches_known_values() {
+        assert!((normal_cdf(0.0) - 0.5).abs() < 1e-7);
+        assert!((normal_cdf(1.959964) - 0.975).abs() < 1e-6);
+        assert!((normal_cdf(-1.0) - 0.158655).abs() < 1e-6);
+        for z in [-3.0, -0.7, 0.2, 2.5] {
+            assert!((normal_cdf(z) + normal_cdf(-z) - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn inverse_normal_cdf_round_trips() {
+        for p in [1e-6, 0.001, 0.02, 0.1, 0.5, 0.75, 0.98, 0.999] {
+            assert!((normal_cdf(inverse_normal_cdf(p)) - p).abs() < 1e-6, "p = {}", p);
+        }
+        assert!((inverse_normal_cdf(0.975) - 1.959964).abs() < 1e-5);
+        assert!(inverse_normal_cdf(0.0).is_finite() && inverse_normal_cdf(1.0).is_finite());
+    }
+
+    #[test]
+    fn new_rejects_out_of_range_correlation() {
+        assert!(TwoSidedPolicy::new(1.5, false).is_err());
+        assert!(TwoSidedPolicy::new(-1.0, true).is_ok());
+    }
+
+    #[test]
+    fn joint_draws_follow_the_correlation() {
+        assert_eq!(TwoSidedPolicy::default().joint_draws(0.3, 0.8), (0.3, 0.8));
+        let (bid, ask) = TwoSidedPolicy::new(1.0, false).unwrap().joint_draws(0.3, 0.8);
+        assert!(bid == 0.3 && (ask - 0.3).abs() < 1e-6);
+        let (_, ask) = TwoSidedPolicy::new(-1.0, false).unwrap().joint_draws(0.3, 0.8);
+        assert!((ask - 0.7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn joint_draws_keep_the_ask_uniform() {
+        let policy = TwoSidedPolicy::new(0.6, false).unwrap();
+        let draws: Vec<(f64, f64)> = grid(100).map(|(bid, ask)| policy.joint_draws(bid, ask)).collect();
+        let n = draws.len() as f64;
+        let mean = draws.iter().map(|d| d.1).sum::<f64>() / n;
+        let below = draws.iter().filter(|d| d.1 < 0.3).count() as f64 / n;
+        assert!((mean - 0.5).abs() < 0.01, "mean {}", mean);
+        assert!((below - 0.3).abs() < 0.01, "below {}", below);
+    }
+
+    #[test]
+    fn positive_correlation_fills_both_sides_more_often() {
+        let both = |correlation: f64| {
+            let policy = TwoSidedPolicy::new(correlation, false).unwrap();
+            grid(100).map(|(bid, ask)| policy.joint_draws(bid, ask)).filter(|&(bid, ask)| bid < 0.5 && ask < 0.5).count() as f64 / 10_000.0
+        };
+        assert!((both(0.0) - 0.25).abs() < 1e-9);
+        assert!(both(0.8) > 0.35);
+        assert!(both(-0.8) < 0.15);
+    }
+
+    #[test]
+    fn one_fill_keeps_the_side_that_cleared_its_odds_by_more() {
+        let policy = TwoSidedPolicy::new(0.0, true).unwrap();
+        assert_eq!(policy.one_fill((0.1, Some(0.5)), (0.3, Some(0.5))), (0.1, 1.0));
+        assert_eq!(policy.one_fill((0.4, Some(0.5)), (0.1, Some(0.5))), (1.0, 0.1));
+        // Only one side fills, or one side isn't quoted: nothing to resolve
+        assert_eq!(policy.one_fill((0.1, Some(0.5)), (0.6, Some(0.5))), (0.1, 0.6));
+        assert_eq!(policy.one_fill((0.1, Some(0.5)), (0.1, None)), (0.1, 0.1));
+        assert_eq!(TwoSidedPolicy::default().one_fill((0.1, Some(0.5)), (0.1, Some(0.5))), (0.1, 0.1));
+    }
+}
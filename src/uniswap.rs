@@ -0,0 +1,186 @@
+use crate::aggregator::Quote;
+use crate::tokens::{from_units, to_units, EvmToken, TradingPair};
+use anyhow::{bail, Context, Result};
+use std::time::Duration;
+
+/// Uniswap V3 QuoterV2 on Ethereum mainnet
+pub const QUOTER_V2_ADDRESS: &str = "0x61fFE014bA17989E743c5F6cB21bF9697530B21e";
+
+/// Selector of `quoteExactInputSingle((address,address,uint256,uint24,uint160))`
+const QUOTE_EXACT_INPUT_SINGLE: &str = "c6a5026a";
+
+/// Fee tiers (in hundredths of a bip) with a deployed ETH/USDC pool
+pub const DEFAULT_FEE_TIERS: [u32; 3] = [500, 3000, 10000];
+
+/// Where and how to query the on-chain pools, ETH/USDC unless given another pair
+#[derive(Debug, Clone)]
+pub struct UniswapConfig {
+    pub rpc_url: String,
+    pub quoter: String,
+    pub fee_tiers: Vec<u32>,
+    /// USD size each side of the quote is priced at
+    pub notional_usd: f64,
+    pub poll_interval: Duration,
+    pub base: EvmToken,
+    pub quote: EvmToken,
+}
+
+impl UniswapConfig {
+    pub fn new(rpc_url: impl Into<String>, notional_usd: f64) -> Self {
+        let pair = TradingPair::default();
+        let (base, quote) = pair.evm().expect("built-in ETH/USDC is on Ethereum");
+        Self {
+            rpc_url: rpc_url.into(),
+            quoter: QUOTER_V2_ADDRESS.to_string(),
+            fee_tiers: DEFAULT_FEE_TIERS.to_vec(),
+            notional_usd,
+            poll_interval: Duration::from_secs(12),
+            base: base.clone(),
+            quote: quote.clone(),
+        }
+    }
+
+    /// Quote `pair`'s pools on its chain instead
+    pub fn with_pair(mut self, pair: &TradingPair) -> Result<Self> {
+        let (base, quote) = pair.evm()?;
+        self.base = base.clone();
+        self.quote = quote.clone();
+        Ok(self)
+    }
+
+    pub fn with_fee_tiers(mut self, fee_tiers: Vec<u32>) -> Self {
+        self.fee_tiers = fee_tiers;
+        self
+    }
+
+    /// Parse a comma separated list of fee tiers, e.g. `500,3000`
+    pub fn parse_fee_tiers(s: &str) -> Result<Vec<u32>> {
+        s.split(',')
+            .map(|t| {
+                let tier: u32 = t.trim().parse().with_context(|| format!("invalid fee tier '{}'", t))?;
+                if !matches!(tier, 100 | 500 | 3000 | 10000) {
+                    bail!("unsupported fee tier {} (expected 100, 500, 3000 or 10000)", tier);
+                }
+                Ok(tier)
+            })
+            .collect()
+    }
+}
+
+/// Executable prices from one fee tier's pool
+#[derive(Debug, Clone, Copy)]
+pub struct TierQuote {
+    pub fee_tier: u32,
+    /// Quote tokens received per base token when selling
+    pub bid: f64,
+    /// Quote tokens paid per base token when buying
+    pub ask: f64,
+}
+
+/// Quote every configured fee tier and combine the best bid and ask into one quote
+pub async fn fetch_quote(client: &reqwest::Client, config: &UniswapConfig) -> Result<(Quote, Vec<TierQuote>)> {
+    let mut tiers = Vec::new();
+    let mut last_error = None;
+
+    for &fee in &config.fee_tiers {
+        match fetch_tier(client, config, fee).await {
+            Ok(tier) => tiers.push(tier),
+            Err(e) => last_error = Some(e.context(format!("fee tier {}", fee))),
+        }
+    }
+
+    if tiers.is_empty() {
+        return Err(last_error.unwrap_or_else(|| anyhow::anyhow!("no Uniswap fee tiers configured")));
+    }
+
+    let bid = tiers.iter().map(|t| t.bid).fold(f64::MIN, f64::max);
+    let ask = tiers.iter().map(|t| t.ask).fold(f64::MAX, f64::min);
+    let quote = Quote {
+        bid,
+        ask,
+        timestamp: chrono::Utc::now().timestamp_millis(),
+    };
+
+    Ok((quote, tiers))
+}
+
+/// Price both directions of the pool at the configured notional
+async fn fetch_tier(client: &reqwest::Client, config: &UniswapConfig, fee: u32) -> Result<TierQuote> {
+    let (base, quote) = (&config.base, &config.quote);
+
+    // Buy side: quote token in, base token out
+    let quote_in = to_units(config.notional_usd, quote.decimals);
+    let base_out = quote_exact_input(client, config, &quote.address, &base.address, quote_in, fee).await?;
+    let base_amount = from_units(base_out, base.decimals);
+    if base_amount <= 0.0 {
+        bail!("pool returned zero output");
+    }
+    let ask = config.notional_usd / base_amount;
+
+    // Sell side: the same base amount back into the quote token
+    let quote_out = quote_exact_input(client, config, &base.address, &quote.address, base_out, fee).await?;
+    let bid = from_units(quote_out, quote.decimals) / base_amount;
+
+    Ok(TierQuote { fee_tier: fee, bid, ask })
+}
+
+async fn quote_exact_input(
+    client: &reqwest::Client,
+    config: &UniswapConfig,
+    token_in: &str,
+    token_out: &str,
+    amount_in: u128,
+    fee: u32,
+) -> Result<u128> {
+    let calldata = format!(
+        "0x{}{}{}{:064x}{:064x}{:064x}",
+        QUOTE_EXACT_INPUT_SINGLE,
+        encode_address(token_in)?,
+        encode_address(token_out)?,
+        amount_in,
+        fee,
+        0u8, // no sqrtPriceLimitX96
+    );
+
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_call",
+        "params": [{ "to": config.quoter, "data": calldata }, "latest"],
+    });
+
+    let response = client
+        .post(&config.rpc_url)
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<serde_json::Value>()
+        .await?;
+
+    if let Some(error) = response.get("error") {
+        bail!("eth_call failed: {}", error);
+    }
+    let result = response["result"]
+        .as_str()
+        .context("missing result in eth_call response")?;
+
+    // amountOut is the first 32-byte word of the return data
+    let word = result
+        .trim_start_matches("0x")
+        .get(..64)
+        .context("short eth_call return data")?;
+    let (high, low) = word.split_at(32);
+    if u128::from_str_radix(high, 16)? != 0 {
+        bail!("amountOut does not fit in 128 bits");
+    }
+    Ok(u128::from_str_radix(low, 16)?)
+}
+
+fn encode_address(address: &str) -> Result<String> {
+    let hex = address.trim_start_matches("0x");
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("invalid address '{}'", address);
+    }
+    Ok(format!("{:0>64}", hex.to_ascii_lowercase()))
+}
@@ -0,0 +1,86 @@
+use crate::aggregator::AggregatedPrices;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+const GWEI_PER_ETH: f64 = 1e9;
+const LAMPORTS_PER_SOL: f64 = 1e9;
+
+/// Currency a USD amount can be displayed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayUnit {
+    Usd,
+    Gwei,
+    Lamports,
+}
+
+impl DisplayUnit {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            DisplayUnit::Usd => "USD",
+            DisplayUnit::Gwei => "gwei",
+            DisplayUnit::Lamports => "lamports",
+        }
+    }
+
+    /// Parse a comma-separated list such as `gwei,lamports`
+    pub fn parse_list(list: &str) -> Result<Vec<DisplayUnit>> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+impl std::str::FromStr for DisplayUnit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "usd" => Ok(DisplayUnit::Usd),
+            "gwei" | "eth" => Ok(DisplayUnit::Gwei),
+            "lamports" | "sol" => Ok(DisplayUnit::Lamports),
+            other => bail!("unknown display unit '{}' (expected usd, gwei or lamports)", other),
+        }
+    }
+}
+
+/// Exchange rates captured at the moment a trade executed
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ConversionSnapshot {
+    pub eth_usd: f64,
+    pub sol_usd: Option<f64>,
+    pub timestamp: i64,
+}
+
+impl ConversionSnapshot {
+    /// Rates in `prices`, stamped `timestamp` on the session clock
+    pub fn from_prices(prices: &AggregatedPrices, timestamp: i64) -> Option<Self> {
+        Some(Self {
+            eth_usd: prices.median_mid()?,
+            sol_usd: prices.sol_usd,
+            timestamp,
+        })
+    }
+
+    /// Convert a USD amount into the given unit at this snapshot's rates
+    pub fn convert(&self, usd: f64, unit: DisplayUnit) -> Option<f64> {
+        match unit {
+            DisplayUnit::Usd => Some(usd),
+            DisplayUnit::Gwei if self.eth_usd > 0.0 => Some(usd / self.eth_usd * GWEI_PER_ETH),
+            DisplayUnit::Lamports => self
+                .sol_usd
+                .filter(|rate| *rate > 0.0)
+                .map(|rate| usd / rate * LAMPORTS_PER_SOL),
+            _ => None,
+        }
+    }
+
+    pub fn format(&self, usd: f64, unit: DisplayUnit) -> String {
+        match self.convert(usd, unit) {
+            Some(value) if unit == DisplayUnit::Usd => format!("${:.2}", value),
+            Some(value) => format!("{:.0} {}", value, unit.symbol()),
+            None => format!("n/a {}", unit.symbol()),
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use crate::aggregator::AggregatedPrices;
+use crate::clock::SimulatedClock;
+use crate::config::Config;
+use crate::funding::FundingRates;
+use crate::pnl_tracker::{PnLStats, PnLTracker};
+use crate::position::Position;
+use crate::quiet::QuietEvent;
+use crate::trader::{ExecutionAttempt, TradeSide, TradingEngine};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// The exact snapshot one trading cycle decided on, and the draws its fill decisions used
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CycleSnapshot {
+    pub cycle: u64,
+    pub elapsed_secs: u64,
+    pub prices: AggregatedPrices,
+    /// `None` when that side made no decision, e.g. blocked by risk limits
+    pub buy_draw: Option<f64>,
+    pub sell_draw: Option<f64>,
+}
+
+impl CycleSnapshot {
+    fn elapsed_ms(&self) -> i64 {
+        self.elapsed_secs as i64 * 1000
+    }
+
+    fn draw(&self, side: TradeSide) -> Option<f64> {
+        match side {
+            TradeSide::Buy => self.buy_draw,
+            TradeSide::Sell => self.sell_draw,
+        }
+    }
+}
+/// One line of a cycle recording: the session's config first, credentials redacted, then every cycle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum CycleRecord {
+    Header { config: Box<Config> },
+    Cycle(Box<CycleSnapshot>),
+}
+
+/// Writes each cycle's snapshot as JSON lines, for re-simulating the decision layer later
+pub struct CycleRecorder {
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl CycleRecorder {
+    pub fn create(path: impl AsRef<Path>, config: &Config) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .with_context(|| format!("failed to create cycle recording {}", path.display()))?;
+        let recorder = Self {
+            writer: Mutex::new(BufWriter::new(file)),
+        };
+        recorder.write(&CycleRecord::Header {
+            config: Box::new(config.redacted()),
+        })?;
+        Ok(recorder)
+    }
+
+    pub fn record(&self, snapshot: CycleSnapshot) -> Result<()> {
+        self.write(&CycleRecord::Cycle(Box::new(snapshot)))
+    }
+
+    fn write(&self, record: &CycleRecord) -> Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        serde_json::to_writer(&mut *writer, record)?;
+        writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        self.writer.lock().unwrap().flush()?;
+        Ok(())
+    }
+}
+
+/// Read a recording written by `CycleRecorder`: the recorded config and its cycles in order
+pub fn read_cycles(path: impl AsRef<Path>) -> Result<(Config, Vec<CycleSnapshot>)> {
+    let path = path.as_ref();
+    let file = File::open(path).with_context(|| format!("failed to open cycle recording {}", path.display()))?;
+    let mut config = None;
+    let mut cycles = Vec::new();
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.with_context(|| format!("{}:{}: not a cycle recording", path.display(), number + 1))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: CycleRecord = serde_json::from_str(&line)
+            .with_context(|| format!("{}:{}: malformed cycle record", path.display(), number + 1))?;
+        match record {
+            CycleRecord::Header { config: header } => config = Some(*header),
+            CycleRecord::Cycle(snapshot) => cycles.push(*snapshot),
+        }
+    }
+    let Some(config) = config else {
+        bail!("{} has no config header; record it with --record-cycles", path.display());
+    };
+    Ok((config, cycles))
+}
+
+/// Outcome of re-running the decision layer over a recording with one parameter set
+#[derive(Debug, Clone)]
+pub struct WhatIfRun {
+    pub stats: PnLStats,
+    pub position: Position,
+    pub last_mid: Option<f64>,
+    /// (cycle, side) of every decision that filled
+    pub fills: BTreeSet<(u64, TradeSide)>,
+}
+
+impl WhatIfRun {
+    /// Open inventory marked at the last recorded mid
+    pub fn unrealized_pnl(&self) -> f64 {
+        self.last_mid.map_or(0.0, |mid| self.position.unrealized_pnl(mid))
+    }
+}
+
+/// Re-run `engine`'s decisions against every recorded snapshot, in order, using each decision's
+/// recorded draw so a parameter change is the only difference from the original session.
+/// Hedges and pending fill confirmations happen at cycle snapshots, as in the live loop, and
+/// carry at `funding` is charged on the inventory marked at each. The engine's clock steps through
+/// the recorded elapsed times, counted from the feed time of the first snapshot.
+pub async fn resimulate(engine: TradingEngine, cycles: &[CycleSnapshot], funding: Option<FundingRates>) -> WhatIfRun {
+    let start = cycles
+        .first()
+        .map(|first| first.prices.quotes().map(|q| q.timestamp).max().unwrap_or_default() - first.elapsed_ms())
+        .unwrap_or_default();
+    let clock = Arc::new(SimulatedClock::new(start));
+    let engine = engine.with_clock(clock.clone());
+    let mut tracker = PnLTracker::new();
+    if let Some(rates) = funding {
+        tracker = tracker.with_funding(rates);
+    }
+    let mut fills = BTreeSet::new();
+    let mut last_mid = None;
+
+    for snapshot in cycles {
+        clock.set(start + snapshot.elapsed_ms());
+        let prices = &snapshot.prices;
+        last_mid = prices.median_mid().or(last_mid);
+        tracker.mark_to_market(prices).await;
+        if let Some(QuietEvent::Resumed(interval)) = engine.observe_quiet(prices) {
+            tracker.record_quiet_interval(interval).await;
+        }
+        for trade in engine.confirm_fills(prices) {
+            tracker.record_trade(trade).await;
+        }
+        if let Some(trade) = engine.hedge(prices) {
+            tracker.record_trade(trade).await;
+        }
+
+        if engine.quiet_paused() {
+            continue;
+        }
+        let risk = engine.risk_state();
+        for side in [TradeSide::Buy, TradeSide::Sell] {
+            if !risk.allows(side, &engine.position()) {
+                continue;
+            }
+            let attempt = match snapshot.draw(side) {
+                Some(draw) => engine.attempt_with_draw(prices, side, draw),
+                None => engine.attempt(prices, side),
+            };
+            let Some(attempt) = attempt else {
+                continue;
+            };
+            tracker.record_attempt(&attempt).await;
+            if attempt.executed() || attempt.pending {
+                fills.insert((snapshot.cycle, side));
+            }
+            if let ExecutionAttempt { trade: Some(trade), .. } = attempt {
+                tracker.record_trade(trade).await;
+            }
+        }
+    }
+    if let Some(last) = cycles.last() {
+        for trade in engine.confirm_all(&last.prices) {
+            tracker.record_trade(trade).await;
+        }
+        tracker.close_marks(&last.prices).await;
+        let end = last.prices.quotes().map(|q| q.timestamp).max().unwrap_or_default();
+        if let Some(interval) = engine.close_quiet(end) {
+            tracker.record_quiet_interval(interval).await;
+        }
+    }
+
+    WhatIfRun {
+        stats: tracker.get_stats().await,
+        position: engine.position(),
+        last_mid,
+        fills,
+    }
+}
+
+/// Recorded parameters against an alternate set over the same snapshots
+#[derive(Debug, Clone)]
+pub struct WhatIfReport {
+    pub cycles: usize,
+    pub baseline: WhatIfRun,
+    pub alternate: WhatIfRun,
+    /// Config keys that differ, as `key: recorded → alternate`
+    pub changes: Vec<String>,
+}
+
+impl WhatIfReport {
+    /// Decisions that filled in one run but not the other
+    pub fn changed_fills(&self) -> usize {
+        self.baseline.fills.symmetric_difference(&self.alternate.fills).count()
+    }
+}
+
+impl fmt::Display for WhatIfReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "What-if over {} recorded cycles", self.cycles)?;
+        if self.changes.is_empty() {
+            writeln!(f, "  (no parameter changes)")?;
+        }
+        for change in &self.changes {
+            writeln!(f, "  {}", change)?;
+        }
+        writeln!(f)?;
+
+        let (b, a) = (&self.baseline, &self.alternate);
+        writeln!(f, "  {:<24} {:>14} {:>14} {:>14}", "", "RECORDED", "ALTERNATE", "DELTA")?;
+        let mut row = |label: &str, base: f64, alt: f64, decimals: usize| {
+            writeln!(f, "  {:<24} {:>14.*} {:>14.*} {:>+14.*}", label, decimals, base, decimals, alt, decimals, alt - base)
+        };
+        row("Trades", b.stats.total_trades as f64, a.stats.total_trades as f64, 0)?;
+        row("Maker fills", b.stats.maker_trades as f64, a.stats.maker_trades as f64, 0)?;
+        row("Taker fills", b.stats.taker_trades as f64, a.stats.taker_trades as f64, 0)?;
+        row("Hedges", b.stats.hedge_trades as f64, a.stats.hedge_trades as f64, 0)?;
+        row("Notional ($)", b.stats.total_notional, a.stats.total_notional, 0)?;
+        row("Gross PnL ($)", b.stats.gross_pnl, a.stats.gross_pnl, 2)?;
+        row("Fees ($)", b.stats.total_fees, a.stats.total_fees, 2)?;
+        row("Net PnL ($)", b.stats.total_pnl, a.stats.total_pnl, 2)?;
+        row("Final inventory (ETH)", b.position.net_eth, a.position.net_eth, 4)?;
+        row("Inventory MTM ($)", b.unrealized_pnl(), a.unrealized_pnl(), 2)?;
+        if b.stats.funding.total() != 0.0 || a.stats.funding.total() != 0.0 {
+            row("Inventory carry ($)", -b.stats.funding.total(), -a.stats.funding.total(), 2)?;
+        }
+        let paused_min = |run: &WhatIfRun| run.stats.quiet_paused_time().as_secs_f64() / 60.0;
+        row("Quiet pause (min)", paused_min(b), paused_min(a), 1)?;
+        writeln!(f, "\n  {} of the recorded decisions filled differently", self.changed_fills())
+    }
+}
+
+/// `key: old → new` for every config value that differs
+pub fn config_changes(recorded: &Config, alternate: &Config) -> Result<Vec<String>> {
+    let flat = |config: &Config| -> Result<Vec<(String, toml::Value)>> {
+        Ok(crate::config::flatten(&toml::Table::try_from(config)?))
+    };
+    let before = flat(recorded)?;
+    let after = flat(alternate)?;
+    let keys: BTreeSet<&String> = before.iter().chain(after.iter()).map(|(key, _)| key).collect();
+    let lookup = |values: &[(String, toml::Value)], key: &str| {
+        values.iter().find(|(k, _)| k == key).map_or("unset".to_string(), |(_, v)| v.to_string())
+    };
+    Ok(keys
+        .into_iter()
+        .filter_map(|key| {
+            let (old, new) = (lookup(&before, key), lookup(&after, key));
+            (old != new).then(|| format!("{}: {} → {}", key, old, new))
+        })
+        .collect())
+}
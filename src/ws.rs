@@ -0,0 +1,131 @@
+use crate::aggregator::QuoteUpdate;
+use crate::fanout::{ClientStats, FanOut, FlowControl};
+use crate::introspect::StrategyInfo;
+use crate::portfolio::{PortfolioSnapshot, Simulator};
+use crate::trader::Trade;
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::warn;
+
+/// One message to websocket clients, tagged with its `type`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    Quote(QuoteUpdate),
+    Trade(Box<Trade>),
+    /// The portfolio as of the last completed cycle, sent every stats interval
+    Stats(Box<PortfolioSnapshot>),
+    /// The active strategy and its current parameters, sent with every stats event
+    Strategy(Box<StrategyInfo>),
+}
+
+/// Websocket server streaming quote updates, trades and periodic stats to every connected client
+/// as JSON text frames. Each client reads through its own queue under the flow control, so a slow
+/// dashboard only loses its own events.
+pub struct WsServer {
+    addr: SocketAddr,
+    events: Arc<FanOut<StreamEvent>>,
+}
+
+impl WsServer {
+    /// Bind `listen` and stream in the background until the process exits
+    pub async fn spawn(
+        listen: &str,
+        quotes: broadcast::Receiver<QuoteUpdate>,
+        simulator: Simulator,
+        flow: FlowControl,
+        stats_interval: Duration,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind(listen)
+            .await
+            .with_context(|| format!("failed to bind websocket server on {}", listen))?;
+        let addr = listener.local_addr()?;
+        let events = Arc::new(FanOut::new(flow));
+        events.spawn_from(quotes, StreamEvent::Quote);
+        events.spawn_from(simulator.tracker().subscribe_trades(), |trade| StreamEvent::Trade(Box::new(trade)));
+        spawn_stats(Arc::clone(&events), simulator, stats_interval);
+
+        let server_events = Arc::clone(&events);
+        tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, peer)) => {
+                        tokio::spawn(serve_client(stream, peer, Arc::clone(&server_events)));
+                    }
+                    Err(e) => warn!(error = %e, "failed to accept websocket connection"),
+                }
+            }
+        });
+        Ok(Self { addr, events })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Connected clients and their delivery counts
+    pub fn clients(&self) -> Vec<ClientStats> {
+        self.events.clients()
+    }
+
+    /// Clients cut off by the disconnect policy so far
+    pub fn disconnects(&self) -> u64 {
+        self.events.disconnects()
+    }
+}
+
+fn spawn_stats(events: Arc<FanOut<StreamEvent>>, simulator: Simulator, every: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(every);
+        loop {
+            ticker.tick().await;
+            if let Some(snapshot) = simulator.portfolio().await {
+                events.publish(StreamEvent::Stats(Box::new(snapshot)));
+                events.publish(StreamEvent::Strategy(Box::new(StrategyInfo::of(simulator.engine()))));
+            }
+        }
+    });
+}
+
+async fn serve_client(stream: TcpStream, peer: SocketAddr, events: Arc<FanOut<StreamEvent>>) {
+    let ws = match tokio_tungstenite::accept_async(stream).await {
+        Ok(ws) => ws,
+        Err(e) => {
+            warn!(%peer, error = %e, "websocket handshake failed");
+            return;
+        }
+    };
+    let (mut write, mut read) = ws.split();
+    let subscription = events.subscribe(peer.to_string());
+    loop {
+        tokio::select! {
+            event = subscription.recv() => {
+                let Some(event) = event else {
+                    break;
+                };
+                let Ok(text) = serde_json::to_string(&event) else {
+                    continue;
+                };
+                tokio::select! {
+                    sent = write.send(Message::Text(text)) => if sent.is_err() {
+                        return;
+                    },
+                    // The client stopped reading long enough to be cut off mid-send
+                    _ = subscription.disconnected() => break,
+                }
+            }
+            message = read.next() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                // Pings are answered by the protocol layer; clients have nothing else to say
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}